@@ -39,6 +39,28 @@ pub fn main() {
     }
     crates.sort();
 
+    // each utility's manual page comes from the arguments of its `uu_app()`
+    let mut manpages = String::from(
+        "type ManpageMap = HashMap<&'static str, fn() -> uucore::manpage::Manpage>;\n\
+        \n\
+        fn manpage_map() -> ManpageMap {\n\
+        \tlet mut map = ManpageMap::new();\n\
+        ",
+    );
+    for krate in &crates {
+        let (name, path) = match krate.as_ref() {
+            k if k.starts_with(override_prefix) => (&krate[override_prefix.len()..], krate.clone()),
+            "false" | "true" => (&krate[..], format!("r#{}", krate)),
+            _ => (&krate[..], krate.clone()),
+        };
+        manpages.push_str(&format!(
+            "\tmap.insert(\"{name}\", || uucore::manpage::Manpage::from(&{path}::uu_app()));\n",
+            name = name,
+            path = path
+        ));
+    }
+    manpages.push_str("map\n}\n");
+
     let mut mf = File::create(Path::new(&out_dir).join("uutils_map.rs")).unwrap();
     let mut tf = File::create(Path::new(&out_dir).join("test_modules.rs")).unwrap();
 
@@ -111,6 +133,8 @@ pub fn main() {
                         \t\tmap.insert(\"sha3-512sum\", {krate}::uumain);\n\
                         \t\tmap.insert(\"shake128sum\", {krate}::uumain);\n\
                         \t\tmap.insert(\"shake256sum\", {krate}::uumain);\n\
+                        \t\tmap.insert(\"b2sum\", {krate}::uumain);\n\
+                        \t\tmap.insert(\"b3sum\", {krate}::uumain);\n\
                         ",
                         krate = krate
                     )
@@ -150,6 +174,7 @@ pub fn main() {
     }
 
     mf.write_all(b"map\n}\n").unwrap();
+    mf.write_all(format!("\n{}", manpages).as_bytes()).unwrap();
 
     mf.flush().unwrap();
     tf.flush().unwrap();
@@ -18,7 +18,10 @@ include!(concat!(env!("OUT_DIR"), "/uutils_map.rs"));
 
 fn usage<T>(utils: &UtilityMap<T>, name: &str) {
     println!("{} {} (multi-call binary)\n", name, VERSION);
-    println!("Usage: {} [function [arguments...]]\n", name);
+    println!("Usage: {} [function [arguments...]]", name);
+    println!("       {} --list", name);
+    println!("       {} --version", name);
+    println!("       {} manpage UTILITY\n", name);
     println!("Currently defined functions:\n");
     #[allow(clippy::map_clone)]
     let mut utils: Vec<&str> = utils.keys().map(|&s| s).collect();
@@ -31,6 +34,34 @@ fn usage<T>(utils: &UtilityMap<T>, name: &str) {
     );
 }
 
+fn list<T>(utils: &UtilityMap<T>) {
+    let mut utils: Vec<&str> = utils.keys().cloned().collect();
+    utils.sort_unstable();
+    for util in utils {
+        println!("{}", util);
+    }
+}
+
+fn manpage(util: Option<OsString>) -> i32 {
+    let util = match util {
+        Some(util) => util.to_string_lossy().into_owned(),
+        None => {
+            eprintln!("manpage: missing utility name");
+            return 1;
+        }
+    };
+    match manpage_map().get(&util[..]) {
+        Some(manpage) => {
+            print!("{}", manpage());
+            0
+        }
+        None => {
+            eprintln!("{}: function/utility not found", util);
+            1
+        }
+    }
+}
+
 fn binary_path(args: &mut impl Iterator<Item = OsString>) -> PathBuf {
     match args.next() {
         Some(ref s) if !s.is_empty() => PathBuf::from(s),
@@ -79,7 +110,15 @@ fn main() {
                 process::exit(uumain((vec![util_os].into_iter()).chain(args)));
             }
             None => {
-                if util == "--help" || util == "-h" {
+                if util == "--list" {
+                    list(&utils);
+                    process::exit(0);
+                } else if util == "manpage" {
+                    process::exit(manpage(args.next()));
+                } else if util == "--version" {
+                    println!("{}", uucore::help::version(binary_as_util, VERSION));
+                    process::exit(0);
+                } else if util == "--help" || util == "-h" {
                     // see if they want help on a specific util
                     if let Some(util_os) = args.next() {
                         let util = util_os.as_os_str().to_string_lossy();
@@ -10,14 +10,19 @@
 extern crate uucore;
 
 use platform_info::*;
+use uucore::coreopts::CoreOptions;
 
-static SYNTAX: &str = "Display machine architecture";
+static SYNTAX: &str = "[OPTION]...";
 static SUMMARY: &str = "Determine architecture name for current machine.";
 static LONG_HELP: &str = "";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
-    app!(SYNTAX, SUMMARY, LONG_HELP).parse(args.collect_str());
+    uu_app().parse(args.collect_str());
     let uts = return_if_err!(1, PlatformInfo::new());
     println!("{}", uts.machine().trim());
     0
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    app!(SYNTAX, SUMMARY, LONG_HELP)
+}
@@ -7,6 +7,7 @@
 
 #[macro_use]
 extern crate uucore;
+use uucore::coreopts::CoreOptions;
 use uucore::encoding::Format;
 
 mod base_common;
@@ -24,11 +25,9 @@ static LONG_HELP: &str = "
 ";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
-    base_common::execute(
-        args.collect_str(),
-        SYNTAX,
-        SUMMARY,
-        LONG_HELP,
-        Format::Base32,
-    )
+    base_common::execute(args.collect_str(), uu_app(), Some(Format::Base32), &[])
+}
+
+pub fn uu_app() -> CoreOptions<'static> {
+    base_common::base_app(SYNTAX, SUMMARY, LONG_HELP, &[])
 }
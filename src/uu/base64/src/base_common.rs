@@ -8,20 +8,25 @@
 // that was distributed with this source code.
 
 use std::fs::File;
-use std::io::{stdin, stdout, BufReader, Read, Write};
+use std::io::{stdin, stdout, BufWriter, Read};
 use std::path::Path;
 
-use uucore::encoding::{wrap_print, Data, Format};
+use uucore::coreopts::CoreOptions;
+use uucore::encoding::{Data, EncodingError, Format};
 
-pub fn execute(
-    args: Vec<String>,
-    syntax: &str,
-    summary: &str,
-    long_help: &str,
-    format: Format,
-) -> i32 {
-    let matches = app!(syntax, summary, long_help)
-        .optflag("d", "decode", "decode data")
+/// The options of a utility that encodes in the `formats` listed with their
+/// long options and help, or else in its one format.
+pub fn base_app<'a>(
+    syntax: &'a str,
+    summary: &'a str,
+    long_help: &'a str,
+    formats: &[(&str, &str, Format)],
+) -> CoreOptions<'a> {
+    let mut app = app!(syntax, summary, long_help);
+    for &(name, help, _) in formats {
+        app.optflagmulti("", name, help);
+    }
+    app.optflag("d", "decode", "decode data")
         .optflag(
             "i",
             "ignore-garbage",
@@ -32,8 +37,35 @@ pub fn execute(
             "wrap",
             "wrap encoded lines after COLS character (default 76, 0 to disable wrapping)",
             "COLS",
-        )
-        .parse(args);
+        );
+    app
+}
+
+/// Encodes or decodes as the arguments to `app` say, in `format`, or else in
+/// the last of `formats` chosen by its long option.
+pub fn execute(
+    args: Vec<String>,
+    mut app: CoreOptions,
+    format: Option<Format>,
+    formats: &[(&str, &str, Format)],
+) -> i32 {
+    let matches = app.parse(args);
+
+    let chosen = formats
+        .iter()
+        .filter_map(|&(name, _, format)| {
+            let position = matches.opt_positions(name).into_iter().max();
+            position.map(|position| (position, format))
+        })
+        .max_by_key(|&(position, _)| position)
+        .map(|(_, format)| format);
+    let format = match chosen.or(format) {
+        Some(format) => format,
+        None => {
+            show_usage_error!("missing encoding type");
+            return 1;
+        }
+    };
 
     let line_wrap = matches.opt_str("wrap").map(|s| match s.parse() {
         Ok(n) => n,
@@ -60,8 +92,7 @@ pub fn execute(
         );
     } else {
         let path = Path::new(matches.free[0].as_str());
-        let file_buf = safe_unwrap!(File::open(&path));
-        let mut input = BufReader::new(file_buf);
+        let mut input = safe_unwrap!(File::open(&path));
         handle_input(&mut input, format, line_wrap, ignore_garbage, decode);
     };
 
@@ -80,18 +111,17 @@ fn handle_input<R: Read>(
         data = data.line_wrap(wrap);
     }
 
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
     if !decode {
-        let encoded = data.encode();
-        wrap_print(&data, encoded);
+        if let Err(e) = data.encode_to(&mut output) {
+            crash!(1, "{}", e);
+        }
     } else {
-        match data.decode() {
-            Ok(s) => {
-                if stdout().write_all(&s).is_err() {
-                    // on windows console, writing invalid utf8 returns an error
-                    crash!(1, "Cannot write non-utf8 data");
-                }
-            }
-            Err(_) => crash!(1, "invalid input"),
+        match data.decode_to(&mut output) {
+            Ok(()) => {}
+            Err(EncodingError::Decode(e)) => crash!(1, "invalid input: {}", e),
+            Err(EncodingError::Io(e)) => crash!(1, "{}", e),
         }
     }
 }
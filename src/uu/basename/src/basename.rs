@@ -10,7 +10,8 @@
 #[macro_use]
 extern crate uucore;
 
-use std::path::{is_separator, PathBuf};
+use std::path::is_separator;
+use uucore::coreopts::CoreOptions;
 
 static NAME: &str = "basename";
 static SYNTAX: &str = "NAME [SUFFIX]";
@@ -24,32 +25,14 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     //
     // Argument parsing
     //
-    let matches = app!(SYNTAX, SUMMARY, LONG_HELP)
-        .optflag(
-            "a",
-            "multiple",
-            "Support more than one argument. Treat every argument as a name.",
-        )
-        .optopt(
-            "s",
-            "suffix",
-            "Remove a trailing suffix. This option implies the -a option.",
-            "SUFFIX",
-        )
-        .optflag(
-            "z",
-            "zero",
-            "Output a zero byte (ASCII NUL) at the end of each line, rather than a newline.",
-        )
-        .parse(args);
+    let matches = uu_app().parse(args);
 
     // too few arguments
     if matches.free.is_empty() {
         crash!(
             1,
-            "{0}: {1}\nTry '{0} --help' for more information.",
-            NAME,
-            "missing operand"
+            "missing operand\nTry '{} --help' for more information.",
+            NAME
         );
     }
     let opt_s = matches.opt_present("s");
@@ -60,14 +43,15 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     if !multiple_paths && matches.free.len() > 2 {
         crash!(
             1,
-            "{0}: extra operand '{1}'\nTry '{0} --help' for more information.",
-            NAME,
-            matches.free[2]
+            "extra operand '{}'\nTry '{} --help' for more information.",
+            matches.free[2],
+            NAME
         );
     }
 
+    // The last of several suffixes wins
     let suffix = if opt_s {
-        matches.opt_str("s").unwrap()
+        matches.opt_strs("s").pop().unwrap()
     } else if !opt_a && matches.free.len() > 1 {
         matches.free[1].clone()
     } else {
@@ -86,29 +70,52 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
     let line_ending = if opt_z { "\0" } else { "\n" };
     for path in paths {
-        print!("{}{}", basename(&path, &suffix), line_ending);
+        print!("{}{}", basename(path, &suffix), line_ending);
     }
 
     0
 }
 
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    app.optflag(
+        "a",
+        "multiple",
+        "Support more than one argument. Treat every argument as a name.",
+    )
+    .optmulti(
+        "s",
+        "suffix",
+        "Remove a trailing suffix. This option implies the -a option.",
+        "SUFFIX",
+    )
+    .optflag(
+        "z",
+        "zero",
+        "Output a zero byte (ASCII NUL) at the end of each line, rather than a newline.",
+    );
+    app
+}
+
 fn basename(fullname: &str, suffix: &str) -> String {
     // Remove all platform-specific path separators from the end
-    let mut path: String = fullname
-        .chars()
-        .rev()
-        .skip_while(|&ch| is_separator(ch))
-        .collect();
-
-    // Undo reverse
-    path = path.chars().rev().collect();
-
-    // Convert to path buffer and get last path component
-    let pb = PathBuf::from(path);
-    match pb.components().last() {
-        Some(c) => strip_suffix(c.as_os_str().to_str().unwrap(), suffix),
-        None => "".to_owned(),
+    let path = fullname.trim_end_matches(is_separator);
+
+    // A name made only of separators is the root directory
+    if path.is_empty() {
+        return match fullname.chars().next() {
+            Some(ch) => ch.to_string(),
+            None => "".to_owned(),
+        };
     }
+
+    // Take the last component from the string itself, as `Path::components`
+    // would turn "a/." into "a"
+    let name = match path.rfind(is_separator) {
+        Some(i) => &path[i + 1..],
+        None => path,
+    };
+    strip_suffix(name, suffix)
 }
 
 fn strip_suffix(name: &str, suffix: &str) -> String {
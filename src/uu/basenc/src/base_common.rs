@@ -0,0 +1,127 @@
+// This file is part of the uutils coreutils package.
+//
+// (c) Jordy Dickinson <jordy.dickinson@gmail.com>
+// (c) Jian Zeng <anonymousknight96@gmail.com>
+// (c) Alex Lyon <arcterus@mail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::File;
+use std::io::{stdin, stdout, BufWriter, Read};
+use std::path::Path;
+
+use uucore::coreopts::CoreOptions;
+use uucore::encoding::{Data, EncodingError, Format};
+
+/// The options of a utility that encodes in the `formats` listed with their
+/// long options and help, or else in its one format.
+pub fn base_app<'a>(
+    syntax: &'a str,
+    summary: &'a str,
+    long_help: &'a str,
+    formats: &[(&str, &str, Format)],
+) -> CoreOptions<'a> {
+    let mut app = app!(syntax, summary, long_help);
+    for &(name, help, _) in formats {
+        app.optflagmulti("", name, help);
+    }
+    app.optflag("d", "decode", "decode data")
+        .optflag(
+            "i",
+            "ignore-garbage",
+            "when decoding, ignore non-alphabetic characters",
+        )
+        .optopt(
+            "w",
+            "wrap",
+            "wrap encoded lines after COLS character (default 76, 0 to disable wrapping)",
+            "COLS",
+        );
+    app
+}
+
+/// Encodes or decodes as the arguments to `app` say, in `format`, or else in
+/// the last of `formats` chosen by its long option.
+pub fn execute(
+    args: Vec<String>,
+    mut app: CoreOptions,
+    format: Option<Format>,
+    formats: &[(&str, &str, Format)],
+) -> i32 {
+    let matches = app.parse(args);
+
+    let chosen = formats
+        .iter()
+        .filter_map(|&(name, _, format)| {
+            let position = matches.opt_positions(name).into_iter().max();
+            position.map(|position| (position, format))
+        })
+        .max_by_key(|&(position, _)| position)
+        .map(|(_, format)| format);
+    let format = match chosen.or(format) {
+        Some(format) => format,
+        None => {
+            show_usage_error!("missing encoding type");
+            return 1;
+        }
+    };
+
+    let line_wrap = matches.opt_str("wrap").map(|s| match s.parse() {
+        Ok(n) => n,
+        Err(e) => {
+            crash!(1, "invalid wrap size: ‘{}’: {}", s, e);
+        }
+    });
+    let ignore_garbage = matches.opt_present("ignore-garbage");
+    let decode = matches.opt_present("decode");
+
+    if matches.free.len() > 1 {
+        show_usage_error!("extra operand ‘{}’", matches.free[0]);
+        return 1;
+    }
+
+    if matches.free.is_empty() || &matches.free[0][..] == "-" {
+        let stdin_raw = stdin();
+        handle_input(
+            &mut stdin_raw.lock(),
+            format,
+            line_wrap,
+            ignore_garbage,
+            decode,
+        );
+    } else {
+        let path = Path::new(matches.free[0].as_str());
+        let mut input = safe_unwrap!(File::open(&path));
+        handle_input(&mut input, format, line_wrap, ignore_garbage, decode);
+    };
+
+    0
+}
+
+fn handle_input<R: Read>(
+    input: &mut R,
+    format: Format,
+    line_wrap: Option<usize>,
+    ignore_garbage: bool,
+    decode: bool,
+) {
+    let mut data = Data::new(input, format).ignore_garbage(ignore_garbage);
+    if let Some(wrap) = line_wrap {
+        data = data.line_wrap(wrap);
+    }
+
+    let stdout = stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    if !decode {
+        if let Err(e) = data.encode_to(&mut output) {
+            crash!(1, "{}", e);
+        }
+    } else {
+        match data.decode_to(&mut output) {
+            Ok(()) => {}
+            Err(EncodingError::Decode(e)) => crash!(1, "invalid input: {}", e),
+            Err(EncodingError::Io(e)) => crash!(1, "{}", e),
+        }
+    }
+}
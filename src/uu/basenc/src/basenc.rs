@@ -0,0 +1,71 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+// spell-checker:ignore (encodings) basenc msbf lsbf
+
+#[macro_use]
+extern crate uucore;
+use uucore::coreopts::CoreOptions;
+use uucore::encoding::Format;
+
+mod base_common;
+
+static SYNTAX: &str = "[OPTION]... [FILE]";
+static SUMMARY: &str = "Encode or decode FILE, or standard input, to standard output.";
+static LONG_HELP: &str = "
+ With no FILE, or when FILE is -, read standard input.
+
+ When decoding, the input may contain newlines in addition to the bytes of
+ the formal alphabet. Use --ignore-garbage to attempt to recover from any
+ other non-alphabet bytes in the encoded stream.
+";
+
+static FORMATS: &[(&str, &str, Format)] = &[
+    (
+        "base64",
+        "same as 'base64' program (RFC4648 section 4)",
+        Format::Base64,
+    ),
+    (
+        "base64url",
+        "file- and url-safe base64 (RFC4648 section 5)",
+        Format::Base64Url,
+    ),
+    (
+        "base32",
+        "same as 'base32' program (RFC4648 section 6)",
+        Format::Base32,
+    ),
+    (
+        "base32hex",
+        "extended hex alphabet base32 (RFC4648 section 7)",
+        Format::Base32Hex,
+    ),
+    ("base16", "hex encoding (RFC4648 section 8)", Format::Base16),
+    (
+        "base2msbf",
+        "bit string with most significant bit (msb) first",
+        Format::Base2Msbf,
+    ),
+    (
+        "base2lsbf",
+        "bit string with least significant bit (lsb) first",
+        Format::Base2Lsbf,
+    ),
+    (
+        "z85",
+        "ascii85-like encoding (ZeroMQ spec:32/Z85); when encoding, input length must be \
+         a multiple of 4; when decoding, input length must be a multiple of 5",
+        Format::Z85,
+    ),
+];
+
+pub fn uumain(args: impl uucore::Args) -> i32 {
+    base_common::execute(args.collect_str(), uu_app(), None, FORMATS)
+}
+
+pub fn uu_app() -> CoreOptions<'static> {
+    base_common::base_app(SYNTAX, SUMMARY, LONG_HELP, FORMATS)
+}
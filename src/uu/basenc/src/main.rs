@@ -0,0 +1 @@
+uucore_procs::main!(uu_basenc); // spell-checker:ignore procs uucore
@@ -142,67 +142,7 @@ mod options {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = App::new(executable!())
-        .name(NAME)
-        .version(VERSION)
-        .usage(SYNTAX)
-        .about(SUMMARY)
-        .arg(Arg::with_name(options::FILE).hidden(true).multiple(true))
-        .arg(
-            Arg::with_name(options::SHOW_ALL)
-                .short("A")
-                .long(options::SHOW_ALL)
-                .help("equivalent to -vET"),
-        )
-        .arg(
-            Arg::with_name(options::NUMBER_NONBLANK)
-                .short("b")
-                .long(options::NUMBER_NONBLANK)
-                .help("number nonempty output lines, overrides -n")
-                .overrides_with(options::NUMBER),
-        )
-        .arg(
-            Arg::with_name(options::SHOW_NONPRINTING_ENDS)
-                .short("e")
-                .help("equivalent to -vE"),
-        )
-        .arg(
-            Arg::with_name(options::SHOW_ENDS)
-                .short("E")
-                .long(options::SHOW_ENDS)
-                .help("display $ at end of each line"),
-        )
-        .arg(
-            Arg::with_name(options::NUMBER)
-                .short("n")
-                .long(options::NUMBER)
-                .help("number all output lines"),
-        )
-        .arg(
-            Arg::with_name(options::SQUEEZE_BLANK)
-                .short("s")
-                .long(options::SQUEEZE_BLANK)
-                .help("suppress repeated empty output lines"),
-        )
-        .arg(
-            Arg::with_name(options::SHOW_NONPRINTING_TABS)
-                .short("t")
-                .long(options::SHOW_NONPRINTING_TABS)
-                .help("equivalent to -vT"),
-        )
-        .arg(
-            Arg::with_name(options::SHOW_TABS)
-                .short("T")
-                .long(options::SHOW_TABS)
-                .help("display TAB characters at ^I"),
-        )
-        .arg(
-            Arg::with_name(options::SHOW_NONPRINTING)
-                .short("v")
-                .long(options::SHOW_NONPRINTING)
-                .help("use ^ and M- notation, except for LF (\\n) and TAB (\\t)"),
-        )
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app(), args);
 
     let number_mode = if matches.is_present(options::NUMBER_NONBLANK) {
         NumberingMode::NonEmpty
@@ -275,6 +215,69 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     }
 }
 
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .name(NAME)
+        .version(VERSION)
+        .usage(SYNTAX)
+        .about(SUMMARY)
+        .arg(Arg::with_name(options::FILE).hidden(true).multiple(true))
+        .arg(
+            Arg::with_name(options::SHOW_ALL)
+                .short("A")
+                .long(options::SHOW_ALL)
+                .help("equivalent to -vET"),
+        )
+        .arg(
+            Arg::with_name(options::NUMBER_NONBLANK)
+                .short("b")
+                .long(options::NUMBER_NONBLANK)
+                .help("number nonempty output lines, overrides -n")
+                .overrides_with(options::NUMBER),
+        )
+        .arg(
+            Arg::with_name(options::SHOW_NONPRINTING_ENDS)
+                .short("e")
+                .help("equivalent to -vE"),
+        )
+        .arg(
+            Arg::with_name(options::SHOW_ENDS)
+                .short("E")
+                .long(options::SHOW_ENDS)
+                .help("display $ at end of each line"),
+        )
+        .arg(
+            Arg::with_name(options::NUMBER)
+                .short("n")
+                .long(options::NUMBER)
+                .help("number all output lines"),
+        )
+        .arg(
+            Arg::with_name(options::SQUEEZE_BLANK)
+                .short("s")
+                .long(options::SQUEEZE_BLANK)
+                .help("suppress repeated empty output lines"),
+        )
+        .arg(
+            Arg::with_name(options::SHOW_NONPRINTING_TABS)
+                .short("t")
+                .long(options::SHOW_NONPRINTING_TABS)
+                .help("equivalent to -vT"),
+        )
+        .arg(
+            Arg::with_name(options::SHOW_TABS)
+                .short("T")
+                .long(options::SHOW_TABS)
+                .help("display TAB characters at ^I"),
+        )
+        .arg(
+            Arg::with_name(options::SHOW_NONPRINTING)
+                .short("v")
+                .long(options::SHOW_NONPRINTING)
+                .help("use ^ and M- notation, except for LF (\\n) and TAB (\\t)"),
+        )
+}
+
 /// Classifies the `InputType` of file at `path` if possible
 ///
 /// # Arguments
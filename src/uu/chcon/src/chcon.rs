@@ -0,0 +1,370 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (ToDO) RFILE RFILE's ENODATA levelrange
+
+#[macro_use]
+extern crate uucore;
+use clap::{App, Arg};
+use std::path::Path;
+use uucore::error_message;
+use uucore::libc;
+use uucore::safe_traversal::{is_root, Entry, Error as WalkError, Symlinks, Visitor, Walker};
+use uucore::selinux::{self, Component, Context};
+
+static VERSION: &str = env!("CARGO_PKG_VERSION");
+static ABOUT: &str = "Change the SELinux security context of each FILE to CONTEXT.
+With --reference, change the security context of each FILE to that of RFILE.";
+
+mod options {
+    pub const DEREFERENCE: &str = "dereference";
+    pub const NO_DEREFERENCE: &str = "no-dereference";
+    pub const USER: &str = "user";
+    pub const ROLE: &str = "role";
+    pub const TYPE: &str = "type";
+    pub const RANGE: &str = "range";
+    pub const NO_PRESERVE_ROOT: &str = "no-preserve-root";
+    pub const PRESERVE_ROOT: &str = "preserve-root";
+    pub const REFERENCE: &str = "reference";
+    pub const RECURSIVE: &str = "recursive";
+    pub const VERBOSE: &str = "verbose";
+    pub mod traverse {
+        pub const TRAVERSE: &str = "H";
+        pub const EVERY: &str = "L";
+        pub const NO_TRAVERSE: &str = "P";
+    }
+    pub const ARGS: &str = "args";
+}
+
+fn get_usage() -> String {
+    format!(
+        "{0} [OPTION]... CONTEXT FILE...
+    or:  {0} [OPTION]... [-u USER] [-r ROLE] [-l RANGE] [-t TYPE] FILE...
+    or:  {0} [OPTION]... --reference=RFILE FILE...",
+        executable!()
+    )
+}
+
+pub fn uumain(args: impl uucore::Args) -> i32 {
+    let args = args.collect_str();
+    let usage = get_usage();
+
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let recursive = matches.is_present(options::RECURSIVE);
+    let no_dereference = matches.is_present(options::NO_DEREFERENCE);
+    // whether symbolic links are changed through, rather than themselves
+    let (symlinks, follow) = if recursive {
+        if matches.is_present(options::traverse::TRAVERSE) {
+            (Symlinks::CommandLine, true)
+        } else if matches.is_present(options::traverse::EVERY) {
+            (Symlinks::Logical, true)
+        } else if matches.is_present(options::DEREFERENCE) {
+            show_info!("-R --dereference requires either -H or -L");
+            return 1;
+        } else {
+            (Symlinks::Physical, false)
+        }
+    } else if no_dereference {
+        (Symlinks::Physical, false)
+    } else {
+        (Symlinks::CommandLine, true)
+    };
+    if recursive && follow && no_dereference {
+        show_info!("-R -h requires -P");
+        return 1;
+    }
+
+    let components: Vec<(Component, &str)> = [
+        (Component::User, options::USER),
+        (Component::Range, options::RANGE),
+        (Component::Role, options::ROLE),
+        (Component::Type, options::TYPE),
+    ]
+    .iter()
+    .filter_map(|&(component, name)| matches.value_of(name).map(|value| (component, value)))
+    .collect();
+    let reference = matches.value_of(options::REFERENCE);
+
+    let mut files: Vec<&str> = matches
+        .values_of(options::ARGS)
+        .map(|args| args.collect())
+        .unwrap_or_default();
+    let needed = if reference.is_some() || !components.is_empty() {
+        1
+    } else {
+        2
+    };
+    if files.len() < needed {
+        match files.last() {
+            Some(last) => show_usage_error!("missing operand after '{}'", last),
+            None => show_usage_error!("missing operand"),
+        }
+        return 1;
+    }
+
+    let context = if let Some(reference) = reference {
+        match selinux::file_context(reference, true) {
+            Ok(context) => Some(context),
+            Err(e) => {
+                show_info!(
+                    "failed to get security context of '{}': {}",
+                    reference,
+                    error_message(&e)
+                );
+                return 1;
+            }
+        }
+    } else if !components.is_empty() {
+        None
+    } else {
+        let context = files.remove(0);
+        if selinux::is_enabled() {
+            if let Err(e) = selinux::check_context(context) {
+                show_info!("invalid context: '{}': {}", context, error_message(&e));
+                return 1;
+            }
+        }
+        Some(context.to_owned())
+    };
+    if reference.is_some() && !components.is_empty() {
+        show_usage_error!("conflicting security context specifiers given");
+        return 1;
+    }
+
+    let walker = Walker::new(symlinks).recursive(recursive);
+    let mut chcon = Chcon {
+        context,
+        components,
+        follow,
+        recursive,
+        preserve_root: matches.is_present(options::PRESERVE_ROOT),
+        verbose: matches.is_present(options::VERBOSE),
+        status: 0,
+    };
+    for file in files {
+        walker.walk(file, &mut chcon);
+    }
+    chcon.status
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(Arg::with_name(options::DEREFERENCE).long(options::DEREFERENCE).help(
+            "affect the referent of each symbolic link (this is the default), rather than the symbolic link itself",
+        ))
+        .arg(
+            Arg::with_name(options::NO_DEREFERENCE)
+                .short("h")
+                .long(options::NO_DEREFERENCE)
+                .help("affect symbolic links instead of any referenced file"),
+        )
+        .arg(
+            Arg::with_name(options::USER)
+                .short("u")
+                .long(options::USER)
+                .help("set user USER in the target security context")
+                .value_name("USER"),
+        )
+        .arg(
+            Arg::with_name(options::ROLE)
+                .short("r")
+                .long(options::ROLE)
+                .help("set role ROLE in the target security context")
+                .value_name("ROLE"),
+        )
+        .arg(
+            Arg::with_name(options::TYPE)
+                .short("t")
+                .long(options::TYPE)
+                .help("set type TYPE in the target security context")
+                .value_name("TYPE"),
+        )
+        .arg(
+            Arg::with_name(options::RANGE)
+                .short("l")
+                .long(options::RANGE)
+                .help("set range RANGE in the target security context")
+                .value_name("RANGE"),
+        )
+        .arg(
+            Arg::with_name(options::NO_PRESERVE_ROOT)
+                .long(options::NO_PRESERVE_ROOT)
+                .help("do not treat '/' specially (the default)"),
+        )
+        .arg(
+            Arg::with_name(options::PRESERVE_ROOT)
+                .long(options::PRESERVE_ROOT)
+                .help("fail to operate recursively on '/'"),
+        )
+        .arg(
+            Arg::with_name(options::REFERENCE)
+                .long(options::REFERENCE)
+                .help("use RFILE's security context rather than specifying a CONTEXT value")
+                .value_name("RFILE"),
+        )
+        .arg(
+            Arg::with_name(options::RECURSIVE)
+                .short("R")
+                .long(options::RECURSIVE)
+                .help("operate on files and directories recursively"),
+        )
+        .arg(
+            Arg::with_name(options::VERBOSE)
+                .short("v")
+                .long(options::VERBOSE)
+                .help("output a diagnostic for every file processed"),
+        )
+        .arg(
+            Arg::with_name(options::traverse::TRAVERSE)
+                .short(options::traverse::TRAVERSE)
+                .help("if a command line argument is a symbolic link to a directory, traverse it")
+                .overrides_with_all(&[options::traverse::EVERY, options::traverse::NO_TRAVERSE]),
+        )
+        .arg(
+            Arg::with_name(options::traverse::EVERY)
+                .short(options::traverse::EVERY)
+                .help("traverse every symbolic link to a directory encountered")
+                .overrides_with_all(&[options::traverse::TRAVERSE, options::traverse::NO_TRAVERSE]),
+        )
+        .arg(
+            Arg::with_name(options::traverse::NO_TRAVERSE)
+                .short(options::traverse::NO_TRAVERSE)
+                .help("do not traverse any symbolic links (default)")
+                .overrides_with_all(&[options::traverse::TRAVERSE, options::traverse::EVERY]),
+        )
+        .arg(Arg::with_name(options::ARGS).hidden(true).multiple(true))
+}
+
+/// Changes the context of the files a walk meets.
+struct Chcon<'a> {
+    /// the context to change to, or `None` to change only `components`
+    context: Option<String>,
+    components: Vec<(Component, &'a str)>,
+    follow: bool,
+    recursive: bool,
+    preserve_root: bool,
+    verbose: bool,
+    status: i32,
+}
+
+impl<'a> Chcon<'a> {
+    /// The context the file at `path` is to get, from the one it has if only
+    /// some components are to change; `None` if that's the one it has.
+    fn new_context(&self, path: &Path) -> Result<Option<String>, String> {
+        if let Some(context) = &self.context {
+            return Ok(Some(context.clone()));
+        }
+        let current = match selinux::file_context(path, self.follow) {
+            Ok(context) => context,
+            // there's no telling what the other components should be
+            Err(ref e) if e.raw_os_error() == Some(libc::ENODATA) => {
+                return Err(format!(
+                    "can't apply partial context to unlabeled file '{}'",
+                    path.display()
+                ))
+            }
+            Err(e) => {
+                return Err(format!(
+                    "failed to get security context of '{}': {}",
+                    path.display(),
+                    error_message(&e)
+                ))
+            }
+        };
+        let mut context = Context::parse(&current)
+            .map_err(|e| format!("bad security context '{}': {}", current, error_message(&e)))?;
+        for &(component, value) in &self.components {
+            context.set(component, value).map_err(|e| {
+                format!(
+                    "failed to set {} security context component to '{}': {}",
+                    component,
+                    value,
+                    error_message(&e)
+                )
+            })?;
+        }
+        let context = context.to_string();
+        Ok(if context == current {
+            None
+        } else {
+            Some(context)
+        })
+    }
+
+    fn change(&mut self, entry: &Entry) {
+        let path = entry.path();
+        if self.verbose {
+            println!("changing security context of '{}'", path.display());
+        }
+        let context = match self.new_context(path) {
+            Ok(Some(context)) => context,
+            Ok(None) => return,
+            Err(msg) => {
+                show_info!("{}", msg);
+                self.status = 1;
+                return;
+            }
+        };
+        if let Err(e) = selinux::set_file_context(path, &context, self.follow) {
+            show_info!(
+                "failed to change context of '{}' to '{}': {}",
+                path.display(),
+                context,
+                error_message(&e)
+            );
+            self.status = 1;
+        }
+    }
+}
+
+impl<'a> Visitor for Chcon<'a> {
+    fn visit(&mut self, entry: &Entry) -> bool {
+        if !self.recursive {
+            self.change(entry);
+            return false;
+        }
+        if entry.depth() == 0 && self.preserve_root && entry.is_dir() && is_root(entry.path()) {
+            if entry.path() == Path::new("/") {
+                show_info!("it is dangerous to operate recursively on '/'");
+            } else {
+                show_info!(
+                    "it is dangerous to operate recursively on '{}' (same as '/')",
+                    entry.path().display()
+                );
+            }
+            show_info!("use --no-preserve-root to override this failsafe");
+            self.status = 1;
+            return false;
+        }
+        // like GNU, directories change once everything in them has
+        if !entry.is_dir() {
+            self.change(entry);
+        }
+        true
+    }
+
+    fn leave(&mut self, entry: &Entry) {
+        self.change(entry);
+    }
+
+    fn error(&mut self, path: &Path, err: WalkError) {
+        match err {
+            WalkError::Access(e) => {
+                show_info!("cannot access '{}': {}", path.display(), error_message(&e))
+            }
+            WalkError::ReadDir(e) => show_info!(
+                "cannot read directory '{}': {}",
+                path.display(),
+                error_message(&e)
+            ),
+            // like GNU, a loop met by following links is just not entered again
+            WalkError::Cycle => return,
+        }
+        self.status = 1;
+    }
+}
@@ -0,0 +1 @@
+uucore_procs::main!(uu_chcon); // spell-checker:ignore procs uucore
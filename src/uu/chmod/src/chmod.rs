@@ -11,13 +11,19 @@
 extern crate uucore;
 
 use clap::{App, Arg};
+#[cfg(unix)]
 use std::fs;
-use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::io;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
 use std::path::Path;
+use uucore::error_message;
 use uucore::fs::display_permissions_unix;
-#[cfg(not(windows))]
 use uucore::mode;
-use walkdir::WalkDir;
+#[cfg(unix)]
+use uucore::safe_traversal::{is_root, Entry, Error, Symlinks, Visitor, Walker};
+#[cfg(windows)]
+use uucore::winfs::{is_root, Entry, Error, Symlinks, Visitor, Walker};
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static ABOUT: &str = "Change the mode of each FILE to MODE.
@@ -31,6 +37,11 @@ mod options {
     pub const PRESERVE_ROOT: &str = "preserve-root";
     pub const REFERENCE: &str = "RFILE";
     pub const RECURSIVE: &str = "recursive";
+    pub mod traverse {
+        pub const TRAVERSE: &str = "H";
+        pub const NO_TRAVERSE: &str = "P";
+        pub const EVERY: &str = "L";
+    }
     pub const MODE: &str = "MODE";
     pub const FILE: &str = "FILE";
 }
@@ -44,9 +55,8 @@ or: {0} [OPTION]... --reference=RFILE FILE...",
     )
 }
 
-fn get_long_usage() -> String {
-    String::from("Each MODE is of the form '[ugoa]*([-+=]([rwxXst]*|[ugo]))+|[-+=]?[0-7]+'.")
-}
+static LONG_USAGE: &str =
+    "Each MODE is of the form '[ugoa]*([-+=]([rwxXst]*|[ugo]))+|[-+=]?[0-7]+'.";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let mut args = args.collect_str();
@@ -56,13 +66,78 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     let mode_had_minus_prefix = strip_minus_from_mode(&mut args);
 
     let usage = get_usage();
-    let after_help = get_long_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let changes = matches.is_present(options::CHANGES);
+    let quiet = matches.is_present(options::QUIET);
+    let verbose = matches.is_present(options::VERBOSE);
+    let preserve_root = matches.is_present(options::PRESERVE_ROOT);
+    let recursive = matches.is_present(options::RECURSIVE);
+    // -H, -L and -P only matter with -R: otherwise each FILE is followed
+    let symlinks = if recursive && matches.is_present(options::traverse::EVERY) {
+        Symlinks::Logical
+    } else if recursive && matches.is_present(options::traverse::NO_TRAVERSE) {
+        Symlinks::Physical
+    } else {
+        Symlinks::CommandLine
+    };
+    let fmode = matches
+        .value_of(options::REFERENCE)
+        .and_then(|ref fref| match file_mode(fref) {
+            // only the permission bits are copied, not the file type
+            Ok(mode) => Some(mode & 0o7777),
+            Err(err) => crash!(
+                1,
+                "failed to get attributes of '{}': {}",
+                fref,
+                error_message(&err)
+            ),
+        });
+    let modes = matches.value_of(options::MODE).unwrap(); // should always be Some because required
+    let mut cmode = if mode_had_minus_prefix {
+        // clap parsing is finished, now put prefix back
+        Some(format!("-{}", modes))
+    } else {
+        Some(modes.to_string())
+    };
+    let mut files: Vec<String> = matches
+        .values_of(options::FILE)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+    if fmode.is_some() {
+        // "--reference" and MODE are mutually exclusive
+        // if "--reference" was used MODE needs to be interpreted as another FILE
+        // it wasn't possible to implement this behavior directly with clap
+        files.push(cmode.unwrap());
+        cmode = None;
+    }
+    if let Some(ref mode) = cmode {
+        // a bad MODE is reported once, before any file is touched
+        if let Err(err) = mode::parse_mode(0, mode, false) {
+            show_usage_error!("{}", err);
+            return 1;
+        }
+    }
+
+    let mut chmoder = Chmoder {
+        changes,
+        quiet,
+        verbose,
+        preserve_root,
+        recursive,
+        fmode,
+        cmode,
+        status: 0,
+    };
+    chmoder.chmod(files, symlinks)
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
-        .after_help(&after_help[..])
+        .after_help(LONG_USAGE)
         .arg(
             Arg::with_name(options::CHANGES)
                 .long(options::CHANGES)
@@ -98,6 +173,24 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .short("R")
                 .help("change files and directories recursively"),
         )
+        .arg(
+            Arg::with_name(options::traverse::TRAVERSE)
+                .short(options::traverse::TRAVERSE)
+                .help("if a command line argument is a symbolic link to a directory, traverse it (the default)")
+                .overrides_with_all(&[options::traverse::EVERY, options::traverse::NO_TRAVERSE]),
+        )
+        .arg(
+            Arg::with_name(options::traverse::EVERY)
+                .short(options::traverse::EVERY)
+                .help("traverse every symbolic link to a directory encountered")
+                .overrides_with_all(&[options::traverse::TRAVERSE, options::traverse::NO_TRAVERSE]),
+        )
+        .arg(
+            Arg::with_name(options::traverse::NO_TRAVERSE)
+                .short(options::traverse::NO_TRAVERSE)
+                .help("do not traverse any symbolic links")
+                .overrides_with_all(&[options::traverse::TRAVERSE, options::traverse::EVERY]),
+        )
         .arg(
             Arg::with_name(options::REFERENCE)
                 .long("reference")
@@ -117,54 +210,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .required_unless(options::MODE)
                 .multiple(true),
         )
-        .get_matches_from(args);
-
-    let changes = matches.is_present(options::CHANGES);
-    let quiet = matches.is_present(options::QUIET);
-    let verbose = matches.is_present(options::VERBOSE);
-    let preserve_root = matches.is_present(options::PRESERVE_ROOT);
-    let recursive = matches.is_present(options::RECURSIVE);
-    let fmode =
-        matches
-            .value_of(options::REFERENCE)
-            .and_then(|ref fref| match fs::metadata(fref) {
-                Ok(meta) => Some(meta.mode()),
-                Err(err) => crash!(1, "cannot stat attributes of '{}': {}", fref, err),
-            });
-    let modes = matches.value_of(options::MODE).unwrap(); // should always be Some because required
-    let mut cmode = if mode_had_minus_prefix {
-        // clap parsing is finished, now put prefix back
-        Some(format!("-{}", modes))
-    } else {
-        Some(modes.to_string())
-    };
-    let mut files: Vec<String> = matches
-        .values_of(options::FILE)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-    if fmode.is_some() {
-        // "--reference" and MODE are mutually exclusive
-        // if "--reference" was used MODE needs to be interpreted as another FILE
-        // it wasn't possible to implement this behavior directly with clap
-        files.push(cmode.unwrap());
-        cmode = None;
-    }
-
-    let chmoder = Chmoder {
-        changes,
-        quiet,
-        verbose,
-        preserve_root,
-        recursive,
-        fmode,
-        cmode,
-    };
-    match chmoder.chmod(files) {
-        Ok(()) => {}
-        Err(e) => return e,
-    }
-
-    0
 }
 
 // Iterate 'args' and delete the first occurrence
@@ -196,107 +241,20 @@ struct Chmoder {
     recursive: bool,
     fmode: Option<u32>,
     cmode: Option<String>,
+    status: i32,
 }
 
 impl Chmoder {
-    fn chmod(&self, files: Vec<String>) -> Result<(), i32> {
-        let mut r = Ok(());
-
+    fn chmod(&mut self, files: Vec<String>, symlinks: Symlinks) -> i32 {
+        let walker = Walker::new(symlinks).recursive(self.recursive);
         for filename in &files {
-            let filename = &filename[..];
-            let file = Path::new(filename);
-            if !file.exists() {
-                if is_symlink(file) {
-                    println!(
-                        "failed to change mode of '{}' from 0000 (---------) to 0000 (---------)",
-                        filename
-                    );
-                    if !self.quiet {
-                        show_error!("cannot operate on dangling symlink '{}'", filename);
-                    }
-                } else {
-                    show_error!("cannot access '{}': No such file or directory", filename);
-                }
-                return Err(1);
-            }
-            if self.recursive && self.preserve_root && filename == "/" {
-                show_error!(
-                    "it is dangerous to operate recursively on '{}'\nuse --no-preserve-root to override this failsafe",
-                    filename
-                );
-                return Err(1);
-            }
-            if !self.recursive {
-                r = self.chmod_file(&file).and(r);
-            } else {
-                for entry in WalkDir::new(&filename).into_iter().filter_map(|e| e.ok()) {
-                    let file = entry.path();
-                    r = self.chmod_file(&file).and(r);
-                }
-            }
+            walker.walk(filename, self);
         }
-        r
+        self.status
     }
 
-    #[cfg(windows)]
-    fn chmod_file(&self, file: &Path) -> Result<(), i32> {
-        // chmod is useless on Windows
-        // it doesn't set any permissions at all
-        // instead it just sets the readonly attribute on the file
-        Err(0)
-    }
-    #[cfg(any(unix, target_os = "redox"))]
-    fn chmod_file(&self, file: &Path) -> Result<(), i32> {
-        let mut fperm = match fs::metadata(file) {
-            Ok(meta) => meta.mode() & 0o7777,
-            Err(err) => {
-                if is_symlink(file) {
-                    if self.verbose {
-                        println!(
-                            "neither symbolic link '{}' nor referent has been changed",
-                            file.display()
-                        );
-                    }
-                    return Ok(());
-                } else {
-                    show_error!("{}: '{}'", err, file.display());
-                }
-                return Err(1);
-            }
-        };
-        match self.fmode {
-            Some(mode) => self.change_file(fperm, mode, file)?,
-            None => {
-                let cmode_unwrapped = self.cmode.clone().unwrap();
-                for mode in cmode_unwrapped.split(',') {
-                    // cmode is guaranteed to be Some in this case
-                    let arr: &[char] = &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
-                    let result = if mode.contains(arr) {
-                        mode::parse_numeric(fperm, mode)
-                    } else {
-                        mode::parse_symbolic(fperm, mode, file.is_dir())
-                    };
-                    match result {
-                        Ok(mode) => {
-                            self.change_file(fperm, mode, file)?;
-                            fperm = mode;
-                        }
-                        Err(f) => {
-                            if !self.quiet {
-                                show_error!("{}", f);
-                            }
-                            return Err(1);
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(())
-    }
-
-    #[cfg(unix)]
-    fn change_file(&self, fperm: u32, mode: u32, file: &Path) -> Result<(), i32> {
+    fn change_file(&mut self, fperm: u32, mode: u32, entry: &Entry) {
+        let file = entry.path();
         if fperm == mode {
             if self.verbose && !self.changes {
                 println!(
@@ -306,26 +264,17 @@ impl Chmoder {
                     display_permissions_unix(fperm),
                 );
             }
-            Ok(())
-        } else if let Err(err) = fs::set_permissions(file, fs::Permissions::from_mode(mode)) {
+        } else if let Err(err) = entry.chmod(mode) {
             if !self.quiet {
-                show_error!("{}", err);
-            }
-            if self.verbose {
-                show_info!(
-                    "failed to change mode of file '{}' from {:o} ({}) to {:o} ({})",
+                show_error!(
+                    "changing permissions of '{}': {}",
                     file.display(),
-                    fperm,
-                    display_permissions_unix(fperm),
-                    mode,
-                    display_permissions_unix(mode)
+                    error_message(&err)
                 );
             }
-            Err(1)
-        } else {
-            if self.verbose || self.changes {
-                show_info!(
-                    "mode of '{}' changed from {:o} ({}) to {:o} ({})",
+            if self.verbose {
+                println!(
+                    "failed to change mode of '{}' from {:04o} ({}) to {:04o} ({})",
                     file.display(),
                     fperm,
                     display_permissions_unix(fperm),
@@ -333,14 +282,113 @@ impl Chmoder {
                     display_permissions_unix(mode)
                 );
             }
-            Ok(())
+            self.status = 1;
+        } else if self.verbose || self.changes {
+            println!(
+                "mode of '{}' changed from {:04o} ({}) to {:04o} ({})",
+                file.display(),
+                fperm,
+                display_permissions_unix(fperm),
+                mode,
+                display_permissions_unix(mode)
+            );
         }
     }
 }
 
-pub fn is_symlink<P: AsRef<Path>>(path: P) -> bool {
-    match fs::symlink_metadata(path) {
-        Ok(m) => m.file_type().is_symlink(),
-        Err(_) => false,
+impl Visitor for Chmoder {
+    fn visit(&mut self, entry: &Entry) -> bool {
+        let file = entry.path();
+        if entry.is_dangling() {
+            if self.verbose {
+                println!(
+                    "failed to change mode of '{}' from 0000 (---------) to 0000 (---------)",
+                    file.display()
+                );
+            }
+            if !self.quiet {
+                show_error!("cannot operate on dangling symlink '{}'", file.display());
+            }
+            self.status = 1;
+            return false;
+        }
+        if entry.is_symlink() {
+            // the permissions of a link itself can't be changed
+            if self.verbose {
+                println!(
+                    "neither symbolic link '{}' nor referent has been changed",
+                    file.display()
+                );
+            }
+            return false;
+        }
+        if self.recursive && self.preserve_root && entry.depth() == 0 && is_root(file) {
+            if file == Path::new("/") {
+                show_error!("it is dangerous to operate recursively on '/'");
+            } else {
+                show_error!(
+                    "it is dangerous to operate recursively on '{}' (same as '/')",
+                    file.display()
+                );
+            }
+            show_error!("use --no-preserve-root to override this failsafe");
+            self.status = 1;
+            return false;
+        }
+
+        let fperm = entry.mode() & 0o7777;
+        let mode = match self.fmode {
+            Some(mode) => mode,
+            // cmode is guaranteed to be Some in this case
+            None => match mode::parse_mode(fperm, self.cmode.as_ref().unwrap(), entry.is_dir()) {
+                Ok(mode) => mode,
+                Err(f) => {
+                    if !self.quiet {
+                        show_error!("{}", f);
+                    }
+                    self.status = 1;
+                    return false;
+                }
+            },
+        };
+        self.change_file(fperm, mode, entry);
+        true
+    }
+
+    fn error(&mut self, path: &Path, err: Error) {
+        match err {
+            Error::Access(err) => {
+                if !self.quiet {
+                    show_error!(
+                        "cannot access '{}': {}",
+                        path.display(),
+                        error_message(&err)
+                    );
+                }
+            }
+            Error::ReadDir(err) => {
+                if !self.quiet {
+                    show_error!(
+                        "cannot read directory '{}': {}",
+                        path.display(),
+                        error_message(&err)
+                    );
+                }
+            }
+            // like GNU, a loop met by following links is just not entered again
+            Error::Cycle => return,
+        }
+        self.status = 1;
     }
 }
+
+/// The mode of `file`, following symbolic links.
+#[cfg(unix)]
+fn file_mode(file: &str) -> io::Result<u32> {
+    fs::metadata(file).map(|meta| meta.mode())
+}
+
+#[cfg(windows)]
+fn file_mode(file: &str) -> io::Result<u32> {
+    uucore::winfs::Metadata::new(file, true).map(|meta| meta.mode())
+}
@@ -5,25 +5,20 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) COMFOLLOW Chowner Passwd RFILE RFILE's derefer dgid duid
+// spell-checker:ignore (ToDO) RFILE RFILE's
 
 #[macro_use]
 extern crate uucore;
-pub use uucore::entries::{self, Group, Locate, Passwd};
-use uucore::fs::resolve_relative_path;
-use uucore::libc::{gid_t, uid_t};
-use uucore::perms::{wrap_chown, Verbosity};
+pub use uucore::entries;
+use uucore::error_message;
+use uucore::perms::{self, ChownExecutor, IfFrom, OwnerSpec, Verbosity};
+use uucore::safe_traversal::Symlinks;
 
 use clap::{App, Arg};
 
-use walkdir::WalkDir;
-
-use std::fs::{self, Metadata};
+use std::fs;
 use std::os::unix::fs::MetadataExt;
 
-use std::convert::AsRef;
-use std::path::Path;
-
 static ABOUT: &str = "change file owner and group";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -55,10 +50,6 @@ pub mod options {
 static ARG_OWNER: &str = "owner";
 static ARG_FILES: &str = "files";
 
-const FTS_COMFOLLOW: u8 = 1;
-const FTS_PHYSICAL: u8 = 1 << 1;
-const FTS_LOGICAL: u8 = 1 << 2;
-
 fn get_usage() -> String {
     format!(
         "{0} [OPTION]... [OWNER][:[GROUP]] FILE...\n{0} [OPTION]... --reference=RFILE FILE...",
@@ -71,10 +62,123 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    /* First arg is the owner/group */
+    let owner = matches.value_of(ARG_OWNER).unwrap_or_default();
+
+    /* Then the list of files */
+    let mut files: Vec<String> = matches
+        .values_of(ARG_FILES)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+    if matches.is_present(options::REFERENCE) {
+        // with --reference there's no OWNER, so the first operand is a FILE
+        if let Some(owner) = matches.value_of(ARG_OWNER) {
+            files.insert(0, owner.to_string());
+        }
+        if files.is_empty() {
+            show_usage_error!("missing operand");
+            return 1;
+        }
+    }
+
+    let preserve_root = matches.is_present(options::preserve_root::PRESERVE);
+
+    let recursive = matches.is_present(options::RECURSIVE);
+    let symlinks = if recursive {
+        if matches.is_present(options::traverse::TRAVERSE) {
+            Symlinks::CommandLine
+        } else if matches.is_present(options::traverse::EVERY) {
+            Symlinks::Logical
+        } else if matches.is_present(options::dereference::DEREFERENCE) {
+            show_info!("-R --dereference requires either -H or -L");
+            return 1;
+        } else {
+            Symlinks::Physical
+        }
+    } else if matches.is_present(options::dereference::NO_DEREFERENCE) {
+        Symlinks::Physical
+    } else {
+        Symlinks::CommandLine
+    };
+
+    let verbosity = if matches.is_present(options::verbosity::CHANGES) {
+        Verbosity::Changes
+    } else if matches.is_present(options::verbosity::SILENT)
+        || matches.is_present(options::verbosity::QUIET)
+    {
+        Verbosity::Silent
+    } else if matches.is_present(options::verbosity::VERBOSE) {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    };
+
+    let filter = if let Some(spec) = matches.value_of(options::FROM) {
+        match parse_spec(spec) {
+            Ok(spec) => spec.if_from(),
+            Err(e) => {
+                show_info!("{}", e);
+                return 1;
+            }
+        }
+    } else {
+        IfFrom::All
+    };
+
+    let dest = if let Some(file) = matches.value_of(options::REFERENCE) {
+        match fs::metadata(file) {
+            Ok(meta) => OwnerSpec {
+                uid: Some(meta.uid()),
+                gid: Some(meta.gid()),
+                user: Some(entries::uid2usr(meta.uid()).unwrap_or_else(|_| meta.uid().to_string())),
+                group: Some(
+                    entries::gid2grp(meta.gid()).unwrap_or_else(|_| meta.gid().to_string()),
+                ),
+            },
+            Err(e) => {
+                show_info!(
+                    "failed to get attributes of '{}': {}",
+                    file,
+                    error_message(&e)
+                );
+                return 1;
+            }
+        }
+    } else {
+        match parse_spec(owner) {
+            Ok(spec) => spec,
+            Err(e) => {
+                show_info!("{}", e);
+                return 1;
+            }
+        }
+    };
+    let executor = ChownExecutor {
+        util_name: executable!(),
+        dest_uid: dest.uid,
+        dest_gid: dest.gid,
+        // with only a group, what changes is still reported as ownership
+        dest_user: match (dest.user, &dest.group) {
+            (None, Some(_)) => Some(String::new()),
+            (user, _) => user,
+        },
+        dest_group: dest.group,
+        symlinks,
+        verbosity,
+        filter,
+        files,
+        recursive,
+        preserve_root,
+    };
+    executor.exec()
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .arg(
             Arg::with_name(options::verbosity::CHANGES)
                 .short("c")
@@ -126,7 +230,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .long(options::REFERENCE)
                 .help("use RFILE's owner and group rather than specifying OWNER:GROUP values")
                 .value_name("RFILE")
-                .min_values(1),
+                .takes_value(true),
         )
         .arg(Arg::with_name(options::verbosity::SILENT).short("f").long(options::verbosity::SILENT))
         .arg(
@@ -156,342 +260,28 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             Arg::with_name(ARG_OWNER)
                 .multiple(false)
                 .takes_value(true)
-                .required(true),
+                .required_unless(options::REFERENCE),
         )
         .arg(
             Arg::with_name(ARG_FILES)
                 .multiple(true)
                 .takes_value(true)
-                .required(true)
+                .required_unless(options::REFERENCE)
                 .min_values(1),
         )
-        .get_matches_from(args);
-
-    /* First arg is the owner/group */
-    let owner = matches.value_of(ARG_OWNER).unwrap();
-
-    /* Then the list of files */
-    let files: Vec<String> = matches
-        .values_of(ARG_FILES)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    let preserve_root = matches.is_present(options::preserve_root::PRESERVE);
-
-    let mut derefer = if matches.is_present(options::dereference::NO_DEREFERENCE) {
-        1
-    } else {
-        0
-    };
-
-    let mut bit_flag = if matches.is_present(options::traverse::TRAVERSE) {
-        FTS_COMFOLLOW | FTS_PHYSICAL
-    } else if matches.is_present(options::traverse::EVERY) {
-        FTS_LOGICAL
-    } else {
-        FTS_PHYSICAL
-    };
-
-    let recursive = matches.is_present(options::RECURSIVE);
-    if recursive {
-        if bit_flag == FTS_PHYSICAL {
-            if derefer == 1 {
-                show_info!("-R --dereference requires -H or -L");
-                return 1;
-            }
-            derefer = 0;
-        }
-    } else {
-        bit_flag = FTS_PHYSICAL;
-    }
-
-    let verbosity = if matches.is_present(options::verbosity::CHANGES) {
-        Verbosity::Changes
-    } else if matches.is_present(options::verbosity::SILENT)
-        || matches.is_present(options::verbosity::QUIET)
-    {
-        Verbosity::Silent
-    } else if matches.is_present(options::verbosity::VERBOSE) {
-        Verbosity::Verbose
-    } else {
-        Verbosity::Normal
-    };
-
-    let filter = if let Some(spec) = matches.value_of(options::FROM) {
-        match parse_spec(&spec) {
-            Ok((Some(uid), None)) => IfFrom::User(uid),
-            Ok((None, Some(gid))) => IfFrom::Group(gid),
-            Ok((Some(uid), Some(gid))) => IfFrom::UserGroup(uid, gid),
-            Ok((None, None)) => IfFrom::All,
-            Err(e) => {
-                show_info!("{}", e);
-                return 1;
-            }
-        }
-    } else {
-        IfFrom::All
-    };
-
-    let dest_uid: Option<u32>;
-    let dest_gid: Option<u32>;
-    if let Some(file) = matches.value_of(options::REFERENCE) {
-        match fs::metadata(&file) {
-            Ok(meta) => {
-                dest_gid = Some(meta.gid());
-                dest_uid = Some(meta.uid());
-            }
-            Err(e) => {
-                show_info!("failed to get attributes of '{}': {}", file, e);
-                return 1;
-            }
-        }
-    } else {
-        match parse_spec(&owner) {
-            Ok((u, g)) => {
-                dest_uid = u;
-                dest_gid = g;
-            }
-            Err(e) => {
-                show_info!("{}", e);
-                return 1;
-            }
-        }
-    }
-    let executor = Chowner {
-        bit_flag,
-        dest_uid,
-        dest_gid,
-        verbosity,
-        recursive,
-        dereference: derefer != 0,
-        filter,
-        preserve_root,
-        files,
-    };
-    executor.exec()
-}
-
-fn parse_spec(spec: &str) -> Result<(Option<u32>, Option<u32>), String> {
-    let args = spec.split(':').collect::<Vec<_>>();
-    let usr_only = args.len() == 1;
-    let grp_only = args.len() == 2 && args[0].is_empty() && !args[1].is_empty();
-    let usr_grp = args.len() == 2 && !args[0].is_empty() && !args[1].is_empty();
-
-    if usr_only {
-        Ok((
-            Some(match Passwd::locate(args[0]) {
-                Ok(v) => v.uid(),
-                _ => return Err(format!("invalid user: '{}'", spec)),
-            }),
-            None,
-        ))
-    } else if grp_only {
-        Ok((
-            None,
-            Some(match Group::locate(args[1]) {
-                Ok(v) => v.gid(),
-                _ => return Err(format!("invalid group: '{}'", spec)),
-            }),
-        ))
-    } else if usr_grp {
-        Ok((
-            Some(match Passwd::locate(args[0]) {
-                Ok(v) => v.uid(),
-                _ => return Err(format!("invalid user: '{}'", spec)),
-            }),
-            Some(match Group::locate(args[1]) {
-                Ok(v) => v.gid(),
-                _ => return Err(format!("invalid group: '{}'", spec)),
-            }),
-        ))
-    } else {
-        Ok((None, None))
-    }
-}
-
-enum IfFrom {
-    All,
-    User(u32),
-    Group(u32),
-    UserGroup(u32, u32),
 }
 
-struct Chowner {
-    dest_uid: Option<u32>,
-    dest_gid: Option<u32>,
-    bit_flag: u8,
-    verbosity: Verbosity,
-    filter: IfFrom,
-    files: Vec<String>,
-    recursive: bool,
-    preserve_root: bool,
-    dereference: bool,
-}
-
-macro_rules! unwrap {
-    ($m:expr, $e:ident, $err:block) => {
-        match $m {
-            Ok(meta) => meta,
-            Err($e) => $err,
-        }
-    };
-}
-
-impl Chowner {
-    fn exec(&self) -> i32 {
-        let mut ret = 0;
-        for f in &self.files {
-            ret |= self.traverse(f);
-        }
-        ret
-    }
-
-    fn traverse<P: AsRef<Path>>(&self, root: P) -> i32 {
-        let follow_arg = self.dereference || self.bit_flag != FTS_PHYSICAL;
-        let path = root.as_ref();
-        let meta = match self.obtain_meta(path, follow_arg) {
-            Some(m) => m,
-            _ => return 1,
-        };
-
-        // Prohibit only if:
-        // (--preserve-root and -R present) &&
-        // (
-        //     (argument is not symlink && resolved to be '/') ||
-        //     (argument is symlink && should follow argument && resolved to be '/')
-        // )
-        if self.recursive && self.preserve_root {
-            let may_exist = if follow_arg {
-                path.canonicalize().ok()
-            } else {
-                let real = resolve_relative_path(path);
-                if real.is_dir() {
-                    Some(real.canonicalize().expect("failed to get real path"))
-                } else {
-                    Some(real.into_owned())
-                }
-            };
-
-            if let Some(p) = may_exist {
-                if p.parent().is_none() {
-                    show_info!("it is dangerous to operate recursively on '/'");
-                    show_info!("use --no-preserve-root to override this failsafe");
-                    return 1;
-                }
+/// Parse an owner spec, also taking the obsolete `USER.GROUP` form.
+fn parse_spec(spec: &str) -> Result<OwnerSpec, String> {
+    match perms::parse_spec(spec, ':') {
+        // try the old separator only if the spec can't be taken as a user
+        Err(e) if spec.contains('.') && !spec.contains(':') => match perms::parse_spec(spec, '.') {
+            Ok(parsed) => {
+                show_warning!("'.' should be ':': '{}'", spec);
+                Ok(parsed)
             }
-        }
-
-        let ret = if self.matched(meta.uid(), meta.gid()) {
-            match wrap_chown(
-                path,
-                &meta,
-                self.dest_uid,
-                self.dest_gid,
-                follow_arg,
-                self.verbosity.clone(),
-            ) {
-                Ok(n) => {
-                    if n != "" {
-                        show_info!("{}", n);
-                    }
-                    0
-                }
-                Err(e) => {
-                    if self.verbosity != Verbosity::Silent {
-                        show_info!("{}", e);
-                    }
-                    1
-                }
-            }
-        } else {
-            0
-        };
-
-        if !self.recursive {
-            ret
-        } else {
-            ret | self.dive_into(&root)
-        }
-    }
-
-    fn dive_into<P: AsRef<Path>>(&self, root: P) -> i32 {
-        let mut ret = 0;
-        let root = root.as_ref();
-        let follow = self.dereference || self.bit_flag & FTS_LOGICAL != 0;
-        for entry in WalkDir::new(root).follow_links(follow).min_depth(1) {
-            let entry = unwrap!(entry, e, {
-                ret = 1;
-                show_info!("{}", e);
-                continue;
-            });
-            let path = entry.path();
-            let meta = match self.obtain_meta(path, follow) {
-                Some(m) => m,
-                _ => {
-                    ret = 1;
-                    continue;
-                }
-            };
-
-            if !self.matched(meta.uid(), meta.gid()) {
-                continue;
-            }
-
-            ret = match wrap_chown(
-                path,
-                &meta,
-                self.dest_uid,
-                self.dest_gid,
-                follow,
-                self.verbosity.clone(),
-            ) {
-                Ok(n) => {
-                    if n != "" {
-                        show_info!("{}", n);
-                    }
-                    0
-                }
-                Err(e) => {
-                    if self.verbosity != Verbosity::Silent {
-                        show_info!("{}", e);
-                    }
-                    1
-                }
-            }
-        }
-        ret
-    }
-
-    fn obtain_meta<P: AsRef<Path>>(&self, path: P, follow: bool) -> Option<Metadata> {
-        use self::Verbosity::*;
-        let path = path.as_ref();
-        let meta = if follow {
-            unwrap!(path.metadata(), e, {
-                match self.verbosity {
-                    Silent => (),
-                    _ => show_info!("cannot access '{}': {}", path.display(), e),
-                }
-                return None;
-            })
-        } else {
-            unwrap!(path.symlink_metadata(), e, {
-                match self.verbosity {
-                    Silent => (),
-                    _ => show_info!("cannot dereference '{}': {}", path.display(), e),
-                }
-                return None;
-            })
-        };
-        Some(meta)
-    }
-
-    #[inline]
-    fn matched(&self, uid: uid_t, gid: gid_t) -> bool {
-        match self.filter {
-            IfFrom::All => true,
-            IfFrom::User(u) => u == uid,
-            IfFrom::Group(g) => g == gid,
-            IfFrom::UserGroup(u, g) => u == uid && g == gid,
-        }
+            Err(_) => Err(e),
+        },
+        result => result,
     }
 }
@@ -6,38 +6,151 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) NEWROOT Userspec pstatus
+// spell-checker:ignore (ToDO) NEWROOT Userspec execvp setgroups
 
 #[macro_use]
 extern crate uucore;
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg};
+use std::env;
 use std::ffi::CString;
 use std::io::Error;
-use std::path::Path;
-use std::process::Command;
-use uucore::entries;
-use uucore::libc::{self, chroot, setgid, setgroups, setuid};
+use std::ptr;
+use uucore::entries::{Locate, Passwd};
+use uucore::error_message;
+use uucore::libc::{self, c_char, chroot, execvp, gid_t, setgid, setgroups, setuid, uid_t};
+use uucore::perms;
+use uucore::safe_traversal::is_root;
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
-static NAME: &str = "chroot";
 static ABOUT: &str = "Run COMMAND with root directory set to NEWROOT.";
 static SYNTAX: &str = "[OPTION]... NEWROOT [COMMAND [ARG]...]";
 
+// exit codes that match the GNU implementation
+static EXIT_CANCELED: i32 = 125;
+static EXIT_CANNOT_INVOKE: i32 = 126;
+static EXIT_ENOENT: i32 = 127;
+
 mod options {
     pub const NEWROOT: &str = "newroot";
     pub const USER: &str = "user";
     pub const GROUP: &str = "group";
     pub const GROUPS: &str = "groups";
     pub const USERSPEC: &str = "userspec";
+    pub const SKIP_CHDIR: &str = "skip-chdir";
+    pub const COMMAND: &str = "command";
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = App::new(executable!())
+    let app = uu_app();
+
+    let matches = match uucore::help::get_matches_from_safe(app, args) {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_CANCELED;
+        }
+    };
+
+    let newroot = matches.value_of(options::NEWROOT).unwrap();
+    let is_oldroot = is_root(newroot);
+    let skip_chdir = matches.is_present(options::SKIP_CHDIR);
+    if skip_chdir && !is_oldroot {
+        show_usage_error!("option --skip-chdir only permitted if NEWROOT is old '/'");
+        return EXIT_CANCELED;
+    }
+
+    let userspec = match matches.value_of(options::USERSPEC) {
+        Some(spec) => Some(spec.to_owned()),
+        None => match (
+            matches.value_of(options::USER),
+            matches.value_of(options::GROUP),
+        ) {
+            (None, None) => None,
+            (user, None) => user.map(ToOwned::to_owned),
+            (user, Some(group)) => Some(format!("{}:{}", user.unwrap_or_default(), group)),
+        },
+    };
+    let groups = matches.value_of(options::GROUPS);
+
+    let outside = if is_oldroot {
+        None
+    } else {
+        // Names are looked up once more inside the new root, where the IDs
+        // they stand for may differ; doing it here first as well loads what
+        // the lookups need while it can still be found.
+        Some(resolve(userspec.as_deref(), groups))
+    };
+
+    if !is_oldroot {
+        let root = CString::new(newroot).unwrap();
+        if unsafe { chroot(root.as_ptr()) } != 0 {
+            show_info!(
+                "cannot change root directory to '{}': {}",
+                newroot,
+                error_message(&Error::last_os_error())
+            );
+            return EXIT_CANCELED;
+        }
+    }
+    if !skip_chdir {
+        if let Err(e) = env::set_current_dir("/") {
+            show_info!("cannot chdir to root directory: {}", error_message(&e));
+            return EXIT_CANCELED;
+        }
+    }
+
+    let context = match (resolve(userspec.as_deref(), groups), outside) {
+        (Ok(context), _) => context,
+        // supplementary groups that can't be found here may still have been
+        // found outside
+        (Err(Failure::Groups(_)), Some(Ok(context))) => context,
+        (Err(failure), _) => {
+            show_info!("{}", failure.message());
+            return EXIT_CANCELED;
+        }
+    };
+    if let Err(e) = context.enter() {
+        show_info!("{}", e);
+        return EXIT_CANCELED;
+    }
+
+    let command: Vec<String> = match matches.values_of(options::COMMAND) {
+        Some(command) => command.map(ToOwned::to_owned).collect(),
+        // with no COMMAND, run an interactive shell
+        None => vec![
+            env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_owned()),
+            "-i".to_owned(),
+        ],
+    };
+    let cstrs: Vec<CString> = command
+        .iter()
+        .map(|arg| CString::new(arg.as_bytes()).unwrap())
+        .collect();
+    let mut argv: Vec<*const c_char> = cstrs.iter().map(|s| s.as_ptr()).collect();
+    argv.push(ptr::null());
+    unsafe { execvp(argv[0], argv.as_mut_ptr()) };
+
+    let err = Error::last_os_error();
+    show_info!(
+        "failed to run command '{}': {}",
+        command[0],
+        error_message(&err)
+    );
+    if err.raw_os_error() == Some(libc::ENOENT) {
+        EXIT_ENOENT
+    } else {
+        EXIT_CANNOT_INVOKE
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
         .usage(SYNTAX)
+        .setting(AppSettings::TrailingVarArg)
         .arg(Arg::with_name(options::NEWROOT).hidden(true).required(true))
         .arg(
             Arg::with_name(options::USER)
@@ -57,7 +170,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             Arg::with_name(options::GROUPS)
                 .short("G")
                 .long(options::GROUPS)
-                .help("Comma-separated list of groups to switch to")
+                .help("Comma-separated list of supplementary groups to switch to")
                 .value_name("GROUP1,GROUP2..."),
         )
         .arg(
@@ -70,156 +183,124 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 )
                 .value_name("USER:GROUP"),
         )
-        .get_matches_from(args);
-
-    let default_shell: &'static str = "/bin/sh";
-    let default_option: &'static str = "-i";
-    let user_shell = std::env::var("SHELL");
-
-    let newroot: &Path = match matches.value_of(options::NEWROOT) {
-        Some(v) => Path::new(v),
-        None => crash!(
-            1,
-            "Missing operand: NEWROOT\nTry '{} --help' for more information.",
-            NAME
-        ),
-    };
+        .arg(
+            Arg::with_name(options::SKIP_CHDIR)
+                .long(options::SKIP_CHDIR)
+                .help("do not change working directory to '/'"),
+        )
+        .arg(Arg::with_name(options::COMMAND).hidden(true).multiple(true))
+}
 
-    if !newroot.is_dir() {
-        crash!(
-            1,
-            "cannot change root directory to `{}`: no such directory",
-            newroot.display()
-        );
-    }
+/// Why the user and groups to run as couldn't be worked out.
+enum Failure {
+    Spec(String),
+    Groups(String),
+}
 
-    let command: Vec<&str> = match matches.args.len() {
-        1 => {
-            let shell: &str = match user_shell {
-                Err(_) => default_shell,
-                Ok(ref s) => s.as_ref(),
-            };
-            vec![shell, default_option]
-        }
-        _ => {
-            let mut vector: Vec<&str> = Vec::new();
-            for (&k, v) in matches.args.iter() {
-                vector.push(k.clone());
-                vector.push(&v.vals[0].to_str().unwrap());
-            }
-            vector
+impl Failure {
+    fn message(&self) -> &str {
+        match self {
+            Failure::Spec(msg) | Failure::Groups(msg) => msg,
         }
-    };
-
-    set_context(&newroot, &matches);
-
-    let pstatus = Command::new(command[0])
-        .args(&command[1..])
-        .status()
-        .unwrap_or_else(|e| crash!(1, "Cannot exec: {}", e));
-
-    if pstatus.success() {
-        0
-    } else {
-        pstatus.code().unwrap_or(-1)
     }
 }
 
-fn set_context(root: &Path, options: &clap::ArgMatches) {
-    let userspec_str = options.value_of(options::USERSPEC);
-    let user_str = options.value_of(options::USER).unwrap_or_default();
-    let group_str = options.value_of(options::GROUP).unwrap_or_default();
-    let groups_str = options.value_of(options::GROUPS).unwrap_or_default();
-    let userspec = match userspec_str {
-        Some(ref u) => {
-            let s: Vec<&str> = u.split(':').collect();
-            if s.len() != 2 || s.iter().any(|&spec| spec == "") {
-                crash!(1, "invalid userspec: `{}`", u)
-            };
-            s
-        }
-        None => Vec::new(),
-    };
-
-    let (user, group) = if userspec.is_empty() {
-        (&user_str[..], &group_str[..])
-    } else {
-        (&userspec[0][..], &userspec[1][..])
-    };
-
-    enter_chroot(root);
-
-    set_groups_from_str(&groups_str[..]);
-    set_main_group(&group[..]);
-    set_user(&user[..]);
+/// The credentials the command runs with; whatever is `None` stays as it is.
+struct Context {
+    uid: Option<uid_t>,
+    gid: Option<gid_t>,
+    groups: Option<Vec<gid_t>>,
 }
 
-fn enter_chroot(root: &Path) {
-    let root_str = root.display();
-    std::env::set_current_dir(root).unwrap();
-    let err = unsafe {
-        chroot(CString::new(".").unwrap().as_bytes_with_nul().as_ptr() as *const libc::c_char)
-    };
-    if err != 0 {
-        crash!(
-            1,
-            "cannot chroot to {}: {}",
-            root_str,
-            Error::last_os_error()
-        )
+/// Work out the credentials `--userspec` and `--groups` ask for, as the user
+/// and group databases have them.
+fn resolve(userspec: Option<&str>, groups: Option<&str>) -> Result<Context, Failure> {
+    let (uid, mut gid) = match userspec {
+        Some(spec) => match perms::parse_spec(spec, ':') {
+            Ok(spec) => (spec.uid, spec.gid),
+            Err(e) => return Err(Failure::Spec(e)),
+        },
+        None => (None, None),
     };
-}
 
-fn set_main_group(group: &str) {
-    if !group.is_empty() {
-        let group_id = match entries::grp2gid(group) {
-            Ok(g) => g,
-            _ => crash!(1, "no such group: {}", group),
-        };
-        let err = unsafe { setgid(group_id) };
-        if err != 0 {
-            crash!(
-                1,
-                "cannot set gid to {}: {}",
-                group_id,
-                Error::last_os_error()
-            )
+    let mut user = None;
+    if let Some(uid) = uid {
+        match Passwd::locate(uid) {
+            Ok(passwd) => {
+                if gid.is_none() {
+                    gid = Some(passwd.gid());
+                }
+                user = Some(passwd);
+            }
+            Err(_) if gid.is_none() => {
+                return Err(Failure::Spec(format!(
+                    "no group specified for unknown uid: {}",
+                    uid
+                )))
+            }
+            Err(_) => {}
         }
     }
+
+    let groups = match groups {
+        Some(list) => {
+            let mut gids = Vec::new();
+            for group in list.split(',').filter(|group| !group.is_empty()) {
+                match perms::parse_group(group) {
+                    Some(gid) => gids.push(gid),
+                    None => return Err(Failure::Groups(format!("invalid group '{}'", group))),
+                }
+            }
+            Some(gids)
+        }
+        // a new user gets their own groups, or none at all
+        None => uid.map(|_| match (&user, gid) {
+            (Some(user), Some(gid)) => user.belongs_to_with(gid),
+            _ => Vec::new(),
+        }),
+    };
+
+    Ok(Context { uid, gid, groups })
 }
 
 #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
-fn set_groups(groups: Vec<libc::gid_t>) -> libc::c_int {
+fn set_groups(groups: &[gid_t]) -> libc::c_int {
     unsafe { setgroups(groups.len() as libc::c_int, groups.as_ptr()) }
 }
 
 #[cfg(target_os = "linux")]
-fn set_groups(groups: Vec<libc::gid_t>) -> libc::c_int {
+fn set_groups(groups: &[gid_t]) -> libc::c_int {
     unsafe { setgroups(groups.len() as libc::size_t, groups.as_ptr()) }
 }
 
-fn set_groups_from_str(groups: &str) {
-    if !groups.is_empty() {
-        let groups_vec: Vec<libc::gid_t> = groups
-            .split(',')
-            .map(|x| match entries::grp2gid(x) {
-                Ok(g) => g,
-                _ => crash!(1, "no such group: {}", x),
-            })
-            .collect();
-        let err = set_groups(groups_vec);
-        if err != 0 {
-            crash!(1, "cannot set groups: {}", Error::last_os_error())
+impl Context {
+    /// Switch to these credentials, the supplementary groups first and the
+    /// user last, while there's still the privilege to.
+    fn enter(&self) -> Result<(), String> {
+        if let Some(groups) = &self.groups {
+            if set_groups(groups) != 0 {
+                return Err(format!(
+                    "failed to set supplemental groups: {}",
+                    error_message(&Error::last_os_error())
+                ));
+            }
         }
-    }
-}
-
-fn set_user(user: &str) {
-    if !user.is_empty() {
-        let user_id = entries::usr2uid(user).unwrap();
-        let err = unsafe { setuid(user_id as libc::uid_t) };
-        if err != 0 {
-            crash!(1, "cannot set user to {}: {}", user, Error::last_os_error())
+        if let Some(gid) = self.gid {
+            if unsafe { setgid(gid) } != 0 {
+                return Err(format!(
+                    "failed to set group-ID: {}",
+                    error_message(&Error::last_os_error())
+                ));
+            }
+        }
+        if let Some(uid) = self.uid {
+            if unsafe { setuid(uid) } != 0 {
+                return Err(format!(
+                    "failed to set user-ID: {}",
+                    error_message(&Error::last_os_error())
+                ));
+            }
         }
+        Ok(())
     }
 }
@@ -5,217 +5,574 @@
 //  For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) fname
+// spell-checker:ignore (ToDO) fname algo sysv untagged
 
 #[macro_use]
 extern crate uucore;
 
 use clap::{App, Arg};
-use std::fs::File;
-use std::io::{self, stdin, BufReader, Read};
-use std::path::Path;
-
-// NOTE: CRC_TABLE_LEN *must* be <= 256 as we cast 0..CRC_TABLE_LEN to u8
-const CRC_TABLE_LEN: usize = 256;
-const CRC_TABLE: [u32; CRC_TABLE_LEN] = generate_crc_table();
+use std::io::{self, stdout, Write};
+use uucore::encoding::{self, Format};
+use uucore::error_message;
+use uucore::sum::{
+    self, digest_file, escape, from_hex, needs_escape, open, parse_line, Blake2b, Bsd,
+    CheckOptions, Crc, Digest, Listed, Md5, Sha1, Sha224, Sha256, Sha384, Sha512, Sm3, SysV,
+};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const NAME: &str = "cksum";
 const SYNTAX: &str = "[OPTIONS] [FILE]...";
-const SUMMARY: &str = "Print CRC and size for each file";
-
-// this is basically a hack to get "loops" to work on Rust 1.33.  Once we update to Rust 1.46 or
-// greater, we can just use while loops
-macro_rules! unroll {
-    (256, |$i:ident| $s:expr) => {{
-        unroll!(@ 32, 0 * 32, $i, $s);
-        unroll!(@ 32, 1 * 32, $i, $s);
-        unroll!(@ 32, 2 * 32, $i, $s);
-        unroll!(@ 32, 3 * 32, $i, $s);
-        unroll!(@ 32, 4 * 32, $i, $s);
-        unroll!(@ 32, 5 * 32, $i, $s);
-        unroll!(@ 32, 6 * 32, $i, $s);
-        unroll!(@ 32, 7 * 32, $i, $s);
-    }};
-    (8, |$i:ident| $s:expr) => {{
-        unroll!(@ 8, 0, $i, $s);
-    }};
-
-    (@ 32, $start:expr, $i:ident, $s:expr) => {{
-        unroll!(@ 8, $start + 0 * 8, $i, $s);
-        unroll!(@ 8, $start + 1 * 8, $i, $s);
-        unroll!(@ 8, $start + 2 * 8, $i, $s);
-        unroll!(@ 8, $start + 3 * 8, $i, $s);
-    }};
-    (@ 8, $start:expr, $i:ident, $s:expr) => {{
-        unroll!(@ 4, $start, $i, $s);
-        unroll!(@ 4, $start + 4, $i, $s);
-    }};
-    (@ 4, $start:expr, $i:ident, $s:expr) => {{
-        unroll!(@ 2, $start, $i, $s);
-        unroll!(@ 2, $start + 2, $i, $s);
-    }};
-    (@ 2, $start:expr, $i:ident, $s:expr) => {{
-        unroll!(@ 1, $start, $i, $s);
-        unroll!(@ 1, $start + 1, $i, $s);
-    }};
-    (@ 1, $start:expr, $i:ident, $s:expr) => {{
-        let $i = $start;
-        let _ = $s;
-    }};
-}
+const SUMMARY: &str = "Print or check CRC, BSD, System V or message digest checksums.";
+const LONG_HELP: &str = "
+ With no FILE, or when FILE is -, read standard input.
 
-const fn generate_crc_table() -> [u32; CRC_TABLE_LEN] {
-    let mut table = [0; CRC_TABLE_LEN];
-
-    // NOTE: works on Rust 1.46
-    //let mut i = 0;
-    //while i < CRC_TABLE_LEN {
-    //    table[i] = crc_entry(i as u8) as u32;
-    //
-    //    i += 1;
-    //}
-    unroll!(256, |i| {
-        table[i] = crc_entry(i as u8) as u32;
-    });
-
-    table
-}
+ The default algorithm is crc, which prints the CRC and size of each FILE.
+ The others print a tagged checksum line, as --tag does, unless --untagged
+ is given.";
 
-const fn crc_entry(input: u8) -> u32 {
-    let mut crc = (input as u32) << 24;
-
-    // NOTE: this does not work on Rust 1.33, but *does* on 1.46
-    //let mut i = 0;
-    //while i < 8 {
-    //    if crc & 0x8000_0000 != 0 {
-    //        crc <<= 1;
-    //        crc ^= 0x04c1_1db7;
-    //    } else {
-    //        crc <<= 1;
-    //    }
-    //
-    //    i += 1;
-    //}
-    unroll!(8, |_i| {
-        let if_cond = crc & 0x8000_0000;
-        let if_body = (crc << 1) ^ 0x04c1_1db7;
-        let else_body = crc << 1;
-
-        // NOTE: i feel like this is easier to understand than emulating an if statement in bitwise
-        //       ops
-        let cond_table = [else_body, if_body];
-
-        crc = cond_table[(if_cond != 0) as usize];
-    });
-
-    crc
+/// What -a/--algorithm takes, in the order --help lists them.
+const ALGORITHMS: &[&str] = &[
+    "bsd", "sysv", "crc", "md5", "sha1", "sha224", "sha256", "sha384", "sha512", "blake2b", "sm3",
+];
+
+mod options {
+    pub static ALGORITHM: &str = "algorithm";
+    pub static BASE64: &str = "base64";
+    pub static CHECK: &str = "check";
+    pub static DEBUG: &str = "debug";
+    pub static FILE: &str = "file";
+    pub static IGNORE_MISSING: &str = "ignore-missing";
+    pub static LENGTH: &str = "length";
+    pub static QUIET: &str = "quiet";
+    pub static RAW: &str = "raw";
+    pub static STATUS: &str = "status";
+    pub static STRICT: &str = "strict";
+    pub static TAG: &str = "tag";
+    pub static UNTAGGED: &str = "untagged";
+    pub static WARN: &str = "warn";
+    pub static ZERO: &str = "zero";
 }
 
-#[inline]
-fn crc_update(crc: u32, input: u8) -> u32 {
-    (crc << 8) ^ CRC_TABLE[((crc >> 24) as usize ^ input as usize) & 0xFF]
+/// An algorithm, as -a/--algorithm names it.
+#[derive(Clone, Copy, PartialEq)]
+enum Algorithm {
+    Bsd,
+    SysV,
+    Crc,
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Blake2b,
+    Sm3,
 }
 
-#[inline]
-fn crc_final(mut crc: u32, mut length: usize) -> u32 {
-    while length != 0 {
-        crc = crc_update(crc, length as u8);
-        length >>= 8;
+impl Algorithm {
+    fn from_name(name: &str) -> Option<Algorithm> {
+        Some(match name {
+            "bsd" => Algorithm::Bsd,
+            "sysv" => Algorithm::SysV,
+            "crc" => Algorithm::Crc,
+            "md5" => Algorithm::Md5,
+            "sha1" => Algorithm::Sha1,
+            "sha224" => Algorithm::Sha224,
+            "sha256" => Algorithm::Sha256,
+            "sha384" => Algorithm::Sha384,
+            "sha512" => Algorithm::Sha512,
+            "blake2b" => Algorithm::Blake2b,
+            "sm3" => Algorithm::Sm3,
+            _ => return None,
+        })
     }
 
-    !crc
-}
+    /// The algorithm a checksum line tagged `tag` was made with.
+    fn from_tag(tag: &str) -> Option<Algorithm> {
+        Some(match tag {
+            "MD5" => Algorithm::Md5,
+            "SHA1" => Algorithm::Sha1,
+            "SHA224" => Algorithm::Sha224,
+            "SHA256" => Algorithm::Sha256,
+            "SHA384" => Algorithm::Sha384,
+            "SHA512" => Algorithm::Sha512,
+            "BLAKE2b" => Algorithm::Blake2b,
+            "SM3" => Algorithm::Sm3,
+            _ => return None,
+        })
+    }
 
-fn init_byte_array() -> Vec<u8> {
-    vec![0; 1024 * 1024]
-}
+    /// How a checksum line is tagged with the algorithm, and how the
+    /// algorithm is named in warnings about lines.
+    fn tag(self) -> &'static str {
+        match self {
+            Algorithm::Bsd => "BSD",
+            Algorithm::SysV => "SYSV",
+            Algorithm::Crc => "CRC",
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha224 => "SHA224",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha384 => "SHA384",
+            Algorithm::Sha512 => "SHA512",
+            Algorithm::Blake2b => "BLAKE2b",
+            Algorithm::Sm3 => "SM3",
+        }
+    }
 
-#[inline]
-fn cksum(fname: &str) -> io::Result<(u32, usize)> {
-    let mut crc = 0u32;
-    let mut size = 0usize;
-
-    let file;
-    let mut rd: Box<dyn Read> = match fname {
-        "-" => Box::new(stdin()),
-        _ => {
-            let path = &Path::new(fname);
-            if path.is_dir() {
-                return Err(std::io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "Is a directory",
-                ));
-            };
-            if !path.metadata().is_ok() {
-                return Err(std::io::Error::new(
-                    io::ErrorKind::NotFound,
-                    "No such file or directory",
-                ));
-            };
-            file = File::open(&path)?;
-            Box::new(BufReader::new(file))
+    /// Whether the algorithm prints the checksum and size in blocks (or
+    /// bytes) of a file, as `sum` and the old `cksum` do, rather than a
+    /// checksum line.
+    fn is_legacy(self) -> bool {
+        match self {
+            Algorithm::Bsd | Algorithm::SysV | Algorithm::Crc => true,
+            _ => false,
         }
-    };
+    }
 
-    let mut bytes = init_byte_array();
-    loop {
-        match rd.read(&mut bytes) {
-            Ok(num_bytes) => {
-                if num_bytes == 0 {
-                    return Ok((crc_final(crc, size), size));
-                }
-                for &b in bytes[..num_bytes].iter() {
-                    crc = crc_update(crc, b);
-                }
-                size += num_bytes;
-            }
-            Err(err) => return Err(err),
+    /// A digest for the algorithm, of `bits` bits for BLAKE2b.
+    fn digest(self, bits: usize) -> Box<dyn Digest> {
+        match self {
+            Algorithm::Bsd => Box::new(Bsd::new()),
+            Algorithm::SysV => Box::new(SysV::new()),
+            Algorithm::Crc => Box::new(Crc::new()),
+            Algorithm::Md5 => Box::new(Md5::new()),
+            Algorithm::Sha1 => Box::new(Sha1::new()),
+            Algorithm::Sha224 => Box::new(Sha224::new()),
+            Algorithm::Sha256 => Box::new(Sha256::new()),
+            Algorithm::Sha384 => Box::new(Sha384::new()),
+            Algorithm::Sha512 => Box::new(Sha512::new()),
+            Algorithm::Blake2b => Box::new(Blake2b::new(bits / 8)),
+            Algorithm::Sm3 => Box::new(Sm3::new()),
         }
     }
 }
 
-mod options {
-    pub static FILE: &str = "file";
+struct Options {
+    algorithm: Algorithm,
+    /// The length of BLAKE2b digests, in bits.
+    bits: usize,
+    untagged: bool,
+    raw: bool,
+    base64: bool,
+    zero: bool,
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = App::new(executable!())
-        .name(NAME)
-        .version(VERSION)
-        .about(SUMMARY)
-        .usage(SYNTAX)
-        .arg(Arg::with_name(options::FILE).hidden(true).multiple(true))
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app(), args);
+
+    // the last -a wins
+    let algorithm = match matches.values_of(options::ALGORITHM).and_then(|v| v.last()) {
+        Some(name) => match Algorithm::from_name(name) {
+            Some(algorithm) => Some(algorithm),
+            None => {
+                show_info!("invalid argument '{}' for '--algorithm'", name);
+                eprintln!("Valid arguments are:");
+                for name in ALGORITHMS {
+                    eprintln!("  - '{}'", name);
+                }
+                eprintln!("Try '{} --help' for more information.", executable!());
+                return 1;
+            }
+        },
+        None => None,
+    };
+    let check = matches.is_present(options::CHECK);
+
+    let bits = match matches.value_of(options::LENGTH) {
+        Some(_) if algorithm != Some(Algorithm::Blake2b) => {
+            show_info!("--length is only supported with --algorithm=blake2b");
+            return 1;
+        }
+        Some(length) => match length.parse::<usize>() {
+            // as if it weren't given
+            Ok(0) => 512,
+            Ok(bits) if bits % 8 != 0 => {
+                show_info!("invalid length: '{}'", length);
+                show_info!("length is not a multiple of 8");
+                return 1;
+            }
+            Ok(bits) if bits > 512 => {
+                show_info!("invalid length: '{}'", length);
+                show_info!("maximum digest length for 'BLAKE2b' is 512 bits");
+                return 1;
+            }
+            Ok(bits) => bits,
+            Err(_) => {
+                show_info!("invalid length: '{}'", length);
+                return 1;
+            }
+        },
+        None => 512,
+    };
+
+    if check {
+        if let Some(algorithm) = algorithm {
+            if algorithm.is_legacy() {
+                show_info!("--check is not supported with --algorithm={{bsd,sysv,crc}}");
+                return 1;
+            }
+        }
+        if matches.is_present(options::ZERO) {
+            show_usage_error!("the --zero option is not supported when verifying checksums");
+            return 1;
+        }
+    } else {
+        for option in &[
+            options::IGNORE_MISSING,
+            options::QUIET,
+            options::STATUS,
+            options::STRICT,
+            options::WARN,
+        ] {
+            if matches.is_present(option) {
+                show_usage_error!(
+                    "the --{} option is meaningful only when verifying checksums",
+                    option
+                );
+                return 1;
+            }
+        }
+    }
 
     let files: Vec<String> = match matches.values_of(options::FILE) {
         Some(v) => v.clone().map(|v| v.to_owned()).collect(),
         None => vec![],
     };
+    let raw = matches.is_present(options::RAW);
+    if raw && files.len() > 1 {
+        show_info!("the --raw option is not supported with multiple files");
+        return 1;
+    }
+
+    // of --tag and --untagged, and of --quiet, --status and --warn, the
+    // last given wins
+    let last = |names: &[&'static str]| {
+        names
+            .iter()
+            .filter_map(|&option| matches.index_of(option).map(|index| (index, option)))
+            .max()
+            .map(|(_, option)| option)
+    };
+    let untagged = last(&[options::TAG, options::UNTAGGED]) == Some(options::UNTAGGED);
+    let last_report = last(&[options::QUIET, options::STATUS, options::WARN]);
+    let status = last_report == Some(options::STATUS);
+
+    let opts = Options {
+        algorithm: algorithm.unwrap_or(Algorithm::Crc),
+        bits,
+        untagged,
+        raw,
+        base64: matches.is_present(options::BASE64),
+        zero: matches.is_present(options::ZERO),
+    };
 
-    if files.is_empty() {
-        match cksum("-") {
-            Ok((crc, size)) => println!("{} {}", crc, size),
-            Err(err) => {
-                show_error!("{}", err);
-                return 2;
+    if check {
+        let check_options = CheckOptions {
+            util_name: executable!(),
+            status,
+            quiet: last_report == Some(options::QUIET) || status,
+            strict: matches.is_present(options::STRICT),
+            warn: last_report == Some(options::WARN),
+            ignore_missing: matches.is_present(options::IGNORE_MISSING),
+        };
+        let lists = if files.is_empty() {
+            vec!["-".to_owned()]
+        } else {
+            files
+        };
+        let mut exit_code = 0;
+        for list in &lists {
+            let ok = match open(list) {
+                Ok(reader) => sum::check(
+                    &check_options,
+                    list,
+                    reader,
+                    |line| listed(line, algorithm, opts.algorithm),
+                    |lines, report| lines.into_iter().for_each(report),
+                ),
+                Err(e) => {
+                    show_info!("{}: {}", list, error_message(&e));
+                    false
+                }
+            };
+            if !ok {
+                exit_code = 1;
             }
         }
-        return 0;
+        return exit_code;
     }
 
+    // only the old formats leave the name of standard input out
+    let named = !files.is_empty() || !opts.algorithm.is_legacy();
+    let files = if files.is_empty() {
+        vec!["-".to_owned()]
+    } else {
+        files
+    };
+    // which CRC implementation is used, once one is
+    let mut debug = matches.is_present(options::DEBUG) && opts.algorithm == Algorithm::Crc;
     let mut exit_code = 0;
     for fname in &files {
-        match cksum(fname.as_ref()) {
-            Ok((crc, size)) => println!("{} {} {}", crc, size, fname),
-            Err(err) => {
-                show_error!("'{}' {}", fname, err);
-                exit_code = 2;
+        let mut digest = opts.algorithm.digest(opts.bits);
+        let bytes = digest.output_bytes();
+        match digest_file(fname, &mut *digest, true, bytes) {
+            Ok((sum, size)) => {
+                if debug {
+                    show_info!("using {} hardware support", Crc::implementation());
+                    debug = false;
+                }
+                if let Err(e) = print_sum(&opts, if named { Some(fname) } else { None }, &sum, size)
+                {
+                    crash!(1, "{}", error_message(&e));
+                }
+            }
+            Err(e) => {
+                show_info!("{}: {}", fname, error_message(&e));
+                exit_code = 1;
             }
         }
     }
 
     exit_code
 }
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .name(NAME)
+        .version(VERSION)
+        .about(SUMMARY)
+        .usage(SYNTAX)
+        .after_help(LONG_HELP)
+        .arg(
+            Arg::with_name(options::ALGORITHM)
+                .short("a")
+                .long(options::ALGORITHM)
+                .help("select the digest type to use (one of bsd, sysv, crc, md5, sha1, sha224, sha256, sha384, sha512, blake2b, sm3)")
+                .takes_value(true)
+                .value_name("TYPE")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name(options::BASE64)
+                .long(options::BASE64)
+                .help("emit base64-encoded digests, not hexadecimal"),
+        )
+        .arg(
+            Arg::with_name(options::CHECK)
+                .short("c")
+                .long(options::CHECK)
+                .help("read checksums from the FILEs and check them"),
+        )
+        .arg(
+            Arg::with_name(options::LENGTH)
+                .short("l")
+                .long(options::LENGTH)
+                .help("digest length in bits; must not exceed the maximum for the blake2 algorithm and must be a multiple of 8")
+                .takes_value(true)
+                .value_name("BITS"),
+        )
+        .arg(
+            Arg::with_name(options::RAW)
+                .long(options::RAW)
+                .help("emit a raw binary digest, not hexadecimal"),
+        )
+        .arg(
+            Arg::with_name(options::TAG)
+                .long(options::TAG)
+                .help("create a BSD-style checksum (the default)"),
+        )
+        .arg(
+            Arg::with_name(options::UNTAGGED)
+                .long(options::UNTAGGED)
+                .help("create a reversed style checksum, without digest type"),
+        )
+        .arg(
+            Arg::with_name(options::ZERO)
+                .short("z")
+                .long(options::ZERO)
+                .help("end each output line with NUL, not newline, and disable file name escaping"),
+        )
+        .arg(
+            Arg::with_name(options::IGNORE_MISSING)
+                .long(options::IGNORE_MISSING)
+                .help("don't fail or report status for missing files"),
+        )
+        .arg(
+            Arg::with_name(options::QUIET)
+                .long(options::QUIET)
+                .help("don't print OK for each successfully verified file"),
+        )
+        .arg(
+            Arg::with_name(options::STATUS)
+                .long(options::STATUS)
+                .help("don't output anything, status code shows success"),
+        )
+        .arg(
+            Arg::with_name(options::STRICT)
+                .long(options::STRICT)
+                .help("exit non-zero for improperly formatted checksum lines"),
+        )
+        .arg(
+            Arg::with_name(options::WARN)
+                .short("w")
+                .long(options::WARN)
+                .help("warn about improperly formatted checksum lines"),
+        )
+        .arg(
+            Arg::with_name(options::DEBUG)
+                .long(options::DEBUG)
+                .help("indicate which implementation is used"),
+        )
+        .arg(Arg::with_name(options::FILE).hidden(true).multiple(true))
+}
+
+/// Print the checksum `sum` of the file named `fname`, of `size` bytes, as
+/// `options` say to.
+fn print_sum(options: &Options, fname: Option<&str>, sum: &[u8], size: u64) -> io::Result<()> {
+    let stdout = stdout();
+    let mut out = stdout.lock();
+    if options.raw {
+        return out.write_all(sum);
+    }
+
+    let end = if options.zero { '\0' } else { '\n' };
+    let name = match fname {
+        Some(fname) => fname,
+        None => "",
+    };
+    let sep = if fname.is_some() { " " } else { "" };
+    match options.algorithm {
+        Algorithm::Crc => write!(out, "{} {}{}{}{}", to_u32(sum), size, sep, name, end),
+        Algorithm::SysV => write!(
+            out,
+            "{} {}{}{}{}",
+            to_u32(sum),
+            (size + 511) / 512,
+            sep,
+            name,
+            end
+        ),
+        Algorithm::Bsd => write!(
+            out,
+            "{:05} {:5}{}{}{}",
+            to_u32(sum),
+            (size + 1023) / 1024,
+            sep,
+            name,
+            end
+        ),
+        algorithm => {
+            let sum = if options.base64 {
+                encoding::encode(Format::Base64, sum)
+            } else {
+                to_hex(sum)
+            };
+            // a name that would break the line up is escaped, as a line
+            // starting with a backslash says
+            let (prefix, name) = if !options.zero && needs_escape(name) {
+                ("\\", escape(name))
+            } else {
+                ("", name.to_owned())
+            };
+            if options.untagged {
+                write!(out, "{}{}  {}{}", prefix, sum, name, end)
+            } else if algorithm == Algorithm::Blake2b && options.bits != 512 {
+                write!(
+                    out,
+                    "{}BLAKE2b-{} ({}) = {}{}",
+                    prefix, options.bits, name, sum, end
+                )
+            } else {
+                write!(
+                    out,
+                    "{}{} ({}) = {}{}",
+                    prefix,
+                    algorithm.tag(),
+                    name,
+                    sum,
+                    end
+                )
+            }
+        }
+    }
+}
+
+/// The file the checksum line `line` names, and its checksum. A line is
+/// either tagged with an algorithm, which must be `algorithm` if that's
+/// given, or untagged, which only an `algorithm` that's given can check.
+/// The checksum can be hexadecimal or base64. A line that isn't properly
+/// formatted gives the algorithm that would check it, or `default`.
+fn listed(line: &str, algorithm: Option<Algorithm>, default: Algorithm) -> Result<Listed, String> {
+    let parsed = parse_line(line);
+    let tag = parsed.as_ref().and_then(|parsed| parsed.tag);
+    let tagged = tag.and_then(|(tag, _)| Algorithm::from_tag(tag));
+    let improper = || algorithm.or(tagged).unwrap_or(default).tag().to_owned();
+    let parsed = parsed.ok_or_else(improper)?;
+
+    let (algorithm, bytes) = match tag {
+        Some((_, bits)) => {
+            let tagged = tagged.ok_or_else(improper)?;
+            if algorithm.map_or(false, |algorithm| algorithm != tagged) {
+                return Err(improper());
+            }
+            let bytes = match bits {
+                // as it's written, so "BLAKE2b-08" isn't taken for "BLAKE2b-8"
+                Some(bits) => match bits.parse::<usize>() {
+                    Ok(n) if n.to_string() == bits && n % 8 == 0 && n > 0 && n <= 512 => {
+                        Some(n / 8)
+                    }
+                    _ => return Err(improper()),
+                },
+                None => Some(tagged.digest(512).output_bytes()),
+            };
+            (tagged, bytes)
+        }
+        None => {
+            let algorithm = algorithm.ok_or_else(improper)?;
+            // an untagged BLAKE2b line is as long as its digest is
+            let bytes = if algorithm == Algorithm::Blake2b {
+                None
+            } else {
+                Some(algorithm.digest(512).output_bytes())
+            };
+            (algorithm, bytes)
+        }
+    };
+
+    let sum = match bytes {
+        Some(bytes) => decode_sum(parsed.sum, bytes),
+        None => decode_sum(parsed.sum, 64)
+            .or_else(|| from_hex(parsed.sum))
+            .filter(|sum| sum.len() <= 64),
+    };
+    let sum = sum.ok_or_else(improper)?;
+    Ok(Listed {
+        filename: parsed.filename,
+        digest: algorithm.digest(sum.len() * 8),
+        sum,
+        binary: true,
+    })
+}
+
+/// The `bytes` bytes of checksum `sum` is, in hexadecimal or base64.
+fn decode_sum(sum: &str, bytes: usize) -> Option<Vec<u8>> {
+    let decoded = if sum.len() == bytes * 2 {
+        from_hex(sum)
+    } else {
+        encoding::decode(Format::Base64, sum.as_bytes()).ok()
+    };
+    decoded.filter(|decoded| decoded.len() == bytes)
+}
+
+fn to_hex(sum: &[u8]) -> String {
+    sum.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The checksum of an old format, `sum` and `cksum` print in decimal.
+fn to_u32(sum: &[u8]) -> u32 {
+    sum.iter().fold(0, |n, &byte| n << 8 | u32::from(byte))
+}
@@ -296,10 +296,34 @@ static DEFAULT_ATTRIBUTES: &[Attribute] = &[
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let options = crash_if_err!(EXIT_ERR, Options::from_matches(&matches));
+    let paths: Vec<String> = matches
+        .values_of(OPT_PATHS)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    let (sources, target) = crash_if_err!(EXIT_ERR, parse_path_args(&paths, &options));
+
+    if let Err(error) = copy(&sources, &target, &options) {
+        match error {
+            // Error::NotAllFilesCopied is non-fatal, but the error
+            // code should still be EXIT_ERR as does GNU cp
+            Error::NotAllFilesCopied => {}
+            // Else we caught a fatal bubbled-up error, log it to stderr
+            _ => show_error!("{}", error),
+        };
+        return EXIT_ERR;
+    }
+
+    EXIT_OK
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .arg(Arg::with_name(OPT_TARGET_DIRECTORY)
              .short("t")
              .conflicts_with(OPT_NO_TARGET_DIRECTORY)
@@ -458,28 +482,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
         .arg(Arg::with_name(OPT_PATHS)
              .multiple(true))
-        .get_matches_from(args);
-
-    let options = crash_if_err!(EXIT_ERR, Options::from_matches(&matches));
-    let paths: Vec<String> = matches
-        .values_of(OPT_PATHS)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    let (sources, target) = crash_if_err!(EXIT_ERR, parse_path_args(&paths, &options));
-
-    if let Err(error) = copy(&sources, &target, &options) {
-        match error {
-            // Error::NotAllFilesCopied is non-fatal, but the error
-            // code should still be EXIT_ERR as does GNU cp
-            Error::NotAllFilesCopied => {}
-            // Else we caught a fatal bubbled-up error, log it to stderr
-            _ => show_error!("{}", error),
-        };
-        return EXIT_ERR;
-    }
-
-    EXIT_OK
 }
 
 impl ClobberMode {
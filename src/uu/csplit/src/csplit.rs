@@ -17,6 +17,7 @@ mod splitname;
 
 use crate::csplit_error::CsplitError;
 use crate::splitname::SplitName;
+use uucore::coreopts::CoreOptions;
 
 static SYNTAX: &str = "[OPTION]... FILE PATTERN...";
 static SUMMARY: &str = "split a file into sections determined by context lines";
@@ -704,29 +705,7 @@ mod tests {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = app!(SYNTAX, SUMMARY, LONG_HELP)
-        .optopt(
-            "b",
-            SUFFIX_FORMAT_OPT,
-            "use sprintf FORMAT instead of %02d",
-            "FORMAT",
-        )
-        .optopt("f", PREFIX_OPT, "use PREFIX instead of 'xx'", "PREFIX")
-        .optflag("k", KEEP_FILES_OPT, "do not remove output files on errors")
-        .optflag(
-            "",
-            SUPPRESS_MATCHED_OPT,
-            "suppress the lines matching PATTERN",
-        )
-        .optopt(
-            "n",
-            DIGITS_OPT,
-            "use specified number of digits instead of 2",
-            "DIGITS",
-        )
-        .optflag("s", QUIET_OPT, "do not print counts of output file sizes")
-        .optflag("z", ELIDE_EMPTY_FILES_OPT, "remove empty output files")
-        .parse(args);
+    let matches = uu_app().parse(args);
 
     // check for mandatory arguments
     if matches.free.is_empty() {
@@ -755,3 +734,29 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     };
     0
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    app.optopt(
+        "b",
+        SUFFIX_FORMAT_OPT,
+        "use sprintf FORMAT instead of %02d",
+        "FORMAT",
+    )
+    .optopt("f", PREFIX_OPT, "use PREFIX instead of 'xx'", "PREFIX")
+    .optflag("k", KEEP_FILES_OPT, "do not remove output files on errors")
+    .optflag(
+        "",
+        SUPPRESS_MATCHED_OPT,
+        "suppress the lines matching PATTERN",
+    )
+    .optopt(
+        "n",
+        DIGITS_OPT,
+        "use specified number of digits instead of 2",
+        "DIGITS",
+    )
+    .optflag("s", QUIET_OPT, "do not print counts of output file sizes")
+    .optflag("z", ELIDE_EMPTY_FILES_OPT, "remove empty output files");
+    app
+}
@@ -10,14 +10,16 @@
 #[macro_use]
 extern crate uucore;
 
+use std::cmp::min;
 use std::fs::File;
-use std::io::{stdin, stdout, BufRead, BufReader, Read, Stdout, Write};
+use std::io::{self, stdin, stdout, BufRead, BufReader, BufWriter, IoSlice, Read, Write};
 use std::path::Path;
+use std::str;
 
 use self::searcher::Searcher;
+use uucore::coreopts::CoreOptions;
 use uucore::ranges::Range;
 
-mod buffer;
 mod searcher;
 
 static SYNTAX: &str =
@@ -30,7 +32,10 @@ static LONG_HELP: &str = "
 
  Specifying a mode
 
-    Use --bytes (-b) or --characters (-c) to specify byte mode
+    Use --bytes (-b) to specify byte mode, or --characters (-c) to specify
+    character mode, where multibyte UTF-8 characters count as one column.
+    In byte mode, -n prevents multibyte characters from being split: a
+    character is only printed if its last byte is selected
 
     Use --fields (-f) to specify field mode, where each line is broken into
     fields identified by a delimiter character. For example for a typical CSV
@@ -93,6 +98,12 @@ static LONG_HELP: &str = "
         useful for transforming tabular data - e.g. to convert a CSV to a
         TSV (tab-separated file)
 
+        In byte and character mode, --output-delimiter is printed between
+        the selected ranges instead
+
+    cut -b 1-2,5- --output-delimiter=: some_file.txt
+    will display 'ab:efg' for the line 'abcdefg'
+
  Line endings
 
     When the --zero-terminated (-z) option is used, cut sees \\0 (null) as the
@@ -104,8 +115,11 @@ static LONG_HELP: &str = "
     will result in 'a\\0c\\0'
 ";
 
+const OUTPUT_BUFFER_SIZE: usize = 64 * 1024;
+
 struct Options {
     out_delim: Option<String>,
+    no_split: bool,
     zero_terminated: bool,
 }
 
@@ -130,90 +144,15 @@ fn list_to_ranges(list: &str, complement: bool) -> Result<Vec<Range>, String> {
     }
 }
 
-fn cut_bytes<R: Read>(reader: R, ranges: &[Range], opts: &Options) -> i32 {
-    use self::buffer::Bytes::Select;
-    use self::buffer::Bytes::Selected::*;
-
+fn cut_bytes<R: Read>(reader: R, ranges: &[Range], characters: bool, opts: &Options) -> i32 {
     let newline_char = if opts.zero_terminated { b'\0' } else { b'\n' };
-    let mut buf_read = buffer::ByteReader::new(reader, newline_char);
-    let mut out = stdout();
-
-    'newline: loop {
-        let mut cur_pos = 1;
-        let mut print_delim = false;
-
-        for &Range { low, high } in ranges.iter() {
-            // skip up to low
-            let orig_pos = cur_pos;
-            loop {
-                match buf_read.select(low - cur_pos, None::<&mut Stdout>) {
-                    NewlineFound => {
-                        crash_if_err!(1, out.write_all(&[newline_char]));
-                        continue 'newline;
-                    }
-                    Complete(len) => {
-                        cur_pos += len;
-                        break;
-                    }
-                    Partial(len) => cur_pos += len,
-                    EndOfFile => {
-                        if orig_pos != cur_pos {
-                            crash_if_err!(1, out.write_all(&[newline_char]));
-                        }
-
-                        break 'newline;
-                    }
-                }
-            }
-
-            if let Some(ref delim) = opts.out_delim {
-                if print_delim {
-                    crash_if_err!(1, out.write_all(delim.as_bytes()));
-                }
-                print_delim = true;
-            }
-
-            // write out from low to high
-            loop {
-                match buf_read.select(high - cur_pos + 1, Some(&mut out)) {
-                    NewlineFound => continue 'newline,
-                    Partial(len) => cur_pos += len,
-                    Complete(_) => {
-                        cur_pos = high + 1;
-                        break;
-                    }
-                    EndOfFile => {
-                        if cur_pos != low || low == high {
-                            crash_if_err!(1, out.write_all(&[newline_char]));
-                        }
-
-                        break 'newline;
-                    }
-                }
-            }
-        }
-
-        buf_read.consume_line();
-        crash_if_err!(1, out.write_all(&[newline_char]));
-    }
-
-    0
-}
-
-#[allow(clippy::cognitive_complexity)]
-fn cut_fields_delimiter<R: Read>(
-    reader: R,
-    ranges: &[Range],
-    delim: &str,
-    only_delimited: bool,
-    newline_char: u8,
-    out_delim: &str,
-) -> i32 {
     let mut buf_in = BufReader::new(reader);
-    let mut out = stdout();
+    let out = stdout();
+    let mut out = out.lock();
     let mut buffer = Vec::new();
+    let mut bounds = Vec::new();
 
-    'newline: loop {
+    loop {
         buffer.clear();
         match buf_in.read_until(newline_char, &mut buffer) {
             Ok(n) if n == 0 => break,
@@ -225,59 +164,44 @@ fn cut_fields_delimiter<R: Read>(
             _ => (),
         }
 
-        let line = &buffer[..];
-        let mut fields_pos = 1;
-        let mut low_idx = 0;
-        let mut delim_search = Searcher::new(line, delim.as_bytes()).peekable();
-        let mut print_delim = false;
-
-        if delim_search.peek().is_none() {
-            if !only_delimited {
-                crash_if_err!(1, out.write_all(line));
-                if line[line.len() - 1] != newline_char {
-                    crash_if_err!(1, out.write_all(&[newline_char]));
-                }
-            }
-
-            continue;
+        let line = match buffer.last() {
+            Some(&c) if c == newline_char => &buffer[..buffer.len() - 1],
+            _ => &buffer[..],
+        };
+        if characters || opts.no_split {
+            char_boundaries(line, &mut bounds);
         }
+        let mut print_delim = false;
 
         for &Range { low, high } in ranges.iter() {
-            if low - fields_pos > 0 {
-                low_idx = match delim_search.nth(low - fields_pos - 1) {
-                    Some((_, beyond_delim)) => beyond_delim,
-                    None => break,
-                };
+            let (start, end) = if characters {
+                let chars = bounds.len() - 1;
+                if low > chars {
+                    break;
+                }
+                (bounds[low - 1], bounds[min(high, chars)])
+            } else {
+                if low > line.len() {
+                    break;
+                }
+                let end = min(high, line.len());
+                if opts.no_split {
+                    (char_start(&bounds, low - 1), char_start(&bounds, end))
+                } else {
+                    (low - 1, end)
+                }
+            };
+            if start >= end {
+                continue;
             }
 
-            for _ in 0..=high - low {
+            if let Some(ref delim) = opts.out_delim {
                 if print_delim {
-                    crash_if_err!(1, out.write_all(out_delim.as_bytes()));
-                }
-
-                match delim_search.next() {
-                    Some((high_idx, next_low_idx)) => {
-                        let segment = &line[low_idx..high_idx];
-
-                        crash_if_err!(1, out.write_all(segment));
-
-                        print_delim = true;
-
-                        low_idx = next_low_idx;
-                        fields_pos = high + 1;
-                    }
-                    None => {
-                        let segment = &line[low_idx..];
-
-                        crash_if_err!(1, out.write_all(segment));
-
-                        if line[line.len() - 1] == newline_char {
-                            continue 'newline;
-                        }
-                        break;
-                    }
+                    crash_if_err!(1, out.write_all(delim.as_bytes()));
                 }
+                print_delim = true;
             }
+            crash_if_err!(1, out.write_all(&line[start..end]));
         }
 
         crash_if_err!(1, out.write_all(&[newline_char]));
@@ -286,25 +210,51 @@ fn cut_fields_delimiter<R: Read>(
     0
 }
 
-#[allow(clippy::cognitive_complexity)]
-fn cut_fields<R: Read>(reader: R, ranges: &[Range], opts: &FieldOptions) -> i32 {
-    let newline_char = if opts.zero_terminated { b'\0' } else { b'\n' };
-    if let Some(ref o_delim) = opts.out_delimiter {
-        return cut_fields_delimiter(
-            reader,
-            ranges,
-            &opts.delimiter,
-            opts.only_delimited,
-            newline_char,
-            o_delim,
-        );
+/// Fill `bounds` with the byte offset of each character of `line`, followed by
+/// the length of the line. Bytes that are not valid UTF-8 count as one
+/// character each, so that they are passed through unchanged.
+fn char_boundaries(line: &[u8], bounds: &mut Vec<usize>) {
+    bounds.clear();
+    let mut offset = 0;
+    while offset < line.len() {
+        let (valid, invalid) = match str::from_utf8(&line[offset..]) {
+            Ok(_) => (line.len() - offset, 0),
+            Err(e) => (
+                e.valid_up_to(),
+                e.error_len().unwrap_or(line.len() - offset - e.valid_up_to()),
+            ),
+        };
+        // the prefix was just validated
+        let text = str::from_utf8(&line[offset..offset + valid]).unwrap();
+        bounds.extend(text.char_indices().map(|(i, _)| offset + i));
+        offset += valid;
+        bounds.extend(offset..offset + invalid);
+        offset += invalid;
     }
+    bounds.push(line.len());
+}
 
+/// Move the byte offset `pos` back to the start of the character containing it.
+fn char_start(bounds: &[usize], pos: usize) -> usize {
+    match bounds.binary_search(&pos) {
+        Ok(_) => pos,
+        Err(i) => bounds[i - 1],
+    }
+}
+
+fn cut_fields<R: Read>(reader: R, ranges: &[Range], opts: &FieldOptions) -> i32 {
+    let newline_char = if opts.zero_terminated { b'\0' } else { b'\n' };
+    let delim = opts.delimiter.as_bytes();
+    let out_delim = opts
+        .out_delimiter
+        .as_ref()
+        .map_or(delim, |out_delim| out_delim.as_bytes());
     let mut buf_in = BufReader::new(reader);
-    let mut out = stdout();
+    let out = stdout();
+    let mut out = BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, out.lock());
     let mut buffer = Vec::new();
 
-    'newline: loop {
+    loop {
         buffer.clear();
         match buf_in.read_until(newline_char, &mut buffer) {
             Ok(n) if n == 0 => break,
@@ -316,64 +266,90 @@ fn cut_fields<R: Read>(reader: R, ranges: &[Range], opts: &FieldOptions) -> i32
             _ => (),
         }
 
-        let line = &buffer[..];
-        let mut fields_pos = 1;
-        let mut low_idx = 0;
-        let mut delim_search = Searcher::new(line, opts.delimiter.as_bytes()).peekable();
-        let mut print_delim = false;
+        let line = match buffer.last() {
+            Some(&c) if c == newline_char => &buffer[..buffer.len() - 1],
+            _ => &buffer[..],
+        };
+        let mut delim_search = Searcher::new(line, delim).peekable();
 
         if delim_search.peek().is_none() {
             if !opts.only_delimited {
-                crash_if_err!(1, out.write_all(line));
-                if line[line.len() - 1] != newline_char {
-                    crash_if_err!(1, out.write_all(&[newline_char]));
-                }
+                crash_if_err!(
+                    1,
+                    write_all_vectored(&mut out, &[IoSlice::new(line), IoSlice::new(&[newline_char])])
+                );
             }
 
             continue;
         }
 
-        for &Range { low, high } in ranges.iter() {
-            if low - fields_pos > 0 {
-                low_idx = match delim_search.nth(low - fields_pos - 1) {
-                    Some((_, beyond_delim)) => beyond_delim,
-                    None => break,
-                };
-            }
+        let newline = [newline_char];
+        let mut slices = Vec::with_capacity(2 * ranges.len() + 1);
+        let mut ranges_iter = ranges.iter().peekable();
+        let mut field = 1;
+        let mut field_start = 0;
 
-            if print_delim && low_idx >= opts.delimiter.as_bytes().len() {
-                low_idx -= opts.delimiter.as_bytes().len();
+        loop {
+            while ranges_iter.peek().map_or(false, |range| range.high < field) {
+                ranges_iter.next();
             }
+            let range = match ranges_iter.peek() {
+                Some(range) => range,
+                None => break,
+            };
 
-            match delim_search.nth(high - low) {
-                Some((high_idx, next_low_idx)) => {
-                    let segment = &line[low_idx..high_idx];
-
-                    crash_if_err!(1, out.write_all(segment));
-
-                    print_delim = true;
-                    low_idx = next_low_idx;
-                    fields_pos = high + 1;
+            // the rest of the line can be copied as is when it is all selected
+            // and the delimiters don't need to be replaced
+            if field >= range.low && range.is_open_ended() && out_delim == delim {
+                if !slices.is_empty() {
+                    slices.push(IoSlice::new(out_delim));
                 }
-                None => {
-                    let segment = &line[low_idx..line.len()];
-
-                    crash_if_err!(1, out.write_all(segment));
+                slices.push(IoSlice::new(&line[field_start..]));
+                break;
+            }
 
-                    if line[line.len() - 1] == newline_char {
-                        continue 'newline;
-                    }
-                    break;
+            let (field_end, next_start) = match delim_search.next() {
+                Some((field_end, next_start)) => (field_end, Some(next_start)),
+                None => (line.len(), None),
+            };
+            if field >= range.low {
+                if !slices.is_empty() {
+                    slices.push(IoSlice::new(out_delim));
+                }
+                slices.push(IoSlice::new(&line[field_start..field_end]));
+            }
+            match next_start {
+                Some(next_start) => {
+                    field_start = next_start;
+                    field += 1;
                 }
+                None => break,
             }
         }
 
-        crash_if_err!(1, out.write_all(&[newline_char]));
+        slices.push(IoSlice::new(&newline));
+        crash_if_err!(1, write_all_vectored(&mut out, &slices));
     }
 
+    crash_if_err!(1, out.flush());
     0
 }
 
+/// Write all of `slices`, finishing with plain writes if the vectored write
+/// was only partial.
+fn write_all_vectored<W: Write>(out: &mut W, slices: &[IoSlice]) -> io::Result<()> {
+    let mut written = out.write_vectored(slices)?;
+    for slice in slices {
+        if written >= slice.len() {
+            written -= slice.len();
+        } else {
+            out.write_all(&slice[written..])?;
+            written = 0;
+        }
+    }
+    Ok(())
+}
+
 fn cut_files(mut filenames: Vec<String>, mode: Mode) -> i32 {
     let mut stdin_read = false;
     let mut exit_code = 0;
@@ -389,8 +365,8 @@ fn cut_files(mut filenames: Vec<String>, mode: Mode) -> i32 {
             }
 
             exit_code |= match mode {
-                Mode::Bytes(ref ranges, ref opts) => cut_bytes(stdin(), ranges, opts),
-                Mode::Characters(ref ranges, ref opts) => cut_bytes(stdin(), ranges, opts),
+                Mode::Bytes(ref ranges, ref opts) => cut_bytes(stdin(), ranges, false, opts),
+                Mode::Characters(ref ranges, ref opts) => cut_bytes(stdin(), ranges, true, opts),
                 Mode::Fields(ref ranges, ref opts) => cut_fields(stdin(), ranges, opts),
             };
 
@@ -412,8 +388,8 @@ fn cut_files(mut filenames: Vec<String>, mode: Mode) -> i32 {
             };
 
             exit_code |= match mode {
-                Mode::Bytes(ref ranges, ref opts) => cut_bytes(file, ranges, opts),
-                Mode::Characters(ref ranges, ref opts) => cut_bytes(file, ranges, opts),
+                Mode::Bytes(ref ranges, ref opts) => cut_bytes(file, ranges, false, opts),
+                Mode::Characters(ref ranges, ref opts) => cut_bytes(file, ranges, true, opts),
                 Mode::Fields(ref ranges, ref opts) => cut_fields(file, ranges, opts),
             };
         }
@@ -425,18 +401,16 @@ fn cut_files(mut filenames: Vec<String>, mode: Mode) -> i32 {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = app!(SYNTAX, SUMMARY, LONG_HELP)
-        .optopt("b", "bytes", "filter byte columns from the input source", "sequence")
-        .optopt("c", "characters", "alias for character mode", "sequence")
-        .optopt("d", "delimiter", "specify the delimiter character that separates fields in the input source. Defaults to Tab.", "delimiter")
-        .optopt("f", "fields", "filter field columns from the input source", "sequence")
-        .optflag("n", "", "legacy option - has no effect.")
-        .optflag("", "complement", "invert the filter - instead of displaying only the filtered columns, display all but those columns")
-        .optflag("s", "only-delimited", "in field mode, only print lines which contain the delimiter")
-        .optflag("z", "zero-terminated", "instead of filtering columns based on line, filter columns based on \\0 (NULL character)")
-        .optopt("", "output-delimiter", "in field mode, replace the delimiter in output lines with this option's argument", "new delimiter")
-        .parse(args);
+    let matches = uu_app().parse(args);
     let complement = matches.opt_present("complement");
+    // like GNU cut, an empty output delimiter means the NUL character
+    let out_delim = matches.opt_str("output-delimiter").map(|s| {
+        if s.is_empty() {
+            "\0".to_owned()
+        } else {
+            s
+        }
+    });
 
     let mode_parse = match (
         matches.opt_str("bytes"),
@@ -448,7 +422,8 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 Mode::Bytes(
                     ranges,
                     Options {
-                        out_delim: matches.opt_str("output-delimiter"),
+                        out_delim: out_delim.clone(),
+                        no_split: matches.opt_present("n"),
                         zero_terminated: matches.opt_present("zero-terminated"),
                     },
                 )
@@ -459,7 +434,8 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 Mode::Characters(
                     ranges,
                     Options {
-                        out_delim: matches.opt_str("output-delimiter"),
+                        out_delim: out_delim.clone(),
+                        no_split: false,
                         zero_terminated: matches.opt_present("zero-terminated"),
                     },
                 )
@@ -467,16 +443,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         }
         (None, None, Some(field_ranges)) => {
             list_to_ranges(&field_ranges[..], complement).and_then(|ranges| {
-                let out_delim = match matches.opt_str("output-delimiter") {
-                    Some(s) => {
-                        if s.is_empty() {
-                            Some("\0".to_owned())
-                        } else {
-                            Some(s)
-                        }
-                    }
-                    None => None,
-                };
+                let out_delim = out_delim.clone();
 
                 let only_delimited = matches.opt_present("only-delimited");
                 let zero_terminated = matches.opt_present("zero-terminated");
@@ -555,3 +522,17 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         }
     }
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    app.optopt("b", "bytes", "filter byte columns from the input source", "sequence")
+        .optopt("c", "characters", "alias for character mode", "sequence")
+        .optopt("d", "delimiter", "specify the delimiter character that separates fields in the input source. Defaults to Tab.", "delimiter")
+        .optopt("f", "fields", "filter field columns from the input source", "sequence")
+        .optflag("n", "", "with -b: don't split multibyte characters")
+        .optflag("", "complement", "invert the filter - instead of displaying only the filtered columns, display all but those columns")
+        .optflag("s", "only-delimited", "in field mode, only print lines which contain the delimiter")
+        .optflag("z", "zero-terminated", "instead of filtering columns based on line, filter columns based on \\0 (NULL character)")
+        .optopt("", "output-delimiter", "use STRING as the output delimiter; the default is to use the input delimiter in field mode, and nothing between byte or character ranges", "STRING");
+    app
+}
@@ -5,6 +5,8 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
+use memchr::memchr;
+
 #[derive(Clone)]
 pub struct Searcher<'a> {
     haystack: &'a [u8],
@@ -26,27 +28,23 @@ impl<'a> Iterator for Searcher<'a> {
     type Item = (usize, usize);
 
     fn next(&mut self) -> Option<(usize, usize)> {
-        if self.needle.len() == 1 {
-            for offset in self.position..self.haystack.len() {
-                if self.haystack[offset] == self.needle[0] {
-                    self.position = offset + 1;
-                    return Some((offset, offset + 1));
-                }
-            }
-
-            self.position = self.haystack.len();
-            return None;
-        }
-
+        // look for the first byte of the needle with memchr, and only compare
+        // the rest of a multibyte needle at the candidates
         while self.position + self.needle.len() <= self.haystack.len() {
-            if &self.haystack[self.position..self.position + self.needle.len()] == self.needle {
-                let match_pos = self.position;
-                self.position += self.needle.len();
-                return Some((match_pos, match_pos + self.needle.len()));
-            } else {
-                self.position += 1;
+            let match_pos = match memchr(self.needle[0], &self.haystack[self.position..]) {
+                Some(offset) => self.position + offset,
+                None => break,
+            };
+            let match_end = match_pos + self.needle.len();
+            if match_end <= self.haystack.len() && &self.haystack[match_pos..match_end] == self.needle
+            {
+                self.position = match_end;
+                return Some((match_pos, match_end));
             }
+            self.position = match_pos + 1;
         }
+
+        self.position = self.haystack.len();
         None
     }
 }
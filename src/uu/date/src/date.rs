@@ -12,15 +12,23 @@
 #[macro_use]
 extern crate uucore;
 
-use chrono::{DateTime, FixedOffset, Local, Offset, Utc};
+mod strftime;
+
+use chrono::{DateTime, FixedOffset, Local, Offset, TimeZone, Utc};
 #[cfg(windows)]
 use chrono::{Datelike, Timelike};
 use clap::{App, Arg};
 #[cfg(all(unix, not(target_os = "macos")))]
 use libc::{clock_settime, timespec, CLOCK_REALTIME};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use strftime::{strftime, Locale};
+use uucore::error_message;
+use uucore::i18n::{self, Category};
+use uucore::parse_datetime::{parse_datetime_at_date_debug, time_zone_prefix};
 #[cfg(windows)]
 use winapi::{
     shared::minwindef::WORD,
@@ -32,11 +40,12 @@ const DATE: &str = "date";
 const HOURS: &str = "hours";
 const MINUTES: &str = "minutes";
 const SECONDS: &str = "seconds";
-const HOUR: &str = "hour";
-const MINUTE: &str = "minute";
-const SECOND: &str = "second";
 const NS: &str = "ns";
 
+/// Precisions accepted by --iso-8601 and --rfc-3339, in GNU's order.
+const ISO_8601_PRECISIONS: &[&str] = &[HOURS, MINUTES, DATE, SECONDS, NS];
+const RFC_3339_PRECISIONS: &[&str] = &[DATE, SECONDS, NS];
+
 const NAME: &str = "date";
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const ABOUT: &str = "print or set the system date and time";
@@ -77,6 +86,7 @@ static OPT_SET_HELP_STRING: &str = "set time described by STRING (not available
 /// Settings for this program, parsed from the command line
 struct Settings {
     utc: bool,
+    debug: bool,
     format: Format,
     date_source: DateSource,
     set_to: Option<DateTime<FixedOffset>>,
@@ -96,6 +106,7 @@ enum DateSource {
     Now,
     Custom(String),
     File(PathBuf),
+    Reference(PathBuf),
 }
 
 enum Iso8601Format {
@@ -109,12 +120,12 @@ enum Iso8601Format {
 impl<'a> From<&'a str> for Iso8601Format {
     fn from(s: &str) -> Self {
         match s {
-            HOURS | HOUR => Iso8601Format::Hours,
-            MINUTES | MINUTE => Iso8601Format::Minutes,
-            SECONDS | SECOND => Iso8601Format::Seconds,
+            HOURS => Iso8601Format::Hours,
+            MINUTES => Iso8601Format::Minutes,
+            SECONDS => Iso8601Format::Seconds,
             NS => Iso8601Format::Ns,
             DATE => Iso8601Format::Date,
-            // Should be caught by clap
+            // Should be caught by match_precision
             _ => panic!("Invalid format: {}", s),
         }
     }
@@ -130,9 +141,9 @@ impl<'a> From<&'a str> for Rfc3339Format {
     fn from(s: &str) -> Self {
         match s {
             DATE => Rfc3339Format::Date,
-            SECONDS | SECOND => Rfc3339Format::Seconds,
+            SECONDS => Rfc3339Format::Seconds,
             NS => Rfc3339Format::Ns,
-            // Should be caught by clap
+            // Should be caught by match_precision
             _ => panic!("Invalid format: {}", s),
         }
     }
@@ -144,10 +155,187 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
  {0} [OPTION]... [MMDDhhmm[[CC]YY][.ss]]",
         NAME
     );
-    let matches = App::new(executable!())
+    let about = i18n::tr(ABOUT);
+    let app = uu_app().about(&about[..]).usage(&syntax[..]);
+    let matches = uucore::help::get_matches_from(app, args);
+
+    let formats = [OPT_FORMAT, OPT_ISO_8601, OPT_RFC_EMAIL, OPT_RFC_3339];
+    if formats.iter().filter(|&&f| matches.is_present(f)).count() > 1 {
+        eprintln!("date: {}", i18n::tr("multiple output formats specified"));
+        return 1;
+    }
+
+    let format = if let Some(form) = matches.value_of(OPT_FORMAT) {
+        let form = form[1..].into();
+        Format::Custom(form)
+    } else if matches.is_present(OPT_ISO_8601) {
+        let fmt = matches.value_of(OPT_ISO_8601).unwrap_or(DATE);
+        match match_precision(OPT_ISO_8601, fmt, ISO_8601_PRECISIONS) {
+            Ok(fmt) => Format::Iso8601(fmt.into()),
+            Err(e) => {
+                eprintln!("date: {}", e);
+                return 1;
+            }
+        }
+    } else if matches.is_present(OPT_RFC_EMAIL) {
+        Format::Rfc5322
+    } else if let Some(fmt) = matches.value_of(OPT_RFC_3339) {
+        match match_precision(OPT_RFC_3339, fmt, RFC_3339_PRECISIONS) {
+            Ok(fmt) => Format::Rfc3339(fmt.into()),
+            Err(e) => {
+                eprintln!("date: {}", e);
+                return 1;
+            }
+        }
+    } else {
+        Format::Default
+    };
+
+    let sources = [OPT_DATE, OPT_FILE, OPT_REFERENCE];
+    let sources = sources.iter().filter(|&&s| matches.is_present(s)).count();
+    if sources > 1 {
+        eprintln!(
+            "date: {}",
+            i18n::tr("the options to specify dates for printing are mutually exclusive")
+        );
+        return 1;
+    }
+    if sources > 0 && matches.is_present(OPT_SET) {
+        eprintln!(
+            "date: {}",
+            i18n::tr("the options to print and set the time may not be used together")
+        );
+        return 1;
+    }
+
+    let date_source = if let Some(date) = matches.value_of(OPT_DATE) {
+        DateSource::Custom(date.into())
+    } else if let Some(file) = matches.value_of(OPT_FILE) {
+        DateSource::File(file.into())
+    } else if let Some(file) = matches.value_of(OPT_REFERENCE) {
+        DateSource::Reference(file.into())
+    } else {
+        DateSource::Now
+    };
+
+    let utc = matches.is_present(OPT_UNIVERSAL);
+    let debug = matches.is_present(OPT_DEBUG);
+    let set_to = match matches
+        .value_of(OPT_SET)
+        .map(|date| parse_date(date, utc, debug))
+    {
+        None => None,
+        Some(Err(e)) => {
+            eprintln!("date: {}", e);
+            return 1;
+        }
+        Some(Ok(date)) => Some(date),
+    };
+
+    let settings = Settings {
+        utc,
+        debug,
+        format,
+        date_source,
+        set_to,
+    };
+
+    if let Some(date) = settings.set_to {
+        // All set time functions expect UTC datetimes.
+        let status = set_system_datetime(date.with_timezone(&Utc));
+        if status != 0 {
+            return status;
+        }
+    }
+
+    // Get the current time, either in the local time zone or UTC.
+    let now: DateTime<FixedOffset> = if settings.utc {
+        let now = Utc::now();
+        now.with_timezone(&now.offset().fix())
+    } else {
+        let now = Local::now();
+        now.with_timezone(now.offset())
+    };
+
+    // Iterate over all dates - whether it's a single date or a file.
+    let dates: Box<dyn Iterator<Item = _>> = match settings.date_source {
+        DateSource::Custom(ref input) => {
+            let date = parse_date(input, settings.utc, settings.debug);
+            let iter = std::iter::once(date);
+            Box::new(iter)
+        }
+        DateSource::File(ref path) => {
+            let reader: Box<dyn BufRead> = if path == Path::new("-") {
+                Box::new(BufReader::new(io::stdin()))
+            } else {
+                match File::open(path) {
+                    Ok(file) => Box::new(BufReader::new(file)),
+                    Err(e) => {
+                        eprintln!("date: {}: {}", path.display(), error_message(&e));
+                        return 1;
+                    }
+                }
+            };
+            let (utc, debug) = (settings.utc, settings.debug);
+            let path = path.clone();
+            let iter = reader
+                .split(b'\n')
+                // stop at the first read error
+                .scan(false, |failed, line| {
+                    if *failed {
+                        return None;
+                    }
+                    *failed = line.is_err();
+                    Some(line)
+                })
+                .map(move |line| match line {
+                    Ok(line) => parse_date(String::from_utf8_lossy(&line), utc, debug),
+                    Err(e) => Err(format!("{}: {}", path.display(), error_message(&e))),
+                });
+            Box::new(iter)
+        }
+        DateSource::Reference(ref path) => {
+            let date = reference_date(path, settings.utc);
+            let iter = std::iter::once(date);
+            Box::new(iter)
+        }
+        DateSource::Now => {
+            // After setting the clock, show the time it was set to.
+            let iter = std::iter::once(Ok(settings.set_to.unwrap_or(now)));
+            Box::new(iter)
+        }
+    };
+
+    let format_string = make_format_string(&settings);
+    let locale = match settings.format {
+        Format::Rfc5322 => Locale::C,
+        _ => Locale::Current,
+    };
+
+    // Format all the dates
+    let mut status = 0;
+    for date in dates {
+        match date {
+            Ok(date) => {
+                if settings.debug {
+                    eprintln!("date: output format: '{}'", format_string);
+                }
+                let zone = zone_name(&date, settings.utc);
+                println!("{}", strftime(&format_string, &date, &zone, locale));
+            }
+            Err(e) => {
+                eprintln!("date: {}", e);
+                status = 1;
+            }
+        }
+    }
+    status
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&syntax[..])
         .arg(
             Arg::with_name(OPT_DATE)
                 .short("d")
@@ -160,13 +348,17 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .short("f")
                 .long(OPT_FILE)
                 .takes_value(true)
+                .value_name("DATEFILE")
                 .help("like --date; once for each line of DATEFILE"),
         )
         .arg(
             Arg::with_name(OPT_ISO_8601)
                 .short("I")
                 .long(OPT_ISO_8601)
+                .value_name("FMT")
                 .takes_value(true)
+                .require_equals(true)
+                .min_values(0)
                 .help(ISO_8601_HELP_STRING),
         )
         .arg(
@@ -178,6 +370,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         .arg(
             Arg::with_name(OPT_RFC_3339)
                 .long(OPT_RFC_3339)
+                .value_name("FMT")
                 .takes_value(true)
                 .help(RFC_3339_HELP_STRING),
         )
@@ -208,136 +401,188 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .help("print or set Coordinated Universal Time (UTC)"),
         )
         .arg(Arg::with_name(OPT_FORMAT).multiple(true))
-        .get_matches_from(args);
-
-    let format = if let Some(form) = matches.value_of(OPT_FORMAT) {
-        let form = form[1..].into();
-        Format::Custom(form)
-    } else if let Some(fmt) = matches
-        .values_of(OPT_ISO_8601)
-        .map(|mut iter| iter.next().unwrap_or(DATE).into())
-    {
-        Format::Iso8601(fmt)
-    } else if matches.is_present(OPT_RFC_EMAIL) {
-        Format::Rfc5322
-    } else if let Some(fmt) = matches.value_of(OPT_RFC_3339).map(Into::into) {
-        Format::Rfc3339(fmt)
-    } else {
-        Format::Default
-    };
-
-    let date_source = if let Some(date) = matches.value_of(OPT_DATE) {
-        DateSource::Custom(date.into())
-    } else if let Some(file) = matches.value_of(OPT_FILE) {
-        DateSource::File(file.into())
-    } else {
-        DateSource::Now
-    };
-
-    let set_to = match matches.value_of(OPT_SET).map(parse_date) {
-        None => None,
-        Some(Err((input, _err))) => {
-            eprintln!("date: invalid date '{}'", input);
-            return 1;
-        }
-        Some(Ok(date)) => Some(date),
-    };
+}
 
-    let settings = Settings {
-        utc: matches.is_present(OPT_UNIVERSAL),
-        format,
-        date_source,
-        set_to,
+/// Find the precision named by `arg`, which may be abbreviated to any
+/// unambiguous prefix.
+fn match_precision(
+    option: &str,
+    arg: &str,
+    valid: &[&'static str],
+) -> Result<&'static str, String> {
+    if let Some(&name) = valid.iter().find(|&&name| name == arg) {
+        return Ok(name);
+    }
+    let matching: Vec<&str> = valid
+        .iter()
+        .cloned()
+        .filter(|name| name.starts_with(arg))
+        .collect();
+    let problem = match matching.len() {
+        0 => "invalid",
+        1 => return Ok(matching[0]),
+        _ => "ambiguous",
     };
-
-    if let Some(date) = settings.set_to {
-        // All set time functions expect UTC datetimes.
-        let date: DateTime<Utc> = if settings.utc {
-            date.with_timezone(&Utc)
-        } else {
-            date.into()
-        };
-
-        return set_system_datetime(date);
-    } else {
-        // Declare a file here because it needs to outlive the `dates` iterator.
-        let file: File;
-
-        // Get the current time, either in the local time zone or UTC.
-        let now: DateTime<FixedOffset> = if settings.utc {
-            let now = Utc::now();
-            now.with_timezone(&now.offset().fix())
-        } else {
-            let now = Local::now();
-            now.with_timezone(now.offset())
-        };
-
-        // Iterate over all dates - whether it's a single date or a file.
-        let dates: Box<dyn Iterator<Item = _>> = match settings.date_source {
-            DateSource::Custom(ref input) => {
-                let date = parse_date(input.clone());
-                let iter = std::iter::once(date);
-                Box::new(iter)
-            }
-            DateSource::File(ref path) => {
-                file = File::open(path).unwrap();
-                let lines = BufReader::new(file).lines();
-                let iter = lines.filter_map(Result::ok).map(parse_date);
-                Box::new(iter)
-            }
-            DateSource::Now => {
-                let iter = std::iter::once(Ok(now));
-                Box::new(iter)
-            }
-        };
-
-        let format_string = make_format_string(&settings);
-
-        // Format all the dates
-        for date in dates {
-            match date {
-                Ok(date) => {
-                    let formatted = date.format(format_string);
-                    println!("{}", formatted);
-                }
-                Err((input, _err)) => {
-                    println!("date: invalid date '{}'", input);
-                }
-            }
-        }
+    let mut msg = format!(
+        "{} argument '{}' for '--{}'\nValid arguments are:",
+        problem, arg, option
+    );
+    for name in valid {
+        msg.push_str(&format!("\n  - '{}'", name));
     }
-
-    0
+    msg.push_str(&format!(
+        "\nTry '{} --help' for more information.",
+        executable!()
+    ));
+    Err(msg)
 }
 
 /// Return the appropriate format string for the given settings.
-fn make_format_string(settings: &Settings) -> &str {
-    match settings.format {
+fn make_format_string(settings: &Settings) -> Cow<'_, str> {
+    Cow::Borrowed(match settings.format {
         Format::Iso8601(ref fmt) => match *fmt {
             Iso8601Format::Date => "%F",
             Iso8601Format::Hours => "%FT%H%:z",
             Iso8601Format::Minutes => "%FT%H:%M%:z",
             Iso8601Format::Seconds => "%FT%T%:z",
-            Iso8601Format::Ns => "%FT%T,%f%:z",
+            Iso8601Format::Ns => "%FT%T,%N%:z",
         },
         Format::Rfc5322 => "%a, %d %h %Y %T %z",
         Format::Rfc3339(ref fmt) => match *fmt {
             Rfc3339Format::Date => "%F",
             Rfc3339Format::Seconds => "%F %T%:z",
-            Rfc3339Format::Ns => "%F %T.%f%:z",
+            Rfc3339Format::Ns => "%F %T.%N%:z",
         },
         Format::Custom(ref fmt) => fmt,
-        Format::Default => "%c",
-    }
+        Format::Default => return i18n::tr_in(Category::Time, "%a %b %e %H:%M:%S %Z %Y"),
+    })
 }
 
-/// Parse a `String` into a `DateTime`.
-/// If it fails, return a tuple of the `String` along with its `ParseError`.
-fn parse_date<S: AsRef<str> + Clone>(
+/// Parse a date string as GNU date does, and convert the result to the time
+/// zone it will be displayed in. With `debug`, explain the parse on stderr.
+fn parse_date<S: AsRef<str>>(
     s: S,
-) -> Result<DateTime<FixedOffset>, (String, chrono::format::ParseError)> {
-    // TODO: The GNU date command can parse a wide variety of inputs.
-    s.as_ref().parse().map_err(|e| (s.as_ref().into(), e))
+    utc: bool,
+    debug: bool,
+) -> Result<DateTime<FixedOffset>, String> {
+    let show = |msg: String| {
+        if debug {
+            eprintln!("date: {}", msg);
+        }
+    };
+    let s = s.as_ref();
+    let date = match time_zone_prefix(s) {
+        // a TZ="ZONE" prefix says which zone local times are in
+        Some(zone) => with_time_zone(&zone, || {
+            parse_datetime_at_date_debug(Local::now(), s, show)
+        })?,
+        None if utc => parse_datetime_at_date_debug(Utc::now(), s, show)?,
+        None => parse_datetime_at_date_debug(Local::now(), s, show)?,
+    };
+    let date = to_output_zone(date, utc);
+    if debug {
+        eprintln!(
+            "date: final: {}.{:09} (epoch-seconds)",
+            date.timestamp(),
+            date.timestamp_subsec_nanos()
+        );
+        eprintln!(
+            "date: final: {} (UTC)",
+            date.naive_utc().format("(Y-M-D) %Y-%m-%d %H:%M:%S")
+        );
+        eprintln!(
+            "date: final: {} (UTC{})",
+            date.naive_local().format("(Y-M-D) %Y-%m-%d %H:%M:%S"),
+            date.offset()
+        );
+    }
+    Ok(date)
+}
+
+/// Run `f` with the local time zone set to `zone`, as if it was given in
+/// the `TZ` environment variable, and restore the local time zone after.
+fn with_time_zone<T, F: FnOnce() -> T>(zone: &str, f: F) -> T {
+    let previous = env::var_os("TZ");
+    env::set_var("TZ", zone);
+    reload_time_zone();
+    let result = f();
+    match previous {
+        Some(previous) => env::set_var("TZ", previous),
+        None => env::remove_var("TZ"),
+    }
+    reload_time_zone();
+    result
+}
+
+/// Make the C library read `TZ` again, loading the system time zone data.
+#[cfg(unix)]
+fn reload_time_zone() {
+    unsafe { libc::tzset() };
+}
+
+#[cfg(not(unix))]
+fn reload_time_zone() {}
+
+/// The last modification time of `path`.
+fn reference_date(path: &Path, utc: bool) -> Result<DateTime<FixedOffset>, String> {
+    match fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        Ok(time) => Ok(to_output_zone(DateTime::<Utc>::from(time), utc)),
+        Err(e) => Err(format!("{}: {}", path.display(), error_message(&e))),
+    }
+}
+
+/// Convert `date` to UTC or to the local time zone, for display.
+fn to_output_zone<Tz: TimeZone>(date: DateTime<Tz>, utc: bool) -> DateTime<FixedOffset> {
+    if utc {
+        date.with_timezone(&Utc.fix())
+    } else {
+        let date = date.with_timezone(&Local);
+        date.with_timezone(date.offset())
+    }
+}
+
+/// The abbreviation of the time zone `date` is shown in, for `%Z`.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+fn zone_name(date: &DateTime<FixedOffset>, utc: bool) -> String {
+    if utc {
+        return "UTC".to_owned();
+    }
+    let time = date.timestamp() as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::localtime_r(&time, &mut tm) };
+    if result.is_null() || tm.tm_zone.is_null() {
+        return date.offset().to_string();
+    }
+    unsafe { std::ffi::CStr::from_ptr(tm.tm_zone) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The abbreviation of the time zone `date` is shown in, for `%Z`. Without
+/// one, the offset is used.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+)))]
+fn zone_name(date: &DateTime<FixedOffset>, utc: bool) -> String {
+    if utc {
+        "UTC".to_owned()
+    } else {
+        date.offset().to_string()
+    }
 }
 
 #[cfg(not(any(unix, windows)))]
@@ -366,9 +611,13 @@ fn set_system_datetime(date: DateTime<Utc>) -> i32 {
     let result = unsafe { clock_settime(CLOCK_REALTIME, &timespec) };
 
     if result != 0 {
-        let error = std::io::Error::last_os_error();
-        eprintln!("date: cannot set date: {}", error);
-        error.raw_os_error().unwrap()
+        let error = io::Error::last_os_error();
+        eprintln!(
+            "date: {}: {}",
+            i18n::tr("cannot set date"),
+            error_message(&error)
+        );
+        1
     } else {
         0
     }
@@ -396,9 +645,13 @@ fn set_system_datetime(date: DateTime<Utc>) -> i32 {
     let result = unsafe { SetSystemTime(&system_time) };
 
     if result == 0 {
-        let error = std::io::Error::last_os_error();
-        eprintln!("date: cannot set date: {}", error);
-        error.raw_os_error().unwrap()
+        let error = io::Error::last_os_error();
+        eprintln!(
+            "date: {}: {}",
+            i18n::tr("cannot set date"),
+            error_message(&error)
+        );
+        1
     } else {
         0
     }
@@ -0,0 +1,403 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (format) strftime
+
+//! GNU-compatible `strftime`, including the `%N`, `%q` and `%:z` extensions,
+//! the `-`, `_`, `0`, `+`, `^` and `#` flags, and field widths.
+
+use chrono::{DateTime, Datelike, FixedOffset, Timelike};
+use std::borrow::Cow;
+use uucore::i18n::{self, Category};
+
+/// Whose names, and formats for `%c`, `%r`, `%x` and `%X`, to use.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Locale {
+    /// the "C" locale's, for formats that are read by programs
+    C,
+    /// those of the locale set for `LC_TIME`
+    Current,
+}
+
+impl Locale {
+    /// The locale's version of a format, or of `AM` or `PM`.
+    fn translate(self, s: &'static str) -> Cow<'static, str> {
+        match self {
+            Locale::C => Cow::Borrowed(s),
+            Locale::Current => i18n::tr_in(Category::Time, s),
+        }
+    }
+
+    fn day_name(self, day: usize, abbreviated: bool) -> Cow<'static, str> {
+        match self {
+            Locale::C if abbreviated => Cow::Borrowed(&i18n::DAYS[day][..3]),
+            Locale::C => Cow::Borrowed(i18n::DAYS[day]),
+            Locale::Current => i18n::day_name(day, abbreviated),
+        }
+    }
+
+    fn month_name(self, month0: usize, abbreviated: bool) -> Cow<'static, str> {
+        match self {
+            Locale::C if abbreviated => Cow::Borrowed(&i18n::MONTHS[month0][..3]),
+            Locale::C => Cow::Borrowed(i18n::MONTHS[month0]),
+            Locale::Current => i18n::month_name(month0, abbreviated),
+        }
+    }
+
+    /// `AM` or `PM`, or what the locale has instead.
+    fn meridiem(self, date: &DateTime<FixedOffset>) -> Cow<'static, str> {
+        self.translate(if date.hour() < 12 { "AM" } else { "PM" })
+    }
+}
+
+/// How a single conversion was modified.
+#[derive(Clone, Copy, Default)]
+struct Spec {
+    /// `-`, `_`, `0` or `+`: how to pad (the last one given wins)
+    pad: Option<char>,
+    /// `^`: convert to upper case
+    upper: bool,
+    /// `#`: use the opposite case where it makes sense
+    swap_case: bool,
+    width: Option<usize>,
+    /// `E` or `O`: an alternative representation, the same in the C locale
+    /// except for padding
+    modifier: Option<char>,
+    /// the number of colons in `%:z`, `%::z` and `%:::z`
+    colons: usize,
+}
+
+impl Spec {
+    /// The character to pad with, or `None` for no padding at all.
+    fn fill(&self, default: char) -> Option<char> {
+        match self.pad {
+            Some('-') => None,
+            Some('_') => Some(' '),
+            Some('0') | Some('+') => Some('0'),
+            _ => Some(default),
+        }
+    }
+}
+
+/// Format `date` according to `format`, using `zone` as the time zone
+/// abbreviation for `%Z`.
+pub fn strftime(format: &str, date: &DateTime<FixedOffset>, zone: &str, locale: Locale) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let (converted, len) = directive(&rest[start..], date, zone, locale);
+        out.push_str(&converted);
+        rest = &rest[start + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Expand the directive at the start of `s`, returning the expansion and
+/// the length of the directive.
+fn directive(s: &str, date: &DateTime<FixedOffset>, zone: &str, locale: Locale) -> (String, usize) {
+    let mut spec = Spec::default();
+    let mut chars = s.char_indices().skip(1).peekable();
+    while let Some(&(_, flag)) = chars.peek() {
+        match flag {
+            '-' | '_' | '0' | '+' => spec.pad = Some(flag),
+            '^' => spec.upper = true,
+            '#' => spec.swap_case = true,
+            _ => break,
+        }
+        chars.next();
+    }
+    let mut width = String::new();
+    while let Some(&(_, digit)) = chars.peek() {
+        if !digit.is_ascii_digit() {
+            break;
+        }
+        width.push(digit);
+        chars.next();
+    }
+    spec.width = width.parse().ok();
+    let mut first_colon = None;
+    while let Some(&(i, modifier)) = chars.peek() {
+        match modifier {
+            'E' | 'O' => spec.modifier = Some(modifier),
+            ':' => {
+                first_colon = first_colon.or(Some(i));
+                spec.colons += 1;
+            }
+            _ => break,
+        }
+        chars.next();
+    }
+    let (i, conversion) = match chars.next() {
+        Some(next) => next,
+        None => return (invalid(s, &spec), s.len()),
+    };
+    if let Some(colon) = first_colon {
+        if conversion != 'z' || spec.colons > 3 {
+            // colons only go with 'z': the directive ends at the first one
+            return (invalid(&s[..=colon], &spec), colon + 1);
+        }
+    }
+    let len = i + conversion.len_utf8();
+    let valid = match spec.modifier {
+        Some('E') => "cCnpPqrRstTuxXyYzZ".contains(conversion),
+        Some('O') => "bBCdegGhHIjklmMnNpPrRsStTuUVwWyzZ".contains(conversion),
+        _ => true,
+    };
+    let converted = match conversion {
+        _ if !valid => None,
+        '%' if len == 2 => Some("%".to_owned()),
+        // with flags or a width, what precedes the final '%' is kept as is
+        // and the '%' starts a new directive
+        '%' => return (invalid(&s[..i], &spec), i),
+        _ => convert(conversion, &spec, date, zone, locale),
+    };
+    match converted {
+        Some(converted) => (converted, len),
+        None => (invalid(&s[..len], &spec), len),
+    }
+}
+
+/// A directive that is not a valid conversion is copied, padded to the
+/// width.
+fn invalid(directive: &str, spec: &Spec) -> String {
+    text(directive, spec)
+}
+
+/// Expand the conversion `c`, or return `None` if it is unknown.
+fn convert(
+    c: char,
+    spec: &Spec,
+    date: &DateTime<FixedOffset>,
+    zone: &str,
+    locale: Locale,
+) -> Option<String> {
+    let hour12 = (date.hour() + 11) % 12 + 1;
+    let year = i64::from(date.year());
+    let iso_year = i64::from(date.iso_week().year());
+    let weekday = date.weekday().num_days_from_sunday() as usize;
+    let month = date.month0() as usize;
+    let converted = match c {
+        'a' => name(&locale.day_name(weekday, true), spec),
+        'A' => name(&locale.day_name(weekday, false), spec),
+        'b' | 'h' => name(&locale.month_name(month, true), spec),
+        'B' => name(&locale.month_name(month, false), spec),
+        'c' => compound(
+            &locale.translate("%a %b %e %H:%M:%S %Y"),
+            spec,
+            date,
+            zone,
+            locale,
+        ),
+        'C' => year_number(year.div_euclid(100), 2, spec),
+        'd' => number(date.day().into(), 2, '0', spec),
+        'D' => compound("%m/%d/%y", spec, date, zone, locale),
+        'e' => number(date.day().into(), 2, ' ', spec),
+        'F' => {
+            // the width applies to the year, which has at least four digits
+            let year_spec = match spec.width {
+                Some(width) => Spec {
+                    width: Some(width.saturating_sub(6).max(4)),
+                    pad: spec.pad,
+                    ..Spec::default()
+                },
+                None => Spec {
+                    width: Some(4),
+                    pad: spec.pad.or(Some('+')),
+                    ..Spec::default()
+                },
+            };
+            format!(
+                "{}-{}",
+                year_number(year, 4, &year_spec),
+                strftime("%m-%d", date, zone, locale)
+            )
+        }
+        'g' => number(iso_year.rem_euclid(100), 2, '0', spec),
+        'G' => year_number(iso_year, 4, spec),
+        'H' => number(date.hour().into(), 2, '0', spec),
+        'I' => number(hour12.into(), 2, '0', spec),
+        'j' => number(date.ordinal().into(), 3, '0', spec),
+        'k' => number(date.hour().into(), 2, ' ', spec),
+        'l' => number(hour12.into(), 2, ' ', spec),
+        'm' => number(date.month().into(), 2, '0', spec),
+        'M' => number(date.minute().into(), 2, '0', spec),
+        'n' => text("\n", spec),
+        'N' => nanoseconds(date.nanosecond(), spec),
+        'p' if spec.swap_case => text(&locale.meridiem(date).to_lowercase(), spec),
+        'p' => text(&locale.meridiem(date), spec),
+        'P' => {
+            let spec = Spec {
+                upper: false,
+                ..*spec
+            };
+            text(&locale.meridiem(date).to_lowercase(), &spec)
+        }
+        'q' => number((date.month0() / 3 + 1).into(), 1, '0', spec),
+        'r' => compound(&locale.translate("%I:%M:%S %p"), spec, date, zone, locale),
+        'R' => compound("%H:%M", spec, date, zone, locale),
+        's' => {
+            let spec = Spec {
+                modifier: None,
+                ..*spec
+            };
+            number(date.timestamp(), 1, '0', &spec)
+        }
+        'S' => number(date.second().into(), 2, '0', spec),
+        't' => text("\t", spec),
+        'T' => compound("%H:%M:%S", spec, date, zone, locale),
+        'u' => number(date.weekday().number_from_monday().into(), 1, '0', spec),
+        'U' => {
+            let week = (date.ordinal0() + 7 - date.weekday().num_days_from_sunday()) / 7;
+            number(week.into(), 2, '0', spec)
+        }
+        'V' => number(date.iso_week().week().into(), 2, '0', spec),
+        'w' => number(date.weekday().num_days_from_sunday().into(), 1, '0', spec),
+        'W' => {
+            let week = (date.ordinal0() + 7 - date.weekday().num_days_from_monday()) / 7;
+            number(week.into(), 2, '0', spec)
+        }
+        'x' => compound(&locale.translate("%m/%d/%y"), spec, date, zone, locale),
+        'X' => compound(&locale.translate("%H:%M:%S"), spec, date, zone, locale),
+        'y' => number(year.rem_euclid(100), 2, '0', spec),
+        'Y' => year_number(year, 4, spec),
+        'z' => offset(date.offset().local_minus_utc(), spec),
+        'Z' if spec.swap_case => text(
+            &zone.to_lowercase(),
+            &Spec {
+                upper: false,
+                ..*spec
+            },
+        ),
+        'Z' => text(zone, spec),
+        _ => return None,
+    };
+    Some(converted)
+}
+
+/// Pad `s` on the left to `width` with `fill`. Zeros go after a sign.
+fn pad(s: &str, width: Option<usize>, fill: Option<char>) -> String {
+    let len = s.chars().count();
+    let (width, fill) = match (width, fill) {
+        (Some(width), Some(fill)) if width > len => (width, fill),
+        _ => return s.to_owned(),
+    };
+    let mut padded = String::with_capacity(width);
+    if fill == '0' && (s.starts_with('-') || s.starts_with('+')) {
+        padded.push_str(&s[..1]);
+        padded.extend(std::iter::repeat('0').take(width - len));
+        padded.push_str(&s[1..]);
+    } else {
+        padded.extend(std::iter::repeat(fill).take(width - len));
+        padded.push_str(s);
+    }
+    padded
+}
+
+/// Some text, padded with spaces if a width is given.
+fn text(s: &str, spec: &Spec) -> String {
+    let s = if spec.upper {
+        s.to_uppercase()
+    } else {
+        s.to_owned()
+    };
+    pad(&s, spec.width, spec.fill(' '))
+}
+
+/// A day or month name. With `#`, names are shown in upper case.
+fn name(s: &str, spec: &Spec) -> String {
+    let spec = Spec {
+        upper: spec.upper || spec.swap_case,
+        ..*spec
+    };
+    text(s, &spec)
+}
+
+/// A number, padded to `width` digits with `default_pad` unless the flags
+/// say otherwise.
+fn number(value: i64, width: usize, default_pad: char, spec: &Spec) -> String {
+    if spec.modifier.is_some() && (spec.pad.is_none() || spec.pad == Some('_')) {
+        // alternative digits are padded as usual, then with spaces
+        let digits = pad(&value.to_string(), Some(width), Some(default_pad));
+        return pad(&digits, spec.width, Some(' '));
+    }
+    pad(
+        &value.to_string(),
+        Some(spec.width.unwrap_or(width)),
+        spec.fill(default_pad),
+    )
+}
+
+/// A year or century of `digits` digits. With `+`, a sign is shown if the
+/// number or the width is larger than that.
+fn year_number(value: i64, digits: u32, spec: &Spec) -> String {
+    let max = 10_i64.pow(digits) - 1;
+    let sign = spec.pad == Some('+')
+        && value >= 0
+        && (value > max || spec.width.map_or(false, |width| width > digits as usize));
+    if sign {
+        pad(&format!("+{}", value), spec.width, Some('0'))
+    } else {
+        number(value, digits as usize, '0', spec)
+    }
+}
+
+/// Nanoseconds, truncated to the width (nine digits by default). `-` with a
+/// width drops trailing zeros, `_` replaces them with spaces.
+fn nanoseconds(nanoseconds: u32, spec: &Spec) -> String {
+    let digits = format!("{:09}", nanoseconds % 1_000_000_000);
+    let width = spec.width.unwrap_or(9);
+    let digits = &digits[..width.min(9)];
+    let (digits, fill) = match spec.pad {
+        Some('-') if spec.width.is_some() => return digits.trim_end_matches('0').to_owned(),
+        Some('-') => return digits.to_owned(),
+        Some('_') => (digits.trim_end_matches('0'), ' '),
+        _ => (digits, '0'),
+    };
+    let mut padded = digits.to_owned();
+    padded.extend(std::iter::repeat(fill).take(width - digits.len()));
+    padded
+}
+
+/// A UTC offset: `+hhmm`, `+hh:mm` (`%:z`), `+hh:mm:ss` (`%::z`), or only as
+/// precise as needed (`%:::z`). A width pads the hours, after the sign.
+fn offset(seconds: i32, spec: &Spec) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    let (value, default_width, rest) = match spec.colons {
+        0 => (hours * 100 + minutes, 4, String::new()),
+        1 => (hours, 2, format!(":{:02}", minutes)),
+        2 => (hours, 2, format!(":{:02}:{:02}", minutes, seconds)),
+        _ if seconds != 0 => (hours, 2, format!(":{:02}:{:02}", minutes, seconds)),
+        _ if minutes != 0 => (hours, 2, format!(":{:02}", minutes)),
+        _ => (hours, 2, String::new()),
+    };
+    let width = spec
+        .width
+        .map_or(default_width, |width| width.saturating_sub(1 + rest.len()));
+    let hours = pad(&value.to_string(), Some(width), spec.fill('0'));
+    // spaces go before the sign
+    let digits = hours.trim_start_matches(' ');
+    format!(
+        "{}{}{}{}",
+        &hours[..hours.len() - digits.len()],
+        sign,
+        digits,
+        rest
+    )
+}
+
+/// A conversion defined in terms of others; a width applies to the whole.
+fn compound(
+    format: &str,
+    spec: &Spec,
+    date: &DateTime<FixedOffset>,
+    zone: &str,
+    locale: Locale,
+) -> String {
+    text(&strftime(format, date, zone, locale), spec)
+}
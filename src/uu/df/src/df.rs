@@ -6,7 +6,7 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) mountinfo mtab BLOCKSIZE getmntinfo fobj mptr noatime Iused overmounted
+// spell-checker:ignore (ToDO) iavail ipcent itotal iused pcent mountinfo mtab BLOCKSIZE getmntinfo fobj mptr noatime Iused overmounted
 // spell-checker:ignore (libc/fs) asyncreads asyncwrites autofs bavail bfree bsize charspare cifs debugfs devfs devpts ffree frsize fsid fstypename fusectl inode inodes iosize kernfs mntbufp mntfromname mntonname mqueue namemax pipefs smbfs statfs statvfs subfs syncreads syncwrites sysfs wcslen
 
 #[macro_use]
@@ -22,10 +22,15 @@ use winapi::um::fileapi::{
     GetVolumePathNamesForVolumeNameW, QueryDosDeviceW,
 };
 
-use number_prefix::NumberPrefix;
-use std::cell::Cell;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use uucore::human::{human_readable, BlockSize};
+use uucore::json;
 
 #[cfg(unix)]
 use std::ffi::CString;
@@ -58,8 +63,6 @@ use std::os::windows::ffi::OsStrExt;
 #[cfg(windows)]
 use std::os::windows::ffi::OsStringExt;
 #[cfg(windows)]
-use std::path::Path;
-#[cfg(windows)]
 use winapi::shared::minwindef::DWORD;
 #[cfg(windows)]
 use winapi::um::fileapi::GetDiskFreeSpaceW;
@@ -71,6 +74,16 @@ use winapi::um::winbase::DRIVE_REMOTE;
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static ABOUT: &str = "Show information about the file system on which each FILE resides,\n\
                       or all file systems by default.";
+static LONG_HELP: &str = "Display values are in units of the first available SIZE from --block-size,
+and the DF_BLOCK_SIZE, BLOCK_SIZE and BLOCKSIZE environment variables.
+Otherwise, units default to 1024 bytes (or 512 if POSIXLY_CORRECT is set).
+
+SIZE is an integer and optional unit (example: 10M is 10*1024*1024).  Units
+are K, M, G, T, P, E, Z, Y (powers of 1024) or KB, MB, ... (powers of 1000).
+
+FIELD_LIST is a comma-separated list of columns to be included.  Valid
+field names are: 'source', 'fstype', 'itotal', 'iused', 'iavail', 'ipcent',
+'size', 'used', 'avail', 'pcent', 'file' and 'target'.";
 
 static EXIT_OK: i32 = 0;
 static EXIT_ERR: i32 = 1;
@@ -78,10 +91,13 @@ static EXIT_ERR: i32 = 1;
 #[cfg(windows)]
 const MAX_PATH: usize = 266;
 
+/// How long to wait for file systems to report their usage.
+const STAT_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[cfg(target_os = "linux")]
-static LINUX_MOUNTINFO: &str = "/proc/self/mountinfo";
+const LINUX_MOUNTINFO: &str = "/proc/self/mountinfo";
 #[cfg(target_os = "linux")]
-static LINUX_MTAB: &str = "/etc/mtab";
+const LINUX_MTAB: &str = "/etc/mtab";
 
 static OPT_ALL: &str = "all";
 static OPT_BLOCKSIZE: &str = "blocksize";
@@ -90,6 +106,7 @@ static OPT_TOTAL: &str = "total";
 static OPT_HUMAN_READABLE: &str = "human-readable";
 static OPT_HUMAN_READABLE_2: &str = "human-readable-2";
 static OPT_INODES: &str = "inodes";
+static OPT_JSON: &str = "json";
 static OPT_KILO: &str = "kilo";
 static OPT_LOCAL: &str = "local";
 static OPT_NO_SYNC: &str = "no-sync";
@@ -117,11 +134,29 @@ struct Options {
     show_fs_type: bool,
     show_inode_instead: bool,
     print_grand_total: bool,
-    // block_size: usize,
-    human_readable_base: i64,
+    posix_format: bool,
+    block_size: BlockSize,
+    custom_columns: bool,
     fs_selector: FsSelector,
 }
 
+/// A field of the `--output` list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Column {
+    Source,
+    FsType,
+    ITotal,
+    IUsed,
+    IAvail,
+    IPcent,
+    Size,
+    Used,
+    Avail,
+    Pcent,
+    File,
+    Target,
+}
+
 #[derive(Debug, Clone)]
 struct MountInfo {
     // it stores `volume_name` in windows platform and `dev_id` in unix platform
@@ -181,7 +216,8 @@ struct FsUsage {
 #[derive(Debug, Clone)]
 struct Filesystem {
     mountinfo: MountInfo,
-    usage: FsUsage,
+    // `None` when the file system did not respond in time
+    usage: Option<FsUsage>,
 }
 
 #[cfg(windows)]
@@ -287,29 +323,230 @@ impl Options {
             show_fs_type: false,
             show_inode_instead: false,
             print_grand_total: false,
-            // block_size: match env::var("BLOCKSIZE") {
-            //     Ok(size) => size.parse().unwrap(),
-            //     Err(_) => 512,
-            // },
-            human_readable_base: -1,
+            posix_format: false,
+            block_size: BlockSize::Fixed(1024),
+            custom_columns: false,
             fs_selector: FsSelector::new(),
         }
     }
 }
 
+impl Column {
+    const ALL: [Column; 12] = [
+        Column::Source,
+        Column::FsType,
+        Column::ITotal,
+        Column::IUsed,
+        Column::IAvail,
+        Column::IPcent,
+        Column::Size,
+        Column::Used,
+        Column::Avail,
+        Column::Pcent,
+        Column::File,
+        Column::Target,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Column::Source => "source",
+            Column::FsType => "fstype",
+            Column::ITotal => "itotal",
+            Column::IUsed => "iused",
+            Column::IAvail => "iavail",
+            Column::IPcent => "ipcent",
+            Column::Size => "size",
+            Column::Used => "used",
+            Column::Avail => "avail",
+            Column::Pcent => "pcent",
+            Column::File => "file",
+            Column::Target => "target",
+        }
+    }
+
+    /// The columns shown without `--output`.
+    fn defaults(opt: &Options) -> Vec<Column> {
+        let mut columns = vec![Column::Source];
+        if opt.show_fs_type {
+            columns.push(Column::FsType);
+        }
+        if opt.show_inode_instead {
+            columns.extend_from_slice(&[
+                Column::ITotal,
+                Column::IUsed,
+                Column::IAvail,
+                Column::IPcent,
+            ]);
+        } else {
+            columns.extend_from_slice(&[Column::Size, Column::Used, Column::Avail, Column::Pcent]);
+        }
+        columns.push(Column::Target);
+        columns
+    }
+
+    /// Parse the `--output` field list, all fields if it is empty.
+    fn parse_list<'a, I: Iterator<Item = &'a str>>(
+        fields: Option<I>,
+    ) -> Result<Vec<Column>, String> {
+        let mut columns = Vec::new();
+        for field in fields.into_iter().flatten() {
+            let column = Column::ALL
+                .iter()
+                .find(|c| c.name() == field)
+                .ok_or_else(|| format!("field '{}' unknown", field))?;
+            if columns.contains(column) {
+                return Err(format!("field '{}' used more than once", field));
+            }
+            columns.push(*column);
+        }
+        if columns.is_empty() {
+            columns.extend_from_slice(&Column::ALL);
+        }
+        Ok(columns)
+    }
+
+    fn header(self, opt: &Options) -> String {
+        let human = match opt.block_size.bytes() {
+            None => true,
+            Some(size) => {
+                if self == Column::Size {
+                    return if opt.posix_format {
+                        format!("{}-blocks", size)
+                    } else {
+                        format!("{}-blocks", opt.block_size.name())
+                    };
+                }
+                false
+            }
+        };
+        match self {
+            Column::Source => "Filesystem",
+            Column::FsType => "Type",
+            Column::ITotal => "Inodes",
+            Column::IUsed => "IUsed",
+            Column::IAvail => "IFree",
+            Column::IPcent => "IUse%",
+            Column::Size => "Size",
+            Column::Used => "Used",
+            Column::Avail if human || opt.custom_columns => "Avail",
+            Column::Avail => "Available",
+            Column::Pcent if opt.posix_format => "Capacity",
+            Column::Pcent => "Use%",
+            Column::File => "File",
+            Column::Target => "Mounted on",
+        }
+        .to_string()
+    }
+
+    fn value(self, fs: &Filesystem, file: &str, opt: &Options) -> String {
+        let count = match self {
+            Column::Source => return fs.mountinfo.dev_name.clone(),
+            Column::FsType => return fs.mountinfo.fs_type.clone(),
+            Column::File => return file.to_string(),
+            Column::Target => return fs.mountinfo.mount_point().to_string(),
+            _ => match self.count(fs) {
+                Some(count) => count,
+                None => return "-".to_string(),
+            },
+        };
+        match self {
+            Column::IPcent | Column::Pcent => format!("{}%", count),
+            Column::Size | Column::Used | Column::Avail => opt.block_size.format(count),
+            // inode counts are scaled by -h and --si, but not by a block size
+            _ => match opt.block_size {
+                BlockSize::Human(base) => human_readable(count, base),
+                _ => count.to_string(),
+            },
+        }
+    }
+
+    /// Add the field to the `--json` record of `fs`, with sizes in bytes,
+    /// percentages as numbers and `null` for what isn't known.
+    fn add_to(self, record: &mut json::Object, fs: &Filesystem, file: Option<&str>) {
+        let name = self.name();
+        match self {
+            Column::Source => record.string(name, &fs.mountinfo.dev_name),
+            Column::FsType => record.string(name, &fs.mountinfo.fs_type),
+            Column::Target => record.string(name, fs.mountinfo.mount_point()),
+            Column::File => match file {
+                Some(file) => record.string(name, file),
+                None => record.null(name),
+            },
+            _ => match self.count(fs) {
+                Some(count) => record.number(name, count),
+                None => record.null(name),
+            },
+        };
+    }
+
+    /// The value of a numeric field, in bytes for sizes, if it is known.
+    fn count(self, fs: &Filesystem) -> Option<u64> {
+        let usage = fs.usage.as_ref()?;
+        let used = usage.blocks.saturating_sub(usage.bfree);
+        // a negative count of available blocks is shown as none
+        let avail = if usage.bavail_top_bit_set {
+            0
+        } else {
+            usage.bavail
+        };
+        // some file systems don't count inodes at all, but report all ones
+        let inodes_known = usage.files != u64::max_value();
+        let iused = usage.files.saturating_sub(usage.ffree);
+        match self {
+            Column::ITotal | Column::IUsed | Column::IAvail | Column::IPcent if !inodes_known => {
+                None
+            }
+            Column::ITotal => Some(usage.files),
+            Column::IUsed => Some(iused),
+            Column::IAvail => Some(usage.ffree),
+            Column::IPcent => percentage(iused, usage.ffree),
+            Column::Size => Some(usage.blocks * usage.blocksize),
+            Column::Used => Some(used * usage.blocksize),
+            Column::Avail => Some(avail * usage.blocksize),
+            Column::Pcent => percentage(used, avail),
+            Column::Source | Column::FsType | Column::File | Column::Target => None,
+        }
+    }
+
+    fn min_width(self) -> usize {
+        match self {
+            Column::Source => 14,
+            Column::FsType | Column::File => 4,
+            Column::Target => 0,
+            _ => 5,
+        }
+    }
+
+    fn is_left_aligned(self) -> bool {
+        match self {
+            Column::Source | Column::FsType | Column::File | Column::Target => true,
+            _ => false,
+        }
+    }
+}
+
 impl MountInfo {
+    /// The directory the file system is mounted on, or its root on Windows.
+    fn mount_point(&self) -> &str {
+        if self.mount_dir.is_empty() {
+            &self.mount_root
+        } else {
+            &self.mount_dir
+        }
+    }
+
     fn set_missing_fields(&mut self) {
         #[cfg(unix)]
         {
-            // We want to keep the dev_id on Windows
-            // but set dev_id
-            let path = CString::new(self.mount_dir.clone()).unwrap();
-            unsafe {
-                let mut stat = mem::zeroed();
-                if libc::stat(path.as_ptr(), &mut stat) == 0 {
-                    self.dev_id = (stat.st_dev as i32).to_string();
-                } else {
-                    self.dev_id = "".to_string();
+            // We want to keep the dev_id on Windows, and the mount list may
+            // have provided it already
+            if self.dev_id.is_empty() {
+                let path = CString::new(self.mount_dir.clone()).unwrap();
+                unsafe {
+                    let mut stat = mem::zeroed();
+                    if libc::stat(path.as_ptr(), &mut stat) == 0 {
+                        self.dev_id = (stat.st_dev as i32).to_string();
+                    }
                 }
             }
         }
@@ -317,7 +554,7 @@ impl MountInfo {
         match self.fs_type.as_ref() {
             "autofs" | "proc" | "subfs"
             /* for Linux 2.6/3.x */
-            | "debugfs" | "devpts" | "fusectl" | "mqueue" | "rpc_pipefs" | "sysfs"
+            | "debugfs" | "devpts" | "fusectl" | "fuse.portal" | "mqueue" | "rpc_pipefs" | "sysfs"
             /* FreeBSD, Linux 2.4 */
             | "devfs"
             /* for NetBSD 3.0 */
@@ -334,54 +571,57 @@ impl MountInfo {
         }
         #[cfg(unix)]
         {
-            if self.dev_name.find(':').is_some()
-                || (self.dev_name.starts_with("//") && self.fs_type == "smbfs"
-                    || self.fs_type == "cifs")
-                || self.dev_name == "-hosts"
-            {
-                self.remote = true;
-            } else {
-                self.remote = false;
-            }
+            self.remote = self.dev_name.contains(':')
+                || (self.dev_name.starts_with("//")
+                    && (self.fs_type == "smbfs"
+                        || self.fs_type == "smb3"
+                        || self.fs_type == "cifs"))
+                || self.dev_name == "-hosts";
         }
     }
 
     #[cfg(target_os = "linux")]
-    fn new(file_name: &str, raw: Vec<&str>) -> Option<MountInfo> {
-        match file_name {
+    fn new(file_name: &str, line: &str) -> Option<MountInfo> {
+        let mut fields = line.split(' ').map(unescape);
+        let mut m = match file_name {
             // Format: 36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue
             // "man proc" for more details
-            "/proc/self/mountinfo" => {
-                let mut m = MountInfo {
-                    dev_id: "".to_string(),
-                    dev_name: raw[9].to_string(),
-                    fs_type: raw[8].to_string(),
-                    mount_root: raw[3].to_string(),
-                    mount_dir: raw[4].to_string(),
-                    mount_option: raw[5].to_string(),
+            LINUX_MOUNTINFO => {
+                let dev_id = fields.nth(2)?;
+                let mount_root = fields.next()?;
+                let mount_dir = fields.next()?;
+                let mount_option = fields.next()?;
+                // skip the optional fields, which are ended by a single hyphen
+                let fs_type = fields.find(|field| field == "-").and(fields.next())?;
+                let dev_name = fields.next()?;
+                MountInfo {
+                    dev_id,
+                    dev_name,
+                    fs_type,
+                    mount_root,
+                    mount_dir,
+                    mount_option,
                     remote: false,
                     dummy: false,
-                };
-                m.set_missing_fields();
-                Some(m)
-            }
-            "/etc/mtab" => {
-                let mut m = MountInfo {
-                    dev_id: "".to_string(),
-                    dev_name: raw[0].to_string(),
-                    fs_type: raw[2].to_string(),
-                    mount_root: "".to_string(),
-                    mount_dir: raw[1].to_string(),
-                    mount_option: raw[3].to_string(),
-                    remote: false,
-                    dummy: false,
-                };
-                m.set_missing_fields();
-                Some(m)
+                }
             }
-            _ => None,
-        }
+            // Format: /dev/root / ext3 rw,noatime 0 0
+            LINUX_MTAB => MountInfo {
+                dev_id: "".to_string(),
+                dev_name: fields.next()?,
+                mount_dir: fields.next()?,
+                fs_type: fields.next()?,
+                mount_option: fields.next()?,
+                mount_root: "".to_string(),
+                remote: false,
+                dummy: false,
+            },
+            _ => return None,
+        };
+        m.set_missing_fields();
+        Some(m)
     }
+
     #[cfg(windows)]
     fn new(mut volume_name: String) -> Option<MountInfo> {
         let mut dev_name_buf = [0u16; MAX_PATH];
@@ -464,8 +704,8 @@ impl FsUsage {
                 bfree: statvfs.f_bfree as u64,
                 bavail: statvfs.f_bavail as u64,
                 bavail_top_bit_set: ((statvfs.f_bavail as u64) & (1u64.rotate_right(1))) != 0,
-                files: statvfs.f_files as u64,
-                ffree: statvfs.f_ffree as u64,
+                files: all_ones_as_max(statvfs.f_files as u64, mem::size_of_val(&statvfs.f_files)),
+                ffree: all_ones_as_max(statvfs.f_ffree as u64, mem::size_of_val(&statvfs.f_ffree)),
             }
         }
     }
@@ -525,7 +765,7 @@ impl FsUsage {
             // Total number of file nodes (inodes) on the file system.
             files: 0 as u64, // Not available on windows
             // Total number of free file nodes (inodes).
-            ffree: 4096 as u64, // Meaningless on Windows
+            ffree: 0 as u64, // Not available on windows
         }
     }
 }
@@ -555,18 +795,124 @@ impl Filesystem {
             } else {
                 Some(Filesystem {
                     mountinfo,
-                    usage: FsUsage::new(statvfs),
+                    usage: Some(FsUsage::new(statvfs)),
                 })
             }
         }
         #[cfg(windows)]
         Some(Filesystem {
             mountinfo,
-            usage: FsUsage::new(Path::new(&_stat_path)),
+            usage: Some(FsUsage::new(Path::new(&_stat_path))),
         })
     }
 }
 
+/// Undo the octal escapes (e.g. `\040` for a space) of a mount list field.
+#[cfg(target_os = "linux")]
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes
+            .get(i + 1..i + 4)
+            .filter(|digits| digits.iter().all(|d| b'0' <= *d && *d <= b'7') && digits[0] <= b'3');
+        match octal {
+            Some(digits) if bytes[i] == b'\\' => {
+                unescaped.push(digits.iter().fold(0, |value, d| value * 8 + (d - b'0')));
+                i += 4;
+            }
+            _ => {
+                unescaped.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+impl Filesystem {
+    /// The sum of `filesystems`, shown as the `--total` row.
+    fn total<'a, I: Iterator<Item = &'a Filesystem>>(filesystems: I) -> Filesystem {
+        let mut usage = FsUsage {
+            blocksize: 1,
+            blocks: 0,
+            bfree: 0,
+            bavail: 0,
+            bavail_top_bit_set: false,
+            files: 0,
+            ffree: 0,
+        };
+        for fs in filesystems.filter_map(|fs| fs.usage.as_ref()) {
+            usage.blocks += fs.blocks * fs.blocksize;
+            usage.bfree += fs.bfree * fs.blocksize;
+            if !fs.bavail_top_bit_set {
+                usage.bavail += fs.bavail * fs.blocksize;
+            }
+            if fs.files != u64::max_value() {
+                usage.files += fs.files;
+                usage.ffree += fs.ffree;
+            }
+        }
+        Filesystem {
+            mountinfo: MountInfo {
+                dev_id: "".to_string(),
+                dev_name: "total".to_string(),
+                fs_type: "-".to_string(),
+                mount_dir: "-".to_string(),
+                mount_option: "".to_string(),
+                mount_root: "".to_string(),
+                remote: false,
+                dummy: false,
+            },
+            usage: Some(usage),
+        }
+    }
+}
+
+/// Get the usage of each of `mounts`. Remote file systems are asked in a
+/// thread of their own, so that one that does not respond (its server is
+/// down) holds up df for no more than `STAT_TIMEOUT`; its usage is then
+/// shown as unknown. Local ones are asked directly. `None` stands for a file
+/// system whose usage can't be read.
+fn stat_filesystems(mounts: Vec<MountInfo>) -> Vec<Option<Filesystem>> {
+    let (sender, receiver) = mpsc::channel();
+    let mut filesystems: Vec<Option<Option<Filesystem>>> = vec![None; mounts.len()];
+    let mut pending = 0;
+    for (i, mi) in mounts.iter().cloned().enumerate() {
+        if mi.remote {
+            let sender = sender.clone();
+            thread::spawn(move || sender.send((i, Filesystem::new(mi))));
+            pending += 1;
+        } else {
+            filesystems[i] = Some(Filesystem::new(mi));
+        }
+    }
+
+    let deadline = Instant::now() + STAT_TIMEOUT;
+    for _ in 0..pending {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match receiver.recv_timeout(timeout) {
+            Ok((i, fs)) => filesystems[i] = Some(fs),
+            Err(_) => break,
+        }
+    }
+
+    filesystems
+        .into_iter()
+        .zip(mounts)
+        .map(|(fs, mountinfo)| {
+            fs.unwrap_or_else(|| {
+                show_warning!("{}: file system not responding", mountinfo.mount_point());
+                Some(Filesystem {
+                    mountinfo,
+                    usage: None,
+                })
+            })
+        })
+        .collect()
+}
+
 /// Read file system list.
 fn read_fs_list() -> Vec<MountInfo> {
     #[cfg(target_os = "linux")]
@@ -579,10 +925,7 @@ fn read_fs_list() -> Vec<MountInfo> {
         reader
             .lines()
             .filter_map(|line| line.ok())
-            .filter_map(|line| {
-                let raw_data = line.split_whitespace().collect::<Vec<&str>>();
-                MountInfo::new(file_name, raw_data)
-            })
+            .filter_map(|line| MountInfo::new(file_name, &line))
             .collect::<Vec<_>>()
     }
     #[cfg(any(target_os = "freebsd", target_vendor = "apple"))]
@@ -641,7 +984,7 @@ fn read_fs_list() -> Vec<MountInfo> {
     }
 }
 
-fn filter_mount_list(vmi: Vec<MountInfo>, paths: &[String], opt: &Options) -> Vec<MountInfo> {
+fn filter_mount_list(vmi: Vec<MountInfo>, opt: &Options) -> Vec<MountInfo> {
     vmi.into_iter()
         .filter_map(|mi| {
             if (mi.remote && opt.show_local_fs)
@@ -650,33 +993,23 @@ fn filter_mount_list(vmi: Vec<MountInfo>, paths: &[String], opt: &Options) -> Ve
             {
                 None
             } else {
-                if paths.is_empty() {
-                    // No path specified
-                    return Some((mi.dev_id.clone(), mi));
-                }
-                if paths.contains(&mi.mount_dir) {
-                    // One or more paths have been provided
-                    Some((mi.dev_id.clone(), mi))
-                } else {
-                    // Not a path we want to see
-                    None
-                }
+                Some((mi.dev_id.clone(), mi))
             }
         })
         .fold(
-            HashMap::<String, Cell<MountInfo>>::new(),
-            |mut acc, (id, mi)| {
-                #[allow(clippy::map_entry)]
-                {
-                    if acc.contains_key(&id) {
-                        let seen = acc[&id].replace(mi.clone());
+            (HashMap::<String, usize>::new(), Vec::<MountInfo>::new()),
+            |(mut ids, mut acc), (id, mi)| {
+                match ids.get(&id) {
+                    // with -a every mount is listed, as is any we can't identify
+                    Some(&i) if !opt.show_all_fs && !id.is_empty() => {
+                        let seen = &acc[i];
                         let target_nearer_root = seen.mount_dir.len() > mi.mount_dir.len();
                         // With bind mounts, prefer items nearer the root of the source
                         let source_below_root = !seen.mount_root.is_empty()
                             && !mi.mount_root.is_empty()
                             && seen.mount_root.len() < mi.mount_root.len();
                         // let "real" devices with '/' in the name win.
-                        if (!mi.dev_name.starts_with('/') || seen.dev_name.starts_with('/'))
+                        if !((!mi.dev_name.starts_with('/') || seen.dev_name.starts_with('/'))
                             // let points towards the root of the device win.
                             && (!target_nearer_root || source_below_root)
                             // let an entry overmounted on a new device win...
@@ -685,61 +1018,281 @@ fn filter_mount_list(vmi: Vec<MountInfo>, paths: &[String], opt: &Options) -> Ve
                             to avoid problematic replacement when given
                             inaccurate mount lists, seen with some chroot
                             environments for example.  */
-                            || seen.mount_dir != mi.mount_dir)
+                            || seen.mount_dir != mi.mount_dir))
                         {
-                            acc[&id].replace(seen);
+                            acc[i] = mi;
                         }
-                    } else {
-                        acc.insert(id, Cell::new(mi));
                     }
-                    acc
+                    _ => {
+                        ids.insert(id, acc.len());
+                        acc.push(mi);
+                    }
                 }
+                (ids, acc)
             },
         )
-        .into_iter()
-        .map(|ent| ent.1.into_inner())
-        .collect::<Vec<_>>()
+        .1
 }
 
-/// Convert `value` to a human readable string based on `base`.
-/// e.g. It returns 1G when value is 1 * 1024 * 1024 * 1024 and base is 1024.
-/// Note: It returns `value` if `base` isn't positive.
-fn human_readable(value: u64, base: i64) -> String {
-    match base {
-        d if d < 0 => value.to_string(),
+/// Widen `value`, a field `size` bytes wide, keeping all ones (which stands
+/// for an unknown value) as all ones.
+#[cfg(unix)]
+fn all_ones_as_max(value: u64, size: usize) -> u64 {
+    if size < mem::size_of::<u64>() && value == (1 << (size * 8)) - 1 {
+        u64::max_value()
+    } else {
+        value
+    }
+}
 
-        // ref: [Binary prefix](https://en.wikipedia.org/wiki/Binary_prefix) @@ <https://archive.is/cnwmF>
-        // ref: [SI/metric prefix](https://en.wikipedia.org/wiki/Metric_prefix) @@ <https://archive.is/QIuLj>
-        1000 => match NumberPrefix::decimal(value as f64) {
-            NumberPrefix::Standalone(bytes) => bytes.to_string(),
-            NumberPrefix::Prefixed(prefix, bytes) => format!("{:.1}{}", bytes, prefix.symbol()),
-        },
+/// The percentage of `used` in `used + avail`, rounded up as GNU df does.
+fn percentage(used: u64, avail: u64) -> Option<u64> {
+    let total = used + avail;
+    if total == 0 {
+        return None;
+    }
+    Some((used * 100 + total - 1) / total)
+}
 
-        1024 => match NumberPrefix::binary(value as f64) {
-            NumberPrefix::Standalone(bytes) => bytes.to_string(),
-            NumberPrefix::Prefixed(prefix, bytes) => format!("{:.1}{}", bytes, prefix.symbol()),
-        },
+/// Find the file system that `path` resides on, that is the mount point
+/// which is its deepest ancestor, the one mounted last winning.
+fn mount_for_path<'a>(mounts: &'a [MountInfo], path: &str) -> Option<&'a MountInfo> {
+    let path = fs::canonicalize(path).ok()?;
+    mounts
+        .iter()
+        .filter(|mi| path.starts_with(mi.mount_point()))
+        .max_by_key(|mi| Path::new(mi.mount_point()).components().count())
+}
 
-        _ => crash!(EXIT_ERR, "Internal error: Unknown base value {}", base),
+fn print_table(columns: &[Column], rows: &[Vec<String>]) {
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(column.min_width())
+        })
+        .collect();
+    for row in rows {
+        let mut line = String::new();
+        for (i, (cell, column)) in row.iter().zip(columns).enumerate() {
+            if i > 0 {
+                line.push(' ');
+            }
+            if !column.is_left_aligned() {
+                line.push_str(&format!("{:>1$}", cell, widths[i]));
+            } else if i + 1 < columns.len() {
+                line.push_str(&format!("{:<1$}", cell, widths[i]));
+            } else {
+                line.push_str(cell);
+            }
+        }
+        println!("{}", line);
     }
 }
 
-fn use_size(free_size: u64, total_size: u64) -> String {
-    if total_size == 0 {
-        return String::from("-");
+pub fn uumain(args: impl uucore::Args) -> i32 {
+    let usage = get_usage();
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let paths: Vec<String> = matches
+        .values_of(OPT_PATHS)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    #[cfg(windows)]
+    {
+        if matches.is_present(OPT_INODES) {
+            println!("{}: doesn't support -i option", executable!());
+            return EXIT_OK;
+        }
+    }
+
+    let mut opt = Options::new();
+    if matches.is_present(OPT_LOCAL) {
+        opt.show_local_fs = true;
+    }
+    if matches.is_present(OPT_ALL) {
+        opt.show_all_fs = true;
+    }
+    if matches.is_present(OPT_TOTAL) {
+        opt.print_grand_total = true;
+    }
+    if matches.is_present(OPT_INODES) {
+        opt.show_inode_instead = true;
+    }
+    if matches.is_present(OPT_PRINT_TYPE) {
+        opt.show_fs_type = true;
+    }
+    if matches.is_present(OPT_PORTABILITY) {
+        opt.posix_format = true;
+    }
+    // the last of the options setting the block size wins
+    let block_size_option = [
+        OPT_BLOCKSIZE,
+        OPT_KILO,
+        OPT_HUMAN_READABLE,
+        OPT_HUMAN_READABLE_2,
+    ]
+    .iter()
+    .filter_map(|&name| matches.index_of(name).map(|index| (index, name)))
+    .max();
+    opt.block_size = match block_size_option {
+        Some((_, name)) if name == OPT_BLOCKSIZE => {
+            let arg = matches.value_of(OPT_BLOCKSIZE).unwrap();
+            match BlockSize::parse(arg) {
+                Ok(block_size) => block_size,
+                Err(e) => crash!(EXIT_ERR, "{}", e.message("--block-size", arg)),
+            }
+        }
+        Some((_, name)) if name == OPT_KILO => BlockSize::Fixed(1024),
+        Some((_, name)) if name == OPT_HUMAN_READABLE => BlockSize::Human(1024),
+        Some(_) => BlockSize::Human(1000),
+        None if opt.posix_format => BlockSize::from_env(&[]),
+        None => BlockSize::from_env(&["DF_BLOCK_SIZE", "BLOCK_SIZE", "BLOCKSIZE"]),
+    };
+    for fs_type in matches.values_of_lossy(OPT_TYPE).unwrap_or_default() {
+        opt.fs_selector.include(fs_type.to_owned());
+    }
+    for fs_type in matches
+        .values_of_lossy(OPT_EXCLUDE_TYPE)
+        .unwrap_or_default()
+    {
+        opt.fs_selector.exclude(fs_type.to_owned());
+    }
+    if let Some(fs_type) = opt
+        .fs_selector
+        .include
+        .intersection(&opt.fs_selector.exclude)
+        .next()
+    {
+        show_error!("file system type '{}' both selected and excluded", fs_type);
+        return EXIT_ERR;
+    }
+
+    let columns = if matches.is_present(OPT_OUTPUT) {
+        for (option, name) in &[
+            (OPT_INODES, "-i"),
+            (OPT_PORTABILITY, "-P"),
+            (OPT_PRINT_TYPE, "-T"),
+        ] {
+            if matches.is_present(option) {
+                show_usage_error!("options {} and --output are mutually exclusive", name);
+                return EXIT_ERR;
+            }
+        }
+        match Column::parse_list(matches.values_of(OPT_OUTPUT)) {
+            Ok(columns) => columns,
+            Err(e) => {
+                show_usage_error!("option --output: {}", e);
+                return EXIT_ERR;
+            }
+        }
+    } else {
+        Column::defaults(&opt)
+    };
+    opt.custom_columns = matches.is_present(OPT_OUTPUT);
+
+    let mut status = EXIT_OK;
+    let mounts = read_fs_list();
+    let filesystems: Vec<(Filesystem, Option<&str>)> = if paths.is_empty() {
+        stat_filesystems(filter_mount_list(mounts, &opt))
+            .into_iter()
+            .flatten()
+            .filter(|fs| {
+                fs.usage.as_ref().map_or(true, |usage| usage.blocks != 0)
+                    || opt.show_all_fs
+                    || opt.show_listed_fs
+            })
+            .map(|fs| (fs, None))
+            .collect()
+    } else {
+        let mut listed = Vec::new();
+        for path in &paths {
+            let mi = match mount_for_path(&mounts, path) {
+                Some(mi) => mi,
+                None => {
+                    let e = fs::metadata(path).err().map_or_else(
+                        || "cannot find the file system it resides on".to_string(),
+                        |e| e.to_string(),
+                    );
+                    show_error!("{}: {}", path, e);
+                    status = EXIT_ERR;
+                    continue;
+                }
+            };
+            if (mi.remote && opt.show_local_fs) || !opt.fs_selector.should_select(&mi.fs_type) {
+                continue;
+            }
+            listed.push((mi.clone(), &path[..]));
+        }
+        let (mounts, paths): (Vec<_>, Vec<_>) = listed.into_iter().unzip();
+        stat_filesystems(mounts)
+            .into_iter()
+            .zip(paths)
+            .filter_map(|(fs, path)| fs.map(|fs| (fs, Some(path))))
+            .collect()
+    };
+    if filesystems.is_empty() {
+        if paths.is_empty() {
+            crash!(EXIT_ERR, "no file systems processed");
+        }
+        return status;
+    }
+
+    let total = if opt.print_grand_total {
+        Some(Filesystem::total(filesystems.iter().map(|(fs, _)| fs)))
+    } else {
+        None
+    };
+
+    if matches.is_present(OPT_JSON) {
+        // every field, unless --output picks some
+        let columns = if opt.custom_columns {
+            columns
+        } else {
+            Column::ALL.to_vec()
+        };
+        for (fs, file) in filesystems
+            .iter()
+            .map(|(fs, file)| (fs, *file))
+            .chain(total.iter().map(|fs| (fs, None)))
+        {
+            let mut record = json::Object::new();
+            for column in &columns {
+                column.add_to(&mut record, fs, file);
+            }
+            println!("{}", record);
+        }
+        return status;
     }
-    return format!(
-        "{:.0}%",
-        100f64 - 100f64 * (free_size as f64 / total_size as f64)
+
+    let mut rows: Vec<Vec<String>> = vec![columns.iter().map(|c| c.header(&opt)).collect()];
+    rows.extend(
+        filesystems
+            .iter()
+            .map(|(fs, file)| (fs, *file))
+            .chain(total.iter().map(|fs| (fs, None)))
+            .map(|(fs, file)| {
+                columns
+                    .iter()
+                    .map(|c| c.value(fs, file.unwrap_or("-"), &opt))
+                    .collect()
+            }),
     );
+    print_table(&columns, &rows);
+
+    status
 }
 
-pub fn uumain(args: impl uucore::Args) -> i32 {
-    let usage = get_usage();
-    let matches = App::new(executable!())
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
+        .after_help(LONG_HELP)
         .arg(
             Arg::with_name(OPT_ALL)
                 .short("a")
@@ -770,14 +1323,12 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             Arg::with_name(OPT_HUMAN_READABLE)
                 .short("h")
                 .long("human-readable")
-                .conflicts_with(OPT_HUMAN_READABLE_2)
                 .help("print sizes in human readable format (e.g., 1K 234M 2G)"),
         )
         .arg(
             Arg::with_name(OPT_HUMAN_READABLE_2)
                 .short("H")
                 .long("si")
-                .conflicts_with(OPT_HUMAN_READABLE)
                 .help("likewise, but use powers of 1000 not 1024"),
         )
         .arg(
@@ -786,6 +1337,11 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .long("inodes")
                 .help("list inode information instead of block usage"),
         )
+        .arg(
+            Arg::with_name(OPT_JSON)
+                .long("json")
+                .help("print a JSON object for each file system, one per line"),
+        )
         .arg(
             Arg::with_name(OPT_KILO)
                 .short("k")
@@ -806,7 +1362,10 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         .arg(
             Arg::with_name(OPT_OUTPUT)
                 .long("output")
+                .value_name("FIELD_LIST")
                 .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
                 .use_delimiter(true)
                 .help(
                     "use the output format defined by FIELD_LIST,\
@@ -830,7 +1389,8 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .short("t")
                 .long("type")
                 .takes_value(true)
-                .use_delimiter(true)
+                .multiple(true)
+                .number_of_values(1)
                 .help("limit listing to file systems of type TYPE"),
         )
         .arg(
@@ -844,142 +1404,10 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .short("x")
                 .long("exclude-type")
                 .takes_value(true)
-                .use_delimiter(true)
+                .multiple(true)
+                .number_of_values(1)
                 .help("limit listing to file systems not of type TYPE"),
         )
         .arg(Arg::with_name(OPT_PATHS).multiple(true))
         .help("Filesystem(s) to list")
-        .get_matches_from(args);
-
-    let paths: Vec<String> = matches
-        .values_of(OPT_PATHS)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    #[cfg(windows)]
-    {
-        if matches.is_present(OPT_INODES) {
-            println!("{}: doesn't support -i option", executable!());
-            return EXIT_OK;
-        }
-    }
-
-    let mut opt = Options::new();
-    if matches.is_present(OPT_LOCAL) {
-        opt.show_local_fs = true;
-    }
-    if matches.is_present(OPT_ALL) {
-        opt.show_all_fs = true;
-    }
-    if matches.is_present(OPT_TOTAL) {
-        opt.print_grand_total = true;
-    }
-    if matches.is_present(OPT_INODES) {
-        opt.show_inode_instead = true;
-    }
-    if matches.is_present(OPT_PRINT_TYPE) {
-        opt.show_fs_type = true;
-    }
-    if matches.is_present(OPT_HUMAN_READABLE) {
-        opt.human_readable_base = 1024;
-    }
-    if matches.is_present(OPT_HUMAN_READABLE_2) {
-        opt.human_readable_base = 1000;
-    }
-    for fs_type in matches.values_of_lossy(OPT_TYPE).unwrap_or_default() {
-        opt.fs_selector.include(fs_type.to_owned());
-    }
-    for fs_type in matches
-        .values_of_lossy(OPT_EXCLUDE_TYPE)
-        .unwrap_or_default()
-    {
-        opt.fs_selector.exclude(fs_type.to_owned());
-    }
-
-    let fs_list = filter_mount_list(read_fs_list(), &paths, &opt)
-        .into_iter()
-        .filter_map(Filesystem::new)
-        .filter(|fs| fs.usage.blocks != 0 || opt.show_all_fs || opt.show_listed_fs)
-        .collect::<Vec<_>>();
-
-    // set headers
-    let mut header = vec!["Filesystem"];
-    if opt.show_fs_type {
-        header.push("Type");
-    }
-    header.extend_from_slice(&if opt.show_inode_instead {
-        ["Inodes", "Iused", "IFree", "IUses%"]
-    } else {
-        [
-            if opt.human_readable_base == -1 {
-                "1k-blocks"
-            } else {
-                "Size"
-            },
-            "Used",
-            "Available",
-            "Use%",
-        ]
-    });
-    header.push("Mounted on");
-
-    for (idx, title) in header.iter().enumerate() {
-        if idx == 0 || idx == header.len() - 1 {
-            print!("{0: <16} ", title);
-        } else if opt.show_fs_type && idx == 1 {
-            print!("{0: <5} ", title);
-        } else if idx == header.len() - 2 {
-            print!("{0: >5} ", title);
-        } else {
-            print!("{0: >12} ", title);
-        }
-    }
-    println!();
-    for fs in fs_list.iter() {
-        print!("{0: <16} ", fs.mountinfo.dev_name);
-        if opt.show_fs_type {
-            print!("{0: <5} ", fs.mountinfo.fs_type);
-        }
-        if opt.show_inode_instead {
-            print!(
-                "{0: >12} ",
-                human_readable(fs.usage.files, opt.human_readable_base)
-            );
-            print!(
-                "{0: >12} ",
-                human_readable(fs.usage.files - fs.usage.ffree, opt.human_readable_base)
-            );
-            print!(
-                "{0: >12} ",
-                human_readable(fs.usage.ffree, opt.human_readable_base)
-            );
-            print!(
-                "{0: >5} ",
-                format!(
-                    "{0:.1}%",
-                    100f64 - 100f64 * (fs.usage.ffree as f64 / fs.usage.files as f64)
-                )
-            );
-        } else {
-            let total_size = fs.usage.blocksize * fs.usage.blocks;
-            let free_size = fs.usage.blocksize * fs.usage.bfree;
-            print!(
-                "{0: >12} ",
-                human_readable(total_size, opt.human_readable_base)
-            );
-            print!(
-                "{0: >12} ",
-                human_readable(total_size - free_size, opt.human_readable_base)
-            );
-            print!(
-                "{0: >12} ",
-                human_readable(free_size, opt.human_readable_base)
-            );
-            print!("{0: >5} ", use_size(free_size, total_size));
-        }
-        print!("{0: <16}", fs.mountinfo.mount_dir);
-        println!();
-    }
-
-    EXIT_OK
 }
@@ -14,6 +14,7 @@ use std::borrow::Borrow;
 use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use uucore::coreopts::CoreOptions;
 
 static SYNTAX: &str = "[OPTION]... [FILE]";
 static SUMMARY: &str = "Output commands to set the LS_COLORS environment variable.";
@@ -55,17 +56,7 @@ pub fn guess_syntax() -> OutputFmt {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = app!(SYNTAX, SUMMARY, LONG_HELP)
-        .optflag("b", "sh", "output Bourne shell code to set LS_COLORS")
-        .optflag(
-            "",
-            "bourne-shell",
-            "output Bourne shell code to set LS_COLORS",
-        )
-        .optflag("c", "csh", "output C shell code to set LS_COLORS")
-        .optflag("", "c-shell", "output C shell code to set LS_COLORS")
-        .optflag("p", "print-database", "print the byte counts")
-        .parse(args);
+    let matches = uu_app().parse(args);
 
     if (matches.opt_present("csh")
         || matches.opt_present("c-shell")
@@ -145,6 +136,20 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     }
 }
 
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    app.optflag("b", "sh", "output Bourne shell code to set LS_COLORS")
+        .optflag(
+            "",
+            "bourne-shell",
+            "output Bourne shell code to set LS_COLORS",
+        )
+        .optflag("c", "csh", "output C shell code to set LS_COLORS")
+        .optflag("", "c-shell", "output C shell code to set LS_COLORS")
+        .optflag("p", "print-database", "print the byte counts");
+    app
+}
+
 pub trait StrUtils {
     /// Remove comments and trim whitespace
     fn purify(&self) -> &Self;
@@ -8,7 +8,8 @@
 #[macro_use]
 extern crate uucore;
 
-use std::path::Path;
+use std::path::is_separator;
+use uucore::coreopts::CoreOptions;
 
 static NAME: &str = "dirname";
 static SYNTAX: &str = "[OPTION] NAME...";
@@ -22,9 +23,7 @@ static LONG_HELP: &str = "
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = app!(SYNTAX, SUMMARY, LONG_HELP)
-        .optflag("z", "zero", "separate output with NUL rather than newline")
-        .parse(args);
+    let matches = uu_app().parse(args);
 
     let separator = if matches.opt_present("zero") {
         "\0"
@@ -32,32 +31,43 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         "\n"
     };
 
-    if !matches.free.is_empty() {
-        for path in &matches.free {
-            let p = Path::new(path);
-            match p.parent() {
-                Some(d) => {
-                    if d.components().next() == None {
-                        print!(".")
-                    } else {
-                        print!("{}", d.to_string_lossy());
-                    }
-                }
-                None => {
-                    if p.is_absolute() || path == "/" {
-                        print!("/");
-                    } else {
-                        print!(".");
-                    }
-                }
-            }
-            print!("{}", separator);
-        }
-    } else {
-        println!("{0}: missing operand", NAME);
-        println!("Try '{0} --help' for more information.", NAME);
+    if matches.free.is_empty() {
+        show_error!("missing operand");
+        eprintln!("Try '{0} --help' for more information.", NAME);
         return 1;
     }
 
+    for path in &matches.free {
+        print!("{}{}", dirname(path), separator);
+    }
+
     0
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    app.optflag("z", "zero", "separate output with NUL rather than newline");
+    app
+}
+
+/// The directory part of `path`, found on the string itself, as
+/// `Path::parent` would normalize "a/." into "a" and lose its parent
+fn dirname(path: &str) -> &str {
+    // Trailing separators don't start a new component
+    let trimmed = path.trim_end_matches(is_separator);
+    if trimmed.is_empty() {
+        return if path.is_empty() { "." } else { &path[..1] };
+    }
+
+    match trimmed.rfind(is_separator) {
+        None => ".",
+        Some(i) => {
+            let dir = trimmed[..i].trim_end_matches(is_separator);
+            if dir.is_empty() {
+                &trimmed[..1]
+            } else {
+                dir
+            }
+        }
+    }
+}
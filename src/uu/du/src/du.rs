@@ -10,16 +10,26 @@
 #[macro_use]
 extern crate uucore;
 
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::env;
+use std::ffi::OsStr;
 use std::fs;
-use std::io::{stderr, Result, Write};
+use std::io::{stderr, stdin, stdout, Read, Result, Write};
 use std::iter;
+use std::os::unix::ffi::OsStrExt;
 use std::os::unix::fs::MetadataExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
 use time::Timespec;
+use uucore::coreopts::CoreOptions;
+use uucore::fnmatch::fnmatch;
+use uucore::human::{BlockSize, BlockSizeError};
+use uucore::json;
 
 const NAME: &str = "du";
+const SYNTAX: &str = "[OPTION]... [FILE]...
+ du [OPTION]... --files0-from=F";
 const SUMMARY: &str = "estimate file space usage";
 const LONG_HELP: &str = "
  Display  values  are  in  units  of  the  first  available  SIZE from
@@ -32,15 +42,16 @@ const LONG_HELP: &str = "
  ers of 1000).
 ";
 
-// TODO: Support Z & Y (currently limited by size of u64)
-const UNITS: [(char, u32); 6] = [('E', 6), ('P', 5), ('T', 4), ('G', 3), ('M', 2), ('K', 1)];
-
 struct Options {
     all: bool,
     program_name: String,
     max_depth: Option<usize>,
     total: bool,
     separate_dirs: bool,
+    excludes: Arc<Vec<String>>,
+    count_links: bool,
+    // operands may overlap, so anything at all is only counted once
+    dedupe_all: bool,
 }
 
 struct Stat {
@@ -48,10 +59,14 @@ struct Stat {
     is_dir: bool,
     size: u64,
     blocks: u64,
+    // the number of inodes, one for each entry
+    inodes: u64,
     inode: u64,
-    created: u64,
-    accessed: u64,
-    modified: u64,
+    dev: u64,
+    nlink: u64,
+    accessed: Timespec,
+    changed: Timespec,
+    modified: Timespec,
 }
 
 impl Stat {
@@ -62,437 +77,719 @@ impl Stat {
             is_dir: metadata.is_dir(),
             size: metadata.len(),
             blocks: metadata.blocks() as u64,
+            inodes: 1,
             inode: metadata.ino() as u64,
-            created: metadata.mtime() as u64,
-            accessed: metadata.atime() as u64,
-            modified: metadata.mtime() as u64,
+            dev: metadata.dev() as u64,
+            nlink: metadata.nlink() as u64,
+            accessed: Timespec::new(metadata.atime(), metadata.atime_nsec() as i32),
+            changed: Timespec::new(metadata.ctime(), metadata.ctime_nsec() as i32),
+            modified: Timespec::new(metadata.mtime(), metadata.mtime_nsec() as i32),
         })
     }
+
+    /// The grand total of `--total`, before any operand is added to it.
+    fn total() -> Stat {
+        Stat {
+            path: PathBuf::from("total"),
+            is_dir: false,
+            size: 0,
+            blocks: 0,
+            inodes: 0,
+            inode: 0,
+            dev: 0,
+            nlink: 0,
+            accessed: Timespec::new(0, 0),
+            changed: Timespec::new(0, 0),
+            modified: Timespec::new(0, 0),
+        }
+    }
+
+    /// Account for `other`, which is inside this directory: its size adds
+    /// up, and its times count as the latest if they are.
+    fn add(&mut self, other: &Stat) {
+        self.size += other.size;
+        self.blocks += other.blocks;
+        self.inodes += other.inodes;
+        self.accessed = self.accessed.max(other.accessed);
+        self.changed = self.changed.max(other.changed);
+        self.modified = self.modified.max(other.modified);
+    }
 }
 
-fn unit_string_to_number(s: &str) -> Option<u64> {
-    let mut offset = 0;
-    let mut s_chars = s.chars().rev();
+/// Whether `stat` was counted already, and remember it if not. Unless
+/// `--count-links` is given, files with several hard links count only once.
+fn is_seen(stat: &Stat, options: &Options, seen: &mut HashSet<(u64, u64)>) -> bool {
+    let dedupe = !options.count_links && (options.dedupe_all || (!stat.is_dir && stat.nlink > 1));
+    dedupe && !seen.insert((stat.dev, stat.inode))
+}
 
-    let (mut ch, multiple) = match s_chars.next() {
-        Some('B') | Some('b') => ('B', 1000u64),
-        Some(ch) => (ch, 1024u64),
-        None => return None,
-    };
-    if ch == 'B' {
-        ch = s_chars.next()?;
-        offset += 1;
+/// Which entries `--threshold` leaves out.
+#[derive(Clone, Copy)]
+enum Threshold {
+    /// Those smaller than this size.
+    Lower(u64),
+    /// Those larger than this size.
+    Upper(u64),
+}
+
+impl Threshold {
+    fn parse(s: &str) -> std::result::Result<Threshold, BlockSizeError> {
+        let (negative, size) = if s.starts_with('-') {
+            (true, &s[1..])
+        } else {
+            (false, s)
+        };
+        let size = if !size.is_empty() && size.chars().all(|c| c.is_ascii_digit()) {
+            size.parse::<u64>().map_err(|_| BlockSizeError::TooLarge)?
+        } else {
+            BlockSize::parse(size)?
+                .bytes()
+                .ok_or(BlockSizeError::Invalid)?
+        };
+        match (negative, size) {
+            (true, 0) => Err(BlockSizeError::Invalid),
+            (true, size) => Ok(Threshold::Upper(size)),
+            (false, size) => Ok(Threshold::Lower(size)),
+        }
     }
-    ch = ch.to_ascii_uppercase();
 
-    let unit = UNITS
-        .iter()
-        .rev()
-        .find(|&&(unit_ch, _)| unit_ch == ch)
-        .map(|&(_, val)| {
-            // we found a match, so increment offset
-            offset += 1;
-            val
-        })
-        .or_else(|| if multiple == 1024 { Some(0) } else { None })?;
+    fn should_exclude(self, size: u64) -> bool {
+        match self {
+            Threshold::Lower(threshold) => size < threshold,
+            Threshold::Upper(threshold) => size > threshold,
+        }
+    }
+}
 
-    let number = s[..s.len() - offset].parse::<u64>().ok()?;
+/// Whether one of the wildcard `patterns` matches `path`, or the part of it
+/// after any `/`, so that a pattern can name a file wherever it is.
+fn is_excluded(path: &Path, patterns: &[String]) -> bool {
+    if patterns.is_empty() {
+        return false;
+    }
+    let path = path.to_string_lossy();
+    let suffixes = path
+        .match_indices('/')
+        .map(|(i, _)| &path[i + 1..])
+        .filter(|suffix| !suffix.starts_with('/'));
+    iter::once(&path[..])
+        .chain(suffixes)
+        .any(|name| patterns.iter().any(|pattern| fnmatch(pattern, name)))
+}
 
-    Some(number * multiple.pow(unit))
+/// Read the patterns in `file`, one per line, or from standard input if it
+/// is `-`.
+fn read_exclude_file(file: &str) -> Result<Vec<String>> {
+    let mut contents = String::new();
+    if file == "-" {
+        stdin().read_to_string(&mut contents)?;
+    } else {
+        fs::File::open(file)?.read_to_string(&mut contents)?;
+    }
+    Ok(contents.lines().map(String::from).collect())
 }
 
-fn translate_to_pure_number(s: &Option<String>) -> Option<u64> {
-    match *s {
-        Some(ref s) => unit_string_to_number(s),
-        None => None,
+/// Read the NUL-terminated file names in `file`, or standard input if it is
+/// `-`. Empty names are kept, to be reported.
+fn read_files0_from(file: &str) -> Result<Vec<Vec<u8>>> {
+    let mut contents = Vec::new();
+    if file == "-" {
+        stdin().read_to_end(&mut contents)?;
+    } else {
+        fs::File::open(file)?.read_to_end(&mut contents)?;
+    }
+    if contents.last() == Some(&0) {
+        contents.pop();
+    }
+    if contents.is_empty() {
+        return Ok(Vec::new());
     }
+    Ok(contents.split(|&b| b == 0).map(<[u8]>::to_vec).collect())
 }
 
-fn read_block_size(s: Option<String>) -> u64 {
-    match translate_to_pure_number(&s) {
-        Some(v) => v,
-        None => {
-            if let Some(value) = s {
-                show_error!("invalid --block-size argument '{}'", value);
-            };
+/// Print a line of the listing. The path is written as is, so that any file
+/// name comes out unchanged.
+fn print_entry(size: &str, time: Option<&str>, path: &Path, line_separator: &str) {
+    let stdout = stdout();
+    let mut out = stdout.lock();
+    let _ = write!(out, "{}\t", size);
+    if let Some(time) = time {
+        let _ = write!(out, "{}\t", time);
+    }
+    let _ = out.write_all(path.as_os_str().as_bytes());
+    let _ = out.write_all(line_separator.as_bytes());
+}
 
-            for env_var in &["DU_BLOCK_SIZE", "BLOCK_SIZE", "BLOCKSIZE"] {
-                if let Some(quantity) = translate_to_pure_number(&env::var(env_var).ok()) {
-                    return quantity;
-                }
-            }
+/// Print the `--json` record of an entry, with every size it has.
+fn print_record(stat: &Stat) {
+    let mut record = json::Object::new();
+    record
+        .string("path", &stat.path.to_string_lossy())
+        .number("size", stat.size)
+        // blocks are counted in units of 512 bytes
+        .number("disk_usage", stat.blocks * 512)
+        .number("inodes", stat.inodes);
+    for &(key, time) in &[
+        ("atime", stat.accessed),
+        ("mtime", stat.modified),
+        ("ctime", stat.changed),
+    ] {
+        record.timestamp(key, time.sec, time.nsec as u32);
+    }
+    println!("{}", record);
+}
 
-            if env::var("POSIXLY_CORRECT").is_ok() {
-                512
-            } else {
-                1024
-            }
+/// The block size given with `-B`, or else by the environment.
+fn read_block_size(s: Option<&str>) -> std::result::Result<BlockSize, BlockSizeError> {
+    match s {
+        Some(s) => BlockSize::parse(s),
+        None => Ok(BlockSize::from_env(&[
+            "DU_BLOCK_SIZE",
+            "BLOCK_SIZE",
+            "BLOCKSIZE",
+        ])),
+    }
+}
+
+/// The entries of a directory, each statted, in the order they were read,
+/// or why it couldn't be read.
+type Listing = Result<Vec<Result<Stat>>>;
+
+/// How many of the subdirectories of a directory are read ahead of the walk.
+const READ_AHEAD: usize = 64;
+
+/// Read the directory at `path`, leaving out the entries `excludes` matches.
+fn read_listing(path: &Path, excludes: &[String]) -> Listing {
+    let mut entries = vec![];
+    for entry in fs::read_dir(path)? {
+        match entry {
+            Ok(entry) if is_excluded(&entry.path(), excludes) => {}
+            Ok(entry) => entries.push(Stat::new(entry.path())),
+            Err(error) => entries.push(Err(error)),
         }
     }
+    Ok(entries)
+}
+
+/// Read the directory at `path` on the thread pool, for its listing to be
+/// ready by the time the walk gets to it.
+fn read_ahead(path: PathBuf, excludes: &Arc<Vec<String>>) -> Receiver<Listing> {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let excludes = Arc::clone(excludes);
+    rayon::spawn(move || {
+        // the walk may have dropped the receiver, having counted it already
+        let _ = sender.send(read_listing(&path, &excludes));
+    });
+    receiver
 }
 
-// this takes `my_stat` to avoid having to stat files multiple times.
-// XXX: this should use the impl Trait return type when it is stabilized
-fn du(
+/// Walk the tree under `my_stat`, which is `depth` levels below an operand
+/// and has the entries in `listing`, and give `report` the entries to list
+/// as soon as they are done with, each directory after its contents, in
+/// the order they were read. The totals of `my_stat` are returned.
+///
+/// Directories are read, and their entries statted, on a pool of as many
+/// threads as there are CPUs that steal work from each other, a few ahead
+/// of the walk. The walk itself goes through them in order, so that of
+/// several hard links to a file the first one is counted, as it is one at
+/// a time.
+///
+/// This takes `my_stat` to avoid having to stat files multiple times.
+fn du<F: FnMut(&Stat)>(
     mut my_stat: Stat,
+    listing: Listing,
     options: &Options,
     depth: usize,
-    inodes: &mut HashSet<u64>,
-) -> Box<dyn DoubleEndedIterator<Item = Stat>> {
-    let mut stats = vec![];
-    let mut futures = vec![];
-
-    if my_stat.is_dir {
-        let read = match fs::read_dir(&my_stat.path) {
-            Ok(read) => read,
-            Err(e) => {
-                safe_writeln!(
-                    stderr(),
-                    "{}: cannot read directory ‘{}‘: {}",
-                    options.program_name,
-                    my_stat.path.display(),
-                    e
-                );
-                return Box::new(iter::once(my_stat));
+    seen: &mut HashSet<(u64, u64)>,
+    report: &mut F,
+) -> Stat {
+    let entries = match listing {
+        Ok(entries) => entries,
+        Err(e) => {
+            safe_writeln!(
+                stderr(),
+                "{}: cannot read directory ‘{}‘: {}",
+                options.program_name,
+                my_stat.path.display(),
+                e
+            );
+            return my_stat;
+        }
+    };
+
+    let mut dirs = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            Ok(stat) if stat.is_dir => Some(stat.path.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .into_iter();
+    let mut ahead: VecDeque<Receiver<Listing>> = dirs
+        .by_ref()
+        .take(READ_AHEAD)
+        .map(|path| read_ahead(path, &options.excludes))
+        .collect();
+
+    let within_depth = options.max_depth.map_or(true, |max| depth < max);
+    for entry in entries {
+        let stat = match entry {
+            Ok(stat) => stat,
+            Err(error) => {
+                show_error!("{}", error);
+                continue;
             }
         };
-
-        for f in read {
-            match f {
-                Ok(entry) => match Stat::new(entry.path()) {
-                    Ok(this_stat) => {
-                        if this_stat.is_dir {
-                            futures.push(du(this_stat, options, depth + 1, inodes));
-                        } else {
-                            if inodes.contains(&this_stat.inode) {
-                                continue;
-                            }
-                            inodes.insert(this_stat.inode);
-                            my_stat.size += this_stat.size;
-                            my_stat.blocks += this_stat.blocks;
-                            if options.all {
-                                stats.push(this_stat);
-                            }
-                        }
-                    }
-                    Err(error) => show_error!("{}", error),
-                },
-                Err(error) => show_error!("{}", error),
+        let listing = if stat.is_dir {
+            if let Some(path) = dirs.next() {
+                ahead.push_back(read_ahead(path, &options.excludes));
             }
+            ahead.pop_front()
+        } else {
+            None
+        };
+        if is_seen(&stat, options, seen) {
+            continue;
         }
-    }
 
-    stats.extend(futures.into_iter().flatten().rev().filter(|stat| {
-        if !options.separate_dirs && stat.path.parent().unwrap() == my_stat.path {
-            my_stat.size += stat.size;
-            my_stat.blocks += stat.blocks;
+        let stat = match listing {
+            Some(listing) => {
+                let listing = listing.recv().unwrap();
+                du(stat, listing, options, depth + 1, seen, report)
+            }
+            None => stat,
+        };
+        if !stat.is_dir || !options.separate_dirs {
+            my_stat.add(&stat);
         }
-        options.max_depth == None || depth < options.max_depth.unwrap()
-    }));
-    stats.push(my_stat);
-    Box::new(stats.into_iter())
-}
-
-fn convert_size_human(size: u64, multiplier: u64, _block_size: u64) -> String {
-    for &(unit, power) in &UNITS {
-        let limit = multiplier.pow(power);
-        if size >= limit {
-            return format!("{:.1}{}", (size as f64) / (limit as f64), unit);
+        if within_depth && (stat.is_dir || options.all) {
+            report(&stat);
         }
     }
-    format!("{}B", size)
-}
-
-fn convert_size_b(size: u64, _multiplier: u64, _block_size: u64) -> String {
-    format!("{}", ((size as f64) / (1_f64)).ceil())
-}
-
-fn convert_size_k(size: u64, multiplier: u64, _block_size: u64) -> String {
-    format!("{}", ((size as f64) / (multiplier as f64)).ceil())
-}
-
-fn convert_size_m(size: u64, multiplier: u64, _block_size: u64) -> String {
-    format!(
-        "{}",
-        ((size as f64) / ((multiplier * multiplier) as f64)).ceil()
-    )
-}
-
-fn convert_size_other(size: u64, _multiplier: u64, block_size: u64) -> String {
-    format!("{}", ((size as f64) / (block_size as f64)).ceil())
+    my_stat
 }
 
 #[allow(clippy::cognitive_complexity)]
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let syntax = format!(
-        "[OPTION]... [FILE]...
- {0} [OPTION]... --files0-from=F",
-        NAME
-    );
-    let matches = app!(&syntax, SUMMARY, LONG_HELP)
-        // In task
-        .optflag(
-            "a",
-            "all",
-            " write counts for all files, not just directories",
-        )
-        // In main
-        .optflag(
-            "",
-            "apparent-size",
-            "print apparent sizes,  rather  than  disk  usage
-            although  the apparent  size is usually smaller, it may be larger due to holes
-            in ('sparse') files, internal  fragmentation,  indirect  blocks, and the like",
-        )
-        // In main
-        .optopt(
-            "B",
-            "block-size",
-            "scale sizes  by  SIZE before printing them.
-            E.g., '-BM' prints sizes in units of 1,048,576 bytes.  See SIZE format below.",
-            "SIZE",
-        )
-        // In main
-        .optflag(
-            "b",
-            "bytes",
-            "equivalent to '--apparent-size --block-size=1'",
-        )
-        // In main
-        .optflag("c", "total", "produce a grand total")
-        // In task
-        // opts.optflag("D", "dereference-args", "dereference only symlinks that are listed
-        //     on the command line"),
-        // In main
-        // opts.optopt("", "files0-from", "summarize disk usage of the NUL-terminated file
-        //                   names specified in file F;
-        //                   If F is - then read names from standard input", "F"),
-        // // In task
-        // opts.optflag("H", "", "equivalent to --dereference-args (-D)"),
-        // In main
-        .optflag(
-            "h",
-            "human-readable",
-            "print sizes in human readable format (e.g., 1K 234M 2G)",
-        )
-        // In main
-        .optflag("", "si", "like -h, but use powers of 1000 not 1024")
-        // In main
-        .optflag("k", "", "like --block-size=1K")
-        // In task
-        .optflag("l", "count-links", "count sizes many times if hard linked")
-        // // In main
-        .optflag("m", "", "like --block-size=1M")
-        // // In task
-        // opts.optflag("L", "dereference", "dereference all symbolic links"),
-        // // In task
-        // opts.optflag("P", "no-dereference", "don't follow any symbolic links (this is the default)"),
-        // // In main
-        .optflag(
-            "0",
-            "null",
-            "end each output line with 0 byte rather than newline",
-        )
-        // In main
-        .optflag(
-            "S",
-            "separate-dirs",
-            "do not include size of subdirectories",
-        )
-        // In main
-        .optflag("s", "summarize", "display only a total for each argument")
-        // // In task
-        // opts.optflag("x", "one-file-system", "skip directories on different file systems"),
-        // // In task
-        // opts.optopt("X", "exclude-from", "exclude files that match any pattern in FILE", "FILE"),
-        // // In task
-        // opts.optopt("", "exclude", "exclude files that match PATTERN", "PATTERN"),
-        // In main
-        .optopt(
-            "d",
-            "max-depth",
-            "print the total for a directory (or file, with --all)
-            only if it is N or fewer levels below the command
-            line argument;  --max-depth=0 is the same as --summarize",
-            "N",
-        )
-        // In main
-        .optflagopt(
-            "",
-            "time",
-            "show time of the last modification of any file in the
-            directory, or any of its subdirectories.  If WORD is given, show time as WORD instead
-            of modification time: atime, access, use, ctime or status",
-            "WORD",
-        )
-        // In main
-        .optopt(
-            "",
-            "time-style",
-            "show times using style STYLE:
-            full-iso, long-iso, iso, +FORMAT FORMAT is interpreted like 'date'",
-            "STYLE",
-        )
-        .parse(args);
+    let matches = uu_app().parse(args);
 
     let summarize = matches.opt_present("summarize");
 
     let max_depth_str = matches.opt_str("max-depth");
     let max_depth = max_depth_str.as_ref().and_then(|s| s.parse::<usize>().ok());
     match (max_depth_str, max_depth) {
-        (Some(ref s), _) if summarize => {
-            show_error!("summarizing conflicts with --max-depth={}", *s);
-            return 1;
-        }
         (Some(ref s), None) => {
             show_error!("invalid maximum depth '{}'", *s);
             return 1;
         }
+        (Some(ref s), Some(depth)) if summarize && depth != 0 => {
+            show_error!("summarizing conflicts with --max-depth={}", *s);
+            return 1;
+        }
         (Some(_), Some(_)) | (None, _) => { /* valid */ }
     }
 
+    let threshold = match matches.opt_str("threshold") {
+        Some(s) => match Threshold::parse(&s) {
+            Ok(threshold) => Some(threshold),
+            Err(e) => {
+                show_error!("{}", e.message("--threshold", &s));
+                return 1;
+            }
+        },
+        None => None,
+    };
+
+    let mut excludes = matches.opt_strs("exclude");
+    for file in matches.opt_strs("exclude-from") {
+        match read_exclude_file(&file) {
+            Ok(patterns) => excludes.extend(patterns),
+            Err(e) => {
+                show_error!("{}: {}", file, e);
+                return 1;
+            }
+        }
+    }
+
+    let mut status = 0;
+    let paths = match matches.opt_str("files0-from") {
+        Some(file) => {
+            if let Some(operand) = matches.free.first() {
+                show_usage_error!(
+                    "extra operand '{}'\nfile operands cannot be combined with --files0-from",
+                    operand
+                );
+                return 1;
+            }
+            let names = match read_files0_from(&file) {
+                Ok(names) => names,
+                Err(e) => {
+                    show_error!("cannot open '{}' for reading: {}", file, e);
+                    return 1;
+                }
+            };
+            let mut paths = Vec::new();
+            for (i, name) in names.iter().enumerate() {
+                if name.is_empty() {
+                    show_error!("{}:{}: invalid zero-length file name", file, i + 1);
+                    status = 1;
+                } else if file == "-" && name == b"-" {
+                    show_error!("when reading file names from stdin, no file name of '-' allowed");
+                    status = 1;
+                } else {
+                    paths.push(PathBuf::from(OsStr::from_bytes(name)));
+                }
+            }
+            paths
+        }
+        None if matches.free.is_empty() => vec![PathBuf::from("./")],
+        None => matches.free.iter().map(PathBuf::from).collect(),
+    };
+
     let options = Options {
         all: matches.opt_present("all"),
         program_name: NAME.to_owned(),
         max_depth,
         total: matches.opt_present("total"),
         separate_dirs: matches.opt_present("S"),
+        excludes: Arc::new(excludes),
+        count_links: matches.opt_present("count-links"),
+        dedupe_all: paths.len() > 1,
     };
 
-    let strs = if matches.free.is_empty() {
-        vec!["./".to_owned()]
-    } else {
-        matches.free.clone()
-    };
-
-    let block_size = read_block_size(matches.opt_str("block-size"));
+    let apparent_size = matches.opt_present("apparent-size") || matches.opt_present("b");
+    let inodes = matches.opt_present("inodes");
+    if inodes && apparent_size {
+        show_warning!("options --apparent-size and -b are ineffective with --inodes");
+    }
 
-    let multiplier: u64 = if matches.opt_present("si") {
-        1000
-    } else {
-        1024
+    // the last of the options that set the block size wins
+    let mut block_sizes: Vec<(usize, BlockSize)> = Vec::new();
+    for (pos, s) in matches.opt_strs_pos("block-size") {
+        match read_block_size(Some(&s)) {
+            Ok(block_size) => block_sizes.push((pos, block_size)),
+            Err(e) => {
+                show_error!("{}", e.message("--block-size", &s));
+                return 1;
+            }
+        }
+    }
+    for &(option, block_size) in &[
+        ("b", BlockSize::Fixed(1)),
+        ("k", BlockSize::Fixed(1024)),
+        ("m", BlockSize::Fixed(1024 * 1024)),
+        ("human-readable", BlockSize::Human(1024)),
+        ("si", BlockSize::Human(1000)),
+    ] {
+        block_sizes.extend(
+            matches
+                .opt_positions(option)
+                .into_iter()
+                .map(|pos| (pos, block_size)),
+        );
+    }
+    let block_size = match block_sizes.into_iter().max_by_key(|&(pos, _)| pos) {
+        Some((_, block_size)) => block_size,
+        None => read_block_size(None).unwrap(),
     };
-    let convert_size_fn = {
-        if matches.opt_present("human-readable") || matches.opt_present("si") {
-            convert_size_human
-        } else if matches.opt_present("b") {
-            convert_size_b
-        } else if matches.opt_present("k") {
-            convert_size_k
-        } else if matches.opt_present("m") {
-            convert_size_m
+    // inode counts aren't scaled
+    let convert_size = |size: u64| {
+        if inodes {
+            size.to_string()
         } else {
-            convert_size_other
+            block_size.format(size)
         }
     };
-    let convert_size = |size| convert_size_fn(size, multiplier, block_size);
-
-    let time_format_str = match matches.opt_str("time-style") {
-        Some(s) => match &s[..] {
-            "full-iso" => "%Y-%m-%d %H:%M:%S.%f %z",
-            "long-iso" => "%Y-%m-%d %H:%M",
-            "iso" => "%Y-%m-%d",
-            _ => {
+
+    // the time to show, the latest of any file in a directory
+    let time_field: Option<fn(&Stat) -> Timespec> = if matches.opt_present("time") {
+        match matches.opt_str("time").as_deref() {
+            None => Some(|stat| stat.modified),
+            Some("atime") | Some("access") | Some("use") => Some(|stat| stat.accessed),
+            Some("ctime") | Some("status") => Some(|stat| stat.changed),
+            Some(word) => {
                 show_error!(
-                    "invalid argument '{}' for 'time style'
+                    "invalid argument '{}' for '--time'
 Valid arguments are:
-- 'full-iso'
-- 'long-iso'
-- 'iso'
+  - 'atime', 'access', 'use'
+  - 'ctime', 'status'
 Try '{} --help' for more information.",
-                    s,
+                    word,
                     NAME
                 );
                 return 1;
             }
+        }
+    } else {
+        None
+    };
+
+    let time_style = match matches.opt_str("time-style") {
+        Some(style) => style,
+        None => match env::var("TIME_STYLE") {
+            // "locale" is only meaningful to ls
+            Ok(ref style) if style == "locale" => "long-iso".to_owned(),
+            Ok(style) => {
+                let mut style = &style[..];
+                while style.starts_with("posix-") {
+                    style = &style["posix-".len()..];
+                }
+                style.to_owned()
+            }
+            Err(_) => "long-iso".to_owned(),
         },
-        None => "%Y-%m-%d %H:%M",
     };
+    let time_format_str = match &time_style[..] {
+        "full-iso" => "%Y-%m-%d %H:%M:%S.%f %z".to_owned(),
+        "long-iso" => "%Y-%m-%d %H:%M".to_owned(),
+        "iso" => "%Y-%m-%d".to_owned(),
+        // only the first line of a format is used
+        style if style.starts_with('+') => {
+            style[1..].lines().next().unwrap_or("").replace("%N", "%f")
+        }
+        style if time_field.is_some() => {
+            show_error!(
+                "invalid argument '{}' for 'time style'
+Valid arguments are:
+  - 'full-iso'
+  - 'long-iso'
+  - 'iso'
+Try '{} --help' for more information.",
+                style,
+                NAME
+            );
+            return 1;
+        }
+        _ => String::new(),
+    };
+    if time_field.is_some() && time::now().strftime(&time_format_str).is_err() {
+        show_error!("invalid time style format '{}'", time_style);
+        return 1;
+    }
 
     let line_separator = if matches.opt_present("0") { "\0" } else { "\n" };
+    let json = matches.opt_present("json");
+    let measure = |stat: &Stat| {
+        if inodes {
+            stat.inodes
+        } else if apparent_size {
+            stat.size
+        } else {
+            // C's stat is such that each block is assume to be 512 bytes
+            // See: http://linux.die.net/man/2/stat
+            stat.blocks * 512
+        }
+    };
 
-    let mut grand_total = 0;
-    for path_str in strs {
-        let path = PathBuf::from(&path_str);
-        match Stat::new(path) {
+    // print an entry, unless it is left out by its size
+    let report = |stat: &Stat| {
+        let size = measure(stat);
+        if threshold.map_or(false, |threshold| threshold.should_exclude(size)) {
+            return;
+        }
+        if json {
+            print_record(stat);
+            return;
+        }
+        let time_str = time_field.map(|time_field| {
+            let tm = time::at(time_field(stat));
+            tm.strftime(&time_format_str).unwrap().to_string()
+        });
+        print_entry(
+            &convert_size(size),
+            time_str.as_deref(),
+            &stat.path,
+            line_separator,
+        );
+    };
+
+    let mut grand_total = Stat::total();
+    let mut seen = HashSet::new();
+    for path in paths {
+        if is_excluded(&path, &options.excludes) {
+            continue;
+        }
+        match Stat::new(path.clone()) {
             Ok(stat) => {
-                let mut inodes: HashSet<u64> = HashSet::new();
-
-                let iter = du(stat, &options, 0, &mut inodes);
-                let (_, len) = iter.size_hint();
-                let len = len.unwrap();
-                for (index, stat) in iter.enumerate() {
-                    let size = if matches.opt_present("apparent-size") || matches.opt_present("b") {
-                        stat.size
-                    } else {
-                        // C's stat is such that each block is assume to be 512 bytes
-                        // See: http://linux.die.net/man/2/stat
-                        stat.blocks * 512
-                    };
-                    if matches.opt_present("time") {
-                        let tm = {
-                            let (secs, nsecs) = {
-                                let time = match matches.opt_str("time") {
-                                    Some(s) => match &s[..] {
-                                        "accessed" => stat.accessed,
-                                        "created" => stat.created,
-                                        "modified" => stat.modified,
-                                        _ => {
-                                            show_error!(
-                                                "invalid argument 'modified' for '--time'
-    Valid arguments are:
-      - 'accessed', 'created', 'modified'
-    Try '{} --help' for more information.",
-                                                NAME
-                                            );
-                                            return 1;
-                                        }
-                                    },
-                                    None => stat.modified,
-                                };
-                                ((time / 1000) as i64, (time % 1000 * 1_000_000) as i32)
-                            };
-                            time::at(Timespec::new(secs, nsecs))
-                        };
-                        if !summarize || index == len - 1 {
-                            let time_str = tm.strftime(time_format_str).unwrap();
-                            print!(
-                                "{}\t{}\t{}{}",
-                                convert_size(size),
-                                time_str,
-                                stat.path.display(),
-                                line_separator
-                            );
+                if is_seen(&stat, &options, &mut seen) {
+                    continue;
+                }
+
+                let stat = if stat.is_dir {
+                    let listing = read_listing(&stat.path, &options.excludes);
+                    du(stat, listing, &options, 0, &mut seen, &mut |stat| {
+                        if !summarize {
+                            report(stat);
                         }
-                    } else if !summarize || index == len - 1 {
-                        print!(
-                            "{}\t{}{}",
-                            convert_size(size),
-                            stat.path.display(),
-                            line_separator
-                        );
-                    }
-                    if options.total && index == (len - 1) {
-                        // The last element will be the total size of the the path under
-                        // path_str.  We add it to the grand total.
-                        grand_total += size;
-                    }
+                    })
+                } else {
+                    stat
+                };
+                if options.total {
+                    grand_total.add(&stat);
                 }
+                report(&stat);
             }
             Err(_) => {
-                show_error!("{}: {}", path_str, "No such file or directory");
+                show_error!("{}: {}", path.display(), "No such file or directory");
+                status = 1;
             }
         }
     }
 
     if options.total {
-        print!("{}\ttotal", convert_size(grand_total));
-        print!("{}", line_separator);
+        if json {
+            print_record(&grand_total);
+        } else {
+            print_entry(
+                &convert_size(measure(&grand_total)),
+                None,
+                &grand_total.path,
+                line_separator,
+            );
+        }
     }
 
-    0
+    status
+}
+
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    // In task
+    app.optflag(
+        "a",
+        "all",
+        " write counts for all files, not just directories",
+    )
+    // In main
+    .optflag(
+        "",
+        "apparent-size",
+        "print apparent sizes,  rather  than  disk  usage
+            although  the apparent  size is usually smaller, it may be larger due to holes
+            in ('sparse') files, internal  fragmentation,  indirect  blocks, and the like",
+    )
+    // In main
+    .optflag(
+        "",
+        "inodes",
+        "list inode usage information instead of block usage",
+    )
+    // In main
+    .optopt(
+        "B",
+        "block-size",
+        "scale sizes  by  SIZE before printing them.
+            E.g., '-BM' prints sizes in units of 1,048,576 bytes.  See SIZE format below.",
+        "SIZE",
+    )
+    // In main
+    .optflag(
+        "b",
+        "bytes",
+        "equivalent to '--apparent-size --block-size=1'",
+    )
+    // In main
+    .optflag("c", "total", "produce a grand total")
+    // In main
+    .optopt(
+        "t",
+        "threshold",
+        "exclude entries smaller than SIZE if positive,
+            or entries greater than SIZE if negative",
+        "SIZE",
+    )
+    // In task
+    // opts.optflag("D", "dereference-args", "dereference only symlinks that are listed
+    //     on the command line"),
+    // In main
+    .optopt(
+        "",
+        "files0-from",
+        "summarize disk usage of the NUL-terminated file
+            names specified in file F; If F is - then read names from standard input",
+        "F",
+    )
+    // // In task
+    // opts.optflag("H", "", "equivalent to --dereference-args (-D)"),
+    // In main
+    .optflag(
+        "h",
+        "human-readable",
+        "print sizes in human readable format (e.g., 1K 234M 2G)",
+    )
+    // In main
+    .optflag("", "si", "like -h, but use powers of 1000 not 1024")
+    // In main
+    .optflag(
+        "",
+        "json",
+        "print a JSON object for each entry, one per line",
+    )
+    // In main
+    .optflag("k", "", "like --block-size=1K")
+    // In task
+    .optflag("l", "count-links", "count sizes many times if hard linked")
+    // // In main
+    .optflag("m", "", "like --block-size=1M")
+    // // In task
+    // opts.optflag("L", "dereference", "dereference all symbolic links"),
+    // // In task
+    // opts.optflag("P", "no-dereference", "don't follow any symbolic links (this is the default)"),
+    // // In main
+    .optflag(
+        "0",
+        "null",
+        "end each output line with 0 byte rather than newline",
+    )
+    // In main
+    .optflag(
+        "S",
+        "separate-dirs",
+        "do not include size of subdirectories",
+    )
+    // In main
+    .optflag("s", "summarize", "display only a total for each argument")
+    // // In task
+    // opts.optflag("x", "one-file-system", "skip directories on different file systems"),
+    // In task
+    .optmulti(
+        "X",
+        "exclude-from",
+        "exclude files that match any pattern in FILE",
+        "FILE",
+    )
+    // In task
+    .optmulti("", "exclude", "exclude files that match PATTERN", "PATTERN")
+    // In main
+    .optopt(
+        "d",
+        "max-depth",
+        "print the total for a directory (or file, with --all)
+            only if it is N or fewer levels below the command
+            line argument;  --max-depth=0 is the same as --summarize",
+        "N",
+    )
+    // In main
+    .optflagopt(
+        "",
+        "time",
+        "show time of the last modification of any file in the
+            directory, or any of its subdirectories.  If WORD is given, show time as WORD instead
+            of modification time: atime, access, use, ctime or status",
+        "WORD",
+    )
+    // In main
+    .optopt(
+        "",
+        "time-style",
+        "show times using style STYLE:
+            full-iso, long-iso, iso, +FORMAT FORMAT is interpreted like 'date'",
+        "STYLE",
+    );
+    app
 }
 
 #[cfg(test)]
@@ -500,29 +797,19 @@ mod test_du {
     #[allow(unused_imports)]
     use super::*;
 
-    #[test]
-    fn test_translate_to_pure_number() {
-        let test_data = [
-            (Some("10".to_string()), Some(10)),
-            (Some("10K".to_string()), Some(10 * 1024)),
-            (Some("5M".to_string()), Some(5 * 1024 * 1024)),
-            (Some("900KB".to_string()), Some(900 * 1000)),
-            (Some("BAD_STRING".to_string()), None),
-        ];
-        for it in test_data.into_iter() {
-            assert_eq!(translate_to_pure_number(&it.0), it.1);
-        }
-    }
-
     #[test]
     fn test_read_block_size() {
         let test_data = [
-            (Some("10".to_string()), 10),
-            (None, 1024),
-            (Some("BAD_STRING".to_string()), 1024),
+            (Some("10"), Ok(BlockSize::Fixed(10))),
+            (Some("10K"), Ok(BlockSize::Fixed(10 * 1024))),
+            (Some("5M"), Ok(BlockSize::Fixed(5 * 1024 * 1024))),
+            (Some("900KB"), Ok(BlockSize::Fixed(900 * 1000))),
+            (Some("si"), Ok(BlockSize::Human(1000))),
+            (None, Ok(BlockSize::Fixed(1024))),
+            (Some("BAD_STRING"), Err(BlockSizeError::InvalidSuffix)),
         ];
-        for it in test_data.into_iter() {
-            assert_eq!(read_block_size(it.0.clone()), it.1);
+        for it in test_data.iter() {
+            assert_eq!(read_block_size(it.0), it.1);
         }
     }
 }
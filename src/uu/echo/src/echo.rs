@@ -10,6 +10,7 @@
 extern crate uucore;
 
 use clap::{crate_version, App, Arg};
+use std::env;
 use std::io::{self, Write};
 use std::iter::Peekable;
 use std::str::Chars;
@@ -34,94 +35,155 @@ static AFTER_HELP: &str = r#"
  \\v      vertical tab
  \\0NNN   byte with octal value NNN (1 to 3 digits)
  \\xHH    byte with hexadecimal value HH (1 to 2 digits)
+ \\uHHHH  character with hexadecimal value HHHH (4 digits)
+ \\UHHHHHHHH  character with hexadecimal value HHHHHHHH (8 digits)
+
+ If POSIXLY_CORRECT is set, escapes are always recognized, and options only
+ when the first argument is -n.
 "#;
 
 mod options {
-    pub static STRING: &str = "STRING";
     pub static NO_NEWLINE: &str = "no_newline";
     pub static ENABLE_BACKSLASH_ESCAPE: &str = "enable_backslash_escape";
     pub static DISABLE_BACKSLASH_ESCAPE: &str = "disable_backslash_escape";
 }
 
-fn parse_code(
-    input: &mut Peekable<Chars>,
-    base: u32,
-    max_digits: u32,
-    bits_per_digit: u32,
-) -> Option<char> {
-    let mut ret = 0x8000_0000;
+/// The value of `start` followed by the up to `max_digits` digits in `base` at
+/// the start of `input`, or `None` if there are no digits.
+fn parse_code(input: &mut Peekable<Chars>, base: u32, max_digits: u32, start: u32) -> Option<u32> {
+    let mut ret = None;
     for _ in 0..max_digits {
         match input.peek().and_then(|c| c.to_digit(base)) {
-            Some(n) => ret = (ret << bits_per_digit) | n,
+            Some(n) => ret = Some(ret.unwrap_or(start) * base + n),
             None => break,
         }
         input.next();
     }
-    std::char::from_u32(ret)
+    ret
 }
 
-fn print_escaped(input: &str, mut output: impl Write) -> io::Result<bool> {
-    let mut should_stop = false;
-
-    let mut buffer = ['\\'; 2];
+/// The character of exactly `digits` hexadecimal digits at the start of
+/// `input`; unless there is one, `input` is left as it was.
+fn parse_unicode(input: &mut Peekable<Chars>, digits: usize) -> Option<char> {
+    let mut lookahead = input.clone();
+    let mut ret = 0;
+    for _ in 0..digits {
+        ret = ret * 16 + lookahead.next()?.to_digit(16)?;
+    }
+    let c = std::char::from_u32(ret)?;
+    *input = lookahead;
+    Some(c)
+}
 
+fn print_escaped(input: &str, mut output: impl Write) -> io::Result<bool> {
     let mut iter = input.chars().peekable();
-    while let Some(mut c) = iter.next() {
-        let mut start = 1;
-
-        if c == '\\' {
-            if let Some(next) = iter.next() {
-                c = match next {
-                    '\\' => '\\',
-                    'a' => '\x07',
-                    'b' => '\x08',
-                    'c' => {
-                        should_stop = true;
-                        break;
-                    }
-                    'e' => '\x1b',
-                    'f' => '\x0c',
-                    'n' => '\n',
-                    'r' => '\r',
-                    't' => '\t',
-                    'v' => '\x0b',
-                    'x' => parse_code(&mut iter, 16, 2, 4).unwrap_or_else(|| {
-                        start = 0;
-                        next
-                    }),
-                    '0' => parse_code(&mut iter, 8, 3, 3).unwrap_or_else(|| {
-                        start = 0;
-                        next
-                    }),
-                    _ => {
-                        start = 0;
-                        next
-                    }
-                };
-            }
-        }
-
-        buffer[1] = c;
-
-        // because printing char slices is apparently not available in the standard library
-        for ch in &buffer[start..] {
-            write!(output, "{}", ch)?;
+    while let Some(c) = iter.next() {
+        if c != '\\' {
+            write!(output, "{}", c)?;
+            continue;
         }
+        let next = match iter.next() {
+            Some(next) => next,
+            None => {
+                write!(output, "\\")?;
+                break;
+            }
+        };
+
+        // Numeric escapes are bytes, not characters: \0377 is 0xff, not ÿ in UTF-8
+        let byte = match next {
+            '\\' => b'\\',
+            'a' => b'\x07',
+            'b' => b'\x08',
+            'c' => return Ok(true),
+            'e' => b'\x1b',
+            'f' => b'\x0c',
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            'v' => b'\x0b',
+            'x' => match parse_code(&mut iter, 16, 2, 0) {
+                Some(n) => n as u8,
+                None => {
+                    write!(output, "\\x")?;
+                    continue;
+                }
+            },
+            // Like GNU, without digits it's NUL, \NNN works without the 0 too,
+            // and above 0o377 it wraps
+            '0' => parse_code(&mut iter, 8, 3, 0).unwrap_or(0) as u8,
+            '1'..='7' => {
+                let start = next.to_digit(8).unwrap();
+                parse_code(&mut iter, 8, 2, start).unwrap_or(start) as u8
+            }
+            'u' | 'U' => {
+                match parse_unicode(&mut iter, if next == 'u' { 4 } else { 8 }) {
+                    Some(ch) => write!(output, "{}", ch)?,
+                    None => write!(output, "\\{}", next)?,
+                }
+                continue;
+            }
+            _ => {
+                write!(output, "\\{}", next)?;
+                continue;
+            }
+        };
+        output.write_all(&[byte])?;
     }
 
-    Ok(should_stop)
+    Ok(false)
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = App::new(executable!())
+    // POSIX echo has no options, but GNU's still takes them after a first -n
+    let posixly_correct = env::var_os("POSIXLY_CORRECT").is_some();
+    let allow_options = !posixly_correct || args.get(1).map_or(false, |arg| arg == "-n");
+
+    // --help and --version only count on their own, `echo --help me` echoes
+    if allow_options && args.len() == 2 && (args[1] == "--help" || args[1] == "--version") {
+        uucore::help::get_matches_from(uu_app(), args);
+        return 0;
+    }
+
+    // Options are the leading arguments made only of the letters n, e and E,
+    // and the last of -e and -E wins; anything else starts the strings
+    let mut no_newline = false;
+    let mut escaped = false;
+    let mut strings = &args[1..];
+    if allow_options {
+        while let Some((arg, rest)) = strings.split_first() {
+            if arg.len() < 2
+                || !arg.starts_with('-')
+                || !arg[1..].chars().all(|c| "neE".contains(c))
+            {
+                break;
+            }
+            for c in arg[1..].chars() {
+                match c {
+                    'n' => no_newline = true,
+                    'e' => escaped = true,
+                    _ => escaped = false,
+                }
+            }
+            strings = rest;
+        }
+    }
+
+    // Even -E doesn't turn escapes off for POSIX
+    match execute(no_newline, escaped || posixly_correct, strings) {
+        Ok(_) => 0,
+        Err(f) => {
+            show_error!("{}", f);
+            1
+        }
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .name(NAME)
-        // TrailingVarArg specifies the final positional argument is a VarArg
-        // and it doesn't attempts the parse any further args.
-        // Final argument must have multiple(true) or the usage string equivalent.
-        .setting(clap::AppSettings::TrailingVarArg)
-        .setting(clap::AppSettings::AllowLeadingHyphen)
         .version(crate_version!())
         .usage(USAGE)
         .about(SUMMARY)
@@ -147,31 +209,9 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .takes_value(false)
                 .display_order(3),
         )
-        .arg(
-            Arg::with_name(options::STRING)
-                .hidden(true)
-                .multiple(true)
-                .allow_hyphen_values(true),
-        )
-        .get_matches_from(args);
-
-    let no_newline = matches.is_present(options::NO_NEWLINE);
-    let escaped = matches.is_present(options::ENABLE_BACKSLASH_ESCAPE);
-    let values: Vec<String> = match matches.values_of(options::STRING) {
-        Some(s) => s.map(|s| s.to_string()).collect(),
-        None => vec!["".to_string()],
-    };
-
-    match execute(no_newline, escaped, values) {
-        Ok(_) => 0,
-        Err(f) => {
-            show_error!("{}", f);
-            1
-        }
-    }
 }
 
-fn execute(no_newline: bool, escaped: bool, free: Vec<String>) -> io::Result<()> {
+fn execute(no_newline: bool, escaped: bool, free: &[String]) -> io::Result<()> {
     let stdout = io::stdout();
     let mut output = stdout.lock();
 
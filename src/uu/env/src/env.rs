@@ -7,28 +7,58 @@
 
 /* last synced with: env (GNU coreutils) 8.13 */
 
-// spell-checker:ignore (ToDO) execvp progname subcommand subcommands unsets
+// spell-checker:ignore (ToDO) execvp progname subcommand subcommands unsets sigprocmask
 
 #[macro_use]
 extern crate clap;
+#[macro_use]
+extern crate uucore;
+
+#[cfg(unix)]
+mod signals;
+mod split;
 
 use clap::{App, AppSettings, Arg};
 use ini::Ini;
 use std::borrow::Cow;
 use std::env;
+#[cfg(unix)]
+use std::ffi::CString;
 use std::io::{self, Write};
 use std::iter::Iterator;
+#[cfg(not(unix))]
 use std::process::Command;
+#[cfg(unix)]
+use std::ptr;
+use uucore::error_message;
 
 const USAGE: &str = "env [OPTION]... [-] [NAME=VALUE]... [COMMAND [ARG]...]";
 const AFTER_HELP: &str = "\
 A mere - implies -i. If no COMMAND, print the resulting environment.
+
+SIG may be a signal name like 'PIPE', or a signal number like '13'.
+Without SIG, all known signals are included. Multiple signals can be
+comma-separated.
 ";
 
+// exit codes that match the GNU implementation
+static EXIT_CANCELED: i32 = 125;
+static EXIT_CANNOT_INVOKE: i32 = 126;
+static EXIT_ENOENT: i32 = 127;
+
+// the options that take a value, which can be the next argument
+const VALUE_OPTIONS: &[(char, &str)] = &[
+    ('a', "argv0"),
+    ('C', "chdir"),
+    ('f', "file"),
+    ('u', "unset"),
+];
+
 struct Options<'a> {
     ignore_env: bool,
     null: bool,
     running_directory: Option<&'a str>,
+    argv0: Option<&'a str>,
     files: Vec<&'a str>,
     unsets: Vec<&'a str>,
     sets: Vec<(&'a str, &'a str)>,
@@ -63,7 +93,7 @@ fn parse_program_opt<'a>(opts: &mut Options<'a>, opt: &'a str) -> Result<(), i32
     if opts.null {
         eprintln!("{}: cannot specify --null (-0) with command", crate_name!());
         eprintln!("Type \"{} --help\" for detailed information", crate_name!());
-        Err(1)
+        Err(EXIT_CANCELED)
     } else {
         opts.program.push(opt);
         Ok(())
@@ -86,7 +116,7 @@ fn load_config_file(opts: &mut Options) -> Result<(), i32> {
             Ok(config) => config,
             Err(error) => {
                 eprintln!("env: error: \"{}\": {}", file, error);
-                return Err(1);
+                return Err(EXIT_CANCELED);
             }
         };
 
@@ -117,8 +147,51 @@ fn build_command<'a, 'b>(args: &'a mut Vec<&'b str>) -> (Cow<'b, str>, &'a [&'b
     (progname, &args[..])
 }
 
-fn create_app() -> App<'static, 'static> {
-    App::new(crate_name!())
+// replace env with the program, which then inherits everything it set up; it gets argv0, if
+// given, rather than its name as its zeroth argument
+#[cfg(unix)]
+fn run_program(prog: &str, argv0: Option<&str>, args: &[&str]) -> Result<(), i32> {
+    let file = CString::new(prog).unwrap();
+    let argv: Vec<CString> = std::iter::once(argv0.unwrap_or(prog))
+        .chain(args.iter().cloned())
+        .map(|arg| CString::new(arg).unwrap())
+        .collect();
+    let mut argv: Vec<*const libc::c_char> = argv.iter().map(|arg| arg.as_ptr()).collect();
+    argv.push(ptr::null());
+    unsafe { libc::execvp(file.as_ptr(), argv.as_ptr()) };
+
+    // execvp() only returns if it failed
+    let err = io::Error::last_os_error();
+    eprintln!("{}: '{}': {}", crate_name!(), prog, error_message(&err));
+    if err.raw_os_error() == Some(libc::ENOENT) {
+        Err(EXIT_ENOENT)
+    } else {
+        Err(EXIT_CANNOT_INVOKE)
+    }
+}
+
+#[cfg(not(unix))]
+fn run_program(prog: &str, _argv0: Option<&str>, args: &[&str]) -> Result<(), i32> {
+    match Command::new(prog).args(args).status() {
+        Ok(exit) => {
+            if !exit.success() {
+                return Err(exit.code().unwrap());
+            }
+        }
+        Err(err) => {
+            eprintln!("{}: '{}': {}", crate_name!(), prog, error_message(&err));
+            return Err(if err.kind() == io::ErrorKind::NotFound {
+                EXIT_ENOENT
+            } else {
+                EXIT_CANNOT_INVOKE
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn uu_app() -> App<'static, 'static> {
+    let app = App::new(executable!())
         .version(crate_version!())
         .author(crate_authors!())
         .about(crate_description!())
@@ -130,7 +203,7 @@ fn create_app() -> App<'static, 'static> {
             .long("ignore-environment")
             .help("start with an empty environment"))
         .arg(Arg::with_name("chdir")
-            .short("c")
+            .short("C")
             .long("chdir")
             .takes_value(true)
             .number_of_values(1)
@@ -158,15 +231,213 @@ fn create_app() -> App<'static, 'static> {
             .value_name("NAME")
             .multiple(true)
             .help("remove variable from the environment"))
+        .arg(Arg::with_name("split-string")
+            .short("S")
+            .long("split-string")
+            .takes_value(true)
+            .value_name("S")
+            .help("process and split S into separate arguments; used to pass multiple arguments \
+                    on shebang lines"));
+
+    #[cfg(unix)]
+    let app = app
+        .arg(
+            Arg::with_name("argv0")
+                .short("a")
+                .long("argv0")
+                .takes_value(true)
+                .number_of_values(1)
+                .value_name("ARG")
+                .help("pass ARG as the zeroth argument of COMMAND"),
+        )
+        .arg(
+            Arg::with_name("block-signal")
+                .long("block-signal")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .multiple(true)
+                .value_name("SIG")
+                .help("block delivery of SIG signal(s) to COMMAND"),
+        )
+        .arg(
+            Arg::with_name("default-signal")
+                .long("default-signal")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .multiple(true)
+                .value_name("SIG")
+                .help("reset handling of SIG signal(s) to the default"),
+        )
+        .arg(
+            Arg::with_name("ignore-signal")
+                .long("ignore-signal")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .multiple(true)
+                .value_name("SIG")
+                .help("set handling of SIG signal(s) to do nothing"),
+        )
+        .arg(
+            Arg::with_name("list-signal-handling")
+                .long("list-signal-handling")
+                .help("list non default signal handling to stderr"),
+        );
+
+    app
+}
+
+// the handling of signals the signal options ask for; all signals, asked for by an option without
+// a value, first, then the named ones in the order they're given, so the last of them wins
+#[cfg(unix)]
+fn signal_handling(matches: &clap::ArgMatches) -> Result<signals::Handling, String> {
+    let mut handling = signals::Handling::default();
+    let mut named = vec![];
+    for &(name, action) in &[
+        ("default-signal", signals::Action::Default),
+        ("ignore-signal", signals::Action::Ignore),
+    ] {
+        let values: Vec<&str> = matches
+            .values_of(name)
+            .map(Iterator::collect)
+            .unwrap_or_default();
+        if matches.occurrences_of(name) > values.len() as u64 {
+            handling.set_all(action);
+        }
+        if let Some(indices) = matches.indices_of(name) {
+            named.extend(
+                indices
+                    .zip(values)
+                    .map(|(idx, signals)| (idx, signals, action)),
+            );
+        }
+    }
+    named.sort_by_key(|&(idx, _, _)| idx);
+    for (_, signals, action) in named {
+        handling.set(signals, action)?;
+    }
+
+    let blocked: Vec<&str> = matches
+        .values_of("block-signal")
+        .map(Iterator::collect)
+        .unwrap_or_default();
+    if matches.occurrences_of("block-signal") > blocked.len() as u64 {
+        handling.block_all();
+    }
+    for signals in blocked {
+        handling.block(signals)?;
+    }
+
+    Ok(handling)
+}
+
+// replace each -S string among the options with the arguments it splits into, which are then
+// read as options in turn, the way getopt() goes on through them in GNU env
+fn split_strings(mut args: Vec<String>) -> Result<Vec<String>, i32> {
+    let mut i = 1;
+    while i < args.len() {
+        let arg = &args[i];
+        if arg == "-" || arg == "--" || !arg.starts_with('-') {
+            // the options are over
+            break;
+        }
+
+        // the options before the -S in a cluster, the -S string and the number of arguments
+        // they span
+        let found = if arg.starts_with("--") {
+            match arg.find('=') {
+                Some(idx) if &arg[2..idx] == "split-string" => {
+                    Some((None, arg[idx + 1..].to_owned(), 1))
+                }
+                Some(_) => None,
+                None if arg == "--split-string" => match args.get(i + 1) {
+                    Some(string) => Some((None, string.clone(), 2)),
+                    None => break,
+                },
+                None => {
+                    if VALUE_OPTIONS.iter().any(|&(_, long)| &arg[2..] == long) {
+                        // skip the value
+                        i += 1;
+                    }
+                    None
+                }
+            }
+        } else {
+            match arg[1..].find(|c| c == 'S' || VALUE_OPTIONS.iter().any(|&(short, _)| c == short))
+            {
+                Some(idx) if arg[idx + 1..].starts_with('S') => {
+                    let before = if idx > 0 {
+                        Some(arg[..=idx].to_owned())
+                    } else {
+                        None
+                    };
+                    match &arg[idx + 2..] {
+                        "" => match args.get(i + 1) {
+                            Some(string) => Some((before, string.clone(), 2)),
+                            None => break,
+                        },
+                        string => Some((before, string.to_owned(), 1)),
+                    }
+                }
+                Some(idx) => {
+                    if idx + 2 == arg.len() {
+                        // skip the value
+                        i += 1;
+                    }
+                    None
+                }
+                None => None,
+            }
+        };
+        let (before, string, len) = match found {
+            Some(found) => found,
+            None => {
+                i += 1;
+                continue;
+            }
+        };
+
+        let split = match split::split(&string) {
+            Ok(split) => split,
+            Err(msg) => {
+                eprintln!("{}: {}", crate_name!(), msg);
+                return Err(EXIT_CANCELED);
+            }
+        };
+        args.splice(i..i + len, before.into_iter().chain(split));
+    }
+
+    Ok(args)
 }
 
 fn run_env(args: impl uucore::Args) -> Result<(), i32> {
-    let app = create_app();
-    let matches = app.get_matches_from(args);
+    let args = split_strings(args.collect_str())?;
+    let app = uu_app();
+    let matches = match uucore::help::get_matches_from_safe(app, args) {
+        Ok(matches) => matches,
+        Err(e) => {
+            // like the failures of env itself, rather than of the command
+            eprintln!("{}", e);
+            return Err(EXIT_CANCELED);
+        }
+    };
+
+    #[cfg(unix)]
+    let handling = match signal_handling(&matches) {
+        Ok(handling) => handling,
+        Err(msg) => {
+            eprintln!("{}: {}", crate_name!(), msg);
+            eprintln!("Type \"{} --help\" for detailed information", crate_name!());
+            return Err(EXIT_CANCELED);
+        }
+    };
 
     let ignore_env = matches.is_present("ignore-environment");
     let null = matches.is_present("null");
     let running_directory = matches.value_of("chdir");
+    let argv0 = matches.value_of("argv0");
     let files = matches
         .values_of("file")
         .map(Iterator::collect)
@@ -180,23 +451,13 @@ fn run_env(args: impl uucore::Args) -> Result<(), i32> {
         ignore_env,
         null,
         running_directory,
+        argv0,
         files,
         unsets,
         sets: vec![],
         program: vec![],
     };
 
-    // change directory
-    if let Some(d) = opts.running_directory {
-        match env::set_current_dir(d) {
-            Ok(()) => d,
-            Err(error) => {
-                eprintln!("env: cannot change directory to \"{}\": {}", d, error);
-                return Err(125);
-            }
-        };
-    }
-
     // we handle the name, value pairs and the program to be executed by treating them as external
     // subcommands in clap
     if let (external, Some(matches)) = matches.subcommand() {
@@ -226,6 +487,17 @@ fn run_env(args: impl uucore::Args) -> Result<(), i32> {
         }
     }
 
+    if opts.running_directory.is_some() && opts.program.is_empty() {
+        eprintln!("{}: must specify command with --chdir (-C)", crate_name!());
+        eprintln!("Type \"{} --help\" for detailed information", crate_name!());
+        return Err(EXIT_CANCELED);
+    }
+    if opts.argv0.is_some() && opts.program.is_empty() {
+        eprintln!("{}: must specify command with --argv0 (-a)", crate_name!());
+        eprintln!("Type \"{} --help\" for detailed information", crate_name!());
+        return Err(EXIT_CANCELED);
+    }
+
     // NOTE: we manually set and unset the env vars below rather than using Command::env() to more
     //       easily handle the case where no command is given
 
@@ -251,19 +523,34 @@ fn run_env(args: impl uucore::Args) -> Result<(), i32> {
     }
 
     if !opts.program.is_empty() {
-        // we need to execute a command
-        let (prog, args) = build_command(&mut opts.program);
+        // change directory
+        if let Some(d) = opts.running_directory {
+            if let Err(error) = env::set_current_dir(d) {
+                eprintln!(
+                    "{}: cannot change directory to '{}': {}",
+                    crate_name!(),
+                    d,
+                    error_message(&error)
+                );
+                return Err(EXIT_CANCELED);
+            }
+        }
 
-        // FIXME: this should just use execvp() (no fork()) on Unix-like systems
-        match Command::new(&*prog).args(args).status() {
-            Ok(exit) => {
-                if !exit.success() {
-                    return Err(exit.code().unwrap());
-                }
+        // the command keeps the signal handling env sets up for itself
+        #[cfg(unix)]
+        {
+            if let Err(msg) = handling.apply() {
+                eprintln!("{}: {}", crate_name!(), msg);
+                return Err(EXIT_CANCELED);
+            }
+            if matches.is_present("list-signal-handling") {
+                signals::list();
             }
-            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Err(127),
-            Err(_) => return Err(126),
         }
+
+        // we need to execute a command
+        let (prog, args) = build_command(&mut opts.program);
+        run_program(&prog, opts.argv0, args)?;
     } else {
         // no program provided, so just dump all env vars to stdout
         print_env(opts.null);
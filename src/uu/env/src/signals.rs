@@ -0,0 +1,161 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! The handling of signals env sets up for the command it runs, which keeps
+//! it across `exec`.
+
+// spell-checker:ignore (ToDO) sigaction sigprocmask sigset sigaddset sigemptyset sigismember
+
+use crate::error_message;
+use libc::{c_int, sighandler_t};
+use std::io::{Error, Result as IOResult};
+use std::mem;
+use std::ptr;
+use uucore::signals::{signal_by_name_or_value, ALL_SIGNALS};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Default,
+    Ignore,
+}
+
+/// How signals are to be handled, by the signal options.
+pub struct Handling {
+    /// the action each signal, by number, is to get, and whether it was
+    /// named; only a failure for one named is an error
+    actions: Vec<Option<(Action, bool)>>,
+    blocked: Vec<usize>,
+}
+
+impl Default for Handling {
+    fn default() -> Handling {
+        let mut actions = vec![None; ALL_SIGNALS.len() + 1];
+        // the Rust runtime ignores SIGPIPE, which the command isn't to inherit
+        actions[libc::SIGPIPE as usize] = Some((Action::Default, false));
+        Handling {
+            actions,
+            blocked: Vec::new(),
+        }
+    }
+}
+
+impl Handling {
+    /// Have every signal that can be handled get `action`.
+    pub fn set_all(&mut self, action: Action) {
+        for signal in ALL_SIGNALS.iter() {
+            if signal.value != libc::SIGKILL as usize && signal.value != libc::SIGSTOP as usize {
+                self.actions[signal.value] = Some((action, false));
+            }
+        }
+    }
+
+    /// Have the signals in the comma-separated list `signals` get `action`.
+    pub fn set(&mut self, signals: &str, action: Action) -> Result<(), String> {
+        for signal in parse(signals)? {
+            self.actions[signal] = Some((action, true));
+        }
+        Ok(())
+    }
+
+    /// Block delivery of every signal.
+    pub fn block_all(&mut self) {
+        self.blocked = ALL_SIGNALS.iter().map(|signal| signal.value).collect();
+    }
+
+    /// Block delivery of the signals in the comma-separated list `signals`.
+    pub fn block(&mut self, signals: &str) -> Result<(), String> {
+        self.blocked.extend(parse(signals)?);
+        Ok(())
+    }
+
+    /// Put the handling into effect for this process, and so for the program
+    /// it goes on to run.
+    pub fn apply(&self) -> Result<(), String> {
+        for (signal, action) in self.actions.iter().enumerate() {
+            if let Some((action, named)) = *action {
+                let handler = match action {
+                    Action::Default => libc::SIG_DFL,
+                    Action::Ignore => libc::SIG_IGN,
+                };
+                match set_handler(signal as c_int, handler) {
+                    Err(e) if named => {
+                        return Err(format!(
+                            "failed to set signal action for signal {}: {}",
+                            signal,
+                            error_message(&e)
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if !self.blocked.is_empty() {
+            let mut set: libc::sigset_t = unsafe { mem::zeroed() };
+            unsafe {
+                libc::sigemptyset(&mut set);
+                for &signal in &self.blocked {
+                    libc::sigaddset(&mut set, signal as c_int);
+                }
+            }
+            if unsafe { libc::sigprocmask(libc::SIG_BLOCK, &set, ptr::null_mut()) } != 0 {
+                return Err(format!(
+                    "failed to set signal process mask: {}",
+                    error_message(&Error::last_os_error())
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// List on stderr the signals that are blocked or ignored.
+pub fn list() {
+    let mut mask: libc::sigset_t = unsafe { mem::zeroed() };
+    unsafe {
+        libc::sigemptyset(&mut mask);
+        libc::sigprocmask(libc::SIG_BLOCK, ptr::null(), &mut mask);
+    }
+    for signal in ALL_SIGNALS.iter() {
+        let mut action: libc::sigaction = unsafe { mem::zeroed() };
+        if unsafe { libc::sigaction(signal.value as c_int, ptr::null(), &mut action) } != 0 {
+            continue;
+        }
+        let blocked = unsafe { libc::sigismember(&mask, signal.value as c_int) } == 1;
+        let ignored = action.sa_sigaction == libc::SIG_IGN;
+        let state = match (blocked, ignored) {
+            (true, true) => "BLOCK,IGNORE",
+            (true, false) => "BLOCK",
+            (false, true) => "IGNORE",
+            (false, false) => continue,
+        };
+        eprintln!("{:<10} ({:>2}): {}", signal.name, signal.value, state);
+    }
+}
+
+fn set_handler(signal: c_int, handler: sighandler_t) -> IOResult<()> {
+    let mut action: libc::sigaction = unsafe { mem::zeroed() };
+    action.sa_sigaction = handler;
+    if unsafe { libc::sigaction(signal, &action, ptr::null_mut()) } == 0 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+/// The numbers of the signals in the comma-separated list `signals`, which
+/// has their names, with or without "SIG", or numbers.
+fn parse(signals: &str) -> Result<Vec<usize>, String> {
+    signals
+        .split(',')
+        .filter(|signal| !signal.is_empty())
+        .map(|signal| {
+            signal_by_name_or_value(&signal.to_uppercase())
+                .filter(|&value| value != 0)
+                .ok_or_else(|| format!("'{}': invalid signal", signal))
+        })
+        .collect()
+}
@@ -0,0 +1,131 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! The splitting of a `-S` string into arguments, by the quoting, escaping
+//! and expansion rules GNU env has for it.
+
+// spell-checker:ignore (ToDO) VARNAME
+
+use std::env;
+use std::str::Chars;
+
+/// What a backslash and the character after it stand for.
+enum Escape {
+    Char(char),
+    /// `\_`, which separates arguments outside of quotes
+    Separator,
+    /// `\c`, which ends the string
+    Stop,
+}
+
+/// The arguments in `s`, or the message for why it can't be split.
+pub fn split(s: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    // the argument being read, if one has started; "" is one, but nothing
+    // isn't
+    let mut arg: Option<String> = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' | '\x0b' | '\x0c' | '\r' => args.extend(arg.take()),
+            // a comment runs to the end of the string
+            '#' if arg.is_none() => break,
+            '\'' => {
+                let arg = arg.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        // only these two can be escaped in single quotes
+                        Some('\\') if chars.as_str().starts_with(&['\\', '\''][..]) => {
+                            arg.extend(chars.next())
+                        }
+                        Some(c) => arg.push(c),
+                        None => return Err(no_terminating_quote()),
+                    }
+                }
+            }
+            '"' => {
+                let arg = arg.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match escape(&mut chars)? {
+                            Escape::Char(c) => arg.push(c),
+                            Escape::Separator => arg.push(' '),
+                            Escape::Stop => {
+                                return Err(
+                                    "'\\c' must not appear in double-quoted -S string".to_owned()
+                                )
+                            }
+                        },
+                        Some('$') => arg.push_str(&expand(&mut chars)?),
+                        Some(c) => arg.push(c),
+                        None => return Err(no_terminating_quote()),
+                    }
+                }
+            }
+            '\\' => match escape(&mut chars)? {
+                Escape::Char(c) => arg.get_or_insert_with(String::new).push(c),
+                Escape::Separator => args.extend(arg.take()),
+                Escape::Stop => break,
+            },
+            '$' => {
+                let value = expand(&mut chars)?;
+                if !value.is_empty() {
+                    arg.get_or_insert_with(String::new).push_str(&value);
+                }
+            }
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(arg);
+    Ok(args)
+}
+
+/// Read the escape sequence `chars` is at, just past its backslash.
+fn escape(chars: &mut Chars) -> Result<Escape, String> {
+    Ok(Escape::Char(match chars.next() {
+        Some('_') => return Ok(Escape::Separator),
+        Some('c') => return Ok(Escape::Stop),
+        Some('f') => '\x0c',
+        Some('n') => '\n',
+        Some('r') => '\r',
+        Some('t') => '\t',
+        Some('v') => '\x0b',
+        Some(c @ '"') | Some(c @ '\'') | Some(c @ '#') | Some(c @ '$') | Some(c @ '\\') => c,
+        Some(c) => return Err(format!("invalid sequence '\\{}' in -S", c)),
+        None => return Err("invalid backslash at end of string in -S".to_owned()),
+    }))
+}
+
+/// The value of the variable `chars` names as `{NAME}`, just past its `$`;
+/// nothing if it isn't set.
+fn expand(chars: &mut Chars) -> Result<String, String> {
+    let rest = chars.as_str();
+    let name = rest
+        .find('}')
+        .filter(|_| rest.starts_with('{'))
+        .map(|end| &rest[1..end])
+        .filter(|name| {
+            name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    match name {
+        Some(name) => {
+            *chars = rest[name.len() + 2..].chars();
+            Ok(env::var_os(name)
+                .map(|value| value.to_string_lossy().into_owned())
+                .unwrap_or_default())
+        }
+        None => Err(format!(
+            "only ${{VARNAME}} expansion is supported, error at: ${}",
+            rest
+        )),
+    }
+}
+
+fn no_terminating_quote() -> String {
+    "no terminating quote in -S string".to_owned()
+}
@@ -7,7 +7,7 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) ctype cwidth iflag nbytes nspaces nums tspaces uflag
+// spell-checker:ignore (ToDO) ctype cwidth iflag nbytes uflag
 
 #[macro_use]
 extern crate uucore;
@@ -15,9 +15,10 @@ extern crate uucore;
 use clap::{App, Arg, ArgMatches};
 use std::fs::File;
 use std::io::{stdin, stdout, BufRead, BufReader, BufWriter, Read, Write};
-use std::iter::repeat;
+use std::cmp::min;
 use std::str::from_utf8;
 use unicode_width::UnicodeWidthChar;
+use uucore::tabstops::TabStops;
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static ABOUT: &str = "Convert tabs in each FILE to spaces, writing to standard output.
@@ -32,67 +33,31 @@ pub mod options {
 
 static LONG_HELP: &str = "";
 
-static DEFAULT_TABSTOP: usize = 8;
-
 fn get_usage() -> String {
     format!("{0} [OPTION]... [FILE]...", executable!())
 }
 
-fn tabstops_parse(s: String) -> Vec<usize> {
-    let words = s.split(',');
-
-    let nums = words
-        .map(|sn| {
-            sn.parse::<usize>()
-                .unwrap_or_else(|_| crash!(1, "{}\n", "tab size contains invalid character(s)"))
-        })
-        .collect::<Vec<usize>>();
-
-    if nums.iter().any(|&n| n == 0) {
-        crash!(1, "{}\n", "tab size cannot be 0");
-    }
-
-    if let (false, _) = nums
-        .iter()
-        .fold((true, 0), |(acc, last), &n| (acc && last <= n, n))
-    {
-        crash!(1, "{}\n", "tab sizes must be ascending");
-    }
-
-    nums
-}
-
 struct Options {
     files: Vec<String>,
-    tabstops: Vec<usize>,
-    tspaces: String,
+    tabstops: TabStops,
     iflag: bool,
     uflag: bool,
 }
 
 impl Options {
     fn new(matches: &ArgMatches) -> Options {
-        let tabstops = match matches.value_of(options::TABS) {
-            Some(s) => tabstops_parse(s.to_string()),
-            None => vec![DEFAULT_TABSTOP],
+        // every -t option adds to the list of tab stops
+        let tabstops = match matches.values_of(options::TABS) {
+            Some(lists) => match TabStops::parse(&lists.collect::<Vec<_>>().join(",")) {
+                Ok(tabstops) => tabstops,
+                Err(e) => crash!(1, "{}", e),
+            },
+            None => TabStops::default(),
         };
 
         let iflag = matches.is_present(options::INITIAL);
         let uflag = !matches.is_present(options::NO_UTF8);
 
-        // avoid allocations when dumping out long sequences of spaces
-        // by precomputing the longest string of spaces we will ever need
-        let nspaces = tabstops
-            .iter()
-            .scan(0, |pr, &it| {
-                let ret = Some(it - *pr);
-                *pr = it;
-                ret
-            })
-            .max()
-            .unwrap(); // length of tabstops is guaranteed >= 1
-        let tspaces = repeat(' ').take(nspaces).collect();
-
         let files: Vec<String> = match matches.values_of(options::FILES) {
             Some(s) => s.map(|v| v.to_string()).collect(),
             None => vec!["-".to_owned()],
@@ -101,7 +66,6 @@ impl Options {
         Options {
             files,
             tabstops,
-            tspaces,
             iflag,
             uflag,
         }
@@ -110,10 +74,16 @@ impl Options {
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    expand(Options::new(&matches));
+    0
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .after_help(LONG_HELP)
         .arg(
             Arg::with_name(options::INITIAL)
@@ -127,7 +97,11 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .short("t")
                 .value_name("N, LIST")
                 .takes_value(true)
-                .help("have tabs N characters apart, not 8 or use comma separated list of explicit tab positions"),
+                .multiple(true)
+                .number_of_values(1)
+                .help("have tabs N characters apart, not 8, or use a comma separated list of explicit tab positions; \
+                       the last position may be prefixed with '/' to continue with tab stops at every multiple of it, \
+                       or with '+' to continue with tab stops that many columns apart"),
         )
         .arg(
             Arg::with_name(options::NO_UTF8)
@@ -140,10 +114,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .hidden(true)
                 .takes_value(true)
         )
-        .get_matches_from(args);
-
-    expand(Options::new(&matches));
-    0
 }
 
 fn open(path: String) -> BufReader<Box<dyn Read + 'static>> {
@@ -159,14 +129,13 @@ fn open(path: String) -> BufReader<Box<dyn Read + 'static>> {
     }
 }
 
-fn next_tabstop(tabstops: &[usize], col: usize) -> usize {
-    if tabstops.len() == 1 {
-        tabstops[0] - col % tabstops[0]
-    } else {
-        match tabstops.iter().find(|&&t| t > col) {
-            Some(t) => t - col,
-            None => 1,
-        }
+/// Write `n` spaces without allocating.
+fn write_spaces<W: Write>(output: &mut W, mut n: usize) {
+    const SPACES: [u8; 64] = [b' '; 64];
+    while n > 0 {
+        let chunk = min(n, SPACES.len());
+        safe_unwrap!(output.write_all(&SPACES[..chunk]));
+        n -= chunk;
     }
 }
 
@@ -181,7 +150,7 @@ fn expand(options: Options) {
     use self::CharType::*;
 
     let mut output = BufWriter::new(stdout());
-    let ts = options.tabstops.as_ref();
+    let ts = &options.tabstops;
     let mut buf = Vec::new();
 
     for file in options.files.into_iter() {
@@ -231,13 +200,14 @@ fn expand(options: Options) {
                 // figure out how many columns this char takes up
                 match ctype {
                     Tab => {
-                        // figure out how many spaces to the next tabstop
-                        let nts = next_tabstop(ts, col);
+                        // figure out how many spaces to the next tabstop; past the
+                        // last one, a tab becomes a single space
+                        let nts = ts.next_tabstop(col).unwrap_or(1);
                         col += nts;
 
                         // now dump out either spaces if we're expanding, or a literal tab if we're not
                         if init || !options.iflag {
-                            safe_unwrap!(output.write_all(&options.tspaces[..nts].as_bytes()));
+                            write_spaces(&mut output, nts);
                         } else {
                             safe_unwrap!(output.write_all(&buf[byte..byte + nbytes]));
                         }
@@ -11,8 +11,58 @@ extern crate uucore;
 mod syntax_tree;
 mod tokens;
 
+use uucore::coreopts::CoreOptions;
+use uucore::help::{self, Help};
+
 static NAME: &str = "expr";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
+static SYNTAX: &str = "EXPRESSION\n  or:  expr OPTION";
+static SUMMARY: &str = "Print the value of EXPRESSION to standard output.";
+static LONG_HELP: &str = r#"A blank line below
+separates increasing precedence groups.  EXPRESSION may be:
+
+  ARG1 | ARG2       ARG1 if it is neither null nor 0, otherwise ARG2
+
+  ARG1 & ARG2       ARG1 if neither argument is null or 0, otherwise 0
+
+  ARG1 < ARG2       ARG1 is less than ARG2
+  ARG1 <= ARG2      ARG1 is less than or equal to ARG2
+  ARG1 = ARG2       ARG1 is equal to ARG2
+  ARG1 != ARG2      ARG1 is unequal to ARG2
+  ARG1 >= ARG2      ARG1 is greater than or equal to ARG2
+  ARG1 > ARG2       ARG1 is greater than ARG2
+
+  ARG1 + ARG2       arithmetic sum of ARG1 and ARG2
+  ARG1 - ARG2       arithmetic difference of ARG1 and ARG2
+
+  ARG1 * ARG2       arithmetic product of ARG1 and ARG2
+  ARG1 / ARG2       arithmetic quotient of ARG1 divided by ARG2
+  ARG1 % ARG2       arithmetic remainder of ARG1 divided by ARG2
+
+  STRING : REGEXP   anchored pattern match of REGEXP in STRING
+
+  match STRING REGEXP        same as STRING : REGEXP
+  substr STRING POS LENGTH   substring of STRING, POS counted from 1
+  index STRING CHARS         index in STRING where any CHARS is found, or 0
+  length STRING              length of STRING
+  + TOKEN                    interpret TOKEN as a string, even if it is a
+                               keyword like 'match' or an operator like '/'
+
+  ( EXPRESSION )             value of EXPRESSION
+
+Beware that many operators need to be escaped or quoted for shells.
+Comparisons are arithmetic if both ARGs are numbers, else lexicographical.
+Pattern matches return the string matched between \( and \) or null; if
+\( and \) are not used, they return the number of characters matched or 0.
+
+Exit status is 0 if EXPRESSION is neither null nor 0, 1 if EXPRESSION is null
+or 0, 2 if EXPRESSION is syntactically invalid, and 3 if an error occurred.
+
+Environment variables:
+    * EXPR_DEBUG_TOKENS=1   dump expression's tokens
+    * EXPR_DEBUG_RPN=1      dump expression represented in reverse polish notation
+    * EXPR_DEBUG_SYA_STEP=1 dump each parser step
+    * EXPR_DEBUG_AST=1      dump expression represented abstract syntax tree"#;
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
@@ -32,6 +82,10 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     }
 }
 
+pub fn uu_app() -> CoreOptions<'static> {
+    app!(SYNTAX, SUMMARY, LONG_HELP)
+}
+
 fn process_expr(token_strings: &[String]) -> Result<String, String> {
     let maybe_tokens = tokens::strings_to_tokens(&token_strings);
     let maybe_ast = syntax_tree::tokens_to_ast(maybe_tokens);
@@ -76,62 +130,9 @@ fn maybe_handle_help_or_version(args: &[String]) -> bool {
 }
 
 fn print_help() {
-    //! The following is taken from GNU coreutils' "expr --help" output.
-    println!(
-        r#"Usage: expr EXPRESSION
-  or:  expr OPTION
-
-      --help       display this help and exit
-      --version    output version information and exit
-
-Print the value of EXPRESSION to standard output.  A blank line below
-separates increasing precedence groups.  EXPRESSION may be:
-
-  ARG1 | ARG2       ARG1 if it is neither null nor 0, otherwise ARG2
-
-  ARG1 & ARG2       ARG1 if neither argument is null or 0, otherwise 0
-
-  ARG1 < ARG2       ARG1 is less than ARG2
-  ARG1 <= ARG2      ARG1 is less than or equal to ARG2
-  ARG1 = ARG2       ARG1 is equal to ARG2
-  ARG1 != ARG2      ARG1 is unequal to ARG2
-  ARG1 >= ARG2      ARG1 is greater than or equal to ARG2
-  ARG1 > ARG2       ARG1 is greater than ARG2
-
-  ARG1 + ARG2       arithmetic sum of ARG1 and ARG2
-  ARG1 - ARG2       arithmetic difference of ARG1 and ARG2
-
-  ARG1 * ARG2       arithmetic product of ARG1 and ARG2
-  ARG1 / ARG2       arithmetic quotient of ARG1 divided by ARG2
-  ARG1 % ARG2       arithmetic remainder of ARG1 divided by ARG2
-
-  STRING : REGEXP   anchored pattern match of REGEXP in STRING
-
-  match STRING REGEXP        same as STRING : REGEXP
-  substr STRING POS LENGTH   substring of STRING, POS counted from 1
-  index STRING CHARS         index in STRING where any CHARS is found, or 0
-  length STRING              length of STRING
-  + TOKEN                    interpret TOKEN as a string, even if it is a
-                               keyword like 'match' or an operator like '/'
-
-  ( EXPRESSION )             value of EXPRESSION
-
-Beware that many operators need to be escaped or quoted for shells.
-Comparisons are arithmetic if both ARGs are numbers, else lexicographical.
-Pattern matches return the string matched between \( and \) or null; if
-\( and \) are not used, they return the number of characters matched or 0.
-
-Exit status is 0 if EXPRESSION is neither null nor 0, 1 if EXPRESSION is null
-or 0, 2 if EXPRESSION is syntactically invalid, and 3 if an error occurred.
-
-Environment variables:
-    * EXPR_DEBUG_TOKENS=1   dump expression's tokens
-    * EXPR_DEBUG_RPN=1      dump expression represented in reverse polish notation
-    * EXPR_DEBUG_SYA_STEP=1 dump each parser step
-    * EXPR_DEBUG_AST=1      dump expression represented abstract syntax tree"#
-    );
+    print!("{}", Help::from(&uu_app()));
 }
 
 fn print_version() {
-    println!("{} {}", NAME, VERSION);
+    println!("{}", help::version(NAME, VERSION));
 }
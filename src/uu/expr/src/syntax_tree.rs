@@ -12,7 +12,9 @@
 
 // spell-checker:ignore (ToDO) binop binops ints paren prec
 
-use onig::{Regex, RegexOptions, Syntax};
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+use onig::{Regex, RegexOptions, Region, SearchOptions, Syntax};
 
 use crate::tokens::Token;
 
@@ -86,31 +88,28 @@ impl ASTNode {
             ASTNode::Node { ref op_type, .. } => match self.operand_values() {
                 Err(reason) => Err(reason),
                 Ok(operand_values) => match op_type.as_ref() {
-                    "+" => infix_operator_two_ints(
-                        |a: i64, b: i64| checked_binop(|| a.checked_add(b), "+"),
-                        &operand_values,
-                    ),
-                    "-" => infix_operator_two_ints(
-                        |a: i64, b: i64| checked_binop(|| a.checked_sub(b), "-"),
-                        &operand_values,
-                    ),
-                    "*" => infix_operator_two_ints(
-                        |a: i64, b: i64| checked_binop(|| a.checked_mul(b), "*"),
-                        &operand_values,
-                    ),
+                    "+" => {
+                        infix_operator_two_ints(|a: BigInt, b: BigInt| Ok(a + b), &operand_values)
+                    }
+                    "-" => {
+                        infix_operator_two_ints(|a: BigInt, b: BigInt| Ok(a - b), &operand_values)
+                    }
+                    "*" => {
+                        infix_operator_two_ints(|a: BigInt, b: BigInt| Ok(a * b), &operand_values)
+                    }
                     "/" => infix_operator_two_ints(
-                        |a: i64, b: i64| {
-                            if b == 0 {
+                        |a: BigInt, b: BigInt| {
+                            if b.is_zero() {
                                 Err("division by zero".to_owned())
                             } else {
-                                checked_binop(|| a.checked_div(b), "/")
+                                Ok(a / b)
                             }
                         },
                         &operand_values,
                     ),
                     "%" => infix_operator_two_ints(
-                        |a: i64, b: i64| {
-                            if b == 0 {
+                        |a: BigInt, b: BigInt| {
+                            if b.is_zero() {
                                 Err("division by zero".to_owned())
                             } else {
                                 Ok(a % b)
@@ -119,32 +118,32 @@ impl ASTNode {
                         &operand_values,
                     ),
                     "=" => infix_operator_two_ints_or_two_strings(
-                        |a: i64, b: i64| Ok(bool_as_int(a == b)),
+                        |a: BigInt, b: BigInt| Ok(bool_as_int(a == b)),
                         |a: &String, b: &String| Ok(bool_as_string(a == b)),
                         &operand_values,
                     ),
                     "!=" => infix_operator_two_ints_or_two_strings(
-                        |a: i64, b: i64| Ok(bool_as_int(a != b)),
+                        |a: BigInt, b: BigInt| Ok(bool_as_int(a != b)),
                         |a: &String, b: &String| Ok(bool_as_string(a != b)),
                         &operand_values,
                     ),
                     "<" => infix_operator_two_ints_or_two_strings(
-                        |a: i64, b: i64| Ok(bool_as_int(a < b)),
+                        |a: BigInt, b: BigInt| Ok(bool_as_int(a < b)),
                         |a: &String, b: &String| Ok(bool_as_string(a < b)),
                         &operand_values,
                     ),
                     ">" => infix_operator_two_ints_or_two_strings(
-                        |a: i64, b: i64| Ok(bool_as_int(a > b)),
+                        |a: BigInt, b: BigInt| Ok(bool_as_int(a > b)),
                         |a: &String, b: &String| Ok(bool_as_string(a > b)),
                         &operand_values,
                     ),
                     "<=" => infix_operator_two_ints_or_two_strings(
-                        |a: i64, b: i64| Ok(bool_as_int(a <= b)),
+                        |a: BigInt, b: BigInt| Ok(bool_as_int(a <= b)),
                         |a: &String, b: &String| Ok(bool_as_string(a <= b)),
                         &operand_values,
                     ),
                     ">=" => infix_operator_two_ints_or_two_strings(
-                        |a: i64, b: i64| Ok(bool_as_int(a >= b)),
+                        |a: BigInt, b: BigInt| Ok(bool_as_int(a >= b)),
                         |a: &String, b: &String| Ok(bool_as_string(a >= b)),
                         &operand_values,
                     ),
@@ -420,20 +419,24 @@ fn move_till_match_paren(
     }
 }
 
-fn checked_binop<F: Fn() -> Option<T>, T>(cb: F, op: &str) -> Result<T, String> {
-    match cb() {
-        Some(v) => Ok(v),
-        None => Err(format!("{}: Numerical result out of range", op)),
+/// The value of `s` if it's an integer: an optional `-` and at least one
+/// decimal digit, of any size.
+fn parse_int(s: &str) -> Option<BigInt> {
+    let digits = if s.starts_with('-') { &s[1..] } else { s };
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
     }
 }
 
 fn infix_operator_two_ints<F>(f: F, values: &[String]) -> Result<String, String>
 where
-    F: Fn(i64, i64) -> Result<i64, String>,
+    F: Fn(BigInt, BigInt) -> Result<BigInt, String>,
 {
     assert!(values.len() == 2);
-    if let Ok(left) = values[0].parse::<i64>() {
-        if let Ok(right) = values[1].parse::<i64>() {
+    if let Some(left) = parse_int(&values[0]) {
+        if let Some(right) = parse_int(&values[1]) {
             return match f(left, right) {
                 Ok(result) => Ok(result.to_string()),
                 Err(reason) => Err(reason),
@@ -449,13 +452,11 @@ fn infix_operator_two_ints_or_two_strings<FI, FS>(
     values: &[String],
 ) -> Result<String, String>
 where
-    FI: Fn(i64, i64) -> Result<i64, String>,
+    FI: Fn(BigInt, BigInt) -> Result<BigInt, String>,
     FS: Fn(&String, &String) -> Result<String, String>,
 {
     assert!(values.len() == 2);
-    if let (Some(a_int), Some(b_int)) =
-        (values[0].parse::<i64>().ok(), values[1].parse::<i64>().ok())
-    {
+    if let (Some(a_int), Some(b_int)) = (parse_int(&values[0]), parse_int(&values[1])) {
         match fi(a_int, b_int) {
             Ok(result) => Ok(result.to_string()),
             Err(reason) => Err(reason),
@@ -482,21 +483,33 @@ fn infix_operator_and(values: &[String]) -> String {
     }
 }
 
+/// The match of `values[0]` against the regex `values[1]`, a BRE anchored at
+/// the start of the string: the first `\(...\)` group, or the number of
+/// characters matched if there is none.
 fn operator_match(values: &[String]) -> Result<String, String> {
     assert!(values.len() == 2);
+    let subject = &values[0];
     let re = match Regex::with_options(&values[1], RegexOptions::REGEX_OPTION_NONE, Syntax::grep())
     {
         Ok(m) => m,
         Err(err) => return Err(err.description().to_string()),
     };
+    let mut region = Region::new();
+    let matched = re.match_with_options(
+        subject,
+        0,
+        SearchOptions::SEARCH_OPTION_NONE,
+        Some(&mut region),
+    );
     if re.captures_len() > 0 {
-        Ok(match re.captures(&values[0]) {
-            Some(captures) => captures.at(1).unwrap().to_string(),
-            None => "".to_string(),
+        // a group that took no part in the match matched nothing
+        Ok(match (matched, region.pos(1)) {
+            (Some(_), Some((start, end))) => subject[start..end].to_string(),
+            _ => "".to_string(),
         })
     } else {
-        Ok(match re.find(&values[0]) {
-            Some((start, end)) => (end - start).to_string(),
+        Ok(match matched {
+            Some(len) => subject[..len].chars().count().to_string(),
             None => "0".to_string(),
         })
     }
@@ -504,9 +517,11 @@ fn operator_match(values: &[String]) -> Result<String, String> {
 
 fn prefix_operator_length(values: &[String]) -> String {
     assert!(values.len() == 1);
-    values[0].len().to_string()
+    values[0].chars().count().to_string()
 }
 
+/// The position, counted from 1, of the first character of `values[0]` that
+/// is any of `values[1]`, or 0.
 fn prefix_operator_index(values: &[String]) -> String {
     assert!(values.len() == 2);
     let haystack = &values[0];
@@ -515,7 +530,7 @@ fn prefix_operator_index(values: &[String]) -> String {
     for (current_idx, ch_h) in haystack.chars().enumerate() {
         for ch_n in needles.chars() {
             if ch_n == ch_h {
-                return current_idx.to_string();
+                return (current_idx + 1).to_string();
             }
         }
     }
@@ -553,11 +568,11 @@ fn prefix_operator_substr(values: &[String]) -> Result<String, String> {
     Ok(out_str)
 }
 
-fn bool_as_int(b: bool) -> i64 {
+fn bool_as_int(b: bool) -> BigInt {
     if b {
-        1
+        BigInt::one()
     } else {
-        0
+        BigInt::zero()
     }
 }
 fn bool_as_string(b: bool) -> String {
@@ -571,8 +586,8 @@ fn value_as_bool(s: &str) -> bool {
     if s.is_empty() {
         return false;
     }
-    match s.parse::<i64>() {
-        Ok(n) => n != 0,
-        Err(_) => true,
+    match parse_int(s) {
+        Some(n) => !n.is_zero(),
+        None => true,
     }
 }
@@ -56,18 +56,14 @@ impl Token {
             _ => false,
         }
     }
-    fn is_a_number(&self) -> bool {
-        match *self {
-            Token::Value { ref value, .. } => value.parse::<i64>().is_ok(),
-            _ => false,
-        }
-    }
-    fn is_a_close_paren(&self) -> bool {
+    /// Whether an operand is expected after the token: the tokens after
+    /// operators and open parentheses start operands.
+    fn expects_operand_after(&self) -> bool {
         #[allow(clippy::match_like_matches_macro)]
         // `matches!(...)` macro not stabilized until rust v1.42
         match *self {
-            Token::ParClose => true,
-            _ => false,
+            Token::Value { .. } | Token::ParClose => false,
+            _ => true,
         }
     }
 }
@@ -144,14 +140,15 @@ fn maybe_dump_tokens_acc(tokens_acc: &[(usize, Token)]) {
 }
 
 fn push_token_if_not_escaped(acc: &mut Vec<(usize, Token)>, tok_idx: usize, token: Token, s: &str) {
-    // Smells heuristics... :(
+    // a "+" where an operand is expected isn't an addition, but quotes the
+    // token after it, which is then a string even if it's a keyword or an
+    // operator: `expr + match`, `expr length + /`
     let prev_is_plus = match acc.last() {
         None => false,
         Some(ref t) => t.1.is_infix_plus(),
     };
     let should_use_as_escaped = if prev_is_plus && acc.len() >= 2 {
-        let pre_prev = &acc[acc.len() - 2];
-        !(pre_prev.1.is_a_number() || pre_prev.1.is_a_close_paren())
+        acc[acc.len() - 2].1.expects_operand_after()
     } else {
         prev_is_plus
     };
@@ -11,6 +11,7 @@ extern crate uucore;
 
 use std::error::Error;
 use std::io::{self, stdin, stdout, BufRead, Write};
+use uucore::coreopts::CoreOptions;
 
 mod factor;
 pub(crate) use factor::*;
@@ -25,31 +26,90 @@ static SUMMARY: &str = "Print the prime factors of the given number(s).
  If none are specified, read from standard input.";
 static LONG_HELP: &str = "";
 
-fn print_factors_str(num_str: &str, w: &mut impl io::Write) -> Result<(), Box<dyn Error>> {
-    num_str
-        .parse::<u64>()
-        .map_err(|e| e.into())
-        .and_then(|x| writeln!(w, "{}:{}", x, factor(x)).map_err(|e| e.into()))
+static OPT_EXPONENTS: &str = "exponents";
+
+fn print_factors_str(
+    num_str: &str,
+    w: &mut impl io::Write,
+    exponents: bool,
+) -> Result<(), Box<dyn Error>> {
+    let x = num_str.parse::<u128>()?;
+    if exponents {
+        writeln!(w, "{}:{:#}", x, factor(x))?;
+    } else {
+        writeln!(w, "{}:{}", x, factor(x))?;
+    }
+    Ok(())
+}
+
+fn print_factors_bytes(num: &[u8], w: &mut impl io::Write, exponents: bool) {
+    let number = String::from_utf8_lossy(num);
+    if let Err(e) = print_factors_str(&number, w, exponents) {
+        show_warning!("{}: {}", number, e);
+    }
+}
+
+/// Factors the whitespace separated numbers of `input` as they come, without
+/// waiting for whole lines: the output is flushed whenever more input has to
+/// be read, so that each number's factors are out before the next read blocks.
+fn print_factors_stream(
+    mut input: impl BufRead,
+    w: &mut impl io::Write,
+    exponents: bool,
+) -> io::Result<()> {
+    // The start of a number cut at the end of the buffer
+    let mut pending = Vec::new();
+
+    loop {
+        w.flush()?;
+        let buf = match input.fill_buf() {
+            Ok(buf) => buf,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if buf.is_empty() {
+            break;
+        }
+
+        let len = buf.len();
+        let mut start = 0;
+        for (i, b) in buf.iter().enumerate() {
+            if b.is_ascii_whitespace() {
+                if !pending.is_empty() {
+                    pending.extend_from_slice(&buf[start..i]);
+                    print_factors_bytes(&pending, w, exponents);
+                    pending.clear();
+                } else if start < i {
+                    print_factors_bytes(&buf[start..i], w, exponents);
+                }
+                start = i + 1;
+            }
+        }
+        pending.extend_from_slice(&buf[start..]);
+        input.consume(len);
+    }
+
+    if !pending.is_empty() {
+        print_factors_bytes(&pending, w, exponents);
+    }
+    Ok(())
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
-    let matches = app!(SYNTAX, SUMMARY, LONG_HELP).parse(args.collect_str());
+    let matches = uu_app().parse(args.collect_str());
+    let exponents = matches.opt_present(OPT_EXPONENTS);
     let stdout = stdout();
     let mut w = io::BufWriter::new(stdout.lock());
 
     if matches.free.is_empty() {
         let stdin = stdin();
 
-        for line in stdin.lock().lines() {
-            for number in line.unwrap().split_whitespace() {
-                if let Err(e) = print_factors_str(number, &mut w) {
-                    show_warning!("{}: {}", number, e);
-                }
-            }
+        if let Err(e) = print_factors_stream(stdin.lock(), &mut w, exponents) {
+            show_error!("{}", e);
         }
     } else {
         for number in &matches.free {
-            if let Err(e) = print_factors_str(number, &mut w) {
+            if let Err(e) = print_factors_str(number, &mut w, exponents) {
                 show_warning!("{}: {}", number, e);
             }
         }
@@ -61,3 +121,13 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
     0
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    app.optflag(
+        "h",
+        OPT_EXPONENTS,
+        "print repeated factors in form p^e unless e is 1",
+    );
+    app
+}
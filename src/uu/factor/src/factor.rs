@@ -9,13 +9,13 @@ use smallvec::SmallVec;
 use std::cell::RefCell;
 use std::fmt;
 
-use crate::numeric::{Arithmetic, Montgomery};
+use crate::numeric::{isqrt, Arithmetic, Montgomery, Montgomery128};
 use crate::{miller_rabin, rho, table};
 
 type Exponent = u8;
 
 #[derive(Clone, Debug)]
-struct Decomposition(SmallVec<[(u64, Exponent); NUM_FACTORS_INLINE]>);
+struct Decomposition(SmallVec<[(u128, Exponent); NUM_FACTORS_INLINE]>);
 
 // The number of factors to inline directly into a `Decomposition` object.
 // As a consequence of the Erdős–Kac theorem, the average number of prime factors
@@ -27,7 +27,7 @@ impl Decomposition {
         Decomposition(SmallVec::new())
     }
 
-    fn add(&mut self, factor: u64, exp: Exponent) {
+    fn add(&mut self, factor: u128, exp: Exponent) {
         debug_assert!(exp > 0);
 
         if let Some((_, e)) = self.0.iter_mut().find(|(f, _)| *f == factor) {
@@ -38,13 +38,13 @@ impl Decomposition {
     }
 
     #[cfg(test)]
-    fn product(&self) -> u64 {
+    fn product(&self) -> u128 {
         self.0
             .iter()
             .fold(1, |acc, (p, exp)| acc * p.pow(*exp as u32))
     }
 
-    fn get(&self, p: u64) -> Option<&(u64, u8)> {
+    fn get(&self, p: u128) -> Option<&(u128, u8)> {
         self.0.iter().find(|(q, _)| *q == p)
     }
 }
@@ -76,27 +76,33 @@ impl Factors {
         Factors(RefCell::new(Decomposition::one()))
     }
 
-    pub fn add(&mut self, prime: u64, exp: Exponent) {
-        debug_assert!(miller_rabin::is_prime(prime));
+    pub fn add(&mut self, prime: u128, exp: Exponent) {
+        debug_assert!(miller_rabin::is_prime_u128(prime));
         self.0.borrow_mut().add(prime, exp)
     }
 
-    pub fn push(&mut self, prime: u64) {
+    pub fn push(&mut self, prime: u128) {
         self.add(prime, 1)
     }
 
     #[cfg(test)]
-    fn product(&self) -> u64 {
+    fn product(&self) -> u128 {
         self.0.borrow().product()
     }
 }
 
+// With the alternate flag, `{:#}`, a repeated factor is written once as
+//  `p^exp`, like `factor --exponents` does.
 impl fmt::Display for Factors {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let v = &mut (self.0).borrow_mut().0;
         v.sort_unstable();
 
         for (p, exp) in v.iter() {
+            if f.alternate() && *exp > 1 {
+                write!(f, " {}^{}", p, exp)?;
+                continue;
+            }
             for _ in 0..*exp {
                 write!(f, " {}", p)?
             }
@@ -126,7 +132,7 @@ fn _factor<A: Arithmetic + miller_rabin::Basis>(num: u64, f: Factors) -> Factors
     let divisor = match miller_rabin::test::<A>(n) {
         Prime => {
             let mut r = f;
-            r.push(num);
+            r.push(num.into());
             return r;
         }
 
@@ -138,7 +144,44 @@ fn _factor<A: Arithmetic + miller_rabin::Basis>(num: u64, f: Factors) -> Factors
     _factor(num / divisor, f)
 }
 
-pub fn factor(mut n: u64) -> Factors {
+// Factors of 128b integers, which go to `_factor` as soon as they fit in 64b.
+fn _factor_u128(num: u128, f: Factors) -> Factors {
+    use miller_rabin::Result::*;
+
+    if num >> 64 == 0 {
+        let num = num as u64;
+        return if num < (1 << 32) {
+            _factor::<Montgomery<u32>>(num, f)
+        } else {
+            _factor::<Montgomery<u64>>(num, f)
+        };
+    }
+
+    let n = Montgomery128::new(num);
+    let divisor = match miller_rabin::test_u128(n) {
+        Prime => {
+            let mut r = f;
+            r.push(num);
+            return r;
+        }
+
+        Composite(d) => d,
+        Pseudoprime => {
+            // rho takes ~√p steps to find p, too many for p² with large p
+            let r = isqrt(num);
+            if r * r == num {
+                r
+            } else {
+                rho::find_divisor_u128(n)
+            }
+        }
+    };
+
+    let f = _factor_u128(divisor, f);
+    _factor_u128(num / divisor, f)
+}
+
+pub fn factor(mut n: u128) -> Factors {
     let mut factors = Factors::one();
 
     if n < 2 {
@@ -155,13 +198,14 @@ pub fn factor(mut n: u64) -> Factors {
         return factors;
     }
 
-    let (factors, n) = table::factor(n, factors);
-
-    if n < (1 << 32) {
-        _factor::<Montgomery<u32>>(n, factors)
+    let (factors, n) = if n >> 64 == 0 {
+        let (factors, n) = table::factor(n as u64, factors);
+        (factors, n.into())
     } else {
-        _factor::<Montgomery<u64>>(n, factors)
-    }
+        table::factor_u128(n, factors)
+    };
+
+    _factor_u128(n, factors)
 }
 
 #[cfg(test)]
@@ -191,10 +235,42 @@ mod tests {
     #[test]
     fn factor_recombines_overflowing() {
         assert!((0..250)
-            .map(|i| 2 * i + 2u64.pow(32) + 1)
+            .map(|i| 2 * i + 2u128.pow(32) + 1)
+            .all(|i| factor(i).product() == i));
+        assert!((0..250)
+            .map(|i| 2 * i + 2u128.pow(64) + 1)
             .all(|i| factor(i).product() == i));
     }
 
+    #[test]
+    fn factor_u128_semiprimes() {
+        // The largest primes below 2⁶⁴ and 2⁴⁰, and a Mersenne prime
+        let (p, q, r) = (18_446_744_073_709_551_557, 1_099_511_627_689, (1 << 61) - 1);
+        for &(a, b) in &[(p, q), (q, r), (p, 3), (q, q)] {
+            let mut f = Factors::one();
+            f.push(a);
+            f.push(b);
+            assert_eq!(factor(a * b), f);
+        }
+    }
+
+    #[test]
+    fn factor_u128_primes() {
+        // Mersenne prime 2¹²⁷ - 1, and the largest prime below 2¹²⁸
+        for &p in &[(1 << 127) - 1, std::u128::MAX - 158] {
+            let mut f = Factors::one();
+            f.push(p);
+            assert_eq!(factor(p), f);
+        }
+    }
+
+    #[test]
+    fn display_exponents() {
+        let f = factor(2 * 2 * 2 * 3 * 5 * 5);
+        assert_eq!(format!("{}", f), " 2 2 2 3 5 5");
+        assert_eq!(format!("{:#}", f), " 2^3 3 5^2");
+    }
+
     #[test]
     fn factor_recombines_strong_pseudoprime() {
         // This is a strong pseudoprime (wrt. miller_rabin::BASIS)
@@ -210,7 +286,12 @@ mod tests {
 
     quickcheck! {
         fn factor_recombines(i: u64) -> bool {
-            i == 0 || factor(i).product() == i
+            i == 0 || factor(i.into()).product() == i.into()
+        }
+
+        fn factor_recombines_u128(f: Factors, k: u32) -> bool {
+            let n = f.product() * u128::from(k);
+            k == 0 || factor(n).product() == n
         }
 
         fn recombines_factors(f: Factors) -> () {
@@ -243,7 +324,7 @@ impl quickcheck::Arbitrary for Factors {
                 n = gen.gen_range(1, n);
                 if miller_rabin::is_prime(n) {
                     if let Some(h) = g.checked_mul(n) {
-                        f.push(n);
+                        f.push(n.into());
                         g = h;
                     } else {
                         // We are overflowing u64, retry
@@ -7,6 +7,8 @@
 
 // spell-checker:ignore (URL) appspot
 
+use std::mem::swap;
+
 use crate::numeric::*;
 
 pub(crate) trait Basis {
@@ -33,13 +35,13 @@ impl Basis for Montgomery<u32> {
 
 #[derive(Eq, PartialEq)]
 #[must_use = "Ignoring the output of a primality test."]
-pub(crate) enum Result {
+pub(crate) enum Result<T = u64> {
     Prime,
     Pseudoprime,
-    Composite(u64),
+    Composite(T),
 }
 
-impl Result {
+impl<T: Eq> Result<T> {
     pub(crate) fn is_prime(&self) -> bool {
         *self == Result::Prime
     }
@@ -95,6 +97,146 @@ pub(crate) fn test<A: Arithmetic + Basis>(m: A) -> Result {
     Prime
 }
 
+// Bases for the Miller-Rabin test of 128b integers: the first 13 primes, which
+//  are deterministic below 3.3 × 10²⁴; see Sorenson and Webster (2015),
+//  Strong pseudoprimes to twelve prime bases.
+const BASIS_U128: &[u128] = &[2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+// Miller-Rabin for integers of 65 to 128 bits, followed by a strong Lucas test:
+// above the range where `BASIS_U128` is deterministic, that's the Baillie-PSW
+// test, to which no pseudoprime is known.
+#[allow(clippy::many_single_char_names)]
+pub(crate) fn test_u128(m: Montgomery128) -> Result<u128> {
+    use self::Result::*;
+
+    let n = m.modulus();
+    debug_assert!(n % 2 != 0);
+
+    // n-1 = r 2ⁱ
+    let i = (n - 1).trailing_zeros();
+    let r = (n - 1) >> i;
+
+    let one = m.one();
+    let minus_one = m.minus_one();
+
+    'witness: for &a in BASIS_U128 {
+        // x = a^r mod n; n > 2⁶⁴ exceeds every basis element
+        let mut x = m.pow(m.from_u128(a), r);
+
+        if x == one || x == minus_one {
+            continue;
+        }
+
+        for _ in 1..i {
+            let y = m.mul(x, x);
+            if y == one {
+                return Composite(gcd_u128(m.to_u128(x) - 1, n));
+            } else if y == minus_one {
+                continue 'witness;
+            }
+            x = y;
+        }
+
+        return Pseudoprime;
+    }
+
+    if strong_lucas(m) {
+        Prime
+    } else {
+        Pseudoprime
+    }
+}
+
+// Strong Lucas probable prime test, with Selfridge's parameters: P = 1 and
+//  Q = (1 - D)/4, for the first D of 5, -7, 9, -11, … with (D/n) = -1.
+fn strong_lucas(m: Montgomery128) -> bool {
+    let n = m.modulus();
+    // There's no such D for a square, and n > 2⁶⁴ is odd so n+1 can't overflow
+    if is_square(n) || n == std::u128::MAX {
+        return false;
+    }
+
+    let from_i64 = |x: i64| {
+        if x >= 0 {
+            m.from_u128(x as u128)
+        } else {
+            m.from_u128(n - (-x) as u128)
+        }
+    };
+
+    let mut d: i64 = 5;
+    loop {
+        match jacobi(if d > 0 { d as u128 } else { n - (-d) as u128 }, n) {
+            -1 => break,
+            // d and n share a factor, and n > |d|
+            0 => return false,
+            _ => d = if d > 0 { -d - 2 } else { -d + 2 },
+        }
+    }
+    let q = from_i64((1 - d) / 4);
+    let d = from_i64(d);
+
+    // n+1 = k 2ˢ
+    let s = (n + 1).trailing_zeros();
+    let k = (n + 1) >> s;
+
+    // U_k, V_k and Q^k, from U_1 = V_1 = P = 1 down the bits of k
+    let (mut u, mut v, mut q_k) = (m.one(), m.one(), q);
+    for bit in (0..127 - k.leading_zeros()).rev() {
+        u = m.mul(u, v);
+        v = m.sub(m.mul(v, v), m.add(q_k, q_k));
+        q_k = m.mul(q_k, q_k);
+        if k >> bit & 1 != 0 {
+            let (u_, v_) = (m.half(m.add(u, v)), m.half(m.add(m.mul(d, u), v)));
+            u = u_;
+            v = v_;
+            q_k = m.mul(q_k, q);
+        }
+    }
+
+    if u == 0 || v == 0 {
+        return true;
+    }
+    // V_{k 2ʳ} for 0 < r < s
+    for _ in 1..s {
+        v = m.sub(m.mul(v, v), m.add(q_k, q_k));
+        if v == 0 {
+            return true;
+        }
+        q_k = m.mul(q_k, q_k);
+    }
+    false
+}
+
+// The Jacobi symbol (a/n), for odd n
+fn jacobi(mut a: u128, mut n: u128) -> i8 {
+    let mut t = 1;
+    a %= n;
+    while a != 0 {
+        let z = a.trailing_zeros();
+        a >>= z;
+        if z % 2 == 1 && (n % 8 == 3 || n % 8 == 5) {
+            t = -t;
+        }
+        // Quadratic reciprocity
+        if a % 4 == 3 && n % 4 == 3 {
+            t = -t;
+        }
+        swap(&mut a, &mut n);
+        a %= n;
+    }
+    if n == 1 {
+        t
+    } else {
+        0
+    }
+}
+
+fn is_square(n: u128) -> bool {
+    let r = isqrt(n);
+    r * r == n
+}
+
 // Used by build.rs' tests and debug assertions
 #[allow(dead_code)]
 pub(crate) fn is_prime(n: u64) -> bool {
@@ -107,6 +249,15 @@ pub(crate) fn is_prime(n: u64) -> bool {
     }
 }
 
+#[allow(dead_code)]
+pub(crate) fn is_prime_u128(n: u128) -> bool {
+    if n >> 64 == 0 {
+        is_prime(n as u64)
+    } else {
+        n % 2 != 0 && test_u128(Montgomery128::new(n)).is_prime()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
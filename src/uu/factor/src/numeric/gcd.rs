@@ -49,6 +49,16 @@ pub fn gcd(mut u: u64, mut v: u64) -> u64 {
     }
 }
 
+/// `gcd` of 128b integers, which is only needed once per batch of Pollard-Brent
+///  steps, so Euclid's algorithm does.
+pub(crate) fn gcd_u128(mut u: u128, mut v: u128) -> u128 {
+    while v != 0 {
+        u %= v;
+        swap(&mut u, &mut v);
+    }
+    u
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,6 +78,10 @@ mod tests {
             gcd(a, b) == g
         }
 
+        fn binary(a: u64, b: u64) -> bool {
+            gcd_u128(a.into(), b.into()) == gcd(a, b).into()
+        }
+
         fn one(a: u64) -> bool {
             gcd(1, a) == 1
         }
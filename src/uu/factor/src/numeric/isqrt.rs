@@ -0,0 +1,47 @@
+// * This file is part of the uutils coreutils package.
+// *
+// * For the full copyright and license information, please view the LICENSE file
+// * that was distributed with this source code.
+
+/// ⌊√n⌋
+pub(crate) fn isqrt(n: u128) -> u128 {
+    if n == 0 {
+        return 0;
+    }
+
+    // Newton's method, from above the square root so that it decreases
+    let bits = 128 - n.leading_zeros();
+    let mut x = 1 << ((bits + 1) / 2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::quickcheck;
+
+    #[test]
+    fn limits() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(1), 1);
+        assert_eq!(isqrt(std::u128::MAX), std::u64::MAX.into());
+    }
+
+    quickcheck! {
+        fn floor(n: u128) -> bool {
+            let r = isqrt(n);
+            r * r <= n && (r + 1).checked_mul(r + 1).map_or(true, |s| s > n)
+        }
+
+        fn squares(r: u64) -> bool {
+            let r = u128::from(r);
+            isqrt(r * r) == r
+        }
+    }
+}
@@ -7,6 +7,10 @@
 
 mod gcd;
 pub use gcd::gcd;
+pub(crate) use gcd::gcd_u128;
+
+mod isqrt;
+pub(crate) use isqrt::isqrt;
 
 pub(crate) mod traits;
 
@@ -15,3 +19,6 @@ pub(crate) use modular_inverse::modular_inverse;
 
 mod montgomery;
 pub(crate) use montgomery::{Arithmetic, Montgomery};
+
+mod montgomery128;
+pub(crate) use montgomery128::Montgomery128;
@@ -0,0 +1,194 @@
+// * This file is part of the uutils coreutils package.
+// *
+// * For the full copyright and license information, please view the LICENSE file
+// * that was distributed with this source code.
+
+use super::*;
+
+/// The full product `a × b`, as its high and low 128 bits.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const LOW: u128 = (1 << 64) - 1;
+    let (a1, a0) = (a >> 64, a & LOW);
+    let (b1, b0) = (b >> 64, b & LOW);
+
+    let p00 = a0 * b0;
+    let p01 = a0 * b1;
+    let p10 = a1 * b0;
+    let p11 = a1 * b1;
+
+    // Below 3 × 2⁶⁴, this can't overflow
+    let mid = (p00 >> 64) + (p01 & LOW) + (p10 & LOW);
+    let lo = (p00 & LOW) | (mid << 64);
+    let hi = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (hi, lo)
+}
+
+/// Montgomery arithmetic mod odd `n` ≥ 2⁶⁴, with R = 2¹²⁸.
+///
+/// `Montgomery<T>` multiplies in `T::DoubleWidth`, which doesn't exist for
+///  u128, so this one builds the 256 bits wide products from halves.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Montgomery128 {
+    n: u128,
+    // -n⁻¹ mod R
+    a: u128,
+    // R² mod n
+    r2: u128,
+}
+
+impl Montgomery128 {
+    pub(crate) fn new(n: u128) -> Self {
+        debug_assert!(n % 2 == 1 && n >> 64 != 0);
+        let a = modular_inverse(n).wrapping_neg();
+
+        // R mod n, then doubled 128 times
+        let r = n.wrapping_neg() % n;
+        let mut m = Montgomery128 { n, a, r2: 0 };
+        m.r2 = (0..128).fold(r, |x, _| m.add(x, x));
+        m
+    }
+
+    pub(crate) fn modulus(&self) -> u128 {
+        self.n
+    }
+
+    /// computes x/R mod n, for x = hi·R + lo < nR
+    fn reduce(&self, (hi, lo): (u128, u128)) -> u128 {
+        debug_assert!(hi < self.n);
+        let m = lo.wrapping_mul(self.a);
+        let (mh, ml) = mul_wide(m, self.n);
+
+        // lo + ml ≡ 0 mod R, so it carries unless both are zero
+        let carry = (lo.overflowing_add(ml).1) as u128;
+        let (y, o1) = hi.overflowing_add(mh);
+        let (y, o2) = y.overflowing_add(carry);
+
+        // (x + mn)/R < 2n, with the overflowing bit in o1 or o2
+        if o1 || o2 || y >= self.n {
+            y.wrapping_sub(self.n)
+        } else {
+            y
+        }
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub(crate) fn from_u128(&self, x: u128) -> u128 {
+        debug_assert!(x < self.n);
+        self.reduce(mul_wide(x, self.r2))
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub(crate) fn to_u128(&self, x: u128) -> u128 {
+        self.reduce((0, x))
+    }
+
+    pub(crate) fn add(&self, a: u128, b: u128) -> u128 {
+        let (r, overflow) = a.overflowing_add(b);
+        if overflow || r >= self.n {
+            r.wrapping_sub(self.n)
+        } else {
+            r
+        }
+    }
+
+    pub(crate) fn sub(&self, a: u128, b: u128) -> u128 {
+        if a >= b {
+            a - b
+        } else {
+            a.wrapping_sub(b).wrapping_add(self.n)
+        }
+    }
+
+    pub(crate) fn mul(&self, a: u128, b: u128) -> u128 {
+        self.reduce(mul_wide(a, b))
+    }
+
+    /// a/2, as the representation of a is halved along with a
+    pub(crate) fn half(&self, a: u128) -> u128 {
+        if a % 2 == 0 {
+            a >> 1
+        } else {
+            // (a + n)/2 without overflowing, as both are odd
+            (a >> 1) + (self.n >> 1) + 1
+        }
+    }
+
+    pub(crate) fn pow(&self, mut a: u128, mut b: u128) -> u128 {
+        let mut result = self.one();
+        while b > 0 {
+            if b & 1 != 0 {
+                result = self.mul(result, a);
+            }
+            a = self.mul(a, a);
+            b >>= 1;
+        }
+        result
+    }
+
+    pub(crate) fn one(&self) -> u128 {
+        self.n.wrapping_neg() % self.n
+    }
+
+    pub(crate) fn minus_one(&self) -> u128 {
+        self.n - self.one()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::quickcheck;
+
+    // The largest prime below 2¹²⁸, and a composite modulus
+    const MODULI: &[u128] = &[0xFFFF_FFFF_FFFF_FFFF_FFFF_FFFF_FFFF_FF61, (1 << 64) + 1];
+
+    fn mod_mul(a: u128, b: u128, n: u128) -> u128 {
+        // Double and add, in u128 without overflowing
+        let mut r = 0;
+        for i in (0..128).rev() {
+            r = if r >= n - r { r - (n - r) } else { 2 * r };
+            if b >> i & 1 != 0 {
+                r = if r >= n - a { r - (n - a) } else { r + a };
+            }
+        }
+        r
+    }
+
+    #[test]
+    fn mul_wide_halves() {
+        let max = std::u128::MAX;
+        assert_eq!(mul_wide(max, max), (max - 1, 1));
+        assert_eq!(mul_wide(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(mul_wide(3, 5), (0, 15));
+    }
+
+    quickcheck! {
+        fn roundtrip(x: u128) -> () {
+            for &n in MODULI {
+                let m = Montgomery128::new(n);
+                let x = x % n;
+                assert_eq!(m.to_u128(m.from_u128(x)), x);
+            }
+        }
+
+        fn mult(x: u128, y: u128) -> () {
+            for &n in MODULI {
+                let m = Montgomery128::new(n);
+                let (x, y) = (x % n, y % n);
+                let r = m.mul(m.from_u128(x), m.from_u128(y));
+                assert_eq!(m.to_u128(r), mod_mul(x, y, n));
+            }
+        }
+
+        fn add_sub_half(x: u128, y: u128) -> () {
+            for &n in MODULI {
+                let m = Montgomery128::new(n);
+                let (x, y) = (x % n, y % n);
+                let (m_x, m_y) = (m.from_u128(x), m.from_u128(y));
+                let sum = m.add(m_x, m_y);
+                assert_eq!(m.sub(sum, m_y), m_x);
+                assert_eq!(m.add(m.half(sum), m.half(sum)), sum);
+            }
+        }
+    }
+}
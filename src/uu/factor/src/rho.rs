@@ -45,3 +45,65 @@ pub(crate) fn find_divisor<A: Arithmetic>(n: A) -> u64 {
         }
     }
 }
+
+// Pollard's rho with Brent's cycle detection, for 128b `n`: the gcd with n of
+//  each batch of `BATCH` differences is taken at once, from their product.
+pub(crate) fn find_divisor_u128(m: Montgomery128) -> u128 {
+    #![allow(clippy::many_single_char_names)]
+    const BATCH: usize = 128;
+
+    let n = m.modulus();
+    let mut rand = {
+        let range = Uniform::new(1, n);
+        let mut rng = SmallRng::from_rng(&mut thread_rng()).unwrap();
+        move || m.from_u128(range.sample(&mut rng))
+    };
+
+    loop {
+        let c = rand();
+        let f = |x| m.add(m.mul(x, x), c);
+        let mut y = rand();
+        let mut x = y;
+        let mut ys = y;
+        let mut q = m.one();
+        let mut g = 1;
+
+        // x is the value at the last power of two, y goes r steps past it
+        let mut r = 1;
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..min(BATCH, r - k) {
+                    y = f(y);
+                    q = m.mul(q, m.sub(x, y));
+                }
+                // q is the product in Montgomery form, q R mod n, but R is
+                //  invertible mod n so that's the same gcd
+                g = gcd_u128(q, n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+
+        if g == n {
+            // The product of the batch got to 0 mod n: redo it step by step
+            loop {
+                ys = f(ys);
+                g = gcd_u128(m.sub(x, ys), n);
+                if g != 1 {
+                    break;
+                }
+            }
+        }
+
+        if g != n {
+            return g;
+        }
+        // Failure, retry with a different quadratic
+    }
+}
@@ -35,7 +35,7 @@ pub(crate) fn factor(mut num: u64, mut factors: Factors) -> (Factors, u64) {
                 k += 1;
             } else {
                 if k > 0 {
-                    factors.add(prime, k);
+                    factors.add(prime.into(), k);
                 }
                 break;
             }
@@ -44,3 +44,21 @@ pub(crate) fn factor(mut num: u64, mut factors: Factors) -> (Factors, u64) {
 
     (factors, num)
 }
+
+// Trial division of integers above 2⁶⁴, by the same primes: their inverses
+//  mod 2⁶⁴ don't help there, so it computes remainders.
+pub(crate) fn factor_u128(mut num: u128, mut factors: Factors) -> (Factors, u128) {
+    for &(prime, _, _) in P_INVS_U64 {
+        let p = prime.into();
+        let mut k = 0;
+        while num % p == 0 {
+            num /= p;
+            k += 1;
+        }
+        if k > 0 {
+            factors.add(p, k);
+        }
+    }
+
+    (factors, num)
+}
@@ -11,7 +11,6 @@
 extern crate uucore;
 
 use clap::{App, Arg};
-use std::cmp;
 use std::fs::File;
 use std::io::{stdin, stdout, Write};
 use std::io::{BufReader, BufWriter, Read};
@@ -34,6 +33,7 @@ mod parasplit;
 static ABOUT: &str = "Reformat paragraphs from input files (or stdin) to stdout.";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static MAX_WIDTH: usize = 2500;
+static DEFAULT_WIDTH: usize = 75;
 
 static OPT_CROWN_MARGIN: &str = "crown-margin";
 static OPT_TAGGED_PARAGRAPH: &str = "tagged-paragraph";
@@ -78,129 +78,7 @@ pub struct FmtOptions {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .arg(
-            Arg::with_name(OPT_CROWN_MARGIN)
-                .short("c")
-                .long(OPT_CROWN_MARGIN)
-                .help(
-                    "First and second line of paragraph
-        may have different indentations, in which
-        case the first line's indentation is preserved,
-        and each subsequent line's indentation matches the second line.",
-                ),
-        )
-        .arg(
-            Arg::with_name(OPT_TAGGED_PARAGRAPH)
-                .short("t")
-                .long("tagged-paragraph")
-                .help(
-                    "Like -c, except that the first and second line of a paragraph *must*
-                    have different indentation or they are treated as separate paragraphs.",
-                ),
-        )
-        .arg(
-            Arg::with_name(OPT_PRESERVE_HEADERS)
-                .short("m")
-                .long("preserve-headers")
-                .help(
-                    "Attempt to detect and preserve mail headers in the input.
-                    Be careful when combining this flag with -p.",
-                ),
-        )
-        .arg(
-            Arg::with_name(OPT_SPLIT_ONLY)
-                .short("s")
-                .long("split-only")
-                .help("Split lines only, do not reflow."),
-        )
-        .arg(
-            Arg::with_name(OPT_UNIFORM_SPACING)
-                .short("u")
-                .long("uniform-spacing")
-                .help(
-                    "Insert exactly one
-                    space between words, and two between sentences.
-                    Sentence breaks in the input are detected as [?!.]
-                    followed by two spaces or a newline; other punctuation
-                    is not interpreted as a sentence break.",
-                ),
-        )
-        .arg(
-            Arg::with_name(OPT_PREFIX)
-                .short("p")
-                .long("prefix")
-                .help(
-                    "Reformat only lines
-                    beginning with PREFIX, reattaching PREFIX to reformatted lines.
-                    Unless -x is specified, leading whitespace will be ignored
-                    when matching PREFIX.",
-                )
-                .value_name("PREFIX"),
-        )
-        .arg(
-            Arg::with_name(OPT_SKIP_PREFIX)
-                .short("P")
-                .long("skip-prefix")
-                .help(
-                    "Do not reformat lines
-                    beginning with PSKIP. Unless -X is specified, leading whitespace
-                    will be ignored when matching PSKIP",
-                )
-                .value_name("PSKIP"),
-        )
-        .arg(
-            Arg::with_name(OPT_EXACT_PREFIX)
-                .short("x")
-                .long("exact-prefix")
-                .help(
-                    "PREFIX must match at the
-                    beginning of the line with no preceding whitespace.",
-                ),
-        )
-        .arg(
-            Arg::with_name(OPT_EXACT_SKIP_PREFIX)
-                .short("X")
-                .long("exact-skip-prefix")
-                .help(
-                    "PSKIP must match at the
-                    beginning of the line with no preceding whitespace.",
-                ),
-        )
-        .arg(
-            Arg::with_name(OPT_WIDTH)
-                .short("w")
-                .long("width")
-                .help("Fill output lines up to a maximum of WIDTH columns, default 79.")
-                .value_name("WIDTH"),
-        )
-        .arg(
-            Arg::with_name(OPT_GOAL)
-                .short("g")
-                .long("goal")
-                .help("Goal width, default ~0.94*WIDTH. Must be less than WIDTH.")
-                .value_name("GOAL"),
-        )
-        .arg(Arg::with_name(OPT_QUICK).short("q").long("quick").help(
-            "Break lines more quickly at the
-            expense of a potentially more ragged appearance.",
-        ))
-        .arg(
-            Arg::with_name(OPT_TAB_WIDTH)
-                .short("T")
-                .long("tab-width")
-                .help(
-                    "Treat tabs as TABWIDTH spaces for
-                    determining line length, default 8. Note that this is used only for
-                    calculating line lengths; tabs are preserved in the output.",
-                )
-                .value_name("TABWIDTH"),
-        )
-        .arg(Arg::with_name(ARG_FILES).multiple(true).takes_value(true))
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
 
     let mut files: Vec<String> = matches
         .values_of(ARG_FILES)
@@ -220,8 +98,8 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         use_anti_prefix: false,
         anti_prefix: String::new(),
         xanti_prefix: false,
-        width: 79,
-        goal: 74,
+        width: DEFAULT_WIDTH,
+        goal: default_goal(DEFAULT_WIDTH),
         tabwidth: 8,
     };
 
@@ -239,15 +117,17 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         fmt_opts.tagged = false;
     }
     fmt_opts.xprefix = matches.is_present(OPT_EXACT_PREFIX);
-    fmt_opts.xanti_prefix = matches.is_present(OPT_SKIP_PREFIX);
+    fmt_opts.xanti_prefix = matches.is_present(OPT_EXACT_SKIP_PREFIX);
 
-    if let Some(s) = matches.value_of(OPT_PREFIX).map(String::from) {
-        fmt_opts.prefix = s;
+    // like gnu fmt, surrounding spaces in PREFIX are not significant; leading
+    // spaces are only kept when the prefix has to match exactly
+    if let Some(s) = matches.value_of(OPT_PREFIX) {
+        fmt_opts.prefix = trim_prefix(s, fmt_opts.xprefix);
         fmt_opts.use_prefix = true;
     };
 
-    if let Some(s) = matches.value_of(OPT_SKIP_PREFIX).map(String::from) {
-        fmt_opts.anti_prefix = s;
+    if let Some(s) = matches.value_of(OPT_SKIP_PREFIX) {
+        fmt_opts.anti_prefix = trim_prefix(s, fmt_opts.xanti_prefix);
         fmt_opts.use_anti_prefix = true;
     };
 
@@ -265,7 +145,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 fmt_opts.width
             );
         }
-        fmt_opts.goal = cmp::min(fmt_opts.width * 94 / 100, fmt_opts.width - 3);
+        fmt_opts.goal = default_goal(fmt_opts.width);
     };
 
     if let Some(s) = matches.value_of(OPT_GOAL) {
@@ -276,7 +156,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             }
         };
         if !matches.is_present(OPT_WIDTH) {
-            fmt_opts.width = cmp::max(fmt_opts.goal * 100 / 94, fmt_opts.goal + 3);
+            fmt_opts.width = fmt_opts.goal + 10;
         } else if fmt_opts.goal > fmt_opts.width {
             crash!(1, "GOAL cannot be greater than WIDTH.");
         }
@@ -332,3 +212,141 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
     0
 }
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(
+            Arg::with_name(OPT_CROWN_MARGIN)
+                .short("c")
+                .long(OPT_CROWN_MARGIN)
+                .help(
+                    "First and second line of paragraph
+        may have different indentations, in which
+        case the first line's indentation is preserved,
+        and each subsequent line's indentation matches the second line.",
+                ),
+        )
+        .arg(
+            Arg::with_name(OPT_TAGGED_PARAGRAPH)
+                .short("t")
+                .long("tagged-paragraph")
+                .help(
+                    "Like -c, except that the first and second line of a paragraph *must*
+                    have different indentation or they are treated as separate paragraphs.",
+                ),
+        )
+        .arg(
+            Arg::with_name(OPT_PRESERVE_HEADERS)
+                .short("m")
+                .long("preserve-headers")
+                .help(
+                    "Attempt to detect and preserve mail headers in the input.
+                    Be careful when combining this flag with -p.",
+                ),
+        )
+        .arg(
+            Arg::with_name(OPT_SPLIT_ONLY)
+                .short("s")
+                .long("split-only")
+                .help("Split lines only, do not reflow."),
+        )
+        .arg(
+            Arg::with_name(OPT_UNIFORM_SPACING)
+                .short("u")
+                .long("uniform-spacing")
+                .help(
+                    "Insert exactly one
+                    space between words, and two between sentences.
+                    Sentence breaks in the input are detected as [?!.]
+                    followed by two spaces or a newline; other punctuation
+                    is not interpreted as a sentence break.",
+                ),
+        )
+        .arg(
+            Arg::with_name(OPT_PREFIX)
+                .short("p")
+                .long("prefix")
+                .help(
+                    "Reformat only lines
+                    beginning with PREFIX, reattaching PREFIX to reformatted lines.
+                    Unless -x is specified, leading whitespace will be ignored
+                    when matching PREFIX.",
+                )
+                .value_name("PREFIX"),
+        )
+        .arg(
+            Arg::with_name(OPT_SKIP_PREFIX)
+                .short("P")
+                .long("skip-prefix")
+                .help(
+                    "Do not reformat lines
+                    beginning with PSKIP. Unless -X is specified, leading whitespace
+                    will be ignored when matching PSKIP",
+                )
+                .value_name("PSKIP"),
+        )
+        .arg(
+            Arg::with_name(OPT_EXACT_PREFIX)
+                .short("x")
+                .long("exact-prefix")
+                .help(
+                    "PREFIX must match at the
+                    beginning of the line with no preceding whitespace.",
+                ),
+        )
+        .arg(
+            Arg::with_name(OPT_EXACT_SKIP_PREFIX)
+                .short("X")
+                .long("exact-skip-prefix")
+                .help(
+                    "PSKIP must match at the
+                    beginning of the line with no preceding whitespace.",
+                ),
+        )
+        .arg(
+            Arg::with_name(OPT_WIDTH)
+                .short("w")
+                .long("width")
+                .help("Fill output lines up to a maximum of WIDTH columns, default 75.")
+                .value_name("WIDTH"),
+        )
+        .arg(
+            Arg::with_name(OPT_GOAL)
+                .short("g")
+                .long("goal")
+                .help("Goal width, default ~0.93*WIDTH. Must be less than WIDTH.")
+                .value_name("GOAL"),
+        )
+        .arg(Arg::with_name(OPT_QUICK).short("q").long("quick").help(
+            "Break lines more quickly at the
+            expense of a potentially more ragged appearance.",
+        ))
+        .arg(
+            Arg::with_name(OPT_TAB_WIDTH)
+                .short("T")
+                .long("tab-width")
+                .help(
+                    "Treat tabs as TABWIDTH spaces for
+                    determining line length, default 8. Note that this is used only for
+                    calculating line lengths; tabs are preserved in the output.",
+                )
+                .value_name("TABWIDTH"),
+        )
+        .arg(Arg::with_name(ARG_FILES).multiple(true).takes_value(true))
+}
+
+// like gnu fmt, the goal is 7% short of the maximum width
+fn default_goal(width: usize) -> usize {
+    width * (2 * (100 - 7) + 1) / 200
+}
+
+fn trim_prefix(prefix: &str, exact: bool) -> String {
+    let prefix = prefix.trim_end_matches(' ');
+    if exact {
+        prefix.to_owned()
+    } else {
+        prefix.trim_start_matches(' ').to_owned()
+    }
+}
@@ -7,10 +7,8 @@
 
 // spell-checker:ignore (ToDO) INFTY MULT accum breakwords linebreak linebreaking linebreaks linelen maxlength minlength nchars ostream overlen parasplit plass posn powf punct signum slen sstart tabwidth tlen underlen winfo wlen wordlen
 
-use std::cmp;
 use std::i64;
 use std::io::{BufWriter, Stdout, Write};
-use std::mem;
 
 use crate::parasplit::{ParaWords, Paragraph, WordInfo};
 use crate::FmtOptions;
@@ -21,6 +19,7 @@ struct BreakArgs<'a> {
     indent_str: &'a str,
     indent_len: usize,
     uniform: bool,
+    ends_input: bool,
     ostream: &'a mut BufWriter<Stdout>,
 }
 
@@ -51,13 +50,14 @@ pub fn break_lines(para: &Paragraph, opts: &FmtOptions, ostream: &mut BufWriter<
 
     // the first word will *always* appear on the first line
     // make sure of this here
-    let (w, w_len) = match p_words_words.next() {
-        Some(winfo) => (winfo.word, winfo.word_nchars),
+    let first_word = match p_words_words.next() {
+        Some(winfo) => winfo,
         None => {
             silent_unwrap!(ostream.write_all(b"\n"));
             return;
         }
     };
+    let (w, w_len) = (first_word.word, first_word.word_nchars);
     // print the init, if it exists, and get its length
     let p_init_len = w_len
         + if opts.crown || opts.tagged {
@@ -84,13 +84,14 @@ pub fn break_lines(para: &Paragraph, opts: &FmtOptions, ostream: &mut BufWriter<
         indent_str: &p_indent[..],
         indent_len: p_indent_len,
         uniform,
+        ends_input: para.ends_input,
         ostream,
     };
 
     if opts.quick || para.mail_header {
         break_simple(p_words_words, &mut break_args);
     } else {
-        break_knuth_plass(p_words_words, &mut break_args);
+        break_optimal(first_word, p_words_words, &mut break_args);
     }
 }
 
@@ -128,338 +129,171 @@ fn accum_words_simple<'a>(
     }
 }
 
-// break_knuth_plass implements an "optimal" breaking algorithm in the style of
-//    Knuth, D.E., and Plass, M.F. "Breaking Paragraphs into Lines." in Software,
-//    Practice and Experience. Vol. 11, No. 11, November 1981.
-//    http://onlinelibrary.wiley.com/doi/10.1002/spe.4380111102/pdf
-fn break_knuth_plass<'a, T: Clone + Iterator<Item = &'a WordInfo<'a>>>(
-    mut iter: T,
+// break_optimal implements the cost-based line breaking of GNU fmt: for every word,
+// working backwards from the end of the paragraph, find the cheapest way to lay out
+// the rest of the paragraph if a line starts with that word. Short lines, ragged
+// lines, and breaks inside sentences are penalized; breaks after sentences and
+// punctuation are rewarded. The last line of a paragraph is free, so a very
+// short last line only costs what it makes its predecessor look like.
+fn break_optimal<'a, T: Iterator<Item = &'a WordInfo<'a>>>(
+    first: &'a WordInfo<'a>,
+    iter: T,
     args: &mut BreakArgs<'a>,
 ) {
-    // run the algorithm to get the breakpoints
-    let breakpoints = find_kp_breakpoints(iter.clone(), args);
-
-    // iterate through the breakpoints (note that breakpoints is in reverse break order, so we .rev() it
-    let (mut prev_punct, mut fresh) = breakpoints.iter().rev().fold(
-        (false, false),
-        |(mut prev_punct, mut fresh), &(next_break, break_before)| {
-            if fresh {
-                write_newline(args.indent_str, args.ostream);
-            }
-            // at each breakpoint, keep emitting words until we find the word matching this breakpoint
-            for winfo in &mut iter {
-                let (slen, word) = slice_if_fresh(
-                    fresh,
-                    winfo.word,
-                    winfo.word_start,
-                    args.uniform,
-                    winfo.new_line,
-                    winfo.sentence_start,
-                    prev_punct,
-                );
-                fresh = false;
-                prev_punct = winfo.ends_punct;
-
-                // We find identical breakpoints here by comparing addresses of the references.
-                // This is OK because the backing vector is not mutating once we are linebreaking.
-                let winfo_ptr = winfo as *const _;
-                let next_break_ptr = next_break as *const _;
-                if winfo_ptr == next_break_ptr {
-                    // OK, we found the matching word
-                    if break_before {
-                        write_newline(args.indent_str, args.ostream);
-                        write_with_spaces(&winfo.word[winfo.word_start..], 0, args.ostream);
-                    } else {
-                        // breaking after this word, so that means "fresh" is true for the next iteration
-                        write_with_spaces(word, slen, args.ostream);
-                        fresh = true;
-                    }
-                    break;
-                } else {
-                    write_with_spaces(word, slen, args.ostream);
-                }
-            }
-            (prev_punct, fresh)
-        },
-    );
-
-    // after the last linebreak, write out the rest of the final line.
-    for winfo in iter {
-        if fresh {
+    let words: Vec<&WordInfo> = Some(first).into_iter().chain(iter).collect();
+    let breaks = find_optimal_breakpoints(&words, args);
+    let mut breaks = breaks.iter().peekable();
+
+    let mut prev_punct = first.ends_punct;
+    for (i, winfo) in words.iter().enumerate().skip(1) {
+        if breaks.peek() == Some(&&i) {
+            breaks.next();
             write_newline(args.indent_str, args.ostream);
+            write_with_spaces(&winfo.word[winfo.word_start..], 0, args.ostream);
+        } else {
+            let slen = compute_slen(
+                args.uniform,
+                winfo.new_line,
+                winfo.sentence_start,
+                prev_punct,
+            );
+            write_with_spaces(winfo.word, slen, args.ostream);
         }
-        let (slen, word) = slice_if_fresh(
-            fresh,
-            winfo.word,
-            winfo.word_start,
-            args.uniform,
-            winfo.new_line,
-            winfo.sentence_start,
-            prev_punct,
-        );
         prev_punct = winfo.ends_punct;
-        fresh = false;
-        write_with_spaces(word, slen, args.ostream);
     }
     silent_unwrap!(args.ostream.write_all(b"\n"));
 }
 
-struct LineBreak<'a> {
-    prev: usize,
-    linebreak: Option<&'a WordInfo<'a>>,
-    break_before: bool,
-    demerits: i64,
-    prev_rat: f32,
-    length: usize,
-    fresh: bool,
+// costs are squared "equivalent" character counts, as in GNU fmt
+const fn equiv(n: i64) -> i64 {
+    n * n
+}
+const LINE_COST: i64 = equiv(70);
+const SENTENCE_BONUS: i64 = equiv(50);
+const NOBREAK_COST: i64 = equiv(600);
+const PAREN_BONUS: i64 = equiv(40);
+const PUNCT_BONUS: i64 = equiv(40);
+
+// cost of a line that is n columns shorter than the goal
+fn short_cost(n: i64) -> i64 {
+    equiv(n * 10)
 }
 
-fn find_kp_breakpoints<'a, T: Iterator<Item = &'a WordInfo<'a>>>(
-    iter: T,
-    args: &BreakArgs<'a>,
-) -> Vec<(&'a WordInfo<'a>, bool)> {
-    let mut iter = iter.peekable();
-    // set up the initial null linebreak
-    let mut linebreaks = vec![LineBreak {
-        prev: 0,
-        linebreak: None,
-        break_before: false,
-        demerits: 0,
-        prev_rat: 0.0f32,
-        length: args.init_len,
-        fresh: false,
-    }];
-    // this vec holds the current active linebreaks; next_ holds the breaks that will be active for
-    // the next word
-    let active_breaks = &mut vec![0];
-    let next_active_breaks = &mut vec![];
-
-    let stretch = (args.opts.width - args.opts.goal) as isize;
-    let minlength = args.opts.goal - stretch as usize;
-    let mut new_linebreaks = vec![];
-    let mut is_sentence_start = false;
-    let mut least_demerits = 0;
-    loop {
-        let w = match iter.next() {
-            None => break,
-            Some(w) => w,
-        };
+// cost of a line that is n columns longer or shorter than the following one
+fn ragged_cost(n: i64) -> i64 {
+    short_cost(n) / 2
+}
 
-        // if this is the last word, we don't add additional demerits for this break
-        let (is_last_word, is_sentence_end) = match iter.peek() {
-            None => (true, true),
-            Some(&&WordInfo {
-                sentence_start: st,
-                new_line: nl,
-                ..
-            }) => (false, st || (nl && w.ends_punct)),
-        };
+// cost of breaking a line after the first word of a sentence
+fn widow_cost(len: usize) -> i64 {
+    equiv(200) / (len as i64 + 2)
+}
 
-        // should we be adding extra space at the beginning of the next sentence?
-        let slen = compute_slen(args.uniform, w.new_line, is_sentence_start, false);
-
-        let mut ld_new = i64::MAX;
-        let mut ld_next = i64::MAX;
-        let mut ld_idx = 0;
-        new_linebreaks.clear();
-        next_active_breaks.clear();
-        // go through each active break, extending it and possibly adding a new active
-        // break if we are above the minimum required length
-        for &i in active_breaks.iter() {
-            let active = &mut linebreaks[i];
-            // normalize demerits to avoid overflow, and record if this is the least
-            active.demerits -= least_demerits;
-            if active.demerits < ld_next {
-                ld_next = active.demerits;
-                ld_idx = i;
-            }
+// cost of breaking a line before the last word of a sentence
+fn orphan_cost(len: usize) -> i64 {
+    equiv(150) / (len as i64 + 2)
+}
 
-            // get the new length
-            let tlen = w.word_nchars
-                + args.compute_width(w, active.length, active.fresh)
-                + slen
-                + active.length;
-
-            // if tlen is longer than args.opts.width, we drop this break from the active list
-            // otherwise, we extend the break, and possibly add a new break at this point
-            if tlen <= args.opts.width {
-                // this break will still be active next time
-                next_active_breaks.push(i);
-                // we can put this word on this line
-                active.fresh = false;
-                active.length = tlen;
-
-                // if we're above the minlength, we can also consider breaking here
-                if tlen >= minlength {
-                    let (new_demerits, new_ratio) = if is_last_word {
-                        // there is no penalty for the final line's length
-                        (0, 0.0)
-                    } else {
-                        compute_demerits(
-                            (args.opts.goal - tlen) as isize,
-                            stretch,
-                            w.word_nchars as isize,
-                            active.prev_rat,
-                        )
-                    };
-
-                    // do not even consider adding a line that has too many demerits
-                    // also, try to detect overflow by checking signum
-                    let total_demerits = new_demerits + active.demerits;
-                    if new_demerits < BAD_INFTY_SQ
-                        && total_demerits < ld_new
-                        && active.demerits.signum() <= new_demerits.signum()
-                    {
-                        ld_new = total_demerits;
-                        new_linebreaks.push(LineBreak {
-                            prev: i,
-                            linebreak: Some(w),
-                            break_before: false,
-                            demerits: total_demerits,
-                            prev_rat: new_ratio,
-                            length: args.indent_len,
-                            fresh: true,
-                        });
-                    }
+fn find_optimal_breakpoints<'a>(words: &[&'a WordInfo<'a>], args: &BreakArgs<'a>) -> Vec<usize> {
+    let count = words.len();
+    let max_width = args.opts.width;
+    let goal = args.opts.goal as i64;
+
+    // a word ends a sentence if it ends in [.?!] and is followed by a sentence break;
+    // like GNU fmt, the very last word of the input always counts as one
+    let finals: Vec<bool> = (0..count)
+        .map(|i| match words.get(i + 1) {
+            None => args.ends_input || words[i].ends_punct,
+            Some(next) => words[i].ends_punct && (next.sentence_start || next.new_line),
+        })
+        .collect();
+
+    // for a line starting at word i: the cost of the remaining paragraph, the
+    // word starting the following line, and the length of the line
+    let mut best_cost = vec![0i64; count + 1];
+    let mut next_break = vec![count; count + 1];
+    let mut line_length = vec![0usize; count + 1];
+
+    for start in (0..count).rev() {
+        let mut best = i64::MAX;
+        let mut len = if start == 0 {
+            args.init_len
+        } else {
+            args.indent_len + words[start].word_nchars
+        };
+
+        // at least one word, however long, goes on each line
+        let mut next = start + 1;
+        loop {
+            let mut cost = best_cost[next];
+            if next < count {
+                cost += short_cost(goal - len as i64);
+                if next_break[next] != count {
+                    cost += ragged_cost(len as i64 - line_length[next] as i64);
                 }
             }
-        }
-
-        // if we generated any new linebreaks, add the last one to the list
-        // the last one is always the best because we don't add to new_linebreaks unless
-        // it's better than the best one so far
-        match new_linebreaks.pop() {
-            None => (),
-            Some(lb) => {
-                next_active_breaks.push(linebreaks.len());
-                linebreaks.push(lb);
+            if cost < best {
+                best = cost;
+                next_break[start] = next;
+                line_length[start] = len;
             }
-        }
 
-        if next_active_breaks.is_empty() {
-            // every potential linebreak is too long! choose the linebreak with the least demerits, ld_idx
-            let new_break =
-                restart_active_breaks(args, &linebreaks[ld_idx], ld_idx, w, slen, minlength);
-            next_active_breaks.push(linebreaks.len());
-            linebreaks.push(new_break);
-            least_demerits = 0;
-        } else {
-            // next time around, normalize out the demerits fields
-            // on active linebreaks to make overflow less likely
-            least_demerits = cmp::max(ld_next, 0);
+            if next == count {
+                break;
+            }
+            let winfo = words[next];
+            len += winfo.word_nchars
+                + args.compute_width(winfo, len, false)
+                + compute_slen(
+                    args.uniform,
+                    winfo.new_line,
+                    winfo.sentence_start,
+                    words[next - 1].ends_punct,
+                );
+            if len >= max_width {
+                break;
+            }
+            next += 1;
         }
-        // swap in new list of active breaks
-        mem::swap(active_breaks, next_active_breaks);
-        // If this was the last word in a sentence, the next one must be the first in the next.
-        is_sentence_start = is_sentence_end;
+        best_cost[start] = best + base_cost(words, &finals, start);
     }
 
-    // return the best path
-    build_best_path(&linebreaks, active_breaks)
+    let mut breaks = vec![];
+    let mut i = next_break[0];
+    while i < count {
+        breaks.push(i);
+        i = next_break[i];
+    }
+    breaks
 }
 
-fn build_best_path<'a>(paths: &[LineBreak<'a>], active: &[usize]) -> Vec<(&'a WordInfo<'a>, bool)> {
-    let mut breakwords = vec![];
-    // of the active paths, we select the one with the fewest demerits
-    let mut best_idx = match active.iter().min_by_key(|&&a| paths[a].demerits) {
-        None => crash!(
-            1,
-            "Failed to find a k-p linebreak solution. This should never happen."
-        ),
-        Some(&s) => s,
-    };
-
-    // now, chase the pointers back through the break list, recording
-    // the words at which we should break
-    loop {
-        let next_best = &paths[best_idx];
-        match next_best.linebreak {
-            None => return breakwords,
-            Some(prev) => {
-                breakwords.push((prev, next_best.break_before));
-                best_idx = next_best.prev
+// the part of the cost of breaking before word i that does not depend on line lengths
+fn base_cost(words: &[&WordInfo], finals: &[bool], i: usize) -> i64 {
+    let mut cost = LINE_COST;
+    if i > 0 {
+        let prev = words[i - 1];
+        if prev.ends_punct {
+            if finals[i - 1] {
+                cost -= SENTENCE_BONUS;
+            } else {
+                cost += NOBREAK_COST;
             }
+        } else if trimmed(prev).ends_with(|c: char| c.is_ascii_punctuation()) {
+            cost -= PUNCT_BONUS;
+        } else if i > 1 && finals[i - 2] {
+            cost += widow_cost(prev.word_nchars);
         }
     }
+    if trimmed(words[i]).starts_with(|c: char| "(['`\"".contains(c)) {
+        cost -= PAREN_BONUS;
+    } else if finals[i] {
+        cost += orphan_cost(words[i].word_nchars);
+    }
+    cost
 }
 
-// "infinite" badness is more like (1+BAD_INFTY)^2 because of how demerits are computed
-const BAD_INFTY: i64 = 10_000_000;
-const BAD_INFTY_SQ: i64 = BAD_INFTY * BAD_INFTY;
-// badness = BAD_MULT * abs(r) ^ 3
-const BAD_MULT: f32 = 100.0;
-// DR_MULT is multiplier for delta-R between lines
-const DR_MULT: f32 = 600.0;
-// DL_MULT is penalty multiplier for short words at end of line
-const DL_MULT: f32 = 300.0;
-
-fn compute_demerits(delta_len: isize, stretch: isize, wlen: isize, prev_rat: f32) -> (i64, f32) {
-    // how much stretch are we using?
-    let ratio = if delta_len == 0 {
-        0.0f32
-    } else {
-        delta_len as f32 / stretch as f32
-    };
-
-    // compute badness given the stretch ratio
-    let bad_linelen = if ratio.abs() > 1.0f32 {
-        BAD_INFTY
-    } else {
-        (BAD_MULT * ratio.powf(3f32).abs()) as i64
-    };
-
-    // we penalize lines ending in really short words
-    let bad_wordlen = if wlen >= stretch {
-        0
-    } else {
-        (DL_MULT
-            * ((stretch - wlen) as f32 / (stretch - 1) as f32)
-                .powf(3f32)
-                .abs()) as i64
-    };
-
-    // we penalize lines that have very different ratios from previous lines
-    let bad_delta_r = (DR_MULT * (((ratio - prev_rat) / 2.0).powf(3f32)).abs()) as i64;
-
-    let demerits = i64::pow(1 + bad_linelen + bad_wordlen + bad_delta_r, 2);
-
-    (demerits, ratio)
-}
-
-fn restart_active_breaks<'a>(
-    args: &BreakArgs<'a>,
-    active: &LineBreak<'a>,
-    act_idx: usize,
-    w: &'a WordInfo<'a>,
-    slen: usize,
-    min: usize,
-) -> LineBreak<'a> {
-    let (break_before, line_length) = if active.fresh {
-        // never break before a word if that word would be the first on a line
-        (false, args.indent_len)
-    } else {
-        // choose the lesser evil: breaking too early, or breaking too late
-        let wlen = w.word_nchars + args.compute_width(w, active.length, active.fresh);
-        let underlen = (min - active.length) as isize;
-        let overlen = ((wlen + slen + active.length) - args.opts.width) as isize;
-        if overlen > underlen {
-            // break early, put this word on the next line
-            (true, args.indent_len + w.word_nchars)
-        } else {
-            (false, args.indent_len)
-        }
-    };
-
-    // restart the linebreak. This will be our only active path.
-    LineBreak {
-        prev: act_idx,
-        linebreak: Some(w),
-        break_before,
-        demerits: 0, // this is the only active break, so we can reset the demerit count
-        prev_rat: if break_before { 1.0 } else { -1.0 },
-        length: line_length,
-        fresh: !break_before,
-    }
+fn trimmed<'a>(winfo: &WordInfo<'a>) -> &'a str {
+    &winfo.word[winfo.word_start..]
 }
 
 // Number of spaces to add before a word, based on mode, newline, sentence start.
@@ -475,24 +309,6 @@ fn compute_slen(uniform: bool, newline: bool, start: bool, punct: bool) -> usize
     }
 }
 
-// If we're on a fresh line, slen=0 and we slice off leading whitespace.
-// Otherwise, compute slen and leave whitespace alone.
-fn slice_if_fresh(
-    fresh: bool,
-    word: &str,
-    start: usize,
-    uniform: bool,
-    newline: bool,
-    sstart: bool,
-    punct: bool,
-) -> (usize, &str) {
-    if fresh {
-        (0, &word[start..])
-    } else {
-        (compute_slen(uniform, newline, sstart, punct), word)
-    }
-}
-
 // Write a newline and add the indent.
 fn write_newline(indent: &str, ostream: &mut BufWriter<Stdout>) {
     silent_unwrap!(ostream.write_all(b"\n"));
@@ -221,6 +221,7 @@ pub struct Paragraph {
     pub indent_len: usize,  // length of above
     indent_end: usize, // byte location of end of indent (in crown and tagged mode, only applies to 2nd line and onward)
     pub mail_header: bool, // we need to know if this is a mail header because we do word splitting differently in that case
+    pub ends_input: bool,  // whether this is the last paragraph of the input
 }
 
 // an iterator producing a stream of paragraphs from a stream of lines
@@ -350,14 +351,19 @@ impl<'a> Iterator for ParagraphStream<'a> {
                         prefix_len = fl.prefix_len;
                         pfxind_end = fl.pfxind_end;
 
-                        // in tagged mode, add 4 spaces of additional indenting by default
-                        // (gnu fmt's behavior is different: it seems to find the closest column to
-                        // indent_end that is divisible by 3. But honestly that behavior seems
-                        // pretty arbitrary.
-                        // Perhaps a better default would be 1 TABWIDTH? But ugh that's so big.
+                        // in tagged mode, a one-line paragraph is continued like gnu fmt does:
+                        // indented by 3 if the first line is flush left, otherwise flush left
+                        // (after the prefix, if there is one)
                         if self.opts.tagged {
-                            indent_str.push_str("    ");
-                            indent_len += 4;
+                            indent_str.clear();
+                            if fl.indent_len == 0 {
+                                indent_str.push_str("   ");
+                                indent_len = 3;
+                            } else {
+                                let prefix_end = fl.pfxind_end + self.opts.prefix.len();
+                                indent_str.push_str(&fl.line[..prefix_end]);
+                                indent_len = fl.prefix_len;
+                            }
                         }
                     }
                 } else if in_mail {
@@ -374,10 +380,7 @@ impl<'a> Iterator for ParagraphStream<'a> {
                     }
 
                     // in tagged mode, indent has to be *different* on following lines
-                    if self.opts.tagged
-                        && indent_len - 4 == fl.indent_len
-                        && indent_end == fl.indent_end
-                    {
+                    if self.opts.tagged && init_len == fl.indent_len && init_end == fl.indent_end {
                         break;
                     }
 
@@ -422,6 +425,7 @@ impl<'a> Iterator for ParagraphStream<'a> {
             indent_len,
             indent_end,
             mail_header: in_mail,
+            ends_input: self.lines.peek().is_none(),
         }))
     }
 }
@@ -540,6 +544,15 @@ impl<'a> WordSplit<'a> {
         }
     }
 
+    fn is_closing(c: char) -> bool {
+        #[allow(clippy::match_like_matches_macro)]
+        // `matches!(...)` macro not stabilized until rust v1.42
+        match c {
+            ')' | ']' | '\'' | '"' => true,
+            _ => false,
+        }
+    }
+
     fn is_punctuation(c: char) -> bool {
         #[allow(clippy::match_like_matches_macro)]
         // `matches!(...)` macro not stabilized until rust v1.42
@@ -604,11 +617,13 @@ impl<'a> Iterator for WordSplit<'a> {
         let is_start_of_sentence =
             self.prev_punct && (before_tab.is_some() || word_start_relative > 1);
 
-        // now record whether this word ends in punctuation
-        self.prev_punct = match self.string[..self.position].chars().rev().next() {
-            Some(ch) => WordSplit::is_punctuation(ch),
-            _ => panic!("fatal: expected word not to be empty"),
-        };
+        // now record whether this word ends in punctuation, looking past closing
+        // quotes and brackets as in `(like this.)`
+        self.prev_punct = self.string[word_start..self.position]
+            .chars()
+            .rev()
+            .find(|&c| !WordSplit::is_closing(c))
+            .map_or(false, WordSplit::is_punctuation);
 
         let (word, word_start_relative, before_tab, after_tab) = if self.opts.uniform {
             (&self.string[word_start..self.position], 0, None, 0)
@@ -5,43 +5,28 @@
 //  * For the full copyright and license information, please view the LICENSE
 //  * file that was distributed with this source code.
 
-// spell-checker:ignore (ToDOs) ncount routput
-
 #[macro_use]
 extern crate uucore;
 
 use std::fs::File;
-use std::io::{stdin, BufRead, BufReader, Read};
+use std::io::{stdin, stdout, BufRead, BufReader, Read, Write};
 use std::path::Path;
+use std::str;
+use unicode_width::UnicodeWidthChar;
+use uucore::coreopts::CoreOptions;
 
 static SYNTAX: &str = "[OPTION]... [FILE]...";
 static SUMMARY: &str = "Writes each file (or standard input if no files are given)
  to standard output whilst breaking long lines";
 static LONG_HELP: &str = "";
 
+const TAB_WIDTH: usize = 8;
+
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
     let (args, obs_width) = handle_obsolete(&args[..]);
-    let matches = app!(SYNTAX, SUMMARY, LONG_HELP)
-        .optflag(
-            "b",
-            "bytes",
-            "count using bytes rather than columns (meaning control characters \
-             such as newline are not treated specially)",
-        )
-        .optflag(
-            "s",
-            "spaces",
-            "break lines at word boundaries rather than a hard cut-off",
-        )
-        .optopt(
-            "w",
-            "width",
-            "set WIDTH as the maximum line width rather than 80",
-            "WIDTH",
-        )
-        .parse(args);
+    let matches = uu_app().parse(args);
 
     let bytes = matches.opt_present("b");
     let spaces = matches.opt_present("s");
@@ -67,6 +52,28 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     0
 }
 
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    app.optflag(
+        "b",
+        "bytes",
+        "count using bytes rather than columns (meaning control characters \
+         such as newline are not treated specially)",
+    )
+    .optflag(
+        "s",
+        "spaces",
+        "break lines at word boundaries rather than a hard cut-off",
+    )
+    .optopt(
+        "w",
+        "width",
+        "set WIDTH as the maximum line width rather than 80",
+        "WIDTH",
+    );
+    app
+}
+
 fn handle_obsolete(args: &[String]) -> (Vec<String>, Option<String>) {
     for (i, arg) in args.iter().enumerate() {
         let slice = &arg;
@@ -97,115 +104,117 @@ fn fold(filenames: Vec<String>, bytes: bool, spaces: bool, width: usize) {
 }
 
 #[inline]
-fn fold_file<T: Read>(file: BufReader<T>, bytes: bool, spaces: bool, width: usize) {
-    for line_result in file.lines() {
-        let mut line = safe_unwrap!(line_result);
-        if bytes {
-            let len = line.len();
-            let mut i = 0;
-            while i < len {
-                let width = if len - i >= width { width } else { len - i };
-                let slice = {
-                    let slice = &line[i..i + width];
-                    if spaces && i + width < len {
-                        match slice.rfind(char::is_whitespace) {
-                            Some(m) => &slice[..=m],
-                            None => slice,
-                        }
-                    } else {
-                        slice
-                    }
-                };
-                print!("{}", slice);
-                i += slice.len();
+fn fold_file<T: Read>(mut file: BufReader<T>, bytes: bool, spaces: bool, width: usize) {
+    let stdout = stdout();
+    let mut stdout = stdout.lock();
+    let mut line = Vec::new();
+    let mut output = Vec::new();
+    loop {
+        line.clear();
+        if safe_unwrap!(file.read_until(b'\n', &mut line)) == 0 {
+            break;
+        }
+        fold_line(&line, bytes, spaces, width, &mut output);
+        safe_unwrap!(stdout.write_all(&output));
+        output.clear();
+    }
+}
+
+/// Fold a single line (including its trailing newline, if any) into `output`.
+///
+/// A character that would take the column past `width` starts a new output
+/// line; with `spaces`, the line is instead broken after its last blank and
+/// the rest is carried over to the next line. Unless counting bytes,
+/// multibyte UTF-8 characters are never split.
+fn fold_line(line: &[u8], bytes: bool, spaces: bool, width: usize, output: &mut Vec<u8>) {
+    let mut column = 0;
+    let mut current = Vec::with_capacity(line.len());
+    let mut rest = line;
+    while !rest.is_empty() {
+        let (c, tail) = rest.split_at(char_len(rest, bytes));
+        rest = tail;
+        if c == b"\n" {
+            current.push(b'\n');
+            break;
+        }
+        loop {
+            column = adjust_column(column, c, bytes);
+            if column <= width {
+                current.extend_from_slice(c);
+                break;
             }
-        } else {
-            let mut len = line.chars().count();
-            let newline = line.ends_with('\n');
-            if newline {
-                if len == 1 {
-                    println!();
+            if spaces {
+                if let Some(blank) = current.iter().rposition(|&b| b == b' ' || b == b'\t') {
+                    output.extend_from_slice(&current[..=blank]);
+                    output.push(b'\n');
+                    current.drain(..=blank);
+                    column = line_width(&current, bytes);
                     continue;
                 }
-                len -= 1;
-                line.truncate(len);
-            }
-            let mut output = String::new();
-            let mut count = 0;
-            for (i, ch) in line.chars().enumerate() {
-                if count >= width {
-                    let (val, ncount) = {
-                        let slice = &output[..];
-                        let (out, val, ncount) = if spaces && i + 1 < len {
-                            match rfind_whitespace(slice) {
-                                Some(m) => {
-                                    let routput = &slice[m + 1..slice.chars().count()];
-                                    let ncount = routput.chars().fold(0, |out, ch: char| {
-                                        out + match ch {
-                                            '\t' => 8,
-                                            '\x08' => {
-                                                if out > 0 {
-                                                    !0
-                                                } else {
-                                                    0
-                                                }
-                                            }
-                                            '\r' => return 0,
-                                            _ => 1,
-                                        }
-                                    });
-                                    (&slice[0..=m], routput, ncount)
-                                }
-                                None => (slice, "", 0),
-                            }
-                        } else {
-                            (slice, "", 0)
-                        };
-                        println!("{}", out);
-                        (val.to_owned(), ncount)
-                    };
-                    output = val;
-                    count = ncount;
-                }
-                match ch {
-                    '\t' => {
-                        count += 8;
-                        if count > width {
-                            println!("{}", output);
-                            output.truncate(0);
-                            count = 8;
-                        }
-                    }
-                    '\x08' => {
-                        if count > 0 {
-                            count -= 1;
-                            let len = output.len() - 1;
-                            output.truncate(len);
-                        }
-                        continue;
-                    }
-                    '\r' => {
-                        output.truncate(0);
-                        count = 0;
-                        continue;
-                    }
-                    _ => count += 1,
-                };
-                output.push(ch);
             }
-            if count > 0 {
-                println!("{}", output);
+            if current.is_empty() {
+                current.extend_from_slice(c);
+                break;
             }
+            output.extend_from_slice(&current);
+            output.push(b'\n');
+            current.clear();
+            column = 0;
         }
     }
+    output.extend_from_slice(&current);
 }
 
-#[inline]
-fn rfind_whitespace(slice: &str) -> Option<usize> {
-    for (i, ch) in slice.chars().rev().enumerate() {
-        if ch.is_whitespace() {
-            return Some(slice.chars().count() - (i + 1));
+/// Length of the character at the start of `bytes`: a whole UTF-8 sequence, or
+/// a single byte when counting bytes or when the input is not valid UTF-8.
+fn char_len(bytes: &[u8], count_bytes: bool) -> usize {
+    if count_bytes {
+        return 1;
+    }
+    let len = match bytes[0] {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return 1,
+    };
+    if bytes.len() >= len && str::from_utf8(&bytes[..len]).is_ok() {
+        len
+    } else {
+        1
+    }
+}
+
+/// Column reached after printing all of `line` from the start of a line.
+fn line_width(line: &[u8], bytes: bool) -> usize {
+    let mut column = 0;
+    let mut rest = line;
+    while !rest.is_empty() {
+        let (c, tail) = rest.split_at(char_len(rest, bytes));
+        column = adjust_column(column, c, bytes);
+        rest = tail;
+    }
+    column
+}
+
+/// Compute the column after printing the character `c` at `column`: tabs
+/// advance to the next tab stop, backspaces move back one column, carriage
+/// returns go back to the start of the line, and wide characters take two
+/// columns, unless only bytes are being counted.
+fn adjust_column(column: usize, c: &[u8], bytes: bool) -> usize {
+    if bytes {
+        return column + 1;
+    }
+    match c {
+        b"\x08" => column.saturating_sub(1),
+        b"\r" => 0,
+        b"\t" => column + TAB_WIDTH - column % TAB_WIDTH,
+        _ => {
+            column
+                + str::from_utf8(c)
+                    .ok()
+                    .and_then(|s| s.chars().next())
+                    .and_then(UnicodeWidthChar::width)
+                    .unwrap_or(1)
         }
     }
-    None
 }
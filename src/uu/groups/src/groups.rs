@@ -11,54 +11,91 @@
 #[macro_use]
 extern crate uucore;
 use uucore::entries::{get_groups, gid2grp, Locate, Passwd};
+use uucore::libc::gid_t;
+use uucore::process::{getegid, getgid};
 
 use clap::{App, Arg};
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
-static ABOUT: &str = "display current group names";
-static OPT_USER: &str = "user";
+static ABOUT: &str = "Print group memberships for each USERNAME or, if no USERNAME is specified, for\nthe current process (which may differ if the groups database has changed).";
+static OPT_USERS: &str = "users";
 
 fn get_usage() -> String {
-    format!("{0} [USERNAME]", executable!())
+    format!("{0} [USERNAME]...", executable!())
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let users: Vec<&str> = matches
+        .values_of(OPT_USERS)
+        .map(|v| v.collect())
+        .unwrap_or_default();
+
+    if users.is_empty() {
+        let groups = get_groups().unwrap_or_default();
+        return print_groups(None, getgid(), getegid(), &groups);
+    }
+
+    let mut status = 0;
+    for user in users {
+        // users are named; a number isn't taken for an ID
+        let passwd = if user.parse::<u32>().is_ok() {
+            None
+        } else {
+            Passwd::locate(user).ok()
+        };
+        match passwd {
+            Some(p) => {
+                // the groups the database has the user in, not those of this process
+                let groups = p.belongs_to();
+                status |= print_groups(Some(user), p.gid(), p.gid(), &groups);
+            }
+            None => {
+                show_info!("'{}': no such user", user);
+                status = 1;
+            }
+        }
+    }
+    status
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
-        .arg(Arg::with_name(OPT_USER))
-        .get_matches_from(args);
+        .arg(Arg::with_name(OPT_USERS).multiple(true).takes_value(true))
+}
 
-    match matches.value_of(OPT_USER) {
-        None => {
-            println!(
-                "{}",
-                get_groups()
-                    .unwrap()
-                    .iter()
-                    .map(|&g| gid2grp(g).unwrap())
-                    .collect::<Vec<_>>()
-                    .join(" ")
-            );
-            0
-        }
-        Some(user) => {
-            if let Ok(p) = Passwd::locate(user) {
-                println!(
-                    "{}",
-                    p.belongs_to()
-                        .iter()
-                        .map(|&g| gid2grp(g).unwrap())
-                        .collect::<Vec<_>>()
-                        .join(" ")
-                );
-                0
-            } else {
-                crash!(1, "unknown user {}", user);
-            }
+/// Print the real group, the effective one if it differs, and then the
+/// others, after `user :` when a user was named.
+fn print_groups(user: Option<&str>, rgid: gid_t, egid: gid_t, groups: &[gid_t]) -> i32 {
+    let mut list = vec![rgid];
+    if egid != rgid {
+        list.push(egid);
+    }
+    for &gid in groups {
+        if !list.contains(&gid) {
+            list.push(gid);
         }
     }
+
+    let mut status = 0;
+    let names: Vec<String> = list
+        .iter()
+        .map(|&gid| {
+            gid2grp(gid).unwrap_or_else(|_| {
+                show_info!("cannot find name for group ID {}", gid);
+                status = 1;
+                gid.to_string()
+            })
+        })
+        .collect();
+    match user {
+        Some(user) => println!("{} : {}", user, names.join(" ")),
+        None => println!("{}", names.join(" ")),
+    }
+    status
 }
@@ -7,7 +7,7 @@
 //  * For the full copyright and license information, please view the LICENSE
 //  * file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) algo, algoname, regexes, nread
+// spell-checker:ignore (ToDO) algo, algoname
 
 #[macro_use]
 extern crate clap;
@@ -15,25 +15,21 @@ extern crate clap;
 #[macro_use]
 extern crate uucore;
 
-mod digest;
-
-use self::digest::Digest;
-
-use blake2_rfc::blake2b::Blake2b;
 use clap::{App, Arg, ArgMatches};
 use hex::ToHex;
-use md5::Context as Md5;
-use regex::Regex;
-use sha1::Sha1;
-use sha2::{Sha224, Sha256, Sha384, Sha512};
-use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256};
-use std::cmp::Ordering;
+use rayon::ThreadPoolBuilder;
+use std::collections::HashMap;
 use std::ffi::{OsStr, OsString};
-use std::fs::File;
-use std::io::{self, stdin, BufRead, BufReader, Read};
 use std::iter;
 use std::num::ParseIntError;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use uucore::error_message;
+use uucore::sum::{
+    self, digest_file, escape, from_hex, needs_escape, open, parse_line, Blake2b, Blake3,
+    CheckOptions, ChecksumLine, Digest, Listed, Md5, Sha1, Sha224, Sha256, Sha384, Sha3_224,
+    Sha3_256, Sha3_384, Sha3_512, Sha512, Shake128, Shake256,
+};
 
 const NAME: &str = "hashsum";
 
@@ -47,6 +43,9 @@ struct Options {
     quiet: bool,
     strict: bool,
     warn: bool,
+    ignore_missing: bool,
+    zero: bool,
+    jobs: usize,
     output_bits: usize,
 }
 
@@ -56,7 +55,7 @@ fn is_custom_binary(program: &str) -> bool {
     match program {
         "md5sum" | "sha1sum" | "sha224sum" | "sha256sum" | "sha384sum" | "sha512sum"
         | "sha3sum" | "sha3-224sum" | "sha3-256sum" | "sha3-384sum" | "sha3-512sum"
-        | "shake128sum" | "shake256sum" | "b2sum" => true,
+        | "shake128sum" | "shake256sum" | "b2sum" | "b3sum" => true,
         _ => false,
     }
 }
@@ -76,37 +75,9 @@ fn detect_algo<'a>(
         "sha256sum" => ("SHA256", Box::new(Sha256::new()) as Box<dyn Digest>, 256),
         "sha384sum" => ("SHA384", Box::new(Sha384::new()) as Box<dyn Digest>, 384),
         "sha512sum" => ("SHA512", Box::new(Sha512::new()) as Box<dyn Digest>, 512),
-        "b2sum" => ("BLAKE2", Box::new(Blake2b::new(64)) as Box<dyn Digest>, 512),
-        "sha3sum" => match matches.value_of("bits") {
-            Some(bits_str) => match usize::from_str_radix(&bits_str, 10) {
-                Ok(224) => (
-                    "SHA3-224",
-                    Box::new(Sha3_224::new()) as Box<dyn Digest>,
-                    224,
-                ),
-                Ok(256) => (
-                    "SHA3-256",
-                    Box::new(Sha3_256::new()) as Box<dyn Digest>,
-                    256,
-                ),
-                Ok(384) => (
-                    "SHA3-384",
-                    Box::new(Sha3_384::new()) as Box<dyn Digest>,
-                    384,
-                ),
-                Ok(512) => (
-                    "SHA3-512",
-                    Box::new(Sha3_512::new()) as Box<dyn Digest>,
-                    512,
-                ),
-                Ok(_) => crash!(
-                    1,
-                    "Invalid output size for SHA3 (expected 224, 256, 384, or 512)"
-                ),
-                Err(err) => crash!(1, "{}", err),
-            },
-            None => crash!(1, "--bits required for SHA3"),
-        },
+        "b2sum" => blake2b(matches),
+        "b3sum" => ("BLAKE3", Box::new(Blake3::new()) as Box<dyn Digest>, 256),
+        "sha3sum" => sha3(matches),
         "sha3-224sum" => (
             "SHA3-224",
             Box::new(Sha3_224::new()) as Box<dyn Digest>,
@@ -127,28 +98,16 @@ fn detect_algo<'a>(
             Box::new(Sha3_512::new()) as Box<dyn Digest>,
             512,
         ),
-        "shake128sum" => match matches.value_of("bits") {
-            Some(bits_str) => match usize::from_str_radix(&bits_str, 10) {
-                Ok(bits) => (
-                    "SHAKE128",
-                    Box::new(Shake128::new()) as Box<dyn Digest>,
-                    bits,
-                ),
-                Err(err) => crash!(1, "{}", err),
-            },
-            None => crash!(1, "--bits required for SHAKE-128"),
-        },
-        "shake256sum" => match matches.value_of("bits") {
-            Some(bits_str) => match usize::from_str_radix(&bits_str, 10) {
-                Ok(bits) => (
-                    "SHAKE256",
-                    Box::new(Shake256::new()) as Box<dyn Digest>,
-                    bits,
-                ),
-                Err(err) => crash!(1, "{}", err),
-            },
-            None => crash!(1, "--bits required for SHAKE-256"),
-        },
+        "shake128sum" => (
+            "SHAKE128",
+            Box::new(Shake128::new()) as Box<dyn Digest>,
+            shake_bits(matches, "SHAKE-128"),
+        ),
+        "shake256sum" => (
+            "SHAKE256",
+            Box::new(Shake256::new()) as Box<dyn Digest>,
+            shake_bits(matches, "SHAKE-256"),
+        ),
         _ => {
             {
                 let mut set_or_crash = |n, val, bits| {
@@ -178,39 +137,15 @@ fn detect_algo<'a>(
                     set_or_crash("SHA512", Box::new(Sha512::new()), 512)
                 }
                 if matches.is_present("b2sum") {
-                    set_or_crash("BLAKE2", Box::new(Blake2b::new(64)), 512)
+                    let (name, digest, bits) = blake2b(matches);
+                    set_or_crash(name, digest, bits)
+                }
+                if matches.is_present("b3sum") {
+                    set_or_crash("BLAKE3", Box::new(Blake3::new()), 256)
                 }
                 if matches.is_present("sha3") {
-                    match matches.value_of("bits") {
-                        Some(bits_str) => match usize::from_str_radix(&bits_str, 10) {
-                            Ok(224) => set_or_crash(
-                                "SHA3-224",
-                                Box::new(Sha3_224::new()) as Box<dyn Digest>,
-                                224,
-                            ),
-                            Ok(256) => set_or_crash(
-                                "SHA3-256",
-                                Box::new(Sha3_256::new()) as Box<dyn Digest>,
-                                256,
-                            ),
-                            Ok(384) => set_or_crash(
-                                "SHA3-384",
-                                Box::new(Sha3_384::new()) as Box<dyn Digest>,
-                                384,
-                            ),
-                            Ok(512) => set_or_crash(
-                                "SHA3-512",
-                                Box::new(Sha3_512::new()) as Box<dyn Digest>,
-                                512,
-                            ),
-                            Ok(_) => crash!(
-                                1,
-                                "Invalid output size for SHA3 (expected 224, 256, 384, or 512)"
-                            ),
-                            Err(err) => crash!(1, "{}", err),
-                        },
-                        None => crash!(1, "--bits required for SHA3"),
-                    }
+                    let (name, digest, bits) = sha3(matches);
+                    set_or_crash(name, digest, bits)
                 }
                 if matches.is_present("sha3-224") {
                     set_or_crash("SHA3-224", Box::new(Sha3_224::new()), 224)
@@ -225,22 +160,12 @@ fn detect_algo<'a>(
                     set_or_crash("SHA3-512", Box::new(Sha3_512::new()), 512)
                 }
                 if matches.is_present("shake128") {
-                    match matches.value_of("bits") {
-                        Some(bits_str) => match usize::from_str_radix(&bits_str, 10) {
-                            Ok(bits) => set_or_crash("SHAKE128", Box::new(Shake128::new()), bits),
-                            Err(err) => crash!(1, "{}", err),
-                        },
-                        None => crash!(1, "--bits required for SHAKE-128"),
-                    }
+                    let bits = shake_bits(matches, "SHAKE-128");
+                    set_or_crash("SHAKE128", Box::new(Shake128::new()), bits)
                 }
                 if matches.is_present("shake256") {
-                    match matches.value_of("bits") {
-                        Some(bits_str) => match usize::from_str_radix(&bits_str, 10) {
-                            Ok(bits) => set_or_crash("SHAKE256", Box::new(Shake256::new()), bits),
-                            Err(err) => crash!(1, "{}", err),
-                        },
-                        None => crash!(1, "--bits required for SHAKE-256"),
-                    }
+                    let bits = shake_bits(matches, "SHAKE-256");
+                    set_or_crash("SHAKE256", Box::new(Shake256::new()), bits)
                 }
             }
             if alg.is_none() {
@@ -251,6 +176,68 @@ fn detect_algo<'a>(
     }
 }
 
+/// BLAKE2b, making digests of the length in bits --length gives, if any.
+fn blake2b(matches: &ArgMatches) -> (&'static str, Box<dyn Digest + 'static>, usize) {
+    let bits = match matches.value_of("length") {
+        Some(length) => match length.parse::<usize>() {
+            // as if it weren't given
+            Ok(0) => 512,
+            Ok(bits) if bits % 8 != 0 => {
+                show_info!("invalid length: '{}'", length);
+                show_info!("length is not a multiple of 8");
+                exit!(1)
+            }
+            Ok(bits) if bits > 512 => {
+                show_info!("invalid length: '{}'", length);
+                show_info!("maximum digest length for 'BLAKE2b' is 512 bits");
+                exit!(1)
+            }
+            Ok(bits) => bits,
+            Err(_) => {
+                show_info!("invalid length: '{}'", length);
+                exit!(1)
+            }
+        },
+        None => 512,
+    };
+    ("BLAKE2b", Box::new(Blake2b::new(bits / 8)), bits)
+}
+
+/// The SHA3 of the size in bits --bits gives.
+fn sha3(matches: &ArgMatches) -> (&'static str, Box<dyn Digest + 'static>, usize) {
+    match matches.value_of("bits") {
+        Some(bits_str) => match parse_bit_num(&bits_str) {
+            Ok(224) => ("SHA3-224", Box::new(Sha3_224::new()), 224),
+            Ok(256) => ("SHA3-256", Box::new(Sha3_256::new()), 256),
+            Ok(384) => ("SHA3-384", Box::new(Sha3_384::new()), 384),
+            Ok(512) => ("SHA3-512", Box::new(Sha3_512::new()), 512),
+            Ok(_) => crash!(
+                1,
+                "Invalid output size for SHA3 (expected 224, 256, 384, or 512)"
+            ),
+            Err(err) => crash!(1, "{}", err),
+        },
+        None => crash!(1, "--bits required for SHA3"),
+    }
+}
+
+/// The size in bits --bits gives the digests of `algorithm`, a SHAKE, which
+/// can be any whole number of bytes.
+fn shake_bits(matches: &ArgMatches, algorithm: &str) -> usize {
+    match matches.value_of("bits") {
+        Some(bits_str) => match parse_bit_num(&bits_str) {
+            Ok(bits) if bits > 0 && bits % 8 == 0 => bits,
+            Ok(_) => crash!(
+                1,
+                "Invalid output size for {} (expected a positive multiple of 8)",
+                algorithm
+            ),
+            Err(err) => crash!(1, "{}", err),
+        },
+        None => crash!(1, "--bits required for {}", algorithm),
+    }
+}
+
 // TODO: return custom error type
 fn parse_bit_num(arg: &str) -> Result<usize, ParseIntError> {
     usize::from_str_radix(arg, 10)
@@ -272,26 +259,117 @@ pub fn uumain(mut args: impl uucore::Args) -> i32 {
 
     let args = iter::once(program.clone()).chain(args);
 
-    // Default binary in Windows, text mode otherwise
-    let binary_flag_default = cfg!(windows);
-
-    let binary_help = format!(
-        "read in binary mode{}",
-        if binary_flag_default {
-            " (default)"
-        } else {
-            ""
-        }
-    );
+    // FIXME: this should use get_matches_from_safe() and crash!(), but at the moment that just
+    //        causes "error: " to be printed twice (once from crash!() and once from clap).  With
+    //        the current setup, the name of the utility is not printed, but I think this is at
+    //        least somewhat better from a user's perspective.
+    let matches = uucore::help::get_matches_from(app_for(&binary_name), args);
+
+    let (name, algo, bits) = detect_algo(&binary_name, &matches);
+    if matches.is_present("length") && name != "BLAKE2b" {
+        crash!(1, "--length is only supported with --b2sum");
+    }
+
+    let binary = if matches.is_present("binary") {
+        true
+    } else if matches.is_present("text") {
+        false
+    } else {
+        // binary by default in Windows, text mode otherwise
+        cfg!(windows)
+    };
+    let check = matches.is_present("check");
+    let tag = matches.is_present("tag");
+    let strict = matches.is_present("strict");
+    let ignore_missing = matches.is_present("ignore-missing");
+    let zero = matches.is_present("zero");
 
-    let text_help = format!(
-        "read in text mode{}",
-        if binary_flag_default {
-            ""
-        } else {
-            " (default)"
+    if tag && check {
+        show_usage_error!("the --tag option is meaningless when verifying checksums");
+        return 1;
+    }
+    if zero && check {
+        show_usage_error!("the --zero option is not supported when verifying checksums");
+        return 1;
+    }
+    if tag && matches.is_present("text") {
+        show_usage_error!("--tag does not support --text mode");
+        return 1;
+    }
+    if !check {
+        for option in &["ignore-missing", "quiet", "status", "strict", "warn"] {
+            if matches.is_present(option) {
+                show_usage_error!(
+                    "the --{} option is meaningful only when verifying checksums",
+                    option
+                );
+                return 1;
+            }
         }
-    );
+    }
+
+    let jobs = match matches.value_of("jobs") {
+        Some(jobs) => match jobs.parse() {
+            Ok(jobs) if jobs > 0 => jobs,
+            _ => {
+                show_usage_error!("invalid number of jobs: '{}'", jobs);
+                return 1;
+            }
+        },
+        // as many as there are CPUs
+        None => 0,
+    };
+
+    // of --quiet, --status and --warn, the last given wins
+    let last = ["quiet", "status", "warn"]
+        .iter()
+        .filter_map(|&option| matches.index_of(option).map(|index| (index, option)))
+        .max()
+        .map(|(_, option)| option);
+    let status = last == Some("status");
+    let quiet = last == Some("quiet") || status;
+    let warn = last == Some("warn");
+
+    let opts = Options {
+        algoname: name,
+        digest: algo,
+        output_bits: bits,
+        binary,
+        check,
+        tag,
+        status,
+        quiet,
+        strict,
+        warn,
+        ignore_missing,
+        zero,
+        jobs,
+    };
+
+    let res = match matches.values_of_os("FILE") {
+        Some(files) => hashsum(opts, files),
+        None => hashsum(opts, iter::once(OsStr::new("-"))),
+    };
+
+    match res {
+        Ok(()) => 0,
+        Err(e) => e,
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    app_for(NAME)
+}
+
+/// The arguments of hashsum, or of the single-algorithm utility `binary_name`
+/// such as md5sum, which has no options to choose the algorithm.
+fn app_for<'a>(binary_name: &str) -> App<'a, 'a> {
+    // Default binary in Windows, text mode otherwise
+    let (binary_help, text_help) = if cfg!(windows) {
+        ("read in binary mode (default)", "read in text mode")
+    } else {
+        ("read in binary mode", "read in text mode (default)")
+    };
 
     let mut app = App::new(executable!())
         .version(crate_version!())
@@ -300,7 +378,7 @@ pub fn uumain(mut args: impl uucore::Args) -> i32 {
             Arg::with_name("binary")
                 .short("b")
                 .long("binary")
-                .help(&binary_help),
+                .help(binary_help),
         )
         .arg(
             Arg::with_name("check")
@@ -317,7 +395,7 @@ pub fn uumain(mut args: impl uucore::Args) -> i32 {
             Arg::with_name("text")
                 .short("t")
                 .long("text")
-                .help(&text_help)
+                .help(text_help)
                 .conflicts_with("binary"),
         )
         .arg(
@@ -343,6 +421,24 @@ pub fn uumain(mut args: impl uucore::Args) -> i32 {
                 .long("warn")
                 .help("warn about improperly formatted checksum lines"),
         )
+        .arg(
+            Arg::with_name("zero")
+                .short("z")
+                .long("zero")
+                .help("end each output line with NUL, not newline, and disable file name escaping"),
+        )
+        .arg(
+            Arg::with_name("jobs")
+                .long("jobs")
+                .help("read up to N files at once (default: as many as there are CPUs)")
+                .takes_value(true)
+                .value_name("N"),
+        )
+        .arg(
+            Arg::with_name("ignore-missing")
+                .long("ignore-missing")
+                .help("don't fail or report status for missing files"),
+        )
         // Needed for variable-length output sums (e.g. SHAKE)
         .arg(
             Arg::with_name("bits")
@@ -360,7 +456,7 @@ pub fn uumain(mut args: impl uucore::Args) -> i32 {
                 .value_name("FILE"),
         );
 
-    if !is_custom_binary(&binary_name) {
+    if !is_custom_binary(binary_name) {
         let algos = &[
             ("md5", "work with MD5"),
             ("sha1", "work with SHA1"),
@@ -382,229 +478,189 @@ pub fn uumain(mut args: impl uucore::Args) -> i32 {
                 "work with SHAKE256 using BITS for the output size",
             ),
             ("b2sum", "work with BLAKE2"),
+            ("b3sum", "work with BLAKE3"),
         ];
 
         for (name, desc) in algos {
             app = app.arg(Arg::with_name(name).long(name).help(desc));
         }
     }
-
-    // FIXME: this should use get_matches_from_safe() and crash!(), but at the moment that just
-    //        causes "error: " to be printed twice (once from crash!() and once from clap).  With
-    //        the current setup, the name of the utility is not printed, but I think this is at
-    //        least somewhat better from a user's perspective.
-    let matches = app.get_matches_from(args);
-
-    let (name, algo, bits) = detect_algo(&binary_name, &matches);
-
-    let binary = if matches.is_present("binary") {
-        true
-    } else if matches.is_present("text") {
-        false
-    } else {
-        binary_flag_default
-    };
-    let check = matches.is_present("check");
-    let tag = matches.is_present("tag");
-    let status = matches.is_present("status");
-    let quiet = matches.is_present("quiet") || status;
-    let strict = matches.is_present("strict");
-    let warn = matches.is_present("warn") && !status;
-
-    let opts = Options {
-        algoname: name,
-        digest: algo,
-        output_bits: bits,
-        binary,
-        check,
-        tag,
-        status,
-        quiet,
-        strict,
-        warn,
-    };
-
-    let res = match matches.values_of_os("FILE") {
-        Some(files) => hashsum(opts, files),
-        None => hashsum(opts, iter::once(OsStr::new("-"))),
-    };
-
-    match res {
-        Ok(()) => 0,
-        Err(e) => e,
+    if binary_name == "b2sum" || !is_custom_binary(binary_name) {
+        app = app.arg(
+            Arg::with_name("length")
+                .short("l")
+                .long("length")
+                .help(
+                    "digest length in bits; must not exceed the maximum for the BLAKE2 \
+                     algorithm and must be a multiple of 8",
+                )
+                .takes_value(true)
+                .value_name("BITS"),
+        );
     }
+
+    app
 }
 
-#[allow(clippy::cognitive_complexity)]
-fn hashsum<'a, I>(mut options: Options, files: I) -> Result<(), i32>
+fn hashsum<'a, I>(options: Options, files: I) -> Result<(), i32>
 where
     I: Iterator<Item = &'a OsStr>,
 {
-    let mut bad_format = 0;
-    let mut failed = 0;
-    let binary_marker = if options.binary { "*" } else { " " };
-    for filename in files {
-        let filename = Path::new(filename);
-
-        let stdin_buf;
-        let file_buf;
-        let mut file = BufReader::new(if filename == OsStr::new("-") {
-            stdin_buf = stdin();
-            Box::new(stdin_buf) as Box<dyn Read>
-        } else {
-            file_buf = safe_unwrap!(File::open(filename));
-            Box::new(file_buf) as Box<dyn Read>
-        });
-        if options.check {
-            // Set up Regexes for line validation and parsing
-            let bytes = options.digest.output_bits() / 4;
-            let gnu_re = safe_unwrap!(Regex::new(&format!(
-                r"^(?P<digest>[a-fA-F0-9]{{{}}}) (?P<binary>[ \*])(?P<fileName>.*)",
-                bytes
-            )));
-            let bsd_re = safe_unwrap!(Regex::new(&format!(
-                r"^{algorithm} \((?P<fileName>.*)\) = (?P<digest>[a-fA-F0-9]{{{digest_size}}})",
-                algorithm = options.algoname,
-                digest_size = bytes
-            )));
-
-            let buffer = file;
-            for (i, line) in buffer.lines().enumerate() {
-                let line = safe_unwrap!(line);
-                let (ck_filename, sum, binary_check) = match gnu_re.captures(&line) {
-                    Some(caps) => (
-                        caps.name("fileName").unwrap().as_str(),
-                        caps.name("digest").unwrap().as_str().to_ascii_lowercase(),
-                        caps.name("binary").unwrap().as_str() == "*",
-                    ),
-                    None => match bsd_re.captures(&line) {
-                        Some(caps) => (
-                            caps.name("fileName").unwrap().as_str(),
-                            caps.name("digest").unwrap().as_str().to_ascii_lowercase(),
-                            true,
-                        ),
-                        None => {
-                            bad_format += 1;
-                            if options.strict {
-                                return Err(1);
-                            }
-                            if options.warn {
-                                show_warning!(
-                                    "{}: {}: improperly formatted {} checksum line",
-                                    filename.display(),
-                                    i + 1,
-                                    options.algoname
-                                );
-                            }
-                            continue;
-                        }
-                    },
-                };
-                let f = safe_unwrap!(File::open(ck_filename));
-                let mut ckf = BufReader::new(Box::new(f) as Box<dyn Read>);
-                let real_sum = safe_unwrap!(digest_reader(
-                    &mut *options.digest,
-                    &mut ckf,
-                    binary_check,
-                    options.output_bits
-                ))
-                .to_ascii_lowercase();
-                if sum == real_sum {
-                    if !options.quiet {
-                        println!("{}: OK", ck_filename);
-                    }
-                } else {
-                    if !options.status {
-                        println!("{}: FAILED", ck_filename);
-                    }
-                    failed += 1;
+    let mut status = Ok(());
+    if options.check {
+        let check_options = CheckOptions {
+            util_name: executable!(),
+            status: options.status,
+            quiet: options.quiet,
+            strict: options.strict,
+            warn: options.warn,
+            ignore_missing: options.ignore_missing,
+        };
+        for filename in files {
+            let filename = Path::new(filename);
+            let checksums = match open(filename) {
+                Ok(checksums) => checksums,
+                Err(e) => {
+                    show_info!("{}: {}", filename.display(), error_message(&e));
+                    status = Err(1);
+                    continue;
                 }
+            };
+            let ok = sum::check(
+                &check_options,
+                &filename.display().to_string(),
+                checksums,
+                |line| listed(&options, line),
+                |lines, report| hash_all(options.jobs, lines, ChecksumLine::verify, report),
+            );
+            if !ok {
+                status = Err(1);
             }
-        } else {
-            let sum = safe_unwrap!(digest_reader(
-                &mut *options.digest,
-                &mut file,
-                options.binary,
-                options.output_bits
-            ));
+        }
+        return status;
+    }
+
+    let binary_marker = if options.binary { "*" } else { " " };
+    // a BLAKE2b digest shorter than the longest is tagged with its length
+    let algoname = if options.algoname == "BLAKE2b" && options.output_bits != 512 {
+        format!("{}-{}", options.algoname, options.output_bits)
+    } else {
+        options.algoname.to_owned()
+    };
+    let files = files
+        .map(|filename| (PathBuf::from(filename), options.digest.new_boxed()))
+        .collect();
+    let (binary, output_bits) = (options.binary, options.output_bits);
+    hash_all(
+        options.jobs,
+        files,
+        move |(filename, mut digest)| {
+            let sum = digest_file(&filename, &mut *digest, binary, (output_bits + 7) / 8);
+            (filename, sum.map(|(sum, _)| sum.to_hex()))
+        },
+        |(filename, sum)| {
+            let sum = match sum {
+                Ok(sum) => sum,
+                Err(e) => {
+                    show_info!("{}: {}", filename.display(), error_message(&e));
+                    status = Err(1);
+                    return;
+                }
+            };
+            // a name that would break the line up is escaped, as a line
+            // starting with a backslash says
+            let name = filename.display().to_string();
+            let (prefix, name) = if !options.zero && needs_escape(&name) {
+                ("\\", escape(&name))
+            } else {
+                ("", name)
+            };
+            let end = if options.zero { '\0' } else { '\n' };
             if options.tag {
-                println!("{} ({}) = {}", options.algoname, filename.display(), sum);
+                print!("{}{} ({}) = {}{}", prefix, algoname, name, sum, end);
             } else {
-                println!("{} {}{}", sum, binary_marker, filename.display());
+                print!("{}{} {}{}{}", prefix, sum, binary_marker, name, end);
             }
+        },
+    );
+    status
+}
+
+/// Call `hash` on each of `items` on a pool of `jobs` threads, or of as many
+/// as there are CPUs for 0, and `report` on the results in the order of
+/// `items`, each as soon as it and those before it are done.
+fn hash_all<T, R, H, F>(jobs: usize, items: Vec<T>, hash: H, mut report: F)
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    H: Fn(T) -> R + Send + Sync + 'static,
+    F: FnMut(R),
+{
+    // not worth starting threads for
+    if jobs == 1 || items.len() < 2 {
+        for item in items {
+            report(hash(item));
         }
+        return;
     }
-    if !options.status {
-        match bad_format.cmp(&1) {
-            Ordering::Equal => show_warning!("{} line is improperly formatted", bad_format),
-            Ordering::Greater => show_warning!("{} lines are improperly formatted", bad_format),
-            _ => {}
-        };
-        if failed > 0 {
-            show_warning!("{} computed checksum did NOT match", failed);
+
+    let pool = match ThreadPoolBuilder::new().num_threads(jobs).build() {
+        Ok(pool) => pool,
+        Err(e) => crash!(1, "{}", e),
+    };
+    let hash = Arc::new(hash);
+    let (sender, receiver) = mpsc::channel();
+    for (i, item) in items.into_iter().enumerate() {
+        let (hash, sender) = (Arc::clone(&hash), sender.clone());
+        pool.spawn(move || {
+            // the receiver outlives every task
+            let _ = sender.send((i, hash(item)));
+        });
+    }
+    drop(sender);
+
+    let mut done = HashMap::new();
+    let mut next = 0;
+    for (i, result) in receiver {
+        done.insert(i, result);
+        while let Some(result) = done.remove(&next) {
+            report(result);
+            next += 1;
         }
     }
-
-    Ok(())
 }
 
-fn digest_reader<'a, T: Read>(
-    digest: &mut (dyn Digest + 'a),
-    reader: &mut BufReader<T>,
-    binary: bool,
-    output_bits: usize,
-) -> io::Result<String> {
-    digest.reset();
-
-    // Digest file, do not hold too much in memory at any given moment
-    let windows = cfg!(windows);
-    let mut buffer = Vec::with_capacity(524_288);
-    let mut vec = Vec::with_capacity(524_288);
-    let mut looking_for_newline = false;
-    loop {
-        match reader.read_to_end(&mut buffer) {
-            Ok(0) => {
-                break;
-            }
-            Ok(nread) => {
-                if windows && !binary {
-                    // Windows text mode returns '\n' when reading '\r\n'
-                    for &b in buffer.iter().take(nread) {
-                        if looking_for_newline {
-                            if b != b'\n' {
-                                vec.push(b'\r');
-                            }
-                            if b != b'\r' {
-                                vec.push(b);
-                                looking_for_newline = false;
-                            }
-                        } else if b != b'\r' {
-                            vec.push(b);
-                        } else {
-                            looking_for_newline = true;
-                        }
-                    }
-                    digest.input(&vec);
-                    vec.clear();
-                } else {
-                    digest.input(&buffer[..nread]);
-                }
-            }
-            Err(e) => return Err(e),
-        }
-    }
-    if windows && looking_for_newline {
-        vec.push(b'\r');
-        digest.input(&vec);
+/// The file the checksum line `line` names, and its checksum, which must be
+/// as long as the ones `options` make, and tagged with their algorithm if
+/// it is tagged. A BLAKE2b checksum can be any whole number of bytes up to
+/// 64, and a tag can give its length.
+fn listed(options: &Options, line: &str) -> Result<Listed, String> {
+    let improper = || options.algoname.to_owned();
+    let parsed = parse_line(line).ok_or_else(improper)?;
+    let sum = from_hex(parsed.sum).ok_or_else(improper)?;
+    let blake2b = options.algoname == "BLAKE2b";
+    let bits = sum.len() * 8;
+    let proper = match parsed.tag {
+        Some((tag, _)) if tag != options.algoname => false,
+        // as it's written, so "BLAKE2b-08" isn't taken for "BLAKE2b-8"
+        Some((_, Some(written))) => written == bits.to_string(),
+        Some((_, None)) if blake2b => bits == 512,
+        None if blake2b => bits <= 512,
+        _ => bits == options.output_bits,
+    };
+    if !proper {
+        return Err(improper());
     }
-
-    if digest.output_bits() > 0 {
-        Ok(digest.result_str())
+    // each BLAKE2b line has its own length, whatever --length says
+    let digest: Box<dyn Digest> = if blake2b {
+        Box::new(Blake2b::new(sum.len()))
     } else {
-        // Assume it's SHAKE.  result_str() doesn't work with shake (as of 8/30/2016)
-        let mut bytes = Vec::new();
-        bytes.resize((output_bits + 7) / 8, 0);
-        digest.result(&mut bytes);
-        Ok(bytes.to_hex())
-    }
+        options.digest.new_boxed()
+    };
+    Ok(Listed {
+        filename: parsed.filename,
+        sum,
+        binary: parsed.binary,
+        digest,
+    })
 }
@@ -30,7 +30,7 @@ mod options {
 mod parse;
 mod split;
 
-fn app<'a>() -> App<'a, 'a> {
+pub fn uu_app<'a>() -> App<'a, 'a> {
     App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
@@ -162,7 +162,7 @@ impl HeadOptions {
 
     ///Construct options from matches
     pub fn get_from(args: impl uucore::Args) -> Result<Self, String> {
-        let matches = app().get_matches_from(arg_iterate(args)?);
+        let matches = uucore::help::get_matches_from(uu_app(), arg_iterate(args)?);
 
         let mut options = HeadOptions::new();
 
@@ -5,43 +5,109 @@
 //  * For the full copyright and license information, please view the LICENSE file
 //  * that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) gethostid
+// spell-checker:ignore (ToDO) gethostid hostid gethostname
 
 #[macro_use]
 extern crate uucore;
 
-use libc::c_long;
+use uucore::coreopts::CoreOptions;
 
 static SYNTAX: &str = "[options]";
-static SUMMARY: &str = "";
+static SUMMARY: &str = "Print the numeric identifier (in hexadecimal) for the current host.";
 static LONG_HELP: &str = "";
 
-// currently rust libc interface doesn't include gethostid
-extern "C" {
-    pub fn gethostid() -> c_long;
-}
-
 pub fn uumain(args: impl uucore::Args) -> i32 {
-    app!(SYNTAX, SUMMARY, LONG_HELP).parse(args.collect_str());
+    let matches = uu_app().parse(args.collect_str());
+    if let Some(operand) = matches.free.first() {
+        show_usage_error!("extra operand '{}'", operand);
+        return 1;
+    }
     hostid();
     0
 }
 
+pub fn uu_app() -> CoreOptions<'static> {
+    app!(SYNTAX, SUMMARY, LONG_HELP)
+}
+
 fn hostid() {
     /*
      * POSIX says gethostid returns a "32-bit identifier" but is silent
-     * whether it's sign-extended.  Turn off any sign-extension.  This
-     * is a no-op unless unsigned int is wider than 32 bits.
+     * whether it's sign-extended.  This is always just the 32 bits.
      */
+    println!("{:0>8x}", host_id());
+}
+
+/// The identifier of this host: the one set in /etc/hostid, or like glibc
+/// gives, its primary IPv4 address with the halves swapped; failing that, a
+/// hash of its machine ID.
+#[cfg(target_os = "linux")]
+fn host_id() -> u32 {
+    linux::from_file()
+        .or_else(linux::from_address)
+        .or_else(linux::from_machine_id)
+        .unwrap_or(0)
+}
 
-    let mut result: c_long;
-    unsafe {
-        result = gethostid();
+// the C library has the identifier the kernel keeps
+#[cfg(not(target_os = "linux"))]
+fn host_id() -> u32 {
+    extern "C" {
+        fn gethostid() -> libc::c_long;
     }
+    unsafe { gethostid() as u32 }
+}
 
-    #[allow(overflowing_literals)]
-    let mask = 0xffff_ffff;
+// glibc has gethostid work this way, but musl's always gives 0
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ffi::CStr;
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::net::{SocketAddr, ToSocketAddrs};
+
+    /// The identifier in /etc/hostid, where `sethostid` puts it as a 32-bit
+    /// integer in the byte order of the host.
+    pub fn from_file() -> Option<u32> {
+        let mut id = [0; 4];
+        File::open("/etc/hostid")
+            .and_then(|mut file| file.read_exact(&mut id))
+            .ok()?;
+        Some(u32::from_ne_bytes(id))
+    }
 
-    result &= mask;
-    println!("{:0>8x}", result);
+    /// The first IPv4 address the host name resolves to, as in memory in
+    /// network byte order, with the halves swapped.
+    pub fn from_address() -> Option<u32> {
+        let mut name = [0u8; 256];
+        if unsafe { libc::gethostname(name.as_mut_ptr() as *mut libc::c_char, name.len()) } != 0 {
+            return None;
+        }
+        let name = CStr::from_bytes_with_nul(&name[..=name.iter().position(|&b| b == 0)?]).ok()?;
+        let address = (name.to_str().ok()?, 0)
+            .to_socket_addrs()
+            .ok()?
+            .filter_map(|address| match address {
+                SocketAddr::V4(address) => Some(*address.ip()),
+                SocketAddr::V6(_) => None,
+            })
+            .next()?;
+        Some(u32::from_ne_bytes(address.octets()).rotate_left(16))
+    }
+
+    /// The 128-bit machine ID systemd and D-Bus keep, folded into 32 bits.
+    pub fn from_machine_id() -> Option<u32> {
+        let id = fs::read_to_string("/etc/machine-id")
+            .or_else(|_| fs::read_to_string("/var/lib/dbus/machine-id"))
+            .ok()?;
+        let id = id.trim();
+        if id.len() != 32 {
+            return None;
+        }
+        (0..32).step_by(8).try_fold(0, |hash, i| {
+            u32::from_str_radix(&id[i..i + 8], 16)
+                .ok()
+                .map(|word| hash ^ word)
+        })
+    }
 }
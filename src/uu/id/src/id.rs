@@ -20,9 +20,11 @@ extern crate uucore;
 
 use clap::{App, Arg};
 use std::ffi::CStr;
+use std::fs;
+use std::path::Path;
 use uucore::entries::{self, Group, Locate, Passwd};
 pub use uucore::libc;
-use uucore::libc::{getlogin, uid_t};
+use uucore::libc::{getlogin, gid_t, uid_t};
 use uucore::process::{getegid, geteuid, getgid, getuid};
 
 macro_rules! cstr2cow {
@@ -70,36 +72,176 @@ mod audit {
     }
 }
 
-static ABOUT: &str = "Display user and group information for the specified USER,\n or (when USER omitted) for the current user.";
+static ABOUT: &str = "Display user and group information for each specified USER,\n or (when USER omitted) for the current user.";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 
 static OPT_AUDIT: &str = "audit";
+static OPT_CONTEXT: &str = "context";
 static OPT_EFFECTIVE_USER: &str = "effective-user";
 static OPT_GROUP: &str = "group";
 static OPT_GROUPS: &str = "groups";
 static OPT_HUMAN_READABLE: &str = "human-readable";
+static OPT_IGNORE: &str = "ignore";
 static OPT_NAME: &str = "name";
 static OPT_PASSWORD: &str = "password";
-static OPT_REAL_ID: &str = "real-id";
+static OPT_REAL_ID: &str = "real";
+static OPT_ZERO: &str = "zero";
 
 static ARG_USERS: &str = "users";
 
 fn get_usage() -> String {
-    format!("{0} [OPTION]... [USER]", executable!())
+    format!("{0} [OPTION]... [USER]...", executable!())
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let users: Vec<String> = matches
+        .values_of(ARG_USERS)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    if matches.is_present(OPT_AUDIT) {
+        auditid();
+        return 0;
+    }
+
+    let nflag = matches.is_present(OPT_NAME);
+    let uflag = matches.is_present(OPT_EFFECTIVE_USER);
+    let gflag = matches.is_present(OPT_GROUP);
+    let groups_flag = matches.is_present(OPT_GROUPS);
+    let rflag = matches.is_present(OPT_REAL_ID);
+    let zflag = matches.is_present(OPT_ZERO);
+    let context_flag = matches.is_present(OPT_CONTEXT);
+
+    if context_flag && !selinux_enabled() {
+        show_info!("--context (-Z) works only on an SELinux-enabled kernel");
+        return 1;
+    }
+    if context_flag && !users.is_empty() {
+        show_info!("cannot print security context when user specified");
+        return 1;
+    }
+    if [uflag, gflag, groups_flag, context_flag]
+        .iter()
+        .filter(|&&flag| flag)
+        .count()
+        > 1
+    {
+        show_info!("cannot print \"only\" of more than one choice");
+        return 1;
+    }
+    let default_format = !(uflag || gflag || groups_flag || context_flag);
+    if default_format && (nflag || rflag) {
+        show_info!("cannot print only names or real IDs in default format");
+        return 1;
+    }
+    if default_format && zflag {
+        show_info!("option --zero not permitted in default format");
+        return 1;
+    }
+
+    let line_end = if zflag { '\0' } else { '\n' };
+
+    if context_flag {
+        match selinux_context() {
+            Some(context) => print!("{}{}", context, line_end),
+            None => {
+                show_info!("can't get process context");
+                return 1;
+            }
+        }
+        return 0;
+    }
+
+    // the process itself, or each of the users given
+    let targets: Vec<Option<&str>> = if users.is_empty() {
+        vec![None]
+    } else {
+        users.iter().map(|user| Some(user.as_str())).collect()
+    };
+
+    let mut status = 0;
+    for target in targets {
+        let possible_pw = match target {
+            Some(user) => match Passwd::locate(user) {
+                Ok(p) => Some(p),
+                Err(_) => {
+                    show_info!("'{}': no such user", user);
+                    status = 1;
+                    continue;
+                }
+            },
+            None => None,
+        };
+        let ids = Ids::of(possible_pw.as_ref());
+
+        if uflag || gflag {
+            let name = if uflag {
+                let id = if rflag { ids.ruid } else { ids.euid };
+                user_name(id, nflag)
+            } else {
+                let id = if rflag { ids.rgid } else { ids.egid };
+                group_name(id, nflag)
+            };
+            if name.is_err() {
+                status = 1;
+            }
+            print!("{}{}", name.unwrap_or_else(|id| id), line_end);
+            continue;
+        }
+
+        if groups_flag {
+            let delimiter = if zflag { "\0" } else { " " };
+            let mut names = Vec::new();
+            for &id in &ids.group_list() {
+                names.push(group_name(id, nflag).unwrap_or_else(|id| {
+                    status = 1;
+                    id
+                }));
+            }
+            print!("{}{}", names.join(delimiter), line_end);
+            continue;
+        }
+
+        if matches.is_present(OPT_PASSWORD) {
+            pline(possible_pw.map(|v| v.uid()));
+            continue;
+        };
+
+        if matches.is_present(OPT_HUMAN_READABLE) {
+            pretty(possible_pw);
+            continue;
+        }
+
+        id_print(&ids, possible_pw.is_none());
+    }
+
+    status
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .arg(
             Arg::with_name(OPT_AUDIT)
                 .short("A")
                 .help("Display the process audit (not available on Linux)"),
         )
+        .arg(
+            Arg::with_name(OPT_IGNORE)
+                .short("a")
+                .help("ignore, for compatibility with other versions"),
+        )
+        .arg(
+            Arg::with_name(OPT_CONTEXT)
+                .short("Z")
+                .long(OPT_CONTEXT)
+                .help("print only the security context of the process"),
+        )
         .arg(
             Arg::with_name(OPT_EFFECTIVE_USER)
                 .short("u")
@@ -126,6 +268,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         .arg(
             Arg::with_name(OPT_NAME)
                 .short("n")
+                .long(OPT_NAME)
                 .help("Display the name of the user or group ID for the -G, -g and -u options"),
         )
         .arg(
@@ -136,106 +279,97 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         .arg(
             Arg::with_name(OPT_REAL_ID)
                 .short("r")
+                .long(OPT_REAL_ID)
                 .help("Display the real ID for the -g and -u options"),
         )
+        .arg(Arg::with_name(OPT_ZERO).short("z").long(OPT_ZERO).help(
+            "delimit entries with NUL characters, not whitespace; not permitted in default format",
+        ))
         .arg(Arg::with_name(ARG_USERS).multiple(true).takes_value(true))
-        .get_matches_from(args);
+}
 
-    let users: Vec<String> = matches
-        .values_of(ARG_USERS)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
+/// The IDs `id` reports on, those of a user or of the process.
+struct Ids {
+    ruid: uid_t,
+    euid: uid_t,
+    rgid: gid_t,
+    egid: gid_t,
+    groups: Vec<gid_t>,
+}
 
-    if matches.is_present(OPT_AUDIT) {
-        auditid();
-        return 0;
+impl Ids {
+    fn of(possible_pw: Option<&Passwd>) -> Ids {
+        match possible_pw {
+            Some(p) => Ids {
+                ruid: p.uid(),
+                euid: p.uid(),
+                rgid: p.gid(),
+                egid: p.gid(),
+                groups: p.belongs_to(),
+            },
+            None => Ids {
+                ruid: getuid(),
+                euid: geteuid(),
+                rgid: getgid(),
+                egid: getegid(),
+                groups: entries::get_groups().unwrap_or_default(),
+            },
+        }
     }
 
-    let possible_pw = if users.is_empty() {
-        None
-    } else {
-        match Passwd::locate(users[0].as_str()) {
-            Ok(p) => Some(p),
-            Err(_) => crash!(1, "No such user/group: {}", users[0]),
+    /// The real group, the effective one if it differs, then the rest.
+    fn group_list(&self) -> Vec<gid_t> {
+        let mut list = vec![self.rgid];
+        if self.egid != self.rgid {
+            list.push(self.egid);
         }
-    };
-
-    let nflag = matches.is_present(OPT_NAME);
-    let uflag = matches.is_present(OPT_EFFECTIVE_USER);
-    let gflag = matches.is_present(OPT_GROUP);
-    let rflag = matches.is_present(OPT_REAL_ID);
-
-    if gflag {
-        let id = possible_pw
-            .map(|p| p.gid())
-            .unwrap_or(if rflag { getgid() } else { getegid() });
-        println!(
-            "{}",
-            if nflag {
-                entries::gid2grp(id).unwrap_or_else(|_| id.to_string())
-            } else {
-                id.to_string()
+        for &gid in &self.groups {
+            if !list.contains(&gid) {
+                list.push(gid);
             }
-        );
-        return 0;
+        }
+        list
     }
+}
 
-    if uflag {
-        let id = possible_pw
-            .map(|p| p.uid())
-            .unwrap_or(if rflag { getuid() } else { geteuid() });
-        println!(
-            "{}",
-            if nflag {
-                entries::uid2usr(id).unwrap_or_else(|_| id.to_string())
-            } else {
-                id.to_string()
-            }
-        );
-        return 0;
+/// The name of user `id` if `names` is set, its number otherwise; an
+/// unknown name is reported, and the number is given instead.
+fn user_name(id: uid_t, names: bool) -> Result<String, String> {
+    if !names {
+        return Ok(id.to_string());
     }
+    entries::uid2usr(id).map_err(|_| {
+        show_info!("cannot find name for user ID {}", id);
+        id.to_string()
+    })
+}
 
-    if matches.is_present(OPT_GROUPS) {
-        println!(
-            "{}",
-            if nflag {
-                possible_pw
-                    .map(|p| p.belongs_to())
-                    .unwrap_or_else(|| entries::get_groups().unwrap())
-                    .iter()
-                    .map(|&id| entries::gid2grp(id).unwrap())
-                    .collect::<Vec<_>>()
-                    .join(" ")
-            } else {
-                possible_pw
-                    .map(|p| p.belongs_to())
-                    .unwrap_or_else(|| entries::get_groups().unwrap())
-                    .iter()
-                    .map(|&id| id.to_string())
-                    .collect::<Vec<_>>()
-                    .join(" ")
-            }
-        );
-        return 0;
+/// Like `user_name`, for group `id`.
+fn group_name(id: gid_t, names: bool) -> Result<String, String> {
+    if !names {
+        return Ok(id.to_string());
     }
+    entries::gid2grp(id).map_err(|_| {
+        show_info!("cannot find name for group ID {}", id);
+        id.to_string()
+    })
+}
 
-    if matches.is_present(OPT_PASSWORD) {
-        pline(possible_pw.map(|v| v.uid()));
-        return 0;
-    };
-
-    if matches.is_present(OPT_HUMAN_READABLE) {
-        pretty(possible_pw);
-        return 0;
-    }
+/// Whether the kernel runs SELinux.
+fn selinux_enabled() -> bool {
+    cfg!(target_os = "linux") && Path::new("/sys/fs/selinux/enforce").exists()
+}
 
-    if possible_pw.is_some() {
-        id_print(possible_pw, false, false)
+/// The security context of the process.
+fn selinux_context() -> Option<String> {
+    let context = fs::read("/proc/self/attr/current").ok()?;
+    let context = String::from_utf8_lossy(&context);
+    let context = context.trim_end_matches(&['\0', '\n'][..]);
+    if context.is_empty() {
+        None
     } else {
-        id_print(possible_pw, true, true)
+        Some(context.to_owned())
     }
-
-    0
 }
 
 fn pretty(possible_pw: Option<Passwd>) {
@@ -348,35 +482,42 @@ fn auditid() {
     println!("asid={}", auditinfo.ai_asid);
 }
 
-fn id_print(possible_pw: Option<Passwd>, p_euid: bool, p_egid: bool) {
-    let (uid, gid) = possible_pw
-        .map(|p| (p.uid(), p.gid()))
-        .unwrap_or((getuid(), getgid()));
-
-    let groups = match Passwd::locate(uid) {
-        Ok(p) => p.belongs_to(),
-        Err(e) => crash!(1, "Could not find uid {}: {}", uid, e),
+fn id_print(ids: &Ids, process: bool) {
+    let user = |id: uid_t| match entries::uid2usr(id) {
+        Ok(name) => format!("{}({})", id, name),
+        Err(_) => id.to_string(),
+    };
+    let group = |id: gid_t| match entries::gid2grp(id) {
+        Ok(name) => format!("{}({})", id, name),
+        Err(_) => id.to_string(),
     };
 
-    print!("uid={}({})", uid, entries::uid2usr(uid).unwrap());
-    print!(" gid={}({})", gid, entries::gid2grp(gid).unwrap());
-
-    let euid = geteuid();
-    if p_euid && (euid != uid) {
-        print!(" euid={}({})", euid, entries::uid2usr(euid).unwrap());
+    print!("uid={} gid={}", user(ids.ruid), group(ids.rgid));
+    if ids.euid != ids.ruid {
+        print!(" euid={}", user(ids.euid));
     }
-
-    let egid = getegid();
-    if p_egid && (egid != gid) {
-        print!(" egid={}({})", euid, entries::gid2grp(egid).unwrap());
+    if ids.egid != ids.rgid {
+        print!(" egid={}", group(ids.egid));
     }
 
-    println!(
+    // a user's groups start with their login group, those of the process
+    // with its effective group
+    let first = if process { ids.egid } else { ids.rgid };
+    let mut groups = vec![first];
+    groups.extend(ids.groups.iter().filter(|&&gid| gid != first));
+    print!(
         " groups={}",
         groups
             .iter()
-            .map(|&gr| format!("{}({})", gr, entries::gid2grp(gr).unwrap()))
+            .map(|&gr| group(gr))
             .collect::<Vec<_>>()
             .join(",")
     );
+
+    if process && selinux_enabled() {
+        if let Some(context) = selinux_context() {
+            print!(" context={}", context);
+        }
+    }
+    println!();
 }
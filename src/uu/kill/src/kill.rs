@@ -5,18 +5,25 @@
 //  * For the full copyright and license information, please view the LICENSE file
 //  * that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) signalname pids
+// spell-checker:ignore (ToDO) signalname pids strsignal
 
 #[macro_use]
 extern crate uucore;
 
 use libc::{c_int, pid_t};
+use std::ffi::CStr;
 use std::io::Error;
-use uucore::signals::ALL_SIGNALS;
+use uucore::coreopts::CoreOptions;
+use uucore::error_message;
+use uucore::signals::{signal_name_by_value, ALL_SIGNALS, DEFAULT_SIGNAL};
 
-static SYNTAX: &str = "[options] <pid> [...]";
-static SUMMARY: &str = "";
-static LONG_HELP: &str = "";
+static SYNTAX: &str = "[-s SIGNAL | -SIGNAL] PID...
+ or:  kill -l [SIGNAL]...
+ or:  kill -t [SIGNAL]...";
+static SUMMARY: &str = "Send signals to processes, or list signals.";
+static LONG_HELP: &str = "SIGNAL may be a signal name like 'HUP', or a signal number like '1',
+or the exit status of a process terminated by a signal.
+PID is an integer; if negative it identifies a process group.";
 
 static EXIT_OK: i32 = 0;
 static EXIT_ERR: i32 = 1;
@@ -31,19 +38,15 @@ pub enum Mode {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let (args, obs_signal) = handle_obsolete(args);
-    let matches = app!(SYNTAX, SUMMARY, LONG_HELP)
-        .optopt("s", "signal", "specify the <signal> to be sent", "SIGNAL")
-        .optflagopt(
-            "l",
-            "list",
-            "list all signal names, or convert one to a name",
-            "LIST",
-        )
-        .optflag("L", "table", "list all signal names in a nice table")
-        .parse(args);
+    let (args, obs_signals) = handle_obsolete(args);
+    let matches = uu_app().parse(args);
 
-    let mode = if matches.opt_present("table") {
+    let lists = matches.opt_count("list") + matches.opt_count("table") + matches.opt_count("L");
+    if lists > 1 {
+        show_usage_error!("multiple -l or -t options specified");
+        return EXIT_ERR;
+    }
+    let mode = if matches.opt_present("table") || matches.opt_present("L") {
         Mode::Table
     } else if matches.opt_present("list") {
         Mode::List
@@ -51,116 +54,210 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         Mode::Kill
     };
 
+    let mut signals = obs_signals;
+    signals.extend(matches.opt_strs("signal"));
+    signals.extend(matches.opt_strs("n"));
+    if signals.len() > 1 {
+        show_usage_error!("'{}': multiple signals specified", signals[1]);
+        return EXIT_ERR;
+    }
+    let signal = match signals.pop() {
+        Some(signal) => match parse_signal(&signal) {
+            Some(value) => Some(value),
+            None => {
+                show_usage_error!("'{}': invalid signal", signal);
+                return EXIT_ERR;
+            }
+        },
+        None => None,
+    };
+
     match mode {
         Mode::Kill => {
-            return kill(
-                &matches
-                    .opt_str("signal")
-                    .unwrap_or_else(|| obs_signal.unwrap_or_else(|| "9".to_owned())),
-                matches.free,
-            )
+            if matches.free.is_empty() {
+                show_usage_error!("no process ID specified");
+                return EXIT_ERR;
+            }
+            kill(signal.unwrap_or(DEFAULT_SIGNAL), &matches.free)
+        }
+        Mode::Table | Mode::List if signal.is_some() => {
+            show_usage_error!("cannot combine signal with -l or -t");
+            EXIT_ERR
         }
-        Mode::Table => table(),
-        Mode::List => list(matches.opt_str("list")),
+        Mode::Table => table(&matches.free),
+        Mode::List => list(&matches.free),
     }
+}
 
-    0
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    app.optmulti("s", "signal", "specify the <signal> to be sent", "SIGNAL")
+        .optmulti("n", "", "same as -s", "SIGNAL")
+        .optflagmulti(
+            "l",
+            "list",
+            "list signal names, or convert signals to names",
+        )
+        .optflagmulti("t", "table", "list signal names, numbers and descriptions")
+        .optflagmulti("L", "", "same as -t");
+    app
 }
 
-fn handle_obsolete(mut args: Vec<String>) -> (Vec<String>, Option<String>) {
-    let mut i = 0;
+/// Take out the signals given as `-SIGNAL`, which only a number in the first
+/// argument can be: after that, a negative number is a process group.
+fn handle_obsolete(mut args: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let mut signals = Vec::new();
+    let mut i = 1;
     while i < args.len() {
-        // this is safe because slice is valid when it is referenced
-        let slice = &args[i].clone();
-        if slice.starts_with('-') && slice.len() > 1 && slice.chars().nth(1).unwrap().is_digit(10) {
-            let val = &slice[1..];
-            match val.parse() {
-                Ok(num) => {
-                    if uucore::signals::is_signal(num) {
-                        args.remove(i);
-                        return (args, Some(val.to_owned()));
-                    }
+        match args[i].as_str() {
+            "--" => break,
+            // their value could look like one of these
+            "-s" | "-n" | "--signal" => {
+                i += 2;
+                continue;
+            }
+            _ => {}
+        }
+        let mut chars = args[i].chars();
+        if chars.next() == Some('-') {
+            match chars.next() {
+                Some(c) if c.is_ascii_digit() && i == 1 => {
+                    signals.push(args.remove(i)[1..].to_owned());
+                    continue;
+                }
+                Some(c) if c.is_ascii_digit() => {
+                    // getopts is to leave it and the rest alone
+                    args.insert(i, "--".to_owned());
+                    break;
+                }
+                // -L is the table
+                Some(c) if c.is_ascii_uppercase() && c != 'L' => {
+                    signals.push(args.remove(i)[1..].to_owned());
+                    continue;
                 }
-                Err(_) => break, /* getopts will error out for us */
+                _ => {}
             }
         }
         i += 1;
     }
-    (args, None)
+    (args, signals)
 }
 
-fn table() {
-    let mut name_width = 0;
-    /* Compute the maximum width of a signal name. */
-    for s in &ALL_SIGNALS {
-        if s.name.len() > name_width {
-            name_width = s.name.len()
-        }
+/// The number of the signal `signal` names, without "SIG" or in any case,
+/// or has as its number; or as the exit status of a process the signal
+/// terminated, as shells give it.
+fn parse_signal(signal: &str) -> Option<usize> {
+    if signal.starts_with(|c: char| c.is_ascii_digit()) {
+        let value: usize = signal.parse().ok()?;
+        // ksh has the exit status be 256 more than the signal, not 128
+        let value = if value >= 0xFF {
+            value & 0xFF
+        } else {
+            value & 0x7F
+        };
+        return signal_name(value).map(|_| value);
+    }
+    let name = signal.to_uppercase();
+    let name = name.trim_start_matches("SIG");
+    if name == "EXIT" {
+        return Some(0);
     }
+    ALL_SIGNALS.iter().find(|s| s.name == name).map(|s| s.value)
+}
 
-    for (idx, signal) in ALL_SIGNALS.iter().enumerate() {
-        print!("{0: >#2} {1: <#8}", idx + 1, signal.name);
-        //TODO: obtain max signal width here
+/// The name of signal `value`; 0, which only checks a process can be sent
+/// signals, is "EXIT".
+fn signal_name(value: usize) -> Option<&'static str> {
+    if value == 0 {
+        Some("EXIT")
+    } else {
+        signal_name_by_value(value)
+    }
+}
 
-        if (idx + 1) % 7 == 0 {
-            println!();
-        }
+/// The values of `signals`, with whether each was given as a number, or of
+/// every signal if there are none; an invalid one is reported and left out.
+fn operands(signals: &[String]) -> (Vec<(usize, bool)>, i32) {
+    if signals.is_empty() {
+        return (
+            ALL_SIGNALS.iter().map(|s| (s.value, true)).collect(),
+            EXIT_OK,
+        );
     }
+    let mut status = EXIT_OK;
+    let values = signals
+        .iter()
+        .filter_map(|signal| match parse_signal(signal) {
+            Some(value) => Some((value, signal.starts_with(|c: char| c.is_ascii_digit()))),
+            None => {
+                show_info!("'{}': invalid signal", signal);
+                status = EXIT_ERR;
+                None
+            }
+        })
+        .collect();
+    (values, status)
 }
 
-fn print_signal(signal_name_or_value: &str) {
-    for signal in &ALL_SIGNALS {
-        if signal.name == signal_name_or_value
-            || (format!("SIG{}", signal.name)) == signal_name_or_value
-        {
-            println!("{}", signal.value);
-            exit!(EXIT_OK as i32)
-        } else if signal_name_or_value == signal.value.to_string() {
-            println!("{}", signal.name);
-            exit!(EXIT_OK as i32)
-        }
+fn table(signals: &[String]) -> i32 {
+    let num_width = ALL_SIGNALS
+        .iter()
+        .map(|s| s.value.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let name_width = ALL_SIGNALS.iter().map(|s| s.name.len()).max().unwrap_or(0);
+
+    let (values, status) = operands(signals);
+    for (value, _) in values {
+        println!(
+            "{0:>1$} {2:<3$} {4}",
+            value,
+            num_width,
+            signal_name(value).unwrap(),
+            name_width,
+            description(value)
+        );
     }
-    crash!(EXIT_ERR, "unknown signal name {}", signal_name_or_value)
+    status
 }
 
-fn print_signals() {
-    let mut pos = 0;
-    for (idx, signal) in ALL_SIGNALS.iter().enumerate() {
-        pos += signal.name.len();
-        print!("{}", signal.name);
-        if idx > 0 && pos > 73 {
-            println!();
-            pos = 0;
-        } else {
-            pos += 1;
-            print!(" ");
-        }
+fn description(value: usize) -> String {
+    let description = unsafe { libc::strsignal(value as c_int) };
+    if description.is_null() {
+        return String::new();
     }
+    unsafe { CStr::from_ptr(description) }
+        .to_string_lossy()
+        .into_owned()
 }
 
-fn list(arg: Option<String>) {
-    match arg {
-        Some(ref x) => print_signal(x),
-        None => print_signals(),
-    };
+fn list(signals: &[String]) -> i32 {
+    let (values, status) = operands(signals);
+    for (value, number) in values {
+        // a number is converted to its name, and a name to its number
+        if number {
+            println!("{}", signal_name(value).unwrap());
+        } else {
+            println!("{}", value);
+        }
+    }
+    status
 }
 
-fn kill(signalname: &str, pids: std::vec::Vec<String>) -> i32 {
-    let mut status = 0;
-    let optional_signal_value = uucore::signals::signal_by_name_or_value(signalname);
-    let signal_value = match optional_signal_value {
-        Some(x) => x,
-        None => crash!(EXIT_ERR, "unknown signal name {}", signalname),
-    };
-    for pid in &pids {
-        match pid.parse::<usize>() {
+fn kill(signal_value: usize, pids: &[String]) -> i32 {
+    let mut status = EXIT_OK;
+    for pid in pids {
+        match pid.parse::<pid_t>() {
             Ok(x) => {
-                if unsafe { libc::kill(x as pid_t, signal_value as c_int) } != 0 {
-                    show_error!("{}", Error::last_os_error());
-                    status = 1;
+                if unsafe { libc::kill(x, signal_value as c_int) } != 0 {
+                    show_info!("'{}': {}", pid, error_message(&Error::last_os_error()));
+                    status = EXIT_ERR;
                 }
             }
-            Err(e) => crash!(EXIT_ERR, "failed to parse argument {}: {}", pid, e),
+            Err(_) => {
+                show_info!("'{}': invalid process id", pid);
+                status = EXIT_ERR;
+            }
         };
     }
     status
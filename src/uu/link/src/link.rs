@@ -11,6 +11,7 @@ extern crate uucore;
 use std::fs::hard_link;
 use std::io::Error;
 use std::path::Path;
+use uucore::coreopts::CoreOptions;
 
 static SYNTAX: &str = "[OPTIONS] FILE1 FILE2";
 static SUMMARY: &str = "Create a link named FILE2 to FILE1";
@@ -24,7 +25,7 @@ pub fn normalize_error_message(e: Error) -> String {
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
-    let matches = app!(SYNTAX, SUMMARY, LONG_HELP).parse(args.collect_str());
+    let matches = uu_app().parse(args.collect_str());
     if matches.free.len() != 2 {
         crash!(1, "{}", msg_wrong_number_of_arguments!(2));
     }
@@ -40,3 +41,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         }
     }
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    app!(SYNTAX, SUMMARY, LONG_HELP)
+}
@@ -19,10 +19,10 @@ use std::fs;
 use std::io::{stdin, Result};
 #[cfg(any(unix, target_os = "redox"))]
 use std::os::unix::fs::symlink;
-#[cfg(windows)]
-use std::os::windows::fs::{symlink_dir, symlink_file};
 use std::path::{Path, PathBuf};
 use uucore::fs::{canonicalize, CanonicalizeMode};
+#[cfg(windows)]
+use uucore::winfs::symlink;
 
 pub struct Settings {
     overwrite: OverwriteMode,
@@ -97,11 +97,68 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
     let long_usage = get_long_usage();
 
-    let matches = App::new(executable!())
+    let app = uu_app().usage(&usage[..]).after_help(&long_usage[..]);
+    let matches = uucore::help::get_matches_from(app, args);
+
+    /* the list of files */
+
+    let paths: Vec<PathBuf> = matches
+        .values_of(ARG_FILES)
+        .unwrap()
+        .map(PathBuf::from)
+        .collect();
+
+    let overwrite_mode = if matches.is_present(OPT_FORCE) {
+        OverwriteMode::Force
+    } else if matches.is_present(OPT_INTERACTIVE) {
+        OverwriteMode::Interactive
+    } else {
+        OverwriteMode::NoClobber
+    };
+
+    let backup_mode = if matches.is_present(OPT_B) {
+        BackupMode::ExistingBackup
+    } else if matches.is_present(OPT_BACKUP) {
+        match matches.value_of(OPT_BACKUP) {
+            None => BackupMode::ExistingBackup,
+            Some(mode) => match &mode[..] {
+                "simple" | "never" => BackupMode::SimpleBackup,
+                "numbered" | "t" => BackupMode::NumberedBackup,
+                "existing" | "nil" => BackupMode::ExistingBackup,
+                "none" | "off" => BackupMode::NoBackup,
+                _ => panic!(), // cannot happen as it is managed by clap
+            },
+        }
+    } else {
+        BackupMode::NoBackup
+    };
+
+    let backup_suffix = if matches.is_present(OPT_SUFFIX) {
+        matches.value_of(OPT_SUFFIX).unwrap()
+    } else {
+        "~"
+    };
+
+    let settings = Settings {
+        overwrite: overwrite_mode,
+        backup: backup_mode,
+        force: matches.is_present(OPT_FORCE),
+        suffix: backup_suffix.to_string(),
+        symbolic: matches.is_present(OPT_SYMBOLIC),
+        relative: matches.is_present(OPT_RELATIVE),
+        target_dir: matches.value_of(OPT_TARGET_DIRECTORY).map(String::from),
+        no_target_dir: matches.is_present(OPT_NO_TARGET_DIRECTORY),
+        no_dereference: matches.is_present(OPT_NO_DEREFERENCE),
+        verbose: matches.is_present(OPT_VERBOSE),
+    };
+
+    exec(&paths[..], &settings)
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
-        .after_help(&long_usage[..])
         .arg(Arg::with_name(OPT_B).short(OPT_B).help(
             "make a backup of each file that would otherwise be overwritten or \
              removed",
@@ -200,61 +257,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .required(true)
                 .min_values(1),
         )
-        .get_matches_from(args);
-
-    /* the list of files */
-
-    let paths: Vec<PathBuf> = matches
-        .values_of(ARG_FILES)
-        .unwrap()
-        .map(PathBuf::from)
-        .collect();
-
-    let overwrite_mode = if matches.is_present(OPT_FORCE) {
-        OverwriteMode::Force
-    } else if matches.is_present(OPT_INTERACTIVE) {
-        OverwriteMode::Interactive
-    } else {
-        OverwriteMode::NoClobber
-    };
-
-    let backup_mode = if matches.is_present(OPT_B) {
-        BackupMode::ExistingBackup
-    } else if matches.is_present(OPT_BACKUP) {
-        match matches.value_of(OPT_BACKUP) {
-            None => BackupMode::ExistingBackup,
-            Some(mode) => match &mode[..] {
-                "simple" | "never" => BackupMode::SimpleBackup,
-                "numbered" | "t" => BackupMode::NumberedBackup,
-                "existing" | "nil" => BackupMode::ExistingBackup,
-                "none" | "off" => BackupMode::NoBackup,
-                _ => panic!(), // cannot happen as it is managed by clap
-            },
-        }
-    } else {
-        BackupMode::NoBackup
-    };
-
-    let backup_suffix = if matches.is_present(OPT_SUFFIX) {
-        matches.value_of(OPT_SUFFIX).unwrap()
-    } else {
-        "~"
-    };
-
-    let settings = Settings {
-        overwrite: overwrite_mode,
-        backup: backup_mode,
-        force: matches.is_present(OPT_FORCE),
-        suffix: backup_suffix.to_string(),
-        symbolic: matches.is_present(OPT_SYMBOLIC),
-        relative: matches.is_present(OPT_RELATIVE),
-        target_dir: matches.value_of(OPT_TARGET_DIRECTORY).map(String::from),
-        no_target_dir: matches.is_present(OPT_NO_TARGET_DIRECTORY),
-        no_dereference: matches.is_present(OPT_NO_DEREFERENCE),
-        verbose: matches.is_present(OPT_VERBOSE),
-    };
-
-    exec(&paths[..], &settings)
 }
 
 fn exec(files: &[PathBuf], settings: &Settings) -> i32 {
@@ -478,15 +480,6 @@ fn existing_backup_path(path: &PathBuf, suffix: &str) -> PathBuf {
     simple_backup_path(path, suffix)
 }
 
-#[cfg(windows)]
-pub fn symlink<P1: AsRef<Path>, P2: AsRef<Path>>(src: P1, dst: P2) -> Result<()> {
-    if src.as_ref().is_dir() {
-        symlink_dir(src, dst)
-    } else {
-        symlink_file(src, dst)
-    }
-}
-
 pub fn is_symlink<P: AsRef<Path>>(path: P) -> bool {
     match fs::symlink_metadata(path) {
         Ok(m) => m.file_type().is_symlink(),
@@ -12,35 +12,30 @@
 #[macro_use]
 extern crate uucore;
 
-use std::ffi::CStr;
+mod platform;
 
-extern "C" {
-    // POSIX requires using getlogin (or equivalent code)
-    pub fn getlogin() -> *const libc::c_char;
-}
-
-fn get_userlogin() -> Option<String> {
-    unsafe {
-        let login: *const libc::c_char = getlogin();
-        if login.is_null() {
-            None
-        } else {
-            Some(String::from_utf8_lossy(CStr::from_ptr(login).to_bytes()).to_string())
-        }
-    }
-}
+use platform::get_userlogin;
+use uucore::coreopts::CoreOptions;
 
 static SYNTAX: &str = "";
 static SUMMARY: &str = "Print user's login name";
 static LONG_HELP: &str = "";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
-    app!(SYNTAX, SUMMARY, LONG_HELP).parse(args.collect_str());
+    uu_app().parse(args.collect_str());
 
     match get_userlogin() {
-        Some(userlogin) => println!("{}", userlogin),
-        None => show_error!("no login name"),
+        Some(userlogin) => {
+            println!("{}", userlogin);
+            0
+        }
+        None => {
+            show_error!("no login name");
+            1
+        }
     }
+}
 
-    0
+pub fn uu_app() -> CoreOptions<'static> {
+    app!(SYNTAX, SUMMARY, LONG_HELP)
 }
@@ -0,0 +1,27 @@
+// This file is part of the uutils coreutils package.
+//
+// (c) Benoit Benedetti <benoit.benedetti@gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (ToDO) getlogin userlogin
+
+use std::ffi::CStr;
+
+extern "C" {
+    // POSIX requires using getlogin (or equivalent code)
+    pub fn getlogin() -> *const libc::c_char;
+}
+
+/// The name the login record of the controlling terminal has.
+pub fn get_userlogin() -> Option<String> {
+    unsafe {
+        let login: *const libc::c_char = getlogin();
+        if login.is_null() {
+            None
+        } else {
+            Some(String::from_utf8_lossy(CStr::from_ptr(login).to_bytes()).to_string())
+        }
+    }
+}
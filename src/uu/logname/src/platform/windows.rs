@@ -0,0 +1,61 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (ToDO) getlogin userlogin wtsapi winbase LPWSTR UNLEN lmcons
+
+extern crate winapi;
+
+use self::winapi::shared::lmcons::UNLEN;
+use self::winapi::shared::minwindef::DWORD;
+use self::winapi::um::winbase::GetUserNameW;
+use self::winapi::um::winnt::LPWSTR;
+use self::winapi::um::wtsapi32::{
+    WTSFreeMemory, WTSQuerySessionInformationW, WTSUserName, WTS_CURRENT_SERVER_HANDLE,
+    WTS_CURRENT_SESSION,
+};
+use std::ptr;
+use std::slice;
+use uucore::wide::FromWide;
+
+/// The user logged on to the session this runs in, who needn't be the user
+/// it runs as; failing that, the latter.
+pub fn get_userlogin() -> Option<String> {
+    session_user().or_else(process_user)
+}
+
+fn session_user() -> Option<String> {
+    unsafe {
+        let mut name: LPWSTR = ptr::null_mut();
+        let mut size: DWORD = 0;
+        if WTSQuerySessionInformationW(
+            WTS_CURRENT_SERVER_HANDLE,
+            WTS_CURRENT_SESSION,
+            WTSUserName,
+            &mut name,
+            &mut size,
+        ) == 0
+        {
+            return None;
+        }
+        // size is in bytes, and counts the terminating NUL
+        let len = (size as usize / 2).saturating_sub(1);
+        let user = String::from_wide(slice::from_raw_parts(name, len));
+        WTSFreeMemory(name as _);
+        if user.is_empty() {
+            None
+        } else {
+            Some(user)
+        }
+    }
+}
+
+fn process_user() -> Option<String> {
+    let mut buffer = [0_u16; UNLEN as usize + 1];
+    let mut len = buffer.len() as DWORD;
+    if unsafe { GetUserNameW(buffer.as_mut_ptr(), &mut len) } == 0 {
+        return None;
+    }
+    Some(String::from_wide(&buffer[..len as usize - 1]))
+}
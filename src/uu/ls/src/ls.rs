@@ -5,7 +5,7 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) cpio svgz webm somegroup nlink rmvb xspf
+// spell-checker:ignore (ToDO) cpio svgz webm nlink rmvb xspf
 
 #[cfg(unix)]
 #[macro_use]
@@ -20,7 +20,9 @@ use number_prefix::NumberPrefix;
 #[cfg(unix)]
 use std::collections::HashMap;
 use std::fs;
-use std::fs::{DirEntry, FileType, Metadata};
+#[cfg(not(windows))]
+use std::fs::{read_link, Metadata};
+use std::fs::{DirEntry, FileType};
 #[cfg(unix)]
 use std::os::unix::fs::FileTypeExt;
 #[cfg(any(unix, target_os = "redox"))]
@@ -28,9 +30,7 @@ use std::os::unix::fs::MetadataExt;
 #[cfg(windows)]
 use std::os::windows::fs::MetadataExt;
 use std::path::{Path, PathBuf};
-#[cfg(unix)]
-use std::time::Duration;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{cmp::Reverse, process::exit};
 
 use term_grid::{Cell, Direction, Filling, Grid, GridOptions};
@@ -38,7 +38,13 @@ use time::{strftime, Timespec};
 #[cfg(unix)]
 use unicode_width::UnicodeWidthStr;
 #[cfg(unix)]
+use uucore::entries::{group_name, user_name};
+use uucore::i18n::{self, Category};
+use uucore::json;
+#[cfg(unix)]
 use uucore::libc::{mode_t, S_ISGID, S_ISUID, S_ISVTX, S_IWOTH, S_IXGRP, S_IXOTH, S_IXUSR};
+#[cfg(windows)]
+use uucore::winfs::{group_name, read_link, user_name, Metadata};
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static ABOUT: &str = "
@@ -85,6 +91,7 @@ pub mod options {
         pub static LONG_NO_OWNER: &str = "g";
         pub static LONG_NO_GROUP: &str = "o";
         pub static LONG_NUMERIC_UID_GID: &str = "numeric-uid-gid";
+        pub static JSON: &str = "json";
     }
     pub mod files {
         pub static ALL: &str = "all";
@@ -139,6 +146,7 @@ enum Format {
     OneLine,
     Across,
     Commas,
+    Json,
 }
 
 enum Sort {
@@ -215,6 +223,7 @@ impl Config {
                     "columns" | "vertical" => Format::Columns,
                     "across" | "horizontal" => Format::Across,
                     "commas" => Format::Commas,
+                    "json" => Format::Json,
                     // below should never happen as clap already restricts the values.
                     _ => unreachable!("Invalid field for --format"),
                 },
@@ -226,6 +235,8 @@ impl Config {
             (Format::Across, options::format::ACROSS)
         } else if options.is_present(options::format::COMMAS) {
             (Format::Commas, options::format::COMMAS)
+        } else if options.is_present(options::format::JSON) {
+            (Format::Json, options::format::JSON)
         } else {
             (Format::Columns, options::format::COLUMNS)
         };
@@ -412,10 +423,23 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
     let usage = get_usage();
 
-    let app = App::new(executable!())
+    let about = i18n::tr(ABOUT);
+    let app = uu_app().about(&about[..]).usage(&usage[..]);
+
+    let matches = uucore::help::get_matches_from(app, args);
+
+    let locs = matches
+        .values_of(options::PATHS)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_else(|| vec![String::from(".")]);
+
+    list(locs, Config::from(matches))
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
 
         // Format arguments
         .arg(
@@ -423,7 +447,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .long(options::FORMAT)
                 .help("Set the display format.")
                 .takes_value(true)
-                .possible_values(&["long", "verbose", "single-column", "columns", "vertical", "across", "horizontal", "commas"])
+                .possible_values(&["long", "verbose", "single-column", "columns", "vertical", "across", "horizontal", "commas", "json"])
                 .hide_possible_values(true)
                 .require_equals(true)
                 .overrides_with_all(&[
@@ -432,6 +456,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                     options::format::LONG,
                     options::format::ACROSS,
                     options::format::COLUMNS,
+                    options::format::JSON,
                 ]),
         )
         .arg(
@@ -444,6 +469,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                     options::format::LONG,
                     options::format::ACROSS,
                     options::format::COLUMNS,
+                    options::format::JSON,
                 ]),
         )
         .arg(
@@ -457,6 +483,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                     options::format::LONG,
                     options::format::ACROSS,
                     options::format::COLUMNS,
+                    options::format::JSON,
                 ]),
         )
         .arg(
@@ -469,6 +496,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                     options::format::LONG,
                     options::format::ACROSS,
                     options::format::COLUMNS,
+                    options::format::JSON,
                 ]),
         )
         .arg(
@@ -481,6 +509,20 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                     options::format::LONG,
                     options::format::ACROSS,
                     options::format::COLUMNS,
+                    options::format::JSON,
+                ]),
+        )
+        .arg(
+            Arg::with_name(options::format::JSON)
+                .long(options::format::JSON)
+                .help("Print a JSON object for each file, one per line.")
+                .overrides_with_all(&[
+                    options::FORMAT,
+                    options::format::COLUMNS,
+                    options::format::LONG,
+                    options::format::ACROSS,
+                    options::format::COLUMNS,
+                    options::format::JSON,
                 ]),
         )
         // The next four arguments do not override with the other format
@@ -781,16 +823,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 ]))
 
     // Positional arguments
-        .arg(Arg::with_name(options::PATHS).multiple(true).takes_value(true));
-
-    let matches = app.get_matches_from(args);
-
-    let locs = matches
-        .values_of(options::PATHS)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_else(|| vec![String::from(".")]);
-
-    list(locs, Config::from(matches))
+        .arg(Arg::with_name(options::PATHS).multiple(true).takes_value(true))
 }
 
 fn list(locs: Vec<String>, config: Config) -> i32 {
@@ -831,7 +864,7 @@ fn list(locs: Vec<String>, config: Config) -> i32 {
 
     sort_entries(&mut dirs, &config);
     for dir in dirs {
-        if number_of_locs > 1 {
+        if number_of_locs > 1 && config.format != Format::Json {
             println!("\n{}:", dir.to_string_lossy());
         }
         enter_directory(&dir, &config);
@@ -909,12 +942,15 @@ fn enter_directory(dir: &PathBuf, config: &Config) {
 
     if config.recursive {
         for e in entries.iter().filter(|p| p.is_dir()) {
-            println!("\n{}:", e.to_string_lossy());
+            if config.format != Format::Json {
+                println!("\n{}:", e.to_string_lossy());
+            }
             enter_directory(&e, config);
         }
     }
 }
 
+#[cfg(not(windows))]
 fn get_metadata(entry: &PathBuf, config: &Config) -> std::io::Result<Metadata> {
     if config.dereference {
         entry.metadata().or_else(|_| entry.symlink_metadata())
@@ -923,6 +959,15 @@ fn get_metadata(entry: &PathBuf, config: &Config) -> std::io::Result<Metadata> {
     }
 }
 
+#[cfg(windows)]
+fn get_metadata(entry: &PathBuf, config: &Config) -> std::io::Result<Metadata> {
+    if config.dereference {
+        Metadata::new(entry, true).or_else(|_| Metadata::new(entry, false))
+    } else {
+        Metadata::new(entry, false)
+    }
+}
+
 fn display_dir_entry_size(entry: &PathBuf, config: &Config) -> (usize, usize) {
     if let Ok(md) = get_metadata(entry, config) {
         (
@@ -939,7 +984,11 @@ fn pad_left(string: String, count: usize) -> String {
 }
 
 fn display_items(items: &[PathBuf], strip: Option<&Path>, config: &Config) {
-    if config.format == Format::Long {
+    if config.format == Format::Json {
+        for item in items {
+            display_item_json(item, strip, config);
+        }
+    } else if config.format == Format::Long {
         let (mut max_links, mut max_size) = (1, 1);
         for item in items {
             let (links, size) = display_dir_entry_size(item, config);
@@ -1015,8 +1064,14 @@ fn display_grid(names: impl Iterator<Item = Cell>, width: u16, direction: Direct
     }
 }
 
+#[cfg(not(windows))]
 use uucore::fs::display_permissions;
 
+#[cfg(windows)]
+fn display_permissions(metadata: &Metadata) -> String {
+    uucore::fs::display_permissions_unix(metadata.mode())
+}
+
 fn display_item_long(
     item: &PathBuf,
     strip: Option<&Path>,
@@ -1033,11 +1088,8 @@ fn display_item_long(
         Ok(md) => md,
     };
 
-    #[cfg(unix)]
-    {
-        if config.inode {
-            print!("{} ", get_inode(&md));
-        }
+    if config.inode {
+        print!("{} ", get_inode(&md));
     }
 
     print!(
@@ -1069,48 +1121,73 @@ fn display_item_long(
     );
 }
 
-#[cfg(unix)]
+/// Print the record of `item` for `--json`. Unlike the long format, it has
+/// every field, and sizes and times as they are.
+fn display_item_json(item: &PathBuf, strip: Option<&Path>, config: &Config) {
+    let md = match get_metadata(item, config) {
+        Err(e) => {
+            let filename = get_file_name(&item, strip);
+            show_error!("{}: {}", filename, e);
+            return;
+        }
+        Ok(md) => md,
+    };
+
+    let mut record = json::Object::new();
+    record
+        .string("name", &get_file_name(item, strip))
+        .string("path", &item.to_string_lossy())
+        .string("type", json::file_type(&md.file_type()))
+        .number("size", md.len())
+        .string("permissions", &display_permissions(&md))
+        .number("mode", md.mode() & 0o7777)
+        .number("links", md.nlink())
+        .number("inode", md.ino())
+        .number("uid", md.uid());
+    match user_name(&md) {
+        Some(user) => record.string("user", &user),
+        None => record.null("user"),
+    };
+    record.number("gid", md.gid());
+    match group_name(&md) {
+        Some(group) => record.string("group", &group),
+        None => record.null("group"),
+    };
+    record
+        .timestamp("atime", md.atime(), md.atime_nsec() as u32)
+        .timestamp("mtime", md.mtime(), md.mtime_nsec() as u32)
+        .timestamp("ctime", md.ctime(), md.ctime_nsec() as u32);
+
+    match read_link(item) {
+        Ok(target) if md.file_type().is_symlink() => {
+            record.string("target", &target.to_string_lossy())
+        }
+        _ => record.null("target"),
+    };
+
+    println!("{}", record);
+}
+
 fn get_inode(metadata: &Metadata) -> String {
     format!("{:8}", metadata.ino())
 }
 
-// Currently getpwuid is `linux` target only. If it's broken out into
-// a posix-compliant attribute this can be updated...
-#[cfg(unix)]
-use uucore::entries;
-
-#[cfg(unix)]
 fn display_uname(metadata: &Metadata, config: &Config) -> String {
     if config.long.numeric_uid_gid {
         metadata.uid().to_string()
     } else {
-        entries::uid2usr(metadata.uid()).unwrap_or_else(|_| metadata.uid().to_string())
+        user_name(metadata).unwrap_or_else(|| metadata.uid().to_string())
     }
 }
 
-#[cfg(unix)]
 fn display_group(metadata: &Metadata, config: &Config) -> String {
     if config.long.numeric_uid_gid {
         metadata.gid().to_string()
     } else {
-        entries::gid2grp(metadata.gid()).unwrap_or_else(|_| metadata.gid().to_string())
+        group_name(metadata).unwrap_or_else(|| metadata.gid().to_string())
     }
 }
 
-#[cfg(not(unix))]
-fn display_uname(_metadata: &Metadata, _config: &Config) -> String {
-    "somebody".to_string()
-}
-
-#[cfg(not(unix))]
-#[allow(unused_variables)]
-fn display_group(_metadata: &Metadata, _config: &Config) -> String {
-    "somegroup".to_string()
-}
-
-// The implementations for get_time are separated because some options, such
-// as ctime will not be available
-#[cfg(unix)]
 fn get_system_time(md: &Metadata, config: &Config) -> Option<SystemTime> {
     match config.time {
         Time::Change => Some(UNIX_EPOCH + Duration::new(md.ctime() as u64, md.ctime_nsec() as u32)),
@@ -1119,15 +1196,6 @@ fn get_system_time(md: &Metadata, config: &Config) -> Option<SystemTime> {
     }
 }
 
-#[cfg(not(unix))]
-fn get_system_time(md: &Metadata, config: &Config) -> Option<SystemTime> {
-    match config.time {
-        Time::Modification => md.modified().ok(),
-        Time::Access => md.accessed().ok(),
-        _ => None,
-    }
-}
-
 fn get_time(md: &Metadata, config: &Config) -> Option<time::Tm> {
     let duration = get_system_time(md, config)?
         .duration_since(UNIX_EPOCH)
@@ -1138,12 +1206,49 @@ fn get_time(md: &Metadata, config: &Config) -> Option<time::Tm> {
 }
 
 fn display_date(metadata: &Metadata, config: &Config) -> String {
+    const DEFAULT_FORMAT: &str = "%F %R";
     match get_time(metadata, config) {
-        Some(time) => strftime("%F %R", &time).unwrap(),
+        Some(time) => {
+            let format = i18n::tr_in(Category::Time, DEFAULT_FORMAT);
+            strftime(&localize_names(&format, &time), &time)
+                .or_else(|_| strftime(DEFAULT_FORMAT, &time))
+                .unwrap()
+        }
         None => "???".into(),
     }
 }
 
+/// Substitute the locale's month and day names for `%a`, `%A`, `%b`,
+/// `%h` and `%B` in `format`, which `strftime` only knows in English.
+fn localize_names(format: &str, time: &time::Tm) -> String {
+    let mut localized = String::with_capacity(format.len());
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            localized.push(c);
+            continue;
+        }
+        let (month, day) = (time.tm_mon as usize, time.tm_wday as usize);
+        let name = match chars.next() {
+            Some('a') => i18n::day_name(day, true),
+            Some('A') => i18n::day_name(day, false),
+            Some('b') | Some('h') => i18n::month_name(month, true),
+            Some('B') => i18n::month_name(month, false),
+            Some(c) => {
+                localized.push('%');
+                localized.push(c);
+                continue;
+            }
+            None => {
+                localized.push('%');
+                break;
+            }
+        };
+        localized.push_str(&name.replace('%', "%%"));
+    }
+    localized
+}
+
 // There are a few peculiarities to how GNU formats the sizes:
 // 1. One decimal place is given if and only if the size is smaller than 10
 // 2. It rounds sizes up.
@@ -1199,7 +1304,7 @@ fn get_file_name(name: &Path, strip: Option<&Path>) -> String {
     name.to_string_lossy().into_owned()
 }
 
-#[cfg(not(unix))]
+#[cfg(windows)]
 fn display_file_name(
     path: &Path,
     strip: Option<&Path>,
@@ -1207,6 +1312,9 @@ fn display_file_name(
     config: &Config,
 ) -> Cell {
     let mut name = get_file_name(path, strip);
+    if config.format != Format::Long && config.inode {
+        name = get_inode(metadata) + " " + &name;
+    }
     let file_type = metadata.file_type();
 
     match config.indicator_style {
@@ -1217,6 +1325,12 @@ fn display_file_name(
             if file_type.is_symlink() {
                 name.push('@');
             }
+            if config.indicator_style == IndicatorStyle::Classify
+                && file_type.is_file()
+                && metadata.mode() & 0o111 != 0
+            {
+                name.push('*');
+            }
         }
         IndicatorStyle::Slash => {
             if file_type.is_dir() {
@@ -1227,7 +1341,7 @@ fn display_file_name(
     };
 
     if config.format == Format::Long && metadata.file_type().is_symlink() {
-        if let Ok(target) = path.read_link() {
+        if let Ok(target) = read_link(path) {
             // We don't bother updating width here because it's not used for long listings
             let target_name = target.to_string_lossy().to_string();
             name.push_str(" -> ");
@@ -1360,7 +1474,7 @@ fn display_file_name(
     }
 
     if config.format == Format::Long && metadata.file_type().is_symlink() {
-        if let Ok(target) = path.read_link() {
+        if let Ok(target) = read_link(path) {
             // We don't bother updating width here because it's not used for long listings
             let code = if target.exists() { "fi" } else { "mi" };
             let target_name = color_name(target.to_string_lossy().to_string(), code);
@@ -1375,14 +1489,6 @@ fn display_file_name(
     }
 }
 
-#[cfg(not(unix))]
-fn display_symlink_count(_metadata: &Metadata) -> String {
-    // Currently not sure of how to get this on Windows, so I'm punting.
-    // Git Bash looks like it may do the same thing.
-    String::from("1")
-}
-
-#[cfg(unix)]
 fn display_symlink_count(metadata: &Metadata) -> String {
     metadata.nlink().to_string()
 }
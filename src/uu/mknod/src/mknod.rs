@@ -15,12 +15,26 @@ extern crate uucore;
 use libc::{dev_t, mode_t};
 use libc::{S_IFBLK, S_IFCHR, S_IFIFO, S_IRGRP, S_IROTH, S_IRUSR, S_IWGRP, S_IWOTH, S_IWUSR};
 
-use getopts::Options;
-
 use std::ffi::CString;
+use uucore::coreopts::CoreOptions;
 
 static NAME: &str = "mknod";
-static VERSION: &str = env!("CARGO_PKG_VERSION");
+static SYNTAX: &str = "[OPTION]... NAME TYPE [MAJOR MINOR]";
+static SUMMARY: &str = "Create the special file NAME of the given TYPE.";
+static LONG_HELP: &str = "
+Both MAJOR and MINOR must be specified when TYPE is b, c, or u, and they
+must be omitted when TYPE is p.  If MAJOR or MINOR begins with 0x or 0X,
+it is interpreted as hexadecimal; otherwise, if it begins with 0, as octal;
+otherwise, as decimal.  TYPE may be:
+
+  b      create a block (buffered) special file
+  c, u   create a character (unbuffered) special file
+  p      create a FIFO
+
+NOTE: your shell may have its own version of mknod, which usually supersedes
+the version described here.  Please refer to your shell's documentation
+for details about the options it supports.
+";
 
 const MODE_RW_UGO: mode_t = S_IRUSR | S_IWUSR | S_IRGRP | S_IWGRP | S_IROTH | S_IWOTH;
 
@@ -44,56 +58,7 @@ fn _makenod(path: CString, mode: mode_t, dev: dev_t) -> i32 {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let mut opts = Options::new();
-
-    // Linux-specific options, not implemented
-    // opts.optflag("Z", "", "set the SELinux security context to default type");
-    // opts.optopt("", "context", "like -Z, or if CTX is specified then set the SELinux or SMACK security context to CTX");
-    opts.optopt(
-        "m",
-        "mode",
-        "set file permission bits to MODE, not a=rw - umask",
-        "MODE",
-    );
-
-    opts.optflag("", "help", "display this help and exit");
-    opts.optflag("", "version", "output version information and exit");
-
-    let matches = match opts.parse(&args[1..]) {
-        Ok(m) => m,
-        Err(f) => crash!(1, "{}\nTry '{} --help' for more information.", f, NAME),
-    };
-
-    if matches.opt_present("help") {
-        println!(
-            "Usage: {0} [OPTION]... NAME TYPE [MAJOR MINOR]
-
-Mandatory arguments to long options are mandatory for short options too.
-  -m, --mode=MODE    set file permission bits to MODE, not a=rw - umask
-      --help     display this help and exit
-      --version  output version information and exit
-
-Both MAJOR and MINOR must be specified when TYPE is b, c, or u, and they
-must be omitted when TYPE is p.  If MAJOR or MINOR begins with 0x or 0X,
-it is interpreted as hexadecimal; otherwise, if it begins with 0, as octal;
-otherwise, as decimal.  TYPE may be:
-
-  b      create a block (buffered) special file
-  c, u   create a character (unbuffered) special file
-  p      create a FIFO
-
-NOTE: your shell may have its own version of mknod, which usually supersedes
-the version described here.  Please refer to your shell's documentation
-for details about the options it supports.",
-            NAME
-        );
-        return 0;
-    }
-
-    if matches.opt_present("version") {
-        println!("{} {}", NAME, VERSION);
-        return 0;
-    }
+    let matches = uu_app().parse(args);
 
     let mut last_umask: mode_t = 0;
     let mut newmode: mode_t = MODE_RW_UGO;
@@ -196,3 +161,17 @@ for details about the options it supports.",
 
     ret
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    // Linux-specific options, not implemented
+    // app.optflag("Z", "", "set the SELinux security context to default type");
+    // app.optopt("", "context", "like -Z, or if CTX is specified then set the SELinux or SMACK security context to CTX");
+    app.optopt(
+        "m",
+        "mode",
+        "set file permission bits to MODE, not a=rw - umask",
+        "MODE",
+    );
+    app
+}
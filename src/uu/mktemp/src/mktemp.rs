@@ -44,61 +44,7 @@ fn get_usage() -> String {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .arg(
-            Arg::with_name(OPT_DIRECTORY)
-                .short("d")
-                .long(OPT_DIRECTORY)
-                .help("Make a directory instead of a file"),
-        )
-        .arg(
-            Arg::with_name(OPT_DRY_RUN)
-                .short("u")
-                .long(OPT_DRY_RUN)
-                .help("do not create anything; merely print a name (unsafe)"),
-        )
-        .arg(
-            Arg::with_name(OPT_QUIET)
-                .short("q")
-                .long("quiet")
-                .help("Fail silently if an error occurs."),
-        )
-        .arg(
-            Arg::with_name(OPT_SUFFIX)
-                .long(OPT_SUFFIX)
-                .help(
-                    "append SUFF to TEMPLATE; SUFF must not contain a path separator. \
-         This option is implied if TEMPLATE does not end with X.",
-                )
-                .value_name("SUFF"),
-        )
-        .arg(
-            Arg::with_name(OPT_TMPDIR)
-                .short("p")
-                .long(OPT_TMPDIR)
-                .help(
-                    "interpret TEMPLATE relative to DIR; if DIR is not specified, use \
-         $TMPDIR if set, else /tmp. With this option, TEMPLATE must not \
-         be an absolute name; unlike with -t, TEMPLATE may contain \
-         slashes, but mktemp creates only the final component",
-                )
-                .value_name("DIR"),
-        )
-        .arg(Arg::with_name(OPT_T).short(OPT_T).help(
-            "Generate a template (using the supplied prefix and TMPDIR if set) \
-                               to create a filename template [deprecated]",
-        ))
-        .arg(
-            Arg::with_name(ARG_TEMPLATE)
-                .multiple(false)
-                .takes_value(true)
-                .max_values(1)
-                .default_value(DEFAULT_TEMPLATE),
-        )
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
 
     let template = matches.value_of(ARG_TEMPLATE).unwrap();
     let tmpdir = matches.value_of(OPT_TMPDIR).unwrap_or_default();
@@ -175,6 +121,62 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     }
 }
 
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(
+            Arg::with_name(OPT_DIRECTORY)
+                .short("d")
+                .long(OPT_DIRECTORY)
+                .help("Make a directory instead of a file"),
+        )
+        .arg(
+            Arg::with_name(OPT_DRY_RUN)
+                .short("u")
+                .long(OPT_DRY_RUN)
+                .help("do not create anything; merely print a name (unsafe)"),
+        )
+        .arg(
+            Arg::with_name(OPT_QUIET)
+                .short("q")
+                .long("quiet")
+                .help("Fail silently if an error occurs."),
+        )
+        .arg(
+            Arg::with_name(OPT_SUFFIX)
+                .long(OPT_SUFFIX)
+                .help(
+                    "append SUFF to TEMPLATE; SUFF must not contain a path separator. \
+         This option is implied if TEMPLATE does not end with X.",
+                )
+                .value_name("SUFF"),
+        )
+        .arg(
+            Arg::with_name(OPT_TMPDIR)
+                .short("p")
+                .long(OPT_TMPDIR)
+                .help(
+                    "interpret TEMPLATE relative to DIR; if DIR is not specified, use \
+         $TMPDIR if set, else /tmp. With this option, TEMPLATE must not \
+         be an absolute name; unlike with -t, TEMPLATE may contain \
+         slashes, but mktemp creates only the final component",
+                )
+                .value_name("DIR"),
+        )
+        .arg(Arg::with_name(OPT_T).short(OPT_T).help(
+            "Generate a template (using the supplied prefix and TMPDIR if set) \
+                               to create a filename template [deprecated]",
+        ))
+        .arg(
+            Arg::with_name(ARG_TEMPLATE)
+                .multiple(false)
+                .takes_value(true)
+                .max_values(1)
+                .default_value(DEFAULT_TEMPLATE),
+        )
+}
+
 fn parse_template(temp: &str) -> Option<(&str, usize, &str)> {
     let right = match temp.rfind('X') {
         Some(r) => r + 1,
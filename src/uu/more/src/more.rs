@@ -5,81 +5,338 @@
 //  * For the full copyright and license information, please view the LICENSE file
 //  * that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) lflag ICANON tcgetattr tcsetattr TCSADRAIN
+// spell-checker:ignore (ToDO) lflag ICANON ISIG tcgetattr tcsetattr TCSADRAIN TIOCGWINSZ winsize WINCH
 
 #[macro_use]
 extern crate uucore;
 
 use std::fs::File;
-use std::io::{stdin, stdout, BufRead, BufReader, Read, Write};
+use std::io::{self, stdin, stdout, Read, Write};
 
 #[cfg(all(unix, not(target_os = "fuchsia")))]
 extern crate nix;
 #[cfg(all(unix, not(target_os = "fuchsia")))]
+use nix::libc;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
+#[cfg(all(unix, not(target_os = "fuchsia")))]
 use nix::sys::termios::{self, LocalFlags, SetArg};
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+use std::os::unix::io::AsRawFd;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(target_os = "redox")]
 extern crate redox_termios;
 #[cfg(target_os = "redox")]
 extern crate syscall;
 
-use clap::{App, Arg, ArgMatches};
+use clap::{App, Arg};
+use regex::Regex;
+use uucore::error_message;
+use uucore::fs::is_stdout_interactive;
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static ABOUT: &str = "A file perusal filter for CRT viewing.";
+static LONG_HELP: &str = "
+Commands, while a file is displayed:
+  SPACE, f, PAGE DOWN   display the next screen
+  b, PAGE UP            go back one screen
+  RETURN, j, DOWN       display the next line
+  k, UP                 go back one line
+  d, u                  scroll half a screen down or up
+  g, HOME / G, END      go to the first or last screen
+  /PATTERN, ?PATTERN    search forward or backward for a regular expression
+  n, N                  repeat the last search, in the same or other direction
+  :n, :p                go to the next or previous file
+  q, :q, ESC            quit";
 
 mod options {
     pub const FILE: &str = "file";
 }
 
+// lines are padded to this multiple when expanding tabs
+const TAB_WIDTH: usize = 8;
+
 fn get_usage() -> String {
-    format!("{} [options] <file>...", executable!())
+    format!("{} [options] [+NUM | +/PATTERN] <file>...", executable!())
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    // "+NUM" and "+/PATTERN" choose where each file starts, everything else is a file
+    let mut start = Start::Line(0);
+    let mut names = vec![];
+    for arg in matches.values_of(options::FILE).into_iter().flatten() {
+        if arg.starts_with("+/") {
+            start = Start::Pattern(arg[2..].to_owned());
+        } else if arg.starts_with('+') {
+            match arg[1..].parse::<usize>() {
+                Ok(line) => start = Start::Line(line.saturating_sub(1)),
+                Err(_) => crash!(1, "invalid line number '{}'", &arg[1..]),
+            }
+        } else {
+            names.push(arg.to_owned());
+        }
+    }
+
+    // FixME: `more` should work with no arguments (ie, for piped input)
+    if names.is_empty() {
+        println!("{}: incorrect usage", executable!());
+        return 1;
+    }
+
+    let mut retcode = 0;
+    let mut documents = vec![];
+    for name in names {
+        match Document::open(name, &start) {
+            Ok(document) => documents.push(document),
+            Err((name, e)) => {
+                show_error!("cannot open {}: {}", name, error_message(&e));
+                retcode = 1;
+            }
+        }
+    }
+    if documents.is_empty() {
+        return retcode;
+    }
+
+    let result = if is_stdout_interactive() {
+        Pager::new(documents).and_then(|mut pager| pager.run())
+    } else {
+        concatenate(&documents)
+    };
+    if let Err(e) = result {
+        show_error!("{}", error_message(&e));
+        retcode = 1;
+    }
+    retcode
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
-        .usage(&usage[..])
         .about(ABOUT)
+        .after_help(LONG_HELP)
         .arg(
             Arg::with_name(options::FILE)
                 .number_of_values(1)
                 .multiple(true),
         )
-        .get_matches_from(args);
+}
 
-    // FixME: fail without panic for now; but `more` should work with no arguments (ie, for piped input)
-    if let None | Some("-") = matches.value_of(options::FILE) {
-        println!("{}: incorrect usage", executable!());
-        return 1;
+/// Where to start displaying each file
+enum Start {
+    Line(usize),
+    Pattern(String),
+}
+
+/// A file, split into lines
+struct Document {
+    name: String,
+    lines: Vec<String>,
+    top: usize,
+    message: Option<String>,
+}
+
+impl Document {
+    fn open(name: String, start: &Start) -> Result<Document, (String, io::Error)> {
+        let mut bytes = vec![];
+        let read = if name == "-" {
+            stdin().read_to_end(&mut bytes)
+        } else {
+            File::open(&name).and_then(|mut f| f.read_to_end(&mut bytes))
+        };
+        if let Err(e) = read {
+            return Err((name, e));
+        }
+
+        let lines: Vec<String> = String::from_utf8_lossy(&bytes)
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        let mut document = Document {
+            name,
+            lines,
+            top: 0,
+            message: None,
+        };
+        match *start {
+            Start::Line(line) => document.top = line.min(document.lines.len()),
+            Start::Pattern(ref pattern) => match Regex::new(pattern) {
+                Ok(re) => match document.lines.iter().position(|line| re.is_match(line)) {
+                    Some(line) => document.top = line,
+                    None => document.message = Some("Pattern not found".to_owned()),
+                },
+                Err(e) => document.message = Some(e.to_string()),
+            },
+        }
+        Ok(document)
     }
+}
 
-    more(matches);
+/// A line as it is displayed, with its tabs turned into spaces
+fn expand_tabs(line: &str) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for ch in line.chars() {
+        if ch == '\t' {
+            let spaces = TAB_WIDTH - column % TAB_WIDTH;
+            expanded.extend(std::iter::repeat(' ').take(spaces));
+            column += spaces;
+        } else {
+            expanded.push(ch);
+            column += 1;
+        }
+    }
+    expanded
+}
 
-    0
+/// Without a terminal to page on, just print everything, like `cat`
+fn concatenate(documents: &[Document]) -> io::Result<()> {
+    let stdout = stdout();
+    let mut out = stdout.lock();
+    for document in documents {
+        if documents.len() > 1 {
+            writeln!(out, "::::::::::::::\n{}\n::::::::::::::", document.name)?;
+        }
+        for line in &document.lines[document.top..] {
+            writeln!(out, "{}", line)?;
+        }
+    }
+    out.flush()
+}
+
+// Set by the SIGWINCH handler, which interrupts the read of the next key
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+extern "C" fn on_resize(_: libc::c_int) {
+    RESIZED.store(true, Ordering::SeqCst);
+}
+
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+fn watch_resize() {
+    // Without SA_RESTART, so that the read of a key returns
+    let action = SigAction::new(
+        SigHandler::Handler(on_resize),
+        SaFlags::empty(),
+        SigSet::empty(),
+    );
+    let _ = unsafe { signal::sigaction(Signal::SIGWINCH, &action) };
+}
+
+#[cfg(not(all(unix, not(target_os = "fuchsia"))))]
+fn watch_resize() {}
+
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+fn resized() -> bool {
+    RESIZED.load(Ordering::SeqCst)
+}
+
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+fn take_resized() -> bool {
+    RESIZED.swap(false, Ordering::SeqCst)
+}
+
+#[cfg(not(all(unix, not(target_os = "fuchsia"))))]
+fn resized() -> bool {
+    false
+}
+
+#[cfg(not(all(unix, not(target_os = "fuchsia"))))]
+fn take_resized() -> bool {
+    false
 }
 
+/// The size of the terminal, as rows and columns
 #[cfg(all(unix, not(target_os = "fuchsia")))]
-fn setup_term() -> termios::Termios {
-    let mut term = termios::tcgetattr(0).unwrap();
+fn terminal_size(keys: &KeyInput) -> (usize, usize) {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let ok = unsafe { libc::ioctl(keys.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } == 0;
+    if ok && size.ws_row > 0 && size.ws_col > 0 {
+        (size.ws_row as usize, size.ws_col as usize)
+    } else {
+        env_terminal_size()
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "fuchsia"))))]
+fn terminal_size(_: &KeyInput) -> (usize, usize) {
+    env_terminal_size()
+}
+
+fn env_terminal_size() -> (usize, usize) {
+    let var = |name: &str, default: usize| {
+        std::env::var(name)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&v| v > 0)
+            .unwrap_or(default)
+    };
+    (var("LINES", 24), var("COLUMNS", 80))
+}
+
+// Keys are read from the terminal, as standard input may be a file to page
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+type KeyInput = File;
+
+#[cfg(not(all(unix, not(target_os = "fuchsia"))))]
+type KeyInput = io::Stdin;
+
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+fn open_keys() -> io::Result<KeyInput> {
+    File::open("/dev/tty")
+}
+
+#[cfg(not(all(unix, not(target_os = "fuchsia"))))]
+fn open_keys() -> io::Result<KeyInput> {
+    Ok(stdin())
+}
+
+/// Whether another byte follows, as part of an escape sequence
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+fn key_pending(keys: &KeyInput) -> bool {
+    let mut fd = libc::pollfd {
+        fd: keys.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    unsafe { libc::poll(&mut fd, 1, 50) > 0 }
+}
+
+#[cfg(not(all(unix, not(target_os = "fuchsia"))))]
+fn key_pending(_: &KeyInput) -> bool {
+    true
+}
+
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+fn setup_term(keys: &KeyInput) -> termios::Termios {
+    let fd = keys.as_raw_fd();
+    let saved = termios::tcgetattr(fd).unwrap();
+    let mut term = saved.clone();
     // Unset canonical mode, so we get characters immediately
     term.local_flags.remove(LocalFlags::ICANON);
     // Disable local echo
     term.local_flags.remove(LocalFlags::ECHO);
-    termios::tcsetattr(0, SetArg::TCSADRAIN, &term).unwrap();
-    term
+    // Read ^C as a key, to quit with the terminal restored
+    term.local_flags.remove(LocalFlags::ISIG);
+    termios::tcsetattr(fd, SetArg::TCSADRAIN, &term).unwrap();
+    saved
 }
 
 #[cfg(any(windows, target_os = "fuchsia"))]
 #[inline(always)]
-fn setup_term() -> usize {
+fn setup_term(_: &KeyInput) -> usize {
     0
 }
 
 #[cfg(target_os = "redox")]
-fn setup_term() -> redox_termios::Termios {
+fn setup_term(_: &KeyInput) -> redox_termios::Termios {
     let mut term = redox_termios::Termios::default();
     let fd = syscall::dup(0, b"termios").unwrap();
     syscall::read(fd, &mut term).unwrap();
@@ -91,18 +348,16 @@ fn setup_term() -> redox_termios::Termios {
 }
 
 #[cfg(all(unix, not(target_os = "fuchsia")))]
-fn reset_term(term: &mut termios::Termios) {
-    term.local_flags.insert(LocalFlags::ICANON);
-    term.local_flags.insert(LocalFlags::ECHO);
-    termios::tcsetattr(0, SetArg::TCSADRAIN, &term).unwrap();
+fn reset_term(keys: &KeyInput, saved: &mut termios::Termios) {
+    termios::tcsetattr(keys.as_raw_fd(), SetArg::TCSADRAIN, saved).unwrap();
 }
 
 #[cfg(any(windows, target_os = "fuchsia"))]
 #[inline(always)]
-fn reset_term(_: &mut usize) {}
+fn reset_term(_: &KeyInput, _: &mut usize) {}
 
-#[cfg(any(target_os = "redox"))]
-fn reset_term(term: &mut redox_termios::Termios) {
+#[cfg(target_os = "redox")]
+fn reset_term(_: &KeyInput, term: &mut redox_termios::Termios) {
     let fd = syscall::dup(0, b"termios").unwrap();
     syscall::read(fd, term).unwrap();
     term.local_flags |= redox_termios::ICANON;
@@ -111,37 +366,375 @@ fn reset_term(term: &mut redox_termios::Termios) {
     let _ = syscall::close(fd);
 }
 
-fn more(matches: ArgMatches) {
-    let mut f: Box<dyn BufRead> = match matches.value_of(options::FILE) {
-        None | Some("-") => Box::new(BufReader::new(stdin())),
-        Some(filename) => Box::new(BufReader::new(File::open(filename).unwrap())),
-    };
-    let mut buffer = [0; 1024];
-
-    let mut term = setup_term();
-
-    let mut end = false;
-    while let Ok(sz) = f.read(&mut buffer) {
-        if sz == 0 {
-            break;
-        }
-        stdout().write_all(&buffer[0..sz]).unwrap();
-        for byte in std::io::stdin().bytes() {
-            match byte.unwrap() {
-                b' ' => break,
-                b'q' | 27 => {
-                    end = true;
-                    break;
+#[cfg(all(unix, not(target_os = "fuchsia")))]
+type TermState = termios::Termios;
+
+#[cfg(any(windows, target_os = "fuchsia"))]
+type TermState = usize;
+
+#[cfg(target_os = "redox")]
+type TermState = redox_termios::Termios;
+
+enum Key {
+    Byte(u8),
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Resize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    Forward,
+    Backward,
+}
+
+struct Pager {
+    documents: Vec<Document>,
+    current: usize,
+    keys: KeyInput,
+    term: TermState,
+    rows: usize,
+    cols: usize,
+    // the line after the last one on screen
+    bottom: usize,
+    search: Option<(Regex, Direction)>,
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        // Leave the cursor at the start of a clean line
+        print!("\r\x1b[K");
+        let _ = stdout().flush();
+        reset_term(&self.keys, &mut self.term);
+    }
+}
+
+impl Pager {
+    fn new(documents: Vec<Document>) -> io::Result<Pager> {
+        let keys = open_keys()?;
+        let term = setup_term(&keys);
+        let (rows, cols) = terminal_size(&keys);
+        watch_resize();
+        Ok(Pager {
+            documents,
+            current: 0,
+            keys,
+            term,
+            rows,
+            cols,
+            bottom: 0,
+            search: None,
+        })
+    }
+
+    fn run(&mut self) -> io::Result<()> {
+        let mut first = true;
+        let mut forward = true;
+        loop {
+            self.draw_page(!first)?;
+            first = false;
+
+            // Like the classic pager, leave once paging forward shows the last line
+            if forward && self.at_end() && self.current + 1 == self.documents.len() {
+                return Ok(());
+            }
+            self.draw_prompt()?;
+
+            let key = self.read_key()?;
+            forward = false;
+            match key {
+                Key::Byte(b' ') | Key::Byte(b'f') | Key::Byte(6) | Key::PageDown => {
+                    let bottom = self.bottom;
+                    self.forward_to(bottom);
+                    forward = true;
+                }
+                Key::Byte(b'\r') | Key::Byte(b'\n') | Key::Byte(b'j') | Key::Down => {
+                    let top = self.document().top;
+                    self.forward_to(top + 1);
+                    forward = true;
+                }
+                Key::Byte(b'd') | Key::Byte(4) => {
+                    let top = self.document().top;
+                    self.forward_to(top + self.page_rows() / 2);
+                    forward = true;
+                }
+                Key::Byte(b'b') | Key::Byte(2) | Key::PageUp => {
+                    let top = self.document().top;
+                    self.document_mut().top = self.page_top_before(top);
+                }
+                Key::Byte(b'k') | Key::Up => {
+                    let document = self.document_mut();
+                    document.top = document.top.saturating_sub(1);
+                }
+                Key::Byte(b'u') | Key::Byte(21) => {
+                    let half = self.page_rows() / 2;
+                    let document = self.document_mut();
+                    document.top = document.top.saturating_sub(half);
+                }
+                Key::Byte(b'g') | Key::Home => self.document_mut().top = 0,
+                Key::Byte(b'G') | Key::End => {
+                    let len = self.document().lines.len();
+                    self.document_mut().top = self.page_top_before(len);
+                }
+                Key::Byte(b'/') => self.prompt_search(Direction::Forward)?,
+                Key::Byte(b'?') => self.prompt_search(Direction::Backward)?,
+                Key::Byte(b'n') => self.repeat_search(false),
+                Key::Byte(b'N') => self.repeat_search(true),
+                Key::Byte(b':') => match self.read_key()? {
+                    Key::Byte(b'n') => forward = self.switch_file(1),
+                    Key::Byte(b'p') => {
+                        self.switch_file(-1);
+                    }
+                    Key::Byte(b'q') | Key::Byte(b'Q') => return Ok(()),
+                    _ => (),
+                },
+                Key::Byte(b'q') | Key::Byte(b'Q') | Key::Byte(27) | Key::Byte(3) => return Ok(()),
+                // ^L, a resize, or an unknown key: just redraw
+                _ => (),
+            }
+        }
+    }
+
+    fn document(&self) -> &Document {
+        &self.documents[self.current]
+    }
+
+    fn document_mut(&mut self) -> &mut Document {
+        &mut self.documents[self.current]
+    }
+
+    /// The rows left for lines, below which is the prompt
+    fn page_rows(&self) -> usize {
+        self.rows.saturating_sub(1).max(1)
+    }
+
+    /// The rows a line takes, once wrapped
+    fn line_rows(&self, line: &str) -> usize {
+        let width = expand_tabs(line).chars().count();
+        ((width + self.cols - 1) / self.cols).max(1)
+    }
+
+    fn at_end(&self) -> bool {
+        self.bottom >= self.document().lines.len()
+    }
+
+    /// Scroll down to `top`, moving to the next file past the end of this one
+    fn forward_to(&mut self, top: usize) {
+        if self.at_end() {
+            self.switch_file(1);
+        } else {
+            let len = self.document().lines.len();
+            self.document_mut().top = top.min(len);
+        }
+    }
+
+    /// The top line of the screen which ends just before line `end`
+    fn page_top_before(&self, end: usize) -> usize {
+        let lines = &self.document().lines;
+        let mut rows = 0;
+        let mut top = end;
+        while top > 0 {
+            rows += self.line_rows(&lines[top - 1]);
+            if rows > self.page_rows() {
+                break;
+            }
+            top -= 1;
+        }
+        top
+    }
+
+    fn switch_file(&mut self, offset: isize) -> bool {
+        let next = self.current as isize + offset;
+        if next < 0 || next as usize >= self.documents.len() {
+            return false;
+        }
+        self.current = next as usize;
+        self.bottom = 0;
+        true
+    }
+
+    fn draw_page(&mut self, clear: bool) -> io::Result<()> {
+        if take_resized() {
+            let (rows, cols) = terminal_size(&self.keys);
+            self.rows = rows;
+            self.cols = cols;
+        }
+
+        let stdout = stdout();
+        let mut out = stdout.lock();
+        if clear {
+            write!(out, "\x1b[H\x1b[2J")?;
+        }
+
+        let multiple = self.documents.len() > 1;
+        let page_rows = self.page_rows();
+        let document = &self.documents[self.current];
+        let mut rows = 0;
+        let mut line = document.top;
+        if multiple && document.top == 0 {
+            writeln!(out, "::::::::::::::\n{}\n::::::::::::::", document.name)?;
+            rows += 3;
+        }
+        while line < document.lines.len() {
+            let line_rows = self.line_rows(&document.lines[line]);
+            if rows > 0 && rows + line_rows > page_rows {
+                break;
+            }
+            writeln!(out, "{}", expand_tabs(&document.lines[line]))?;
+            rows += line_rows;
+            line += 1;
+        }
+        self.bottom = line;
+        out.flush()
+    }
+
+    fn draw_prompt(&mut self) -> io::Result<()> {
+        let document = &self.documents[self.current];
+        let status = match document.message {
+            Some(ref message) => message.clone(),
+            None if self.bottom < document.lines.len() => {
+                format!("{}%", self.bottom * 100 / document.lines.len())
+            }
+            None if self.current + 1 < self.documents.len() => {
+                format!("Next file: {}", self.documents[self.current + 1].name)
+            }
+            None => "END".to_owned(),
+        };
+        self.document_mut().message = None;
+
+        let mut out = stdout();
+        write!(out, "\x1b[7m--More--({})\x1b[27m", status)?;
+        out.flush()
+    }
+
+    /// The next byte from the keyboard, `None` when a resize interrupts the
+    /// wait; a terminal that's gone reads as ^C
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        loop {
+            match self.keys.read(&mut byte) {
+                Ok(0) => return Ok(Some(3)),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {
+                    if resized() {
+                        return Ok(None);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn read_key(&mut self) -> io::Result<Key> {
+        let byte = match self.read_byte()? {
+            Some(byte) => byte,
+            None => return Ok(Key::Resize),
+        };
+        if byte != 27 || !key_pending(&self.keys) {
+            return Ok(Key::Byte(byte));
+        }
+
+        // Escape sequences of the cursor and paging keys
+        let key = match (self.read_byte()?, self.read_byte()?) {
+            (Some(b'['), Some(b'A')) | (Some(b'O'), Some(b'A')) => Key::Up,
+            (Some(b'['), Some(b'B')) | (Some(b'O'), Some(b'B')) => Key::Down,
+            (Some(b'['), Some(b'H')) | (Some(b'O'), Some(b'H')) => Key::Home,
+            (Some(b'['), Some(b'F')) | (Some(b'O'), Some(b'F')) => Key::End,
+            (Some(b'['), Some(code)) if code.is_ascii_digit() => {
+                let _ = self.read_byte()?; // '~'
+                match code {
+                    b'1' | b'7' => Key::Home,
+                    b'4' | b'8' => Key::End,
+                    b'5' => Key::PageUp,
+                    b'6' => Key::PageDown,
+                    _ => Key::Byte(27),
+                }
+            }
+            _ => Key::Byte(27),
+        };
+        Ok(key)
+    }
+
+    /// Read a pattern on the prompt line, `None` when cancelled
+    fn read_pattern(&mut self, prefix: char) -> io::Result<Option<String>> {
+        let mut pattern = vec![];
+        write!(stdout(), "\r\x1b[K{}", prefix)?;
+        stdout().flush()?;
+        loop {
+            match self.read_key()? {
+                Key::Byte(b'\r') | Key::Byte(b'\n') => break,
+                Key::Byte(127) | Key::Byte(8) => {
+                    if pattern.is_empty() {
+                        return Ok(None);
+                    }
+                    // Remove a whole character, with its continuation bytes
+                    while let Some(byte) = pattern.pop() {
+                        if byte & 0xC0 != 0x80 {
+                            break;
+                        }
+                    }
+                    write!(stdout(), "\x08 \x08")?;
+                }
+                Key::Byte(27) | Key::Byte(3) => return Ok(None),
+                Key::Byte(byte) if byte >= b' ' => {
+                    pattern.push(byte);
+                    stdout().write_all(&[byte])?;
                 }
                 _ => (),
             }
+            stdout().flush()?;
         }
+        Ok(Some(String::from_utf8_lossy(&pattern).into_owned()))
+    }
 
-        if end {
-            break;
+    fn prompt_search(&mut self, direction: Direction) -> io::Result<()> {
+        let prefix = if direction == Direction::Forward {
+            '/'
+        } else {
+            '?'
+        };
+        let pattern = match self.read_pattern(prefix)? {
+            Some(pattern) => pattern,
+            None => return Ok(()),
+        };
+        // An empty pattern repeats the last search
+        if !pattern.is_empty() {
+            match Regex::new(&pattern) {
+                Ok(re) => self.search = Some((re, direction)),
+                Err(e) => {
+                    self.document_mut().message = Some(e.to_string());
+                    return Ok(());
+                }
+            }
         }
+        self.repeat_search(false);
+        Ok(())
     }
 
-    reset_term(&mut term);
-    println!();
+    fn repeat_search(&mut self, reverse: bool) {
+        let found = match self.search {
+            Some((ref re, direction)) => {
+                let forward = (direction == Direction::Forward) != reverse;
+                let document = &self.documents[self.current];
+                let lines = &document.lines;
+                if forward {
+                    (document.top + 1..lines.len()).find(|&i| re.is_match(&lines[i]))
+                } else {
+                    (0..document.top).rev().find(|&i| re.is_match(&lines[i]))
+                }
+            }
+            None => {
+                self.document_mut().message = Some("No previous regular expression".to_owned());
+                return;
+            }
+        };
+        match found {
+            Some(line) => self.document_mut().top = line,
+            None => self.document_mut().message = Some("Pattern not found".to_owned()),
+        }
+    }
 }
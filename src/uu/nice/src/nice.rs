@@ -17,6 +17,10 @@ use std::ptr;
 
 use clap::{App, AppSettings, Arg};
 const VERSION: &str = env!("CARGO_PKG_VERSION");
+const ABOUT: &str = "Run COMMAND with an adjusted niceness, which affects process scheduling.
+With no COMMAND, print the current niceness.  Niceness values range from at
+least -20 (most favorable to the process) to 19 (least favorable to the
+process).";
 
 // XXX: PRIO_PROCESS is 0 on at least FreeBSD and Linux.  Don't know about Mac OS X.
 const PRIO_PROCESS: c_int = 0;
@@ -32,35 +36,13 @@ pub mod options {
 }
 
 fn get_usage() -> String {
-    format!(
-        "
-  {0} [OPTIONS] [COMMAND [ARGS]]
-
-Run COMMAND with an adjusted niceness, which affects process scheduling.
-With no COMMAND, print the current niceness.  Niceness values range from at
-least -20 (most favorable to the process) to 19 (least favorable to the
-process).",
-        executable!()
-    )
+    format!("{0} [OPTIONS] [COMMAND [ARGS]]", executable!())
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
-        .setting(AppSettings::TrailingVarArg)
-        .version(VERSION)
-        .usage(&usage[..])
-        .arg(
-            Arg::with_name(options::ADJUSTMENT)
-                .short("n")
-                .long(options::ADJUSTMENT)
-                .help("add N to the niceness (default is 10)")
-                .takes_value(true)
-                .allow_hyphen_values(true),
-        )
-        .arg(Arg::with_name(options::COMMAND).multiple(true))
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
 
     let mut niceness = unsafe {
         nix::errno::Errno::clear();
@@ -121,3 +103,19 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         126
     }
 }
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .setting(AppSettings::TrailingVarArg)
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(
+            Arg::with_name(options::ADJUSTMENT)
+                .short("n")
+                .long(options::ADJUSTMENT)
+                .help("add N to the niceness (default is 10)")
+                .takes_value(true)
+                .allow_hyphen_values(true),
+        )
+        .arg(Arg::with_name(options::COMMAND).multiple(true))
+}
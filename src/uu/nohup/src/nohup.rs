@@ -5,7 +5,7 @@
 //  * For the full copyright and license information, please view the LICENSE
 //  * file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) execvp SIGHUP cproc vprocmgr cstrs homeout
+// spell-checker:ignore (ToDO) execvp SIGHUP cproc vprocmgr cstrs homeout CLOEXEC DUPFD GETFD
 
 #[macro_use]
 extern crate uucore;
@@ -17,17 +17,20 @@ use std::env;
 use std::ffi::CString;
 use std::fs::{File, OpenOptions};
 use std::io::Error;
-use std::os::unix::prelude::*;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::path::{Path, PathBuf};
+use uucore::error_message;
 use uucore::fs::{is_stderr_interactive, is_stdin_interactive, is_stdout_interactive};
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static ABOUT: &str = "Run COMMAND ignoring hangup signals.";
 static LONG_HELP: &str = "
-If standard input is terminal, it'll be replaced with /dev/null.
-If standard output is terminal, it'll be appended to nohup.out instead,
-or $HOME/nohup.out, if nohup.out open failed.
-If standard error is terminal, it'll be redirected to stdout.
+If standard input is a terminal, redirect it from an unreadable file.
+If standard output is a terminal, append output to 'nohup.out' if possible,
+'$HOME/nohup.out' otherwise.
+If standard error is a terminal, redirect it to standard output.
+To save output to FILE, use 'nohup COMMAND > FILE'.
 ";
 static NOHUP_OUT: &str = "nohup.out";
 // exit codes that match the GNU implementation
@@ -43,109 +46,183 @@ mod options {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .after_help(LONG_HELP)
-        .arg(
-            Arg::with_name(options::CMD)
-                .hidden(true)
-                .required(true)
-                .multiple(true),
-        )
-        .setting(AppSettings::TrailingVarArg)
-        .get_matches_from(args);
+    let app = uu_app().usage(&usage[..]);
+
+    let matches = match uucore::help::get_matches_from_safe(app, args) {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("{}", e);
+            return internal_failure_code();
+        }
+    };
+
+    let cmd: Vec<&str> = match matches.values_of(options::CMD) {
+        Some(cmd) => cmd.collect(),
+        None => {
+            show_usage_error!("missing operand");
+            return internal_failure_code();
+        }
+    };
 
-    replace_fds();
+    let saved_stderr = replace_fds();
 
     unsafe { signal(SIGHUP, SIG_IGN) };
 
     if unsafe { !_vprocmgr_detach_from_console(0).is_null() } {
-        crash!(2, "Cannot detach from console")
+        show_info!("Cannot detach from console");
+        return internal_failure_code();
     };
 
-    let cstrs: Vec<CString> = matches
-        .values_of(options::CMD)
-        .unwrap()
+    let cstrs: Vec<CString> = cmd
+        .iter()
         .map(|x| CString::new(x.as_bytes()).unwrap())
         .collect();
     let mut args: Vec<*const c_char> = cstrs.iter().map(|s| s.as_ptr()).collect();
     args.push(std::ptr::null());
 
-    let ret = unsafe { execvp(args[0], args.as_mut_ptr()) };
-    match ret {
-        libc::ENOENT => EXIT_ENOENT,
-        _ => EXIT_CANNOT_INVOKE,
+    unsafe { execvp(args[0], args.as_mut_ptr()) };
+
+    // execvp() only returns if it failed, which is told where stderr went
+    // before it was redirected
+    let err = Error::last_os_error();
+    if let Some(fd) = saved_stderr {
+        unsafe { dup2(fd, 2) };
+    }
+    show_info!(
+        "failed to run command '{}': {}",
+        cmd[0],
+        error_message(&err)
+    );
+    if err.raw_os_error() == Some(libc::ENOENT) {
+        EXIT_ENOENT
+    } else {
+        EXIT_CANNOT_INVOKE
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .after_help(LONG_HELP)
+        .arg(Arg::with_name(options::CMD).hidden(true).multiple(true))
+        .setting(AppSettings::TrailingVarArg)
+}
+
+/// The exit code for a failure of nohup itself, rather than of the command.
+fn internal_failure_code() -> i32 {
+    match env::var("POSIXLY_CORRECT") {
+        Ok(_) => POSIX_NOHUP_FAILURE,
+        Err(_) => EXIT_CANCELED,
     }
 }
 
-fn replace_fds() {
-    if is_stdin_interactive() {
-        let new_stdin = match File::open(Path::new("/dev/null")) {
+/// Redirect those of the standard streams that are terminals, and return a
+/// copy of stderr if it was, for the command's failure to run to be told to.
+fn replace_fds() -> Option<RawFd> {
+    let ignoring_input = is_stdin_interactive();
+    let redirecting_stdout = is_stdout_interactive();
+    let stdout_is_closed = !redirecting_stdout && unsafe { libc::fcntl(1, libc::F_GETFD) } == -1;
+    let redirecting_stderr = is_stderr_interactive();
+
+    if ignoring_input {
+        // opened for writing only, so that the command fails to read it
+        // rather than seeing end of file
+        let new_stdin = match OpenOptions::new().write(true).open("/dev/null") {
             Ok(t) => t,
-            Err(e) => crash!(2, "Cannot replace STDIN: {}", e),
+            Err(e) => {
+                show_info!(
+                    "failed to render standard input unusable: {}",
+                    error_message(&e)
+                );
+                exit!(internal_failure_code())
+            }
         };
         if unsafe { dup2(new_stdin.as_raw_fd(), 0) } != 0 {
-            crash!(2, "Cannot replace STDIN: {}", Error::last_os_error())
+            show_info!(
+                "failed to render standard input unusable: {}",
+                error_message(&Error::last_os_error())
+            );
+            exit!(internal_failure_code())
+        }
+        if !redirecting_stdout && !redirecting_stderr {
+            show_info!("ignoring input");
         }
     }
 
-    if is_stdout_interactive() {
-        let new_stdout = find_stdout();
-        let fd = new_stdout.as_raw_fd();
-
-        if unsafe { dup2(fd, 1) } != 1 {
-            crash!(2, "Cannot replace STDOUT: {}", Error::last_os_error())
+    // the command's output goes to a file if it would go to the terminal,
+    // or nowhere at all
+    if redirecting_stdout || (redirecting_stderr && stdout_is_closed) {
+        let (new_stdout, name) = find_stdout();
+        if ignoring_input {
+            show_info!("ignoring input and appending output to '{}'", name);
+        } else {
+            show_info!("appending output to '{}'", name);
+        }
+        if unsafe { dup2(new_stdout.as_raw_fd(), 1) } != 1 {
+            show_info!(
+                "failed to redirect standard output: {}",
+                error_message(&Error::last_os_error())
+            );
+            exit!(internal_failure_code())
         }
     }
 
-    if is_stderr_interactive() && unsafe { dup2(1, 2) } != 2 {
-        crash!(2, "Cannot replace STDERR: {}", Error::last_os_error())
+    if redirecting_stderr {
+        // kept out of the command's way, closed when it runs
+        let saved_stderr = unsafe { libc::fcntl(2, libc::F_DUPFD_CLOEXEC, 3) };
+        if !redirecting_stdout {
+            if ignoring_input {
+                show_info!("ignoring input and redirecting stderr to stdout");
+            } else {
+                show_info!("redirecting stderr to stdout");
+            }
+        }
+        if unsafe { dup2(1, 2) } != 2 {
+            show_info!(
+                "failed to redirect standard error: {}",
+                error_message(&Error::last_os_error())
+            );
+            exit!(internal_failure_code())
+        }
+        if saved_stderr >= 0 {
+            return Some(saved_stderr);
+        }
     }
+
+    None
 }
 
-fn find_stdout() -> File {
-    let internal_failure_code = match std::env::var("POSIXLY_CORRECT") {
-        Ok(_) => POSIX_NOHUP_FAILURE,
-        Err(_) => EXIT_CANCELED,
+/// Open the file the command's output is to be appended to, "nohup.out" or
+/// else "$HOME/nohup.out", and the name it was opened by.
+fn find_stdout() -> (File, String) {
+    let open = |path: &Path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .mode(0o600)
+            .open(path)
     };
 
-    match OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(true)
-        .open(Path::new(NOHUP_OUT))
-    {
-        Ok(t) => {
-            show_info!("ignoring input and appending output to '{}'", NOHUP_OUT);
-            t
-        }
+    match open(Path::new(NOHUP_OUT)) {
+        Ok(t) => (t, NOHUP_OUT.to_owned()),
         Err(e1) => {
-            let home = match env::var("HOME") {
-                Err(_) => {
-                    show_info!("failed to open '{}': {}", NOHUP_OUT, e1);
-                    exit!(internal_failure_code)
+            let home = match env::var_os("HOME") {
+                None => {
+                    show_info!("failed to open '{}': {}", NOHUP_OUT, error_message(&e1));
+                    exit!(internal_failure_code())
                 }
-                Ok(h) => h,
+                Some(h) => h,
             };
             let mut homeout = PathBuf::from(home);
             homeout.push(NOHUP_OUT);
-            let homeout_str = homeout.to_str().unwrap();
-            match OpenOptions::new()
-                .write(true)
-                .create(true)
-                .append(true)
-                .open(&homeout)
-            {
-                Ok(t) => {
-                    show_info!("ignoring input and appending output to '{}'", homeout_str);
-                    t
-                }
+            let homeout_str = homeout.to_string_lossy().into_owned();
+            match open(&homeout) {
+                Ok(t) => (t, homeout_str),
                 Err(e2) => {
-                    show_info!("failed to open '{}': {}", NOHUP_OUT, e1);
-                    show_info!("failed to open '{}': {}", homeout_str, e2);
-                    exit!(internal_failure_code)
+                    show_info!("failed to open '{}': {}", NOHUP_OUT, error_message(&e1));
+                    show_info!("failed to open '{}': {}", homeout_str, error_message(&e2));
+                    exit!(internal_failure_code())
                 }
             }
         }
@@ -0,0 +1,117 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! The CPU quota the cgroups of this process put on it, with cgroup v2 or
+//! the "cpu" controller of cgroup v1, which is what a container is usually
+//! limited with.
+
+// spell-checker:ignore (ToDO) cgroup cgroups mountinfo cfs
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The number of processors' worth of time this process's cgroups allow it,
+/// rounded to the nearest but at least 1; `None` if they don't limit it.
+pub fn cpu_quota() -> Option<usize> {
+    let cgroups = fs::read_to_string("/proc/self/cgroup").ok()?;
+    let mounts = fs::read_to_string("/proc/self/mountinfo").ok()?;
+
+    let mut quota: Option<f64> = None;
+    for line in cgroups.lines() {
+        // hierarchy-ID:controller-list:cgroup-path
+        let mut fields = line.splitn(3, ':');
+        let (controllers, path) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(_), Some(controllers), Some(path)) => (controllers, path),
+            _ => continue,
+        };
+        // cgroup v2 has the one hierarchy, with no controllers listed
+        let v2 = controllers.is_empty();
+        if !v2 && !controllers.split(',').any(|c| c == "cpu") {
+            continue;
+        }
+        let (mount_point, dir) = match cgroup_dir(&mounts, v2, path) {
+            Some(dirs) => dirs,
+            None => continue,
+        };
+        // the quota of any cgroup above it applies too
+        for dir in dir
+            .ancestors()
+            .take_while(|dir| dir.starts_with(&mount_point))
+        {
+            let dir_quota = if v2 { quota_v2(dir) } else { quota_v1(dir) };
+            if let Some(dir_quota) = dir_quota {
+                quota = Some(quota.map_or(dir_quota, |quota| quota.min(dir_quota)));
+            }
+        }
+    }
+    quota.map(|quota| (quota.round() as usize).max(1))
+}
+
+/// Where the hierarchy is mounted, and the directory of its cgroup `path`,
+/// from the mount table `mounts` has: the cgroup2 one, or the cgroup v1 one
+/// with the "cpu" controller.
+fn cgroup_dir(mounts: &str, v2: bool, path: &str) -> Option<(PathBuf, PathBuf)> {
+    for line in mounts.lines() {
+        // ID parent-ID major:minor root mount-point options [optional...] -
+        // filesystem-type source super-options
+        let mut parts = line.splitn(2, " - ");
+        let (mount, filesystem) = match (parts.next(), parts.next()) {
+            (Some(mount), Some(filesystem)) => (mount, filesystem),
+            _ => continue,
+        };
+        let mount: Vec<&str> = mount.split(' ').collect();
+        let filesystem: Vec<&str> = filesystem.split(' ').collect();
+        if mount.len() < 5 || filesystem.len() < 3 {
+            continue;
+        }
+        let matches = if v2 {
+            filesystem[0] == "cgroup2"
+        } else {
+            filesystem[0] == "cgroup" && filesystem[2].split(',').any(|o| o == "cpu")
+        };
+        if !matches {
+            continue;
+        }
+        // the mount may be of a cgroup below the root of the hierarchy
+        let (root, mount_point) = (mount[3], mount[4]);
+        let relative = match Path::new(path).strip_prefix(root) {
+            Ok(relative) => relative,
+            Err(_) => continue,
+        };
+        let mount_point = PathBuf::from(mount_point);
+        let dir = mount_point.join(relative);
+        return Some((mount_point, dir));
+    }
+    None
+}
+
+/// The quota cgroup v2 has in `dir`'s "cpu.max", "$MAX $PERIOD".
+fn quota_v2(dir: &Path) -> Option<f64> {
+    let max = fs::read_to_string(dir.join("cpu.max")).ok()?;
+    let mut fields = max.split_whitespace();
+    match (fields.next(), fields.next()) {
+        (Some(max), Some(period)) => ratio(max, period),
+        _ => None,
+    }
+}
+
+/// The quota cgroup v1 has in `dir`'s "cpu.cfs_quota_us" and
+/// "cpu.cfs_period_us".
+fn quota_v1(dir: &Path) -> Option<f64> {
+    let max = fs::read_to_string(dir.join("cpu.cfs_quota_us")).ok()?;
+    let period = fs::read_to_string(dir.join("cpu.cfs_period_us")).ok()?;
+    ratio(max.trim(), period.trim())
+}
+
+/// `max` over `period`, if `max` is a limit, which "max" and -1 aren't.
+fn ratio(max: &str, period: &str) -> Option<f64> {
+    let max: f64 = max.parse().ok()?;
+    let period: f64 = period.parse().ok()?;
+    if max > 0.0 && period > 0.0 {
+        Some(max / period)
+    } else {
+        None
+    }
+}
@@ -5,11 +5,14 @@
 //  * For the full copyright and license information, please view the LICENSE
 //  * file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) NPROCESSORS nprocs numstr threadstr sysconf
+// spell-checker:ignore (ToDO) NPROCESSORS nprocs numstr sysconf cgroup getaffinity
 
 #[macro_use]
 extern crate uucore;
 
+#[cfg(target_os = "linux")]
+mod cgroup;
+
 use clap::{App, Arg};
 use std::env;
 
@@ -34,10 +37,36 @@ fn get_usage() -> String {
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let ignore = match matches.value_of(OPT_IGNORE) {
+        Some(numstr) => match numstr.trim_start().parse::<usize>() {
+            Ok(num) => num,
+            Err(_) => {
+                show_info!("invalid number: '{}'", numstr);
+                return 1;
+            }
+        },
+        None => 0,
+    };
+
+    let cores = if matches.is_present(OPT_ALL) {
+        num_cpus_all()
+    } else {
+        // the OpenMP variables don't have an impact on --all
+        let limit = omp_threads("OMP_THREAD_LIMIT");
+        let cores = omp_threads("OMP_NUM_THREADS").unwrap_or_else(num_cpus_current);
+        limit.map_or(cores, |limit| cores.min(limit))
+    };
+
+    println!("{}", if cores > ignore { cores - ignore } else { 1 });
+    0
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .arg(
             Arg::with_name(OPT_ALL)
                 .short("")
@@ -51,40 +80,55 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .takes_value(true)
                 .help("ignore up to N cores"),
         )
-        .get_matches_from(args);
-
-    let mut ignore = match matches.value_of(OPT_IGNORE) {
-        Some(numstr) => match numstr.parse() {
-            Ok(num) => num,
-            Err(e) => {
-                show_error!("\"{}\" is not a valid number: {}", numstr, e);
-                return 1;
-            }
-        },
-        None => 0,
-    };
+}
 
-    if !matches.is_present(OPT_ALL) {
-        // OMP_NUM_THREADS doesn't have an impact on --all
-        ignore += match env::var("OMP_NUM_THREADS") {
-            Ok(threadstr) => threadstr.parse().unwrap_or(0),
-            Err(_) => 0,
-        };
+/// The number of threads the OpenMP environment variable `var` asks for, the
+/// first if it's a list; `None` if it isn't a positive number.
+fn omp_threads(var: &str) -> Option<usize> {
+    let value = env::var(var).ok()?;
+    let value = value.trim_start();
+    let end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let rest = value[end..].trim_start();
+    if !rest.is_empty() && !rest.starts_with(',') {
+        return None;
     }
+    value[..end].parse().ok().filter(|&threads| threads > 0)
+}
 
-    let mut cores = if matches.is_present(OPT_ALL) {
-        num_cpus_all()
-    } else {
-        num_cpus::get()
-    };
+/// The number of processors the process may run on, as its CPU affinity
+/// mask and the CPU quota of its cgroups allow.
+#[cfg(target_os = "linux")]
+fn num_cpus_current() -> usize {
+    let cores = affinity().unwrap_or_else(num_cpus_all);
+    match cgroup::cpu_quota() {
+        Some(quota) => cores.min(quota),
+        None => cores,
+    }
+}
 
-    if cores <= ignore {
-        cores = 1;
+#[cfg(target_os = "linux")]
+fn affinity() -> Option<usize> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) } != 0
+    {
+        return None;
+    }
+    let cores = (0..libc::CPU_SETSIZE as usize)
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+        .count();
+    if cores > 0 {
+        Some(cores)
     } else {
-        cores -= ignore;
+        None
     }
-    println!("{}", cores);
-    0
+}
+
+// Other platforms, num_cpus::get() directly.
+#[cfg(not(target_os = "linux"))]
+fn num_cpus_current() -> usize {
+    num_cpus::get()
 }
 
 #[cfg(any(
@@ -148,10 +148,28 @@ fn parse_options(args: &ArgMatches) -> Result<NumfmtOptions> {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let result =
+        parse_options(&matches).and_then(|options| match matches.values_of(options::NUMBER) {
+            Some(values) => handle_args(values, options),
+            None => handle_stdin(options),
+        });
+
+    match result {
+        Err(e) => {
+            std::io::stdout().flush().expect("error flushing stdout");
+            show_info!("{}", e);
+            1
+        }
+        _ => 0,
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .after_help(LONG_HELP)
         .setting(AppSettings::AllowNegativeNumbers)
         .arg(
@@ -205,20 +223,4 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .hide_default_value(true),
         )
         .arg(Arg::with_name(options::NUMBER).hidden(true).multiple(true))
-        .get_matches_from(args);
-
-    let result =
-        parse_options(&matches).and_then(|options| match matches.values_of(options::NUMBER) {
-            Some(values) => handle_args(values, options),
-            None => handle_stdin(options),
-        });
-
-    match result {
-        Err(e) => {
-            std::io::stdout().flush().expect("error flushing stdout");
-            show_info!("{}", e);
-            1
-        }
-        _ => 0,
-    }
 }
@@ -223,7 +223,47 @@ impl OdOptions {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let clap_opts = clap::App::new(executable!())
+    let clap_matches = uucore::help::get_matches_from(uu_app(), args.clone());
+
+    if clap_matches.is_present(options::VERSION) {
+        println!("{}", uucore::help::version(executable!(), VERSION));
+        return 0;
+    }
+
+    let od_options = match OdOptions::new(clap_matches, args) {
+        Err(s) => {
+            show_usage_error!("{}", s);
+            return 1;
+        }
+        Ok(o) => o,
+    };
+
+    let mut input_offset =
+        InputOffset::new(od_options.radix, od_options.skip_bytes, od_options.label);
+
+    let mut input = open_input_peek_reader(
+        &od_options.input_strings,
+        od_options.skip_bytes,
+        od_options.read_bytes,
+    );
+    let mut input_decoder = InputDecoder::new(
+        &mut input,
+        od_options.line_bytes,
+        PEEK_BUFFER_SIZE,
+        od_options.byte_order,
+    );
+
+    let output_info = OutputInfo::new(
+        od_options.line_bytes,
+        &od_options.formats[..],
+        od_options.output_duplicates,
+    );
+
+    odfunc(&mut input_offset, &mut input_decoder, &output_info)
+}
+
+pub fn uu_app<'a>() -> clap::App<'a, 'a> {
+    clap::App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
         .usage(USAGE)
@@ -449,47 +489,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             AppSettings::DontDelimitTrailingValues,
             AppSettings::DisableVersion,
             AppSettings::DeriveDisplayOrder,
-        ]);
-
-    let clap_matches = clap_opts
-        .clone() // Clone to reuse clap_otps to print help
-        .get_matches_from(args.clone());
-
-    if clap_matches.is_present(options::VERSION) {
-        println!("{} {}", executable!(), VERSION);
-        return 0;
-    }
-
-    let od_options = match OdOptions::new(clap_matches, args) {
-        Err(s) => {
-            show_usage_error!("{}", s);
-            return 1;
-        }
-        Ok(o) => o,
-    };
-
-    let mut input_offset =
-        InputOffset::new(od_options.radix, od_options.skip_bytes, od_options.label);
-
-    let mut input = open_input_peek_reader(
-        &od_options.input_strings,
-        od_options.skip_bytes,
-        od_options.read_bytes,
-    );
-    let mut input_decoder = InputDecoder::new(
-        &mut input,
-        od_options.line_bytes,
-        PEEK_BUFFER_SIZE,
-        od_options.byte_order,
-    );
-
-    let output_info = OutputInfo::new(
-        od_options.line_bytes,
-        &od_options.formats[..],
-        od_options.output_duplicates,
-    );
-
-    odfunc(&mut input_offset, &mut input_decoder, &output_info)
+        ])
 }
 
 /// Loops through the input line by line, calling print_bytes to take care of the output.
@@ -12,8 +12,7 @@ extern crate uucore;
 
 use clap::{App, Arg};
 use std::fs::File;
-use std::io::{stdin, BufRead, BufReader, Read};
-use std::iter::repeat;
+use std::io::{self, stdin, stdout, BufRead, BufReader, StdinLock, Write};
 use std::path::Path;
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -23,22 +22,48 @@ FILE, separated by TABs, to standard output.";
 mod options {
     pub const DELIMITER: &str = "delimiters";
     pub const SERIAL: &str = "serial";
+    pub const ZERO_TERMINATED: &str = "zero-terminated";
     pub const FILE: &str = "file";
 }
 
-// Wraps BufReader and stdin
-fn read_line<R: Read>(
-    reader: Option<&mut BufReader<R>>,
-    buf: &mut String,
-) -> std::io::Result<usize> {
+// Wraps BufReader and stdin: every "-" operand reads from the same stdin
+fn read_until<R: BufRead>(
+    reader: Option<&mut R>,
+    stdin: &mut StdinLock,
+    line_ending: u8,
+    buf: &mut Vec<u8>,
+) -> io::Result<usize> {
     match reader {
-        Some(reader) => reader.read_line(buf),
-        None => stdin().read_line(buf),
+        Some(reader) => reader.read_until(line_ending, buf),
+        None => stdin.read_until(line_ending, buf),
     }
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app(), args);
+
+    let serial = matches.is_present(options::SERIAL);
+    let line_ending = if matches.is_present(options::ZERO_TERMINATED) {
+        b'\0'
+    } else {
+        b'\n'
+    };
+    let delimiters = match parse_delimiters(matches.value_of(options::DELIMITER).unwrap()) {
+        Ok(delimiters) => delimiters,
+        Err(e) => crash!(1, "{}", e),
+    };
+    let files = matches
+        .values_of(options::FILE)
+        .unwrap()
+        .map(|s| s.to_owned())
+        .collect();
+    paste(files, serial, &delimiters, line_ending);
+
+    0
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
         .arg(
@@ -56,27 +81,21 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .default_value("\t")
                 .hide_default_value(true),
         )
+        .arg(
+            Arg::with_name(options::ZERO_TERMINATED)
+                .long(options::ZERO_TERMINATED)
+                .short("z")
+                .help("line delimiter is NUL, not newline"),
+        )
         .arg(
             Arg::with_name(options::FILE)
                 .value_name("FILE")
                 .multiple(true)
                 .default_value("-"),
         )
-        .get_matches_from(args);
-
-    let serial = matches.is_present(options::SERIAL);
-    let delimiters = matches.value_of(options::DELIMITER).unwrap().to_owned();
-    let files = matches
-        .values_of(options::FILE)
-        .unwrap()
-        .map(|s| s.to_owned())
-        .collect();
-    paste(files, serial, delimiters);
-
-    0
 }
 
-fn paste(filenames: Vec<String>, serial: bool, delimiters: String) {
+fn paste(filenames: Vec<String>, serial: bool, delimiters: &[Vec<u8>], line_ending: u8) {
     let mut files: Vec<_> = filenames
         .into_iter()
         .map(|name| {
@@ -89,65 +108,107 @@ fn paste(filenames: Vec<String>, serial: bool, delimiters: String) {
         })
         .collect();
 
-    let delimiters: Vec<String> = unescape(delimiters)
-        .chars()
-        .map(|x| x.to_string())
-        .collect();
-    let mut delim_count = 0;
+    let stdin = stdin();
+    let mut stdin = stdin.lock();
+    let stdout = stdout();
+    let mut stdout = stdout.lock();
+    let mut output = Vec::new();
+    let mut line = Vec::new();
 
     if serial {
         for file in &mut files {
-            let mut output = String::new();
+            output.clear();
+            let mut delim_count = 0;
             loop {
-                let mut line = String::new();
-                match read_line(file.as_mut(), &mut line) {
+                line.clear();
+                match read_until(file.as_mut(), &mut stdin, line_ending, &mut line) {
                     Ok(0) => break,
                     Ok(_) => {
-                        output.push_str(line.trim_end());
-                        output.push_str(&delimiters[delim_count % delimiters.len()]);
+                        if delim_count > 0 {
+                            output.extend_from_slice(
+                                &delimiters[(delim_count - 1) % delimiters.len()],
+                            );
+                        }
+                        output.extend_from_slice(trim_line_ending(&line, line_ending));
                     }
                     Err(e) => crash!(1, "{}", e.to_string()),
                 }
                 delim_count += 1;
             }
-            println!("{}", &output[..output.len() - 1]);
+            output.push(line_ending);
+            crash_if_err!(1, stdout.write_all(&output));
         }
     } else {
-        let mut eof: Vec<bool> = repeat(false).take(files.len()).collect();
+        let mut eof = vec![false; files.len()];
         loop {
-            let mut output = String::new();
+            output.clear();
             let mut eof_count = 0;
             for (i, file) in files.iter_mut().enumerate() {
+                if i > 0 {
+                    output.extend_from_slice(&delimiters[(i - 1) % delimiters.len()]);
+                }
                 if eof[i] {
                     eof_count += 1;
                 } else {
-                    let mut line = String::new();
-                    match read_line(file.as_mut(), &mut line) {
+                    line.clear();
+                    match read_until(file.as_mut(), &mut stdin, line_ending, &mut line) {
                         Ok(0) => {
                             eof[i] = true;
                             eof_count += 1;
                         }
-                        Ok(_) => output.push_str(line.trim_end()),
+                        Ok(_) => output.extend_from_slice(trim_line_ending(&line, line_ending)),
                         Err(e) => crash!(1, "{}", e.to_string()),
                     }
                 }
-                output.push_str(&delimiters[delim_count % delimiters.len()]);
-                delim_count += 1;
             }
             if files.len() == eof_count {
                 break;
             }
-            println!("{}", &output[..output.len() - 1]);
-            delim_count = 0;
+            output.push(line_ending);
+            crash_if_err!(1, stdout.write_all(&output));
         }
     }
 }
 
-// Unescape all special characters
-// TODO: this will need work to conform to GNU implementation
-fn unescape(s: String) -> String {
-    s.replace("\\n", "\n")
-        .replace("\\t", "\t")
-        .replace("\\\\", "\\")
-        .replace("\\", "")
+fn trim_line_ending(line: &[u8], line_ending: u8) -> &[u8] {
+    match line.last() {
+        Some(&c) if c == line_ending => &line[..line.len() - 1],
+        _ => line,
+    }
+}
+
+/// Split the delimiter LIST into the delimiters used in turn, handling the
+/// same escapes as GNU paste: `\0` is an empty delimiter, `\b`, `\f`, `\n`,
+/// `\r`, `\t`, `\v` and `\\` are the usual characters, and any other escaped
+/// character stands for itself.
+fn parse_delimiters(list: &str) -> Result<Vec<Vec<u8>>, String> {
+    let mut delimiters = Vec::new();
+    let mut chars = list.chars();
+    while let Some(c) = chars.next() {
+        let delimiter = if c == '\\' {
+            match chars.next() {
+                Some('0') => String::new(),
+                Some('b') => "\x08".to_owned(),
+                Some('f') => "\x0c".to_owned(),
+                Some('n') => "\n".to_owned(),
+                Some('r') => "\r".to_owned(),
+                Some('t') => "\t".to_owned(),
+                Some('v') => "\x0b".to_owned(),
+                Some(c) => c.to_string(),
+                None => {
+                    return Err(format!(
+                        "delimiter list ends with an unescaped backslash: {}",
+                        list
+                    ))
+                }
+            }
+        } else {
+            c.to_string()
+        };
+        delimiters.push(delimiter.into_bytes());
+    }
+    if delimiters.is_empty() {
+        delimiters.push(Vec::new());
+    }
+    Ok(delimiters)
 }
@@ -1,5 +1,3 @@
-#![allow(unused_must_use)] // because we of writeln!
-
 //  * This file is part of the uutils coreutils package.
 //  *
 //  * (c) Inokentiy Babushkin <inokentiy.babushkin@googlemail.com>
@@ -13,8 +11,10 @@
 extern crate uucore;
 
 use clap::{App, Arg};
+use std::ffi::CString;
 use std::fs;
-use std::io::{ErrorKind, Write};
+use std::io::ErrorKind;
+use uucore::error_message;
 
 // operating mode
 enum Mode {
@@ -46,27 +46,7 @@ fn get_usage() -> String {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .arg(
-            Arg::with_name(options::POSIX)
-                .short("p")
-                .help("check for most POSIX systems"),
-        )
-        .arg(
-            Arg::with_name(options::POSIX_SPECIAL)
-                .short("P")
-                .help(r#"check for empty names and leading "-""#),
-        )
-        .arg(
-            Arg::with_name(options::PORTABILITY)
-                .long(options::PORTABILITY)
-                .help("check for all POSIX systems (equivalent to -p -P)"),
-        )
-        .arg(Arg::with_name(options::PATH).hidden(true).multiple(true))
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
 
     // set working mode
     let is_posix = matches.values_of(options::POSIX).is_some();
@@ -93,14 +73,8 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     };
 
     if res {
-        // free strings are path operands
-        // FIXME: TCS, seems inefficient and overly verbose (?)
         for p in paths.unwrap() {
-            let mut path = Vec::new();
-            for path_segment in p.split('/') {
-                path.push(path_segment.to_string());
-            }
-            res &= check_path(&mode, &path);
+            res &= check_path(&mode, p);
         }
     }
 
@@ -112,141 +86,175 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     }
 }
 
-// check a path, given as a slice of it's components and an operating mode
-fn check_path(mode: &Mode, path: &[String]) -> bool {
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(
+            Arg::with_name(options::POSIX)
+                .short("p")
+                .help("check for most POSIX systems"),
+        )
+        .arg(
+            Arg::with_name(options::POSIX_SPECIAL)
+                .short("P")
+                .help(r#"check for empty names and leading "-""#),
+        )
+        .arg(
+            Arg::with_name(options::PORTABILITY)
+                .long(options::PORTABILITY)
+                .help("check for all POSIX systems (equivalent to -p -P)"),
+        )
+        .arg(Arg::with_name(options::PATH).hidden(true).multiple(true))
+}
+
+// check a path with an operating mode
+fn check_path(mode: &Mode, path: &str) -> bool {
     match *mode {
-        Mode::Basic => check_basic(&path),
-        Mode::Extra => check_default(&path) && check_extra(&path),
-        Mode::Both => check_basic(&path) && check_extra(&path),
-        _ => check_default(&path),
+        Mode::Basic => check_basic(path),
+        Mode::Extra => check_extra(path) && check_default(path),
+        Mode::Both => check_extra(path) && check_basic(path),
+        _ => check_default(path),
     }
 }
 
-// check a path in basic compatibility mode
-fn check_basic(path: &[String]) -> bool {
-    let joined_path = path.join("/");
-    let total_len = joined_path.len();
-    // path length
-    if total_len > POSIX_PATH_MAX {
-        writeln!(
-            &mut std::io::stderr(),
-            "limit {} exceeded by length {} of file name {}",
-            POSIX_PATH_MAX,
-            total_len,
-            joined_path
-        );
+// the non-empty components of a path, with their offsets in it
+fn components(path: &str) -> impl Iterator<Item = (usize, &str)> {
+    path.split('/')
+        .scan(0, |start, component| {
+            let offset = *start;
+            *start += component.len() + 1;
+            Some((offset, component))
+        })
+        .filter(|(_, component)| !component.is_empty())
+}
+
+// check a path in basic compatibility mode, against the POSIX minimums
+fn check_basic(path: &str) -> bool {
+    if path.is_empty() {
+        show_error!("empty file name");
         return false;
-    } else if total_len == 0 {
-        writeln!(&mut std::io::stderr(), "empty file name");
+    }
+    if !check_portable_chars(path) {
         return false;
     }
-    // components: character portability and length
-    for p in path {
-        let component_len = p.len();
-        if component_len > POSIX_NAME_MAX {
-            writeln!(
-                &mut std::io::stderr(),
-                "limit {} exceeded by length {} of file name component '{}'",
-                POSIX_NAME_MAX,
-                component_len,
-                p
-            );
-            return false;
-        }
-        if !check_portable_chars(&p) {
-            return false;
-        }
+    // the limits count the terminating NUL
+    if path.len() >= POSIX_PATH_MAX {
+        show_error!(
+            "limit {} exceeded by length {} of file name '{}'",
+            POSIX_PATH_MAX - 1,
+            path.len(),
+            path
+        );
+        return false;
     }
-    // permission checks
-    check_searchable(&joined_path)
+    components(path).all(|(_, component)| check_component_len(component, POSIX_NAME_MAX))
 }
 
 // check a path in extra compatibility mode
-fn check_extra(path: &[String]) -> bool {
+fn check_extra(path: &str) -> bool {
     // components: leading hyphens
-    for p in path {
-        if !no_leading_hyphen(&p) {
-            writeln!(
-                &mut std::io::stderr(),
-                "leading hyphen in file name component '{}'",
-                p
-            );
-            return false;
-        }
+    if components(path).any(|(_, component)| component.starts_with('-')) {
+        show_error!("leading '-' in a component of file name '{}'", path);
+        return false;
     }
     // path length
-    if path.join("/").is_empty() {
-        writeln!(&mut std::io::stderr(), "empty file name");
+    if path.is_empty() {
+        show_error!("empty file name");
         return false;
     }
     true
 }
 
 // check a path in default mode (using the file system)
-fn check_default(path: &[String]) -> bool {
-    let joined_path = path.join("/");
-    let total_len = joined_path.len();
-    // path length
-    if total_len > libc::PATH_MAX as usize {
-        writeln!(
-            &mut std::io::stderr(),
-            "limit {} exceeded by length {} of file name '{}'",
-            libc::PATH_MAX,
-            total_len,
-            joined_path
-        );
-        return false;
+fn check_default(path: &str) -> bool {
+    // problems with the path itself, like a directory that isn't searchable;
+    // a file that exists needs no length checks
+    match fs::symlink_metadata(path) {
+        Ok(_) => return true,
+        Err(e) => {
+            if e.kind() != ErrorKind::NotFound {
+                show_error!("{}: {}", path, error_message(&e));
+                return false;
+            }
+        }
     }
-    // components: length
-    for p in path {
-        let component_len = p.len();
-        if component_len > libc::FILENAME_MAX as usize {
-            writeln!(
-                &mut std::io::stderr(),
-                "limit {} exceeded by length {} of file name component '{}'",
-                libc::FILENAME_MAX,
-                component_len,
-                p
-            );
-            return false;
+
+    // path length, limited by the file system of the root or working directory
+    if path.len() >= POSIX_PATH_MAX {
+        let dir = if path.starts_with('/') { "/" } else { "." };
+        if let Some(path_max) = path_conf(dir, libc::_PC_PATH_MAX) {
+            if path.len() >= path_max {
+                show_error!(
+                    "limit {} exceeded by length {} of file name '{}'",
+                    path_max - 1,
+                    path.len(),
+                    path
+                );
+                return false;
+            }
         }
     }
-    // permission checks
-    check_searchable(&joined_path)
-}
 
-// check whether a path is or if other problems arise
-fn check_searchable(path: &str) -> bool {
-    // we use lstat, just like the original implementation
-    match fs::symlink_metadata(path) {
-        Ok(_) => true,
-        Err(e) => {
-            if e.kind() == ErrorKind::NotFound {
-                true
-            } else {
-                writeln!(&mut std::io::stderr(), "{}", e);
-                false
+    // components: length, limited by the file system of the directory each
+    // is in, or of its closest existing ancestor
+    if components(path).all(|(_, component)| component.len() <= POSIX_NAME_MAX) {
+        return true;
+    }
+    let mut name_max = Some(POSIX_NAME_MAX);
+    let mut dir_exists = true;
+    for (offset, component) in components(path) {
+        if dir_exists {
+            let dir = if offset == 0 { "." } else { &path[..offset] };
+            match fs::metadata(dir) {
+                Ok(_) => name_max = path_conf(dir, libc::_PC_NAME_MAX),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => dir_exists = false,
+                Err(e) => {
+                    show_error!("{}: {}", dir, error_message(&e));
+                    return false;
+                }
+            }
+        }
+        if let Some(name_max) = name_max {
+            if !check_component_len(component, name_max) {
+                return false;
             }
         }
     }
+    true
+}
+
+// check the length of a path segment
+fn check_component_len(path_segment: &str, name_max: usize) -> bool {
+    if path_segment.len() > name_max {
+        show_error!(
+            "limit {} exceeded by length {} of file name component '{}'",
+            name_max,
+            path_segment.len(),
+            path_segment
+        );
+        return false;
+    }
+    true
 }
 
-// check for a hyphen at the beginning of a path segment
-fn no_leading_hyphen(path_segment: &str) -> bool {
-    !path_segment.starts_with('-')
+// a limit of the file system `dir` is on, or `None` when there is none
+fn path_conf(dir: &str, name: libc::c_int) -> Option<usize> {
+    let dir = CString::new(dir).ok()?;
+    let limit = unsafe { libc::pathconf(dir.as_ptr(), name) };
+    if limit < 0 {
+        None
+    } else {
+        Some(limit as usize)
+    }
 }
 
-// check whether a path segment contains only valid (read: portable) characters
-fn check_portable_chars(path_segment: &str) -> bool {
-    let valid_str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789._-".to_string();
-    for ch in path_segment.chars() {
+// check whether a path contains only valid (read: portable) characters
+fn check_portable_chars(path: &str) -> bool {
+    let valid_str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789._-/";
+    for ch in path.chars() {
         if !valid_str.contains(ch) {
-            writeln!(
-                &mut std::io::stderr(),
-                "nonportable character '{}' in file name component '{}'",
-                ch,
-                path_segment
-            );
+            show_error!("nonportable character '{}' in file name '{}'", ch, path);
             return false;
         }
     }
@@ -5,17 +5,17 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) BUFSIZE gecos fullname, mesg iobuf
+// spell-checker:ignore (ToDO) gecos fullname, mesg
 
 #[macro_use]
 extern crate uucore;
+use uucore::coreopts::CoreOptions;
 use uucore::entries::{Locate, Passwd};
 use uucore::libc::S_IWGRP;
 use uucore::utmpx::{self, time, Utmpx};
 
 use std::io::prelude::*;
-use std::io::BufReader;
-use std::io::Result as IOResult;
+use std::io::{self, Result as IOResult};
 
 use std::fs::File;
 use std::os::unix::fs::MetadataExt;
@@ -24,60 +24,13 @@ use std::path::PathBuf;
 
 static SYNTAX: &str = "[OPTION]... [USER]...";
 static SUMMARY: &str = "A lightweight 'finger' program;  print user information.";
-
-const BUFSIZE: usize = 1024;
+static LONG_HELP: &str = "
+The utmp file will be /var/run/utmp";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let long_help = &format!(
-        "
-  -l              produce long format output for the specified USERs
-  -b              omit the user's home directory and shell in long format
-  -h              omit the user's project file in long format
-  -p              omit the user's plan file in long format
-  -s              do short format output, this is the default
-  -f              omit the line of column headings in short format
-  -w              omit the user's full name in short format
-  -i              omit the user's full name and remote host in short format
-  -q              omit the user's full name, remote host and idle time
-                  in short format
-      --help     display this help and exit
-      --version  output version information and exit
-
-The utmp file will be {}",
-        utmpx::DEFAULT_FILE
-    );
-    let mut opts = app!(SYNTAX, SUMMARY, &long_help);
-    opts.optflag(
-        "l",
-        "",
-        "produce long format output for the specified USERs",
-    );
-    opts.optflag(
-        "b",
-        "",
-        "omit the user's home directory and shell in long format",
-    );
-    opts.optflag("h", "", "omit the user's project file in long format");
-    opts.optflag("p", "", "omit the user's plan file in long format");
-    opts.optflag("s", "", "do short format output, this is the default");
-    opts.optflag("f", "", "omit the line of column headings in short format");
-    opts.optflag("w", "", "omit the user's full name in short format");
-    opts.optflag(
-        "i",
-        "",
-        "omit the user's full name and remote host in short format",
-    );
-    opts.optflag(
-        "q",
-        "",
-        "omit the user's full name, remote host and idle time in short format",
-    );
-    opts.optflag("", "help", "display this help and exit");
-    opts.optflag("", "version", "output version information and exit");
-
-    let matches = opts.parse(args);
+    let matches = uu_app().parse(args);
 
     // If true, display the hours:minutes since each user has touched
     // the keyboard, or blank if within the last minute, or days followed
@@ -147,6 +100,38 @@ The utmp file will be {}",
     }
 }
 
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut opts = app!(SYNTAX, SUMMARY, LONG_HELP);
+    opts.optflag(
+        "l",
+        "",
+        "produce long format output for the specified USERs",
+    );
+    opts.optflag(
+        "b",
+        "",
+        "omit the user's home directory and shell in long format",
+    );
+    opts.optflag("h", "", "omit the user's project file in long format");
+    opts.optflag("p", "", "omit the user's plan file in long format");
+    opts.optflag("s", "", "do short format output, this is the default");
+    opts.optflag("f", "", "omit the line of column headings in short format");
+    opts.optflag("w", "", "omit the user's full name in short format");
+    opts.optflag(
+        "i",
+        "",
+        "omit the user's full name and remote host in short format",
+    );
+    opts.optflag(
+        "q",
+        "",
+        "omit the user's full name, remote host and idle time in short format",
+    );
+    opts.optflag("", "help", "display this help and exit");
+    opts.optflag("", "version", "output version information and exit");
+    opts
+}
+
 struct Pinky {
     include_idle: bool,
     include_heading: bool,
@@ -176,6 +161,14 @@ impl Capitalize for str {
     }
 }
 
+/// The user's name as the GECOS field has it, up to the first comma, with
+/// any '&' standing for the capitalized login name.
+fn full_name(pw: &Passwd) -> String {
+    let gecos = pw.user_info();
+    let name = gecos.split(',').next().unwrap_or_default();
+    name.replace("&", &pw.name().capitalize())
+}
+
 fn idle_string(when: i64) -> String {
     thread_local! {
         static NOW: time::Tm = time::now()
@@ -228,13 +221,9 @@ impl Pinky {
 
         if self.include_fullname {
             if let Ok(pw) = Passwd::locate(ut.user().as_ref()) {
-                let mut gecos = pw.user_info().into_owned();
-                if let Some(n) = gecos.find(',') {
-                    gecos.truncate(n + 1);
-                }
-                print!(" {:<19.19}", gecos.replace("&", &pw.name().capitalize()));
+                print!(" {:<19.19}", full_name(&pw));
             } else {
-                print!(" {:19}", "        ???");
+                print!(" {:>19}", "        ???");
             }
         }
 
@@ -302,7 +291,7 @@ impl Pinky {
         for u in &self.names {
             print!("Login name: {:<28}In real life: ", u);
             if let Ok(pw) = Passwd::locate(u.as_str()) {
-                println!(" {}", pw.user_info().replace("&", &pw.name().capitalize()));
+                println!(" {}", full_name(&pw));
                 if self.include_home_and_shell {
                     print!("Directory: {:<29}", pw.user_dir());
                     println!("Shell:  {}", pw.user_shell());
@@ -332,14 +321,7 @@ impl Pinky {
     }
 }
 
-fn read_to_console<F: Read>(f: F) {
-    let mut reader = BufReader::new(f);
-    let mut iobuf = [0_u8; BUFSIZE];
-    while let Ok(n) = reader.read(&mut iobuf) {
-        if n == 0 {
-            break;
-        }
-        let s = String::from_utf8_lossy(&iobuf);
-        print!("{}", s);
-    }
+fn read_to_console<F: Read>(mut f: F) {
+    let stdout = io::stdout();
+    let _ = io::copy(&mut f, &mut stdout.lock());
 }
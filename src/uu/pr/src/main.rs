@@ -0,0 +1 @@
+uucore_procs::main!(uu_pr); // spell-checker:ignore procs uucore
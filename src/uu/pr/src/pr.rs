@@ -0,0 +1,553 @@
+//  * This file is part of the uutils coreutils package.
+//  *
+//  * For the full copyright and license information, please view the LICENSE
+//  * file that was distributed with this source code.
+
+// spell-checker:ignore (ToDO) lhs rhs
+
+#[macro_use]
+extern crate uucore;
+
+use chrono::{DateTime, Local};
+use clap::{App, Arg};
+use std::fs::File;
+use std::io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Read, Write};
+
+static NAME: &str = "pr";
+static VERSION: &str = env!("CARGO_PKG_VERSION");
+static ABOUT: &str = "Paginate FILE(s) for printing.
+
+With no FILE, or when FILE is -, read standard input.";
+
+const DEFAULT_PAGE_LENGTH: usize = 66;
+const DEFAULT_LINE_WIDTH: usize = 72;
+const DEFAULT_NUMBER_WIDTH: usize = 5;
+const DEFAULT_NUMBER_SEPARATOR: &str = "\t";
+const HEADER_LINES: usize = 5;
+const TRAILER_LINES: usize = 5;
+/// With `-F` the header is shorter and there is no trailer.
+const FORM_FEED_HEADER_LINES: usize = 3;
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+const FORM_FEED: u8 = b'\x0c';
+
+mod options {
+    pub const PAGES: &str = "pages";
+    pub const HEADER: &str = "header";
+    pub const DOUBLE_SPACE: &str = "double-space";
+    pub const FORM_FEED: &str = "form-feed";
+    pub const FORM_FEED_SHORT: &str = "form-feed-short";
+    pub const LENGTH: &str = "length";
+    pub const NUMBER_LINES: &str = "number-lines";
+    pub const FIRST_LINE_NUMBER: &str = "first-line-number";
+    pub const INDENT: &str = "indent";
+    pub const OMIT_HEADER: &str = "omit-header";
+    pub const WIDTH: &str = "width";
+    pub const FILES: &str = "files";
+}
+
+/// How body lines are numbered with `-n`.
+struct NumberingMode {
+    separator: String,
+    width: usize,
+    first_number: usize,
+}
+
+struct Settings {
+    header: Option<String>,
+    page_length: usize,
+    line_width: usize,
+    form_feed: bool,
+    double_space: bool,
+    omit_header: bool,
+    offset: usize,
+    numbering: Option<NumberingMode>,
+    first_page: usize,
+    last_page: Option<usize>,
+}
+
+impl Settings {
+    /// Number of lines available for input text on each page.
+    fn body_lines(&self) -> usize {
+        if self.omit_header {
+            self.page_length
+        } else {
+            self.page_length - extremity_lines(self.form_feed)
+        }
+    }
+}
+
+/// Number of lines taken by the page header and trailer together.
+fn extremity_lines(form_feed: bool) -> usize {
+    if form_feed {
+        FORM_FEED_HEADER_LINES
+    } else {
+        HEADER_LINES + TRAILER_LINES
+    }
+}
+
+fn get_usage() -> String {
+    format!("{0} [OPTION]... [FILE]...", executable!())
+}
+
+pub fn uumain(args: impl uucore::Args) -> i32 {
+    let usage = get_usage();
+    let args = preprocess_args(args.collect_str());
+
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let page_length = match parse_number(matches.value_of(options::LENGTH), DEFAULT_PAGE_LENGTH) {
+        Ok(n) => n,
+        Err(s) => crash!(1, "invalid page length '{}'", s),
+    };
+    let line_width = match parse_number(matches.value_of(options::WIDTH), DEFAULT_LINE_WIDTH) {
+        Ok(n) if n > 0 => n,
+        Ok(_) => crash!(1, "invalid page width '0'"),
+        Err(s) => crash!(1, "invalid page width '{}'", s),
+    };
+    let offset = match parse_number(matches.value_of(options::INDENT), 0) {
+        Ok(n) => n,
+        Err(s) => crash!(1, "'-o MARGIN' invalid line offset: '{}'", s),
+    };
+    let first_number = match parse_number(matches.value_of(options::FIRST_LINE_NUMBER), 1) {
+        Ok(n) => n,
+        Err(s) => crash!(1, "'-N NUMBER' invalid starting line number: '{}'", s),
+    };
+    let numbering = if matches.is_present(options::NUMBER_LINES) {
+        match parse_numbering(matches.value_of(options::NUMBER_LINES).unwrap_or(""), first_number) {
+            Ok(mode) => Some(mode),
+            Err(s) => crash!(
+                1,
+                "'-n' extra characters or invalid number in the argument: '{}'",
+                s
+            ),
+        }
+    } else {
+        None
+    };
+    let (first_page, last_page) = match matches.value_of(options::PAGES) {
+        Some(range) => match parse_page_range(range) {
+            Some(pages) => pages,
+            None => crash!(1, "invalid page range '{}'", range),
+        },
+        None => (1, None),
+    };
+
+    let form_feed =
+        matches.is_present(options::FORM_FEED) || matches.is_present(options::FORM_FEED_SHORT);
+
+    let settings = Settings {
+        header: matches.value_of(options::HEADER).map(String::from),
+        page_length,
+        line_width,
+        form_feed,
+        double_space: matches.is_present(options::DOUBLE_SPACE),
+        omit_header: matches.is_present(options::OMIT_HEADER)
+            || page_length <= extremity_lines(form_feed),
+        offset,
+        numbering,
+        first_page,
+        last_page,
+    };
+
+    let files: Vec<String> = match matches.values_of(options::FILES) {
+        Some(v) => v.map(|v| v.to_owned()).collect(),
+        None => vec!["-".to_owned()],
+    };
+
+    let mut exit_code = 0;
+    let stdout = stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    for path in &files {
+        if let Err(e) = pr(path, &settings, &mut out) {
+            show_error!("{}: {}", path, e);
+            exit_code = 1;
+        }
+    }
+    if let Err(e) = out.flush() {
+        show_error!("write error: {}", e);
+        exit_code = 1;
+    }
+
+    exit_code
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .name(NAME)
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(
+            Arg::with_name(options::PAGES)
+                .long(options::PAGES)
+                .takes_value(true)
+                .value_name("FIRST_PAGE[:LAST_PAGE]")
+                .help("begin [stop] printing with page FIRST_[LAST_]PAGE (also +FIRST_PAGE[:LAST_PAGE])"),
+        )
+        .arg(
+            Arg::with_name(options::HEADER)
+                .short("h")
+                .long(options::HEADER)
+                .takes_value(true)
+                .value_name("HEADER")
+                .help("use a centered HEADER instead of filename in page header"),
+        )
+        .arg(
+            Arg::with_name(options::DOUBLE_SPACE)
+                .short("d")
+                .long(options::DOUBLE_SPACE)
+                .help("double space the output"),
+        )
+        .arg(
+            Arg::with_name(options::FORM_FEED)
+                .short("F")
+                .long(options::FORM_FEED)
+                .help(
+                    "use form feeds instead of newlines to separate pages \
+                     (by a 3-line page header with -F or a 5-line header \
+                     and trailer without -F)",
+                ),
+        )
+        .arg(
+            Arg::with_name(options::FORM_FEED_SHORT)
+                .short("f")
+                .help("same as -F"),
+        )
+        .arg(
+            Arg::with_name(options::LENGTH)
+                .short("l")
+                .long(options::LENGTH)
+                .takes_value(true)
+                .value_name("PAGE_LENGTH")
+                .help(
+                    "set the page length to PAGE_LENGTH (66) lines \
+                     (default number of lines of text 56, and with -F 63)",
+                ),
+        )
+        .arg(
+            Arg::with_name(options::NUMBER_LINES)
+                .short("n")
+                .long(options::NUMBER_LINES)
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .value_name("SEP[DIGITS]")
+                .help(
+                    "number lines, use DIGITS (5) digits, then SEP (TAB); \
+                     default counting starts with 1st line of input file",
+                ),
+        )
+        .arg(
+            Arg::with_name(options::FIRST_LINE_NUMBER)
+                .short("N")
+                .long(options::FIRST_LINE_NUMBER)
+                .takes_value(true)
+                .value_name("NUMBER")
+                .help("start counting with NUMBER at 1st line of first page printed"),
+        )
+        .arg(
+            Arg::with_name(options::INDENT)
+                .short("o")
+                .long(options::INDENT)
+                .takes_value(true)
+                .value_name("MARGIN")
+                .help("offset each line with MARGIN (zero) spaces"),
+        )
+        .arg(
+            Arg::with_name(options::OMIT_HEADER)
+                .short("t")
+                .long(options::OMIT_HEADER)
+                .help("omit page headers and trailers; implied if PAGE_LENGTH <= 10"),
+        )
+        .arg(
+            Arg::with_name(options::WIDTH)
+                .short("w")
+                .long(options::WIDTH)
+                .takes_value(true)
+                .value_name("PAGE_WIDTH")
+                .help("set page width to PAGE_WIDTH (72) characters for the page header"),
+        )
+        .arg(Arg::with_name(options::FILES).multiple(true).hidden(true))
+}
+
+/// Rewrite the argument forms that clap cannot express: the obsolete
+/// `+FIRST[:LAST]` page selection and `-n` with an attached `SEP[DIGITS]` value.
+/// The values of other options are left alone, whatever they look like.
+fn preprocess_args(args: Vec<String>) -> Vec<String> {
+    let mut result = Vec::with_capacity(args.len());
+    let mut iter = args.into_iter();
+    if let Some(arg0) = iter.next() {
+        result.push(arg0);
+    }
+    let mut options_done = false;
+    let mut value_next = false;
+    for arg in iter {
+        if options_done || value_next {
+            value_next = false;
+            result.push(arg);
+        } else if arg == "--" {
+            options_done = true;
+            result.push(arg);
+        } else if arg.len() > 1 && arg.starts_with('+') {
+            result.push(format!("--{}={}", options::PAGES, &arg[1..]));
+        } else if arg.len() > 2 && arg.starts_with("-n") {
+            result.push(format!("--{}={}", options::NUMBER_LINES, &arg[2..]));
+        } else {
+            value_next = takes_separate_value(&arg);
+            result.push(arg);
+        }
+    }
+    result
+}
+
+/// Whether `arg` is an option whose value is the next argument, like
+/// `-h` in `-h TITLE` or `-dl` in `-dl 20`, rather than attached to it.
+fn takes_separate_value(arg: &str) -> bool {
+    if arg.starts_with("--") {
+        let long = &arg[2..];
+        !long.contains('=')
+            && [
+                options::PAGES,
+                options::HEADER,
+                options::LENGTH,
+                options::FIRST_LINE_NUMBER,
+                options::INDENT,
+                options::WIDTH,
+            ]
+            .contains(&long)
+    } else if arg.starts_with('-') {
+        // a short option with a value takes the rest of the argument, if any
+        match arg[1..].find(|c| "hlNow".contains(c)) {
+            Some(i) => i + 2 == arg.len(),
+            None => false,
+        }
+    } else {
+        false
+    }
+}
+
+fn parse_number(value: Option<&str>, default: usize) -> Result<usize, String> {
+    match value {
+        Some(s) => s.parse::<usize>().map_err(|_| s.to_owned()),
+        None => Ok(default),
+    }
+}
+
+/// Parse the `SEP[DIGITS]` argument of `-n`, where SEP is any single non-digit character.
+fn parse_numbering(value: &str, first_number: usize) -> Result<NumberingMode, String> {
+    let (separator, digits) = match value.chars().next() {
+        Some(c) if !c.is_ascii_digit() => (c.to_string(), &value[c.len_utf8()..]),
+        _ => (DEFAULT_NUMBER_SEPARATOR.to_owned(), value),
+    };
+    let width = if digits.is_empty() {
+        DEFAULT_NUMBER_WIDTH
+    } else {
+        match digits.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => return Err(digits.to_owned()),
+        }
+    };
+    Ok(NumberingMode {
+        separator,
+        width,
+        first_number,
+    })
+}
+
+/// Parse `FIRST[:LAST]`, returning `None` for an empty, zero, or inverted range.
+fn parse_page_range(range: &str) -> Option<(usize, Option<usize>)> {
+    let mut parts = range.splitn(2, ':');
+    let first = parts.next()?.parse::<usize>().ok()?;
+    let last = match parts.next() {
+        Some(s) => Some(s.parse::<usize>().ok()?),
+        None => None,
+    };
+    if first == 0 || last.map_or(false, |last| last < first) {
+        return None;
+    }
+    Some((first, last))
+}
+
+fn pr<W: Write>(path: &str, settings: &Settings, out: &mut W) -> io::Result<()> {
+    let (reader, date): (Box<dyn Read>, DateTime<Local>) = if path == "-" {
+        (Box::new(stdin()), Local::now())
+    } else {
+        let file = File::open(path)?;
+        let date = file.metadata()?.modified()?.into();
+        (Box::new(file), date)
+    };
+    let file_text = match settings.header {
+        Some(ref header) => header.clone(),
+        None if path == "-" => String::new(),
+        None => path.to_owned(),
+    };
+    let mut pager = Pager {
+        out,
+        settings,
+        date_text: date.format(DATE_FORMAT).to_string(),
+        file_text,
+        page: 1,
+        row: 0,
+        page_open: false,
+        line_number: settings.numbering.as_ref().map_or(1, |n| n.first_number),
+    };
+
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        if buf.last() == Some(&b'\n') {
+            buf.pop();
+        }
+        // a form feed in the input ends the current page
+        let mut segments = buf.split(|&b| b == FORM_FEED).peekable();
+        while let Some(segment) = segments.next() {
+            if segments.peek().is_some() {
+                if !segment.is_empty() {
+                    pager.add_line(segment)?;
+                }
+                pager.form_feed()?;
+            } else if !segment.is_empty() || buf.last() != Some(&FORM_FEED) {
+                pager.add_line(segment)?;
+            }
+        }
+    }
+    pager.finish()
+}
+
+/// Lays out the lines of a single input file onto pages.
+struct Pager<'a, W: Write> {
+    out: &'a mut W,
+    settings: &'a Settings,
+    date_text: String,
+    file_text: String,
+    page: usize,
+    row: usize,
+    page_open: bool,
+    line_number: usize,
+}
+
+impl<'a, W: Write> Pager<'a, W> {
+    /// Whether the current page is inside the `+FIRST[:LAST]` selection.
+    fn printing(&self) -> bool {
+        self.page >= self.settings.first_page
+            && self.settings.last_page.map_or(true, |last| self.page <= last)
+    }
+
+    fn write_margin(&mut self) -> io::Result<()> {
+        write!(self.out, "{:1$}", "", self.settings.offset)
+    }
+
+    fn start_page(&mut self) -> io::Result<()> {
+        self.page_open = true;
+        self.row = 0;
+        if self.printing() && !self.settings.omit_header {
+            // the header line has a blank line on either side with -F,
+            // and two without it
+            let blank: &[u8] = if self.settings.form_feed {
+                b"\n"
+            } else {
+                b"\n\n"
+            };
+            self.out.write_all(blank)?;
+            self.write_margin()?;
+            let header = header_line(
+                &self.date_text,
+                &self.file_text,
+                self.page,
+                self.settings.line_width,
+            );
+            writeln!(self.out, "{}", header)?;
+            self.out.write_all(blank)?;
+        }
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> io::Result<()> {
+        if self.printing() {
+            if self.settings.form_feed {
+                self.out.write_all(&[FORM_FEED])?;
+            } else if !self.settings.omit_header {
+                let padding = self.settings.body_lines() - self.row + TRAILER_LINES;
+                self.out.write_all(&b"\n".repeat(padding))?;
+            }
+        }
+        self.page_open = false;
+        self.page += 1;
+        Ok(())
+    }
+
+    fn add_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if !self.page_open {
+            self.start_page()?;
+        }
+        if self.printing() {
+            if !line.is_empty() || self.settings.numbering.is_some() {
+                self.write_margin()?;
+            }
+            if let Some(ref numbering) = self.settings.numbering {
+                // like GNU, only the low-order DIGITS digits are shown
+                let modulus = 10usize.checked_pow(numbering.width as u32);
+                let number = modulus.map_or(self.line_number, |m| self.line_number % m);
+                write!(
+                    self.out,
+                    "{:>width$}{}",
+                    number,
+                    numbering.separator,
+                    width = numbering.width
+                )?;
+                self.line_number += 1;
+            }
+            self.out.write_all(line)?;
+            self.out.write_all(b"\n")?;
+        }
+        self.row += 1;
+        if self.settings.double_space && self.row < self.settings.body_lines() {
+            if self.printing() {
+                self.out.write_all(b"\n")?;
+            }
+            self.row += 1;
+        }
+        if self.row >= self.settings.body_lines() {
+            self.end_page()?;
+        }
+        Ok(())
+    }
+
+    fn form_feed(&mut self) -> io::Result<()> {
+        if !self.page_open {
+            self.start_page()?;
+        }
+        self.end_page()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.page_open {
+            self.end_page()?;
+        }
+        Ok(())
+    }
+}
+
+/// Build the page header: the date on the left, the file name (or `-h` header)
+/// centered, and the page number on the right.
+fn header_line(date_text: &str, file_text: &str, page: usize, line_width: usize) -> String {
+    let page_text = format!("Page {}", page);
+    let available = line_width.saturating_sub(
+        date_text.chars().count() + file_text.chars().count() + page_text.chars().count(),
+    );
+    let lhs = available / 2;
+    let rhs = available - lhs;
+    format!(
+        "{}{:lhs$}{}{:rhs$}{}",
+        date_text,
+        "",
+        file_text,
+        "",
+        page_text,
+        lhs = lhs.max(1),
+        rhs = rhs.max(1)
+    )
+}
@@ -20,6 +20,10 @@ static OPT_NULL: &str = "null";
 
 static ARG_VARIABLES: &str = "variables";
 
+// the status when a variable isn't set, and when printenv itself fails
+static EXIT_NOT_FOUND: i32 = 1;
+static EXIT_FAILURE: i32 = 2;
+
 fn get_usage() -> String {
     format!("{0} [VARIABLE]... [OPTION]...", executable!())
 }
@@ -27,23 +31,14 @@ fn get_usage() -> String {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .arg(
-            Arg::with_name(OPT_NULL)
-                .short("0")
-                .long(OPT_NULL)
-                .help("end each output line with 0 byte rather than newline"),
-        )
-        .arg(
-            Arg::with_name(ARG_VARIABLES)
-                .multiple(true)
-                .takes_value(true)
-                .min_values(1),
-        )
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from_safe(uu_app().usage(&usage[..]), args);
+    let matches = match matches {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FAILURE;
+        }
+    };
 
     let variables: Vec<String> = matches
         .values_of(ARG_VARIABLES)
@@ -56,16 +51,47 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     }
 
     if variables.is_empty() {
-        for (env_var, value) in env::vars() {
-            print!("{}={}{}", env_var, value, separator);
+        for (env_var, value) in env::vars_os() {
+            print!(
+                "{}={}{}",
+                env_var.to_string_lossy(),
+                value.to_string_lossy(),
+                separator
+            );
         }
         return 0;
     }
 
+    let mut status = 0;
     for env_var in variables {
-        if let Ok(var) = env::var(env_var) {
-            print!("{}{}", var, separator);
+        // a name with "=" in it can't be that of a variable
+        let value = if env_var.contains('=') {
+            None
+        } else {
+            env::var_os(env_var)
+        };
+        match value {
+            Some(value) => print!("{}{}", value.to_string_lossy(), separator),
+            None => status = EXIT_NOT_FOUND,
         }
     }
-    0
+    status
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(
+            Arg::with_name(OPT_NULL)
+                .short("0")
+                .long(OPT_NULL)
+                .help("end each output line with 0 byte rather than newline"),
+        )
+        .arg(
+            Arg::with_name(ARG_VARIABLES)
+                .multiple(true)
+                .takes_value(true)
+                .min_values(1),
+        )
 }
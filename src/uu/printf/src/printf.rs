@@ -3,29 +3,26 @@
 // spell-checker:ignore (change!) each's
 // spell-checker:ignore (ToDO) LONGHELP FORMATSTRING templating parameterizing formatstr
 
+#[macro_use]
+extern crate uucore;
+
 mod cli;
 mod memo;
 mod tokenize;
 
+use uucore::coreopts::CoreOptions;
+use uucore::help::{self, Help};
+
 static NAME: &str = "printf";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
+static SYNTAX: &str = "FORMATSTRING [ARGUMENT]...";
+static SUMMARY: &str = "basic anonymous string templating";
 static SHORT_USAGE: &str = "printf: usage: printf [-v var] format [arguments]";
-static LONGHELP_LEAD: &str = "printf
-
- USAGE: printf FORMATSTRING [ARGUMENT]...
-
- basic anonymous string templating:
-
+static LONGHELP_BODY: &str = "
  prints format string at least once, repeating as long as there are remaining arguments
  output prints escaped literals in the format string as character literals
  output replaces anonymous fields with the next unused argument, formatted according to the field.
 
-Options:
-    --help              display this help and exit
-    --version           output version information and exit
-
-";
-static LONGHELP_BODY: &str = "
   Prints the , replacing escaped character sequences with character literals
     and substitution field sequences with passed arguments
 
@@ -286,12 +283,16 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     let formatstr = &args[1];
 
     if formatstr == "--help" {
-        print!("{} {}", LONGHELP_LEAD, LONGHELP_BODY);
+        print!("{}", Help::from(&uu_app()));
     } else if formatstr == "--version" {
-        println!("{} {}", NAME, VERSION);
+        println!("{}", help::version(NAME, VERSION));
     } else {
         let printf_args = &args[2..];
         memo::Memo::run_all(formatstr, printf_args);
     }
     0
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    app!(SYNTAX, SUMMARY, LONGHELP_BODY)
+}
@@ -13,7 +13,8 @@ extern crate uucore;
 use clap::{App, Arg};
 use std::fs;
 use std::io::{stdout, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use uucore::error_message;
 use uucore::fs::{canonicalize, CanonicalizeMode};
 
 const NAME: &str = "readlink";
@@ -36,14 +37,72 @@ fn get_usage() -> String {
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let mut no_newline = matches.is_present(OPT_NO_NEWLINE);
+    let use_zero = matches.is_present(OPT_ZERO);
+    let verbose = matches.is_present(OPT_VERBOSE);
+
+    let can_mode = if matches.is_present(OPT_CANONICALIZE) {
+        CanonicalizeMode::Normal
+    } else if matches.is_present(OPT_CANONICALIZE_EXISTING) {
+        CanonicalizeMode::Existing
+    } else if matches.is_present(OPT_CANONICALIZE_MISSING) {
+        CanonicalizeMode::Missing
+    } else {
+        CanonicalizeMode::None
+    };
+
+    let files: Vec<String> = matches
+        .values_of(ARG_FILES)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+    if files.is_empty() {
+        crash!(
+            1,
+            "missing operand\nTry {} --help for more information",
+            NAME
+        );
+    }
+
+    // The delimiter only goes unprinted for a single operand, warned about
+    // even when errors are silenced
+    if no_newline && files.len() > 1 {
+        eprintln!("{}: ignoring --no-newline with multiple arguments", NAME);
+        no_newline = false;
+    }
+
+    let mut retcode = 0;
+    for f in &files {
+        let p = PathBuf::from(f);
+        let result = if can_mode == CanonicalizeMode::None {
+            fs::read_link(&p)
+        } else {
+            canonicalize(&p, can_mode)
+        };
+        match result {
+            Ok(path) => show(&path, no_newline, use_zero),
+            Err(err) => {
+                if verbose {
+                    eprintln!("{}: {}: {}", NAME, f, error_message(&err));
+                }
+                retcode = 1;
+            }
+        }
+    }
+
+    retcode
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .arg(
             Arg::with_name(OPT_CANONICALIZE)
                 .short("f")
                 .long(OPT_CANONICALIZE)
+                .overrides_with_all(&[OPT_CANONICALIZE_EXISTING, OPT_CANONICALIZE_MISSING])
                 .help(
                     "canonicalize by following every symlink in every component of the \
             given name recursively; all but the last component must exist",
@@ -52,7 +111,8 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         .arg(
             Arg::with_name(OPT_CANONICALIZE_EXISTING)
                 .short("e")
-                .long("canonicalize-existing")
+                .long(OPT_CANONICALIZE_EXISTING)
+                .overrides_with_all(&[OPT_CANONICALIZE, OPT_CANONICALIZE_MISSING])
                 .help(
                     "canonicalize by following every symlink in every component of the \
          given name recursively, all components must exist",
@@ -62,6 +122,7 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             Arg::with_name(OPT_CANONICALIZE_MISSING)
                 .short("m")
                 .long(OPT_CANONICALIZE_MISSING)
+                .overrides_with_all(&[OPT_CANONICALIZE, OPT_CANONICALIZE_EXISTING])
                 .help(
                     "canonicalize by following every symlink in every component of the \
          given name recursively, without requirements on components existence",
@@ -77,18 +138,21 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             Arg::with_name(OPT_QUIET)
                 .short("q")
                 .long(OPT_QUIET)
+                .overrides_with_all(&[OPT_SILENT, OPT_VERBOSE])
                 .help("suppress most error messages"),
         )
         .arg(
             Arg::with_name(OPT_SILENT)
                 .short("s")
                 .long(OPT_SILENT)
+                .overrides_with_all(&[OPT_QUIET, OPT_VERBOSE])
                 .help("suppress most error messages"),
         )
         .arg(
             Arg::with_name(OPT_VERBOSE)
                 .short("v")
                 .long(OPT_VERBOSE)
+                .overrides_with_all(&[OPT_QUIET, OPT_SILENT])
                 .help("report error message"),
         )
         .arg(
@@ -98,74 +162,14 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .help("separate output with NUL rather than newline"),
         )
         .arg(Arg::with_name(ARG_FILES).multiple(true).takes_value(true))
-        .get_matches_from(args);
-
-    let mut no_newline = matches.is_present(OPT_NO_NEWLINE);
-    let use_zero = matches.is_present(OPT_ZERO);
-    let silent = matches.is_present(OPT_SILENT) || matches.is_present(OPT_QUIET);
-    let verbose = matches.is_present(OPT_VERBOSE);
-
-    let can_mode = if matches.is_present(OPT_CANONICALIZE) {
-        CanonicalizeMode::Normal
-    } else if matches.is_present(OPT_CANONICALIZE_EXISTING) {
-        CanonicalizeMode::Existing
-    } else if matches.is_present(OPT_CANONICALIZE_MISSING) {
-        CanonicalizeMode::Missing
-    } else {
-        CanonicalizeMode::None
-    };
-
-    let files: Vec<String> = matches
-        .values_of(ARG_FILES)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-    if files.is_empty() {
-        crash!(
-            1,
-            "missing operand\nTry {} --help for more information",
-            NAME
-        );
-    }
-
-    if no_newline && files.len() > 1 && !silent {
-        eprintln!("{}: ignoring --no-newline with multiple arguments", NAME);
-        no_newline = false;
-    }
-
-    for f in &files {
-        let p = PathBuf::from(f);
-        if can_mode == CanonicalizeMode::None {
-            match fs::read_link(&p) {
-                Ok(path) => show(&path, no_newline, use_zero),
-                Err(err) => {
-                    if verbose {
-                        eprintln!("{}: {}: errno {}", NAME, f, err.raw_os_error().unwrap());
-                    }
-                    return 1;
-                }
-            }
-        } else {
-            match canonicalize(&p, can_mode) {
-                Ok(path) => show(&path, no_newline, use_zero),
-                Err(err) => {
-                    if verbose {
-                        eprintln!("{}: {}: errno {:?}", NAME, f, err.raw_os_error().unwrap());
-                    }
-                    return 1;
-                }
-            }
-        }
-    }
-
-    0
 }
 
-fn show(path: &PathBuf, no_newline: bool, use_zero: bool) {
-    let path = path.as_path().to_str().unwrap();
-    if use_zero {
-        print!("{}\0", path);
-    } else if no_newline {
+fn show(path: &Path, no_newline: bool, use_zero: bool) {
+    let path = path.display();
+    if no_newline {
         print!("{}", path);
+    } else if use_zero {
+        print!("{}\0", path);
     } else {
         println!("{}", path);
     }
@@ -12,13 +12,19 @@ extern crate uucore;
 
 use clap::{App, Arg};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{Error, ErrorKind, Result as IOResult};
+use std::path::{Component, Path, PathBuf};
+use uucore::error_message;
 use uucore::fs::{canonicalize, CanonicalizeMode};
 
 static ABOUT: &str = "print the resolved path";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 
+static OPT_CANONICALIZE_EXISTING: &str = "canonicalize-existing";
+static OPT_CANONICALIZE_MISSING: &str = "canonicalize-missing";
 static OPT_QUIET: &str = "quiet";
+static OPT_RELATIVE_BASE: &str = "relative-base";
+static OPT_RELATIVE_TO: &str = "relative-to";
 static OPT_STRIP: &str = "strip";
 static OPT_ZERO: &str = "zero";
 
@@ -28,23 +34,127 @@ fn get_usage() -> String {
     format!("{0} [OPTION]... FILE...", executable!())
 }
 
+struct Options {
+    can_mode: CanonicalizeMode,
+    strip: bool,
+    zero: bool,
+    quiet: bool,
+    relative_to: Option<PathBuf>,
+    relative_base: Option<PathBuf>,
+}
+
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    /*  the list of files */
+
+    let paths: Vec<PathBuf> = matches
+        .values_of(ARG_FILES)
+        .unwrap()
+        .map(PathBuf::from)
+        .collect();
+
+    let can_mode = if matches.is_present(OPT_CANONICALIZE_EXISTING) {
+        CanonicalizeMode::Existing
+    } else if matches.is_present(OPT_CANONICALIZE_MISSING) {
+        CanonicalizeMode::Missing
+    } else {
+        CanonicalizeMode::Normal
+    };
+    let mut options = Options {
+        can_mode,
+        strip: matches.is_present(OPT_STRIP),
+        zero: matches.is_present(OPT_ZERO),
+        quiet: matches.is_present(OPT_QUIET),
+        relative_to: None,
+        relative_base: None,
+    };
+
+    let relative_dir = |name: &str| {
+        matches.value_of(name).map(|dir| {
+            let dir = Path::new(dir);
+            match resolve_dir(dir, &options) {
+                Ok(dir) => dir,
+                Err(e) => crash!(1, "{}: {}", dir.display(), error_message(&e)),
+            }
+        })
+    };
+    let relative_to = relative_dir(OPT_RELATIVE_TO);
+    let relative_base = relative_dir(OPT_RELATIVE_BASE);
+
+    // A lone --relative-base is also the directory to print relative to,
+    // and a --relative-to outside of the base disables both.
+    match (relative_to, relative_base) {
+        (None, Some(base)) => {
+            options.relative_to = Some(base.clone());
+            options.relative_base = Some(base);
+        }
+        (Some(to), Some(base)) => {
+            if to.starts_with(&base) {
+                options.relative_to = Some(to);
+                options.relative_base = Some(base);
+            }
+        }
+        (to, None) => options.relative_to = to,
+    }
+
+    let mut retcode = 0;
+    for path in &paths {
+        if let Err(e) = resolve_path(path, &options) {
+            if !options.quiet {
+                show_error!("{}: {}", path.display(), error_message(&e));
+            }
+            retcode = 1
+        };
+    }
+    retcode
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
+        .arg(
+            Arg::with_name(OPT_CANONICALIZE_EXISTING)
+                .short("e")
+                .long(OPT_CANONICALIZE_EXISTING)
+                .overrides_with(OPT_CANONICALIZE_MISSING)
+                .help("All components of the path must exist"),
+        )
+        .arg(
+            Arg::with_name(OPT_CANONICALIZE_MISSING)
+                .short("m")
+                .long(OPT_CANONICALIZE_MISSING)
+                .overrides_with(OPT_CANONICALIZE_EXISTING)
+                .help("No path components need exist or be a directory"),
+        )
         .arg(
             Arg::with_name(OPT_QUIET)
                 .short("q")
                 .long(OPT_QUIET)
                 .help("Do not print warnings for invalid paths"),
         )
+        .arg(
+            Arg::with_name(OPT_RELATIVE_TO)
+                .long(OPT_RELATIVE_TO)
+                .takes_value(true)
+                .value_name("DIR")
+                .help("Print the resolved path relative to DIR"),
+        )
+        .arg(
+            Arg::with_name(OPT_RELATIVE_BASE)
+                .long(OPT_RELATIVE_BASE)
+                .takes_value(true)
+                .value_name("DIR")
+                .help("Print absolute paths unless paths below DIR"),
+        )
         .arg(
             Arg::with_name(OPT_STRIP)
                 .short("s")
                 .long(OPT_STRIP)
+                .alias("no-symlinks")
                 .help("Only strip '.' and '..' components, but don't resolve symbolic links"),
         )
         .arg(
@@ -60,79 +170,72 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .required(true)
                 .min_values(1),
         )
-        .get_matches_from(args);
+}
 
-    /*  the list of files */
+fn resolve(p: &Path, options: &Options) -> IOResult<PathBuf> {
+    if p.as_os_str().is_empty() {
+        return Err(Error::new(ErrorKind::NotFound, "No such file or directory"));
+    }
 
-    let paths: Vec<PathBuf> = matches
-        .values_of(ARG_FILES)
-        .unwrap()
-        .map(PathBuf::from)
-        .collect();
+    if !options.strip {
+        return canonicalize(p, options.can_mode);
+    }
 
-    let strip = matches.is_present(OPT_STRIP);
-    let zero = matches.is_present(OPT_ZERO);
-    let quiet = matches.is_present(OPT_QUIET);
-    let mut retcode = 0;
-    for path in &paths {
-        if !resolve_path(path, strip, zero, quiet) {
-            retcode = 1
-        };
+    let abs = canonicalize(p, CanonicalizeMode::None)?;
+    if options.can_mode == CanonicalizeMode::Existing {
+        fs::symlink_metadata(&abs)?;
     }
-    retcode
+    Ok(abs)
 }
 
-fn resolve_path(p: &PathBuf, strip: bool, zero: bool, quiet: bool) -> bool {
-    let abs = canonicalize(p, CanonicalizeMode::Normal).unwrap();
-
-    if strip {
-        if zero {
-            print!("{}\0", p.display());
-        } else {
-            println!("{}", p.display())
-        }
-        return true;
+/// Resolve the argument of --relative-to or --relative-base, which must be a
+/// directory when all components have to exist.
+fn resolve_dir(dir: &Path, options: &Options) -> IOResult<PathBuf> {
+    let abs = resolve(dir, options)?;
+    if options.can_mode == CanonicalizeMode::Existing && !abs.is_dir() {
+        return Err(Error::new(ErrorKind::Other, "Not a directory"));
     }
+    Ok(abs)
+}
 
-    let mut result = PathBuf::new();
-    let mut links_left = 256;
+/// The path to `path` from the directory `dir`, both absolute
+fn relative_path(path: &Path, dir: &Path) -> PathBuf {
+    let common = path
+        .components()
+        .zip(dir.components())
+        .take_while(|(a, b)| a == b)
+        .count();
 
-    for part in abs.components() {
+    let mut result = PathBuf::new();
+    for _ in dir.components().skip(common) {
+        result.push(Component::ParentDir.as_os_str());
+    }
+    for part in path.components().skip(common) {
         result.push(part.as_os_str());
-        loop {
-            if links_left == 0 {
-                if !quiet {
-                    show_error!("Too many symbolic links: {}", p.display())
-                };
-                return false;
-            }
-            match fs::metadata(result.as_path()) {
-                Err(_) => break,
-                Ok(ref m) if !m.file_type().is_symlink() => break,
-                Ok(_) => {
-                    links_left -= 1;
-                    match fs::read_link(result.as_path()) {
-                        Ok(x) => {
-                            result.pop();
-                            result.push(x.as_path());
-                        }
-                        _ => {
-                            if !quiet {
-                                show_error!("Invalid path: {}", p.display())
-                            };
-                            return false;
-                        }
-                    }
-                }
-            }
-        }
     }
+    if result.as_os_str().is_empty() {
+        result.push(Component::CurDir.as_os_str());
+    }
+    result
+}
+
+fn resolve_path(p: &Path, options: &Options) -> IOResult<()> {
+    let abs = resolve(p, options)?;
+
+    let below_base = match options.relative_base {
+        Some(ref base) => abs.starts_with(base),
+        None => true,
+    };
+    let result = match options.relative_to {
+        Some(ref dir) if below_base => relative_path(&abs, dir),
+        _ => abs,
+    };
 
-    if zero {
+    if options.zero {
         print!("{}\0", result.display());
     } else {
         println!("{}", result.display());
     }
 
-    true
+    Ok(())
 }
@@ -12,42 +12,18 @@ extern crate uucore;
 
 use std::env;
 use std::path::{Path, PathBuf};
+use uucore::coreopts::CoreOptions;
 use uucore::fs::{canonicalize, CanonicalizeMode};
 
 static NAME: &str = "relpath";
-static VERSION: &str = env!("CARGO_PKG_VERSION");
+static SYNTAX: &str = "[-d DIR] TO [FROM]";
+static SUMMARY: &str = "Convert TO destination to the relative path from the FROM dir.
+ If FROM path is omitted, current working dir will be used.";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let mut opts = getopts::Options::new();
-
-    opts.optflag("h", "help", "Show help and exit");
-    opts.optflag("V", "version", "Show version and exit");
-    opts.optopt(
-        "d",
-        "",
-        "If any of FROM and TO is not subpath of DIR, output absolute path instead of relative",
-        "DIR",
-    );
-
-    let matches = match opts.parse(&args[1..]) {
-        Ok(m) => m,
-        Err(f) => {
-            show_error!("{}", f);
-            show_usage(&opts);
-            return 1;
-        }
-    };
-
-    if matches.opt_present("V") {
-        version();
-        return 0;
-    }
-    if matches.opt_present("h") {
-        show_usage(&opts);
-        return 0;
-    }
+    let matches = uu_app().parse(args);
 
     if matches.free.is_empty() {
         show_error!("Missing operand: TO");
@@ -100,23 +76,15 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     0
 }
 
-fn version() {
-    println!("{} {}", NAME, VERSION)
-}
-
-fn show_usage(opts: &getopts::Options) {
-    version();
-    println!();
-    println!("Usage:");
-    println!("  {} [-d DIR] TO [FROM]", NAME);
-    println!("  {} -V|--version", NAME);
-    println!("  {} -h|--help", NAME);
-    println!();
-    print!(
-        "{}",
-        opts.usage(
-            "Convert TO destination to the relative path from the FROM dir.\n\
-             If FROM path is omitted, current working dir will be used."
-        )
-    );
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, "");
+    app.optopt(
+        "d",
+        "",
+        "If any of FROM and TO is not subpath of DIR, output absolute path instead of relative",
+        "DIR",
+    )
+    .optflag("h", "help", "Show help and exit")
+    .optflag("V", "version", "Show version and exit");
+    app
 }
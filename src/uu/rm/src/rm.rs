@@ -11,12 +11,17 @@
 extern crate uucore;
 
 use clap::{App, Arg};
+#[cfg(not(unix))]
 use remove_dir_all::remove_dir_all;
+#[cfg(not(unix))]
 use std::collections::VecDeque;
 use std::fs;
 use std::io::{stderr, stdin, BufRead, Write};
 use std::ops::BitOr;
 use std::path::Path;
+#[cfg(unix)]
+use uucore::safe_traversal::{Entry, Error as WalkError, Symlinks, Visitor, Walker};
+#[cfg(not(unix))]
 use walkdir::{DirEntry, WalkDir};
 
 #[derive(Eq, PartialEq, Clone, Copy)]
@@ -57,9 +62,8 @@ fn get_usage() -> String {
     format!("{0} [OPTION]... FILE...", executable!())
 }
 
-fn get_long_usage() -> String {
-    String::from(
-        "By default, rm does not remove directories.  Use the --recursive (-r or -R)
+static LONG_USAGE: &str =
+    "By default, rm does not remove directories.  Use the --recursive (-r or -R)
         option to remove each listed directory, too, along with all of its contents
 
         To remove a file whose name starts with a '-', for example '-foo',
@@ -70,20 +74,75 @@ fn get_long_usage() -> String {
 
         Note that if you use rm to remove a file, it might be possible to recover
         some of its contents, given sufficient expertise and/or time.  For greater
-        assurance that the contents are truly unrecoverable, consider using shred.",
-    )
-}
+        assurance that the contents are truly unrecoverable, consider using shred.";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
-    let long_usage = get_long_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let files: Vec<String> = matches
+        .values_of(ARG_FILES)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    let force = matches.is_present(OPT_FORCE);
+
+    if files.is_empty() && !force {
+        // Still check by hand and not use clap
+        // Because "rm -f" is a thing
+        show_error!("missing an argument");
+        show_error!("for help, try '{0} --help'", executable!());
+        return 1;
+    } else {
+        let options = Options {
+            force,
+            interactive: {
+                if matches.is_present(OPT_PROMPT) {
+                    InteractiveMode::Always
+                } else if matches.is_present(OPT_PROMPT_MORE) {
+                    InteractiveMode::Once
+                } else if matches.is_present(OPT_INTERACTIVE) {
+                    match &matches.value_of(OPT_INTERACTIVE).unwrap()[..] {
+                        "none" => InteractiveMode::None,
+                        "once" => InteractiveMode::Once,
+                        "always" => InteractiveMode::Always,
+                        val => crash!(1, "Invalid argument to interactive ({})", val),
+                    }
+                } else {
+                    InteractiveMode::None
+                }
+            },
+            one_fs: matches.is_present(OPT_ONE_FILE_SYSTEM),
+            preserve_root: !matches.is_present(OPT_NO_PRESERVE_ROOT),
+            recursive: matches.is_present(OPT_RECURSIVE) || matches.is_present(OPT_RECURSIVE_R),
+            dir: matches.is_present(OPT_DIR),
+            verbose: matches.is_present(OPT_VERBOSE),
+        };
+        if options.interactive == InteractiveMode::Once && (options.recursive || files.len() > 3) {
+            let msg = if options.recursive {
+                "Remove all arguments recursively? "
+            } else {
+                "Remove all arguments? "
+            };
+            if !prompt(msg) {
+                return 0;
+            }
+        }
+
+        if remove(files, options) {
+            return 1;
+        }
+    }
+
+    0
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
-        .after_help(&long_usage[..])
-
+        .after_help(LONG_USAGE)
         .arg(
             Arg::with_name(OPT_FORCE)
             .short("f")
@@ -152,63 +211,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             .takes_value(true)
             .min_values(1)
         )
-        .get_matches_from(args);
-
-    let files: Vec<String> = matches
-        .values_of(ARG_FILES)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    let force = matches.is_present(OPT_FORCE);
-
-    if files.is_empty() && !force {
-        // Still check by hand and not use clap
-        // Because "rm -f" is a thing
-        show_error!("missing an argument");
-        show_error!("for help, try '{0} --help'", executable!());
-        return 1;
-    } else {
-        let options = Options {
-            force,
-            interactive: {
-                if matches.is_present(OPT_PROMPT) {
-                    InteractiveMode::Always
-                } else if matches.is_present(OPT_PROMPT_MORE) {
-                    InteractiveMode::Once
-                } else if matches.is_present(OPT_INTERACTIVE) {
-                    match &matches.value_of(OPT_INTERACTIVE).unwrap()[..] {
-                        "none" => InteractiveMode::None,
-                        "once" => InteractiveMode::Once,
-                        "always" => InteractiveMode::Always,
-                        val => crash!(1, "Invalid argument to interactive ({})", val),
-                    }
-                } else {
-                    InteractiveMode::None
-                }
-            },
-            one_fs: matches.is_present(OPT_ONE_FILE_SYSTEM),
-            preserve_root: !matches.is_present(OPT_NO_PRESERVE_ROOT),
-            recursive: matches.is_present(OPT_RECURSIVE) || matches.is_present(OPT_RECURSIVE_R),
-            dir: matches.is_present(OPT_DIR),
-            verbose: matches.is_present(OPT_VERBOSE),
-        };
-        if options.interactive == InteractiveMode::Once && (options.recursive || files.len() > 3) {
-            let msg = if options.recursive {
-                "Remove all arguments recursively? "
-            } else {
-                "Remove all arguments? "
-            };
-            if !prompt(msg) {
-                return 0;
-            }
-        }
-
-        if remove(files, options) {
-            return 1;
-        }
-    }
-
-    0
 }
 
 // TODO: implement one-file-system (this may get partially implemented in walkdir)
@@ -251,37 +253,7 @@ fn handle_dir(path: &Path, options: &Options) -> bool {
 
     let is_root = path.has_root() && path.parent().is_none();
     if options.recursive && (!is_root || !options.preserve_root) {
-        if options.interactive != InteractiveMode::Always {
-            // we need the extra crate because apparently fs::remove_dir_all() does not function
-            // correctly on Windows
-            if let Err(e) = remove_dir_all(path) {
-                had_err = true;
-                show_error!("could not remove '{}': {}", path.display(), e);
-            }
-        } else {
-            let mut dirs: VecDeque<DirEntry> = VecDeque::new();
-
-            for entry in WalkDir::new(path) {
-                match entry {
-                    Ok(entry) => {
-                        let file_type = entry.file_type();
-                        if file_type.is_dir() {
-                            dirs.push_back(entry);
-                        } else {
-                            had_err = remove_file(entry.path(), options).bitor(had_err);
-                        }
-                    }
-                    Err(e) => {
-                        had_err = true;
-                        show_error!("recursing in '{}': {}", path.display(), e);
-                    }
-                }
-            }
-
-            for dir in dirs.iter().rev() {
-                had_err = remove_dir(dir.path(), options).bitor(had_err);
-            }
-        }
+        had_err = remove_dir_recursive(path, options);
     } else if options.dir && (!is_root || !options.preserve_root) {
         had_err = remove_dir(path, options).bitor(had_err);
     } else if options.recursive {
@@ -298,6 +270,121 @@ fn handle_dir(path: &Path, options: &Options) -> bool {
     had_err
 }
 
+/// Remove the tree below `path`, files before the directories holding them.
+#[cfg(unix)]
+fn remove_dir_recursive(path: &Path, options: &Options) -> bool {
+    let mut remover = Remover {
+        options,
+        had_err: false,
+    };
+    Walker::new(Symlinks::Physical)
+        .recursive(true)
+        .walk(path, &mut remover);
+    remover.had_err
+}
+
+#[cfg(not(unix))]
+fn remove_dir_recursive(path: &Path, options: &Options) -> bool {
+    let mut had_err = false;
+
+    if options.interactive != InteractiveMode::Always {
+        // we need the extra crate because apparently fs::remove_dir_all() does not function
+        // correctly on Windows
+        if let Err(e) = remove_dir_all(path) {
+            had_err = true;
+            show_error!("could not remove '{}': {}", path.display(), e);
+        }
+    } else {
+        let mut dirs: VecDeque<DirEntry> = VecDeque::new();
+
+        for entry in WalkDir::new(path) {
+            match entry {
+                Ok(entry) => {
+                    let file_type = entry.file_type();
+                    if file_type.is_dir() {
+                        dirs.push_back(entry);
+                    } else {
+                        had_err = remove_file(entry.path(), options).bitor(had_err);
+                    }
+                }
+                Err(e) => {
+                    had_err = true;
+                    show_error!("recursing in '{}': {}", path.display(), e);
+                }
+            }
+        }
+
+        for dir in dirs.iter().rev() {
+            had_err = remove_dir(dir.path(), options).bitor(had_err);
+        }
+    }
+
+    had_err
+}
+
+#[cfg(unix)]
+struct Remover<'a> {
+    options: &'a Options,
+    had_err: bool,
+}
+
+#[cfg(unix)]
+impl<'a> Remover<'a> {
+    fn remove(&mut self, entry: &Entry) {
+        let is_dir = entry.is_dir();
+        if self.options.interactive == InteractiveMode::Always && !prompt_file(entry.path(), is_dir)
+        {
+            return;
+        }
+        match entry.remove() {
+            Ok(_) => {
+                if self.options.verbose {
+                    if is_dir {
+                        println!("removed directory '{}'", entry.path().display());
+                    } else {
+                        println!("removed '{}'", entry.path().display());
+                    }
+                }
+            }
+            Err(e) => {
+                if is_dir {
+                    show_error!("cannot remove '{}': {}", entry.path().display(), e);
+                } else {
+                    show_error!("removing '{}': {}", entry.path().display(), e);
+                }
+                self.had_err = true;
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl<'a> Visitor for Remover<'a> {
+    fn visit(&mut self, entry: &Entry) -> bool {
+        if entry.is_dir() {
+            // emptied first, removed when left
+            return true;
+        }
+        self.remove(entry);
+        false
+    }
+
+    fn leave(&mut self, entry: &Entry) {
+        self.remove(entry);
+    }
+
+    fn error(&mut self, path: &Path, err: WalkError) {
+        match err {
+            WalkError::Access(e) | WalkError::ReadDir(e) => {
+                show_error!("recursing in '{}': {}", path.display(), e);
+                self.had_err = true;
+            }
+            // links aren't followed, so a directory can't be its own ancestor
+            WalkError::Cycle => {}
+        }
+    }
+}
+
 fn remove_dir(path: &Path, options: &Options) -> bool {
     let response = if options.interactive == InteractiveMode::Always {
         prompt_file(path, true)
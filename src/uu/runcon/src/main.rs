@@ -0,0 +1 @@
+uucore_procs::main!(uu_runcon); // spell-checker:ignore procs uucore
@@ -0,0 +1,252 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (ToDO) execvp levelrange
+
+#[macro_use]
+extern crate uucore;
+use clap::{App, AppSettings, Arg};
+use std::env;
+use std::ffi::CString;
+use std::io::Error;
+use std::path::PathBuf;
+use std::ptr;
+use uucore::error_message;
+use uucore::libc::{self, c_char, execvp};
+use uucore::selinux::{self, Component, Context};
+
+static VERSION: &str = env!("CARGO_PKG_VERSION");
+static ABOUT: &str = "Run a program in a different SELinux security context.
+With neither CONTEXT nor COMMAND, print the current security context.";
+
+// exit codes that match the GNU implementation
+static EXIT_CANNOT_INVOKE: i32 = 126;
+static EXIT_ENOENT: i32 = 127;
+
+mod options {
+    pub const COMPUTE: &str = "compute";
+    pub const USER: &str = "user";
+    pub const ROLE: &str = "role";
+    pub const TYPE: &str = "type";
+    pub const RANGE: &str = "range";
+    pub const ARGS: &str = "args";
+}
+
+fn get_usage() -> String {
+    format!(
+        "{0} CONTEXT COMMAND [ARG]...
+    or:  {0} [-c] [-u USER] [-r ROLE] [-t TYPE] [-l RANGE] COMMAND [ARG]...",
+        executable!()
+    )
+}
+
+pub fn uumain(args: impl uucore::Args) -> i32 {
+    let args = args.collect_str();
+    let usage = get_usage();
+
+    let app = uu_app().usage(&usage[..]);
+
+    let matches = match uucore::help::get_matches_from_safe(app, args) {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut args: Vec<&str> = matches
+        .values_of(options::ARGS)
+        .map(|args| args.collect())
+        .unwrap_or_default();
+    if args.is_empty() {
+        return match selinux::current_context() {
+            Ok(context) => {
+                println!("{}", context);
+                0
+            }
+            Err(e) => {
+                show_info!("failed to get current context: {}", error_message(&e));
+                1
+            }
+        };
+    }
+
+    let compute = matches.is_present(options::COMPUTE);
+    let components = [
+        (Component::User, matches.value_of(options::USER)),
+        (Component::Type, matches.value_of(options::TYPE)),
+        (Component::Range, matches.value_of(options::RANGE)),
+        (Component::Role, matches.value_of(options::ROLE)),
+    ];
+    let context = if compute || components.iter().any(|(_, value)| value.is_some()) {
+        None
+    } else {
+        Some(args.remove(0))
+    };
+    if args.is_empty() {
+        show_usage_error!("no command specified");
+        return 1;
+    }
+    if !selinux::is_enabled() {
+        show_info!("{} may be used only on a SELinux kernel", executable!());
+        return 1;
+    }
+
+    let context = match context {
+        Some(context) => match Context::parse(context) {
+            Ok(context) => context,
+            Err(e) => {
+                show_info!(
+                    "failed to create security context: '{}': {}",
+                    context,
+                    error_message(&e)
+                );
+                return 1;
+            }
+        },
+        None => {
+            let mut current = match selinux::current_context() {
+                Ok(context) => context,
+                Err(e) => {
+                    show_info!("failed to get current context: {}", error_message(&e));
+                    return 1;
+                }
+            };
+            if compute {
+                // the context the command would get on its own
+                let file = match selinux::file_context(command_path(args[0]), true) {
+                    Ok(context) => context,
+                    Err(e) => {
+                        show_info!(
+                            "failed to get security context of '{}': {}",
+                            args[0],
+                            error_message(&e)
+                        );
+                        return 1;
+                    }
+                };
+                current = match selinux::compute_create(&current, &file, "process") {
+                    Ok(context) => context,
+                    Err(e) => {
+                        show_info!("failed to compute a new context: {}", error_message(&e));
+                        return 1;
+                    }
+                };
+            }
+            let mut context = match Context::parse(&current) {
+                Ok(context) => context,
+                Err(e) => {
+                    show_info!(
+                        "failed to create security context: '{}': {}",
+                        current,
+                        error_message(&e)
+                    );
+                    return 1;
+                }
+            };
+            for &(component, value) in &components {
+                if let Some(value) = value {
+                    if let Err(e) = context.set(component, value) {
+                        show_info!(
+                            "failed to set new {}: '{}': {}",
+                            component,
+                            value,
+                            error_message(&e)
+                        );
+                        return 1;
+                    }
+                }
+            }
+            context
+        }
+    };
+
+    let context = context.to_string();
+    if let Err(e) = selinux::check_context(&context) {
+        show_info!("invalid context: '{}': {}", context, error_message(&e));
+        return 1;
+    }
+    if let Err(e) = selinux::set_exec_context(&context) {
+        show_info!(
+            "unable to set security context '{}': {}",
+            context,
+            error_message(&e)
+        );
+        return 1;
+    }
+
+    let cstrs: Vec<CString> = args
+        .iter()
+        .map(|arg| CString::new(arg.as_bytes()).unwrap())
+        .collect();
+    let mut argv: Vec<*const c_char> = cstrs.iter().map(|s| s.as_ptr()).collect();
+    argv.push(ptr::null());
+    unsafe { execvp(argv[0], argv.as_mut_ptr()) };
+
+    let err = Error::last_os_error();
+    show_info!("'{}': {}", args[0], error_message(&err));
+    if err.raw_os_error() == Some(libc::ENOENT) {
+        EXIT_ENOENT
+    } else {
+        EXIT_CANNOT_INVOKE
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .setting(AppSettings::TrailingVarArg)
+        .arg(
+            Arg::with_name(options::COMPUTE)
+                .short("c")
+                .long(options::COMPUTE)
+                .help("compute process transition context before modifying"),
+        )
+        .arg(
+            Arg::with_name(options::TYPE)
+                .short("t")
+                .long(options::TYPE)
+                .help("type (for same role as parent)")
+                .value_name("TYPE"),
+        )
+        .arg(
+            Arg::with_name(options::USER)
+                .short("u")
+                .long(options::USER)
+                .help("user identity")
+                .value_name("USER"),
+        )
+        .arg(
+            Arg::with_name(options::ROLE)
+                .short("r")
+                .long(options::ROLE)
+                .help("role")
+                .value_name("ROLE"),
+        )
+        .arg(
+            Arg::with_name(options::RANGE)
+                .short("l")
+                .long(options::RANGE)
+                .help("levelrange")
+                .value_name("RANGE"),
+        )
+        .arg(Arg::with_name(options::ARGS).hidden(true).multiple(true))
+}
+
+/// The file `command` runs, looked up in `PATH` unless it's a path already.
+fn command_path(command: &str) -> PathBuf {
+    if !command.contains('/') {
+        if let Some(dirs) = env::var_os("PATH") {
+            if let Some(found) = env::split_paths(&dirs)
+                .map(|dir| dir.join(command))
+                .find(|file| file.is_file())
+            {
+                return found;
+            }
+        }
+    }
+    PathBuf::from(command)
+}
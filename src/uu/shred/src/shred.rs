@@ -16,12 +16,51 @@ use std::io;
 use std::io::prelude::*;
 use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
+use uucore::coreopts::CoreOptions;
 
 #[macro_use]
 extern crate uucore;
 
 static NAME: &str = "shred";
-static VERSION_STR: &str = "1.0.0";
+static SYNTAX: &str = "[OPTION]... FILE...";
+static SUMMARY: &str = "Overwrite the specified FILE(s) repeatedly, in order to make it harder \
+                        for even very expensive hardware probing to recover the data.";
+static LONG_HELP: &str = "
+Delete FILE(s) if --remove (-u) is specified.  The default is not to remove
+the files because it is common to operate on device files like /dev/hda,
+and those files usually should not be removed.
+
+CAUTION: Note that shred relies on a very important assumption:
+that the file system overwrites data in place.  This is the traditional
+way to do things, but many modern file system designs do not satisfy this
+assumption.  The following are examples of file systems on which shred is
+not effective, or is not guaranteed to be effective in all file system modes:
+
+* log-structured or journaled file systems, such as those supplied with
+AIX and Solaris (and JFS, ReiserFS, XFS, Ext3, etc.)
+
+* file systems that write redundant data and carry on even if some writes
+fail, such as RAID-based file systems
+
+* file systems that make snapshots, such as Network Appliance's NFS server
+
+* file systems that cache in temporary locations, such as NFS
+version 3 clients
+
+* compressed file systems
+
+In the case of ext3 file systems, the above disclaimer applies
+(and shred is thus of limited effectiveness) only in data=journal mode,
+which journals file data in addition to just metadata.  In both the
+data=ordered (default) and data=writeback modes, shred works as usual.
+Ext3 journaling modes can be changed by adding the data=something option
+to the mount options for a particular file system in the /etc/fstab file,
+as documented in the mount man page (man mount).
+
+In addition, file system backups and remote mirrors may contain copies
+of the file that cannot be removed, and that will allow a shredded file
+to be recovered later.
+";
 const BLOCK_SIZE: usize = 512;
 const NAMESET: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_.";
 
@@ -215,53 +254,9 @@ impl<'a> BytesGenerator<'a> {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let mut opts = getopts::Options::new();
+    let matches = uu_app().parse(args);
 
-    // TODO: Add force option
-    opts.optopt(
-        "n",
-        "iterations",
-        "overwrite N times instead of the default (3)",
-        "N",
-    );
-    opts.optopt(
-        "s",
-        "size",
-        "shred this many bytes (suffixes like K, M, G accepted)",
-        "FILESIZE",
-    );
-    opts.optflag(
-        "u",
-        "remove",
-        "truncate and remove the file after overwriting; See below",
-    );
-    opts.optflag("v", "verbose", "show progress");
-    opts.optflag(
-        "x",
-        "exact",
-        "do not round file sizes up to the next full block; \
-         this is the default for non-regular files",
-    );
-    opts.optflag(
-        "z",
-        "zero",
-        "add a final overwrite with zeros to hide shredding",
-    );
-    opts.optflag("", "help", "display this help and exit");
-    opts.optflag("", "version", "output version information and exit");
-
-    let matches = match opts.parse(&args[1..]) {
-        Ok(m) => m,
-        Err(e) => panic!("Invalid options\n{}", e),
-    };
-
-    if matches.opt_present("help") {
-        show_help(&opts);
-        return 0;
-    } else if matches.opt_present("version") {
-        println!("{} {}", NAME, VERSION_STR);
-        return 0;
-    } else if matches.free.is_empty() {
+    if matches.free.is_empty() {
         println!("{}: Missing an argument", NAME);
         println!("For help, try '{} --help'", NAME);
         return 0;
@@ -289,59 +284,40 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     0
 }
 
-fn show_help(opts: &getopts::Options) {
-    println!("Usage: {} [OPTION]... FILE...", NAME);
-    println!(
-        "Overwrite the specified FILE(s) repeatedly, in order to make it harder \
-         for even very expensive hardware probing to recover the data."
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+
+    // TODO: Add force option
+    app.optopt(
+        "n",
+        "iterations",
+        "overwrite N times instead of the default (3)",
+        "N",
     );
-    println!("{}", opts.usage(""));
-    println!("Delete FILE(s) if --remove (-u) is specified.  The default is not to remove");
-    println!("the files because it is common to operate on device files like /dev/hda,");
-    println!("and those files usually should not be removed.");
-    println!();
-    println!(
-        "CAUTION: Note that {} relies on a very important assumption:",
-        NAME
+    app.optopt(
+        "s",
+        "size",
+        "shred this many bytes (suffixes like K, M, G accepted)",
+        "FILESIZE",
     );
-    println!("that the file system overwrites data in place.  This is the traditional");
-    println!("way to do things, but many modern file system designs do not satisfy this");
-    println!(
-        "assumption.  The following are examples of file systems on which {} is",
-        NAME
+    app.optflag(
+        "u",
+        "remove",
+        "truncate and remove the file after overwriting; See below",
     );
-    println!("not effective, or is not guaranteed to be effective in all file system modes:");
-    println!();
-    println!("* log-structured or journaled file systems, such as those supplied with");
-    println!("AIX and Solaris (and JFS, ReiserFS, XFS, Ext3, etc.)");
-    println!();
-    println!("* file systems that write redundant data and carry on even if some writes");
-    println!("fail, such as RAID-based file systems");
-    println!();
-    println!("* file systems that make snapshots, such as Network Appliance's NFS server");
-    println!();
-    println!("* file systems that cache in temporary locations, such as NFS");
-    println!("version 3 clients");
-    println!();
-    println!("* compressed file systems");
-    println!();
-    println!("In the case of ext3 file systems, the above disclaimer applies");
-    println!(
-        "(and {} is thus of limited effectiveness) only in data=journal mode,",
-        NAME
+    app.optflag("v", "verbose", "show progress");
+    app.optflag(
+        "x",
+        "exact",
+        "do not round file sizes up to the next full block; \
+         this is the default for non-regular files",
     );
-    println!("which journals file data in addition to just metadata.  In both the");
-    println!(
-        "data=ordered (default) and data=writeback modes, {} works as usual.",
-        NAME
+    app.optflag(
+        "z",
+        "zero",
+        "add a final overwrite with zeros to hide shredding",
     );
-    println!("Ext3 journaling modes can be changed by adding the data=something option");
-    println!("to the mount options for a particular file system in the /etc/fstab file,");
-    println!("as documented in the mount man page (man mount).");
-    println!();
-    println!("In addition, file system backups and remote mirrors may contain copies");
-    println!("of the file that cannot be removed, and that will allow a shredded file");
-    println!("to be recovered later.");
+    app
 }
 
 // TODO: Add support for all postfixes here up to and including EiB
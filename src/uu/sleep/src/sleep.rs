@@ -9,7 +9,7 @@
 extern crate uucore;
 
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use clap::{App, Arg};
 
@@ -36,10 +36,33 @@ fn get_usage() -> String {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let mut duration = Duration::new(0, 0);
+    let mut status = 0;
+    for arg in matches.values_of(options::NUMBER).unwrap() {
+        match uucore::parse_time::from_str(arg) {
+            Ok(d) => duration = duration.checked_add(d).unwrap_or(uucore::parse_time::MAX),
+            Err(e) => {
+                show_error!("{}", e);
+                status = 1;
+            }
+        }
+    }
+    if status != 0 {
+        eprintln!("Try '{} --help' for more information.", executable!());
+        return status;
+    }
+
+    sleep(duration);
+
+    0
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .after_help(LONG_HELP)
         .arg(
             Arg::with_name(options::NUMBER)
@@ -50,25 +73,21 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .multiple(true)
                 .required(true),
         )
-        .get_matches_from(args);
-
-    if let Some(values) = matches.values_of(options::NUMBER) {
-        let numbers = values.collect();
-        sleep(numbers);
-    }
-
-    0
 }
 
-fn sleep(args: Vec<&str>) {
-    let sleep_dur =
-        args.iter().fold(
-            Duration::new(0, 0),
-            |result, arg| match uucore::parse_time::from_str(&arg[..]) {
-                Ok(m) => m + result,
-                Err(f) => crash!(1, "{}", f),
-            },
-        );
-
-    thread::sleep(sleep_dur);
+/// Sleep for `duration`, resuming after early wake-ups until it has elapsed.
+fn sleep(duration: Duration) {
+    match Instant::now().checked_add(duration) {
+        Some(deadline) => loop {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            thread::sleep(deadline - now);
+        },
+        // too far in the future to measure, e.g. `sleep infinity`
+        None => loop {
+            thread::sleep(duration);
+        },
+    }
 }
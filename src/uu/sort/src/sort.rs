@@ -25,9 +25,11 @@ use std::mem::replace;
 use std::path::Path;
 use twox_hash::XxHash64;
 use uucore::fs::is_stdin_interactive; // for Iterator::dedup()
+use uucore::i18n;
 
 static NAME: &str = "sort";
 static ABOUT: &str = "Display sorted concatenation of all FILE(s).";
+static LONG_HELP: &str = "With no FILE, or when FILE is -, read standard input.";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 
 static OPT_HUMAN_NUMERIC_SORT: &str = "human-numeric-sort";
@@ -168,15 +170,7 @@ impl<'a> Iterator for FileMerger<'a> {
 }
 
 fn get_usage() -> String {
-    format!(
-        "{0} {1}
-Usage:
- {0} [OPTION]... [FILE]...
-Write the sorted concatenation of all FILE(s) to standard output.
-Mandatory arguments for long options are mandatory for short options too.
-With no FILE, or when FILE is -, read standard input.",
-        NAME, VERSION
-    )
+    format!("{0} [OPTION]... [FILE]...", NAME)
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
@@ -184,10 +178,83 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
     let mut settings: Settings = Default::default();
 
-    let matches = App::new(executable!())
+    let about = i18n::tr(ABOUT);
+    let app = uu_app().about(&about[..]).usage(&usage[..]);
+    let matches = uucore::help::get_matches_from(app, args);
+
+    let mut files: Vec<String> = matches
+        .values_of(ARG_FILES)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    settings.mode = if matches.is_present(OPT_HUMAN_NUMERIC_SORT) {
+        SortMode::HumanNumeric
+    } else if matches.is_present(OPT_MONTH_SORT) {
+        SortMode::Month
+    } else if matches.is_present(OPT_NUMERIC_SORT) {
+        SortMode::Numeric
+    } else if matches.is_present(OPT_VERSION_SORT) {
+        SortMode::Version
+    } else {
+        SortMode::Default
+    };
+
+    if matches.is_present(OPT_DICTIONARY_ORDER) {
+        settings.transform_fns.push(remove_nondictionary_chars);
+    }
+
+    settings.merge = matches.is_present(OPT_MERGE);
+    settings.check = matches.is_present(OPT_CHECK);
+
+    if matches.is_present(OPT_IGNORE_CASE) {
+        settings.transform_fns.push(|s| s.to_uppercase());
+    }
+
+    if matches.is_present(OPT_IGNORE_BLANKS) {
+        settings.transform_fns.push(|s| s.trim_start().to_string());
+    }
+
+    settings.outfile = matches.value_of(OPT_OUTPUT).map(String::from);
+    settings.reverse = matches.is_present(OPT_REVERSE);
+    settings.stable = matches.is_present(OPT_STABLE);
+    settings.unique = matches.is_present(OPT_UNIQUE);
+
+    if matches.is_present(OPT_RANDOM) {
+        settings.random = matches.is_present(OPT_RANDOM);
+        settings.salt = get_rand_string();
+    }
+
+    //let mut files = matches.free;
+    if files.is_empty() {
+        /* if no file, default to stdin */
+        files.push("-".to_owned());
+    } else if settings.check && files.len() != 1 {
+        crash!(1, "sort: extra operand `{}' not allowed with -c", files[1])
+    }
+
+    settings.compare_fns.push(match settings.mode {
+        SortMode::Numeric => numeric_compare,
+        SortMode::HumanNumeric => human_numeric_size_compare,
+        SortMode::Month => month_compare,
+        SortMode::Version => version_compare,
+        SortMode::Default => default_compare,
+    });
+
+    if !settings.stable {
+        match settings.mode {
+            SortMode::Default => {}
+            _ => settings.compare_fns.push(default_compare),
+        }
+    }
+
+    exec(files, &mut settings)
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
+        .after_help(LONG_HELP)
         .arg(
             Arg::with_name(OPT_HUMAN_NUMERIC_SORT)
                 .short("h")
@@ -275,74 +342,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .help("output only the first of an equal run"),
         )
         .arg(Arg::with_name(ARG_FILES).multiple(true).takes_value(true))
-        .get_matches_from(args);
-
-    let mut files: Vec<String> = matches
-        .values_of(ARG_FILES)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    settings.mode = if matches.is_present(OPT_HUMAN_NUMERIC_SORT) {
-        SortMode::HumanNumeric
-    } else if matches.is_present(OPT_MONTH_SORT) {
-        SortMode::Month
-    } else if matches.is_present(OPT_NUMERIC_SORT) {
-        SortMode::Numeric
-    } else if matches.is_present(OPT_VERSION_SORT) {
-        SortMode::Version
-    } else {
-        SortMode::Default
-    };
-
-    if matches.is_present(OPT_DICTIONARY_ORDER) {
-        settings.transform_fns.push(remove_nondictionary_chars);
-    }
-
-    settings.merge = matches.is_present(OPT_MERGE);
-    settings.check = matches.is_present(OPT_CHECK);
-
-    if matches.is_present(OPT_IGNORE_CASE) {
-        settings.transform_fns.push(|s| s.to_uppercase());
-    }
-
-    if matches.is_present(OPT_IGNORE_BLANKS) {
-        settings.transform_fns.push(|s| s.trim_start().to_string());
-    }
-
-    settings.outfile = matches.value_of(OPT_OUTPUT).map(String::from);
-    settings.reverse = matches.is_present(OPT_REVERSE);
-    settings.stable = matches.is_present(OPT_STABLE);
-    settings.unique = matches.is_present(OPT_UNIQUE);
-
-    if matches.is_present(OPT_RANDOM) {
-        settings.random = matches.is_present(OPT_RANDOM);
-        settings.salt = get_rand_string();
-    }
-
-    //let mut files = matches.free;
-    if files.is_empty() {
-        /* if no file, default to stdin */
-        files.push("-".to_owned());
-    } else if settings.check && files.len() != 1 {
-        crash!(1, "sort: extra operand `{}' not allowed with -c", files[1])
-    }
-
-    settings.compare_fns.push(match settings.mode {
-        SortMode::Numeric => numeric_compare,
-        SortMode::HumanNumeric => human_numeric_size_compare,
-        SortMode::Month => month_compare,
-        SortMode::Version => version_compare,
-        SortMode::Default => default_compare,
-    });
-
-    if !settings.stable {
-        match settings.mode {
-            SortMode::Default => {}
-            _ => settings.compare_fns.push(default_compare),
-        }
-    }
-
-    exec(files, &mut settings)
 }
 
 fn exec(files: Vec<String>, settings: &mut Settings) -> i32 {
@@ -472,7 +471,7 @@ fn compare_by(a: &str, b: &str, settings: &Settings) -> Ordering {
 }
 
 fn default_compare(a: &str, b: &str) -> Ordering {
-    a.cmp(b)
+    i18n::collate(a, b)
 }
 
 fn get_leading_number(a: &str) -> &str {
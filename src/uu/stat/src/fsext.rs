@@ -9,38 +9,87 @@
 
 extern crate time;
 
+#[cfg(windows)]
+pub use self::modes::*;
 use self::time::Timespec;
-use std::time::UNIX_EPOCH;
+#[cfg(unix)]
 pub use uucore::libc::{
     c_int, mode_t, strerror, S_IFBLK, S_IFCHR, S_IFDIR, S_IFIFO, S_IFLNK, S_IFMT, S_IFREG,
     S_IFSOCK, S_IRGRP, S_IROTH, S_IRUSR, S_ISGID, S_ISUID, S_ISVTX, S_IWGRP, S_IWOTH, S_IWUSR,
     S_IXGRP, S_IXOTH, S_IXUSR,
 };
 
-pub trait BirthTime {
-    fn pretty_birth(&self) -> String;
-    fn birth(&self) -> String;
+use std::fs::Metadata;
+
+/// The Unix file types and permission bits, which Windows doesn't have
+/// but `winfs` makes up modes with.
+#[cfg(windows)]
+#[allow(non_camel_case_types)]
+mod modes {
+    pub type mode_t = u32;
+
+    pub const S_IFMT: mode_t = 0o170_000;
+    pub const S_IFSOCK: mode_t = 0o140_000;
+    pub const S_IFLNK: mode_t = 0o120_000;
+    pub const S_IFREG: mode_t = 0o100_000;
+    pub const S_IFBLK: mode_t = 0o060_000;
+    pub const S_IFDIR: mode_t = 0o040_000;
+    pub const S_IFCHR: mode_t = 0o020_000;
+    pub const S_IFIFO: mode_t = 0o010_000;
+
+    pub const S_ISUID: mode_t = 0o4000;
+    pub const S_ISGID: mode_t = 0o2000;
+    pub const S_ISVTX: mode_t = 0o1000;
+    pub const S_IRUSR: mode_t = 0o400;
+    pub const S_IWUSR: mode_t = 0o200;
+    pub const S_IXUSR: mode_t = 0o100;
+    pub const S_IRGRP: mode_t = 0o040;
+    pub const S_IWGRP: mode_t = 0o020;
+    pub const S_IXGRP: mode_t = 0o010;
+    pub const S_IROTH: mode_t = 0o004;
+    pub const S_IWOTH: mode_t = 0o002;
+    pub const S_IXOTH: mode_t = 0o001;
 }
 
-use std::fs::Metadata;
-impl BirthTime for Metadata {
-    fn pretty_birth(&self) -> String {
-        self.created()
-            .ok()
-            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|e| pretty_time(e.as_secs() as i64, i64::from(e.subsec_nanos())))
-            .unwrap_or_else(|| "-".to_owned())
-    }
-
-    fn birth(&self) -> String {
-        self.created()
-            .ok()
-            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|e| format!("{}", e.as_secs()))
-            .unwrap_or_else(|| "0".to_owned())
+/// When `path` was born, in seconds and nanoseconds since the Epoch, or
+/// `None` if its file system doesn't record it. Linux only tells through
+/// `statx(2)`; elsewhere the metadata carries it.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+pub fn birth_time(path: &str, follow: bool, _meta: &Metadata) -> Option<(i64, i64)> {
+    use uucore::libc::{statx, syscall, SYS_statx, AT_FDCWD, AT_SYMLINK_NOFOLLOW, STATX_BTIME};
+
+    let path = CString::new(path).ok()?;
+    let flags = if follow { 0 } else { AT_SYMLINK_NOFOLLOW };
+    let mut buf: statx = unsafe { mem::zeroed() };
+    // called through syscall(2), as the wrapper needs glibc 2.28; older
+    // kernels fail with ENOSYS and so know no birth time
+    let ret = unsafe {
+        syscall(
+            SYS_statx,
+            AT_FDCWD,
+            path.as_ptr(),
+            flags,
+            STATX_BTIME,
+            &mut buf as *mut statx,
+        )
+    };
+    if ret == 0 && buf.stx_mask & STATX_BTIME != 0 {
+        Some((buf.stx_btime.tv_sec, i64::from(buf.stx_btime.tv_nsec)))
+    } else {
+        None
     }
 }
 
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+pub fn birth_time(_path: &str, _follow: bool, meta: &Metadata) -> Option<(i64, i64)> {
+    use std::time::UNIX_EPOCH;
+
+    meta.created()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|e| (e.as_secs() as i64, i64::from(e.subsec_nanos())))
+}
+
 #[macro_export]
 macro_rules! has {
     ($mode:expr, $perm:expr) => {
@@ -60,6 +109,123 @@ pub fn pretty_time(sec: i64, nsec: i64) -> String {
     }
 }
 
+/// Seconds since the Epoch with the first `precision` digits of the
+/// fraction, as %X and friends print them: truncated, not rounded.
+pub fn epoch_sec(sec: i64, nsec: i64, precision: i32) -> String {
+    if precision <= 0 {
+        return format!("{}", sec);
+    }
+    // a time before the Epoch is printed as its distance from it
+    let (sign, sec, nsec) = if sec < 0 && nsec > 0 {
+        ("-", -(sec + 1), 1_000_000_000 - nsec)
+    } else {
+        ("", sec, nsec)
+    };
+    let mut frac = format!("{:09}", nsec);
+    let precision = precision as usize;
+    if precision < frac.len() {
+        frac.truncate(precision);
+    } else {
+        let zeros = precision - frac.len();
+        frac.extend(iter::repeat('0').take(zeros));
+    }
+    format!("{}{}.{}", sign, sec, frac)
+}
+
+/// The major number of a device ID.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn major(dev: u64) -> u64 {
+    ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff)
+}
+
+/// The minor number of a device ID.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn minor(dev: u64) -> u64 {
+    (dev & 0xff) | ((dev >> 12) & !0xff)
+}
+
+#[cfg(target_vendor = "apple")]
+pub fn major(dev: u64) -> u64 {
+    (dev >> 24) & 0xff
+}
+
+#[cfg(target_vendor = "apple")]
+pub fn minor(dev: u64) -> u64 {
+    dev & 0xff_ffff
+}
+
+#[cfg(target_os = "freebsd")]
+pub fn major(dev: u64) -> u64 {
+    ((dev >> 32) & 0xffff_ff00) | ((dev >> 8) & 0xff)
+}
+
+#[cfg(target_os = "freebsd")]
+pub fn minor(dev: u64) -> u64 {
+    ((dev >> 24) & 0xff00) | (dev & 0xffff_00ff)
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd"
+)))]
+pub fn major(dev: u64) -> u64 {
+    (dev >> 8) & 0xff
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd"
+)))]
+pub fn minor(dev: u64) -> u64 {
+    dev & 0xff
+}
+
+/// The SELinux security context of `path`, read from its extended
+/// attributes.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn security_context(path: &str, follow: bool) -> Result<String, IOError> {
+    use uucore::libc::{getxattr, lgetxattr, ssize_t};
+
+    let get = if follow { getxattr } else { lgetxattr };
+    let path = CString::new(path).map_err(|e| IOError::new(ErrorKind::InvalidInput, e))?;
+    let name = CString::new("security.selinux").unwrap();
+    let mut buf = vec![0_u8; 256];
+    loop {
+        let len: ssize_t = unsafe {
+            get(
+                path.as_ptr(),
+                name.as_ptr(),
+                buf.as_mut_ptr() as *mut _,
+                buf.len(),
+            )
+        };
+        if len >= 0 {
+            buf.truncate(len as usize);
+            break;
+        }
+        let err = IOError::last_os_error();
+        if err.raw_os_error() != Some(uucore::libc::ERANGE) {
+            return Err(err);
+        }
+        let new_len = buf.len() * 2;
+        buf.resize(new_len, 0);
+    }
+    // the attribute usually holds a terminating NUL
+    while buf.last() == Some(&0) {
+        buf.pop();
+    }
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn security_context(_path: &str, _follow: bool) -> Result<String, IOError> {
+    Err(IOError::from_raw_os_error(uucore::libc::ENOTSUP))
+}
+
 pub fn pretty_filetype<'a>(mode: mode_t, size: u64) -> &'a str {
     match mode & S_IFMT {
         S_IFREG => {
@@ -142,10 +308,25 @@ pub fn pretty_access(mode: mode_t) -> String {
 
 use std::borrow::Cow;
 use std::convert::{AsRef, From};
-use std::ffi::CString;
-use std::io::Error as IOError;
+#[cfg(unix)]
+use std::ffi::{CStr, CString};
+use std::io::{Error as IOError, ErrorKind};
+use std::iter;
 use std::mem;
 use std::path::Path;
+#[cfg(windows)]
+use std::ptr;
+
+#[cfg(windows)]
+use uucore::wide::{FromWide, ToWide};
+#[cfg(windows)]
+use winapi::shared::minwindef::{DWORD, MAX_PATH};
+#[cfg(windows)]
+use winapi::shared::ntdef::ULARGE_INTEGER;
+#[cfg(windows)]
+use winapi::um::fileapi::{
+    GetDiskFreeSpaceExW, GetDiskFreeSpaceW, GetVolumeInformationW, GetVolumePathNameW,
+};
 
 #[cfg(any(
     target_os = "linux",
@@ -179,6 +360,18 @@ use uucore::libc::statfs as statfs_fn;
 ))]
 use uucore::libc::statvfs as statfs_fn;
 
+/// What Windows tells of the volume a file is on.
+#[cfg(windows)]
+pub struct Sstatfs {
+    fs_name: String,
+    serial: u32,
+    max_component_len: u32,
+    cluster_size: u64,
+    total_clusters: u64,
+    free_clusters: u64,
+    avail_bytes: u64,
+}
+
 pub trait FsMeta {
     fn fs_type(&self) -> i64;
     fn iosize(&self) -> u64;
@@ -192,7 +385,15 @@ pub trait FsMeta {
     fn namelen(&self) -> u64;
 }
 
+#[cfg(unix)]
 impl FsMeta for Sstatfs {
+    // Linux keeps the fundamental block size in f_frsize, and the optimal
+    // transfer size in f_bsize
+    #[cfg(target_os = "linux")]
+    fn blksize(&self) -> i64 {
+        self.f_frsize as i64
+    }
+    #[cfg(not(target_os = "linux"))]
     fn blksize(&self) -> i64 {
         self.f_bsize as i64
     }
@@ -223,7 +424,7 @@ impl FsMeta for Sstatfs {
 
     #[cfg(target_os = "linux")]
     fn iosize(&self) -> u64 {
-        self.f_frsize as u64
+        self.f_bsize as u64
     }
     #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
     fn iosize(&self) -> u64 {
@@ -271,6 +472,117 @@ impl FsMeta for Sstatfs {
     }
 }
 
+#[cfg(windows)]
+impl FsMeta for Sstatfs {
+    // the magic numbers Linux has for the same file systems
+    fn fs_type(&self) -> i64 {
+        match self.fs_name.as_str() {
+            "NTFS" => 0x5346_544E,
+            "FAT" | "FAT32" => 0x4D44,
+            "exFAT" => 0x2011_BAB0,
+            "CDFS" => 0x9660,
+            "UDF" => 0x1501_3346,
+            _ => 0,
+        }
+    }
+    fn iosize(&self) -> u64 {
+        self.cluster_size
+    }
+    fn blksize(&self) -> i64 {
+        self.cluster_size as i64
+    }
+    fn total_blocks(&self) -> u64 {
+        self.total_clusters
+    }
+    fn free_blocks(&self) -> u64 {
+        self.free_clusters
+    }
+    fn avail_blocks(&self) -> u64 {
+        self.avail_bytes / self.cluster_size
+    }
+    // Windows doesn't count files against a limit
+    fn total_fnodes(&self) -> u64 {
+        0
+    }
+    fn free_fnodes(&self) -> u64 {
+        0
+    }
+    fn fsid(&self) -> u64 {
+        u64::from(self.serial)
+    }
+    fn namelen(&self) -> u64 {
+        u64::from(self.max_component_len)
+    }
+}
+
+/// The root of the volume `path` is on, a drive such as `C:\` or the
+/// folder another volume is mounted on.
+#[cfg(windows)]
+pub fn volume_path<P: AsRef<Path>>(path: P) -> Result<String, IOError> {
+    let path = path.as_ref().to_wide_null();
+    let mut root = [0u16; MAX_PATH + 1];
+    if unsafe { GetVolumePathNameW(path.as_ptr(), root.as_mut_ptr(), root.len() as DWORD) } == 0 {
+        return Err(IOError::last_os_error());
+    }
+    Ok(String::from_wide_null(&root))
+}
+
+#[cfg(windows)]
+pub fn statfs<P: AsRef<Path>>(path: P) -> Result<Sstatfs, String> {
+    // unlike statfs(2), GetVolumePathNameW doesn't mind a missing file
+    let path = path.as_ref();
+    path.metadata().map_err(|e| e.to_string())?;
+    let root = volume_path(path).map_err(|e| e.to_string())?.to_wide_null();
+
+    let mut fs_name = [0u16; MAX_PATH + 1];
+    let mut serial = 0;
+    let mut max_component_len = 0;
+    let mut flags = 0;
+    let mut sectors_per_cluster = 0;
+    let mut bytes_per_sector = 0;
+    let mut free_clusters = 0;
+    let mut total_clusters = 0;
+    let mut avail_bytes: ULARGE_INTEGER = unsafe { mem::zeroed() };
+    let ok = unsafe {
+        GetVolumeInformationW(
+            root.as_ptr(),
+            ptr::null_mut(),
+            0,
+            &mut serial,
+            &mut max_component_len,
+            &mut flags,
+            fs_name.as_mut_ptr(),
+            fs_name.len() as DWORD,
+        ) != 0
+            && GetDiskFreeSpaceW(
+                root.as_ptr(),
+                &mut sectors_per_cluster,
+                &mut bytes_per_sector,
+                &mut free_clusters,
+                &mut total_clusters,
+            ) != 0
+            && GetDiskFreeSpaceExW(
+                root.as_ptr(),
+                &mut avail_bytes,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            ) != 0
+    };
+    if !ok {
+        return Err(IOError::last_os_error().to_string());
+    }
+    Ok(Sstatfs {
+        fs_name: String::from_wide_null(&fs_name),
+        serial,
+        max_component_len,
+        cluster_size: u64::from(sectors_per_cluster) * u64::from(bytes_per_sector),
+        total_clusters: u64::from(total_clusters),
+        free_clusters: u64::from(free_clusters),
+        avail_bytes: unsafe { *avail_bytes.QuadPart() },
+    })
+}
+
+#[cfg(unix)]
 pub fn statfs<P: AsRef<Path>>(path: P) -> Result<Sstatfs, String>
 where
     Vec<u8>: From<P>,
@@ -283,9 +595,9 @@ where
                     0 => Ok(buffer),
                     _ => {
                         let errno = IOError::last_os_error().raw_os_error().unwrap_or(0);
-                        Err(CString::from_raw(strerror(errno))
-                            .into_string()
-                            .unwrap_or_else(|_| "Unknown Error".to_owned()))
+                        Err(CStr::from_ptr(strerror(errno))
+                            .to_string_lossy()
+                            .into_owned())
                     }
                 }
             }
@@ -296,6 +608,7 @@ where
 
 pub fn pretty_fstype<'a>(fstype: i64) -> Cow<'a, str> {
     match fstype {
+        0x5A3C_69F0 => "aafs".into(),
         0x6163_6673 => "acfs".into(),
         0xADF5 => "adfs".into(),
         0xADFF => "affs".into(),
@@ -303,14 +616,17 @@ pub fn pretty_fstype<'a>(fstype: i64) -> Cow<'a, str> {
         0x0904_1934 => "anon-inode FS".into(),
         0x6175_6673 => "aufs".into(),
         0x0187 => "autofs".into(),
+        0x1366_1366 => "balloon-kvm-fs".into(),
         0x4246_5331 => "befs".into(),
         0x6264_6576 => "bdevfs".into(),
         0x1BAD_FACE => "bfs".into(),
+        0x6C6F_6F70 => "binderfs".into(),
         0xCAFE_4A11 => "bpf_fs".into(),
         0x4249_4E4D => "binfmt_misc".into(),
         0x9123_683E => "btrfs".into(),
         0x7372_7279 => "btrfs_test".into(),
         0x00C3_6400 => "ceph".into(),
+        0x6367_7270 => "cgroup2fs".into(),
         0x0027_E0EB => "cgroupfs".into(),
         0xFF53_4D42 => "cifs".into(),
         0x7375_7245 => "coda".into(),
@@ -318,12 +634,17 @@ pub fn pretty_fstype<'a>(fstype: i64) -> Cow<'a, str> {
         0x6265_6570 => "configfs".into(),
         0x28CD_3D45 => "cramfs".into(),
         0x453D_CD28 => "cramfs-wend".into(),
+        0x6464_6178 => "daxfs".into(),
         0x6462_6720 => "debugfs".into(),
         0x1373 => "devfs".into(),
+        0x454D_444D => "devmem".into(),
         0x1CD1 => "devpts".into(),
+        0x444D_4142 => "dma-buf-fs".into(),
         0xF15F => "ecryptfs".into(),
         0xDE5E_81E4 => "efivarfs".into(),
         0x0041_4A53 => "efs".into(),
+        0xE0F5_E1E2 => "erofs".into(),
+        0x2011_BAB0 => "exfat".into(),
         0x5DF5 => "exofs".into(),
         0x137D => "ext".into(),
         0xEF53 => "ext2/ext3".into(),
@@ -373,15 +694,19 @@ pub fn pretty_fstype<'a>(fstype: i64) -> Cow<'a, str> {
         0x794C_7630 => "overlayfs".into(),
         0xAAD7_AAEA => "panfs".into(),
         0x5049_5045 => "pipefs".into(),
+        0xC757_1590 => "ppc-cmm-fs".into(),
         0x7C7C_6673 => "prl_fs".into(),
         0x9FA0 => "proc".into(),
         0x6165_676C => "pstorefs".into(),
         0x002F => "qnx4".into(),
         0x6819_1122 => "qnx6".into(),
         0x8584_58F6 => "ramfs".into(),
+        0x0765_5821 => "rdt".into(),
         0x5265_4973 => "reiserfs".into(),
         0x7275 => "romfs".into(),
         0x6759_6969 => "rpc_pipefs".into(),
+        0x5DCA_2DF5 => "sdcardfs".into(),
+        0x5345_434D => "secretmem".into(),
         0x7363_6673 => "securityfs".into(),
         0xF97C_FF8C => "selinux".into(),
         0x4341_5D53 => "smackfs".into(),
@@ -401,6 +726,7 @@ pub fn pretty_fstype<'a>(fstype: i64) -> Cow<'a, str> {
         0x5419_0100 => "ufs".into(),
         0x9FA2 => "usbdevfs".into(),
         0x0102_1997 => "v9fs".into(),
+        0x786F_4256 => "vboxsf".into(),
         0xBACB_ACBC => "vmhgfs".into(),
         0xA501_FCF5 => "vxfs".into(),
         0x565A_4653 => "vzfs".into(),
@@ -409,7 +735,10 @@ pub fn pretty_fstype<'a>(fstype: i64) -> Cow<'a, str> {
         0x012F_F7B4 => "xenix".into(),
         0x5846_5342 => "xfs".into(),
         0x012F_D16D => "xia".into(),
+        0x0033 => "z3fold".into(),
         0x2FC1_2FC1 => "zfs".into(),
+        0x5A4F_4653 => "zonefs".into(),
+        0x5829_5829 => "zsmalloc".into(),
         other => format!("UNKNOWN ({:#x})", other).into(),
     }
 }
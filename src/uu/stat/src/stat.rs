@@ -13,13 +13,23 @@ pub use crate::fsext::*;
 
 #[macro_use]
 extern crate uucore;
-use uucore::entries;
+#[cfg(unix)]
+use uucore::entries::{group_name, user_name};
+use uucore::error_message;
+use uucore::json;
+use uucore::quoting::{quote, QuotingStyle};
+#[cfg(windows)]
+use uucore::winfs::{group_name, read_link, user_name, Metadata};
 
 use clap::{App, Arg, ArgMatches};
 use std::borrow::Cow;
 use std::convert::AsRef;
+use std::env;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+#[cfg(unix)]
+use std::fs::{read_link, Metadata};
+use std::io::{self, BufRead, BufReader};
+#[cfg(unix)]
 use std::os::unix::fs::{FileTypeExt, MetadataExt};
 use std::path::Path;
 use std::{cmp, fs, iter};
@@ -91,6 +101,7 @@ pub mod options {
     pub static DEREFERENCE: &str = "dereference";
     pub static FILE_SYSTEM: &str = "file-system";
     pub static FORMAT: &str = "format";
+    pub static JSON: &str = "json";
     pub static PRINTF: &str = "printf";
     pub static TERSE: &str = "terse";
 }
@@ -123,6 +134,8 @@ pub enum Token {
         flag: u8,
         width: usize,
         precision: i32,
+        // 'H' or 'L' before 'd' or 'r', asking for the major or minor number
+        modifier: Option<char>,
         format: char,
     },
 }
@@ -212,8 +225,10 @@ pub struct Stater {
     follow: bool,
     showfs: bool,
     from_user: bool,
+    json: bool,
     files: Vec<String>,
     mount_list: Option<Vec<String>>,
+    quoting_style: QuotingStyle,
     default_tokens: Vec<Token>,
     default_dev_tokens: Vec<Token>,
 }
@@ -367,7 +382,8 @@ impl Stater {
                             ' ' => flag |= F_SPACE,
                             '+' => flag |= F_SIGN,
                             '\'' => flag |= F_GROUP,
-                            'I' => unimplemented!(),
+                            // locale digits aren't supported, so plain ones are used
+                            'I' => (),
                             _ => break,
                         }
                         i += 1;
@@ -376,6 +392,7 @@ impl Stater {
 
                     let mut width = 0_usize;
                     let mut precision = -1_i32;
+                    let mut bare_dot = false;
                     let mut j = i;
 
                     if let Some((field_width, offset)) = fmtstr[j..].scan_num::<usize>() {
@@ -395,16 +412,34 @@ impl Stater {
                                 }
                                 j += offset;
                             }
-                            None => precision = 0,
+                            None => {
+                                precision = 0;
+                                bare_dot = true;
+                            }
                         }
                         check_bound!(fmtstr, bound, old, j);
                     }
 
+                    let mut modifier = None;
+                    if (chars[j] == 'H' || chars[j] == 'L')
+                        && j + 1 < bound
+                        && (chars[j + 1] == 'd' || chars[j + 1] == 'r')
+                    {
+                        modifier = Some(chars[j]);
+                        j += 1;
+                    }
+
                     i = j;
+                    // a bare '.' on a time in seconds asks for all nine digits
+                    // of its fraction
+                    if bare_dot && "WXYZ".contains(chars[i]) {
+                        precision = 9;
+                    }
                     tokens.push(Token::Directive {
                         width,
                         flag,
                         precision,
+                        modifier,
                         format: chars[i],
                     })
                 }
@@ -441,6 +476,7 @@ impl Stater {
                             'f' => tokens.push(Token::Char('\x0C')),
                             'n' => tokens.push(Token::Char('\n')),
                             'r' => tokens.push(Token::Char('\r')),
+                            't' => tokens.push(Token::Char('\t')),
                             'v' => tokens.push(Token::Char('\x0B')),
                             c => {
                                 show_warning!("unrecognized escape '\\{}'", c);
@@ -466,35 +502,47 @@ impl Stater {
             .map(|v| v.map(ToString::to_string).collect())
             .unwrap_or_default();
 
-        let fmtstr = if matches.is_present(options::PRINTF) {
-            matches
-                .value_of(options::PRINTF)
-                .expect("Invalid format string")
+        // the later of --format and --printf wins, and an empty format is
+        // still the user's
+        let use_printf = matches.index_of(options::PRINTF) > matches.index_of(options::FORMAT);
+        let fmtstr = if use_printf {
+            matches.value_of(options::PRINTF)
         } else {
-            matches.value_of(options::FORMAT).unwrap_or("")
+            matches.value_of(options::FORMAT)
         };
 
-        let use_printf = matches.is_present(options::PRINTF);
         let terse = matches.is_present(options::TERSE);
         let showfs = matches.is_present(options::FILE_SYSTEM);
 
-        let default_tokens = if fmtstr.is_empty() {
-            Stater::generate_tokens(&Stater::default_fmt(showfs, terse, false), use_printf).unwrap()
-        } else {
-            Stater::generate_tokens(&fmtstr, use_printf)?
+        let default_tokens = match fmtstr {
+            Some(fmtstr) => Stater::generate_tokens(fmtstr, use_printf)?,
+            None => Stater::generate_tokens(&Stater::default_fmt(showfs, terse, false), use_printf)
+                .unwrap(),
         };
         let default_dev_tokens =
             Stater::generate_tokens(&Stater::default_fmt(showfs, terse, true), use_printf).unwrap();
 
+        // %N quotes names the way QUOTING_STYLE asks, as ls does
+        let quoting_style = match env::var("QUOTING_STYLE") {
+            Ok(name) => name.parse().unwrap_or_else(|_| {
+                show_info!(
+                    "ignoring invalid value of environment variable QUOTING_STYLE: '{}'",
+                    name
+                );
+                QuotingStyle::ShellEscapeAlways
+            }),
+            Err(_) => QuotingStyle::ShellEscapeAlways,
+        };
+
         let mount_list = if showfs {
             // mount points aren't displayed when showing filesystem information
             None
         } else {
-            let reader = BufReader::new(
-                File::open(MOUNT_INFO).unwrap_or_else(|_| panic!("Failed to read {}", MOUNT_INFO)),
-            );
-            let mut mount_list = reader
-                .lines()
+            // without a mount table, %m falls back to '?'
+            let mut mount_list = File::open(MOUNT_INFO)
+                .map(|f| BufReader::new(f).lines())
+                .into_iter()
+                .flatten()
                 .filter_map(Result::ok)
                 .filter_map(|line| line.split_whitespace().nth(1).map(ToOwned::to_owned))
                 .collect::<Vec<String>>();
@@ -507,14 +555,17 @@ impl Stater {
         Ok(Stater {
             follow: matches.is_present(options::DEREFERENCE),
             showfs,
-            from_user: !fmtstr.is_empty(),
+            from_user: fmtstr.is_some(),
+            json: matches.is_present(options::JSON),
             files,
             default_tokens,
             default_dev_tokens,
             mount_list,
+            quoting_style,
         })
     }
 
+    #[cfg(unix)]
     fn find_mount_point<P: AsRef<Path>>(&self, p: P) -> Option<String> {
         let path = match p.as_ref().canonicalize() {
             Ok(s) => s,
@@ -530,6 +581,12 @@ impl Stater {
         None
     }
 
+    // Windows knows which volume a file is on without a mount table
+    #[cfg(windows)]
+    fn find_mount_point<P: AsRef<Path>>(&self, p: P) -> Option<String> {
+        volume_path(p).ok()
+    }
+
     fn exec(&self) -> i32 {
         let mut ret = 0;
         for f in &self.files {
@@ -539,21 +596,17 @@ impl Stater {
     }
 
     fn do_stat(&self, file: &str) -> i32 {
+        let mut status = 0;
         if !self.showfs {
-            let result = if self.follow {
-                fs::metadata(file)
-            } else {
-                fs::symlink_metadata(file)
-            };
-            match result {
+            match get_metadata(file, self.follow) {
+                Ok(meta) if self.json => println!("{}", self.file_record(file, &meta)),
                 Ok(meta) => {
                     let ftype = meta.file_type();
-                    let tokens =
-                        if self.from_user || !(ftype.is_char_device() || ftype.is_block_device()) {
-                            &self.default_tokens
-                        } else {
-                            &self.default_dev_tokens
-                        };
+                    let tokens = if self.from_user || !is_device(&ftype) {
+                        &self.default_tokens
+                    } else {
+                        &self.default_dev_tokens
+                    };
 
                     for t in tokens.iter() {
                         match *t {
@@ -562,6 +615,7 @@ impl Stater {
                                 flag,
                                 width,
                                 precision,
+                                modifier,
                                 format,
                             } => {
                                 let arg: String;
@@ -578,6 +632,22 @@ impl Stater {
                                         arg = pretty_access(meta.mode() as mode_t);
                                         otype = OutputType::Str;
                                     }
+                                    // SELinux security context string
+                                    'C' => {
+                                        arg = match security_context(file, self.follow) {
+                                            Ok(context) => context,
+                                            Err(e) => {
+                                                show_info!(
+                                                    "failed to get security context of '{}': {}",
+                                                    file,
+                                                    error_message(&e)
+                                                );
+                                                status = 1;
+                                                "?".to_owned()
+                                            }
+                                        };
+                                        otype = OutputType::Str;
+                                    }
                                     // number of blocks allocated (see %B)
                                     'b' => {
                                         arg = format!("{}", meta.blocks());
@@ -593,9 +663,13 @@ impl Stater {
                                         otype = OutputType::Unsigned;
                                     }
 
-                                    // device number in decimal
+                                    // device number in decimal, or its major or minor number
                                     'd' => {
-                                        arg = format!("{}", meta.dev());
+                                        arg = match modifier {
+                                            Some('H') => format!("{}", major(meta.dev())),
+                                            Some('L') => format!("{}", minor(meta.dev())),
+                                            _ => format!("{}", meta.dev()),
+                                        };
                                         otype = OutputType::Unsigned;
                                     }
                                     // device number in hex
@@ -621,8 +695,8 @@ impl Stater {
                                     }
                                     // group name of owner
                                     'G' => {
-                                        arg = entries::gid2grp(meta.gid())
-                                            .unwrap_or_else(|_| "UNKNOWN".to_owned());
+                                        arg = group_name(&meta)
+                                            .unwrap_or_else(|| "UNKNOWN".to_owned());
                                         otype = OutputType::Str;
                                     }
                                     // number of hard links
@@ -638,7 +712,9 @@ impl Stater {
 
                                     // mount point
                                     'm' => {
-                                        arg = self.find_mount_point(file).unwrap();
+                                        arg = self
+                                            .find_mount_point(file)
+                                            .unwrap_or_else(|| "?".to_owned());
                                         otype = OutputType::Str;
                                     }
 
@@ -649,22 +725,27 @@ impl Stater {
                                     }
                                     // quoted file name with dereference if symbolic link
                                     'N' => {
-                                        if ftype.is_symlink() {
-                                            let dst = match fs::read_link(file) {
-                                                Ok(path) => path,
+                                        let style = self.quoting_style;
+                                        arg = if ftype.is_symlink() {
+                                            match read_link(file) {
+                                                Ok(dst) => format!(
+                                                    "{} -> {}",
+                                                    quote(file, style),
+                                                    quote(dst, style)
+                                                ),
                                                 Err(e) => {
-                                                    println!("{}", e);
-                                                    return 1;
+                                                    show_info!(
+                                                        "cannot read symbolic link '{}': {}",
+                                                        file,
+                                                        error_message(&e)
+                                                    );
+                                                    status = 1;
+                                                    quote(file, style)
                                                 }
-                                            };
-                                            arg = format!(
-                                                "`{}' -> `{}'",
-                                                file,
-                                                dst.to_string_lossy()
-                                            );
+                                            }
                                         } else {
-                                            arg = format!("`{}'", file);
-                                        }
+                                            quote(file, style)
+                                        };
                                         otype = OutputType::Str;
                                     }
                                     // optimal I/O transfer size hint
@@ -672,6 +753,20 @@ impl Stater {
                                         arg = format!("{}", meta.blksize());
                                         otype = OutputType::Unsigned;
                                     }
+                                    // device type in decimal, or its major or minor number
+                                    'r' => {
+                                        arg = match modifier {
+                                            Some('H') => format!("{}", major(meta.rdev())),
+                                            Some('L') => format!("{}", minor(meta.rdev())),
+                                            _ => format!("{}", meta.rdev()),
+                                        };
+                                        otype = OutputType::Unsigned;
+                                    }
+                                    // device type in hex
+                                    'R' => {
+                                        arg = format!("{:x}", meta.rdev());
+                                        otype = OutputType::UnsignedHex;
+                                    }
                                     // total size, in bytes
                                     's' => {
                                         arg = format!("{}", meta.len());
@@ -680,13 +775,13 @@ impl Stater {
                                     // major device type in hex, for character/block device special
                                     // files
                                     't' => {
-                                        arg = format!("{:x}", meta.rdev() >> 8);
+                                        arg = format!("{:x}", major(meta.rdev()));
                                         otype = OutputType::UnsignedHex;
                                     }
                                     // minor device type in hex, for character/block device special
                                     // files
                                     'T' => {
-                                        arg = format!("{:x}", meta.rdev() & 0xff);
+                                        arg = format!("{:x}", minor(meta.rdev()));
                                         otype = OutputType::UnsignedHex;
                                     }
                                     // user ID of owner
@@ -696,20 +791,24 @@ impl Stater {
                                     }
                                     // user name of owner
                                     'U' => {
-                                        arg = entries::uid2usr(meta.uid())
-                                            .unwrap_or_else(|_| "UNKNOWN".to_owned());
+                                        arg = user_name(&meta)
+                                            .unwrap_or_else(|| "UNKNOWN".to_owned());
                                         otype = OutputType::Str;
                                     }
 
                                     // time of file birth, human-readable; - if unknown
                                     'w' => {
-                                        arg = meta.pretty_birth();
+                                        arg = birth_time(file, self.follow, &meta)
+                                            .map(|(sec, nsec)| pretty_time(sec, nsec))
+                                            .unwrap_or_else(|| "-".to_owned());
                                         otype = OutputType::Str;
                                     }
 
                                     // time of file birth, seconds since Epoch; 0 if unknown
                                     'W' => {
-                                        arg = meta.birth();
+                                        let (sec, nsec) =
+                                            birth_time(file, self.follow, &meta).unwrap_or((0, 0));
+                                        arg = epoch_sec(sec, nsec, precision);
                                         otype = OutputType::Integer;
                                     }
 
@@ -720,7 +819,7 @@ impl Stater {
                                     }
                                     // time of last access, seconds since Epoch
                                     'X' => {
-                                        arg = format!("{}", meta.atime());
+                                        arg = epoch_sec(meta.atime(), meta.atime_nsec(), precision);
                                         otype = OutputType::Integer;
                                     }
                                     // time of last data modification, human-readable
@@ -730,8 +829,8 @@ impl Stater {
                                     }
                                     // time of last data modification, seconds since Epoch
                                     'Y' => {
-                                        arg = format!("{}", meta.mtime());
-                                        otype = OutputType::Str;
+                                        arg = epoch_sec(meta.mtime(), meta.mtime_nsec(), precision);
+                                        otype = OutputType::Integer;
                                     }
                                     // time of last status change, human-readable
                                     'z' => {
@@ -740,7 +839,7 @@ impl Stater {
                                     }
                                     // time of last status change, seconds since Epoch
                                     'Z' => {
-                                        arg = format!("{}", meta.ctime());
+                                        arg = epoch_sec(meta.ctime(), meta.ctime_nsec(), precision);
                                         otype = OutputType::Integer;
                                     }
 
@@ -749,18 +848,25 @@ impl Stater {
                                         otype = OutputType::Unknown;
                                     }
                                 }
-                                print_it(&arg, otype, flag, width, precision);
+                                if "WXYZ".contains(format) {
+                                    // the precision went into the fraction, which can't be
+                                    // grouped
+                                    print_it(&arg, otype, flag & !F_GROUP, width, -1);
+                                } else {
+                                    print_it(&arg, otype, flag, width, precision);
+                                }
                             }
                         }
                     }
                 }
                 Err(e) => {
-                    show_info!("cannot stat '{}': {}", file, e);
+                    show_info!("cannot stat '{}': {}", file, error_message(&e));
                     return 1;
                 }
             }
         } else {
             match statfs(file) {
+                Ok(meta) if self.json => println!("{}", fs_record(file, &meta)),
                 Ok(meta) => {
                     let tokens = &self.default_tokens;
 
@@ -772,6 +878,7 @@ impl Stater {
                                 width,
                                 precision,
                                 format,
+                                ..
                             } => {
                                 let arg: String;
                                 let otype: OutputType;
@@ -853,7 +960,57 @@ impl Stater {
                 }
             }
         }
-        0
+        status
+    }
+
+    /// The `--json` record of `file`, with the fields of the directives
+    /// that apply to every file.
+    fn file_record(&self, file: &str, meta: &Metadata) -> json::Object {
+        let mut record = json::Object::new();
+        let user = user_name(meta);
+        let group = group_name(meta);
+        record
+            .string("name", file)
+            .string("type", json::file_type(&meta.file_type()))
+            .number("size", meta.len())
+            .number("blocks", meta.blocks())
+            .number("block_size", 512)
+            .number("io_block", meta.blksize())
+            .number("mode", meta.mode() & 0o7777)
+            .string("permissions", &pretty_access(meta.mode() as mode_t)[1..])
+            .number("uid", meta.uid());
+        match user {
+            Some(user) => record.string("user", &user),
+            None => record.null("user"),
+        };
+        record.number("gid", meta.gid());
+        match group {
+            Some(group) => record.string("group", &group),
+            None => record.null("group"),
+        };
+        record
+            .number("device", meta.dev())
+            .number("inode", meta.ino())
+            .number("links", meta.nlink())
+            .number("device_type", meta.rdev())
+            .timestamp("atime", meta.atime(), meta.atime_nsec() as u32)
+            .timestamp("mtime", meta.mtime(), meta.mtime_nsec() as u32)
+            .timestamp("ctime", meta.ctime(), meta.ctime_nsec() as u32);
+        match birth_time(file, self.follow, meta) {
+            Some((sec, nsec)) => record.timestamp("birth", sec, nsec as u32),
+            None => record.null("birth"),
+        };
+        match self.find_mount_point(file) {
+            Some(mount_point) => record.string("mount_point", &mount_point),
+            None => record.null("mount_point"),
+        };
+        match read_link(file) {
+            Ok(target) if meta.file_type().is_symlink() => {
+                record.string("target", &target.to_string_lossy())
+            }
+            _ => record.null("target"),
+        };
+        record
     }
 
     // taken from coreutils/src/stat.c
@@ -887,6 +1044,52 @@ impl Stater {
     }
 }
 
+#[cfg(unix)]
+fn get_metadata(file: &str, follow: bool) -> io::Result<Metadata> {
+    if follow {
+        fs::metadata(file)
+    } else {
+        fs::symlink_metadata(file)
+    }
+}
+
+#[cfg(windows)]
+fn get_metadata(file: &str, follow: bool) -> io::Result<Metadata> {
+    Metadata::new(file, follow)
+}
+
+/// Whether a file of type `ftype` is shown in the default format for
+/// devices, which Windows doesn't have.
+#[cfg(unix)]
+fn is_device(ftype: &fs::FileType) -> bool {
+    ftype.is_char_device() || ftype.is_block_device()
+}
+
+#[cfg(windows)]
+fn is_device(_ftype: &fs::FileType) -> bool {
+    false
+}
+
+/// The `--json` record of the file system `file` is on, with the fields of
+/// the directives of `--file-system`.
+fn fs_record<M: FsMeta>(file: &str, meta: &M) -> json::Object {
+    let mut record = json::Object::new();
+    record
+        .string("name", file)
+        .string("fsid", &format!("{:x}", meta.fsid()))
+        .string("type", &pretty_fstype(meta.fs_type()))
+        .number("type_id", meta.fs_type())
+        .number("name_max", meta.namelen())
+        .number("block_size", meta.blksize())
+        .number("io_size", meta.iosize())
+        .number("blocks", meta.total_blocks())
+        .number("free_blocks", meta.free_blocks())
+        .number("available_blocks", meta.avail_blocks())
+        .number("inodes", meta.total_fnodes())
+        .number("free_inodes", meta.free_fnodes());
+    record
+}
+
 fn get_usage() -> String {
     format!("{0} [OPTION]... FILE...", executable!())
 }
@@ -901,8 +1104,10 @@ The valid format sequences for files (without --file-system):
   %b   number of blocks allocated (see %B)
   %B   the size in bytes of each block reported by %b
   %C   SELinux security context string
-  %d   device number in decimal
-  %D   device number in hex
+  %d   device number in decimal (st_dev)
+  %D   device number in hex (st_dev)
+  %Hd  major device number in decimal
+  %Ld  minor device number in decimal
   %f   raw mode in hex
   %F   file type
   %g   group ID of owner
@@ -914,6 +1119,10 @@ The valid format sequences for files (without --file-system):
   %N   quoted file name with dereference if symbolic link
   %o   optimal I/O transfer size hint
   %s   total size, in bytes
+  %r   device type in decimal (st_rdev)
+  %R   device type in hex (st_rdev)
+  %Hr  major device type in decimal, for character/block device special files
+  %Lr  minor device type in decimal, for character/block device special files
   %t   major device type in hex, for character/block device special files
   %T   minor device type in hex, for character/block device special files
   %u   user ID of owner
@@ -953,11 +1162,27 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
     let long_usage = get_long_usage();
 
-    let matches = App::new(executable!())
+    let app = uu_app().usage(&usage[..]).after_help(&long_usage[..]);
+    let matches = uucore::help::get_matches_from(app, args);
+
+    if !matches.is_present(ARG_FILES) {
+        show_usage_error!("missing operand");
+        return 1;
+    }
+
+    match Stater::new(matches) {
+        Ok(stater) => stater.exec(),
+        Err(e) => {
+            show_info!("{}", e);
+            1
+        }
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
-        .after_help(&long_usage[..])
         .arg(
             Arg::with_name(options::DEREFERENCE)
                 .short("L")
@@ -996,19 +1221,16 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             if you want a newline, include \n in FORMAT",
                 ),
         )
+        .arg(
+            Arg::with_name(options::JSON)
+                .long(options::JSON)
+                .conflicts_with_all(&[options::FORMAT, options::PRINTF, options::TERSE])
+                .help("print a JSON object for each FILE, one per line"),
+        )
         .arg(
             Arg::with_name(ARG_FILES)
                 .multiple(true)
                 .takes_value(true)
                 .min_values(1),
         )
-        .get_matches_from(args);
-
-    match Stater::new(matches) {
-        Ok(stater) => stater.exec(),
-        Err(e) => {
-            show_info!("{}", e);
-            1
-        }
-    }
 }
@@ -1,36 +1,101 @@
-// spell-checker:ignore (ToDO) dylib libstdbuf deps liblibstdbuf
+// spell-checker:ignore (ToDO) dylib libstdbuf deps liblibstdbuf dragonfly
 
 use std::env;
 use std::fs;
 use std::path::Path;
+use std::process::Command;
 
-#[cfg(not(any(target_vendor = "apple", target_os = "windows")))]
-mod platform {
-    pub const DYLIB_EXT: &str = ".so";
-}
-
-#[cfg(any(target_vendor = "apple"))]
-mod platform {
-    pub const DYLIB_EXT: &str = ".dylib";
-}
-
-#[cfg(target_os = "windows")]
-mod platform {
-    pub const DYLIB_EXT: &str = ".dll";
+/// The extension of dynamic libraries on `target_os`, if it has a way to
+/// preload one into a program.
+fn dylib_ext(target_os: &str) -> Option<&'static str> {
+    match target_os {
+        "macos" | "ios" => Some("dylib"),
+        "linux" | "android" | "freebsd" | "netbsd" | "openbsd" | "dragonfly" => Some("so"),
+        _ => None,
+    }
 }
 
 fn main() {
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("Could not find manifest dir");
     let profile = env::var("PROFILE").expect("Could not determine profile");
-
+    let target = env::var("TARGET").expect("Could not determine target");
+    let target_os = env::var("CARGO_CFG_TARGET_OS").expect("Could not determine target OS");
     let out_dir = env::var("OUT_DIR").unwrap();
-    let libstdbuf = format!(
-        "{}/../../../{}/{}/deps/liblibstdbuf{}",
-        manifest_dir,
-        env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string()),
-        profile,
-        platform::DYLIB_EXT
-    );
 
-    fs::copy(libstdbuf, Path::new(&out_dir).join("libstdbuf.so")).unwrap();
+    let libstdbuf_dir = Path::new(&manifest_dir).join("src").join("libstdbuf");
+    println!("cargo:rerun-if-changed={}", libstdbuf_dir.display());
+
+    let ext = match dylib_ext(&target_os) {
+        Some(ext) => ext,
+        // stdbuf tells it can't work here, without a library to preload
+        None => return,
+    };
+
+    // libstdbuf is built on its own rather than as a dependency, which would
+    // build it for the host that runs build scripts instead of the target. It
+    // has a target directory of its own, within the one this build uses,
+    // since cargo keeps that one locked while build scripts run.
+    let target_dir = Path::new(&out_dir).join("libstdbuf");
+    let cargo_path = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut cargo = Command::new(&cargo_path);
+    cargo
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(libstdbuf_dir.join("Cargo.toml"))
+        .arg("--target")
+        .arg(&target)
+        .arg("--target-dir")
+        .arg(&target_dir);
+    if profile == "release" {
+        cargo.arg("--release");
+    }
+    // the settings of this build are only known from the environment: cargo
+    // doesn't tell build scripts the options it was given
+    if env::var("CARGO_NET_OFFLINE").map_or(false, |offline| offline == "true") {
+        cargo.arg("--offline");
+    }
+    // the lock file of the workspace was brought up to date for this build
+    // already, and mustn't change while it goes on
+    if libstdbuf_dir
+        .ancestors()
+        .any(|dir| dir.join("Cargo.lock").is_file())
+    {
+        cargo.arg("--locked");
+    }
+    if let Ok(flags) = env::var("CARGO_ENCODED_RUSTFLAGS") {
+        cargo.env("CARGO_ENCODED_RUSTFLAGS", flags);
+    }
+    let profile_var = if profile == "release" {
+        "CARGO_PROFILE_RELEASE"
+    } else {
+        "CARGO_PROFILE_DEV"
+    };
+    if let Ok(opt_level) = env::var("OPT_LEVEL") {
+        cargo.env(format!("{}_OPT_LEVEL", profile_var), opt_level);
+    }
+    if let Ok(debug) = env::var("DEBUG") {
+        cargo.env(format!("{}_DEBUG", profile_var), debug);
+    }
+    let status = cargo.status().unwrap_or_else(|e| {
+        panic!(
+            "Could not run {} to build libstdbuf, which stdbuf preloads: {}",
+            cargo_path, e
+        )
+    });
+    if !status.success() {
+        panic!(
+            "Could not build libstdbuf, which stdbuf preloads ({:?} exited with {})",
+            cargo, status
+        );
+    }
+
+    let libstdbuf = target_dir
+        .join(&target)
+        .join(&profile)
+        .join(format!("liblibstdbuf.{}", ext));
+    fs::copy(
+        libstdbuf,
+        Path::new(&out_dir).join(format!("libstdbuf.{}", ext)),
+    )
+    .unwrap();
 }
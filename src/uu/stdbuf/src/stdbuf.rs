@@ -5,12 +5,13 @@
 // * For the full copyright and license information, please view the LICENSE
 // * file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) tempdir dyld dylib dragonflybsd optgrps libstdbuf
+// spell-checker:ignore (ToDO) tempdir dyld dylib dragonfly optgrps libstdbuf libexec
 
 #[macro_use]
 extern crate uucore;
 
 use getopts::{Matches, Options};
+use std::env;
 use std::fs::File;
 use std::io::{self, Write};
 use std::os::unix::process::ExitStatusExt;
@@ -18,11 +19,29 @@ use std::path::PathBuf;
 use std::process::Command;
 use tempfile::tempdir;
 use tempfile::TempDir;
+use uucore::coreopts::CoreOptions;
+use uucore::help::{self, Help};
 
 static NAME: &str = "stdbuf";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
+static SYNTAX: &str = "OPTION... COMMAND";
+static SUMMARY: &str = "Run COMMAND, with modified buffering operations for its standard streams.";
+static LONG_HELP: &str = "If MODE is 'L' the corresponding stream will be line buffered.
+This option is invalid with standard input.
 
-const STDBUF_INJECT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/libstdbuf.so"));
+If MODE is '0' the corresponding stream will be unbuffered.
+
+Otherwise MODE is a number which may be followed by one of the following:
+
+KB 1000, K 1024, MB 1000*1000, M 1024*1024, and so on for G, T, P, E, Z, Y.
+In this case the corresponding stream will be fully buffered with the buffer size set to \
+MODE bytes.
+
+NOTE: If COMMAND adjusts the buffering of its standard streams ('tee' does for e.g.) then \
+that will override corresponding settings changed by 'stdbuf'.
+Also some filters (like 'dd' and 'cat' etc.) don't use streams for I/O, \
+and are thus unaffected by 'stdbuf' settings.
+";
 
 enum BufferType {
     Default,
@@ -47,55 +66,56 @@ enum OkMsg {
     Version,
 }
 
+/// How libstdbuf gets into the command on a platform.
+struct Preload {
+    /// the variable the dynamic linker takes libraries to preload from
+    var: &'static str,
+    /// the extension of dynamic libraries
+    ext: &'static str,
+    /// libstdbuf, as built for the platform
+    lib: &'static [u8],
+}
+
 #[cfg(any(
     target_os = "linux",
+    target_os = "android",
     target_os = "freebsd",
     target_os = "netbsd",
-    target_os = "dragonflybsd"
+    target_os = "openbsd",
+    target_os = "dragonfly"
 ))]
-fn preload_strings() -> (&'static str, &'static str) {
-    ("LD_PRELOAD", "so")
-}
+const PRELOAD: Option<Preload> = Some(Preload {
+    var: "LD_PRELOAD",
+    ext: "so",
+    lib: include_bytes!(concat!(env!("OUT_DIR"), "/libstdbuf.so")),
+});
 
-#[cfg(target_vendor = "apple")]
-fn preload_strings() -> (&'static str, &'static str) {
-    ("DYLD_LIBRARY_PATH", "dylib")
-}
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const PRELOAD: Option<Preload> = Some(Preload {
+    var: "DYLD_INSERT_LIBRARIES",
+    ext: "dylib",
+    lib: include_bytes!(concat!(env!("OUT_DIR"), "/libstdbuf.dylib")),
+});
 
+// the dynamic linker has no way to have libstdbuf loaded
 #[cfg(not(any(
     target_os = "linux",
+    target_os = "android",
     target_os = "freebsd",
     target_os = "netbsd",
-    target_os = "dragonflybsd",
-    target_vendor = "apple"
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "macos",
+    target_os = "ios"
 )))]
-fn preload_strings() -> (&'static str, &'static str) {
-    crash!(1, "Command not supported for this operating system!")
-}
+const PRELOAD: Option<Preload> = None;
 
 fn print_version() {
-    println!("{} {}", NAME, VERSION);
+    println!("{}", help::version(NAME, VERSION));
 }
 
-fn print_usage(opts: &Options) {
-    let brief = "Run COMMAND, with modified buffering operations for its standard streams\n \
-                 Mandatory arguments to long options are mandatory for short options too.";
-    let explanation = "If MODE is 'L' the corresponding stream will be line buffered.\n \
-         This option is invalid with standard input.\n\n \
-         If MODE is '0' the corresponding stream will be unbuffered.\n\n \
-         Otherwise MODE is a number which may be followed by one of the following:\n\n \
-         KB 1000, K 1024, MB 1000*1000, M 1024*1024, and so on for G, T, P, E, Z, Y.\n \
-         In this case the corresponding stream will be fully buffered with the buffer size set to \
-         MODE bytes.\n\n \
-         NOTE: If COMMAND adjusts the buffering of its standard streams ('tee' does for e.g.) then \
-         that will override corresponding settings changed by 'stdbuf'.\n \
-         Also some filters (like 'dd' and 'cat' etc.) don't use streams for I/O, \
-         and are thus unaffected by 'stdbuf' settings.\n";
-    println!("{} {}", NAME, VERSION);
-    println!();
-    println!("Usage: stdbuf OPTION... COMMAND");
-    println!();
-    println!("{}\n{}", opts.usage(brief), explanation);
+fn print_usage(app: &CoreOptions) {
+    print!("{}", Help::from(app));
 }
 
 fn parse_size(size: &str) -> Option<u64> {
@@ -204,41 +224,39 @@ fn set_command_env(command: &mut Command, buffer_name: &str, buffer_type: Buffer
     }
 }
 
-fn get_preload_env(tmp_dir: &mut TempDir) -> io::Result<(String, PathBuf)> {
-    let (preload, extension) = preload_strings();
-    let inject_path = tmp_dir.path().join("libstdbuf").with_extension(extension);
+/// libstdbuf as installed with stdbuf, if it is: beside it, or where GNU
+/// keeps it, in "libexec/coreutils" under the same prefix.
+fn find_installed(preload: &Preload) -> Option<PathBuf> {
+    let exe = env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    let name = format!("libstdbuf.{}", preload.ext);
+    [
+        dir.join(&name),
+        dir.join("../libexec/coreutils").join(&name),
+        dir.join("../lib/coreutils").join(&name),
+    ]
+    .iter()
+    .find(|path| path.is_file())
+    .cloned()
+}
+
+fn get_preload_env(preload: &Preload, tmp_dir: &mut TempDir) -> io::Result<(String, PathBuf)> {
+    if let Some(installed) = find_installed(preload) {
+        return Ok((preload.var.to_owned(), installed));
+    }
 
+    // otherwise, the copy built into stdbuf
+    let inject_path = tmp_dir.path().join("libstdbuf").with_extension(preload.ext);
     let mut file = File::create(&inject_path)?;
-    file.write_all(STDBUF_INJECT)?;
+    file.write_all(preload.lib)?;
 
-    Ok((preload.to_owned(), inject_path))
+    Ok((preload.var.to_owned(), inject_path))
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let mut opts = Options::new();
-
-    opts.optopt(
-        "i",
-        "input",
-        "adjust standard input stream buffering",
-        "MODE",
-    );
-    opts.optopt(
-        "o",
-        "output",
-        "adjust standard output stream buffering",
-        "MODE",
-    );
-    opts.optopt(
-        "e",
-        "error",
-        "adjust standard error stream buffering",
-        "MODE",
-    );
-    opts.optflag("", "help", "display this help and exit");
-    opts.optflag("", "version", "output version information and exit");
+    let mut app = uu_app();
 
     let mut options = ProgramOptions {
         stdin: BufferType::Default,
@@ -247,13 +265,13 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     };
     let mut command_idx: i32 = -1;
     for i in 1..=args.len() {
-        match parse_options(&args[1..i], &mut options, &opts) {
+        match parse_options(&args[1..i], &mut options, app.options()) {
             Ok(OkMsg::Buffering) => {
                 command_idx = (i as i32) - 1;
                 break;
             }
             Ok(OkMsg::Help) => {
-                print_usage(&opts);
+                print_usage(&app);
                 return 0;
             }
             Ok(OkMsg::Version) => {
@@ -270,11 +288,19 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             "Invalid options\nTry 'stdbuf --help' for more information."
         );
     }
+    let preload = match PRELOAD {
+        Some(preload) => preload,
+        None => crash!(
+            125,
+            "buffering can't be changed on {}, which has no way to preload libstdbuf",
+            env::consts::OS
+        ),
+    };
     let command_name = &args[command_idx as usize];
     let mut command = Command::new(command_name);
 
     let mut tmp_dir = tempdir().unwrap();
-    let (preload_env, libstdbuf) = return_if_err!(1, get_preload_env(&mut tmp_dir));
+    let (preload_env, libstdbuf) = return_if_err!(1, get_preload_env(&preload, &mut tmp_dir));
     command
         .args(&args[(command_idx as usize) + 1..])
         .env(preload_env, libstdbuf);
@@ -293,3 +319,26 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         Err(e) => crash!(1, "{}", e),
     }
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    app.optopt(
+        "i",
+        "input",
+        "adjust standard input stream buffering",
+        "MODE",
+    );
+    app.optopt(
+        "o",
+        "output",
+        "adjust standard output stream buffering",
+        "MODE",
+    );
+    app.optopt(
+        "e",
+        "error",
+        "adjust standard error stream buffering",
+        "MODE",
+    );
+    app
+}
@@ -0,0 +1,460 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! The settings stty knows: the mode flags, the combinations of them, the
+//! special characters and the speeds, in the order GNU stty lists them.
+
+// spell-checker:ignore (ToDO) parenb parodd cmspar hupcl cstopb cread clocal crtscts ignbrk brkint ignpar parmrk inpck istrip inlcr igncr icrnl ixon ixoff iuclc ixany imaxbel iutf8 opost olcuc ocrnl onlcr onocr onlret ofill ofdel isig icanon iexten echoe echok echonl noflsh xcase tostop echoprt echoctl echoke flusho extproc crterase prterase ctlecho crtkill evenp oddp litout cbreak decctlq lcase swtch rprnt werase lnext dsusp NLDLY CRDLY TABDLY BSDLY VTDLY FFDLY VSWTC VREPRINT VWERASE VLNEXT VDISCARD VDSUSP VSTATUS CSIZE exta extb tcflag termios
+
+use libc::{cc_t, speed_t, tcflag_t, termios};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Control,
+    Input,
+    Output,
+    Local,
+}
+
+/// The flag is set by "sane".
+pub const SANE_SET: u8 = 1;
+/// The flag is cleared by "sane".
+pub const SANE_UNSET: u8 = 2;
+/// The flag can be cleared, as "-NAME".
+pub const REV: u8 = 4;
+/// The flag is left out of listings, being another name of one.
+pub const OMIT: u8 = 8;
+
+/// Upper case input and output in canonical mode, which libc leaves out.
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(target_arch = "powerpc", target_arch = "powerpc64")
+))]
+const XCASE: tcflag_t = 0o40000;
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    not(any(target_arch = "powerpc", target_arch = "powerpc64"))
+))]
+const XCASE: tcflag_t = 0o4;
+
+/// A mode flag.
+pub struct Flag {
+    pub name: &'static str,
+    pub kind: Kind,
+    /// `SANE_SET`, `SANE_UNSET`, `REV` and `OMIT`
+    pub flags: u8,
+    pub bits: tcflag_t,
+    /// the field `bits` are a value of, for one of several choices like
+    /// "cs8"; 0 for a flag that's just on or off
+    pub mask: tcflag_t,
+}
+
+impl Flag {
+    /// The bits the flag is, in its field.
+    pub fn mask(&self) -> tcflag_t {
+        if self.mask == 0 {
+            self.bits
+        } else {
+            self.mask
+        }
+    }
+
+    /// Whether the flag is set in `mode`.
+    pub fn is_set(&self, mode: &termios) -> bool {
+        *field(mode, self.kind) & self.mask() == self.bits
+    }
+
+    /// Set the flag in `mode`, or clear it.
+    pub fn apply(&self, mode: &mut termios, set: bool) {
+        let field = field_mut(mode, self.kind);
+        if set {
+            *field = (*field & !self.mask()) | self.bits;
+        } else {
+            *field &= !self.mask() & !self.bits;
+        }
+    }
+}
+
+fn field(mode: &termios, kind: Kind) -> &tcflag_t {
+    match kind {
+        Kind::Control => &mode.c_cflag,
+        Kind::Input => &mode.c_iflag,
+        Kind::Output => &mode.c_oflag,
+        Kind::Local => &mode.c_lflag,
+    }
+}
+
+fn field_mut(mode: &mut termios, kind: Kind) -> &mut tcflag_t {
+    match kind {
+        Kind::Control => &mut mode.c_cflag,
+        Kind::Input => &mut mode.c_iflag,
+        Kind::Output => &mut mode.c_oflag,
+        Kind::Local => &mut mode.c_lflag,
+    }
+}
+
+macro_rules! flag {
+    ($name:expr, $kind:ident, $flags:expr, $bits:expr) => {
+        flag!($name, $kind, $flags, $bits, 0)
+    };
+    ($name:expr, $kind:ident, $flags:expr, $bits:expr, $mask:expr) => {
+        Flag {
+            name: $name,
+            kind: Kind::$kind,
+            flags: $flags,
+            bits: $bits,
+            mask: $mask,
+        }
+    };
+}
+
+pub static FLAGS: &[Flag] = &[
+    flag!("parenb", Control, REV, libc::PARENB),
+    flag!("parodd", Control, REV, libc::PARODD),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("cmspar", Control, REV, libc::CMSPAR),
+    flag!("cs5", Control, 0, libc::CS5, libc::CSIZE),
+    flag!("cs6", Control, 0, libc::CS6, libc::CSIZE),
+    flag!("cs7", Control, 0, libc::CS7, libc::CSIZE),
+    flag!("cs8", Control, 0, libc::CS8, libc::CSIZE),
+    flag!("hupcl", Control, REV, libc::HUPCL),
+    flag!("hup", Control, REV | OMIT, libc::HUPCL),
+    flag!("cstopb", Control, REV, libc::CSTOPB),
+    flag!("cread", Control, SANE_SET | REV, libc::CREAD),
+    flag!("clocal", Control, REV, libc::CLOCAL),
+    flag!("crtscts", Control, REV, libc::CRTSCTS),
+    flag!("ignbrk", Input, SANE_UNSET | REV, libc::IGNBRK),
+    flag!("brkint", Input, SANE_SET | REV, libc::BRKINT),
+    flag!("ignpar", Input, REV, libc::IGNPAR),
+    flag!("parmrk", Input, REV, libc::PARMRK),
+    flag!("inpck", Input, REV, libc::INPCK),
+    flag!("istrip", Input, REV, libc::ISTRIP),
+    flag!("inlcr", Input, SANE_UNSET | REV, libc::INLCR),
+    flag!("igncr", Input, SANE_UNSET | REV, libc::IGNCR),
+    flag!("icrnl", Input, SANE_SET | REV, libc::ICRNL),
+    flag!("ixon", Input, REV, libc::IXON),
+    flag!("ixoff", Input, SANE_UNSET | REV, libc::IXOFF),
+    flag!("tandem", Input, REV | OMIT, libc::IXOFF),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("iuclc", Input, SANE_UNSET | REV, libc::IUCLC),
+    flag!("ixany", Input, SANE_UNSET | REV, libc::IXANY),
+    flag!("imaxbel", Input, SANE_SET | REV, libc::IMAXBEL),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("iutf8", Input, SANE_UNSET | REV, libc::IUTF8),
+    flag!("opost", Output, SANE_SET | REV, libc::OPOST),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("olcuc", Output, SANE_UNSET | REV, libc::OLCUC),
+    flag!("ocrnl", Output, SANE_UNSET | REV, libc::OCRNL),
+    flag!("onlcr", Output, SANE_SET | REV, libc::ONLCR),
+    flag!("onocr", Output, SANE_UNSET | REV, libc::ONOCR),
+    flag!("onlret", Output, SANE_UNSET | REV, libc::ONLRET),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("ofill", Output, SANE_UNSET | REV, libc::OFILL),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("ofdel", Output, SANE_UNSET | REV, libc::OFDEL),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("nl1", Output, SANE_UNSET, libc::NL1, libc::NLDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("nl0", Output, SANE_SET, libc::NL0, libc::NLDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("cr3", Output, SANE_UNSET, libc::CR3, libc::CRDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("cr2", Output, SANE_UNSET, libc::CR2, libc::CRDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("cr1", Output, SANE_UNSET, libc::CR1, libc::CRDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("cr0", Output, SANE_SET, libc::CR0, libc::CRDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("tab3", Output, SANE_UNSET, libc::TAB3, libc::TABDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("tab2", Output, SANE_UNSET, libc::TAB2, libc::TABDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("tab1", Output, SANE_UNSET, libc::TAB1, libc::TABDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("tab0", Output, SANE_SET, libc::TAB0, libc::TABDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("bs1", Output, SANE_UNSET, libc::BS1, libc::BSDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("bs0", Output, SANE_SET, libc::BS0, libc::BSDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("vt1", Output, SANE_UNSET, libc::VT1, libc::VTDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("vt0", Output, SANE_SET, libc::VT0, libc::VTDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("ff1", Output, SANE_UNSET, libc::FF1, libc::FFDLY),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("ff0", Output, SANE_SET, libc::FF0, libc::FFDLY),
+    flag!("isig", Local, SANE_SET | REV, libc::ISIG),
+    flag!("icanon", Local, SANE_SET | REV, libc::ICANON),
+    flag!("iexten", Local, SANE_SET | REV, libc::IEXTEN),
+    flag!("echo", Local, SANE_SET | REV, libc::ECHO),
+    flag!("echoe", Local, SANE_SET | REV, libc::ECHOE),
+    flag!("crterase", Local, REV | OMIT, libc::ECHOE),
+    flag!("echok", Local, SANE_SET | REV, libc::ECHOK),
+    flag!("echonl", Local, SANE_UNSET | REV, libc::ECHONL),
+    flag!("noflsh", Local, SANE_UNSET | REV, libc::NOFLSH),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("xcase", Local, SANE_UNSET | REV, XCASE),
+    flag!("tostop", Local, SANE_UNSET | REV, libc::TOSTOP),
+    flag!("echoprt", Local, SANE_UNSET | REV, libc::ECHOPRT),
+    flag!("prterase", Local, REV | OMIT, libc::ECHOPRT),
+    flag!("echoctl", Local, SANE_SET | REV, libc::ECHOCTL),
+    flag!("ctlecho", Local, REV | OMIT, libc::ECHOCTL),
+    flag!("echoke", Local, SANE_SET | REV, libc::ECHOKE),
+    flag!("crtkill", Local, REV | OMIT, libc::ECHOKE),
+    flag!("flusho", Local, SANE_UNSET | REV, libc::FLUSHO),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    flag!("extproc", Local, SANE_UNSET | REV, libc::EXTPROC),
+];
+
+/// The settings that are several flags, with whether they can be reversed.
+pub static COMBINATIONS: &[(&str, bool)] = &[
+    ("evenp", true),
+    ("parity", true),
+    ("oddp", true),
+    ("nl", true),
+    ("ek", false),
+    ("sane", false),
+    ("cooked", true),
+    ("raw", true),
+    ("pass8", true),
+    ("litout", true),
+    ("cbreak", true),
+    ("decctlq", true),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("tabs", true),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("lcase", true),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("LCASE", true),
+    ("crt", false),
+    ("dec", false),
+];
+
+/// The value that disables a special character.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const DISABLED: cc_t = 0;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const DISABLED: cc_t = 0xff;
+
+/// A special character, with the value "sane" gives it.
+pub struct ControlChar {
+    pub name: &'static str,
+    pub index: usize,
+    pub sane: cc_t,
+}
+
+macro_rules! control_char {
+    ($name:expr, $index:expr, $sane:expr) => {
+        ControlChar {
+            name: $name,
+            index: $index,
+            sane: $sane,
+        }
+    };
+}
+
+/// The special characters, and last "min" and "time", which aren't
+/// characters but the number of them and the tenths of a second a read
+/// without "icanon" waits for.
+pub static CONTROL_CHARS: &[ControlChar] = &[
+    control_char!("intr", libc::VINTR, 0o3),
+    control_char!("quit", libc::VQUIT, 0o34),
+    control_char!("erase", libc::VERASE, 0o177),
+    control_char!("kill", libc::VKILL, 0o25),
+    control_char!("eof", libc::VEOF, 0o4),
+    control_char!("eol", libc::VEOL, DISABLED),
+    control_char!("eol2", libc::VEOL2, DISABLED),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    control_char!("swtch", libc::VSWTC, DISABLED),
+    control_char!("start", libc::VSTART, 0o21),
+    control_char!("stop", libc::VSTOP, 0o23),
+    control_char!("susp", libc::VSUSP, 0o32),
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    control_char!("dsusp", libc::VDSUSP, 0o31),
+    control_char!("rprnt", libc::VREPRINT, 0o22),
+    control_char!("werase", libc::VWERASE, 0o27),
+    control_char!("lnext", libc::VLNEXT, 0o26),
+    control_char!("discard", libc::VDISCARD, 0o17),
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    control_char!("status", libc::VSTATUS, 0o24),
+    control_char!("min", libc::VMIN, 1),
+    control_char!("time", libc::VTIME, 0),
+];
+
+/// Apply the combination setting `name` to `mode`, or its reverse.
+pub fn apply_combination(name: &str, mode: &mut termios, set: bool) {
+    use libc::*;
+
+    match (name, set) {
+        ("evenp", true) | ("parity", true) => {
+            mode.c_cflag = (mode.c_cflag & !PARODD & !CSIZE) | PARENB | CS7;
+        }
+        ("oddp", true) => mode.c_cflag = (mode.c_cflag & !CSIZE) | CS7 | PARODD | PARENB,
+        ("evenp", false) | ("parity", false) | ("oddp", false) => {
+            mode.c_cflag = (mode.c_cflag & !PARENB & !CSIZE) | CS8;
+        }
+        ("nl", true) => {
+            mode.c_iflag &= !ICRNL;
+            mode.c_oflag &= !ONLCR;
+        }
+        ("nl", false) => {
+            mode.c_iflag = (mode.c_iflag | ICRNL) & !INLCR & !IGNCR;
+            mode.c_oflag = (mode.c_oflag | ONLCR) & !OCRNL & !ONLRET;
+        }
+        ("ek", _) => {
+            mode.c_cc[VERASE] = sane_value(VERASE);
+            mode.c_cc[VKILL] = sane_value(VKILL);
+        }
+        ("sane", _) => {
+            for c in CONTROL_CHARS {
+                mode.c_cc[c.index] = c.sane;
+            }
+            for flag in FLAGS {
+                if flag.flags & SANE_SET != 0 {
+                    flag.apply(mode, true);
+                } else if flag.flags & SANE_UNSET != 0 {
+                    flag.apply(mode, false);
+                }
+            }
+        }
+        ("cooked", true) | ("raw", false) => {
+            mode.c_iflag |= BRKINT | IGNPAR | ISTRIP | ICRNL | IXON;
+            mode.c_oflag |= OPOST;
+            mode.c_lflag |= ISIG | ICANON;
+        }
+        ("raw", true) | ("cooked", false) => {
+            mode.c_iflag = 0;
+            mode.c_oflag &= !OPOST;
+            mode.c_lflag &= !(ISIG | ICANON);
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            {
+                mode.c_lflag &= !XCASE;
+            }
+            mode.c_cc[VMIN] = 1;
+            mode.c_cc[VTIME] = 0;
+        }
+        ("pass8", true) => {
+            mode.c_cflag = (mode.c_cflag & !PARENB & !CSIZE) | CS8;
+            mode.c_iflag &= !ISTRIP;
+        }
+        ("pass8", false) => {
+            mode.c_cflag = (mode.c_cflag & !CSIZE) | CS7 | PARENB;
+            mode.c_iflag |= ISTRIP;
+        }
+        ("litout", true) => {
+            mode.c_cflag = (mode.c_cflag & !PARENB & !CSIZE) | CS8;
+            mode.c_iflag &= !ISTRIP;
+            mode.c_oflag &= !OPOST;
+        }
+        ("litout", false) => {
+            mode.c_cflag = (mode.c_cflag & !CSIZE) | CS7 | PARENB;
+            mode.c_iflag |= ISTRIP;
+            mode.c_oflag |= OPOST;
+        }
+        ("cbreak", true) => mode.c_lflag &= !ICANON,
+        ("cbreak", false) => mode.c_lflag |= ICANON,
+        ("decctlq", true) => mode.c_iflag &= !IXANY,
+        ("decctlq", false) => mode.c_iflag |= IXANY,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        ("tabs", true) => mode.c_oflag = (mode.c_oflag & !TABDLY) | TAB0,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        ("tabs", false) => mode.c_oflag = (mode.c_oflag & !TABDLY) | TAB3,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        ("lcase", true) | ("LCASE", true) => {
+            mode.c_lflag |= XCASE;
+            mode.c_iflag |= IUCLC;
+            mode.c_oflag |= OLCUC;
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        ("lcase", false) | ("LCASE", false) => {
+            mode.c_lflag &= !XCASE;
+            mode.c_iflag &= !IUCLC;
+            mode.c_oflag &= !OLCUC;
+        }
+        ("crt", _) => mode.c_lflag |= ECHOE | ECHOCTL | ECHOKE,
+        ("dec", _) => {
+            mode.c_cc[VINTR] = sane_value(VINTR);
+            mode.c_cc[VERASE] = sane_value(VERASE);
+            mode.c_cc[VKILL] = sane_value(VKILL);
+            mode.c_lflag |= ECHOE | ECHOCTL | ECHOKE;
+            mode.c_iflag &= !IXANY;
+        }
+        _ => unreachable!("{} is not a combination setting", name),
+    }
+}
+
+fn sane_value(index: usize) -> cc_t {
+    CONTROL_CHARS
+        .iter()
+        .find(|c| c.index == index)
+        .map_or(DISABLED, |c| c.sane)
+}
+
+/// The speeds, by the baud rates that name them.
+pub static SPEEDS: &[(&str, speed_t)] = &[
+    ("0", libc::B0),
+    ("50", libc::B50),
+    ("75", libc::B75),
+    ("110", libc::B110),
+    ("134", libc::B134),
+    ("134.5", libc::B134),
+    ("150", libc::B150),
+    ("200", libc::B200),
+    ("300", libc::B300),
+    ("600", libc::B600),
+    ("1200", libc::B1200),
+    ("1800", libc::B1800),
+    ("2400", libc::B2400),
+    ("4800", libc::B4800),
+    ("9600", libc::B9600),
+    ("19200", libc::B19200),
+    ("38400", libc::B38400),
+    ("exta", libc::B19200),
+    ("extb", libc::B38400),
+    ("57600", libc::B57600),
+    ("115200", libc::B115200),
+    ("230400", libc::B230400),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("460800", libc::B460800),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("500000", libc::B500000),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("576000", libc::B576000),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("921600", libc::B921600),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("1000000", libc::B1000000),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("1152000", libc::B1152000),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("1500000", libc::B1500000),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("2000000", libc::B2000000),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("2500000", libc::B2500000),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("3000000", libc::B3000000),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("3500000", libc::B3500000),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ("4000000", libc::B4000000),
+];
+
+/// The speed the baud rate `baud` names.
+pub fn speed(baud: &str) -> Option<speed_t> {
+    SPEEDS
+        .iter()
+        .find(|&&(name, _)| name == baud)
+        .map(|&(_, speed)| speed)
+}
+
+/// The baud rate of `speed`.
+pub fn baud(speed: speed_t) -> &'static str {
+    SPEEDS
+        .iter()
+        .find(|&&(_, s)| s == speed)
+        .map_or("0", |&(name, _)| name)
+}
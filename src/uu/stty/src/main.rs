@@ -0,0 +1 @@
+uucore_procs::main!(uu_stty); // spell-checker:ignore procs uucore
@@ -0,0 +1,794 @@
+//  * This file is part of the uutils coreutils package.
+//  *
+//  * For the full copyright and license information, please view the LICENSE
+//  * file that was distributed with this source code.
+
+// spell-checker:ignore (ToDO) termios tcgetattr tcsetattr TCSANOW TCSADRAIN winsize TIOCGWINSZ TIOCSWINSZ cfgetispeed cfgetospeed cfsetispeed cfsetospeed ispeed ospeed wrapf icanon CIBAUD NCCS VMIN VTIME undef EOVERFLOW ERANGE
+
+#[macro_use]
+extern crate uucore;
+
+mod flags;
+
+use clap::{App, Arg};
+use flags::{ControlChar, Flag, CONTROL_CHARS, DISABLED, FLAGS, OMIT, REV, SANE_SET, SANE_UNSET};
+use libc::{c_int, cc_t, speed_t, tcflag_t, termios, winsize};
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Error};
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use uucore::error_message;
+
+static VERSION: &str = env!("CARGO_PKG_VERSION");
+static ABOUT: &str = "Print or change terminal characteristics.";
+static LONG_HELP: &str = "
+Settings are mode flags like 'icanon' and 'echo', which a '-' before
+clears, or combinations of them like 'raw' and 'sane'; a special character
+and what to set it to, like 'intr ^C', with '^-' or 'undef' disabling it;
+'min N' and 'time N'; 'ispeed N', 'ospeed N' or just N for the baud rate;
+'rows N' and 'cols N' for the window size; 'line N' for the line
+discipline; and 'size' and 'speed', which print those.";
+
+static EXIT_ERR: i32 = 1;
+
+mod options {
+    pub const ALL: &str = "all";
+    pub const SAVE: &str = "save";
+    pub const FILE: &str = "file";
+}
+
+fn get_usage() -> String {
+    format!(
+        "{0} [-F DEVICE | --file=DEVICE] [SETTING]...
+    {0} [-F DEVICE | --file=DEVICE] [-a|--all]
+    {0} [-F DEVICE | --file=DEVICE] [-g|--save]",
+        executable!()
+    )
+}
+
+/// A setting, checked and ready to apply.
+enum Setting {
+    Flag(&'static Flag, bool),
+    Combination(&'static str, bool),
+    Char(usize, cc_t),
+    ISpeed(speed_t),
+    OSpeed(speed_t),
+    Speed(speed_t),
+    Rows(u16),
+    Cols(u16),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Line(cc_t),
+    Drain(bool),
+    PrintSize,
+    PrintSpeed,
+    Restore(termios),
+}
+
+pub fn uumain(args: impl uucore::Args) -> i32 {
+    let args = args.collect_str();
+    let (options, settings) = separate(args);
+    let usage = get_usage();
+
+    let app = uu_app().usage(&usage[..]);
+    let matches = uucore::help::get_matches_from(app, options);
+
+    let all = matches.is_present(options::ALL);
+    let save = matches.is_present(options::SAVE);
+    if all && save {
+        show_info!(
+            "the options for verbose and stty-readable output styles are\nmutually exclusive"
+        );
+        return EXIT_ERR;
+    }
+    if (all || save) && !settings.is_empty() {
+        show_info!("when specifying an output style, modes may not be set");
+        return EXIT_ERR;
+    }
+
+    // every setting is checked before any is applied
+    let settings = match parse_settings(&settings) {
+        Ok(settings) => settings,
+        Err(status) => return status,
+    };
+
+    let device = matches.value_of(options::FILE);
+    let name = device.unwrap_or("standard input");
+    let file = match device.map(open) {
+        Some(Ok(file)) => Some(file),
+        Some(Err(e)) => {
+            show_info!("{}: {}", quote_name(name), error_message(&e));
+            return EXIT_ERR;
+        }
+        None => None,
+    };
+    let fd = file
+        .as_ref()
+        .map_or(libc::STDIN_FILENO, |file| file.as_raw_fd());
+
+    let mut mode = match get_mode(fd) {
+        Ok(mode) => mode,
+        Err(e) => {
+            show_info!("{}: {}", quote_name(name), error_message(&e));
+            return EXIT_ERR;
+        }
+    };
+
+    let result = if settings.is_empty() {
+        let mut output = Output::new();
+        if save {
+            print_recoverable(&mode);
+            Ok(())
+        } else if all {
+            output.print_all(&mode, fd)
+        } else {
+            output.print_changed(&mode);
+            Ok(())
+        }
+    } else {
+        apply(&settings, &mut mode, fd)
+    };
+    match result {
+        Ok(()) => 0,
+        Err(msg) => {
+            show_info!("{}: {}", quote_name(name), msg);
+            EXIT_ERR
+        }
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .after_help(LONG_HELP)
+        .arg(
+            Arg::with_name(options::ALL)
+                .short("a")
+                .long(options::ALL)
+                .help("print all current settings in human-readable form"),
+        )
+        .arg(
+            Arg::with_name(options::SAVE)
+                .short("g")
+                .long(options::SAVE)
+                .help("print all current settings in a stty-readable form"),
+        )
+        .arg(
+            Arg::with_name(options::FILE)
+                .short("F")
+                .long(options::FILE)
+                .takes_value(true)
+                .value_name("DEVICE")
+                .help("open and use the specified DEVICE instead of stdin"),
+        )
+}
+
+/// Split `args` into the options and the settings, some of which, like
+/// "-echo", look like options.
+fn separate(args: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let mut options = Vec::new();
+    let mut settings = Vec::new();
+    let mut args = args.into_iter();
+    options.extend(args.next());
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--" => {
+                settings.extend(args);
+                break;
+            }
+            "--all" | "--save" | "--help" | "--version" => options.push(arg),
+            "--file" | "-F" => match args.next() {
+                Some(value) => {
+                    options.push(arg);
+                    options.push(value);
+                }
+                // an invalid argument then
+                None => settings.push(arg),
+            },
+            _ if arg.starts_with("--file=") => options.push(arg),
+            _ if is_short_options(&arg) => {
+                // a -F at the end of them takes the next argument
+                if arg.ends_with('F') {
+                    match args.next() {
+                        Some(value) => {
+                            options.push(arg);
+                            options.push(value);
+                        }
+                        None => settings.push(arg),
+                    }
+                } else {
+                    options.push(arg);
+                }
+            }
+            _ => settings.push(arg),
+        }
+    }
+    (options, settings)
+}
+
+/// Whether `arg` is -a and -g, and a -F with its value or at the end.
+fn is_short_options(arg: &str) -> bool {
+    if !arg.starts_with('-') || arg.len() < 2 {
+        return false;
+    }
+    let flags = &arg[1..];
+    let flags = match flags.find('F') {
+        Some(i) => &flags[..i],
+        None => flags,
+    };
+    flags.chars().all(|c| c == 'a' || c == 'g')
+}
+
+fn open(device: &str) -> io::Result<File> {
+    // not waiting for a carrier, but blocking afterwards
+    let file = OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(device)?;
+    let fd = file.as_raw_fd();
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags < 0 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) } < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(file)
+}
+
+fn get_mode(fd: c_int) -> io::Result<termios> {
+    let mut mode: termios = unsafe { mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut mode) } < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(mode)
+}
+
+fn get_window_size(fd: c_int) -> io::Result<winsize> {
+    let mut size: winsize = unsafe { mem::zeroed() };
+    if unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) } < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(size)
+}
+
+fn parse_settings(args: &[String]) -> Result<Vec<Setting>, i32> {
+    let mut settings = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (name, reversed) = if arg.starts_with('-') && arg.len() > 1 {
+            (&arg[1..], true)
+        } else {
+            (&arg[..], false)
+        };
+
+        if name == "drain" {
+            settings.push(Setting::Drain(!reversed));
+            continue;
+        }
+        if let Some(flag) = FLAGS.iter().find(|flag| flag.name == name) {
+            if reversed && flag.flags & REV == 0 {
+                return Err(invalid_argument(arg));
+            }
+            settings.push(Setting::Flag(flag, !reversed));
+            continue;
+        }
+        if let Some(&(name, reversible)) = flags::COMBINATIONS.iter().find(|c| c.0 == name) {
+            if reversed && !reversible {
+                return Err(invalid_argument(arg));
+            }
+            settings.push(Setting::Combination(name, !reversed));
+            continue;
+        }
+        if reversed {
+            return Err(invalid_argument(arg));
+        }
+
+        let setting = match name {
+            "size" => Setting::PrintSize,
+            "speed" => Setting::PrintSpeed,
+            _ => {
+                if !takes_value(name) {
+                    match flags::speed(name) {
+                        Some(speed) => Setting::Speed(speed),
+                        None => match restored_mode(name) {
+                            Some(mode) if args.len() == 0 && settings.is_empty() => {
+                                Setting::Restore(mode)
+                            }
+                            _ => return Err(invalid_argument(arg)),
+                        },
+                    }
+                } else {
+                    let value = match args.next() {
+                        Some(value) => value,
+                        None => {
+                            show_usage_error!("missing argument to '{}'", name);
+                            return Err(EXIT_ERR);
+                        }
+                    };
+                    value_setting(name, value)?
+                }
+            }
+        };
+        settings.push(setting);
+    }
+    Ok(settings)
+}
+
+/// Whether the setting `name` is followed by its value.
+fn takes_value(name: &str) -> bool {
+    match name {
+        "ispeed" | "ospeed" | "rows" | "cols" | "columns" => true,
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        "line" => true,
+        _ => CONTROL_CHARS.iter().any(|c| c.name == name),
+    }
+}
+
+/// The setting `name` is with its value `value`.
+fn value_setting(name: &str, value: &str) -> Result<Setting, i32> {
+    Ok(match name {
+        "ispeed" | "ospeed" => match flags::speed(value) {
+            Some(speed) if name == "ispeed" => Setting::ISpeed(speed),
+            Some(speed) => Setting::OSpeed(speed),
+            None => {
+                show_info!("invalid {} '{}'", name, value);
+                return Err(EXIT_ERR);
+            }
+        },
+        // truncated to the size of the field, like GNU stty
+        "rows" => Setting::Rows(integer_arg(value, i32::max_value() as u64)? as u16),
+        "cols" | "columns" => Setting::Cols(integer_arg(value, i32::max_value() as u64)? as u16),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        "line" => {
+            let line = integer_arg(value, u64::max_value())?;
+            if line > u64::from(cc_t::max_value()) {
+                show_info!("invalid line discipline '{}'", value);
+            }
+            Setting::Line(line as cc_t)
+        }
+        _ => {
+            let c = CONTROL_CHARS.iter().find(|c| c.name == name).unwrap();
+            Setting::Char(c.index, char_value(c, value)?)
+        }
+    })
+}
+
+/// The value `arg` gives the special character `c`: a character, "^X" for
+/// a control character, or a number.
+fn char_value(c: &ControlChar, arg: &str) -> Result<cc_t, i32> {
+    if c.name == "min" || c.name == "time" {
+        return integer_arg(arg, u64::from(cc_t::max_value())).map(|value| value as cc_t);
+    }
+    let bytes = arg.as_bytes();
+    Ok(if bytes.len() < 2 {
+        bytes.first().cloned().unwrap_or(0)
+    } else if arg == "^-" || arg == "undef" {
+        DISABLED
+    } else if bytes[0] == b'^' {
+        match bytes[1] {
+            b'?' => 0o177,
+            b => b & !0o140,
+        }
+    } else {
+        integer_arg(arg, u64::from(cc_t::max_value()))? as cc_t
+    })
+}
+
+fn invalid_argument(arg: &str) -> i32 {
+    show_usage_error!("invalid argument '{}'", arg);
+    EXIT_ERR
+}
+
+/// The value of the number `arg`, which is decimal, octal with a leading 0
+/// or hexadecimal with "0x", and can have the suffix "b" for 512 or "B" for
+/// 1024 times it.
+fn integer_arg(arg: &str, max: u64) -> Result<u64, i32> {
+    match parse_integer(arg) {
+        Ok(value) if value <= max => Ok(value),
+        Ok(value) => {
+            let reason = if value <= (i32::max_value() / 2) as u64 {
+                "Numerical result out of range"
+            } else {
+                "Value too large for defined data type"
+            };
+            show_info!("invalid integer argument: '{}': {}", arg, reason);
+            Err(EXIT_ERR)
+        }
+        Err(None) => {
+            show_info!("invalid integer argument: '{}'", arg);
+            Err(EXIT_ERR)
+        }
+        Err(Some(reason)) => {
+            show_info!("invalid integer argument: '{}': {}", arg, reason);
+            Err(EXIT_ERR)
+        }
+    }
+}
+
+/// The value of the number `arg`, or `Err(None)` if it isn't one and
+/// `Err(Some(reason))` if it's too large.
+fn parse_integer(arg: &str) -> Result<u64, Option<&'static str>> {
+    let overflow = Some("Value too large for defined data type");
+    let (digits, multiplier) = if arg.ends_with('b') {
+        (&arg[..arg.len() - 1], 512)
+    } else if arg.ends_with('B') {
+        (&arg[..arg.len() - 1], 1024)
+    } else {
+        (arg, 1)
+    };
+    let (digits, radix) = if digits.starts_with("0x") || digits.starts_with("0X") {
+        (&digits[2..], 16)
+    } else if digits.starts_with('0') && digits.len() > 1 {
+        (&digits[1..], 8)
+    } else {
+        (digits, 10)
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return Err(None);
+    }
+    let value = u64::from_str_radix(digits, radix).map_err(|_| overflow)?;
+    value.checked_mul(multiplier).ok_or(overflow)
+}
+
+/// The mode `arg` is, as "stty -g" prints it: the input, output, control
+/// and local flags and then every special character, in hexadecimal.
+fn restored_mode(arg: &str) -> Option<termios> {
+    let fields: Vec<&str> = arg.split(':').collect();
+    if fields.len() != 4 + libc::NCCS {
+        return None;
+    }
+    let mut values = Vec::with_capacity(fields.len());
+    for field in fields {
+        if field.is_empty() || !field.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        values.push(u64::from_str_radix(field, 16).ok()?);
+    }
+    let flag = |value: u64| {
+        if value > tcflag_t::max_value() as u64 {
+            None
+        } else {
+            Some(value as tcflag_t)
+        }
+    };
+    let mut mode: termios = unsafe { mem::zeroed() };
+    mode.c_iflag = flag(values[0])?;
+    mode.c_oflag = flag(values[1])?;
+    mode.c_cflag = flag(values[2])?;
+    mode.c_lflag = flag(values[3])?;
+    for (c, &value) in mode.c_cc.iter_mut().zip(&values[4..]) {
+        if value > u64::from(cc_t::max_value()) {
+            return None;
+        }
+        *c = value as cc_t;
+    }
+    Some(mode)
+}
+
+/// Apply `settings` to the terminal `fd`, whose mode is `mode`.
+fn apply(settings: &[Setting], mode: &mut termios, fd: c_int) -> Result<(), String> {
+    let mut changed = false;
+    let mut speed_changed = false;
+    let mut drain = true;
+    for setting in settings {
+        match *setting {
+            Setting::Flag(flag, set) => flag.apply(mode, set),
+            Setting::Combination(name, set) => flags::apply_combination(name, mode, set),
+            Setting::Char(index, value) => mode.c_cc[index] = value,
+            Setting::ISpeed(speed) => unsafe {
+                libc::cfsetispeed(mode, speed);
+            },
+            Setting::OSpeed(speed) => unsafe {
+                libc::cfsetospeed(mode, speed);
+            },
+            Setting::Speed(speed) => unsafe {
+                libc::cfsetispeed(mode, speed);
+                libc::cfsetospeed(mode, speed);
+            },
+            Setting::Rows(rows) => {
+                set_window_size(fd, Some(rows), None).map_err(|e| error_message(&e))?
+            }
+            Setting::Cols(cols) => {
+                set_window_size(fd, None, Some(cols)).map_err(|e| error_message(&e))?
+            }
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Setting::Line(line) => mode.c_line = line,
+            Setting::Drain(set) => drain = set,
+            Setting::PrintSize => {
+                let size = get_window_size(fd).map_err(|e| error_message(&e))?;
+                println!("{} {}", size.ws_row, size.ws_col);
+            }
+            Setting::PrintSpeed => Output::new().print_speed(mode, false),
+            Setting::Restore(restored) => {
+                mode.c_iflag = restored.c_iflag;
+                mode.c_oflag = restored.c_oflag;
+                mode.c_cflag = restored.c_cflag;
+                mode.c_lflag = restored.c_lflag;
+                mode.c_cc = restored.c_cc;
+            }
+        }
+        match *setting {
+            Setting::ISpeed(_) | Setting::OSpeed(_) | Setting::Speed(_) => {
+                changed = true;
+                speed_changed = true;
+            }
+            Setting::Rows(_)
+            | Setting::Cols(_)
+            | Setting::Drain(_)
+            | Setting::PrintSize
+            | Setting::PrintSpeed => {}
+            _ => changed = true,
+        }
+    }
+    if !changed {
+        return Ok(());
+    }
+
+    let when = if drain {
+        libc::TCSADRAIN
+    } else {
+        libc::TCSANOW
+    };
+    if unsafe { libc::tcsetattr(fd, when, mode) } < 0 {
+        return Err(error_message(&Error::last_os_error()));
+    }
+    // tcsetattr succeeds if it made any of the changes, so check them all
+    let new_mode = get_mode(fd).map_err(|e| error_message(&e))?;
+    if !same_mode(mode, &new_mode, speed_changed) {
+        return Err("unable to perform all requested operations".to_owned());
+    }
+    Ok(())
+}
+
+fn same_mode(a: &termios, b: &termios, speed_changed: bool) -> bool {
+    // some systems report a split input speed that wasn't asked for
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let cflag_mask = if speed_changed { !0 } else { !libc::CIBAUD };
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let cflag_mask = !0;
+    let same_speed = !speed_changed
+        || unsafe {
+            libc::cfgetispeed(a) == libc::cfgetispeed(b)
+                && libc::cfgetospeed(a) == libc::cfgetospeed(b)
+        };
+    // glibc keeps the speeds asked for too, which tell when they didn't take
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    let same_speed =
+        same_speed && (!speed_changed || (a.c_ispeed, a.c_ospeed) == (b.c_ispeed, b.c_ospeed));
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let same_line = a.c_line == b.c_line;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let same_line = true;
+    a.c_iflag == b.c_iflag
+        && a.c_oflag == b.c_oflag
+        && a.c_cflag & cflag_mask == b.c_cflag & cflag_mask
+        && a.c_lflag == b.c_lflag
+        && a.c_cc == b.c_cc
+        && same_line
+        && same_speed
+}
+
+fn set_window_size(fd: c_int, rows: Option<u16>, cols: Option<u16>) -> io::Result<()> {
+    let mut size = get_window_size(fd)?;
+    if let Some(rows) = rows {
+        size.ws_row = rows;
+    }
+    if let Some(cols) = cols {
+        size.ws_col = cols;
+    }
+    if unsafe { libc::ioctl(fd, libc::TIOCSWINSZ, &size) } < 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn print_recoverable(mode: &termios) {
+    print!(
+        "{:x}:{:x}:{:x}:{:x}",
+        mode.c_iflag, mode.c_oflag, mode.c_cflag, mode.c_lflag
+    );
+    for c in mode.c_cc.iter() {
+        print!(":{:x}", c);
+    }
+    println!();
+}
+
+/// Settings printed as words, wrapped to the width of the screen.
+struct Output {
+    column: usize,
+    max_column: usize,
+}
+
+impl Output {
+    fn new() -> Output {
+        Output {
+            column: 0,
+            max_column: screen_columns(),
+        }
+    }
+
+    /// Print `text`, after a space or, if it doesn't fit on this line, on
+    /// the next.
+    fn wrap(&mut self, text: &str) {
+        if self.column > 0 {
+            if self.max_column.saturating_sub(self.column) < text.len() {
+                println!();
+                self.column = 0;
+            } else {
+                print!(" ");
+                self.column += 1;
+            }
+        }
+        print!("{}", text);
+        self.column += text.len();
+    }
+
+    fn newline(&mut self) {
+        println!();
+        self.column = 0;
+    }
+
+    /// Print the speed, as part of the settings or, if not `fancy`, alone.
+    fn print_speed(&mut self, mode: &termios, fancy: bool) {
+        let (ispeed, ospeed) = unsafe { (libc::cfgetispeed(mode), libc::cfgetospeed(mode)) };
+        if ispeed == 0 || ispeed == ospeed {
+            if fancy {
+                self.wrap(&format!("speed {} baud;", flags::baud(ospeed)));
+            } else {
+                println!("{}", flags::baud(ospeed));
+            }
+        } else if fancy {
+            self.wrap(&format!(
+                "ispeed {} baud; ospeed {} baud;",
+                flags::baud(ispeed),
+                flags::baud(ospeed)
+            ));
+        } else {
+            println!("{} {}", flags::baud(ispeed), flags::baud(ospeed));
+        }
+    }
+
+    fn print_line(&mut self, mode: &termios) {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        self.wrap(&format!("line = {};", mode.c_line));
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        let _ = mode;
+    }
+
+    /// Print the settings that differ from "sane".
+    fn print_changed(&mut self, mode: &termios) {
+        self.print_speed(mode, true);
+        self.print_line(mode);
+        self.newline();
+
+        let mut empty = true;
+        for c in CONTROL_CHARS.iter().take_while(|c| c.name != "min") {
+            if mode.c_cc[c.index] != c.sane {
+                self.wrap(&format!("{} = {};", c.name, visible(mode.c_cc[c.index])));
+                empty = false;
+            }
+        }
+        if mode.c_lflag & libc::ICANON == 0 {
+            self.wrap(&format!(
+                "min = {}; time = {};\n",
+                mode.c_cc[libc::VMIN],
+                mode.c_cc[libc::VTIME]
+            ));
+        } else if !empty {
+            println!();
+        }
+        self.column = 0;
+
+        let mut empty = true;
+        let mut kind = flags::Kind::Control;
+        for flag in FLAGS.iter().filter(|flag| flag.flags & OMIT == 0) {
+            if flag.kind != kind {
+                if !empty {
+                    self.newline();
+                    empty = true;
+                }
+                kind = flag.kind;
+            }
+            if flag.is_set(mode) {
+                if flag.flags & SANE_UNSET != 0 {
+                    self.wrap(flag.name);
+                    empty = false;
+                }
+            } else if flag.flags & (SANE_SET | REV) == SANE_SET | REV {
+                self.wrap(&format!("-{}", flag.name));
+                empty = false;
+            }
+        }
+        if !empty {
+            self.newline();
+        }
+    }
+
+    /// Print every setting.
+    fn print_all(&mut self, mode: &termios, fd: c_int) -> Result<(), String> {
+        self.print_speed(mode, true);
+        match get_window_size(fd) {
+            Ok(size) => self.wrap(&format!("rows {}; columns {};", size.ws_row, size.ws_col)),
+            // not every terminal has a size
+            Err(ref e) if e.raw_os_error() == Some(libc::EINVAL) => {}
+            Err(e) => return Err(error_message(&e)),
+        }
+        self.print_line(mode);
+        self.newline();
+
+        for c in CONTROL_CHARS.iter().take_while(|c| c.name != "min") {
+            self.wrap(&format!("{} = {};", c.name, visible(mode.c_cc[c.index])));
+        }
+        self.wrap(&format!(
+            "min = {}; time = {};",
+            mode.c_cc[libc::VMIN],
+            mode.c_cc[libc::VTIME]
+        ));
+        if self.column != 0 {
+            println!();
+        }
+        self.column = 0;
+
+        let mut kind = flags::Kind::Control;
+        for flag in FLAGS.iter().filter(|flag| flag.flags & OMIT == 0) {
+            if flag.kind != kind {
+                self.newline();
+                kind = flag.kind;
+            }
+            if flag.is_set(mode) {
+                self.wrap(flag.name);
+            } else if flag.flags & REV != 0 {
+                self.wrap(&format!("-{}", flag.name));
+            }
+        }
+        self.newline();
+        Ok(())
+    }
+}
+
+/// The width of the screen settings are wrapped to: that of the terminal on
+/// standard output, or $COLUMNS, or 80.
+fn screen_columns() -> usize {
+    if let Ok(size) = get_window_size(libc::STDOUT_FILENO) {
+        if size.ws_col > 0 {
+            return size.ws_col as usize;
+        }
+    }
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse::<usize>().ok())
+        .filter(|&columns| columns > 0 && columns <= i32::max_value() as usize)
+        .unwrap_or(80)
+}
+
+/// How the special character `c` is printed: "^X" for a control character,
+/// "M-" before one with the high bit set and "<undef>" if it's disabled.
+fn visible(c: cc_t) -> String {
+    if c == DISABLED {
+        return "<undef>".to_owned();
+    }
+    let (meta, c) = if c >= 0o200 {
+        ("M-", c - 0o200)
+    } else {
+        ("", c)
+    };
+    match c {
+        0o177 => format!("{}^?", meta),
+        c if c < 0o40 => format!("{}^{}", meta, (c + 0o100) as char),
+        c => format!("{}{}", meta, c as char),
+    }
+}
+
+/// `name`, quoted if it has more than letters, digits and punctuation that
+/// the shell takes as it is.
+fn quote_name(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._+,:@%^=-".contains(c))
+    {
+        name.to_owned()
+    } else {
+        format!("'{}'", name)
+    }
+}
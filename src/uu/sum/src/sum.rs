@@ -12,56 +12,34 @@ extern crate uucore;
 
 use clap::{App, Arg};
 use std::fs::File;
-use std::io::{stdin, Read, Result};
+use std::io::{stdin, ErrorKind, Read, Result};
 use std::path::Path;
+use uucore::sum::{Bsd, Digest, SysV};
 
 static NAME: &str = "sum";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
-static USAGE: &str =
-    "[OPTION]... [FILE]...\nWith no FILE, or when  FILE is -, read standard input.";
+static USAGE: &str = "[OPTION]... [FILE]...";
 static SUMMARY: &str = "Checksum and count the blocks in a file.";
+static LONG_HELP: &str = "With no FILE, or when FILE is -, read standard input.";
 
-fn bsd_sum(mut reader: Box<dyn Read>) -> (usize, u16) {
-    let mut buf = [0; 1024];
-    let mut blocks_read = 0;
-    let mut checksum: u16 = 0;
+/// The checksum `digest` makes of `reader`, and how many bytes it read.
+fn sum(mut reader: Box<dyn Read>, digest: &mut dyn Digest) -> Result<([u8; 2], u64)> {
+    let mut buf = [0; 4096];
+    let mut size = 0;
     loop {
         match reader.read(&mut buf) {
-            Ok(n) if n != 0 => {
-                blocks_read += 1;
-                for &byte in buf[..n].iter() {
-                    checksum = (checksum >> 1) + ((checksum & 1) << 15);
-                    checksum = checksum.wrapping_add(u16::from(byte));
-                }
+            Ok(0) => break,
+            Ok(n) => {
+                digest.input(&buf[..n]);
+                size += n as u64;
             }
-            _ => break,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
         }
     }
-
-    (blocks_read, checksum)
-}
-
-fn sysv_sum(mut reader: Box<dyn Read>) -> (usize, u16) {
-    let mut buf = [0; 512];
-    let mut blocks_read = 0;
-    let mut ret = 0u32;
-
-    loop {
-        match reader.read(&mut buf) {
-            Ok(n) if n != 0 => {
-                blocks_read += 1;
-                for &byte in buf[..n].iter() {
-                    ret = ret.wrapping_add(u32::from(byte));
-                }
-            }
-            _ => break,
-        }
-    }
-
-    ret = (ret & 0xffff) + (ret >> 16);
-    ret = (ret & 0xffff) + (ret >> 16);
-
-    (blocks_read, ret as u16)
+    let mut checksum = [0; 2];
+    digest.result(&mut checksum);
+    Ok((checksum, size))
 }
 
 fn open(name: &str) -> Result<Box<dyn Read>> {
@@ -96,60 +74,78 @@ mod options {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = App::new(executable!())
-        .name(NAME)
-        .version(VERSION)
-        .usage(USAGE)
-        .about(SUMMARY)
-        .arg(Arg::with_name(options::FILE).multiple(true).hidden(true))
-        .arg(
-            Arg::with_name(options::BSD_COMPATIBLE)
-                .short(options::BSD_COMPATIBLE)
-                .help("use the BSD compatible algorithm (default)"),
-        )
-        .arg(
-            Arg::with_name(options::SYSTEM_V_COMPATIBLE)
-                .short("s")
-                .long(options::SYSTEM_V_COMPATIBLE)
-                .help("use the BSD compatible algorithm (default)"),
-        )
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app(), args);
 
-    let files: Vec<String> = match matches.values_of(options::FILE) {
-        Some(v) => v.clone().map(|v| v.to_owned()).collect(),
-        None => vec!["-".to_owned()],
+    // names are printed when any files are given, even just "-"
+    let (files, print_names): (Vec<String>, bool) = match matches.values_of(options::FILE) {
+        Some(v) => (v.map(|v| v.to_owned()).collect(), true),
+        None => (vec!["-".to_owned()], false),
     };
 
-    let sysv = matches.is_present(options::SYSTEM_V_COMPATIBLE);
-
-    let print_names = if sysv {
-        files.len() > 1 || files[0] != "-"
-    } else {
-        files.len() > 1
+    // the last of -r and -s wins
+    let last = |option| matches.indices_of(option).and_then(|indices| indices.max());
+    let sysv = match (
+        last(options::SYSTEM_V_COMPATIBLE),
+        last(options::BSD_COMPATIBLE),
+    ) {
+        (Some(sysv), Some(bsd)) => sysv > bsd,
+        (sysv, _) => sysv.is_some(),
     };
 
     let mut exit_code = 0;
     for file in &files {
-        let reader = match open(file) {
-            Ok(f) => f,
+        let result = open(file).and_then(|reader| {
+            if sysv {
+                sum(reader, &mut SysV::new())
+            } else {
+                sum(reader, &mut Bsd::new())
+            }
+        });
+        let (checksum, size) = match result {
+            Ok(result) => result,
             Err(error) => {
                 show_error!("'{}' {}", file, error);
                 exit_code = 2;
                 continue;
             }
         };
-        let (blocks, sum) = if sysv {
-            sysv_sum(reader)
+        let checksum = u16::from_be_bytes(checksum);
+
+        // the BSD sum counts 1K blocks, and the System V sum 512 byte ones
+        let line = if sysv {
+            format!("{} {}", checksum, (size + 511) / 512)
         } else {
-            bsd_sum(reader)
+            format!("{:05} {:5}", checksum, (size + 1023) / 1024)
         };
-
         if print_names {
-            println!("{} {} {}", sum, blocks, file);
+            println!("{} {}", line, file);
         } else {
-            println!("{} {}", sum, blocks);
+            println!("{}", line);
         }
     }
 
     exit_code
 }
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .name(NAME)
+        .version(VERSION)
+        .usage(USAGE)
+        .about(SUMMARY)
+        .after_help(LONG_HELP)
+        .arg(Arg::with_name(options::FILE).multiple(true).hidden(true))
+        .arg(
+            Arg::with_name(options::BSD_COMPATIBLE)
+                .short(options::BSD_COMPATIBLE)
+                .multiple(true)
+                .help("use the BSD sum algorithm, use 1K blocks (default)"),
+        )
+        .arg(
+            Arg::with_name(options::SYSTEM_V_COMPATIBLE)
+                .short("s")
+                .long(options::SYSTEM_V_COMPATIBLE)
+                .multiple(true)
+                .help("use the System V sum algorithm, use 512 bytes blocks"),
+        )
+}
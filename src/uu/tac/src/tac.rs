@@ -12,74 +12,59 @@ extern crate uucore;
 
 use std::fs::File;
 use std::io::{stdin, stdout, BufReader, Read, Stdout, Write};
+use uucore::coreopts::CoreOptions;
 
-static NAME: &str = "tac";
-static VERSION: &str = env!("CARGO_PKG_VERSION");
+static SYNTAX: &str = "[OPTION]... [FILE]...";
+static SUMMARY: &str = "Write each file to standard output, last line first.";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let mut opts = getopts::Options::new();
+    let matches = uu_app().parse(args);
 
-    opts.optflag(
+    let before = matches.opt_present("b");
+    let regex = matches.opt_present("r");
+    let separator = match matches.opt_str("s") {
+        Some(m) => {
+            if m.is_empty() {
+                crash!(1, "separator cannot be empty")
+            } else {
+                m
+            }
+        }
+        None => "\n".to_owned(),
+    };
+    let files = if matches.free.is_empty() {
+        vec!["-".to_owned()]
+    } else {
+        matches.free
+    };
+    tac(files, before, regex, &separator[..]);
+
+    0
+}
+
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, "");
+    app.optflag(
         "b",
         "before",
         "attach the separator before instead of after",
-    );
-    opts.optflag(
+    )
+    .optflag(
         "r",
         "regex",
         "interpret the sequence as a regular expression (NOT IMPLEMENTED)",
-    );
-    opts.optopt(
+    )
+    .optopt(
         "s",
         "separator",
         "use STRING as the separator instead of newline",
         "STRING",
-    );
-    opts.optflag("h", "help", "display this help and exit");
-    opts.optflag("V", "version", "output version information and exit");
-
-    let matches = match opts.parse(&args[1..]) {
-        Ok(m) => m,
-        Err(f) => crash!(1, "{}", f),
-    };
-    if matches.opt_present("help") {
-        let msg = format!(
-            "{0} {1}
-
-Usage:
-  {0} [OPTION]... [FILE]...
-
-Write each file to standard output, last line first.",
-            NAME, VERSION
-        );
-
-        print!("{}", opts.usage(&msg));
-    } else if matches.opt_present("version") {
-        println!("{} {}", NAME, VERSION);
-    } else {
-        let before = matches.opt_present("b");
-        let regex = matches.opt_present("r");
-        let separator = match matches.opt_str("s") {
-            Some(m) => {
-                if m.is_empty() {
-                    crash!(1, "separator cannot be empty")
-                } else {
-                    m
-                }
-            }
-            None => "\n".to_owned(),
-        };
-        let files = if matches.free.is_empty() {
-            vec!["-".to_owned()]
-        } else {
-            matches.free
-        };
-        tac(files, before, regex, &separator[..]);
-    }
-
-    0
+    )
+    .optflag("h", "help", "display this help and exit")
+    .optflag("V", "version", "output version information and exit");
+    app
 }
 
 fn tac(filenames: Vec<String>, before: bool, _: bool, separator: &str) {
@@ -72,74 +72,9 @@ impl Default for Settings {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let mut settings: Settings = Default::default();
 
-    let app = App::new(executable!())
-        .version(crate_version!())
-        .about("output the last part of files")
-        .arg(
-            Arg::with_name(options::BYTES)
-                .short("c")
-                .long(options::BYTES)
-                .takes_value(true)
-                .allow_hyphen_values(true)
-                .help("Number of bytes to print"),
-        )
-        .arg(
-            Arg::with_name(options::FOLLOW)
-                .short("f")
-                .long(options::FOLLOW)
-                .help("Print the file as it grows"),
-        )
-        .arg(
-            Arg::with_name(options::LINES)
-                .short("n")
-                .long(options::LINES)
-                .takes_value(true)
-                .allow_hyphen_values(true)
-                .help("Number of lines to print"),
-        )
-        .arg(
-            Arg::with_name(options::PID)
-                .long(options::PID)
-                .takes_value(true)
-                .help("with -f, terminate after process ID, PID dies"),
-        )
-        .arg(
-            Arg::with_name(options::verbosity::QUIET)
-                .short("q")
-                .long(options::verbosity::QUIET)
-                .help("never output headers giving file names"),
-        )
-        .arg(
-            Arg::with_name(options::verbosity::SILENT)
-                .long(options::verbosity::SILENT)
-                .help("synonym of --quiet"),
-        )
-        .arg(
-            Arg::with_name(options::SLEEP_INT)
-                .short("s")
-                .long(options::SLEEP_INT)
-                .help("Number or seconds to sleep between polling the file when running with -f"),
-        )
-        .arg(
-            Arg::with_name(options::verbosity::VERBOSE)
-                .short("v")
-                .long(options::verbosity::VERBOSE)
-                .help("always output headers giving file names"),
-        )
-        .arg(
-            Arg::with_name(options::ZERO_TERM)
-                .short("z")
-                .long(options::ZERO_TERM)
-                .help("Line delimiter is NUL, not newline"),
-        )
-        .arg(
-            Arg::with_name(ARG_FILES)
-                .multiple(true)
-                .takes_value(true)
-                .min_values(1),
-        );
+    let app = uu_app();
 
-    let matches = app.get_matches_from(args);
+    let matches = uucore::help::get_matches_from(app, args);
 
     settings.follow = matches.is_present(options::FOLLOW);
     if settings.follow {
@@ -260,6 +195,75 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     0
 }
 
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(crate_version!())
+        .about("output the last part of files")
+        .arg(
+            Arg::with_name(options::BYTES)
+                .short("c")
+                .long(options::BYTES)
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .help("Number of bytes to print"),
+        )
+        .arg(
+            Arg::with_name(options::FOLLOW)
+                .short("f")
+                .long(options::FOLLOW)
+                .help("Print the file as it grows"),
+        )
+        .arg(
+            Arg::with_name(options::LINES)
+                .short("n")
+                .long(options::LINES)
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .help("Number of lines to print"),
+        )
+        .arg(
+            Arg::with_name(options::PID)
+                .long(options::PID)
+                .takes_value(true)
+                .help("with -f, terminate after process ID, PID dies"),
+        )
+        .arg(
+            Arg::with_name(options::verbosity::QUIET)
+                .short("q")
+                .long(options::verbosity::QUIET)
+                .help("never output headers giving file names"),
+        )
+        .arg(
+            Arg::with_name(options::verbosity::SILENT)
+                .long(options::verbosity::SILENT)
+                .help("synonym of --quiet"),
+        )
+        .arg(
+            Arg::with_name(options::SLEEP_INT)
+                .short("s")
+                .long(options::SLEEP_INT)
+                .help("Number or seconds to sleep between polling the file when running with -f"),
+        )
+        .arg(
+            Arg::with_name(options::verbosity::VERBOSE)
+                .short("v")
+                .long(options::verbosity::VERBOSE)
+                .help("always output headers giving file names"),
+        )
+        .arg(
+            Arg::with_name(options::ZERO_TERM)
+                .short("z")
+                .long(options::ZERO_TERM)
+                .help("Line delimiter is NUL, not newline"),
+        )
+        .arg(
+            Arg::with_name(ARG_FILES)
+                .multiple(true)
+                .takes_value(true)
+                .min_values(1),
+        )
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum ParseSizeErr {
     ParseFailure(String),
@@ -40,10 +40,27 @@ fn get_usage() -> String {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let options = Options {
+        append: matches.is_present(options::APPEND),
+        ignore_interrupts: matches.is_present(options::IGNORE_INTERRUPTS),
+        files: matches
+            .values_of(options::FILE)
+            .map(|v| v.map(ToString::to_string).collect())
+            .unwrap_or_default(),
+    };
+
+    match tee(options) {
+        Ok(_) => 0,
+        Err(_) => 1,
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .after_help("If a FILE is -, it refers to a file named - .")
         .arg(
             Arg::with_name(options::APPEND)
@@ -58,21 +75,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .help("ignore interrupt signals (ignored on non-Unix platforms)"),
         )
         .arg(Arg::with_name(options::FILE).multiple(true))
-        .get_matches_from(args);
-
-    let options = Options {
-        append: matches.is_present(options::APPEND),
-        ignore_interrupts: matches.is_present(options::IGNORE_INTERRUPTS),
-        files: matches
-            .values_of(options::FILE)
-            .map(|v| v.map(ToString::to_string).collect())
-            .unwrap_or_default(),
-    };
-
-    match tee(options) {
-        Ok(_) => 0,
-        Err(_) => 1,
-    }
 }
 
 #[cfg(unix)]
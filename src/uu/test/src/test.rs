@@ -5,12 +5,24 @@
 //  * For the full copyright and license information, please view the LICENSE
 //  * file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) retval paren prec subprec cond
+// spell-checker:ignore (ToDO) retval paren prec subprec cond nargs unop binop
 
-use std::collections::HashMap;
+#[macro_use]
+extern crate uucore;
+
+use std::fs;
+use std::path::Path;
 use std::str::from_utf8;
+use std::time::SystemTime;
+use uucore::coreopts::CoreOptions;
+
+static SYNTAX: &str = "EXPRESSION";
+static SUMMARY: &str = "Exit with the status determined by EXPRESSION.";
+static LONG_HELP: &str = "
+ The command may also be run as '[ EXPRESSION ]'.
 
-static NAME: &str = "test";
+ Exit status is 0 if EXPRESSION is true, 1 if it is false, and 2 if an error occurred.
+";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args: Vec<_> = args.collect();
@@ -22,87 +34,295 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     if args.is_empty() {
         return 2;
     }
-    let args = if !args[0].ends_with(NAME.as_bytes()) {
-        &args[1..]
+    let args = if args[0].ends_with(b"[") {
+        match args.last() {
+            Some(&b"]") if args.len() > 1 => &args[1..args.len() - 1],
+            _ => {
+                show_error!("missing ']'");
+                return 2;
+            }
+        }
     } else {
-        &args[..]
-    };
-    let args = match args[0] {
-        b"[" => match args[args.len() - 1] {
-            b"]" => &args[1..args.len() - 1],
-            _ => return 2,
-        },
-        _ => &args[1..args.len()],
+        &args[1..]
     };
-    let mut error = false;
-    let retval = 1 - parse_expr(args, &mut error) as i32;
-    if error {
-        2
+
+    let mut parser = Parser { args, pos: 0 };
+    let result = if args.is_empty() {
+        Ok(false)
     } else {
-        retval
+        parser
+            .posix(args.len())
+            .and_then(|value| match args.get(parser.pos) {
+                Some(extra) => Err(format!("extra argument '{}'", show(extra))),
+                None => Ok(value),
+            })
+    };
+    match result {
+        Ok(value) => 1 - value as i32,
+        Err(e) => {
+            show_error!("{}", e);
+            2
+        }
     }
 }
 
-fn one(args: &[&[u8]]) -> bool {
-    !args[0].is_empty()
+pub fn uu_app() -> CoreOptions<'static> {
+    app!(SYNTAX, SUMMARY, LONG_HELP)
+}
+
+fn show(arg: &[u8]) -> String {
+    String::from_utf8_lossy(arg).into_owned()
+}
+
+/// The unary operators: a dash and a letter.
+static UNARY_OPS: &[&[u8]] = &[
+    b"-b", b"-c", b"-d", b"-e", b"-f", b"-g", b"-G", b"-h", b"-k", b"-L", b"-n", b"-N", b"-O",
+    b"-p", b"-r", b"-s", b"-S", b"-t", b"-u", b"-w", b"-x", b"-z",
+];
+
+static BINARY_OPS: &[&[u8]] = &[
+    b"=", b"==", b"!=", b"<", b">", b"-eq", b"-ne", b"-gt", b"-ge", b"-lt", b"-le", b"-nt", b"-ot",
+    b"-ef",
+];
+
+fn is_unary_op(arg: &[u8]) -> bool {
+    UNARY_OPS.contains(&arg)
+}
+
+/// Whether `arg` looks like a unary operator, if it isn't one.
+fn is_unary_like(arg: &[u8]) -> bool {
+    arg.len() == 2 && arg[0] == b'-'
+}
+
+fn is_binary_op(arg: &[u8]) -> bool {
+    BINARY_OPS.contains(&arg)
 }
 
-fn two(args: &[&[u8]], error: &mut bool) -> bool {
-    match args[0] {
-        b"!" => !one(&args[1..]),
-        b"-b" => path(args[1], PathCondition::BlockSpecial),
-        b"-c" => path(args[1], PathCondition::CharacterSpecial),
-        b"-d" => path(args[1], PathCondition::Directory),
-        b"-e" => path(args[1], PathCondition::Exists),
-        b"-f" => path(args[1], PathCondition::Regular),
-        b"-g" => path(args[1], PathCondition::GroupIDFlag),
-        b"-h" => path(args[1], PathCondition::SymLink),
-        b"-L" => path(args[1], PathCondition::SymLink),
-        b"-n" => one(&args[1..]),
-        b"-p" => path(args[1], PathCondition::FIFO),
-        b"-r" => path(args[1], PathCondition::Readable),
-        b"-S" => path(args[1], PathCondition::Socket),
-        b"-s" => path(args[1], PathCondition::NonEmpty),
-        b"-t" => isatty(args[1]),
-        b"-u" => path(args[1], PathCondition::UserIDFlag),
-        b"-w" => path(args[1], PathCondition::Writable),
-        b"-x" => path(args[1], PathCondition::Executable),
-        b"-z" => !one(&args[1..]),
-        _ => {
-            *error = true;
-            false
+type ParseResult = Result<bool, String>;
+
+/// The arguments being evaluated, from `pos` on, as GNU test does: up to 4
+/// arguments go by the POSIX rules, which decide by how many there are, and
+/// more by precedence, `!` binding tighter than `-a`, and `-a` than `-o`.
+struct Parser<'a> {
+    args: &'a [&'a [u8]],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn arg(&self, offset: usize) -> &'a [u8] {
+        self.args[self.pos + offset]
+    }
+
+    /// The error for an operator missing its operand.
+    fn beyond(&self) -> String {
+        format!(
+            "missing argument after '{}'",
+            show(self.args[self.args.len() - 1])
+        )
+    }
+
+    /// The next `nargs` arguments, by the POSIX rules.
+    fn posix(&mut self, nargs: usize) -> ParseResult {
+        match nargs {
+            1 => Ok(self.one()),
+            2 => self.two(),
+            3 => self.three(),
+            4 => self.four(),
+            _ => self.expr(),
         }
     }
-}
 
-fn three(args: &[&[u8]], error: &mut bool) -> bool {
-    match args[1] {
-        b"=" => args[0] == args[2],
-        b"==" => args[0] == args[2],
-        b"!=" => args[0] != args[2],
-        b"-eq" => integers(args[0], args[2], IntegerCondition::Equal),
-        b"-ne" => integers(args[0], args[2], IntegerCondition::Unequal),
-        b"-gt" => integers(args[0], args[2], IntegerCondition::Greater),
-        b"-ge" => integers(args[0], args[2], IntegerCondition::GreaterEqual),
-        b"-lt" => integers(args[0], args[2], IntegerCondition::Less),
-        b"-le" => integers(args[0], args[2], IntegerCondition::LessEqual),
-        _ => match args[0] {
-            b"!" => !two(&args[1..], error),
-            _ => {
-                *error = true;
-                false
+    /// A string: true if it isn't empty.
+    fn one(&mut self) -> bool {
+        let arg = self.arg(0);
+        self.pos += 1;
+        !arg.is_empty()
+    }
+
+    fn two(&mut self) -> ParseResult {
+        let op = self.arg(0);
+        if op == b"!" {
+            self.pos += 1;
+            Ok(!self.one())
+        } else if is_unary_like(op) {
+            if !is_unary_op(op) {
+                return Err(format!("'{}': unary operator expected", show(op)));
             }
-        },
+            let operand = self.arg(1);
+            self.pos += 2;
+            unary(op, operand)
+        } else {
+            Err(self.beyond())
+        }
+    }
+
+    fn three(&mut self) -> ParseResult {
+        let (first, op, last) = (self.arg(0), self.arg(1), self.arg(2));
+        if is_binary_op(op) {
+            self.pos += 3;
+            binary(first, op, last)
+        } else if first == b"!" {
+            self.pos += 1;
+            Ok(!self.two()?)
+        } else if first == b"(" && last == b")" {
+            self.pos += 1;
+            let value = self.one();
+            self.pos += 1;
+            Ok(value)
+        } else if op == b"-a" || op == b"-o" {
+            self.expr()
+        } else {
+            Err(format!("'{}': binary operator expected", show(op)))
+        }
+    }
+
+    fn four(&mut self) -> ParseResult {
+        if self.arg(0) == b"!" {
+            self.pos += 1;
+            Ok(!self.three()?)
+        } else if self.arg(0) == b"(" && self.arg(3) == b")" {
+            self.pos += 1;
+            let value = self.two()?;
+            self.pos += 1;
+            Ok(value)
+        } else {
+            self.expr()
+        }
+    }
+
+    fn expr(&mut self) -> ParseResult {
+        if self.pos >= self.args.len() {
+            return Err(self.beyond());
+        }
+        self.or()
+    }
+
+    fn or(&mut self) -> ParseResult {
+        let mut value = self.and()?;
+        while self.args.get(self.pos) == Some(&&b"-o"[..]) {
+            self.pos += 1;
+            // both sides are parsed, whatever the first one is
+            let rhs = self.and()?;
+            value = value || rhs;
+        }
+        Ok(value)
     }
-}
 
-fn four(args: &[&[u8]], error: &mut bool) -> bool {
-    match args[0] {
-        b"!" => !three(&args[1..], error),
-        _ => {
-            *error = true;
-            false
+    fn and(&mut self) -> ParseResult {
+        let mut value = self.term()?;
+        while self.args.get(self.pos) == Some(&&b"-a"[..]) {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = value && rhs;
         }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> ParseResult {
+        let mut negated = false;
+        while self.args.get(self.pos) == Some(&&b"!"[..]) {
+            self.pos += 1;
+            negated = !negated;
+        }
+        let remaining = self.args.len().saturating_sub(self.pos);
+        if remaining == 0 {
+            return Err(self.beyond());
+        }
+
+        let first = self.arg(0);
+        let value = if first == b"(" {
+            self.pos += 1;
+            if self.pos >= self.args.len() {
+                return Err(self.beyond());
+            }
+            // up to 4 arguments before the ")" go by the POSIX rules
+            let mut nargs = 1;
+            while self.pos + nargs < self.args.len() && self.arg(nargs) != b")" {
+                if nargs == 4 {
+                    nargs = self.args.len() - self.pos;
+                    break;
+                }
+                nargs += 1;
+            }
+            let value = self.posix(nargs)?;
+            match self.args.get(self.pos) {
+                Some(&b")") => self.pos += 1,
+                Some(found) => return Err(format!("')' expected, found '{}'", show(found))),
+                None => return Err("')' expected".to_owned()),
+            }
+            value
+        } else if remaining >= 3 && is_binary_op(self.arg(1)) {
+            let (op, last) = (self.arg(1), self.arg(2));
+            self.pos += 3;
+            binary(first, op, last)?
+        } else if is_unary_like(first) {
+            if !is_unary_op(first) {
+                return Err(format!("'{}': unary operator expected", show(first)));
+            }
+            if remaining < 2 {
+                return Err(self.beyond());
+            }
+            let operand = self.arg(1);
+            self.pos += 2;
+            unary(first, operand)?
+        } else {
+            self.one()
+        };
+        Ok(negated ^ value)
+    }
+}
+
+fn unary(op: &[u8], operand: &[u8]) -> ParseResult {
+    Ok(match op {
+        b"-b" => path(operand, PathCondition::BlockSpecial),
+        b"-c" => path(operand, PathCondition::CharacterSpecial),
+        b"-d" => path(operand, PathCondition::Directory),
+        b"-e" => path(operand, PathCondition::Exists),
+        b"-f" => path(operand, PathCondition::Regular),
+        b"-g" => path(operand, PathCondition::GroupIDFlag),
+        b"-G" => path(operand, PathCondition::GroupOwned),
+        b"-h" => path(operand, PathCondition::SymLink),
+        b"-k" => path(operand, PathCondition::Sticky),
+        b"-L" => path(operand, PathCondition::SymLink),
+        b"-n" => !operand.is_empty(),
+        b"-N" => path(operand, PathCondition::ModifiedSinceRead),
+        b"-O" => path(operand, PathCondition::UserOwned),
+        b"-p" => path(operand, PathCondition::FIFO),
+        b"-r" => path(operand, PathCondition::Readable),
+        b"-S" => path(operand, PathCondition::Socket),
+        b"-s" => path(operand, PathCondition::NonEmpty),
+        b"-t" => isatty(operand),
+        b"-u" => path(operand, PathCondition::UserIDFlag),
+        b"-w" => path(operand, PathCondition::Writable),
+        b"-x" => path(operand, PathCondition::Executable),
+        b"-z" => operand.is_empty(),
+        _ => unreachable!(),
+    })
+}
+
+fn binary(lhs: &[u8], op: &[u8], rhs: &[u8]) -> ParseResult {
+    match op {
+        b"=" | b"==" => Ok(lhs == rhs),
+        b"!=" => Ok(lhs != rhs),
+        b"<" => Ok(lhs < rhs),
+        b">" => Ok(lhs > rhs),
+        b"-eq" => integers(lhs, rhs, IntegerCondition::Equal),
+        b"-ne" => integers(lhs, rhs, IntegerCondition::Unequal),
+        b"-gt" => integers(lhs, rhs, IntegerCondition::Greater),
+        b"-ge" => integers(lhs, rhs, IntegerCondition::GreaterEqual),
+        b"-lt" => integers(lhs, rhs, IntegerCondition::Less),
+        b"-le" => integers(lhs, rhs, IntegerCondition::LessEqual),
+        // a file that doesn't exist is older than any that does
+        b"-nt" => Ok(match (modified(lhs), modified(rhs)) {
+            (Some(lhs), Some(rhs)) => lhs > rhs,
+            (lhs, rhs) => lhs.is_some() && rhs.is_none(),
+        }),
+        b"-ot" => Ok(match (modified(lhs), modified(rhs)) {
+            (Some(lhs), Some(rhs)) => lhs < rhs,
+            (lhs, rhs) => lhs.is_none() && rhs.is_some(),
+        }),
+        b"-ef" => Ok(same_file(lhs, rhs)),
+        _ => unreachable!(),
     }
 }
 
@@ -115,23 +335,22 @@ enum IntegerCondition {
     LessEqual,
 }
 
-fn integers(a: &[u8], b: &[u8], cond: IntegerCondition) -> bool {
-    let (a, b): (&str, &str) = match (from_utf8(a), from_utf8(b)) {
-        (Ok(a), Ok(b)) => (a, b),
-        _ => return false,
-    };
-    let (a, b): (i64, i64) = match (a.parse(), b.parse()) {
-        (Ok(a), Ok(b)) => (a, b),
-        _ => return false,
+fn integers(a: &[u8], b: &[u8], cond: IntegerCondition) -> ParseResult {
+    let integer = |arg: &[u8]| -> Result<i64, String> {
+        from_utf8(arg)
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .ok_or_else(|| format!("invalid integer '{}'", show(arg)))
     };
-    match cond {
+    let (a, b) = (integer(a)?, integer(b)?);
+    Ok(match cond {
         IntegerCondition::Equal => a == b,
         IntegerCondition::Unequal => a != b,
         IntegerCondition::Greater => a > b,
         IntegerCondition::GreaterEqual => a >= b,
         IntegerCondition::Less => a < b,
         IntegerCondition::LessEqual => a <= b,
-    }
+    })
 }
 
 fn isatty(fd: &[u8]) -> bool {
@@ -148,173 +367,6 @@ fn isatty(fd: &[u8]) -> bool {
         })
 }
 
-fn dispatch(args: &mut &[&[u8]], error: &mut bool) -> bool {
-    let (val, idx) = match args.len() {
-        0 => {
-            *error = true;
-            (false, 0)
-        }
-        1 => (one(*args), 1),
-        2 => dispatch_two(args, error),
-        3 => dispatch_three(args, error),
-        _ => dispatch_four(args, error),
-    };
-    *args = &(*args)[idx..];
-    val
-}
-
-fn dispatch_two(args: &mut &[&[u8]], error: &mut bool) -> (bool, usize) {
-    let val = two(*args, error);
-    if *error {
-        *error = false;
-        (one(*args), 1)
-    } else {
-        (val, 2)
-    }
-}
-
-fn dispatch_three(args: &mut &[&[u8]], error: &mut bool) -> (bool, usize) {
-    let val = three(*args, error);
-    if *error {
-        *error = false;
-        dispatch_two(args, error)
-    } else {
-        (val, 3)
-    }
-}
-
-fn dispatch_four(args: &mut &[&[u8]], error: &mut bool) -> (bool, usize) {
-    let val = four(*args, error);
-    if *error {
-        *error = false;
-        dispatch_three(args, error)
-    } else {
-        (val, 4)
-    }
-}
-
-#[derive(Clone, Copy)]
-enum Precedence {
-    Unknown = 0,
-    Paren, // FIXME: this is useless (parentheses have not been implemented)
-    Or,
-    And,
-    BUnOp,
-    BinOp,
-    UnOp,
-}
-
-fn parse_expr(mut args: &[&[u8]], error: &mut bool) -> bool {
-    if args.is_empty() {
-        false
-    } else {
-        let hashmap = setup_hashmap();
-        let lhs = dispatch(&mut args, error);
-
-        if !args.is_empty() {
-            parse_expr_helper(&hashmap, &mut args, lhs, Precedence::Unknown, error)
-        } else {
-            lhs
-        }
-    }
-}
-
-fn parse_expr_helper<'a>(
-    hashmap: &HashMap<&'a [u8], Precedence>,
-    args: &mut &[&'a [u8]],
-    mut lhs: bool,
-    min_prec: Precedence,
-    error: &mut bool,
-) -> bool {
-    let mut prec = *hashmap.get(&args[0]).unwrap_or_else(|| {
-        *error = true;
-        &min_prec
-    });
-    while !*error && !args.is_empty() && prec as usize >= min_prec as usize {
-        let op = args[0];
-        *args = &(*args)[1..];
-        let mut rhs = dispatch(args, error);
-        while !args.is_empty() {
-            let subprec = *hashmap.get(&args[0]).unwrap_or_else(|| {
-                *error = true;
-                &min_prec
-            });
-            if subprec as usize <= prec as usize || *error {
-                break;
-            }
-            rhs = parse_expr_helper(hashmap, args, rhs, subprec, error);
-        }
-        lhs = match prec {
-            Precedence::UnOp | Precedence::BUnOp => {
-                *error = true;
-                false
-            }
-            Precedence::And => lhs && rhs,
-            Precedence::Or => lhs || rhs,
-            Precedence::BinOp => three(
-                &[
-                    if lhs { b" " } else { b"" },
-                    op,
-                    if rhs { b" " } else { b"" },
-                ],
-                error,
-            ),
-            Precedence::Paren => unimplemented!(), // TODO: implement parentheses
-            _ => unreachable!(),
-        };
-        if !args.is_empty() {
-            prec = *hashmap.get(&args[0]).unwrap_or_else(|| {
-                *error = true;
-                &min_prec
-            });
-        }
-    }
-    lhs
-}
-
-#[inline]
-fn setup_hashmap<'a>() -> HashMap<&'a [u8], Precedence> {
-    let mut hashmap = HashMap::<&'a [u8], Precedence>::new();
-
-    hashmap.insert(b"-b", Precedence::UnOp);
-    hashmap.insert(b"-c", Precedence::UnOp);
-    hashmap.insert(b"-d", Precedence::UnOp);
-    hashmap.insert(b"-e", Precedence::UnOp);
-    hashmap.insert(b"-f", Precedence::UnOp);
-    hashmap.insert(b"-g", Precedence::UnOp);
-    hashmap.insert(b"-h", Precedence::UnOp);
-    hashmap.insert(b"-L", Precedence::UnOp);
-    hashmap.insert(b"-n", Precedence::UnOp);
-    hashmap.insert(b"-p", Precedence::UnOp);
-    hashmap.insert(b"-r", Precedence::UnOp);
-    hashmap.insert(b"-S", Precedence::UnOp);
-    hashmap.insert(b"-s", Precedence::UnOp);
-    hashmap.insert(b"-t", Precedence::UnOp);
-    hashmap.insert(b"-u", Precedence::UnOp);
-    hashmap.insert(b"-w", Precedence::UnOp);
-    hashmap.insert(b"-x", Precedence::UnOp);
-    hashmap.insert(b"-z", Precedence::UnOp);
-
-    hashmap.insert(b"=", Precedence::BinOp);
-    hashmap.insert(b"!=", Precedence::BinOp);
-    hashmap.insert(b"-eq", Precedence::BinOp);
-    hashmap.insert(b"-ne", Precedence::BinOp);
-    hashmap.insert(b"-gt", Precedence::BinOp);
-    hashmap.insert(b"-ge", Precedence::BinOp);
-    hashmap.insert(b"-lt", Precedence::BinOp);
-    hashmap.insert(b"-le", Precedence::BinOp);
-
-    hashmap.insert(b"!", Precedence::BUnOp);
-
-    hashmap.insert(b"-a", Precedence::And);
-    hashmap.insert(b"-o", Precedence::Or);
-
-    hashmap.insert(b"(", Precedence::Paren);
-    hashmap.insert(b")", Precedence::Paren);
-
-    hashmap
-}
-
 #[derive(Eq, PartialEq)]
 enum PathCondition {
     BlockSpecial,
@@ -323,27 +375,64 @@ enum PathCondition {
     Exists,
     Regular,
     GroupIDFlag,
+    GroupOwned,
+    ModifiedSinceRead,
     SymLink,
+    Sticky,
     FIFO,
     Readable,
     Socket,
     NonEmpty,
     UserIDFlag,
+    UserOwned,
     Writable,
     Executable,
 }
 
 #[cfg(not(windows))]
-fn path(path: &[u8], cond: PathCondition) -> bool {
+fn to_path(path: &[u8]) -> &Path {
     use std::ffi::OsStr;
-    use std::fs::{self, Metadata};
     use std::os::unix::ffi::OsStrExt;
+
+    Path::new(OsStr::from_bytes(path))
+}
+
+#[cfg(windows)]
+fn to_path(path: &[u8]) -> &Path {
+    Path::new(from_utf8(path).unwrap())
+}
+
+fn modified(path: &[u8]) -> Option<SystemTime> {
+    fs::metadata(to_path(path))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(not(windows))]
+fn same_file(a: &[u8], b: &[u8]) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (fs::metadata(to_path(a)), fs::metadata(to_path(b))) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(windows)]
+fn same_file(_a: &[u8], _b: &[u8]) -> bool {
+    false
+}
+
+#[cfg(not(windows))]
+fn path(path: &[u8], cond: PathCondition) -> bool {
+    use std::fs::Metadata;
     use std::os::unix::fs::{FileTypeExt, MetadataExt};
 
-    let path = OsStr::from_bytes(path);
+    let path = to_path(path);
 
     const S_ISUID: u32 = 0o4000;
     const S_ISGID: u32 = 0o2000;
+    const S_ISVTX: u32 = 0o1000;
 
     enum Permission {
         Read = 0o4,
@@ -391,22 +480,39 @@ fn path(path: &[u8], cond: PathCondition) -> bool {
         PathCondition::Exists => true,
         PathCondition::Regular => file_type.is_file(),
         PathCondition::GroupIDFlag => metadata.mode() & S_ISGID != 0,
+        PathCondition::GroupOwned => metadata.gid() == effective_ids().1,
+        PathCondition::ModifiedSinceRead => {
+            (metadata.mtime(), metadata.mtime_nsec()) > (metadata.atime(), metadata.atime_nsec())
+        }
         PathCondition::SymLink => metadata.file_type().is_symlink(),
+        PathCondition::Sticky => metadata.mode() & S_ISVTX != 0,
         PathCondition::FIFO => file_type.is_fifo(),
         PathCondition::Readable => perm(metadata, Permission::Read),
         PathCondition::Socket => file_type.is_socket(),
         PathCondition::NonEmpty => metadata.size() > 0,
         PathCondition::UserIDFlag => metadata.mode() & S_ISUID != 0,
+        PathCondition::UserOwned => metadata.uid() == effective_ids().0,
         PathCondition::Writable => perm(metadata, Permission::Write),
         PathCondition::Executable => perm(metadata, Permission::Execute),
     }
 }
 
+#[cfg(not(windows))]
+fn effective_ids() -> (u32, u32) {
+    #[cfg(not(target_os = "redox"))]
+    unsafe {
+        (libc::geteuid(), libc::getegid())
+    }
+    #[cfg(target_os = "redox")]
+    (
+        syscall::geteuid().unwrap() as u32,
+        syscall::getegid().unwrap() as u32,
+    )
+}
+
 #[cfg(windows)]
 fn path(path: &[u8], cond: PathCondition) -> bool {
-    use std::fs::metadata;
-    let path = from_utf8(path).unwrap();
-    let stat = match metadata(path) {
+    let stat = match fs::metadata(to_path(path)) {
         Ok(s) => s,
         _ => return false,
     };
@@ -417,12 +523,19 @@ fn path(path: &[u8], cond: PathCondition) -> bool {
         PathCondition::Exists => true,
         PathCondition::Regular => stat.is_file(),
         PathCondition::GroupIDFlag => false,
+        PathCondition::GroupOwned => false,
+        PathCondition::ModifiedSinceRead => match (stat.modified(), stat.accessed()) {
+            (Ok(modified), Ok(accessed)) => modified > accessed,
+            _ => false,
+        },
         PathCondition::SymLink => false,
+        PathCondition::Sticky => false,
         PathCondition::FIFO => false,
         PathCondition::Readable => false, // TODO
         PathCondition::Socket => false,
         PathCondition::NonEmpty => stat.len() > 0,
         PathCondition::UserIDFlag => false,
+        PathCondition::UserOwned => false,
         PathCondition::Writable => false,   // TODO
         PathCondition::Executable => false, // TODO
     }
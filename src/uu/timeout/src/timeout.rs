@@ -11,50 +11,49 @@
 extern crate uucore;
 
 use std::io::ErrorKind;
-use std::process::{Command, Stdio};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
 use std::time::Duration;
-use uucore::process::ChildExt;
+use uucore::coreopts::CoreOptions;
+use uucore::help::{self, Help};
+use uucore::process::{ChildExt, ExitStatus};
 
 static NAME: &str = "timeout";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
+static SYNTAX: &str = "[OPTION] DURATION COMMAND [ARG]...";
+static SUMMARY: &str = "Start COMMAND, and kill it if still running after DURATION.";
+static LONG_HELP: &str =
+    "DURATION is a floating point number with an optional suffix: 's' for seconds
+(the default), 'm' for minutes, 'h' for hours or 'd' for days.  A duration of 0
+disables the associated timeout.
 
+If the command times out, and --preserve-status is not set, then exit with
+status 124.  Otherwise, exit with the status of COMMAND.  If no signal is
+specified, send the TERM signal upon timeout.  The TERM signal kills any
+process that does not block or catch that signal.  It may be necessary to use
+the KILL (9) signal, since this signal cannot be caught, in which case the exit
+status is 128+9 rather than 124.
+";
+
+const EXIT_TIMED_OUT: i32 = 124;
 const ERR_EXIT_STATUS: i32 = 125;
+const EXIT_CANNOT_INVOKE: i32 = 126;
+const EXIT_ENOENT: i32 = 127;
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
     let program = args[0].clone();
 
-    let mut opts = getopts::Options::new();
-    opts.optflag(
-        "",
-        "preserve-status",
-        "exit with the same status as COMMAND, even when the command times out",
-    );
-    opts.optflag("", "foreground", "when not running timeout directly from a shell prompt, allow COMMAND to read from the TTY and get TTY signals; in this mode, children of COMMAND will not be timed out");
-    opts.optopt("k", "kill-after", "also send a KILL signal if COMMAND is still running this long after the initial signal was sent", "DURATION");
-    opts.optflag("s", "signal", "specify the signal to be sent on timeout; SIGNAL may be a name like 'HUP' or a number; see 'kill -l' for a list of signals");
-    opts.optflag("h", "help", "display this help and exit");
-    opts.optflag("V", "version", "output version information and exit");
-    let matches = match opts.parse(&args[1..]) {
+    let mut app = uu_app();
+    let matches = match app.options().parse(&args[1..]) {
         Ok(m) => m,
         Err(f) => crash!(ERR_EXIT_STATUS, "{}", f),
     };
     if matches.opt_present("help") {
-        print!(
-            "{} {}
-
-Usage:
-  {} [OPTION] DURATION COMMAND [ARG]...
-
-{}",
-            NAME,
-            VERSION,
-            program,
-            &opts.usage("Start COMMAND, and kill it if still running after DURATION.")
-        );
+        print!("{}", Help::from(&app));
     } else if matches.opt_present("version") {
-        println!("{} {}", NAME, VERSION);
+        println!("{}", help::version(NAME, VERSION));
     } else if matches.free.len() < 2 {
         show_error!("missing an argument");
         show_error!("for help, try '{0} --help'", program);
@@ -62,6 +61,7 @@ Usage:
     } else {
         let status = matches.opt_present("preserve-status");
         let foreground = matches.opt_present("foreground");
+        let verbose = matches.opt_present("verbose");
         let kill_after = match matches.opt_str("kill-after") {
             Some(tstr) => match uucore::parse_time::from_str(&tstr) {
                 Ok(time) => time,
@@ -97,12 +97,34 @@ Usage:
             kill_after,
             foreground,
             status,
+            verbose,
         );
     }
 
     0
 }
 
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, LONG_HELP);
+    // options after DURATION belong to COMMAND
+    app.parsing_style(getopts::ParsingStyle::StopAtFirstFree);
+    app.optflag(
+        "",
+        "preserve-status",
+        "exit with the same status as COMMAND, even when the command times out",
+    );
+    app.optflag("", "foreground", "when not running timeout directly from a shell prompt, allow COMMAND to read from the TTY and get TTY signals; in this mode, children of COMMAND will not be timed out");
+    app.optopt("k", "kill-after", "also send a KILL signal if COMMAND is still running this long after the initial signal was sent", "DURATION");
+    app.optopt("s", "signal", "specify the signal to be sent on timeout; SIGNAL may be a name like 'HUP' or a number; see 'kill -l' for a list of signals", "SIGNAL");
+    app.optflag(
+        "v",
+        "verbose",
+        "diagnose to stderr any signal sent upon timeout",
+    );
+    app
+}
+
+#[allow(clippy::too_many_arguments)]
 fn timeout(
     cmdname: &str,
     args: &[String],
@@ -111,55 +133,68 @@ fn timeout(
     kill_after: Duration,
     foreground: bool,
     preserve_status: bool,
+    verbose: bool,
 ) -> i32 {
-    if !foreground {
-        unsafe { libc::setpgid(0, 0) };
-    }
-    let mut process = match Command::new(cmdname)
+    let mut command = Command::new(cmdname);
+    command
         .args(args)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .spawn()
-    {
+        .stderr(Stdio::inherit());
+    if !foreground {
+        unsafe { libc::setpgid(0, 0) };
+        // give COMMAND a process group of its own, so that anything it starts
+        // can be signaled along with it without signaling timeout itself
+        unsafe {
+            command.pre_exec(|| {
+                libc::setpgid(0, 0);
+                Ok(())
+            });
+        }
+    }
+    let mut process = match command.spawn() {
         Ok(p) => p,
         Err(err) => {
-            show_error!("failed to execute process: {}", err);
-            if err.kind() == ErrorKind::NotFound {
-                // XXX: not sure which to use
-                return 127;
+            show_error!("failed to run command '{}': {}", cmdname, err);
+            return if err.kind() == ErrorKind::NotFound {
+                EXIT_ENOENT
             } else {
-                // XXX: this may not be 100% correct...
-                return 126;
-            }
+                EXIT_CANNOT_INVOKE
+            };
         }
     };
     match process.wait_or_timeout(duration) {
-        Ok(Some(status)) => status.code().unwrap_or_else(|| status.signal().unwrap()),
+        Ok(Some(status)) => exit_code(status),
         Ok(None) => {
-            return_if_err!(ERR_EXIT_STATUS, process.send_signal(signal));
+            return_if_err!(
+                ERR_EXIT_STATUS,
+                send_signal(&mut process, signal, cmdname, !foreground, verbose)
+            );
+            // a zero kill_after waits for the command however long it takes
             match process.wait_or_timeout(kill_after) {
                 Ok(Some(status)) => {
-                    if preserve_status {
-                        status.code().unwrap_or_else(|| status.signal().unwrap())
+                    // being killed is reported even without --preserve-status
+                    if preserve_status || status.signal() == Some(libc::SIGKILL) {
+                        exit_code(status)
                     } else {
-                        124
+                        EXIT_TIMED_OUT
                     }
                 }
                 Ok(None) => {
-                    if kill_after == Duration::new(0, 0) {
-                        // XXX: this may not be right
-                        return 124;
-                    }
                     return_if_err!(
                         ERR_EXIT_STATUS,
-                        process
-                            .send_signal(uucore::signals::signal_by_name_or_value("KILL").unwrap())
+                        send_signal(
+                            &mut process,
+                            libc::SIGKILL as usize,
+                            cmdname,
+                            !foreground,
+                            verbose,
+                        )
                     );
                     return_if_err!(ERR_EXIT_STATUS, process.wait());
-                    137
+                    128 + libc::SIGKILL
                 }
-                Err(_) => 124,
+                Err(_) => EXIT_TIMED_OUT,
             }
         }
         Err(_) => {
@@ -168,3 +203,39 @@ fn timeout(
         }
     }
 }
+
+/// Send `signal` to the command, or to its whole process group if `group`,
+/// waking it up so that a stopped command receives it too.
+fn send_signal(
+    process: &mut Child,
+    signal: usize,
+    cmdname: &str,
+    group: bool,
+    verbose: bool,
+) -> std::io::Result<()> {
+    if verbose {
+        let name = uucore::signals::signal_name_by_value(signal)
+            .map(String::from)
+            .unwrap_or_else(|| signal.to_string());
+        show_info!("sending signal {} to command '{}'", name, cmdname);
+    }
+    let mut send = |signal| {
+        if group {
+            process.send_signal_group(signal)
+        } else {
+            process.send_signal(signal)
+        }
+    };
+    send(signal)?;
+    if signal != libc::SIGKILL as usize && signal != libc::SIGCONT as usize {
+        send(libc::SIGCONT as usize)?;
+    }
+    Ok(())
+}
+
+/// The exit status of the command as a shell would report it.
+fn exit_code(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .unwrap_or_else(|| 128 + status.signal().unwrap())
+}
@@ -13,17 +13,18 @@ pub extern crate filetime;
 #[macro_use]
 extern crate uucore;
 
-use clap::{App, Arg, ArgGroup};
+use chrono::{DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, TimeZone};
+use clap::{App, Arg};
 use filetime::*;
 use std::fs::{self, File};
-use std::io::Error;
+use std::io::{self, Error};
 use std::path::Path;
+use std::time::SystemTime;
+use uucore::parse_datetime::parse_datetime_at_date;
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static ABOUT: &str = "Update the access and modification times of each FILE to the current time.";
 pub mod options {
-    // Both SOURCES and sources are needed as we need to be able to refer to the ArgGroup.
-    pub static SOURCES: &str = "sources";
     pub mod sources {
         pub static DATE: &str = "date";
         pub static REFERENCE: &str = "reference";
@@ -38,15 +39,14 @@ pub mod options {
 
 static ARG_FILES: &str = "files";
 
-fn to_local(mut tm: time::Tm) -> time::Tm {
-    tm.tm_utcoff = time::now().tm_utcoff;
-    tm
-}
-
-fn local_tm_to_filetime(tm: time::Tm) -> FileTime {
-    let ts = tm.to_timespec();
-    FileTime::from_unix_time(ts.sec as i64, ts.nsec as u32)
-}
+/// The words accepted by `--time`, and the time each one selects.
+const TIME_WORDS: &[(&str, &str)] = &[
+    ("atime", options::ACCESS),
+    ("access", options::ACCESS),
+    ("use", options::ACCESS),
+    ("mtime", options::MODIFICATION),
+    ("modify", options::MODIFICATION),
+];
 
 fn get_usage() -> String {
     format!("{0} [OPTION]... [USER]", executable!())
@@ -55,10 +55,108 @@ fn get_usage() -> String {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let files: Vec<String> = matches
+        .values_of(ARG_FILES)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    let time = match matches.value_of(options::TIME).map(parse_time_word) {
+        Some(Ok(time)) => Some(time),
+        Some(Err(e)) => crash!(1, "{}", e),
+        None => None,
+    };
+    let access = matches.is_present(options::ACCESS) || time == Some(options::ACCESS);
+    let modification =
+        matches.is_present(options::MODIFICATION) || time == Some(options::MODIFICATION);
+    // -a and -m together are the same as neither
+    let (change_atime, change_mtime) = (access || !modification, modification || !access);
+
+    let follow = !matches.is_present(options::NO_DEREF);
+    // not an ArgGroup, since clap 2 then rejects `-t` on its own for
+    // conflicting with the others
+    let sources = [
+        options::sources::CURRENT,
+        options::sources::DATE,
+        options::sources::REFERENCE,
+    ];
+    let now = !sources.iter().any(|source| matches.is_present(source));
+    let date = matches.value_of(options::sources::DATE);
+    let times = if let Some(reference) = matches.value_of(options::sources::REFERENCE) {
+        let (atime, mtime) = stat(reference, follow);
+        match date {
+            // the date string is relative to each of the reference times
+            Some(date) => {
+                let atime = parse_date(to_local(atime), date);
+                let mtime = parse_date(to_local(mtime), date);
+                atime.and_then(|atime| mtime.map(|mtime| (atime, mtime)))
+            }
+            None => Ok((atime, mtime)),
+        }
+    } else {
+        let timestamp = if let Some(date) = date {
+            parse_date(Local::now(), date)
+        } else if let Some(stamp) = matches.value_of(options::sources::CURRENT) {
+            parse_timestamp(stamp)
+        } else {
+            Ok(FileTime::from_system_time(SystemTime::now()))
+        };
+        timestamp.map(|timestamp| (timestamp, timestamp))
+    };
+    let (atime, mtime) = match times {
+        Ok(times) => times,
+        Err(e) => crash!(1, "{}", e),
+    };
+
+    // the time that is not being changed is left alone
+    let atime = if change_atime { Some(atime) } else { None };
+    let mtime = if change_mtime { Some(mtime) } else { None };
+
+    let mut status = 0;
+    for filename in &files {
+        let path = &filename[..];
+
+        // with -h, a dangling symlink is changed rather than created
+        let exists = if follow {
+            Path::new(path).exists()
+        } else {
+            fs::symlink_metadata(path).is_ok()
+        };
+        if !exists {
+            if matches.is_present(options::NO_CREATE) {
+                continue;
+            }
+            if !follow {
+                show_error!("setting times of '{}': No such file or directory", path);
+                status = 1;
+                continue;
+            }
+
+            if let Err(e) = File::create(path) {
+                show_warning!("cannot touch '{}': {}", path, e);
+                continue;
+            };
+
+            // Minor optimization: if no reference time was specified, we're done.
+            if now {
+                continue;
+            }
+        }
+
+        if let Err(e) = set_times(path, atime, mtime, follow) {
+            show_error!("setting times of '{}': {}", path, e);
+            status = 1;
+        }
+    }
+
+    status
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
         .arg(
             Arg::with_name(options::ACCESS)
                 .short("a")
@@ -69,7 +167,8 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .short("t")
                 .help("use [[CC]YY]MMDDhhmm[.ss] instead of the current time")
                 .value_name("STAMP")
-                .takes_value(true),
+                .takes_value(true)
+                .conflicts_with_all(&[options::sources::DATE, options::sources::REFERENCE]),
         )
         .arg(
             Arg::with_name(options::sources::DATE)
@@ -102,7 +201,10 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             Arg::with_name(options::sources::REFERENCE)
                 .short("r")
                 .long(options::sources::REFERENCE)
-                .help("use this file's times instead of the current time")
+                .help(
+                    "use this file's times instead of the current time; \
+                     with --date, adjust them as it says",
+                )
                 .value_name("FILE"),
         )
         .arg(
@@ -114,7 +216,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                      equivalent to -m",
                 )
                 .value_name("WORD")
-                .possible_values(&["access", "atime", "use"])
                 .takes_value(true),
         )
         .arg(
@@ -123,104 +224,13 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .takes_value(true)
                 .min_values(1),
         )
-        .group(ArgGroup::with_name(options::SOURCES).args(&[
-            options::sources::CURRENT,
-            options::sources::DATE,
-            options::sources::REFERENCE,
-        ]))
-        .get_matches_from(args);
-
-    let files: Vec<String> = matches
-        .values_of(ARG_FILES)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    let (mut atime, mut mtime) = if matches.is_present(options::sources::REFERENCE) {
-        stat(
-            &matches.value_of(options::sources::REFERENCE).unwrap()[..],
-            !matches.is_present(options::NO_DEREF),
-        )
-    } else if matches.is_present(options::sources::DATE)
-        || matches.is_present(options::sources::CURRENT)
-    {
-        let timestamp = if matches.is_present(options::sources::DATE) {
-            parse_date(matches.value_of(options::sources::DATE).unwrap().as_ref())
-        } else {
-            parse_timestamp(
-                matches
-                    .value_of(options::sources::CURRENT)
-                    .unwrap()
-                    .as_ref(),
-            )
-        };
-        (timestamp, timestamp)
-    } else {
-        let now = local_tm_to_filetime(time::now());
-        (now, now)
-    };
-
-    for filename in &files {
-        let path = &filename[..];
-
-        if !Path::new(path).exists() {
-            // no-dereference included here for compatibility
-            if matches.is_present(options::NO_CREATE) || matches.is_present(options::NO_DEREF) {
-                continue;
-            }
-
-            if let Err(e) = File::create(path) {
-                show_warning!("cannot touch '{}': {}", path, e);
-                continue;
-            };
-
-            // Minor optimization: if no reference time was specified, we're done.
-            if !matches.is_present(options::SOURCES) {
-                continue;
-            }
-        }
-
-        // If changing "only" atime or mtime, grab the existing value of the other.
-        // Note that "-a" and "-m" may be passed together; this is not an xor.
-        if matches.is_present(options::ACCESS)
-            || matches.is_present(options::MODIFICATION)
-            || matches.is_present(options::TIME)
-        {
-            let st = stat(path, !matches.is_present(options::NO_DEREF));
-            let time = matches.value_of(options::TIME).unwrap_or("");
-
-            if !(matches.is_present(options::ACCESS)
-                || time.contains(&"access".to_owned())
-                || time.contains(&"atime".to_owned())
-                || time.contains(&"use".to_owned()))
-            {
-                atime = st.0;
-            }
-
-            if !(matches.is_present(options::MODIFICATION)
-                || time.contains(&"modify".to_owned())
-                || time.contains(&"mtime".to_owned()))
-            {
-                mtime = st.1;
-            }
-        }
-
-        if matches.is_present(options::NO_DEREF) {
-            if let Err(e) = set_symlink_file_times(path, atime, mtime) {
-                show_warning!("cannot touch '{}': {}", path, e);
-            }
-        } else if let Err(e) = filetime::set_file_times(path, atime, mtime) {
-            show_warning!("cannot touch '{}': {}", path, e);
-        }
-    }
-
-    0
 }
 
 fn stat(path: &str, follow: bool) -> (FileTime, FileTime) {
     let metadata = if follow {
-        fs::symlink_metadata(path)
-    } else {
         fs::metadata(path)
+    } else {
+        fs::symlink_metadata(path)
     };
 
     match metadata {
@@ -237,31 +247,171 @@ fn stat(path: &str, follow: bool) -> (FileTime, FileTime) {
     }
 }
 
-fn parse_date(str: &str) -> FileTime {
-    // This isn't actually compatible with GNU touch, but there doesn't seem to
-    // be any simple specification for what format this parameter allows and I'm
-    // not about to implement GNU parse_datetime.
-    // http://git.savannah.gnu.org/gitweb/?p=gnulib.git;a=blob_plain;f=lib/parse-datetime.y
-    match time::strptime(str, "%c") {
-        Ok(tm) => local_tm_to_filetime(to_local(tm)),
-        Err(e) => panic!("Unable to parse date\n{}", e),
+/// Find the time named by a `--time` argument, which may be abbreviated to
+/// any unambiguous prefix.
+fn parse_time_word(word: &str) -> Result<&'static str, String> {
+    let mut matching = TIME_WORDS
+        .iter()
+        .filter(|&&(name, _)| name.starts_with(word))
+        .map(|&(_, time)| time);
+    let problem = match matching.next() {
+        None => "invalid",
+        Some(time) if matching.all(|other| other == time) => return Ok(time),
+        Some(_) => "ambiguous",
+    };
+    Err(format!(
+        "{} argument '{}' for '--{}'\n\
+         Valid arguments are:\n  \
+         - 'atime', 'access', 'use'\n  \
+         - 'mtime', 'modify'\n\
+         Try '{} --help' for more information.",
+        problem,
+        word,
+        options::TIME,
+        executable!()
+    ))
+}
+
+/// Set the times of `path`, or of the symbolic link itself unless `follow`,
+/// to the nanosecond. A time that is `None` is left unchanged.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_times(
+    path: &str,
+    atime: Option<FileTime>,
+    mtime: Option<FileTime>,
+    follow: bool,
+) -> io::Result<()> {
+    use std::ffi::CString;
+    use uucore::libc;
+
+    let path = CString::new(path).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let to_timespec = |time: Option<FileTime>| match time {
+        Some(time) => libc::timespec {
+            tv_sec: time.unix_seconds() as libc::time_t,
+            tv_nsec: time.nanoseconds() as libc::c_long,
+        },
+        None => libc::timespec {
+            tv_sec: 0,
+            tv_nsec: libc::UTIME_OMIT,
+        },
+    };
+    let times = [to_timespec(atime), to_timespec(mtime)];
+    let flags = if follow { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+    if unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), flags) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
     }
 }
 
-fn parse_timestamp(s: &str) -> FileTime {
-    let now = time::now();
-    let (format, ts) = match s.chars().count() {
-        15 => ("%Y%m%d%H%M.%S", s.to_owned()),
-        12 => ("%Y%m%d%H%M", s.to_owned()),
-        13 => ("%y%m%d%H%M.%S", s.to_owned()),
-        10 => ("%y%m%d%H%M", s.to_owned()),
-        11 => ("%Y%m%d%H%M.%S", format!("{}{}", now.tm_year + 1900, s)),
-        8 => ("%Y%m%d%H%M", format!("{}{}", now.tm_year + 1900, s)),
-        _ => panic!("Unknown timestamp format"),
+/// Set the times of `path`, or of the symbolic link itself unless `follow`.
+/// A time that is `None` is left unchanged. Only the right to write the
+/// attributes is asked for, which a read-only file still grants.
+#[cfg(windows)]
+fn set_times(
+    path: &str,
+    atime: Option<FileTime>,
+    mtime: Option<FileTime>,
+    follow: bool,
+) -> io::Result<()> {
+    use std::fs::OpenOptions;
+    use std::os::windows::fs::OpenOptionsExt;
+    use winapi::um::winbase::{FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT};
+    use winapi::um::winnt::FILE_WRITE_ATTRIBUTES;
+
+    // directories can only be opened with backup semantics
+    let mut flags = FILE_FLAG_BACKUP_SEMANTICS;
+    if !follow {
+        flags |= FILE_FLAG_OPEN_REPARSE_POINT;
+    }
+    let file = OpenOptions::new()
+        .access_mode(FILE_WRITE_ATTRIBUTES)
+        .custom_flags(flags)
+        .open(path)?;
+    filetime::set_file_handle_times(&file, atime, mtime)
+}
+
+/// Set the times of `path`, or of the symbolic link itself unless `follow`.
+/// A time that is `None` is left unchanged.
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+fn set_times(
+    path: &str,
+    atime: Option<FileTime>,
+    mtime: Option<FileTime>,
+    follow: bool,
+) -> io::Result<()> {
+    let metadata = if follow {
+        fs::metadata(path)?
+    } else {
+        fs::symlink_metadata(path)?
     };
+    let atime = atime.unwrap_or_else(|| FileTime::from_last_access_time(&metadata));
+    let mtime = mtime.unwrap_or_else(|| FileTime::from_last_modification_time(&metadata));
+    if follow {
+        filetime::set_file_times(path, atime, mtime)
+    } else {
+        set_symlink_file_times(path, atime, mtime)
+    }
+}
+
+/// Parse a date string as `date -d` does, relative to `now`.
+fn parse_date<Tz: TimeZone>(now: DateTime<Tz>, s: &str) -> Result<FileTime, String> {
+    match parse_datetime_at_date(now, s) {
+        Ok(date) => Ok(to_filetime(&date)),
+        Err(_) => Err(format!("invalid date format '{}'", s)),
+    }
+}
 
-    match time::strptime(&ts, format) {
-        Ok(tm) => local_tm_to_filetime(to_local(tm)),
-        Err(e) => panic!("Unable to parse timestamp\n{}", e),
+/// Parse a POSIX timestamp, `[[CC]YY]MMDDhhmm[.ss]`, in the local time zone.
+fn parse_timestamp(s: &str) -> Result<FileTime, String> {
+    let invalid = || format!("invalid date format '{}'", s);
+    let (digits, seconds) = match s.find('.') {
+        Some(dot) => (&s[..dot], &s[dot + 1..]),
+        None => (s, "00"),
+    };
+    let all_digits = digits
+        .bytes()
+        .chain(seconds.bytes())
+        .all(|b| b.is_ascii_digit());
+    if seconds.len() != 2 || !all_digits {
+        return Err(invalid());
     }
+    let (year, digits) = match digits.len() {
+        8 => (Local::now().year(), digits),
+        10 => {
+            // two-digit years from 69 are in the 20th century
+            let year: i32 = digits[..2].parse().unwrap();
+            (
+                if year < 69 { 2000 + year } else { 1900 + year },
+                &digits[2..],
+            )
+        }
+        12 => (digits[..4].parse().unwrap(), &digits[4..]),
+        _ => return Err(invalid()),
+    };
+    let field = |i: usize| digits[i..i + 2].parse::<u32>().unwrap();
+    // a leap second is taken as the first second of the next minute
+    let (seconds, leap) = match seconds.parse().unwrap() {
+        60 => (59, 1),
+        seconds => (seconds, 0),
+    };
+    let local = NaiveDate::from_ymd_opt(year, field(0), field(2))
+        .and_then(|date| date.and_hms_opt(field(4), field(6), seconds))
+        .ok_or_else(invalid)?;
+    let date = match Local.from_local_datetime(&local) {
+        // skipped by a clock change; some platforms quietly normalize these
+        LocalResult::Single(date) if date.naive_local() != local => return Err(invalid()),
+        LocalResult::Single(date) => date,
+        LocalResult::Ambiguous(earliest, _) => earliest,
+        LocalResult::None => return Err(invalid()),
+    };
+    Ok(to_filetime(&(date + Duration::seconds(leap))))
+}
+
+fn to_local(time: FileTime) -> DateTime<Local> {
+    Local.timestamp(time.unix_seconds(), time.nanoseconds())
+}
+
+fn to_filetime<Tz: TimeZone>(date: &DateTime<Tz>) -> FileTime {
+    FileTime::from_unix_time(date.timestamp(), date.timestamp_subsec_nanos())
 }
@@ -7,19 +7,17 @@
 //  * For the full copyright and license information, please view the LICENSE
 //  * file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) allocs slen unesc
+// spell-checker:ignore (ToDO) allocs alnum cntrl xdigit
 
 use std::char::from_u32;
-use std::cmp::min;
-use std::iter::Peekable;
-use std::ops::RangeInclusive;
 
 /// Parse a backslash escape sequence to the corresponding character. Assumes
 /// the string starts from the character _after_ the `\` and is not empty.
 ///
 /// Returns a tuple containing the character and the number of characters
 /// consumed from the input. The alphabetic escape sequences consume 1
-/// character; octal escape sequences consume 1 to 3 octal digits.
+/// character; octal escape sequences consume 1 to 3 octal digits, as long as
+/// their value fits in a byte.
 #[inline]
 fn parse_sequence(s: &str) -> (char, usize) {
     let c = s.chars().next().expect("invalid escape: empty string");
@@ -31,8 +29,17 @@ fn parse_sequence(s: &str) -> (char, usize) {
 
         for c in s.chars().skip(1).take(2) {
             match c.to_digit(8) {
-                Some(c) => {
-                    v = (v << bits_per_digit) | c;
+                Some(d) if (v << bits_per_digit) | d > 0o377 => {
+                    show_warning!(
+                        "the ambiguous octal escape \\{} is being interpreted as the 2-byte sequence \\0{}, {}",
+                        &s[..3],
+                        &s[..2],
+                        c
+                    );
+                    break;
+                }
+                Some(d) => {
+                    v = (v << bits_per_digit) | d;
                     consumed += 1;
                 }
                 None => break,
@@ -57,89 +64,267 @@ fn parse_sequence(s: &str) -> (char, usize) {
     }
 }
 
-struct Unescape<'a> {
-    string: &'a str,
+/// Parse the character at the start of `s`, which may be a backslash escape.
+///
+/// Returns the character and the number of bytes consumed from `s`. A
+/// backslash at the very end of the string stands for itself.
+#[inline]
+fn parse_char(s: &str) -> (char, usize) {
+    let c = s.chars().next().expect("invalid character: empty string");
+    if c == '\\' && s.len() > 1 {
+        // we know that \ is 1 byte long so we can index into the string safely
+        let (c, consumed) = parse_sequence(&s[1..]);
+        (c, 1 + consumed)
+    } else {
+        (c, c.len_utf8())
+    }
 }
 
-impl<'a> Iterator for Unescape<'a> {
-    type Item = char;
+/// The character classes that may appear in a set as `[:name:]`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Class {
+    Alnum,
+    Alpha,
+    Blank,
+    Cntrl,
+    Digit,
+    Graph,
+    Lower,
+    Print,
+    Punct,
+    Space,
+    Upper,
+    Xdigit,
+}
 
-    #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let slen = self.string.len();
-        (min(slen, 1), None)
+impl Class {
+    fn from_name(name: &str) -> Option<Class> {
+        Some(match name {
+            "alnum" => Class::Alnum,
+            "alpha" => Class::Alpha,
+            "blank" => Class::Blank,
+            "cntrl" => Class::Cntrl,
+            "digit" => Class::Digit,
+            "graph" => Class::Graph,
+            "lower" => Class::Lower,
+            "print" => Class::Print,
+            "punct" => Class::Punct,
+            "space" => Class::Space,
+            "upper" => Class::Upper,
+            "xdigit" => Class::Xdigit,
+            _ => return None,
+        })
     }
 
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.string.is_empty() {
-            return None;
+    fn contains(self, c: char) -> bool {
+        match self {
+            Class::Alnum => c.is_ascii_alphanumeric(),
+            Class::Alpha => c.is_ascii_alphabetic(),
+            Class::Blank => c == ' ' || c == '\t',
+            Class::Cntrl => c.is_ascii_control(),
+            Class::Digit => c.is_ascii_digit(),
+            Class::Graph => c.is_ascii_graphic(),
+            Class::Lower => c.is_ascii_lowercase(),
+            Class::Print => c.is_ascii_graphic() || c == ' ',
+            Class::Punct => c.is_ascii_punctuation(),
+            Class::Space => c.is_ascii_whitespace() || c == '\x0b',
+            Class::Upper => c.is_ascii_uppercase(),
+            Class::Xdigit => c.is_ascii_hexdigit(),
         }
+    }
 
-        // is the next character an escape?
-        let (ret, idx) = match self.string.chars().next().unwrap() {
-            '\\' if self.string.len() > 1 => {
-                // yes---it's \ and it's not the last char in a string
-                // we know that \ is 1 byte long so we can index into the string safely
-                let (c, consumed) = parse_sequence(&self.string[1..]);
-
-                (Some(c), 1 + consumed)
-            }
-            c => (Some(c), c.len_utf8()), // not an escape char
-        };
+    /// The members of the class, in ascending order, so that `[:lower:]` and
+    /// `[:upper:]` line up with each other.
+    fn chars(self) -> impl Iterator<Item = char> {
+        (0u8..=127).map(char::from).filter(move |&c| self.contains(c))
+    }
 
-        self.string = &self.string[idx..]; // advance the pointer to the next char
-        ret
+    fn is_case(self) -> bool {
+        self == Class::Lower || self == Class::Upper
     }
 }
 
-pub struct ExpandSet<'a> {
-    range: RangeInclusive<u32>,
-    unesc: Peekable<Unescape<'a>>,
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Element {
+    Char(char),
+    Range(char, char),
+    Class(Class),
+    Equiv(char),
+    /// `[c*n]`; a count of 0 stands for `[c*]`, which fills SET2 up to the
+    /// length of SET1
+    Repeat(char, usize),
 }
 
-impl<'a> Iterator for ExpandSet<'a> {
-    type Item = char;
+/// A SET operand of tr, as a sequence of characters, ranges, `[:class:]`,
+/// `[=c=]` and `[c*n]` constructs.
+pub struct Set {
+    elements: Vec<Element>,
+}
 
-    #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        self.unesc.size_hint()
-    }
+impl Set {
+    pub fn parse(s: &str) -> Result<Set, String> {
+        let mut elements = Vec::new();
+        let mut rest = s;
+        while !rest.is_empty() {
+            if let Some((element, consumed)) = parse_bracket(rest)? {
+                elements.push(element);
+                rest = &rest[consumed..];
+                continue;
+            }
 
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        // while the Range has elements, try to return chars from it
-        // but make sure that they actually turn out to be Chars!
-        while let Some(n) = self.range.next() {
-            if let Some(c) = from_u32(n) {
-                return Some(c);
+            let (first, consumed) = parse_char(rest);
+            rest = &rest[consumed..];
+            if rest.len() > 1 && rest.starts_with('-') {
+                let (last, consumed) = parse_char(&rest[1..]);
+                if last < first {
+                    return Err(format!(
+                        "range-endpoints of '{}-{}' are in reverse collating sequence order",
+                        first, last
+                    ));
+                }
+                elements.push(Element::Range(first, last));
+                rest = &rest[1 + consumed..];
+            } else {
+                elements.push(Element::Char(first));
             }
         }
+        Ok(Set { elements })
+    }
 
-        if let Some(first) = self.unesc.next() {
-            // peek ahead
-            if self.unesc.peek() == Some(&'-') && self.unesc.size_hint().0 > 1 {
-                self.unesc.next(); // this is the '-'
-                let last = self.unesc.next().unwrap(); // this is the end of the range
+    /// Expand SET1, or a SET2 that is only used for squeezing.
+    pub fn expand(&self, is_set1: bool) -> Result<Vec<char>, String> {
+        let mut chars = Vec::new();
+        for &element in &self.elements {
+            match element {
+                Element::Repeat(..) if is_set1 => {
+                    return Err("the [c*] repeat construct may not appear in string1".to_owned())
+                }
+                Element::Repeat(c, n) => chars.extend((0..n.max(1)).map(|_| c)),
+                element => push_element(&mut chars, element),
+            }
+        }
+        Ok(chars)
+    }
 
-                {
-                    self.range = first as u32 + 1..=last as u32;
+    /// Expand SET2 for translating the characters of `set1`, which expands to
+    /// `set1_len` characters. `[c*]` fills SET2 up to that length, and the
+    /// `[:lower:]` and `[:upper:]` classes must line up with one of those
+    /// classes in SET1.
+    pub fn expand_translation(&self, set1: &Set, set1_len: usize) -> Result<Vec<char>, String> {
+        let mut case_classes = Vec::new();
+        let mut offset = 0;
+        for &element in &set1.elements {
+            if let Element::Class(class) = element {
+                if class.is_case() {
+                    case_classes.push(offset);
                 }
             }
+            offset += element_len(element);
+        }
 
-            return Some(first); // in any case, return the next char
+        let fills = self
+            .elements
+            .iter()
+            .filter(|&&element| match element {
+                Element::Repeat(_, n) => n == 0,
+                _ => false,
+            })
+            .count();
+        if fills > 1 {
+            return Err("only one [c*] repeat construct may appear in string2".to_owned());
         }
+        let fixed_len: usize = self.elements.iter().map(|&element| element_len(element)).sum();
+        let fill_len = set1_len.saturating_sub(fixed_len);
 
-        None
+        let mut chars = Vec::new();
+        for &element in &self.elements {
+            match element {
+                Element::Class(class) if !class.is_case() => {
+                    return Err("when translating, the only character classes that may appear in string2 are 'upper' and 'lower'".to_owned());
+                }
+                Element::Class(_) if !case_classes.contains(&chars.len()) => {
+                    return Err("misaligned [:upper:] and/or [:lower:] construct".to_owned());
+                }
+                Element::Equiv(_) => {
+                    return Err(
+                        "[=c=] expressions may not appear in string2 when translating".to_owned(),
+                    );
+                }
+                Element::Repeat(c, 0) => chars.extend((0..fill_len).map(|_| c)),
+                element => push_element(&mut chars, element),
+            }
+        }
+        Ok(chars)
     }
 }
 
-impl<'a> ExpandSet<'a> {
-    #[inline]
-    pub fn new(s: &'a str) -> ExpandSet<'a> {
-        ExpandSet {
-            range: 0..=0,
-            unesc: Unescape { string: s }.peekable(),
+/// Try to parse a `[:class:]`, `[=c=]` or `[c*n]` construct at the start of
+/// `s`. A `[` that does not start a complete construct stands for itself.
+fn parse_bracket(s: &str) -> Result<Option<(Element, usize)>, String> {
+    if !s.starts_with('[') || s.len() < 3 {
+        return Ok(None);
+    }
+
+    if s.starts_with("[:") {
+        if let Some(end) = s[2..].find(":]") {
+            let name = &s[2..2 + end];
+            return match Class::from_name(name) {
+                Some(class) => Ok(Some((Element::Class(class), end + 4))),
+                None => Err(format!("invalid character class '{}'", name)),
+            };
         }
     }
+
+    if s.starts_with("[=") {
+        let (c, consumed) = parse_char(&s[2..]);
+        if s[2 + consumed..].starts_with("=]") {
+            return Ok(Some((Element::Equiv(c), consumed + 4)));
+        }
+    }
+
+    let (c, consumed) = parse_char(&s[1..]);
+    let rest = &s[1 + consumed..];
+    if rest.starts_with('*') {
+        if let Some(end) = rest.find(']') {
+            let count = &rest[1..end];
+            let n = if count.is_empty() {
+                Ok(0)
+            } else if count.starts_with('0') {
+                usize::from_str_radix(count, 8)
+            } else {
+                count.parse()
+            };
+            return match n {
+                Ok(n) => Ok(Some((Element::Repeat(c, n), 1 + consumed + end + 1))),
+                Err(_) => Err(format!(
+                    "invalid repeat count '{}' in [c*n] construct",
+                    count
+                )),
+            };
+        }
+    }
+
+    Ok(None)
+}
+
+fn element_len(element: Element) -> usize {
+    match element {
+        Element::Char(_) | Element::Equiv(_) => 1,
+        Element::Range(first, last) => char_range(first, last).count(),
+        Element::Class(class) => class.chars().count(),
+        Element::Repeat(_, n) => n,
+    }
+}
+
+fn push_element(chars: &mut Vec<char>, element: Element) {
+    match element {
+        Element::Char(c) | Element::Equiv(c) => chars.push(c),
+        Element::Range(first, last) => chars.extend(char_range(first, last)),
+        Element::Class(class) => chars.extend(class.chars()),
+        Element::Repeat(c, n) => chars.extend((0..n).map(|_| c)),
+    }
+}
+
+fn char_range(first: char, last: char) -> impl Iterator<Item = char> {
+    (first as u32..=last as u32).filter_map(from_u32)
 }
@@ -18,19 +18,25 @@ mod expand;
 use bit_set::BitSet;
 use clap::{App, Arg};
 use fnv::FnvHashMap;
+use std::env;
 use std::io::{stdin, stdout, BufRead, BufWriter, Write};
+use std::str;
 
-use crate::expand::ExpandSet;
+use crate::expand::Set;
 
 static NAME: &str = "tr";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static ABOUT: &str = "translate or delete characters";
 static LONG_HELP: &str = "Translate,  squeeze, and/or delete characters from standard input,
-writing to standard output.";
+writing to standard output.
+
+When the locale (LC_ALL, LC_CTYPE or LANG) uses the UTF-8 encoding, SETs and
+input are made of characters; otherwise, each byte is a character of its own.";
 const BUFFER_LEN: usize = 1024;
 
 mod options {
     pub const COMPLEMENT: &str = "complement";
+    pub const COMPLEMENT_CHARS: &str = "complement-chars";
     pub const DELETE: &str = "delete";
     pub const SQUEEZE: &str = "squeeze-repeats";
     pub const TRUNCATE: &str = "truncate";
@@ -47,9 +53,9 @@ struct DeleteOperation {
 }
 
 impl DeleteOperation {
-    fn new(set: ExpandSet, complement: bool) -> DeleteOperation {
+    fn new(set: &[char], complement: bool) -> DeleteOperation {
         DeleteOperation {
-            bset: set.map(|c| c as usize).collect(),
+            bset: set.iter().map(|&c| c as usize).collect(),
             complement,
         }
     }
@@ -72,9 +78,9 @@ struct SqueezeOperation {
 }
 
 impl SqueezeOperation {
-    fn new(squeeze_set: ExpandSet, complement: bool) -> SqueezeOperation {
+    fn new(squeeze_set: &[char], complement: bool) -> SqueezeOperation {
         SqueezeOperation {
-            squeeze_set: squeeze_set.map(|c| c as usize).collect(),
+            squeeze_set: squeeze_set.iter().map(|&c| c as usize).collect(),
             complement,
         }
     }
@@ -97,14 +103,10 @@ struct DeleteAndSqueezeOperation {
 }
 
 impl DeleteAndSqueezeOperation {
-    fn new(
-        delete_set: ExpandSet,
-        squeeze_set: ExpandSet,
-        complement: bool,
-    ) -> DeleteAndSqueezeOperation {
+    fn new(delete_set: &[char], squeeze_set: &[char], complement: bool) -> DeleteAndSqueezeOperation {
         DeleteAndSqueezeOperation {
-            delete_set: delete_set.map(|c| c as usize).collect(),
-            squeeze_set: squeeze_set.map(|c| c as usize).collect(),
+            delete_set: delete_set.iter().map(|&c| c as usize).collect(),
+            squeeze_set: squeeze_set.iter().map(|&c| c as usize).collect(),
             complement,
         }
     }
@@ -127,17 +129,17 @@ struct TranslateOperation {
 }
 
 impl TranslateOperation {
-    fn new(set1: ExpandSet, set2: &mut ExpandSet, truncate: bool) -> TranslateOperation {
+    fn new(set1: &[char], set2: &[char], truncate: bool) -> TranslateOperation {
         let mut map = FnvHashMap::default();
         let mut s2_prev = '_';
-        for i in set1 {
-            let s2_next = set2.next();
+        for (i, &c) in set1.iter().enumerate() {
+            let s2_next = set2.get(i);
 
             if s2_next.is_none() && truncate {
-                map.insert(i as usize, i);
+                map.insert(c as usize, c);
             } else {
-                s2_prev = s2_next.unwrap_or(s2_prev);
-                map.insert(i as usize, s2_prev);
+                s2_prev = s2_next.cloned().unwrap_or(s2_prev);
+                map.insert(c as usize, s2_prev);
             }
         }
         TranslateOperation { translate_map: map }
@@ -150,37 +152,143 @@ impl SymbolTranslator for TranslateOperation {
     }
 }
 
+/// Translate the complement of SET1: the characters that are not in SET1 are
+/// mapped, in ascending order, to the characters of SET2, whose last character
+/// is repeated as often as necessary.
+struct TranslateComplementOperation {
+    set1: Vec<usize>,
+    set2: Vec<char>,
+    truncate: bool,
+}
+
+impl TranslateComplementOperation {
+    fn new(set1: &[char], set2: &[char], truncate: bool) -> TranslateComplementOperation {
+        let mut set1: Vec<usize> = set1.iter().map(|&c| c as usize).collect();
+        set1.sort();
+        set1.dedup();
+        TranslateComplementOperation {
+            set1,
+            set2: set2.to_vec(),
+            truncate,
+        }
+    }
+}
+
+impl SymbolTranslator for TranslateComplementOperation {
+    fn translate(&self, c: char, _prev_c: char) -> Option<char> {
+        let uc = c as usize;
+        match self.set1.binary_search(&uc) {
+            Ok(_) => Some(c),
+            Err(rank) => match self.set2.get(uc - rank) {
+                Some(&t) => Some(t),
+                None if self.truncate => Some(c),
+                None => Some(*self.set2.last().unwrap_or(&c)),
+            },
+        }
+    }
+}
+
+/// Squeeze repeated characters of the translated output that are in SET2.
+struct TranslateAndSqueezeOperation<T: SymbolTranslator> {
+    translator: T,
+    squeeze_set: BitSet,
+}
+
+impl<T: SymbolTranslator> TranslateAndSqueezeOperation<T> {
+    fn new(translator: T, squeeze_set: &[char]) -> TranslateAndSqueezeOperation<T> {
+        TranslateAndSqueezeOperation {
+            translator,
+            squeeze_set: squeeze_set.iter().map(|&c| c as usize).collect(),
+        }
+    }
+}
+
+impl<T: SymbolTranslator> SymbolTranslator for TranslateAndSqueezeOperation<T> {
+    fn translate(&self, c: char, prev_c: char) -> Option<char> {
+        let t = self.translator.translate(c, prev_c)?;
+        if prev_c == t && self.squeeze_set.contains(t as usize) {
+            None
+        } else {
+            Some(t)
+        }
+    }
+}
+
 fn translate_input<T: SymbolTranslator>(
     input: &mut dyn BufRead,
     output: &mut dyn Write,
     translator: T,
+    utf8: bool,
 ) {
-    let mut buf = String::with_capacity(BUFFER_LEN + 4);
-    let mut output_buf = String::with_capacity(BUFFER_LEN + 4);
+    let mut buf = Vec::with_capacity(BUFFER_LEN + 4);
+    let mut output_buf = Vec::with_capacity(BUFFER_LEN + 4);
+    // the previous character that was written, for squeezing
+    let mut prev_c = 0 as char;
 
-    while let Ok(length) = input.read_line(&mut buf) {
-        let mut prev_c = 0 as char;
+    while let Ok(length) = input.read_until(b'\n', &mut buf) {
         if length == 0 {
             break;
         }
-        {
-            // isolation to make borrow checker happy
-            let filtered = buf.chars().filter_map(|c| {
-                let res = translator.translate(c, prev_c);
-                if res.is_some() {
-                    prev_c = c;
+        if utf8 {
+            let mut rest = &buf[..];
+            while !rest.is_empty() {
+                // bytes that are not valid UTF-8 are passed through untouched
+                let (valid, invalid_len) = match str::from_utf8(rest) {
+                    Ok(valid) => (valid, 0),
+                    Err(e) => (
+                        str::from_utf8(&rest[..e.valid_up_to()]).unwrap(),
+                        e.error_len().unwrap_or(rest.len() - e.valid_up_to()),
+                    ),
+                };
+                for c in valid.chars() {
+                    if let Some(t) = translator.translate(c, prev_c) {
+                        prev_c = t;
+                        let mut encoded = [0; 4];
+                        output_buf.extend_from_slice(t.encode_utf8(&mut encoded).as_bytes());
+                    }
                 }
-                res
-            });
-
-            output_buf.extend(filtered);
-            output.write_all(output_buf.as_bytes()).unwrap();
+                let valid_len = valid.len();
+                output_buf.extend_from_slice(&rest[valid_len..valid_len + invalid_len]);
+                rest = &rest[valid_len + invalid_len..];
+            }
+        } else {
+            for &b in &buf {
+                if let Some(t) = translator.translate(char::from(b), prev_c) {
+                    prev_c = t;
+                    // the sets were made of bytes, and escapes of byte values
+                    output_buf.push(t as u32 as u8);
+                }
+            }
         }
+        output.write_all(&output_buf).unwrap();
         buf.clear();
         output_buf.clear();
     }
 }
 
+/// Whether the locale's character encoding is UTF-8, in which case tr works on
+/// characters rather than bytes.
+fn locale_is_utf8() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+        .to_lowercase();
+    locale.ends_with(".utf-8") || locale.ends_with(".utf8")
+}
+
+/// The number of characters in the complement of `set`, among the values that
+/// fit in a byte.
+fn complement_len(set: &[char]) -> usize {
+    let bset: BitSet = set
+        .iter()
+        .map(|&c| c as usize)
+        .filter(|&uc| uc < 256)
+        .collect();
+    256 - bset.len()
+}
+
 fn get_usage() -> String {
     format!("{} [OPTION]... SET1 [SET2]", executable!())
 }
@@ -188,45 +296,14 @@ fn get_usage() -> String {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .after_help(LONG_HELP)
-        .arg(
-            Arg::with_name(options::COMPLEMENT)
-                .short("C")
-                .short("c")
-                .long(options::COMPLEMENT)
-                .help("use the complement of SET1"),
-        )
-        .arg(
-            Arg::with_name(options::DELETE)
-                .short("d")
-                .long(options::DELETE)
-                .help("delete characters in SET1, do not translate"),
-        )
-        .arg(
-            Arg::with_name(options::SQUEEZE)
-                .long(options::SQUEEZE)
-                .short("s")
-                .help(
-                    "replace each sequence  of  a  repeated  character  that  is
-            listed  in the last specified SET, with a single occurrence
-            of that character",
-                ),
-        )
-        .arg(
-            Arg::with_name(options::TRUNCATE)
-                .long(options::TRUNCATE)
-                .short("t")
-                .help("first truncate SET1 to length of SET2"),
-        )
-        .arg(Arg::with_name(options::SETS).multiple(true))
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
 
     let delete_flag = matches.is_present(options::DELETE);
-    let complement_flag = matches.is_present(options::COMPLEMENT);
+    let complement_flag =
+        matches.is_present(options::COMPLEMENT) || matches.is_present(options::COMPLEMENT_CHARS);
+    // in a UTF-8 locale tr works on characters, except that -c complements the
+    // set of byte values as in the C locale
+    let utf8 = locale_is_utf8() && !matches.is_present(options::COMPLEMENT);
     let squeeze_flag = matches.is_present(options::SQUEEZE);
     let truncate_flag = matches.is_present(options::TRUNCATE);
 
@@ -252,8 +329,62 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         return 1;
     }
 
-    if complement_flag && !delete_flag && !squeeze_flag {
-        show_error!("-c is only supported with -d or -s");
+    if delete_flag && !squeeze_flag && sets.len() > 1 {
+        show_error!(
+            "extra operand ‘{}’\nOnly one string may be given when deleting without squeezing repeats.\nTry `{} --help` for more information.",
+            sets[1],
+            NAME
+        );
+        return 1;
+    }
+
+    let parsed: Result<Vec<Set>, String> = sets
+        .iter()
+        .map(|set| {
+            if utf8 {
+                Set::parse(set)
+            } else {
+                // every byte of the operand is a character of its own
+                Set::parse(&set.bytes().map(char::from).collect::<String>())
+            }
+        })
+        .collect();
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            show_error!("{}", e);
+            return 1;
+        }
+    };
+    let set1 = match parsed[0].expand(true) {
+        Ok(set1) => set1,
+        Err(e) => {
+            show_error!("{}", e);
+            return 1;
+        }
+    };
+    let translating = !delete_flag && parsed.len() > 1;
+    let set2 = match parsed.get(1) {
+        Some(set2) if translating => {
+            let set1_len = if complement_flag {
+                complement_len(&set1)
+            } else {
+                set1.len()
+            };
+            set2.expand_translation(&parsed[0], set1_len)
+        }
+        Some(set2) => set2.expand(false),
+        None => Ok(Vec::new()),
+    };
+    let set2 = match set2 {
+        Ok(set2) => set2,
+        Err(e) => {
+            show_error!("{}", e);
+            return 1;
+        }
+    };
+    if translating && !truncate_flag && set2.is_empty() && !set1.is_empty() {
+        show_error!("when not truncating set1, string2 must be non-empty");
         return 1;
     }
 
@@ -263,24 +394,75 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     let locked_stdout = stdout.lock();
     let mut buffered_stdout = BufWriter::new(locked_stdout);
 
-    let set1 = ExpandSet::new(sets[0].as_ref());
     if delete_flag {
         if squeeze_flag {
-            let set2 = ExpandSet::new(sets[1].as_ref());
-            let op = DeleteAndSqueezeOperation::new(set1, set2, complement_flag);
-            translate_input(&mut locked_stdin, &mut buffered_stdout, op);
+            let op = DeleteAndSqueezeOperation::new(&set1, &set2, complement_flag);
+            translate_input(&mut locked_stdin, &mut buffered_stdout, op, utf8);
+        } else {
+            let op = DeleteOperation::new(&set1, complement_flag);
+            translate_input(&mut locked_stdin, &mut buffered_stdout, op, utf8);
+        }
+    } else if !translating {
+        let op = SqueezeOperation::new(&set1, complement_flag);
+        translate_input(&mut locked_stdin, &mut buffered_stdout, op, utf8);
+    } else if complement_flag {
+        let op = TranslateComplementOperation::new(&set1, &set2, truncate_flag);
+        if squeeze_flag {
+            let op = TranslateAndSqueezeOperation::new(op, &set2);
+            translate_input(&mut locked_stdin, &mut buffered_stdout, op, utf8);
         } else {
-            let op = DeleteOperation::new(set1, complement_flag);
-            translate_input(&mut locked_stdin, &mut buffered_stdout, op);
+            translate_input(&mut locked_stdin, &mut buffered_stdout, op, utf8);
         }
-    } else if squeeze_flag {
-        let op = SqueezeOperation::new(set1, complement_flag);
-        translate_input(&mut locked_stdin, &mut buffered_stdout, op);
     } else {
-        let mut set2 = ExpandSet::new(sets[1].as_ref());
-        let op = TranslateOperation::new(set1, &mut set2, truncate_flag);
-        translate_input(&mut locked_stdin, &mut buffered_stdout, op)
+        let op = TranslateOperation::new(&set1, &set2, truncate_flag);
+        if squeeze_flag {
+            let op = TranslateAndSqueezeOperation::new(op, &set2);
+            translate_input(&mut locked_stdin, &mut buffered_stdout, op, utf8);
+        } else {
+            translate_input(&mut locked_stdin, &mut buffered_stdout, op, utf8);
+        }
     }
 
     0
 }
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .after_help(LONG_HELP)
+        .arg(
+            Arg::with_name(options::COMPLEMENT)
+                .short("c")
+                .long(options::COMPLEMENT)
+                .help("use the complement of the byte values in SET1"),
+        )
+        .arg(
+            Arg::with_name(options::COMPLEMENT_CHARS)
+                .short("C")
+                .help("use the complement of the characters in SET1"),
+        )
+        .arg(
+            Arg::with_name(options::DELETE)
+                .short("d")
+                .long(options::DELETE)
+                .help("delete characters in SET1, do not translate"),
+        )
+        .arg(
+            Arg::with_name(options::SQUEEZE)
+                .long(options::SQUEEZE)
+                .short("s")
+                .help(
+                    "replace each sequence  of  a  repeated  character  that  is
+            listed  in the last specified SET, with a single occurrence
+            of that character",
+                ),
+        )
+        .arg(
+            Arg::with_name(options::TRUNCATE)
+                .long(options::TRUNCATE)
+                .short("t")
+                .help("first truncate SET1 to length of SET2"),
+        )
+        .arg(Arg::with_name(options::SETS).multiple(true))
+}
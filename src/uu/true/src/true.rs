@@ -5,6 +5,31 @@
 //  * For the full copyright and license information, please view the LICENSE
 //  * file that was distributed with this source code.
 
-pub fn uumain(_: impl uucore::Args) -> i32 {
+#[macro_use]
+extern crate uucore;
+
+use uucore::coreopts::CoreOptions;
+use uucore::help::Help;
+
+static SYNTAX: &str = "[ignored command line arguments]\n  or:  true OPTION";
+static SUMMARY: &str = "Exit with a status code indicating success.";
+
+pub fn uumain(args: impl uucore::Args) -> i32 {
+    let args = args.collect_str();
+
+    // --help and --version only count on their own, as any other arguments
+    // are ignored
+    if args.len() == 2 {
+        let help = Help::from(&uu_app());
+        if args[1] == "--help" {
+            print!("{}", help);
+        } else if args[1] == "--version" {
+            println!("{}", help.version());
+        }
+    }
     0
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    app!(SYNTAX, SUMMARY, "")
+}
@@ -41,9 +41,7 @@ fn get_usage() -> String {
     format!("{0} [OPTION]... [FILE]...", executable!())
 }
 
-fn get_long_usage() -> String {
-    String::from(
-        "
+static LONG_USAGE: &str = "
     SIZE is an integer with an optional prefix and optional unit.
     The available units (K, M, G, T, P, E, Z, and Y) use the following format:
         'KB' =>           1000 (kilobytes)
@@ -59,19 +57,41 @@ fn get_long_usage() -> String {
         '<'  => at most
         '>'  => at least
         '/'  => round down to multiple of
-        '%'  => round up to multiple of",
-    )
-}
+        '%'  => round up to multiple of";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
-    let long_usage = get_long_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let files: Vec<String> = matches
+        .values_of(ARG_FILES)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    if files.is_empty() {
+        show_error!("Missing an argument");
+        return 1;
+    } else {
+        let io_blocks = matches.is_present(options::IO_BLOCKS);
+        let no_create = matches.is_present(options::NO_CREATE);
+        let reference = matches.value_of(options::REFERENCE).map(String::from);
+        let size = matches.value_of(options::SIZE).map(String::from);
+        if reference.is_none() && size.is_none() {
+            crash!(1, "you must specify either --reference or --size");
+        } else {
+            truncate(no_create, io_blocks, reference, size, files);
+        }
+    }
+
+    0
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
-        .after_help(&long_usage[..])
+        .after_help(LONG_USAGE)
         .arg(
             Arg::with_name(options::IO_BLOCKS)
             .short("o")
@@ -99,29 +119,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             .value_name("SIZE")
         )
         .arg(Arg::with_name(ARG_FILES).multiple(true).takes_value(true).min_values(1))
-        .get_matches_from(args);
-
-    let files: Vec<String> = matches
-        .values_of(ARG_FILES)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    if files.is_empty() {
-        show_error!("Missing an argument");
-        return 1;
-    } else {
-        let io_blocks = matches.is_present(options::IO_BLOCKS);
-        let no_create = matches.is_present(options::NO_CREATE);
-        let reference = matches.value_of(options::REFERENCE).map(String::from);
-        let size = matches.value_of(options::SIZE).map(String::from);
-        if reference.is_none() && size.is_none() {
-            crash!(1, "you must specify either --reference or --size");
-        } else {
-            truncate(no_create, io_blocks, reference, size, files);
-        }
-    }
-
-    0
 }
 
 fn truncate(
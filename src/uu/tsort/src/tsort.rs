@@ -28,12 +28,7 @@ mod options {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .usage(USAGE)
-        .about(SUMMARY)
-        .arg(Arg::with_name(options::FILE).hidden(true))
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app(), args);
 
     let input = match matches.value_of(options::FILE) {
         Some(v) => v,
@@ -93,6 +88,14 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     0
 }
 
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .usage(USAGE)
+        .about(SUMMARY)
+        .arg(Arg::with_name(options::FILE).hidden(true))
+}
+
 // We use String as a representation of node here
 // but using integer may improve performance.
 struct Graph {
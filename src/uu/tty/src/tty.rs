@@ -7,18 +7,23 @@
 //  *
 //  * Synced with http://lingrok.org/xref/coreutils/src/tty.c
 
-// spell-checker:ignore (ToDO) ttyname filedesc
+// spell-checker:ignore (ToDO) ttyname filedesc conin consoleapi processenv minwinbase msys mintty
 
 #[macro_use]
 extern crate uucore;
 
 use clap::{App, Arg};
-use std::ffi::CStr;
-use uucore::fs::is_stdin_interactive;
+use std::io::{self, Write};
+use uucore::error_message;
 
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 static ABOUT: &str = "Print the file name of the terminal connected to standard input.";
 
+// the statuses of GNU tty
+static EXIT_NOT_TTY: i32 = 1;
+static EXIT_FAILURE: i32 = 2;
+static EXIT_WRITE_ERROR: i32 = 3;
+
 mod options {
     pub const SILENT: &str = "silent";
 }
@@ -31,43 +36,134 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
     let usage = get_usage();
 
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .arg(
-            Arg::with_name(options::SILENT)
-                .long(options::SILENT)
-                .visible_alias("quiet")
-                .short("s")
-                .help("print nothing, only return an exit status")
-                .required(false),
-        )
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from_safe(uu_app().usage(&usage[..]), args);
+    let matches = match matches {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FAILURE;
+        }
+    };
 
-    let silent = matches.is_present(options::SILENT);
+    if matches.is_present(options::SILENT) {
+        return if is_tty() { 0 } else { EXIT_NOT_TTY };
+    }
 
-    // Call libc function ttyname
-    let tty = unsafe {
-        let ptr = libc::ttyname(libc::STDIN_FILENO);
-        if !ptr.is_null() {
-            String::from_utf8_lossy(CStr::from_ptr(ptr).to_bytes()).to_string()
-        } else {
-            "".to_owned()
-        }
+    let (tty, status) = match tty_name() {
+        Some(tty) => (tty, 0),
+        None => ("not a tty".to_owned(), EXIT_NOT_TTY),
     };
+    let mut stdout = io::stdout();
+    if let Err(e) = writeln!(stdout, "{}", tty).and_then(|_| stdout.flush()) {
+        show_info!("write error: {}", error_message(&e));
+        return EXIT_WRITE_ERROR;
+    }
+    status
+}
 
-    if !silent {
-        if !tty.chars().all(|c| c.is_whitespace()) {
-            println!("{}", tty);
-        } else {
-            println!("not a tty");
-        }
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!()).version(VERSION).about(ABOUT).arg(
+        Arg::with_name(options::SILENT)
+            .long(options::SILENT)
+            .visible_alias("quiet")
+            .short("s")
+            .help("print nothing, only return an exit status")
+            .required(false),
+    )
+}
+
+#[cfg(unix)]
+fn is_tty() -> bool {
+    uucore::fs::is_stdin_interactive()
+}
+
+#[cfg(windows)]
+fn is_tty() -> bool {
+    tty_name().is_some()
+}
+
+/// The file name of the terminal standard input is, or `None` if it isn't
+/// one, or isn't one with a name.
+#[cfg(unix)]
+fn tty_name() -> Option<String> {
+    use std::ffi::CStr;
+
+    let ptr = unsafe { libc::ttyname(libc::STDIN_FILENO) };
+    if ptr.is_null() {
+        return None;
     }
+    Some(String::from_utf8_lossy(unsafe { CStr::from_ptr(ptr) }.to_bytes()).into_owned())
+}
 
-    return if is_stdin_interactive() {
-        libc::EXIT_SUCCESS
-    } else {
-        libc::EXIT_FAILURE
+/// The name of the console standard input is, or `None` if it isn't one.
+#[cfg(windows)]
+fn tty_name() -> Option<String> {
+    use std::mem;
+    use std::slice;
+    use winapi::shared::minwindef::{DWORD, MAX_PATH};
+    use winapi::um::consoleapi::GetConsoleMode;
+    use winapi::um::fileapi::GetFileType;
+    use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+    use winapi::um::minwinbase::FileNameInfo;
+    use winapi::um::processenv::GetStdHandle;
+    use winapi::um::winbase::{
+        GetFileInformationByHandleEx, FILE_NAME_INFO, FILE_TYPE_PIPE, STD_INPUT_HANDLE,
     };
+
+    let handle = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+    if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+        return None;
+    }
+
+    // a console window, or the pseudo console (ConPTY) of a terminal
+    let mut mode: DWORD = 0;
+    if unsafe { GetConsoleMode(handle, &mut mode) } != 0 {
+        return Some("CONIN$".to_owned());
+    }
+
+    // the terminals of Cygwin and MSYS2, like mintty, are named pipes
+    if unsafe { GetFileType(handle) } != FILE_TYPE_PIPE {
+        return None;
+    }
+    // u32s, to be aligned as FILE_NAME_INFO is
+    let mut buf = vec![0u32; (mem::size_of::<FILE_NAME_INFO>() + MAX_PATH * 2) / 4 + 1];
+    let ok = unsafe {
+        GetFileInformationByHandleEx(
+            handle,
+            FileNameInfo,
+            buf.as_mut_ptr() as *mut _,
+            (buf.len() * 4) as DWORD,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+    let name = unsafe {
+        let info = &*(buf.as_ptr() as *const FILE_NAME_INFO);
+        slice::from_raw_parts(
+            info.FileName.as_ptr(),
+            info.FileNameLength as usize / mem::size_of::<u16>(),
+        )
+    };
+    pty_name(&String::from_utf16_lossy(name))
+}
+
+/// The device name Cygwin and MSYS2 give the pty whose pipe is named `pipe`,
+/// like "\msys-1888ae32e00d56aa-pty0-from-master" for "/dev/pty0".
+#[cfg(windows)]
+fn pty_name(pipe: &str) -> Option<String> {
+    let pipe = pipe.trim_start_matches('\\');
+    if !pipe.starts_with("msys-") && !pipe.starts_with("cygwin-") {
+        return None;
+    }
+    let number = &pipe[pipe.find("-pty")? + 4..];
+    let end = number.find('-')?;
+    if !number[end..].starts_with("-from-master") && !number[end..].starts_with("-to-master") {
+        return None;
+    }
+    let number = &number[..end];
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("/dev/pty{}", number))
 }
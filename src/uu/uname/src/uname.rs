@@ -8,7 +8,7 @@
 
 // last synced with: uname (GNU coreutils) 8.21
 
-// spell-checker:ignore (ToDO) nodename kernelname kernelrelease kernelversion sysname hwplatform mnrsv
+// spell-checker:ignore (ToDO) nodename kernelname kernelrelease kernelversion sysname hwplatform mnrsv sysctl sysctlbyname
 
 #[macro_use]
 extern crate uucore;
@@ -31,14 +31,22 @@ pub mod options {
     pub static OS: &str = "operating-system";
 }
 
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
 const HOST_OS: &str = "GNU/Linux";
+#[cfg(all(target_os = "linux", not(target_env = "gnu")))]
+const HOST_OS: &str = "Linux";
+#[cfg(target_os = "android")]
+const HOST_OS: &str = "Android";
 #[cfg(target_os = "windows")]
 const HOST_OS: &str = "Windows NT";
 #[cfg(target_os = "freebsd")]
 const HOST_OS: &str = "FreeBSD";
+#[cfg(target_os = "netbsd")]
+const HOST_OS: &str = "NetBSD";
 #[cfg(target_os = "openbsd")]
 const HOST_OS: &str = "OpenBSD";
+#[cfg(target_os = "dragonfly")]
+const HOST_OS: &str = "DragonFly";
 #[cfg(target_vendor = "apple")]
 const HOST_OS: &str = "Darwin";
 #[cfg(target_os = "fuchsia")]
@@ -48,49 +56,7 @@ const HOST_OS: &str = "Redox";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = format!("{} [OPTION]...", executable!());
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .arg(Arg::with_name(options::ALL)
-            .short("a")
-            .long(options::ALL)
-            .help("Behave as though all of the options -mnrsv were specified."))
-        .arg(Arg::with_name(options::KERNELNAME)
-            .short("s")
-            .long(options::KERNELNAME)
-            .alias("sysname") // Obsolescent option in GNU uname
-            .help("print the kernel name."))
-        .arg(Arg::with_name(options::NODENAME)
-            .short("n")
-            .long(options::NODENAME)
-            .help("print the nodename (the nodename may be a name that the system is known by to a communications network)."))
-        .arg(Arg::with_name(options::KERNELRELEASE)
-            .short("r")
-            .long(options::KERNELRELEASE)
-            .alias("release") // Obsolescent option in GNU uname
-            .help("print the operating system release."))
-        .arg(Arg::with_name(options::KERNELVERSION)
-            .short("v")
-            .long(options::KERNELVERSION)
-            .help("print the operating system version."))
-        .arg(Arg::with_name(options::HWPLATFORM)
-            .short("i")
-            .long(options::HWPLATFORM)
-            .help("print the hardware platform (non-portable)"))
-        .arg(Arg::with_name(options::MACHINE)
-            .short("m")
-            .long(options::MACHINE)
-            .help("print the machine hardware name."))
-        .arg(Arg::with_name(options::PROCESSOR)
-            .short("p")
-            .long(options::PROCESSOR)
-            .help("print the processor type (non-portable)"))
-        .arg(Arg::with_name(options::OS)
-            .short("o")
-            .long(options::OS)
-            .help("print the operating system name."))
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
 
     let uname = return_if_err!(1, PlatformInfo::new());
     let mut output = String::new();
@@ -136,17 +102,26 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         output.push_str(&uname.machine());
         output.push(' ');
     }
+    // like GNU, -a leaves these out rather than have them be "unknown"
     if processor || all {
-        // According to https://stackoverflow.com/posts/394271/revisions
-        // Most of the time, it returns unknown
-        output.push_str("unknown");
-        output.push(' ');
+        match processor_type(&uname) {
+            Some(processor) => output.push_str(&processor),
+            None if all => {}
+            None => output.push_str("unknown"),
+        }
+        if !output.ends_with(' ') {
+            output.push(' ');
+        }
     }
     if hwplatform || all {
-        // According to https://lists.gnu.org/archive/html/bug-coreutils/2005-09/msg00063.html
-        // Most of the time, it returns unknown
-        output.push_str("unknown");
-        output.push(' ');
+        match hardware_platform(&uname) {
+            Some(platform) => output.push_str(&platform),
+            None if all => {}
+            None => output.push_str("unknown"),
+        }
+        if !output.ends_with(' ') {
+            output.push(' ');
+        }
     }
     if os || all {
         output.push_str(HOST_OS);
@@ -156,3 +131,153 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
     0
 }
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(Arg::with_name(options::ALL)
+            .short("a")
+            .long(options::ALL)
+            .help("Behave as though all of the options -mnrsv were specified."))
+        .arg(Arg::with_name(options::KERNELNAME)
+            .short("s")
+            .long(options::KERNELNAME)
+            .alias("sysname") // Obsolescent option in GNU uname
+            .help("print the kernel name."))
+        .arg(Arg::with_name(options::NODENAME)
+            .short("n")
+            .long(options::NODENAME)
+            .help("print the nodename (the nodename may be a name that the system is known by to a communications network)."))
+        .arg(Arg::with_name(options::KERNELRELEASE)
+            .short("r")
+            .long(options::KERNELRELEASE)
+            .alias("release") // Obsolescent option in GNU uname
+            .help("print the operating system release."))
+        .arg(Arg::with_name(options::KERNELVERSION)
+            .short("v")
+            .long(options::KERNELVERSION)
+            .help("print the operating system version."))
+        .arg(Arg::with_name(options::HWPLATFORM)
+            .short("i")
+            .long(options::HWPLATFORM)
+            .help("print the hardware platform (non-portable)"))
+        .arg(Arg::with_name(options::MACHINE)
+            .short("m")
+            .long(options::MACHINE)
+            .help("print the machine hardware name."))
+        .arg(Arg::with_name(options::PROCESSOR)
+            .short("p")
+            .long(options::PROCESSOR)
+            .help("print the processor type (non-portable)"))
+        .arg(Arg::with_name(options::OS)
+            .short("o")
+            .long(options::OS)
+            .help("print the operating system name."))
+}
+
+/// The processor type, for -p; `None` if there's no telling.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
+fn processor_type(uname: &PlatformInfo) -> Option<String> {
+    // as distributions have GNU uname give, there being no other source
+    Some(uname.machine().into_owned())
+}
+
+#[cfg(target_vendor = "apple")]
+fn processor_type(_uname: &PlatformInfo) -> Option<String> {
+    // the architecture family, as Apple's uname gives it
+    if cfg!(any(target_arch = "arm", target_arch = "aarch64")) {
+        Some("arm".to_owned())
+    } else if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+        Some("i386".to_owned())
+    } else if cfg!(any(target_arch = "powerpc", target_arch = "powerpc64")) {
+        Some("powerpc".to_owned())
+    } else {
+        None
+    }
+}
+
+#[cfg(target_os = "freebsd")]
+fn processor_type(_uname: &PlatformInfo) -> Option<String> {
+    sysctl("hw.machine_arch")
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "windows",
+    target_vendor = "apple",
+    target_os = "freebsd"
+)))]
+fn processor_type(_uname: &PlatformInfo) -> Option<String> {
+    None
+}
+
+/// The hardware platform, for -i; `None` if there's no telling.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "windows"))]
+fn hardware_platform(uname: &PlatformInfo) -> Option<String> {
+    let machine = uname.machine();
+    // every 32-bit x86 is the one platform
+    let bytes = machine.as_bytes();
+    if bytes.len() == 4
+        && bytes[0] == b'i'
+        && (b'3'..=b'6').contains(&bytes[1])
+        && &bytes[2..] == b"86"
+    {
+        Some("i386".to_owned())
+    } else {
+        Some(machine.into_owned())
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+fn hardware_platform(_uname: &PlatformInfo) -> Option<String> {
+    sysctl("hw.model")
+}
+
+#[cfg(target_os = "freebsd")]
+fn hardware_platform(_uname: &PlatformInfo) -> Option<String> {
+    sysctl("hw.machine")
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "windows",
+    target_vendor = "apple",
+    target_os = "freebsd"
+)))]
+fn hardware_platform(_uname: &PlatformInfo) -> Option<String> {
+    None
+}
+
+/// The string value of the sysctl variable `name`.
+#[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+fn sysctl(name: &str) -> Option<String> {
+    use std::ffi::CString;
+    use std::ptr;
+
+    let name = CString::new(name).ok()?;
+    let mut buf = [0u8; 257];
+    let mut len = buf.len();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            name.as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            &mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 {
+        return None;
+    }
+    let value = &buf[..len];
+    let end = value.iter().position(|&b| b == 0).unwrap_or(len);
+    let value = String::from_utf8_lossy(&value[..end]).into_owned();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
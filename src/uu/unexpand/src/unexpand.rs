@@ -7,15 +7,16 @@
 //  * For the full copyright and license information, please view the LICENSE
 //  * file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) nums aflag uflag scol prevtab amode ctype cwidth nbytes lastcol pctype
+// spell-checker:ignore (ToDO) aflag uflag ctype cwidth nbytes
 
 #[macro_use]
 extern crate uucore;
 use clap::{App, Arg};
 use std::fs::File;
-use std::io::{stdin, stdout, BufRead, BufReader, BufWriter, Read, Stdout, Write};
+use std::io::{stdin, stdout, BufRead, BufReader, BufWriter, Read, Write};
 use std::str::from_utf8;
 use unicode_width::UnicodeWidthChar;
+use uucore::tabstops::TabStops;
 
 static NAME: &str = "unexpand";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -23,32 +24,6 @@ static USAGE: &str = "unexpand [OPTION]... [FILE]...";
 static SUMMARY: &str = "Convert blanks in each FILE to tabs, writing to standard output.\n
                  With no FILE, or when FILE is -, read standard input.";
 
-const DEFAULT_TABSTOP: usize = 8;
-
-fn tabstops_parse(s: String) -> Vec<usize> {
-    let words = s.split(',');
-
-    let nums = words
-        .map(|sn| {
-            sn.parse()
-                .unwrap_or_else(|_| crash!(1, "{}\n", "tab size contains invalid character(s)"))
-        })
-        .collect::<Vec<usize>>();
-
-    if nums.iter().any(|&n| n == 0) {
-        crash!(1, "{}\n", "tab size cannot be 0");
-    }
-
-    if let (false, _) = nums
-        .iter()
-        .fold((true, 0), |(acc, last), &n| (acc && last <= n, n))
-    {
-        crash!(1, "{}\n", "tab sizes must be ascending");
-    }
-
-    nums
-}
-
 mod options {
     pub const FILE: &str = "file";
     pub const ALL: &str = "all";
@@ -59,24 +34,28 @@ mod options {
 
 struct Options {
     files: Vec<String>,
-    tabstops: Vec<usize>,
+    tabstops: TabStops,
     aflag: bool,
     uflag: bool,
 }
 
 impl Options {
     fn new(matches: clap::ArgMatches) -> Options {
-        let tabstops = match matches.value_of(options::TABS) {
-            None => vec![DEFAULT_TABSTOP],
-            Some(s) => tabstops_parse(s.to_string()),
+        // every -t option adds to the list of tab stops
+        let tabstops = match matches.values_of(options::TABS) {
+            None => TabStops::default(),
+            Some(lists) => match TabStops::parse(&lists.collect::<Vec<_>>().join(",")) {
+                Ok(tabstops) => tabstops,
+                Err(e) => crash!(1, "{}", e),
+            },
         };
 
         let aflag = (matches.is_present(options::ALL) || matches.is_present(options::TABS))
             && !matches.is_present(options::FIRST_ONLY);
         let uflag = !matches.is_present(options::NO_UTF8);
 
-        let files = match matches.value_of(options::FILE) {
-            Some(v) => vec![v.to_string()],
+        let files = match matches.values_of(options::FILE) {
+            Some(v) => v.map(|v| v.to_string()).collect(),
             None => vec!["-".to_owned()],
         };
 
@@ -92,7 +71,15 @@ impl Options {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app(), args);
+
+    unexpand(Options::new(matches));
+
+    0
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .name(NAME)
         .version(VERSION)
         .usage(USAGE)
@@ -115,8 +102,12 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             Arg::with_name(options::TABS)
                 .short("t")
                 .long(options::TABS)
-                .long_help("use comma separated LIST of tab positions or have tabs N characters apart instead of 8 (enables -a)")
+                .long_help("use comma separated LIST of tab positions or have tabs N characters apart instead of 8 (enables -a); \
+                            the last position may be prefixed with '/' to continue with tab stops at every multiple of it, \
+                            or with '+' to continue with tab stops that many columns apart")
                 .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
         )
         .arg(
             Arg::with_name(options::NO_UTF8)
@@ -124,11 +115,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .long(options::NO_UTF8)
                 .takes_value(false)
                 .help("interpret input file as 8-bit ASCII rather than UTF-8"))
-        .get_matches_from(args);
-
-    unexpand(Options::new(matches));
-
-    0
 }
 
 fn open(path: String) -> BufReader<Box<dyn Read + 'static>> {
@@ -144,48 +130,6 @@ fn open(path: String) -> BufReader<Box<dyn Read + 'static>> {
     }
 }
 
-fn next_tabstop(tabstops: &[usize], col: usize) -> Option<usize> {
-    if tabstops.len() == 1 {
-        Some(tabstops[0] - col % tabstops[0])
-    } else {
-        // find next larger tab
-        match tabstops.iter().find(|&&t| t > col) {
-            Some(t) => Some(t - col),
-            None => None, // if there isn't one in the list, tab becomes a single space
-        }
-    }
-}
-
-fn write_tabs(
-    output: &mut BufWriter<Stdout>,
-    tabstops: &[usize],
-    mut scol: usize,
-    col: usize,
-    prevtab: bool,
-    init: bool,
-    amode: bool,
-) {
-    // This conditional establishes the following:
-    // We never turn a single space before a non-blank into
-    // a tab, unless it's at the start of the line.
-    let ai = init || amode;
-    if (ai && !prevtab && col > scol + 1) || (col > scol && (init || ai && prevtab)) {
-        while let Some(nts) = next_tabstop(tabstops, scol) {
-            if col < scol + nts {
-                break;
-            }
-
-            safe_unwrap!(output.write_all(b"\t"));
-            scol += nts;
-        }
-    }
-
-    while col > scol {
-        safe_unwrap!(output.write_all(b" "));
-        scol += 1;
-    }
-}
-
 #[derive(PartialEq, Eq, Debug)]
 enum CharType {
     Backspace,
@@ -240,102 +184,97 @@ fn next_char_info(uflag: bool, buf: &[u8], byte: usize) -> (CharType, usize, usi
 
 fn unexpand(options: Options) {
     let mut output = BufWriter::new(stdout());
-    let ts = &options.tabstops[..];
     let mut buf = Vec::new();
-    let lastcol = if ts.len() > 1 { *ts.last().unwrap() } else { 0 };
+    let mut pending = Vec::new();
 
-    for file in options.files.into_iter() {
-        let mut fh = open(file);
+    for file in options.files.iter() {
+        let mut fh = open(file.clone());
 
         while match fh.read_until(b'\n', &mut buf) {
             Ok(s) => s > 0,
             Err(_) => !buf.is_empty(),
         } {
-            let mut byte = 0; // offset into the buffer
-            let mut col = 0; // the current column
-            let mut scol = 0; // the start col for the current span, i.e., the already-printed width
-            let mut init = true; // are we at the start of the line?
-            let mut pctype = CharType::Other;
-
-            while byte < buf.len() {
-                // when we have a finite number of columns, never convert past the last column
-                if lastcol > 0 && col >= lastcol {
-                    write_tabs(
-                        &mut output,
-                        ts,
-                        scol,
-                        col,
-                        pctype == CharType::Tab,
-                        init,
-                        true,
-                    );
-                    safe_unwrap!(output.write_all(&buf[byte..]));
-                    scol = col;
-                    break;
-                }
-
-                // figure out how big the next char is, if it's UTF-8
-                let (ctype, cwidth, nbytes) = next_char_info(options.uflag, &buf, byte);
+            unexpand_line(&buf, &options, &mut pending, &mut output);
+            buf.truncate(0); // clear out the buffer
+        }
+    }
+    crash_if_err!(1, output.flush())
+}
 
-                // now figure out how many columns this char takes up, and maybe print it
-                let tabs_buffered = init || options.aflag;
-                match ctype {
-                    CharType::Space | CharType::Tab => {
-                        // compute next col, but only write space or tab chars if not buffering
-                        col += if ctype == CharType::Space {
-                            1
-                        } else {
-                            next_tabstop(ts, col).unwrap_or(1)
-                        };
+/// Convert the blanks of one line to tabs, following GNU unexpand: a run of
+/// blanks is replaced by tabs when it reaches a tab stop, unless it is a
+/// single space, which is only turned into a tab when it is followed by more
+/// blanks. Backspaces move back one column.
+fn unexpand_line<W: Write>(line: &[u8], options: &Options, pending: &mut Vec<u8>, output: &mut W) {
+    let ts = &options.tabstops;
+    let mut convert = true; // are blanks still converted on this line?
+    let mut col = 0; // the column of the next char
+    let mut prev_blank = true;
+    // was a single space pending right before the previous tab stop?
+    let mut one_blank_before_tab_stop = false;
+    let mut byte = 0; // offset into the line
+
+    pending.clear();
+    while byte < line.len() {
+        let (ctype, cwidth, nbytes) = next_char_info(options.uflag, line, byte);
+        let mut c = &line[byte..byte + nbytes];
+        byte += nbytes;
+
+        if !convert {
+            safe_unwrap!(output.write_all(c));
+            continue;
+        }
 
-                        if !tabs_buffered {
-                            safe_unwrap!(output.write_all(&buf[byte..byte + nbytes]));
-                            scol = col; // now printed up to this column
+        let blank = ctype == CharType::Space || ctype == CharType::Tab;
+        if blank {
+            match ts.next_tabstop(col) {
+                // blanks past the last tab stop are left alone
+                None => convert = false,
+                Some(nts) => {
+                    let next_tab_col = col + nts;
+                    if ctype == CharType::Tab {
+                        col = next_tab_col;
+                    } else {
+                        col += 1;
+                        if !(prev_blank && col == next_tab_col) {
+                            // we don't know yet whether this space becomes a tab
+                            if col == next_tab_col {
+                                one_blank_before_tab_stop = true;
+                            }
+                            pending.push(b' ');
+                            prev_blank = true;
+                            continue;
                         }
+                        c = b"\t";
                     }
-                    CharType::Other | CharType::Backspace => {
-                        // always
-                        write_tabs(
-                            &mut output,
-                            ts,
-                            scol,
-                            col,
-                            pctype == CharType::Tab,
-                            init,
-                            options.aflag,
-                        );
-                        init = false; // no longer at the start of a line
-                        col = if ctype == CharType::Other {
-                            // use computed width
-                            col + cwidth
-                        } else if col > 0 {
-                            // Backspace case, but only if col > 0
-                            col - 1
-                        } else {
-                            0
-                        };
-                        safe_unwrap!(output.write_all(&buf[byte..byte + nbytes]));
-                        scol = col; // we've now printed up to this column
+                    // the pending blanks are absorbed by this tab, except for
+                    // a single blank right before the previous tab stop
+                    if let Some(first) = pending.first_mut() {
+                        *first = b'\t';
                     }
+                    pending.truncate(one_blank_before_tab_stop as usize);
                 }
-
-                byte += nbytes; // move on to next char
-                pctype = ctype; // save the previous type
             }
-
-            // write out anything remaining
-            write_tabs(
-                &mut output,
-                ts,
-                scol,
-                col,
-                pctype == CharType::Tab,
-                init,
-                true,
-            );
-            safe_unwrap!(output.flush());
-            buf.truncate(0); // clear out the buffer
+        } else if ctype == CharType::Backspace {
+            col = col.saturating_sub(1);
+        } else {
+            col += cwidth;
         }
+
+        write_pending(output, pending, one_blank_before_tab_stop);
+        one_blank_before_tab_stop = false;
+        prev_blank = blank;
+        convert &= options.aflag || blank;
+        safe_unwrap!(output.write_all(c));
     }
-    crash_if_err!(1, output.flush())
+
+    write_pending(output, pending, one_blank_before_tab_stop);
+}
+
+fn write_pending<W: Write>(output: &mut W, pending: &mut Vec<u8>, one_blank_before_tab_stop: bool) {
+    if pending.len() > 1 && one_blank_before_tab_stop {
+        pending[0] = b'\t';
+    }
+    safe_unwrap!(output.write_all(pending));
+    pending.clear();
 }
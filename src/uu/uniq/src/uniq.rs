@@ -211,24 +211,64 @@ fn get_usage() -> String {
     format!("{0} [OPTION]... [INPUT [OUTPUT]]...", executable!())
 }
 
-fn get_long_usage() -> String {
-    String::from(
-        "Filter adjacent matching lines from INPUT (or standard input),\n\
+static LONG_USAGE: &str = "Filter adjacent matching lines from INPUT (or standard input),\n\
         writing to OUTPUT (or standard output).
         Note: 'uniq' does not detect repeated lines unless they are adjacent.\n\
-        You may want to sort the input first, or use 'sort -u' without 'uniq'.\n",
-    )
-}
+        You may want to sort the input first, or use 'sort -u' without 'uniq'.\n";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
-    let long_usage = get_long_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let files: Vec<String> = matches
+        .values_of(ARG_FILES)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    let (in_file_name, out_file_name) = match files.len() {
+        0 => ("-".to_owned(), "-".to_owned()),
+        1 => (files[0].clone(), "-".to_owned()),
+        2 => (files[0].clone(), files[1].clone()),
+        _ => {
+            // Cannot happen as clap will fail earlier
+            crash!(1, "Extra operand: {}", files[2]);
+        }
+    };
+
+    let uniq = Uniq {
+        repeats_only: matches.is_present(options::REPEATED)
+            || matches.occurrences_of(options::ALL_REPEATED) > 0,
+        uniques_only: matches.is_present(options::UNIQUE),
+        all_repeated: matches.occurrences_of(options::ALL_REPEATED) > 0,
+        delimiters: match matches.value_of(options::ALL_REPEATED).map(String::from) {
+            Some(ref opt_arg) if opt_arg != "none" => match &(*opt_arg.as_str()) {
+                "prepend" => Delimiters::Prepend,
+                "separate" => Delimiters::Separate,
+                _ => crash!(1, "Incorrect argument for all-repeated: {}", opt_arg),
+            },
+            _ => Delimiters::None,
+        },
+        show_counts: matches.is_present(options::COUNT),
+        skip_fields: opt_parsed(options::SKIP_FIELDS, &matches),
+        slice_start: opt_parsed(options::SKIP_CHARS, &matches),
+        slice_stop: opt_parsed(options::CHECK_CHARS, &matches),
+        ignore_case: matches.is_present(options::IGNORE_CASE),
+        zero_terminated: matches.is_present(options::ZERO_TERMINATED),
+    };
+    uniq.print_uniq(
+        &mut open_input_file(in_file_name),
+        &mut open_output_file(out_file_name),
+    );
+
+    0
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
-        .after_help(&long_usage[..])
+        .after_help(LONG_USAGE)
         .arg(
             Arg::with_name(options::ALL_REPEATED)
                 .short("D")
@@ -295,49 +335,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .takes_value(true)
                 .max_values(2),
         )
-        .get_matches_from(args);
-
-    let files: Vec<String> = matches
-        .values_of(ARG_FILES)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    let (in_file_name, out_file_name) = match files.len() {
-        0 => ("-".to_owned(), "-".to_owned()),
-        1 => (files[0].clone(), "-".to_owned()),
-        2 => (files[0].clone(), files[1].clone()),
-        _ => {
-            // Cannot happen as clap will fail earlier
-            crash!(1, "Extra operand: {}", files[2]);
-        }
-    };
-
-    let uniq = Uniq {
-        repeats_only: matches.is_present(options::REPEATED)
-            || matches.occurrences_of(options::ALL_REPEATED) > 0,
-        uniques_only: matches.is_present(options::UNIQUE),
-        all_repeated: matches.occurrences_of(options::ALL_REPEATED) > 0,
-        delimiters: match matches.value_of(options::ALL_REPEATED).map(String::from) {
-            Some(ref opt_arg) if opt_arg != "none" => match &(*opt_arg.as_str()) {
-                "prepend" => Delimiters::Prepend,
-                "separate" => Delimiters::Separate,
-                _ => crash!(1, "Incorrect argument for all-repeated: {}", opt_arg),
-            },
-            _ => Delimiters::None,
-        },
-        show_counts: matches.is_present(options::COUNT),
-        skip_fields: opt_parsed(options::SKIP_FIELDS, &matches),
-        slice_start: opt_parsed(options::SKIP_CHARS, &matches),
-        slice_stop: opt_parsed(options::CHECK_CHARS, &matches),
-        ignore_case: matches.is_present(options::IGNORE_CASE),
-        zero_terminated: matches.is_present(options::ZERO_TERMINATED),
-    };
-    uniq.print_uniq(
-        &mut open_input_file(in_file_name),
-        &mut open_output_file(out_file_name),
-    );
-
-    0
 }
 
 fn open_input_file(in_file_name: String) -> BufReader<Box<dyn Read + 'static>> {
@@ -12,42 +12,20 @@
 #[macro_use]
 extern crate uucore;
 
-use getopts::Options;
 use libc::{lstat, stat, unlink};
 use libc::{S_IFLNK, S_IFMT, S_IFREG};
 use std::ffi::CString;
 use std::io::{Error, ErrorKind};
+use uucore::coreopts::CoreOptions;
 
 static NAME: &str = "unlink";
-static VERSION: &str = env!("CARGO_PKG_VERSION");
+static SYNTAX: &str = "FILE";
+static SUMMARY: &str = "Unlink the file at FILE.";
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let mut opts = Options::new();
-
-    opts.optflag("h", "help", "display this help and exit");
-    opts.optflag("V", "version", "output version information and exit");
-
-    let matches = match opts.parse(&args[1..]) {
-        Ok(m) => m,
-        Err(f) => crash!(1, "invalid options\n{}", f),
-    };
-
-    if matches.opt_present("help") {
-        println!("{} {}", NAME, VERSION);
-        println!();
-        println!("Usage:");
-        println!("  {} [FILE]... [OPTION]...", NAME);
-        println!();
-        println!("{}", opts.usage("Unlink the file at [FILE]."));
-        return 0;
-    }
-
-    if matches.opt_present("version") {
-        println!("{} {}", NAME, VERSION);
-        return 0;
-    }
+    let matches = uu_app().parse(args);
 
     if matches.free.is_empty() {
         crash!(
@@ -107,3 +85,10 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
     0
 }
+
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut app = app!(SYNTAX, SUMMARY, "");
+    app.optflag("h", "help", "display this help and exit")
+        .optflag("V", "version", "output version information and exit");
+    app
+}
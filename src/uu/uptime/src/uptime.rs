@@ -6,7 +6,7 @@
 //  * For the full copyright and license information, please view the LICENSE
 //  * file that was distributed with this source code.
 
-// spell-checker:ignore (ToDO) getloadavg upsecs updays nusers loadavg boottime uphours upmins
+// spell-checker:ignore (ToDO) getloadavg upsecs updays nusers loadavg boottime uphours upmins upweeks upyears BOOTTIME
 
 use chrono::{Local, TimeZone, Utc};
 use clap::{App, Arg};
@@ -23,6 +23,7 @@ the number of users on the system, and the average number of jobs\n\
 in the run queue over the last 1, 5 and 15 minutes.";
 pub mod options {
     pub static SINCE: &str = "since";
+    pub static PRETTY: &str = "pretty";
 }
 
 #[cfg(unix)]
@@ -39,17 +40,7 @@ fn get_usage() -> String {
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .arg(
-            Arg::with_name(options::SINCE)
-                .short("s")
-                .long(options::SINCE)
-                .help("system up since"),
-        )
-        .get_matches_from(args);
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
 
     let (boot_time, user_count) = process_utmpx();
     let uptime = get_uptime(boot_time);
@@ -63,6 +54,10 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
             println!("{}", initial_date.format("%Y-%m-%d %H:%M:%S"));
             return 0;
         }
+        if matches.is_present(options::PRETTY) {
+            println!("{}", pretty_uptime(uptime));
+            return 0;
+        }
 
         print_time();
         let upsecs = uptime;
@@ -74,6 +69,24 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     }
 }
 
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(
+            Arg::with_name(options::SINCE)
+                .short("s")
+                .long(options::SINCE)
+                .help("system up since"),
+        )
+        .arg(
+            Arg::with_name(options::PRETTY)
+                .short("p")
+                .long(options::PRETTY)
+                .help("show uptime in pretty format"),
+        )
+}
+
 #[cfg(unix)]
 fn print_loadavg() {
     use uucore::libc::c_double;
@@ -110,7 +123,7 @@ fn process_utmpx() -> (Option<time_t>, usize) {
 
     for line in Utmpx::iter_all_records() {
         match line.record_type() {
-            USER_PROCESS => nusers += 1,
+            USER_PROCESS if line.is_user_process() && line.process_exists() => nusers += 1,
             BOOT_TIME => {
                 let t = line.login_time().to_timespec();
                 if t.sec > 0 {
@@ -144,25 +157,34 @@ fn print_time() {
 
 #[cfg(unix)]
 fn get_uptime(boot_time: Option<time_t>) -> i64 {
-    use std::fs::File;
-    use std::io::Read;
-
-    let mut proc_uptime_s = String::new();
-
-    let proc_uptime = File::open("/proc/uptime")
-        .ok()
-        .and_then(|mut f| f.read_to_string(&mut proc_uptime_s).ok())
-        .and_then(|_| proc_uptime_s.split_whitespace().next())
-        .and_then(|s| s.split('.').next().unwrap_or("0").parse().ok());
-
-    proc_uptime.unwrap_or_else(|| match boot_time {
+    match boot_time {
         Some(t) => {
             let now = Local::now().timestamp();
             let boottime = t as i64;
             now - boottime
         }
-        None => -1,
-    })
+        // containers often come without a boot record, but the clock that
+        // counts since the boot of their host is still there
+        None => get_boottime_clock().unwrap_or(-1),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_boottime_clock() -> Option<i64> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut ts) } == 0 {
+        Some(ts.tv_sec as i64)
+    } else {
+        None
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn get_boottime_clock() -> Option<i64> {
+    None
 }
 
 #[cfg(windows)]
@@ -182,3 +204,26 @@ fn print_uptime(upsecs: i64) {
         _ => print!("up  {:2}:{:02}, ", uphours, upmins),
     };
 }
+
+/// Uptime the way `--pretty` has it, as "up 2 weeks, 3 days, 1 hour": the
+/// units in use from years down to minutes, leaving out those that are zero.
+fn pretty_uptime(upsecs: i64) -> String {
+    let upmins = upsecs / 60;
+    let units = [
+        (upmins / (365 * 24 * 60), "year"),
+        (upmins / (7 * 24 * 60) % 52, "week"),
+        (upmins / (24 * 60) % 7, "day"),
+        (upmins / 60 % 24, "hour"),
+        (upmins % 60, "minute"),
+    ];
+    let parts: Vec<String> = units
+        .iter()
+        .filter(|&&(n, _)| n != 0)
+        .map(|&(n, unit)| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" }))
+        .collect();
+    if parts.is_empty() {
+        "up 0 minutes".to_owned()
+    } else {
+        format!("up {}", parts.join(", "))
+    }
+}
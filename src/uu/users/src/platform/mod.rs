@@ -0,0 +1,16 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+#[cfg(unix)]
+pub use self::unix::logged_in;
+
+#[cfg(windows)]
+pub use self::windows::logged_in;
+
+#[cfg(unix)]
+mod unix;
+
+#[cfg(windows)]
+mod windows;
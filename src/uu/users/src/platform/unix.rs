@@ -0,0 +1,21 @@
+// This file is part of the uutils coreutils package.
+//
+// (c) KokaKiwi <kokakiwi@kokakiwi.net>
+// (c) Jian Zeng <anonymousknight86@gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use uucore::utmpx::*;
+
+/// The names of the users with a login record in `file`, or in the system's
+/// login records when there's no `file`; only those are checked for logins
+/// whose process has gone away without a record of it.
+pub fn logged_in(file: Option<&str>) -> Vec<String> {
+    let check_pids = file.is_none();
+    Utmpx::iter_all_records()
+        .read_from(file.unwrap_or(DEFAULT_FILE))
+        .filter(|ut| ut.is_user_process() && (!check_pids || ut.process_exists()))
+        .map(|ut| ut.user())
+        .collect()
+}
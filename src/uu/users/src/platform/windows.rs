@@ -0,0 +1,57 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (ToDO) wtsapi WCHAR LPWSTR
+
+extern crate winapi;
+
+use self::winapi::shared::minwindef::DWORD;
+use self::winapi::um::winnt::LPWSTR;
+use self::winapi::um::wtsapi32::{
+    WTSActive, WTSEnumerateSessionsW, WTSFreeMemory, WTSQuerySessionInformationW, WTSUserName,
+    WTS_CURRENT_SERVER_HANDLE, WTS_SESSION_INFOW,
+};
+use std::ptr;
+use std::slice;
+use uucore::wide::FromWide;
+
+/// The names of the users with an active session. Windows keeps no login
+/// record file, so there's no `file` to read them from instead.
+pub fn logged_in(_file: Option<&str>) -> Vec<String> {
+    let mut users = Vec::new();
+    unsafe {
+        let mut sessions: *mut WTS_SESSION_INFOW = ptr::null_mut();
+        let mut count: DWORD = 0;
+        if WTSEnumerateSessionsW(WTS_CURRENT_SERVER_HANDLE, 0, 1, &mut sessions, &mut count) == 0 {
+            return users;
+        }
+        for session in slice::from_raw_parts(sessions, count as usize) {
+            if session.State != WTSActive {
+                continue;
+            }
+            let mut name: LPWSTR = ptr::null_mut();
+            let mut size: DWORD = 0;
+            if WTSQuerySessionInformationW(
+                WTS_CURRENT_SERVER_HANDLE,
+                session.SessionId,
+                WTSUserName,
+                &mut name,
+                &mut size,
+            ) == 0
+            {
+                continue;
+            }
+            // size is in bytes, and counts the terminating NUL
+            let len = (size as usize / 2).saturating_sub(1);
+            let user = String::from_wide(slice::from_raw_parts(name, len));
+            WTSFreeMemory(name as _);
+            if !user.is_empty() {
+                users.push(user);
+            }
+        }
+        WTSFreeMemory(sessions as _);
+    }
+    users
+}
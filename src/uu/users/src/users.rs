@@ -7,13 +7,11 @@
 //  * file that was distributed with this source code.
 
 /* last synced with: whoami (GNU coreutils) 8.22 */
-// Allow dead code here in order to keep all fields, constants here, for consistency.
-#![allow(dead_code)]
 
 #[macro_use]
 extern crate uucore;
 
-use uucore::utmpx::*;
+mod platform;
 
 use clap::{App, Arg};
 
@@ -29,29 +27,9 @@ fn get_usage() -> String {
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
-        .version(VERSION)
-        .about(ABOUT)
-        .usage(&usage[..])
-        .arg(Arg::with_name(ARG_FILES).takes_value(true).max_values(1))
-        .get_matches_from(args);
-
-    let files: Vec<String> = matches
-        .values_of(ARG_FILES)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    let filename = if !files.is_empty() {
-        files[0].as_ref()
-    } else {
-        DEFAULT_FILE
-    };
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
 
-    let mut users = Utmpx::iter_all_records()
-        .read_from(filename)
-        .filter(Utmpx::is_user_process)
-        .map(|ut| ut.user())
-        .collect::<Vec<_>>();
+    let mut users = platform::logged_in(matches.value_of(ARG_FILES));
 
     if !users.is_empty() {
         users.sort();
@@ -60,3 +38,10 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
     0
 }
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
+        .version(VERSION)
+        .about(ABOUT)
+        .arg(Arg::with_name(ARG_FILES).takes_value(true).max_values(1))
+}
@@ -158,6 +158,7 @@ struct TitledWordCount<'a> {
 
 static ABOUT: &str = "Display newline, word, and byte counts for each FILE, and a total line if
 more than one FILE is specified.";
+static LONG_HELP: &str = "With no FILE, or when FILE is -, read standard input.";
 static VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub mod options {
@@ -171,20 +172,37 @@ pub mod options {
 static ARG_FILES: &str = "files";
 
 fn get_usage() -> String {
-    format!(
-        "{0} [OPTION]... [FILE]...
- With no FILE, or when FILE is -, read standard input.",
-        executable!()
-    )
+    format!("{0} [OPTION]... [FILE]...", executable!())
 }
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let usage = get_usage();
 
-    let matches = App::new(executable!())
+    let matches = uucore::help::get_matches_from(uu_app().usage(&usage[..]), args);
+
+    let mut files: Vec<String> = matches
+        .values_of(ARG_FILES)
+        .map(|v| v.map(ToString::to_string).collect())
+        .unwrap_or_default();
+
+    if files.is_empty() {
+        files.push("-".to_owned());
+    }
+
+    let settings = Settings::new(&matches);
+
+    if wc(files, &settings).is_ok() {
+        0
+    } else {
+        1
+    }
+}
+
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    App::new(executable!())
         .version(VERSION)
         .about(ABOUT)
-        .usage(&usage[..])
+        .after_help(LONG_HELP)
         .arg(
             Arg::with_name(options::BYTES)
                 .short("c")
@@ -216,24 +234,6 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
                 .help("print the word counts"),
         )
         .arg(Arg::with_name(ARG_FILES).multiple(true).takes_value(true))
-        .get_matches_from(args);
-
-    let mut files: Vec<String> = matches
-        .values_of(ARG_FILES)
-        .map(|v| v.map(ToString::to_string).collect())
-        .unwrap_or_default();
-
-    if files.is_empty() {
-        files.push("-".to_owned());
-    }
-
-    let settings = Settings::new(&matches);
-
-    if wc(files, &settings).is_ok() {
-        0
-    } else {
-        1
-    }
 }
 
 const CR: u8 = b'\r';
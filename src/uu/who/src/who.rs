@@ -9,6 +9,7 @@
 
 #[macro_use]
 extern crate uucore;
+use uucore::coreopts::CoreOptions;
 use uucore::libc::{ttyname, STDIN_FILENO, S_IWGRP};
 use uucore::utmpx::{self, time, Utmpx};
 
@@ -20,25 +21,6 @@ use std::path::PathBuf;
 static SYNTAX: &str = "[OPTION]... [ FILE | ARG1 ARG2 ]";
 static SUMMARY: &str = "Print information about users who are currently logged in.";
 static LONG_HELP: &str = "
-  -a, --all         same as -b -d --login -p -r -t -T -u
-  -b, --boot        time of last system boot
-  -d, --dead        print dead processes
-  -H, --heading     print line of column headings
-  -l, --login       print system login processes
-      --lookup      attempt to canonicalize hostnames via DNS
-  -m                only hostname and user associated with stdin
-  -p, --process     print active processes spawned by init
-  -q, --count       all login names and number of users logged on
-  -r, --runlevel    print current runlevel (not available on BSDs)
-  -s, --short       print only name, line, and time (default)
-  -t, --time        print last system clock change
-  -T, -w, --mesg    add user's message status as +, - or ?
-  -u, --users       list users logged in
-      --message     same as -T
-      --writable    same as -T
-      --help     display this help and exit
-      --version  output version information and exit
-
 If FILE is not specified, use /var/run/utmp.  /var/log/wtmp as FILE is common.
 If ARG1 ARG2 given, -m presumed: 'am i' or 'mom likes' are usual.
 ";
@@ -46,34 +28,7 @@ If ARG1 ARG2 given, -m presumed: 'am i' or 'mom likes' are usual.
 pub fn uumain(args: impl uucore::Args) -> i32 {
     let args = args.collect_str();
 
-    let mut opts = app!(SYNTAX, SUMMARY, LONG_HELP);
-    opts.optflag("a", "all", "same as -b -d --login -p -r -t -T -u");
-    opts.optflag("b", "boot", "time of last system boot");
-    opts.optflag("d", "dead", "print dead processes");
-    opts.optflag("H", "heading", "print line of column headings");
-    opts.optflag("l", "login", "print system login processes");
-    opts.optflag("", "lookup", "attempt to canonicalize hostnames via DNS");
-    opts.optflag("m", "", "only hostname and user associated with stdin");
-    opts.optflag("p", "process", "print active processes spawned by init");
-    opts.optflag(
-        "q",
-        "count",
-        "all login names and number of users logged on",
-    );
-    #[cfg(any(target_vendor = "apple", target_os = "linux", target_os = "android"))]
-    opts.optflag("r", "runlevel", "print current runlevel");
-    opts.optflag("s", "short", "print only name, line, and time (default)");
-    opts.optflag("t", "time", "print last system clock change");
-    opts.optflag("u", "users", "list users logged in");
-    opts.optflag("w", "mesg", "add user's message status as +, - or ?");
-    // --message, --writable are the same as --mesg
-    opts.optflag("T", "message", "");
-    opts.optflag("T", "writable", "");
-
-    opts.optflag("", "help", "display this help and exit");
-    opts.optflag("", "version", "output version information and exit");
-
-    let matches = opts.parse(args);
+    let matches = uu_app().parse(args);
 
     // If true, attempt to canonicalize hostnames via a DNS lookup.
     let do_lookup = matches.opt_present("lookup");
@@ -96,8 +51,10 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
 
     // If true, display a '+' for each user if mesg y, a '-' if mesg n,
     // or a '?' if their tty cannot be statted.
-    let include_mesg =
-        matches.opt_present("a") || matches.opt_present("T") || matches.opt_present("w");
+    let include_mesg = matches.opt_present("a")
+        || matches.opt_present("T")
+        || matches.opt_present("w")
+        || matches.opt_present("writable");
 
     // If true, display process termination & exit status.
     let mut include_exit = false;
@@ -231,6 +188,36 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     0
 }
 
+pub fn uu_app() -> CoreOptions<'static> {
+    let mut opts = app!(SYNTAX, SUMMARY, LONG_HELP);
+    opts.optflag("a", "all", "same as -b -d --login -p -r -t -T -u");
+    opts.optflag("b", "boot", "time of last system boot");
+    opts.optflag("d", "dead", "print dead processes");
+    opts.optflag("H", "heading", "print line of column headings");
+    opts.optflag("l", "login", "print system login processes");
+    opts.optflag("", "lookup", "attempt to canonicalize hostnames via DNS");
+    opts.optflag("m", "", "only hostname and user associated with stdin");
+    opts.optflag("p", "process", "print active processes spawned by init");
+    opts.optflag(
+        "q",
+        "count",
+        "all login names and number of users logged on",
+    );
+    #[cfg(any(target_vendor = "apple", target_os = "linux", target_os = "android"))]
+    opts.optflag("r", "runlevel", "print current runlevel");
+    opts.optflag("s", "short", "print only name, line, and time (default)");
+    opts.optflag("t", "time", "print last system clock change");
+    opts.optflag("u", "users", "list users logged in");
+    opts.optflag("T", "mesg", "add user's message status as +, - or ?");
+    // -w, --message and --writable are the same as --mesg
+    opts.optflag("w", "message", "");
+    opts.optflag("", "writable", "");
+
+    opts.optflag("", "help", "display this help and exit");
+    opts.optflag("", "version", "output version information and exit");
+    opts
+}
+
 struct Who {
     do_lookup: bool,
     short_list: bool,
@@ -280,16 +267,18 @@ fn time_string(ut: &Utmpx) -> String {
 }
 
 #[inline]
-fn current_tty() -> String {
+fn current_tty() -> Option<String> {
     unsafe {
         let res = ttyname(STDIN_FILENO);
         if !res.is_null() {
-            CStr::from_ptr(res as *const _)
-                .to_string_lossy()
-                .trim_start_matches("/dev/")
-                .to_owned()
+            Some(
+                CStr::from_ptr(res as *const _)
+                    .to_string_lossy()
+                    .trim_start_matches("/dev/")
+                    .to_owned(),
+            )
         } else {
-            "".to_owned()
+            None
         }
     }
 }
@@ -307,36 +296,46 @@ impl Who {
             res
         };
 
-        let f = if self.args.len() == 1 {
-            self.args[0].as_ref()
+        // like GNU, records of user processes that are gone are skipped in
+        // the default file, which isn't always cleaned up after them
+        let (f, check_pids) = if self.args.len() == 1 {
+            (self.args[0].as_ref(), false)
         } else {
-            utmpx::DEFAULT_FILE
+            (utmpx::DEFAULT_FILE, true)
         };
+        let records = Utmpx::iter_all_records()
+            .read_from(f)
+            .filter(|ut| !check_pids || !ut.is_user_process() || ut.process_exists());
+
         if self.short_list {
-            let users = Utmpx::iter_all_records()
-                .read_from(f)
+            let users = records
                 .filter(Utmpx::is_user_process)
                 .map(|ut| ut.user())
                 .collect::<Vec<_>>();
             println!("{}", users.join(" "));
             println!("# users={}", users.len());
         } else {
-            let mut records = Utmpx::iter_all_records().read_from(f).peekable();
+            let mut records = records.peekable();
             self.has_records = records.peek().is_some();
 
             if self.include_heading {
                 self.print_heading()
             }
             let cur_tty = if self.my_line_only {
-                current_tty()
+                match current_tty() {
+                    Some(tty) => tty,
+                    // without a terminal there's no line of one's own
+                    None => return,
+                }
             } else {
                 "".to_owned()
             };
 
+            let mut boottime = 0;
             for ut in records {
                 if !self.my_line_only || cur_tty == ut.tty_device() {
                     if self.need_users && ut.is_user_process() {
-                        self.print_user(&ut);
+                        self.print_user(&ut, boottime);
                     } else if self.need_runlevel && run_level_chk(ut.record_type()) {
                         self.print_runlevel(&ut);
                     } else if self.need_boottime && ut.record_type() == utmpx::BOOT_TIME {
@@ -352,7 +351,10 @@ impl Who {
                     }
                 }
 
-                if ut.record_type() == utmpx::BOOT_TIME {}
+                // terminals untouched since the last boot are idle for "old"
+                if ut.record_type() == utmpx::BOOT_TIME {
+                    boottime = ut.login_time().to_timespec().sec;
+                }
             }
         }
     }
@@ -362,7 +364,7 @@ impl Who {
         let last = (ut.pid() / 256) as u8 as char;
         let curr = (ut.pid() % 256) as u8 as char;
         let runlvline = format!("run-level {}", curr);
-        let comment = format!("last={}", if last == 'N' { 'S' } else { 'N' });
+        let comment = format!("last={}", if last == 'N' { 'S' } else { last });
 
         self.print_line(
             "",
@@ -371,7 +373,11 @@ impl Who {
             &time_string(ut),
             "",
             "",
-            if !last.is_control() { &comment } else { "" },
+            if (' '..='~').contains(&last) {
+                &comment
+            } else {
+                ""
+            },
             "",
         );
     }
@@ -436,7 +442,8 @@ impl Who {
         self.print_line("", ' ', "system boot", &time_string(ut), "", "", "", "");
     }
 
-    fn print_user(&self, ut: &Utmpx) {
+    fn print_user(&self, ut: &Utmpx, boottime: i64) {
+        // ut_line is relative to /dev unless it's absolute
         let mut p = PathBuf::from("/dev");
         p.push(ut.tty_device().as_str());
         let mesg;
@@ -457,7 +464,7 @@ impl Who {
         }
 
         let idle = if last_change != 0 {
-            idle_string(last_change, 0)
+            idle_string(last_change, boottime)
         } else {
             "  ?".into()
         };
@@ -7,20 +7,29 @@
  * file that was distributed with this source code.
  */
 
-// spell-checker:ignore (ToDO) advapi lmcons winnt getusername WCHAR UNLEN
+// spell-checker:ignore (ToDO) advapi lmcons winnt getusername WCHAR UNLEN secext winbase
 
 extern crate winapi;
 
 use self::winapi::shared::lmcons;
 use self::winapi::shared::minwindef;
+use self::winapi::um::secext::{GetUserNameExW, NameSamCompatible};
+use self::winapi::um::winbase::{GetComputerNameW, MAX_COMPUTERNAME_LENGTH};
 use self::winapi::um::winnt;
 use std::io::{Error, Result};
-use std::mem;
 use uucore::wide::FromWide;
 
+/// The name of the user this runs as, qualified with their domain as
+/// `DOMAIN\user` unless it's an account of this very computer.
 pub unsafe fn getusername() -> Result<String> {
-    #[allow(deprecated)]
-    let mut buffer: [winnt::WCHAR; lmcons::UNLEN as usize + 1] = mem::uninitialized();
+    match domain_username() {
+        Some(name) => Ok(name),
+        None => local_username(),
+    }
+}
+
+unsafe fn local_username() -> Result<String> {
+    let mut buffer = [0 as winnt::WCHAR; lmcons::UNLEN as usize + 1];
     let mut len = buffer.len() as minwindef::DWORD;
     if advapi32::GetUserNameW(buffer.as_mut_ptr(), &mut len) == 0 {
         return Err(Error::last_os_error());
@@ -28,3 +37,26 @@ pub unsafe fn getusername() -> Result<String> {
     let username = String::from_wide(&buffer[..len as usize - 1]);
     Ok(username)
 }
+
+unsafe fn domain_username() -> Option<String> {
+    // a domain is at most 15 characters, then come the backslash and the user
+    let mut buffer = [0 as winnt::WCHAR; lmcons::UNLEN as usize + 17];
+    let mut len = buffer.len() as minwindef::ULONG;
+    if GetUserNameExW(NameSamCompatible, buffer.as_mut_ptr(), &mut len) == 0 {
+        return None;
+    }
+    let name = String::from_wide(&buffer[..len as usize]);
+    let (domain, user) = match name.find('\\') {
+        Some(i) => (&name[..i], &name[i + 1..]),
+        None => return Some(name),
+    };
+    let mut computer = [0 as winnt::WCHAR; MAX_COMPUTERNAME_LENGTH as usize + 1];
+    let mut len = computer.len() as minwindef::DWORD;
+    if GetComputerNameW(computer.as_mut_ptr(), &mut len) != 0
+        && domain.eq_ignore_ascii_case(&String::from_wide(&computer[..len as usize]))
+    {
+        Some(user.to_owned())
+    } else {
+        Some(name)
+    }
+}
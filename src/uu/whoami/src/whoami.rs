@@ -17,18 +17,11 @@ extern crate uucore;
 mod platform;
 
 pub fn uumain(args: impl uucore::Args) -> i32 {
-    let app = app_from_crate!();
-
-    if let Err(err) = app.get_matches_from_safe(args) {
-        if err.kind == clap::ErrorKind::HelpDisplayed
-            || err.kind == clap::ErrorKind::VersionDisplayed
-        {
-            println!("{}", err);
-            0
-        } else {
-            show_error!("{}", err);
-            1
-        }
+    let app = uu_app();
+
+    if let Err(err) = uucore::help::get_matches_from_safe(app, args) {
+        show_error!("{}", err);
+        1
     } else {
         exec();
 
@@ -36,6 +29,10 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
     }
 }
 
+pub fn uu_app<'a>() -> clap::App<'a, 'a> {
+    app_from_crate!().name(executable!())
+}
+
 pub fn exec() {
     unsafe {
         match platform::getusername() {
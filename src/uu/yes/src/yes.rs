@@ -12,7 +12,7 @@ extern crate clap;
 #[macro_use]
 extern crate uucore;
 
-use clap::Arg;
+use clap::{App, Arg};
 use std::borrow::Cow;
 use std::io::{self, Write};
 use uucore::zero_copy::ZeroCopyWriter;
@@ -21,18 +21,16 @@ use uucore::zero_copy::ZeroCopyWriter;
 // systems, but honestly this is good enough
 const BUF_SIZE: usize = 16 * 1024;
 
+// Starting on a page boundary, the buffer is whole pages that vmsplice() can hand to the pipe
+// as they are
+#[repr(align(4096))]
+struct AlignedBuffer([u8; BUF_SIZE]);
+
 pub fn uumain(args: impl uucore::Args) -> i32 {
-    let app = app_from_crate!().arg(Arg::with_name("STRING").index(1).multiple(true));
+    let app = uu_app();
 
-    let matches = match app.get_matches_from_safe(args) {
+    let matches = match uucore::help::get_matches_from_safe(app, args) {
         Ok(m) => m,
-        Err(ref e)
-            if e.kind == clap::ErrorKind::HelpDisplayed
-                || e.kind == clap::ErrorKind::VersionDisplayed =>
-        {
-            println!("{}", e);
-            return 0;
-        }
         Err(f) => {
             show_error!("{}", f);
             return 1;
@@ -48,12 +46,24 @@ pub fn uumain(args: impl uucore::Args) -> i32 {
         Cow::from("y\n")
     };
 
-    let mut buffer = [0; BUF_SIZE];
-    let bytes = prepare_buffer(&string, &mut buffer);
+    let mut buffer = AlignedBuffer([0; BUF_SIZE]);
+    let bytes = prepare_buffer(&string, &mut buffer.0);
 
-    exec(bytes);
+    match exec(bytes) {
+        // the reader closing the pipe is how yes usually ends
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => 0,
+        Err(e) => {
+            show_error!("standard output: {}", e);
+            1
+        }
+        Ok(()) => 0,
+    }
+}
 
-    0
+pub fn uu_app<'a>() -> App<'a, 'a> {
+    app_from_crate!()
+        .name(executable!())
+        .arg(Arg::with_name("STRING").index(1).multiple(true))
 }
 
 #[cfg(not(feature = "latency"))]
@@ -76,11 +86,10 @@ fn prepare_buffer<'a>(input: &'a str, _buffer: &'a mut [u8; BUF_SIZE]) -> &'a [u
     input.as_bytes()
 }
 
-pub fn exec(bytes: &[u8]) {
+pub fn exec(bytes: &[u8]) -> io::Result<()> {
     let mut stdout_raw = io::stdout();
     let mut writer = ZeroCopyWriter::with_default(&mut stdout_raw, |stdout| stdout.lock());
     loop {
-        // TODO: needs to check if pipe fails
-        writer.write_all(bytes).unwrap();
+        writer.write_all(bytes)?;
     }
 }
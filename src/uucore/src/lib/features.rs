@@ -2,18 +2,32 @@
 
 #[cfg(feature = "encoding")]
 pub mod encoding;
+#[cfg(feature = "fnmatch")]
+pub mod fnmatch;
 #[cfg(feature = "fs")]
 pub mod fs;
+#[cfg(feature = "human")]
+pub mod human;
+#[cfg(feature = "i18n")]
+pub mod i18n;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "manpage")]
+pub mod manpage;
+#[cfg(feature = "mode")]
+pub mod mode;
+#[cfg(feature = "parse_datetime")]
+pub mod parse_datetime;
 #[cfg(feature = "parse_time")]
 pub mod parse_time;
+#[cfg(feature = "quoting")]
+pub mod quoting;
+#[cfg(feature = "sum")]
+pub mod sum;
 #[cfg(feature = "zero-copy")]
 pub mod zero_copy;
 
 // * (platform-specific) feature-gated modules
-// ** non-windows
-#[cfg(all(not(windows), feature = "mode"))]
-pub mod mode;
-
 // ** unix-only
 #[cfg(all(unix, feature = "entries"))]
 pub mod entries;
@@ -21,6 +35,10 @@ pub mod entries;
 pub mod perms;
 #[cfg(all(unix, feature = "process"))]
 pub mod process;
+#[cfg(all(unix, feature = "safe-traversal"))]
+pub mod safe_traversal;
+#[cfg(all(unix, feature = "selinux"))]
+pub mod selinux;
 
 #[cfg(all(unix, not(target_os = "fuchsia"), feature = "signals"))]
 pub mod signals;
@@ -34,3 +52,5 @@ pub mod utmpx;
 // ** windows-only
 #[cfg(all(windows, feature = "wide"))]
 pub mod wide;
+#[cfg(all(windows, feature = "winfs"))]
+pub mod winfs;
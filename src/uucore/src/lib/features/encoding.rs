@@ -9,8 +9,12 @@
 
 extern crate data_encoding;
 
-use self::data_encoding::{DecodeError, BASE32, BASE64};
+use self::data_encoding::{
+    BitOrder, DecodeError, DecodeKind, Encoding, Specification, BASE32, BASE32HEX, BASE64,
+    BASE64URL, HEXUPPER_PERMISSIVE,
+};
 
+use std::cmp::min;
 use std::io::{self, Read, Write};
 
 #[cfg(feature = "thiserror")]
@@ -22,29 +26,397 @@ pub enum EncodingError {
     Decode(#[from] DecodeError),
     #[error("{}", _0)]
     Io(#[from] io::Error),
+    #[error("invalid input (length must be multiple of 4 characters)")]
+    Z85Length,
 }
 
 pub type DecodeResult = Result<Vec<u8>, EncodingError>;
 
+/// How many input bytes are encoded at a time: a whole number of blocks of
+/// every format (1 to 5 bytes), so that the chunks encode independently.
+const ENCODE_CHUNK: usize = 15 * 4096;
+/// How many encoded bytes are read at a time when decoding.
+const DECODE_CHUNK: usize = 16 * 4096;
+
 #[derive(Clone, Copy)]
 pub enum Format {
     Base32,
+    Base32Hex,
     Base64,
+    Base64Url,
+    Base16,
+    Base2Msbf,
+    Base2Lsbf,
+    Z85,
 }
 use self::Format::*;
 
-pub fn encode(f: Format, input: &[u8]) -> String {
-    match f {
-        Base32 => BASE32.encode(input),
-        Base64 => BASE64.encode(input),
+impl Format {
+    /// The length of an encoded block, which decodes independently of the
+    /// blocks around it.
+    fn block_len(self) -> usize {
+        match self {
+            Base32 | Base32Hex | Base2Msbf | Base2Lsbf => 8,
+            Base64 | Base64Url => 4,
+            Base16 => 2,
+            Z85 => 5,
+        }
+    }
+
+    /// The characters of the encoding, which are what `--ignore-garbage`
+    /// doesn't ignore.
+    fn alphabet(self) -> &'static [u8] {
+        match self {
+            Base32 => b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567=",
+            Base32Hex => b"0123456789ABCDEFGHIJKLMNOPQRSTUV=",
+            Base64 => b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789=+/",
+            Base64Url => b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789=-_",
+            Base16 => b"0123456789ABCDEFabcdef",
+            Base2Msbf | Base2Lsbf => b"01",
+            Z85 => z85::SYMBOLS,
+        }
     }
 }
 
+/// A string of bits, 8 characters to the byte, in `bit_order`.
+fn base2(bit_order: BitOrder) -> Encoding {
+    let mut spec = Specification::new();
+    spec.symbols.push_str("01");
+    spec.bit_order = bit_order;
+    spec.encoding().unwrap()
+}
+
+/// The encoding of `input`.
+///
+/// # Panics
+///
+/// Panics for Z85, if the input isn't a whole number of 4 byte blocks.
+pub fn encode(f: Format, input: &[u8]) -> String {
+    let mut encoded = Vec::new();
+    encode_append(f, input, &mut encoded).unwrap();
+    // the encodings are all ASCII
+    String::from_utf8(encoded).unwrap()
+}
+
 pub fn decode(f: Format, input: &[u8]) -> DecodeResult {
-    Ok(match f {
-        Base32 => BASE32.decode(input)?,
-        Base64 => BASE64.decode(input)?,
-    })
+    let mut decoded = Vec::new();
+    decode_append(f, input, &mut decoded)?;
+    Ok(decoded)
+}
+
+/// Appends the encoding of `input` to `output`.
+fn encode_append(f: Format, input: &[u8], output: &mut Vec<u8>) -> Result<(), EncodingError> {
+    let (encoding, rest) = match f {
+        Base32 => (BASE32, input),
+        Base32Hex => (BASE32HEX, input),
+        Base64 => (BASE64, &input[simd::encode_base64(input, output)..]),
+        Base64Url => (BASE64URL, input),
+        Base16 => (HEXUPPER_PERMISSIVE, input),
+        Base2Msbf => (base2(BitOrder::MostSignificantFirst), input),
+        Base2Lsbf => (base2(BitOrder::LeastSignificantFirst), input),
+        Z85 => {
+            if input.len() % 4 != 0 {
+                return Err(EncodingError::Z85Length);
+            }
+            z85::encode(input, output);
+            return Ok(());
+        }
+    };
+    let start = output.len();
+    output.resize(start + encoding.encode_len(rest.len()), 0);
+    encoding.encode_mut(rest, &mut output[start..]);
+    Ok(())
+}
+
+/// Appends the decoding of `input` to `output`.
+fn decode_append(f: Format, input: &[u8], output: &mut Vec<u8>) -> Result<(), DecodeError> {
+    let (encoding, done) = match f {
+        Base32 => (BASE32, 0),
+        Base32Hex => (BASE32HEX, 0),
+        Base64 => (BASE64, simd::decode_base64(input, output)),
+        Base64Url => (BASE64URL, 0),
+        Base16 => (HEXUPPER_PERMISSIVE, 0),
+        Base2Msbf => (base2(BitOrder::MostSignificantFirst), 0),
+        Base2Lsbf => (base2(BitOrder::LeastSignificantFirst), 0),
+        Z85 => return z85::decode(input, output),
+    };
+    let rest = &input[done..];
+    // the errors are positioned in `rest`
+    let in_input = |e: DecodeError| DecodeError {
+        position: done + e.position,
+        ..e
+    };
+    let start = output.len();
+    output.resize(
+        start + encoding.decode_len(rest.len()).map_err(in_input)?,
+        0,
+    );
+    match encoding.decode_mut(rest, &mut output[start..]) {
+        Ok(len) => {
+            output.truncate(start + len);
+            Ok(())
+        }
+        Err(partial) => {
+            output.truncate(start + partial.written);
+            Err(in_input(partial.error))
+        }
+    }
+}
+
+/// Z85, the ascii85 variant of ZeroMQ (https://rfc.zeromq.org/spec/32/): each
+/// 4 bytes are a big-endian number, written as 5 digits in base 85.
+mod z85 {
+    use super::{DecodeError, DecodeKind};
+
+    pub const SYMBOLS: &[u8; 85] =
+        b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+    /// Appends the encoding of `input`, a whole number of 4 byte blocks, to
+    /// `output`.
+    pub fn encode(input: &[u8], output: &mut Vec<u8>) {
+        output.reserve(input.len() / 4 * 5);
+        for block in input.chunks_exact(4) {
+            let mut value = u32::from_be_bytes([block[0], block[1], block[2], block[3]]);
+            let mut digits = [0u8; 5];
+            for digit in digits.iter_mut().rev() {
+                *digit = SYMBOLS[(value % 85) as usize];
+                value /= 85;
+            }
+            output.extend_from_slice(&digits);
+        }
+    }
+
+    /// Appends the decoding of `input` to `output`.
+    pub fn decode(input: &[u8], output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let mut values = [None; 256];
+        for (value, &symbol) in SYMBOLS.iter().enumerate() {
+            values[symbol as usize] = Some(value as u64);
+        }
+
+        output.reserve(input.len() / 5 * 4);
+        for (i, block) in input.chunks(5).enumerate() {
+            let error = |position, kind| Err(DecodeError { position, kind });
+            if block.len() < 5 {
+                return error(5 * i, DecodeKind::Length);
+            }
+            let mut value = 0;
+            for (j, &c) in block.iter().enumerate() {
+                match values[c as usize] {
+                    Some(digit) => value = value * 85 + digit,
+                    None => return error(5 * i + j, DecodeKind::Symbol),
+                }
+            }
+            // 5 digits go up to 85^5 - 1, beyond 4 bytes
+            if value > u64::from(std::u32::MAX) {
+                return error(5 * i, DecodeKind::Symbol);
+            }
+            output.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        Ok(())
+    }
+}
+
+/// Base64 by SSSE3, after Wojciech Muła's "Base64 encoding and decoding with
+/// SIMD instructions": 12 bytes are encoded to 16 characters at a time, and
+/// 16 characters decoded to 12 bytes.  What is left over, or isn't plain
+/// base64 characters (padding, or errors to report), is left to the scalar
+/// codec.
+mod simd {
+    /// Appends the encoding of as much of `input` as the CPU can encode
+    /// quickly to `output`, and returns how much that is, a multiple of 3.
+    pub fn encode_base64(input: &[u8], output: &mut Vec<u8>) -> usize {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("ssse3") {
+                return unsafe { ssse3::encode(input, output) };
+            }
+        }
+        let _ = (input, output);
+        0
+    }
+
+    /// Appends the decoding of as much of `input` as the CPU can decode
+    /// quickly to `output`, and returns how much that is, a multiple of 4.
+    pub fn decode_base64(input: &[u8], output: &mut Vec<u8>) -> usize {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("ssse3") {
+                return unsafe { ssse3::decode(input, output) };
+            }
+        }
+        let _ = (input, output);
+        0
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    mod ssse3 {
+        use std::arch::x86_64::*;
+
+        /// The 16 characters encoding the first 12 bytes of `block`.
+        #[inline]
+        #[target_feature(enable = "ssse3")]
+        unsafe fn encode_block(block: __m128i) -> __m128i {
+            // each 3 bytes to a 32-bit lane, as 4 indices of 6 bits
+            let bytes = _mm_shuffle_epi8(
+                block,
+                _mm_set_epi8(10, 11, 9, 10, 7, 8, 6, 7, 4, 5, 3, 4, 1, 2, 0, 1),
+            );
+            let high = _mm_mulhi_epu16(
+                _mm_and_si128(bytes, _mm_set1_epi32(0x0fc0_fc00)),
+                _mm_set1_epi32(0x0400_0040),
+            );
+            let low = _mm_mullo_epi16(
+                _mm_and_si128(bytes, _mm_set1_epi32(0x003f_03f0)),
+                _mm_set1_epi32(0x0100_0010),
+            );
+            let indices = _mm_or_si128(high, low);
+
+            // each index to its character, by adding the offset of its range:
+            // 0 for 0..26, 1 to 10 for 26..52 and 52..62, 11 for 62, 12 for 63
+            let mut range = _mm_subs_epu8(indices, _mm_set1_epi8(51));
+            let upper = _mm_cmpgt_epi8(_mm_set1_epi8(26), indices);
+            range = _mm_or_si128(range, _mm_and_si128(upper, _mm_set1_epi8(13)));
+            let offsets = _mm_setr_epi8(
+                71, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -19, -16, 65, 0, 0,
+            );
+            _mm_add_epi8(indices, _mm_shuffle_epi8(offsets, range))
+        }
+
+        /// Mask of the bytes of `chars` in `low..=high`.
+        #[inline]
+        #[target_feature(enable = "ssse3")]
+        unsafe fn in_range(chars: __m128i, low: u8, high: u8) -> __m128i {
+            _mm_and_si128(
+                _mm_cmpgt_epi8(chars, _mm_set1_epi8(low as i8 - 1)),
+                _mm_cmpgt_epi8(_mm_set1_epi8(high as i8 + 1), chars),
+            )
+        }
+
+        /// The 12 bytes encoded by the 16 characters `chars`, followed by 4
+        /// zeroes, or `None` if they aren't all base64 characters.
+        #[inline]
+        #[target_feature(enable = "ssse3")]
+        unsafe fn decode_block(chars: __m128i) -> Option<__m128i> {
+            let ranges = [
+                (in_range(chars, b'A', b'Z'), -65),
+                (in_range(chars, b'a', b'z'), -71),
+                (in_range(chars, b'0', b'9'), 4),
+                (_mm_cmpeq_epi8(chars, _mm_set1_epi8(b'+' as i8)), 19),
+                (_mm_cmpeq_epi8(chars, _mm_set1_epi8(b'/' as i8)), 16),
+            ];
+            let mut valid = _mm_setzero_si128();
+            let mut offsets = _mm_setzero_si128();
+            for &(mask, offset) in ranges.iter() {
+                valid = _mm_or_si128(valid, mask);
+                offsets = _mm_or_si128(offsets, _mm_and_si128(mask, _mm_set1_epi8(offset)));
+            }
+            if _mm_movemask_epi8(valid) != 0xffff {
+                return None;
+            }
+            let indices = _mm_add_epi8(chars, offsets);
+
+            // each 4 indices of 6 bits to 3 bytes, most significant first
+            let pairs = _mm_maddubs_epi16(indices, _mm_set1_epi32(0x0140_0140));
+            let lanes = _mm_madd_epi16(pairs, _mm_set1_epi32(0x0001_1000));
+            Some(_mm_shuffle_epi8(
+                lanes,
+                _mm_setr_epi8(2, 1, 0, 6, 5, 4, 10, 9, 8, 14, 13, 12, -1, -1, -1, -1),
+            ))
+        }
+
+        #[target_feature(enable = "ssse3")]
+        pub unsafe fn encode(input: &[u8], output: &mut Vec<u8>) -> usize {
+            output.reserve(input.len() / 3 * 4);
+            let mut chars = [0u8; 16];
+            let mut done = 0;
+            // 16 bytes are loaded for each 12 encoded
+            while input.len() - done >= 16 {
+                let block = _mm_loadu_si128(input[done..].as_ptr() as *const __m128i);
+                _mm_storeu_si128(chars.as_mut_ptr() as *mut __m128i, encode_block(block));
+                output.extend_from_slice(&chars);
+                done += 12;
+            }
+            done
+        }
+
+        #[target_feature(enable = "ssse3")]
+        pub unsafe fn decode(input: &[u8], output: &mut Vec<u8>) -> usize {
+            output.reserve(input.len() / 4 * 3);
+            let mut bytes = [0u8; 16];
+            let mut done = 0;
+            while input.len() - done >= 16 {
+                let chars = _mm_loadu_si128(input[done..].as_ptr() as *const __m128i);
+                match decode_block(chars) {
+                    Some(block) => {
+                        _mm_storeu_si128(bytes.as_mut_ptr() as *mut __m128i, block);
+                        output.extend_from_slice(&bytes[..12]);
+                        done += 16;
+                    }
+                    None => break,
+                }
+            }
+            done
+        }
+    }
+}
+
+/// Fills `buf` from `input`, coming up short only at the end of the input,
+/// and returns how much was read.
+fn read_full<R: Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Writes encoded text in lines of `width` characters, or in one unbroken
+/// line for a width of 0, carrying the column over from one write to the
+/// next.
+struct LineWrapper<W: Write> {
+    output: W,
+    width: usize,
+    column: usize,
+}
+
+impl<W: Write> LineWrapper<W> {
+    fn new(output: W, width: usize) -> Self {
+        LineWrapper {
+            output,
+            width,
+            column: 0,
+        }
+    }
+
+    fn write(&mut self, mut text: &[u8]) -> io::Result<()> {
+        if self.width == 0 {
+            return self.output.write_all(text);
+        }
+        while !text.is_empty() {
+            let len = min(self.width - self.column, text.len());
+            self.output.write_all(&text[..len])?;
+            self.column += len;
+            text = &text[len..];
+            if self.column == self.width {
+                self.output.write_all(b"\n")?;
+                self.column = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ends the last line, if it isn't ended already.
+    fn finish(mut self) -> io::Result<()> {
+        if self.column > 0 {
+            self.output.write_all(b"\n")?;
+        }
+        self.output.flush()
+    }
 }
 
 pub struct Data<R: Read> {
@@ -52,7 +424,6 @@ pub struct Data<R: Read> {
     ignore_garbage: bool,
     input: R,
     format: Format,
-    alphabet: &'static [u8],
 }
 
 impl<R: Read> Data<R> {
@@ -62,10 +433,6 @@ impl<R: Read> Data<R> {
             ignore_garbage: false,
             input,
             format,
-            alphabet: match format {
-                Base32 => b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567=",
-                Base64 => b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789=+/",
-            },
         }
     }
 
@@ -79,43 +446,104 @@ impl<R: Read> Data<R> {
         self
     }
 
-    pub fn decode(&mut self) -> DecodeResult {
-        let mut buf = vec![];
-        self.input.read_to_end(&mut buf)?;
+    /// Decodes the input to `output`, a chunk at a time.  What was decoded
+    /// before an invalid block is written out before the error is returned,
+    /// which is positioned at the offending byte of the input.
+    pub fn decode_to<W: Write>(&mut self, output: &mut W) -> Result<(), EncodingError> {
+        let mut keep = [!self.ignore_garbage; 256];
         if self.ignore_garbage {
-            buf.retain(|c| self.alphabet.contains(c));
+            for &c in self.format.alphabet() {
+                keep[c as usize] = true;
+            }
         } else {
-            buf.retain(|&c| c != b'\r' && c != b'\n');
-        };
-        decode(self.format, &buf)
-    }
+            keep[b'\r' as usize] = false;
+            keep[b'\n' as usize] = false;
+        }
 
-    pub fn encode(&mut self) -> String {
-        let mut buf: Vec<u8> = vec![];
-        self.input.read_to_end(&mut buf).unwrap();
-        encode(self.format, buf.as_slice())
-    }
-}
+        let block_len = self.format.block_len();
+        let mut chunk = vec![0; DECODE_CHUNK];
+        let mut chunk_start = 0;
+        let mut pending = Vec::with_capacity(DECODE_CHUNK + block_len);
+        // where in the input the bytes left in `pending` from earlier chunks are
+        let mut carried = Vec::with_capacity(block_len);
+        let mut decoded = Vec::with_capacity(DECODE_CHUNK);
+        loop {
+            let len = read_full(&mut self.input, &mut chunk)?;
+            let eof = len < chunk.len();
+            let read = &chunk[..len];
+            pending.extend(read.iter().filter(|&&c| keep[c as usize]));
 
-// NOTE: this will likely be phased out at some point
-pub fn wrap_print<R: Read>(data: &Data<R>, res: String) {
-    let stdout = io::stdout();
-    wrap_write(stdout.lock(), data.line_wrap, res).unwrap();
-}
+            // where in the input the `n`th byte of `pending` is
+            let offset = |n: usize| {
+                if n < carried.len() {
+                    carried[n]
+                } else if n < pending.len() {
+                    let (i, _) = read
+                        .iter()
+                        .enumerate()
+                        .rev()
+                        .filter(|&(_, &c)| keep[c as usize])
+                        .nth(pending.len() - 1 - n)
+                        .unwrap();
+                    chunk_start + i
+                } else {
+                    chunk_start + len
+                }
+            };
 
-pub fn wrap_write<W: Write>(mut writer: W, line_wrap: usize, res: String) -> io::Result<()> {
-    use std::cmp::min;
+            // a partial block waits for the rest of it, unless there is no more,
+            // and is decoded last so that it's the last error reported
+            let whole = pending.len() - pending.len() % block_len;
+            let end = if eof { pending.len() } else { whole };
+            decoded.clear();
+            let mut result = decode_append(self.format, &pending[..whole], &mut decoded);
+            if result.is_ok() && end > whole {
+                result =
+                    decode_append(self.format, &pending[whole..end], &mut decoded).map_err(|e| {
+                        DecodeError {
+                            position: whole + e.position,
+                            ..e
+                        }
+                    });
+            }
+            output.write_all(&decoded)?;
+            if let Err(e) = result {
+                output.flush()?;
+                return Err(DecodeError {
+                    position: offset(e.position),
+                    ..e
+                }
+                .into());
+            }
 
-    if line_wrap == 0 {
-        return write!(writer, "{}", res);
+            if eof {
+                output.flush()?;
+                return Ok(());
+            }
+            carried = (end..pending.len()).map(offset).collect();
+            pending.drain(..end);
+            chunk_start += len;
+        }
     }
 
-    let mut start = 0;
-    while start < res.len() {
-        let end = min(start + line_wrap, res.len());
-        writeln!(writer, "{}", &res[start..end])?;
-        start = end;
+    /// Encodes the input to `output`, a chunk at a time, wrapping lines
+    /// after `line_wrap` characters.
+    pub fn encode_to<W: Write>(&mut self, output: &mut W) -> Result<(), EncodingError> {
+        let mut chunk = vec![0; ENCODE_CHUNK];
+        let mut encoded = Vec::with_capacity(ENCODE_CHUNK / 3 * 4);
+        let mut wrapper = LineWrapper::new(output, self.line_wrap);
+        loop {
+            let len = read_full(&mut self.input, &mut chunk)?;
+            encoded.clear();
+            let result = encode_append(self.format, &chunk[..len], &mut encoded);
+            wrapper.write(&encoded)?;
+            if let Err(e) = result {
+                wrapper.finish()?;
+                return Err(e);
+            }
+            if len < chunk.len() {
+                return Ok(wrapper.finish()?);
+            }
+        }
     }
-
-    Ok(())
 }
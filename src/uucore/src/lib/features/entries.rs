@@ -41,9 +41,11 @@ use libc::{getgrgid, getgrnam, getgroups, getpwnam, getpwuid, group, passwd};
 
 use std::borrow::Cow;
 use std::ffi::{CStr, CString};
+use std::fs::Metadata;
 use std::io::Error as IOError;
 use std::io::ErrorKind;
 use std::io::Result as IOResult;
+use std::os::unix::fs::MetadataExt;
 use std::ptr;
 
 extern "C" {
@@ -145,9 +147,14 @@ impl Passwd {
     }
 
     pub fn belongs_to(&self) -> Vec<gid_t> {
+        self.belongs_to_with(self.inner.pw_gid)
+    }
+
+    /// The groups the user is in, taking `gid` rather than their login group
+    /// as the primary one.
+    pub fn belongs_to_with(&self, gid: gid_t) -> Vec<gid_t> {
         let mut ngroups: c_int = 8;
         let mut groups = Vec::with_capacity(ngroups as usize);
-        let gid = self.inner.pw_gid;
         let name = self.inner.pw_name;
         unsafe {
             if getgrouplist(name, gid, groups.as_mut_ptr(), &mut ngroups) == -1 {
@@ -259,6 +266,18 @@ pub fn gid2grp(id: gid_t) -> IOResult<String> {
     Group::locate(id).map(|p| p.name().into_owned())
 }
 
+/// The name of the owner of the file `meta` describes, if it can be looked up.
+#[inline]
+pub fn user_name(meta: &Metadata) -> Option<String> {
+    uid2usr(meta.uid()).ok()
+}
+
+/// The name of the group of the file `meta` describes, if it can be looked up.
+#[inline]
+pub fn group_name(meta: &Metadata) -> Option<String> {
+    gid2grp(meta.gid()).ok()
+}
+
 #[inline]
 pub fn usr2uid(name: &str) -> IOResult<uid_t> {
     Passwd::locate(name).map(|p| p.uid())
@@ -0,0 +1,196 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Shell wildcard matching, as done by `fnmatch(3)` without flags.
+//!
+//! `*` matches any run of characters and `?` any one character, both
+//! including `/` and a leading `.`. `[...]` matches one character from a
+//! set, which may hold ranges such as `a-z`, classes such as `[:digit:]`,
+//! and is negated by a leading `!` or `^`. A backslash quotes the character
+//! after it.
+
+// spell-checker:ignore (vars) alnum cntrl xdigit
+
+/// Whether `string` as a whole matches the wildcard `pattern`.
+pub fn fnmatch(pattern: &str, string: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let string: Vec<char> = string.chars().collect();
+    match_chars(&pattern, &string)
+}
+
+fn match_chars(mut pattern: &[char], mut string: &[char]) -> bool {
+    // where to resume after a mismatch: the pattern after the last `*`, and
+    // the string from the first character that `*` hasn't taken yet
+    let mut resume: Option<(&[char], &[char])> = None;
+    loop {
+        match pattern.first() {
+            Some('*') => {
+                while pattern.first() == Some(&'*') {
+                    pattern = &pattern[1..];
+                }
+                if pattern.is_empty() {
+                    return true;
+                }
+                resume = Some((pattern, string));
+                continue;
+            }
+            Some(&p) if !string.is_empty() => {
+                let c = string[0];
+                let len = match p {
+                    '?' => Some(1),
+                    '[' => match match_bracket(&pattern[1..], c) {
+                        Some((true, len)) => Some(len + 1),
+                        Some((false, _)) => None,
+                        // an unclosed bracket is an ordinary character
+                        None if c == '[' => Some(1),
+                        None => None,
+                    },
+                    '\\' if pattern.len() > 1 => {
+                        if pattern[1] == c {
+                            Some(2)
+                        } else {
+                            None
+                        }
+                    }
+                    p if p == c => Some(1),
+                    _ => None,
+                };
+                if let Some(len) = len {
+                    pattern = &pattern[len..];
+                    string = &string[1..];
+                    continue;
+                }
+            }
+            None if string.is_empty() => return true,
+            _ => {}
+        }
+        // on a mismatch, let the last `*` take one more character
+        match resume {
+            Some((after_star, taken)) if !taken.is_empty() => {
+                pattern = after_star;
+                string = &taken[1..];
+                resume = Some((after_star, string));
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Match `c` against the bracket expression at the start of `pattern`, just
+/// after its `[`. Gives whether it matched and the length of the expression
+/// up to and including its `]`, or `None` if it isn't closed.
+fn match_bracket(pattern: &[char], c: char) -> Option<(bool, usize)> {
+    let negated = match pattern.first() {
+        Some('!') | Some('^') => true,
+        _ => false,
+    };
+    let mut i = negated as usize;
+    let start = i;
+    let mut matched = false;
+    loop {
+        let p = *pattern.get(i)?;
+        // a `]` right at the start is part of the set
+        if p == ']' && i > start {
+            return Some((matched != negated, i + 1));
+        }
+        if p == '[' && pattern.get(i + 1) == Some(&':') {
+            let name_end = (i + 2..pattern.len().saturating_sub(1))
+                .find(|&j| pattern[j] == ':' && pattern[j + 1] == ']');
+            if let Some(end) = name_end {
+                let name: String = pattern[i + 2..end].iter().collect();
+                matched |= is_in_class(&name, c);
+                i = end + 2;
+                continue;
+            }
+        }
+        let (low, next) = quoted(pattern, i);
+        i = next;
+        if pattern.get(i) == Some(&'-') && pattern.get(i + 1).map_or(false, |&p| p != ']') {
+            let (high, next) = quoted(pattern, i + 1);
+            i = next;
+            matched |= low <= c && c <= high;
+        } else {
+            matched |= low == c;
+        }
+    }
+}
+
+/// The character at `i` in a bracket expression, following a backslash, and
+/// the index after it.
+fn quoted(pattern: &[char], i: usize) -> (char, usize) {
+    if pattern[i] == '\\' && i + 1 < pattern.len() {
+        (pattern[i + 1], i + 2)
+    } else {
+        (pattern[i], i + 1)
+    }
+}
+
+fn is_in_class(name: &str, c: char) -> bool {
+    match name {
+        "alnum" => c.is_alphanumeric(),
+        "alpha" => c.is_alphabetic(),
+        "blank" => c == ' ' || c == '\t',
+        "cntrl" => c.is_control(),
+        "digit" => c.is_ascii_digit(),
+        "graph" => !c.is_control() && !c.is_whitespace(),
+        "lower" => c.is_lowercase(),
+        "print" => !c.is_control(),
+        "punct" => c.is_ascii_punctuation(),
+        "space" => c.is_whitespace(),
+        "upper" => c.is_uppercase(),
+        "xdigit" => c.is_ascii_hexdigit(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal() {
+        assert!(fnmatch("", ""));
+        assert!(fnmatch("abc", "abc"));
+        assert!(!fnmatch("abc", "abd"));
+        assert!(!fnmatch("abc", "ab"));
+        assert!(!fnmatch("ab", "abc"));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(fnmatch("*", ""));
+        assert!(fnmatch("*", ".hidden"));
+        assert!(fnmatch("*.txt", "a/b.txt"));
+        assert!(!fnmatch("*.txt", "a.txt.gz"));
+        assert!(fnmatch("a*b*c", "aXbYbZc"));
+        assert!(!fnmatch("a*b*c", "aXbYbZ"));
+        assert!(fnmatch("?", "/"));
+        assert!(!fnmatch("?", ""));
+        assert!(fnmatch("a??d", "abcd"));
+    }
+
+    #[test]
+    fn test_brackets() {
+        assert!(fnmatch("[abc]", "b"));
+        assert!(!fnmatch("[abc]", "d"));
+        assert!(fnmatch("[!abc]", "d"));
+        assert!(fnmatch("[^abc]", "d"));
+        assert!(fnmatch("[a-c]x", "bx"));
+        assert!(fnmatch("[]]", "]"));
+        assert!(fnmatch("[a-]", "-"));
+        assert!(fnmatch("[[:digit:]]*", "1st"));
+        assert!(!fnmatch("[[:upper:]]", "a"));
+        assert!(fnmatch("[", "["));
+        assert!(fnmatch("a[b", "a[b"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert!(fnmatch("\\*", "*"));
+        assert!(!fnmatch("\\*", "a"));
+        assert!(fnmatch("[\\]]", "]"));
+        assert!(fnmatch("a\\", "a\\"));
+    }
+}
@@ -22,7 +22,37 @@ use std::io::{Error, ErrorKind};
 use std::os::unix::fs::MetadataExt;
 use std::path::{Component, Path, PathBuf};
 
-#[cfg(unix)]
+// Windows has no mode bits of its own; these are the Unix ones, for the
+// modes that `winfs` makes up
+#[cfg(windows)]
+#[allow(non_camel_case_types)]
+type mode_t = u32;
+#[cfg(windows)]
+const S_IRUSR: mode_t = 0o400;
+#[cfg(windows)]
+const S_IWUSR: mode_t = 0o200;
+#[cfg(windows)]
+const S_IXUSR: mode_t = 0o100;
+#[cfg(windows)]
+const S_IRGRP: mode_t = 0o040;
+#[cfg(windows)]
+const S_IWGRP: mode_t = 0o020;
+#[cfg(windows)]
+const S_IXGRP: mode_t = 0o010;
+#[cfg(windows)]
+const S_IROTH: mode_t = 0o004;
+#[cfg(windows)]
+const S_IWOTH: mode_t = 0o002;
+#[cfg(windows)]
+const S_IXOTH: mode_t = 0o001;
+#[cfg(windows)]
+const S_ISUID: mode_t = 0o4000;
+#[cfg(windows)]
+const S_ISGID: mode_t = 0o2000;
+#[cfg(windows)]
+const S_ISVTX: mode_t = 0o1000;
+
+#[cfg(any(unix, windows))]
 macro_rules! has {
     ($mode:expr, $perm:expr) => {
         $mode & ($perm as u32) != 0
@@ -54,114 +84,135 @@ pub fn resolve_relative_path(path: &Path) -> Cow<Path> {
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum CanonicalizeMode {
+    /// Only remove `.` and `..` components, without looking at the file system
     None,
+    /// Follow every symlink; all but the last component must exist
     Normal,
+    /// Follow every symlink; every component must exist
     Existing,
+    /// Follow every symlink; no component needs to exist
     Missing,
 }
 
-fn resolve<P: AsRef<Path>>(original: P) -> IOResult<PathBuf> {
-    const MAX_LINKS_FOLLOWED: u32 = 255;
-    let mut followed = 0;
-    let mut result = original.as_ref().to_path_buf();
-    loop {
-        if followed == MAX_LINKS_FOLLOWED {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                "maximum links followed",
-            ));
-        }
-
-        match fs::symlink_metadata(&result) {
-            Err(e) => return Err(e),
-            Ok(ref m) if !m.file_type().is_symlink() => break,
-            Ok(..) => {
-                followed += 1;
-                match fs::read_link(&result) {
-                    Ok(path) => {
-                        result.pop();
-                        result.push(path);
-                    }
-                    Err(e) => {
-                        return Err(e);
-                    }
-                }
-            }
-        }
-    }
-    Ok(result)
+/// Queue the components of `path`, so that its first component is popped next
+fn push_parts(parts: &mut Vec<PathBuf>, path: &Path) {
+    parts.extend(
+        path.components()
+            .rev()
+            .map(|c| PathBuf::from(c.as_os_str())),
+    );
 }
 
-pub fn canonicalize<P: AsRef<Path>>(original: P, can_mode: CanonicalizeMode) -> IOResult<PathBuf> {
-    // Create an absolute path
-    let original = original.as_ref();
-    let original = if original.is_absolute() {
+/// Create an absolute path, relative to the (canonical) working directory
+fn make_absolute(original: &Path) -> PathBuf {
+    if original.is_absolute() {
         original.to_path_buf()
     } else {
         dunce::canonicalize(env::current_dir().unwrap())
             .unwrap()
             .join(original)
-    };
+    }
+}
 
+/// Return the canonical, absolute form of a path.
+///
+/// Unlike `std::fs::canonicalize`, symlinks are resolved one component at a
+/// time, so that a `..` following a symlink applies to the link's target,
+/// and `can_mode` decides which components are allowed to be missing.
+pub fn canonicalize<P: AsRef<Path>>(original: P, can_mode: CanonicalizeMode) -> IOResult<PathBuf> {
+    const MAX_LINKS_FOLLOWED: u32 = 255;
+    let mut followed = 0;
+
+    let original = make_absolute(original.as_ref());
+    // A trailing slash or "/.", lost in the components, asks for a directory
+    let wants_dir = {
+        let name = original.to_string_lossy();
+        let name = if name.ends_with('.') {
+            &name[..name.len() - 1]
+        } else {
+            &name[..]
+        };
+        name.ends_with(std::path::is_separator)
+    };
     let mut result = PathBuf::new();
-    let mut parts = vec![];
-
-    // Split path by directory separator; add prefix (Windows-only) and root
-    // directory to final path buffer; add remaining parts to temporary
-    // vector for canonicalization.
-    for part in original.components() {
-        match part {
-            Component::Prefix(_) | Component::RootDir => {
-                result.push(part.as_os_str());
-            }
-            Component::CurDir => (),
-            Component::ParentDir => {
-                parts.pop();
-            }
-            Component::Normal(_) => {
-                parts.push(part.as_os_str());
-            }
-        }
-    }
 
-    // Resolve the symlinks where possible
-    if !parts.is_empty() {
-        for part in parts[..parts.len() - 1].iter() {
-            result.push(part);
+    // The components still to be looked at, the next one last
+    let mut parts: Vec<PathBuf> = vec![];
+    push_parts(&mut parts, &original);
 
-            if can_mode == CanonicalizeMode::None {
+    while let Some(part) = parts.pop() {
+        match part.components().next() {
+            Some(Component::Prefix(_)) | Some(Component::RootDir) => {
+                // Pushing a root replaces the path built so far, as an
+                // absolute link target should
+                result.push(&part);
                 continue;
             }
-
-            match resolve(&result) {
-                Err(e) => match can_mode {
-                    CanonicalizeMode::Missing => continue,
-                    _ => return Err(e),
-                },
-                Ok(path) => {
-                    result.pop();
-                    result.push(path);
-                }
+            Some(Component::CurDir) | None => continue,
+            Some(Component::ParentDir) => {
+                result.pop();
+                continue;
             }
+            Some(Component::Normal(_)) => result.push(&part),
         }
 
-        result.push(parts.last().unwrap());
+        if can_mode == CanonicalizeMode::None {
+            continue;
+        }
 
-        match resolve(&result) {
+        let is_last = parts.is_empty();
+        match fs::symlink_metadata(&result) {
             Err(e) => {
-                if can_mode == CanonicalizeMode::Existing {
+                let missing_ok = can_mode == CanonicalizeMode::Missing
+                    || (can_mode == CanonicalizeMode::Normal
+                        && is_last
+                        && e.kind() == ErrorKind::NotFound);
+                if !missing_ok {
                     return Err(e);
                 }
             }
-            Ok(path) => {
+            Ok(ref m) if m.file_type().is_symlink() => {
+                followed += 1;
+                if followed > MAX_LINKS_FOLLOWED {
+                    if can_mode == CanonicalizeMode::Missing {
+                        continue;
+                    }
+                    return Err(too_many_links());
+                }
+                let target = fs::read_link(&result)?;
                 result.pop();
-                result.push(path);
+                push_parts(&mut parts, &target);
+            }
+            Ok(ref m) => {
+                if (!is_last || wants_dir) && !m.is_dir() && can_mode != CanonicalizeMode::Missing {
+                    return Err(not_a_directory());
+                }
             }
         }
     }
     Ok(result)
 }
 
+#[cfg(unix)]
+fn not_a_directory() -> Error {
+    Error::from_raw_os_error(libc::ENOTDIR)
+}
+
+#[cfg(not(unix))]
+fn not_a_directory() -> Error {
+    Error::new(ErrorKind::Other, "Not a directory")
+}
+
+#[cfg(unix)]
+fn too_many_links() -> Error {
+    Error::from_raw_os_error(libc::ELOOP)
+}
+
+#[cfg(not(unix))]
+fn too_many_links() -> Error {
+    Error::new(ErrorKind::InvalidInput, "maximum links followed")
+}
+
 #[cfg(unix)]
 pub fn is_stdin_interactive() -> bool {
     unsafe { libc::isatty(libc::STDIN_FILENO) == 1 }
@@ -219,7 +270,7 @@ pub fn display_permissions(metadata: &fs::Metadata) -> String {
     display_permissions_unix(mode as u32)
 }
 
-#[cfg(unix)]
+#[cfg(any(unix, windows))]
 pub fn display_permissions_unix(mode: u32) -> String {
     let mut result = String::with_capacity(9);
     result.push(if has!(mode, S_IRUSR) { 'r' } else { '-' });
@@ -0,0 +1,306 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Scaling of sizes for output, as selected by `--block-size`, `-h` and `--si`.
+//!
+//! Sizes are always rounded up, so that a nonempty file never shows as using
+//! no space, as GNU does.
+
+// spell-checker:ignore (vars) POSIXLY
+
+use std::env;
+
+const UNITS: &str = "KMGTPEZY";
+
+/// How sizes are printed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockSize {
+    /// As a number of blocks of this many bytes.
+    Fixed(u64),
+    /// As a number of units of this many bytes followed by the unit, when
+    /// just a unit such as `K`, `kB` or `KiB` was given. `iec` is whether it
+    /// was spelled with `iB`.
+    Unit { size: u64, iec: bool },
+    /// With a unit suffix, in powers of this base (1024, or 1000 for `--si`).
+    Human(u64),
+}
+
+/// The ways a block size argument can be wrong.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockSizeError {
+    Invalid,
+    InvalidSuffix,
+    TooLarge,
+}
+
+impl BlockSizeError {
+    /// The diagnostic for `arg` given to `option`, worded like GNU's.
+    pub fn message(self, option: &str, arg: &str) -> String {
+        match self {
+            BlockSizeError::Invalid => format!("invalid {} argument '{}'", option, arg),
+            BlockSizeError::InvalidSuffix => {
+                format!("invalid suffix in {} argument '{}'", option, arg)
+            }
+            BlockSizeError::TooLarge => format!("{} argument '{}' too large", option, arg),
+        }
+    }
+}
+
+impl BlockSize {
+    /// Parse a block size such as `512`, `1K`, `4KiB`, `1MB` or `M`, or one of
+    /// the words `human-readable` and `si`. A leading `'` (digit grouping) is
+    /// accepted and ignored.
+    pub fn parse(s: &str) -> Result<BlockSize, BlockSizeError> {
+        match s {
+            "human-readable" => return Ok(BlockSize::Human(1024)),
+            "si" => return Ok(BlockSize::Human(1000)),
+            _ => {}
+        }
+        let s = s.trim_start_matches('\'');
+        let digits = s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (number, suffix) = s.split_at(digits);
+        let unit_only = number.is_empty();
+        let number = if unit_only {
+            if suffix.is_empty() {
+                return Err(BlockSizeError::Invalid);
+            }
+            1
+        } else {
+            number
+                .parse::<u64>()
+                .map_err(|_| BlockSizeError::TooLarge)?
+        };
+
+        let mut chars = suffix.chars();
+        let exponent = match chars.next() {
+            None => 0,
+            Some(unit) => match UNITS.find(unit.to_ascii_uppercase()) {
+                // 'z' and 'y' aren't units
+                Some(i) if unit.is_ascii_uppercase() || i < 6 => i as u32 + 1,
+                _ => return Err(BlockSizeError::InvalidSuffix),
+            },
+        };
+        let iec = chars.as_str() == "iB";
+        let base: u64 = match chars.as_str() {
+            "" | "iB" => 1024,
+            "B" => 1000,
+            _ => return Err(BlockSizeError::InvalidSuffix),
+        };
+        if exponent == 0 && base == 1000 {
+            // a bare 'B' isn't a unit
+            return Err(BlockSizeError::InvalidSuffix);
+        }
+
+        match base
+            .checked_pow(exponent)
+            .and_then(|unit| unit.checked_mul(number))
+        {
+            Some(0) => Err(BlockSizeError::Invalid),
+            Some(size) if unit_only => Ok(BlockSize::Unit { size, iec }),
+            Some(size) => Ok(BlockSize::Fixed(size)),
+            None => Err(BlockSizeError::TooLarge),
+        }
+    }
+
+    /// The block size given by the first of the environment variables `vars`
+    /// that holds a valid one, else 512 bytes if `POSIXLY_CORRECT` is set and
+    /// 1024 otherwise.
+    pub fn from_env(vars: &[&str]) -> BlockSize {
+        vars.iter()
+            .filter_map(|var| env::var(var).ok())
+            .filter_map(|value| BlockSize::parse(&value).ok())
+            .next()
+            .unwrap_or_else(|| {
+                if env::var("POSIXLY_CORRECT").is_ok() {
+                    BlockSize::Fixed(512)
+                } else {
+                    BlockSize::Fixed(1024)
+                }
+            })
+    }
+
+    /// The size of a block, or `None` for human-readable sizes.
+    pub fn bytes(self) -> Option<u64> {
+        match self {
+            BlockSize::Fixed(size) | BlockSize::Unit { size, .. } => Some(size),
+            BlockSize::Human(_) => None,
+        }
+    }
+
+    /// Format a size of `bytes`.
+    pub fn format(self, bytes: u64) -> String {
+        let blocks = |size: u64| bytes / size + (bytes % size != 0) as u64;
+        match self {
+            BlockSize::Fixed(size) => blocks(size).to_string(),
+            BlockSize::Unit { size, iec } => {
+                let name = BlockSize::Fixed(size).name();
+                // the name of a unit is "1K", "1kB" and so on
+                let unit = &name[1..];
+                if iec {
+                    format!("{}{}iB", blocks(size), unit)
+                } else {
+                    format!("{}{}", blocks(size), unit)
+                }
+            }
+            BlockSize::Human(base) => human_readable(bytes, base),
+        }
+    }
+
+    /// A short name for a fixed block size, e.g. `1K`, `1MB` or `512B`, as
+    /// used in column headers such as `1K-blocks`.
+    pub fn name(self) -> String {
+        let size = match self.bytes() {
+            Some(size) => size,
+            None => return String::new(),
+        };
+        let mut exponent = 0;
+        let mut value = size;
+        while value % 1024 == 0 {
+            value /= 1024;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            return format!("{}{}", value, &UNITS[exponent - 1..exponent]);
+        }
+        // otherwise in powers of 1000, rounding up
+        let mut unit = 1;
+        while exponent < UNITS.len() && size / unit >= 1000 {
+            unit *= 1000;
+            exponent += 1;
+        }
+        let value = size / unit + (size % unit != 0) as u64;
+        match exponent {
+            0 => format!("{}B", value),
+            1 => format!("{}kB", value),
+            _ => format!("{}{}B", value, &UNITS[exponent - 1..exponent]),
+        }
+    }
+}
+
+/// Format `bytes` with a unit suffix in powers of `base` (1024 or 1000), e.g.
+/// `4.0K` or `252G`. Values below 10 units keep one decimal.
+pub fn human_readable(bytes: u64, base: u64) -> String {
+    let bytes = u128::from(bytes);
+    let base = u128::from(base);
+    if bytes < base {
+        return bytes.to_string();
+    }
+    let mut exponent = 0;
+    let mut unit = 1;
+    while exponent < UNITS.len() && bytes >= unit * base {
+        unit *= base;
+        exponent += 1;
+    }
+    let ceil_div = |n: u128, d: u128| n / d + (n % d != 0) as u128;
+
+    let tenths = ceil_div(bytes * 10, unit);
+    let (value, exponent) = if tenths < 100 {
+        (format!("{}.{}", tenths / 10, tenths % 10), exponent)
+    } else {
+        let whole = ceil_div(bytes, unit);
+        if whole >= base && exponent < UNITS.len() {
+            ("1.0".to_string(), exponent + 1)
+        } else {
+            (whole.to_string(), exponent)
+        }
+    };
+    let suffix = if base == 1000 && exponent == 1 {
+        "k"
+    } else {
+        &UNITS[exponent - 1..exponent]
+    };
+    format!("{}{}", value, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(BlockSize::parse("1"), Ok(BlockSize::Fixed(1)));
+        assert_eq!(BlockSize::parse("512"), Ok(BlockSize::Fixed(512)));
+        assert_eq!(BlockSize::parse("1K"), Ok(BlockSize::Fixed(1024)));
+        assert_eq!(BlockSize::parse("1k"), Ok(BlockSize::Fixed(1024)));
+        assert_eq!(BlockSize::parse("4KiB"), Ok(BlockSize::Fixed(4096)));
+        assert_eq!(BlockSize::parse("1KB"), Ok(BlockSize::Fixed(1000)));
+        assert_eq!(
+            BlockSize::parse("M"),
+            Ok(BlockSize::Unit {
+                size: 1 << 20,
+                iec: false
+            })
+        );
+        assert_eq!(
+            BlockSize::parse("KiB"),
+            Ok(BlockSize::Unit {
+                size: 1024,
+                iec: true
+            })
+        );
+        assert_eq!(BlockSize::parse("2MB"), Ok(BlockSize::Fixed(2_000_000)));
+        assert_eq!(BlockSize::parse("'1"), Ok(BlockSize::Fixed(1)));
+        assert_eq!(BlockSize::parse("si"), Ok(BlockSize::Human(1000)));
+        assert_eq!(
+            BlockSize::parse("human-readable"),
+            Ok(BlockSize::Human(1024))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(BlockSize::parse(""), Err(BlockSizeError::Invalid));
+        assert_eq!(BlockSize::parse("0"), Err(BlockSizeError::Invalid));
+        assert_eq!(BlockSize::parse("x"), Err(BlockSizeError::InvalidSuffix));
+        assert_eq!(BlockSize::parse("1Q"), Err(BlockSizeError::InvalidSuffix));
+        assert_eq!(BlockSize::parse("1B"), Err(BlockSizeError::InvalidSuffix));
+        assert_eq!(BlockSize::parse("1KX"), Err(BlockSizeError::InvalidSuffix));
+        assert_eq!(BlockSize::parse("1Z"), Err(BlockSizeError::TooLarge));
+        assert_eq!(BlockSize::parse("1Y"), Err(BlockSizeError::TooLarge));
+        assert_eq!(
+            BlockSize::parse("99999999999999999999"),
+            Err(BlockSizeError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn test_name() {
+        let name = |size| BlockSize::Fixed(size).name();
+        assert_eq!(name(1), "1B");
+        assert_eq!(name(512), "512B");
+        assert_eq!(name(1000), "1kB");
+        assert_eq!(name(1024), "1K");
+        assert_eq!(name(10240), "10K");
+        assert_eq!(name(1 << 20), "1M");
+        assert_eq!(name(3000), "3kB");
+        assert_eq!(name(12345), "13kB");
+        assert_eq!(name(2_000_000), "2MB");
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(BlockSize::Fixed(1024).format(0), "0");
+        assert_eq!(BlockSize::Fixed(1024).format(1), "1");
+        assert_eq!(BlockSize::Fixed(1024).format(2048), "2");
+        assert_eq!(BlockSize::Fixed(1024).format(2049), "3");
+        assert_eq!(BlockSize::parse("K").unwrap().format(1500), "2K");
+        assert_eq!(BlockSize::parse("kB").unwrap().format(1500), "2kB");
+        assert_eq!(BlockSize::parse("KiB").unwrap().format(1500), "2KiB");
+        assert_eq!(BlockSize::parse("MB").unwrap().format(1500), "1MB");
+    }
+
+    #[test]
+    fn test_human_readable() {
+        assert_eq!(human_readable(0, 1024), "0");
+        assert_eq!(human_readable(1023, 1024), "1023");
+        assert_eq!(human_readable(1024, 1024), "1.0K");
+        assert_eq!(human_readable(1025, 1024), "1.1K");
+        assert_eq!(human_readable(10 * 1024 - 1, 1024), "10K");
+        assert_eq!(human_readable(1024 * 1024 - 1, 1024), "1.0M");
+        assert_eq!(human_readable(270_553_174_016, 1024), "252G");
+        assert_eq!(human_readable(4100, 1000), "4.1k");
+        assert_eq!(human_readable(270_553_174_016, 1000), "271G");
+    }
+}
@@ -0,0 +1,192 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Translation of messages, and the locale-dependent parts of output.
+//!
+//! Messages are looked up by their English text, gettext style, in the
+//! catalog of the locale set for their category: `LC_ALL`, then the
+//! category's own variable, then `LANG`. Catalogs are `.po` files found at
+//! `$UUTILS_LOCALEDIR/<locale>/uutils.po` (or in the directory given by
+//! `UUTILS_LOCALEDIR` at build time), tried for the full locale name
+//! ("de_DE"), then for the language alone ("de"). English is built in, and
+//! is what the "C" and "POSIX" locales, and any message missing from a
+//! catalog, get.
+//!
+//! Month and day names are messages like any other, with the abbreviations
+//! in the "abbreviated" context so that "May" can be translated twice.
+//!
+//! Catalogs, and collation by the locale, come with the `nls` feature.
+//! Without it everything is in English, and strings collate by their bytes,
+//! as they do in the "C" locale.
+
+// spell-checker:ignore (vars) msgctxt msgid msgstr localedir strcoll
+
+use std::borrow::Cow;
+use std::env;
+
+pub const DAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+pub const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// The context that abbreviated month and day names are translated in.
+const ABBREVIATED: &str = "abbreviated";
+
+/// What a piece of text is, which decides the locale it is translated for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Category {
+    /// diagnostics and `--help` text, after `LC_MESSAGES`
+    Messages,
+    /// month and day names and time formats, after `LC_TIME`
+    Time,
+    /// the order of strings, after `LC_COLLATE`
+    Collate,
+}
+
+impl Category {
+    fn variable(self) -> &'static str {
+        match self {
+            Category::Messages => "LC_MESSAGES",
+            Category::Time => "LC_TIME",
+            Category::Collate => "LC_COLLATE",
+        }
+    }
+}
+
+/// The locale set for `category`, or `None` for the "C" locale.
+pub fn locale(category: Category) -> Option<String> {
+    locale_from(category, |name| env::var(name).ok())
+}
+
+fn locale_from<F>(category: Category, var: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let name = ["LC_ALL", category.variable(), "LANG"]
+        .iter()
+        .filter_map(|name| var(name))
+        .find(|value| !value.is_empty())?;
+    if name == "C" || name == "POSIX" || name.starts_with("C.") {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+#[cfg(feature = "nls")]
+mod nls;
+
+#[cfg(feature = "nls")]
+use self::nls::lookup;
+#[cfg(feature = "nls")]
+pub use self::nls::{collate, Catalog};
+
+#[cfg(not(feature = "nls"))]
+fn lookup<'a>(_category: Category, _context: Option<&str>, msgid: &'a str) -> Cow<'a, str> {
+    Cow::Borrowed(msgid)
+}
+
+/// Compare `a` and `b` by their bytes, which is the order of the "C"
+/// locale.
+#[cfg(not(feature = "nls"))]
+pub fn collate(a: &str, b: &str) -> std::cmp::Ordering {
+    a.cmp(b)
+}
+
+/// Translate a diagnostic or a piece of `--help` text.
+pub fn tr(msgid: &str) -> Cow<'_, str> {
+    lookup(Category::Messages, None, msgid)
+}
+
+/// Translate `msgid` for the locale of `category`, like a time format
+/// for `Category::Time`.
+pub fn tr_in(category: Category, msgid: &str) -> Cow<'_, str> {
+    lookup(category, None, msgid)
+}
+
+/// The name of the month `month0` months after January.
+pub fn month_name(month0: usize, abbreviated: bool) -> Cow<'static, str> {
+    name(MONTHS[month0], abbreviated)
+}
+
+/// The name of the day `day` days after Sunday.
+pub fn day_name(day: usize, abbreviated: bool) -> Cow<'static, str> {
+    name(DAYS[day], abbreviated)
+}
+
+fn name(full: &'static str, abbreviated: bool) -> Cow<'static, str> {
+    if abbreviated {
+        lookup(Category::Time, Some(ABBREVIATED), &full[..3])
+    } else {
+        lookup(Category::Time, None, full)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_precedence() {
+        let vars = |set: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                set.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert_eq!(locale_from(Category::Time, vars(&[])), None);
+        assert_eq!(
+            locale_from(Category::Time, vars(&[("LANG", "de_DE.UTF-8")])),
+            Some("de_DE.UTF-8".to_owned())
+        );
+        assert_eq!(
+            locale_from(
+                Category::Time,
+                vars(&[
+                    ("LANG", "de_DE"),
+                    ("LC_TIME", "fr_FR"),
+                    ("LC_MESSAGES", "nl")
+                ])
+            ),
+            Some("fr_FR".to_owned())
+        );
+        assert_eq!(
+            locale_from(Category::Time, vars(&[("LC_ALL", ""), ("LANG", "de_DE")])),
+            Some("de_DE".to_owned())
+        );
+        assert_eq!(
+            locale_from(Category::Messages, vars(&[("LC_ALL", "C"), ("LANG", "de")])),
+            None
+        );
+        assert_eq!(
+            locale_from(Category::Messages, vars(&[("LC_ALL", "C.UTF-8")])),
+            None
+        );
+        assert_eq!(
+            locale_from(Category::Messages, vars(&[("LANG", "POSIX")])),
+            None
+        );
+    }
+}
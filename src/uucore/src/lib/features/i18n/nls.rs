@@ -0,0 +1,325 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Catalogs of translations, and collation by the locale.
+
+// spell-checker:ignore (vars) msgctxt msgid msgstr localedir strcoll
+
+use super::{locale, Category};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// The names a catalog for `locale` can be found under, most specific
+/// first: "de_DE.UTF-8@euro" gives "de_DE.UTF-8@euro", "de_DE" and "de".
+fn candidates(locale: &str) -> Vec<&str> {
+    let territory = locale.find(&['.', '@'][..]);
+    let language = locale.find(&['_', '.', '@'][..]);
+    let mut names = vec![locale];
+    for end in territory.iter().chain(language.iter()) {
+        let name = &locale[..*end];
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Translations of messages, from a `.po` file.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Read the `msgctxt`, `msgid` and `msgstr` entries of a `.po` file.
+    /// Untranslated and fuzzy entries, plural forms and anything else are
+    /// left out.
+    pub fn parse(po: &str) -> Catalog {
+        #[derive(Default)]
+        struct Entry {
+            context: Option<String>,
+            id: String,
+            translation: String,
+            fuzzy: bool,
+        }
+
+        #[derive(Clone, Copy, PartialEq)]
+        enum Field {
+            None,
+            Context,
+            Id,
+            Str,
+            /// `msgid_plural` and `msgstr[N]`, which aren't used
+            Plural,
+        }
+
+        let mut messages = HashMap::new();
+        let mut entry = Entry::default();
+        let mut field = Field::None;
+        for line in po.lines().map(str::trim).chain(Some("")) {
+            // an entry ends with its translation, or with its plural forms
+            if !line.starts_with('"') && (field == Field::Str || field == Field::Plural) {
+                let entry = std::mem::take(&mut entry);
+                let complete = field == Field::Str && !entry.translation.is_empty();
+                if complete && !entry.id.is_empty() && !entry.fuzzy {
+                    let key = key(entry.context.as_ref().map(|c| &c[..]), &entry.id);
+                    messages.insert(key, entry.translation);
+                }
+            }
+            let rest = if line.starts_with("msgctxt ") {
+                field = Field::Context;
+                &line[8..]
+            } else if line.starts_with("msgid ") {
+                field = Field::Id;
+                &line[6..]
+            } else if line.starts_with("msgstr ") {
+                field = Field::Str;
+                &line[7..]
+            } else if line.starts_with("msgid_plural ") || line.starts_with("msgstr[") {
+                field = Field::Plural;
+                continue;
+            } else if line.starts_with('"') {
+                line
+            } else {
+                if line.starts_with("#,") && line.contains("fuzzy") {
+                    entry.fuzzy = true;
+                }
+                field = Field::None;
+                continue;
+            };
+            let text = unquote(rest.trim());
+            match field {
+                Field::Context => entry
+                    .context
+                    .get_or_insert_with(String::new)
+                    .push_str(&text),
+                Field::Id => entry.id.push_str(&text),
+                Field::Str => entry.translation.push_str(&text),
+                Field::None | Field::Plural => {}
+            }
+        }
+        Catalog { messages }
+    }
+
+    /// The translation of `msgid` in `context`, if there is one.
+    pub fn get(&self, context: Option<&str>, msgid: &str) -> Option<&str> {
+        self.messages.get(&key(context, msgid)).map(|s| &s[..])
+    }
+}
+
+/// A message's key in a catalog: gettext separates the context with EOT.
+fn key(context: Option<&str>, msgid: &str) -> String {
+    match context {
+        Some(context) => format!("{}\u{4}{}", context, msgid),
+        None => msgid.to_owned(),
+    }
+}
+
+/// The contents of a quoted `.po` string, with its escapes expanded.
+fn unquote(s: &str) -> String {
+    let s = if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        &s[1..s.len() - 1]
+    } else {
+        s
+    };
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// The directories that catalogs are looked for in.
+fn locale_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env::var_os("UUTILS_LOCALEDIR")
+        .map(|dirs| env::split_paths(&dirs).collect())
+        .unwrap_or_default();
+    if let Some(dir) = option_env!("UUTILS_LOCALEDIR") {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs
+}
+
+/// The catalog for `locale`: the first `.po` file found for any of its
+/// names, or an empty one.
+fn load(locale: &str) -> Catalog {
+    for dir in locale_dirs() {
+        for name in candidates(locale) {
+            if let Ok(po) = fs::read_to_string(dir.join(name).join("uutils.po")) {
+                return Catalog::parse(&po);
+            }
+        }
+    }
+    Catalog::default()
+}
+
+thread_local! {
+    static CATALOGS: RefCell<HashMap<String, Rc<Catalog>>> = RefCell::new(HashMap::new());
+    static C_COLLATION: bool = locale(Category::Collate).is_none();
+}
+
+/// The catalog for `category`'s locale, loaded once.
+fn catalog(category: Category) -> Option<Rc<Catalog>> {
+    let locale = locale(category)?;
+    let catalog = CATALOGS.with(|catalogs| {
+        catalogs
+            .borrow_mut()
+            .entry(locale.clone())
+            .or_insert_with(|| Rc::new(load(&locale)))
+            .clone()
+    });
+    Some(catalog)
+}
+
+pub fn lookup<'a>(category: Category, context: Option<&str>, msgid: &'a str) -> Cow<'a, str> {
+    match catalog(category).and_then(|catalog| catalog.get(context, msgid).map(str::to_owned)) {
+        Some(translation) => Cow::Owned(translation),
+        None => Cow::Borrowed(msgid),
+    }
+}
+
+/// Compare `a` and `b` in the collation order of the locale, like
+/// `strcoll`. Strings that collate the same are ordered by their bytes,
+/// and so is everything in the "C" locale.
+pub fn collate(a: &str, b: &str) -> Ordering {
+    if a == b || C_COLLATION.with(|c| *c) {
+        return a.cmp(b);
+    }
+    platform::strcoll(a, b)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| a.cmp(b))
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::cell::RefCell;
+    use std::cmp::Ordering;
+    use std::ffi::CStr;
+    use std::sync::Once;
+
+    static SET_LOCALE: Once = Once::new();
+
+    thread_local! {
+        // the strings compared last, as C strings, so that a sort doesn't
+        // allocate for every comparison
+        static BUFFERS: RefCell<(Vec<u8>, Vec<u8>)> = RefCell::new((Vec::new(), Vec::new()));
+    }
+
+    pub fn strcoll(a: &str, b: &str) -> Option<Ordering> {
+        SET_LOCALE.call_once(|| unsafe {
+            libc::setlocale(libc::LC_COLLATE, b"\0".as_ptr() as *const libc::c_char);
+        });
+        BUFFERS.with(|buffers| {
+            let mut buffers = buffers.borrow_mut();
+            let (a_buffer, b_buffer) = &mut *buffers;
+            let (a, b) = (c_str(a, a_buffer)?, c_str(b, b_buffer)?);
+            let result = unsafe { libc::strcoll(a.as_ptr(), b.as_ptr()) };
+            Some(result.cmp(&0))
+        })
+    }
+
+    /// `s` as a C string in `buffer`, unless it has a NUL in it.
+    fn c_str<'a>(s: &str, buffer: &'a mut Vec<u8>) -> Option<&'a CStr> {
+        buffer.clear();
+        buffer.extend_from_slice(s.as_bytes());
+        buffer.push(0);
+        CStr::from_bytes_with_nul(buffer).ok()
+    }
+}
+
+#[cfg(not(unix))]
+mod platform {
+    use std::cmp::Ordering;
+
+    pub fn strcoll(_a: &str, _b: &str) -> Option<Ordering> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates() {
+        assert_eq!(
+            candidates("de_DE.UTF-8@euro"),
+            vec!["de_DE.UTF-8@euro", "de_DE", "de"]
+        );
+        assert_eq!(candidates("de_DE"), vec!["de_DE", "de"]);
+        assert_eq!(candidates("de"), vec!["de"]);
+        assert_eq!(candidates("sr@latin"), vec!["sr@latin", "sr"]);
+    }
+
+    #[test]
+    fn test_parse() {
+        let catalog = Catalog::parse(
+            r#"
+# a comment
+msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+msgid "May"
+msgstr "Mai"
+
+msgctxt "abbreviated"
+msgid "May"
+msgstr "Mai."
+
+msgid ""
+"missing "
+"operand"
+msgstr "Operand "
+"fehlt"
+
+msgid "say \"hi\"\n"
+msgstr "sag \"hallo\"\n"
+
+msgid "untranslated"
+msgstr ""
+
+#, fuzzy
+msgid "fuzzy"
+msgstr "unscharf"
+
+msgid "file"
+msgid_plural "files"
+msgstr[0] "Datei"
+msgstr[1] "Dateien"
+
+msgid "directory"
+msgstr "Verzeichnis"
+"#,
+        );
+        assert_eq!(catalog.get(None, "May"), Some("Mai"));
+        assert_eq!(catalog.get(Some("abbreviated"), "May"), Some("Mai."));
+        assert_eq!(catalog.get(None, "missing operand"), Some("Operand fehlt"));
+        assert_eq!(catalog.get(None, "say \"hi\"\n"), Some("sag \"hallo\"\n"));
+        assert_eq!(catalog.get(None, "untranslated"), None);
+        assert_eq!(catalog.get(None, "fuzzy"), None);
+        assert_eq!(catalog.get(None, "file"), None);
+        assert_eq!(catalog.get(None, "directory"), Some("Verzeichnis"));
+        assert_eq!(catalog.get(None, ""), None);
+        assert_eq!(catalog.get(None, "June"), None);
+    }
+}
@@ -0,0 +1,172 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! JSON records, for the `--json` output of utilities that list files and
+//! file systems.
+//!
+//! Each record is an object on a line of its own (JSON Lines), whose field
+//! names stay the same from one release to the next, so that scripts can
+//! read the output without parsing columns. Sizes are in bytes and times
+//! are seconds since the Epoch, whatever the options for showing them.
+
+use std::fmt::{self, Display, Write};
+use std::fs::FileType;
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+
+/// A JSON object, built one field at a time.
+#[derive(Debug, Default)]
+pub struct Object {
+    fields: String,
+}
+
+impl Object {
+    pub fn new() -> Object {
+        Object::default()
+    }
+
+    /// Start the field `key`, ready for its value.
+    fn key(&mut self, key: &str) -> &mut String {
+        if !self.fields.is_empty() {
+            self.fields.push(',');
+        }
+        self.fields.push_str(&quote(key));
+        self.fields.push(':');
+        &mut self.fields
+    }
+
+    pub fn string(&mut self, key: &str, value: &str) -> &mut Object {
+        let quoted = quote(value);
+        self.key(key).push_str(&quoted);
+        self
+    }
+
+    /// A field with an integer value.
+    pub fn number<N: Display>(&mut self, key: &str, value: N) -> &mut Object {
+        let _ = write!(self.key(key), "{}", value);
+        self
+    }
+
+    pub fn boolean(&mut self, key: &str, value: bool) -> &mut Object {
+        self.key(key).push_str(if value { "true" } else { "false" });
+        self
+    }
+
+    /// A field whose value is not known.
+    pub fn null(&mut self, key: &str) -> &mut Object {
+        self.key(key).push_str("null");
+        self
+    }
+
+    /// A time, as seconds since the Epoch with all nine digits of the
+    /// nanoseconds, which are counted forwards from `seconds` as `stat`
+    /// gives them.
+    pub fn timestamp(&mut self, key: &str, seconds: i64, nanoseconds: u32) -> &mut Object {
+        let time = if seconds < 0 && nanoseconds > 0 {
+            format!(
+                "-{}.{:09}",
+                -(seconds + 1),
+                1_000_000_000 - u64::from(nanoseconds)
+            )
+        } else {
+            format!("{}.{:09}", seconds, nanoseconds)
+        };
+        self.key(key).push_str(&time);
+        self
+    }
+}
+
+impl Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{}}}", self.fields)
+    }
+}
+
+/// The `type` of a file in a record.
+pub fn file_type(file_type: &FileType) -> &'static str {
+    if file_type.is_dir() {
+        return "directory";
+    } else if file_type.is_symlink() {
+        return "symlink";
+    } else if file_type.is_file() {
+        return "file";
+    }
+    #[cfg(unix)]
+    {
+        if file_type.is_fifo() {
+            return "fifo";
+        } else if file_type.is_socket() {
+            return "socket";
+        } else if file_type.is_block_device() {
+            return "block device";
+        } else if file_type.is_char_device() {
+            return "character device";
+        }
+    }
+    "unknown"
+}
+
+/// `s` as a JSON string, in double quotes.
+pub fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("plain"), "\"plain\"");
+        assert_eq!(quote("a \"b\" \\c"), "\"a \\\"b\\\" \\\\c\"");
+        assert_eq!(quote("tab\tnew\nline"), "\"tab\\tnew\\nline\"");
+        assert_eq!(quote("\u{1}\u{7f}é"), "\"\\u0001\\u007fé\"");
+    }
+
+    #[test]
+    fn test_object() {
+        assert_eq!(Object::new().to_string(), "{}");
+        let mut object = Object::new();
+        object
+            .string("name", "a b")
+            .number("size", 42)
+            .number("offset", -1)
+            .boolean("dir", false)
+            .null("target");
+        assert_eq!(
+            object.to_string(),
+            r#"{"name":"a b","size":42,"offset":-1,"dir":false,"target":null}"#
+        );
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let time = |seconds, nanoseconds| {
+            Object::new()
+                .timestamp("t", seconds, nanoseconds)
+                .to_string()
+        };
+        assert_eq!(time(1_234_567_890, 5), r#"{"t":1234567890.000000005}"#);
+        assert_eq!(time(0, 0), r#"{"t":0.000000000}"#);
+        assert_eq!(time(-1, 0), r#"{"t":-1.000000000}"#);
+        assert_eq!(time(-2, 500_000_000), r#"{"t":-1.500000000}"#);
+    }
+}
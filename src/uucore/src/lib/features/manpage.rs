@@ -0,0 +1,271 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Manual pages, written in roff from the arguments a utility is defined
+//! with, so that they say what the utility accepts.
+//!
+//! A page has the sections of a GNU one: NAME, SYNOPSIS, DESCRIPTION,
+//! OPTIONS and EXIT STATUS. The options are listed in the order they were
+//! defined in, with `--help` and `--version` last, and a paragraph of the
+//! help text that starts with "Exit status" becomes the EXIT STATUS
+//! section.
+
+// spell-checker:ignore (roff) fB fI fR
+
+use crate::coreopts::CoreOptions;
+use crate::help::{paragraphs, Help, Opt, SUITE};
+use clap::App;
+use std::fmt::{self, Display};
+
+const EXIT_STATUS: &str = "0 on success, and greater than 0 if an error occurs.";
+
+/// The manual page of a utility, which displays as roff. It says what the
+/// utility's help does.
+#[derive(Debug, Default)]
+pub struct Manpage(Help);
+
+impl<'a, 'b> From<&App<'a, 'b>> for Manpage {
+    fn from(app: &App<'a, 'b>) -> Manpage {
+        Manpage(Help::from(app))
+    }
+}
+
+impl<'a> From<&CoreOptions<'a>> for Manpage {
+    fn from(app: &CoreOptions<'a>) -> Manpage {
+        Manpage(Help::from(app))
+    }
+}
+
+impl Display for Manpage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let page = &self.0;
+        writeln!(
+            f,
+            ".TH {} 1 \"\" \"{} {}\" \"User Commands\"",
+            escape(&page.name.to_uppercase()),
+            SUITE,
+            escape(&page.version)
+        )?;
+
+        writeln!(f, ".SH NAME")?;
+        let summary = page.about.trim().lines().next().unwrap_or("");
+        let summary = summary.trim_end_matches('.');
+        if summary.is_empty() {
+            writeln!(f, "{}", escape(&page.name))?;
+        } else {
+            writeln!(f, "{} \\- {}", escape(&page.name), escape(summary))?;
+        }
+
+        writeln!(f, ".SH SYNOPSIS")?;
+        for (i, synopsis) in page.synopsis.iter().enumerate() {
+            if i > 0 {
+                writeln!(f, ".br")?;
+            }
+            writeln!(f, ".B {}", escape(&page.name))?;
+            if !synopsis.is_empty() {
+                writeln!(f, "{}", escape(synopsis))?;
+            }
+        }
+
+        let mut description = vec![];
+        let mut exit_status = vec![];
+        for paragraph in paragraphs(&page.about)
+            .into_iter()
+            .chain(paragraphs(&page.description))
+        {
+            if paragraph.starts_with("Exit status") {
+                exit_status.push(paragraph);
+            } else {
+                description.push(paragraph);
+            }
+        }
+        if !description.is_empty() {
+            writeln!(f, ".SH DESCRIPTION")?;
+            write!(f, "{}", text(&description))?;
+        }
+
+        writeln!(f, ".SH OPTIONS")?;
+        for opt in &page.options {
+            writeln!(f, ".TP")?;
+            writeln!(f, "{}", option(opt))?;
+            let help = opt.help.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !help.is_empty() {
+                writeln!(f, "{}", text_line(&help))?;
+            }
+        }
+
+        writeln!(f, ".SH \"EXIT STATUS\"")?;
+        if exit_status.is_empty() {
+            writeln!(f, "{}", EXIT_STATUS)
+        } else {
+            write!(f, "{}", text(&exit_status))
+        }
+    }
+}
+
+/// The option as its tag in the OPTIONS section: "-B, --block-size=SIZE".
+fn option(opt: &Opt) -> String {
+    let mut names = vec![];
+    if let Some(short) = opt.short {
+        names.push(format!("\\fB\\-{}\\fR", escape(&short.to_string())));
+    }
+    if let Some(ref long) = opt.long {
+        names.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    let mut tag = names.join(", ");
+    if let Some(ref value) = opt.value {
+        let value = format!("\\fI{}\\fR", escape(value));
+        let separator = if opt.long.is_some() { "=" } else { " " };
+        if opt.optional {
+            tag.push_str(&format!("[{}{}]", separator.trim(), value));
+        } else {
+            tag.push_str(separator);
+            tag.push_str(&value);
+        }
+    }
+    tag
+}
+
+/// Paragraphs as roff, separated by `.PP`.
+fn text(paragraphs: &[String]) -> String {
+    let mut out = String::new();
+    for (i, paragraph) in paragraphs.iter().enumerate() {
+        if i > 0 {
+            out.push_str(".PP\n");
+        }
+        for line in paragraph.lines() {
+            out.push_str(&text_line(line));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// A line of text, which roff mustn't take for a request.
+fn text_line(line: &str) -> String {
+    if line.starts_with('.') || line.starts_with('\'') {
+        format!("\\&{}", escape(line))
+    } else {
+        escape(line)
+    }
+}
+
+/// `s` with the characters roff gives a meaning to escaped.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\e").replace('-', "\\-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("--all"), "\\-\\-all");
+        assert_eq!(escape("a\\b"), "a\\eb");
+        assert_eq!(text_line(".hidden"), "\\&.hidden");
+        assert_eq!(text_line("'quoted'"), "\\&'quoted'");
+    }
+
+    #[test]
+    fn test_from_app() {
+        let app = App::new("tool")
+            .version("1.2.3")
+            .about("Do a thing.")
+            .after_help("Exit status is 0 if it worked.")
+            .arg(
+                Arg::with_name("all")
+                    .short("a")
+                    .long("all")
+                    .help("all of it"),
+            )
+            .arg(
+                Arg::with_name("size")
+                    .long("block-size")
+                    .takes_value(true)
+                    .value_name("SIZE")
+                    .help("use blocks of SIZE"),
+            )
+            .arg(Arg::with_name("hidden").long("hidden").hidden(true))
+            .arg(Arg::with_name("files").multiple(true));
+        let page = Manpage::from(&app).to_string();
+        assert_eq!(
+            page,
+            r#".TH TOOL 1 "" "uutils coreutils 1.2.3" "User Commands"
+.SH NAME
+tool \- Do a thing
+.SH SYNOPSIS
+.B tool
+[OPTIONS] [files]...
+.SH DESCRIPTION
+Do a thing.
+.SH OPTIONS
+.TP
+\fB\-a\fR, \fB\-\-all\fR
+all of it
+.TP
+\fB\-\-block\-size\fR=\fISIZE\fR
+use blocks of SIZE
+.TP
+\fB\-h\fR, \fB\-\-help\fR
+display this help and exit
+.TP
+\fB\-V\fR, \fB\-\-version\fR
+output version information and exit
+.SH "EXIT STATUS"
+Exit status is 0 if it worked.
+"#
+        );
+    }
+
+    #[test]
+    fn test_from_core_options() {
+        let mut app = CoreOptions::new(crate::coreopts::HelpText {
+            name: "tool",
+            version: "1.2.3",
+            syntax: "[-s SIGNAL] PID...\n or:  tool -l",
+            summary: "Signal processes.",
+            long_help: "\n  SIGNAL is a name\n  or a number.\n",
+            display_usage: true,
+        });
+        app.optopt("s", "", "the signal to send", "SIGNAL")
+            .optflagopt("", "color", "colorize", "WHEN");
+        let page = Manpage::from(&app).to_string();
+        assert_eq!(
+            page,
+            r#".TH TOOL 1 "" "uutils coreutils 1.2.3" "User Commands"
+.SH NAME
+tool \- Signal processes
+.SH SYNOPSIS
+.B tool
+[\-s SIGNAL] PID...
+.br
+.B tool
+\-l
+.SH DESCRIPTION
+Signal processes.
+.PP
+SIGNAL is a name
+or a number.
+.SH OPTIONS
+.TP
+\fB\-s\fR \fISIGNAL\fR
+the signal to send
+.TP
+\fB\-\-color\fR[=\fIWHEN\fR]
+colorize
+.TP
+\fB\-\-help\fR
+display this help and exit
+.TP
+\fB\-\-version\fR
+output version information and exit
+.SH "EXIT STATUS"
+0 on success, and greater than 0 if an error occurs.
+"#
+        );
+    }
+}
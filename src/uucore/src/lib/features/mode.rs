@@ -25,11 +25,30 @@ pub fn parse_numeric(fperm: u32, mut mode: &str) -> Result<u32, String> {
     }
 }
 
-pub fn parse_symbolic(
-    mut fperm: u32,
-    mut mode: &str,
-    considering_dir: bool,
-) -> Result<u32, String> {
+/// Parse a whole MODE as `chmod` takes it: an octal number, or a
+/// comma-separated list of symbolic clauses. As with GNU, an octal number of
+/// fewer than five digits leaves the set-user-ID and set-group-ID bits of a
+/// directory alone unless it sets them.
+pub fn parse_mode(fperm: u32, mode: &str, considering_dir: bool) -> Result<u32, String> {
+    if !mode.is_empty() && mode.chars().all(|c| c.is_digit(8)) {
+        let value = parse_octal(mode).ok_or_else(|| invalid_mode(mode))?;
+        let mentioned = if mode.len() < 5 {
+            (value & 0o6000) | 0o1777
+        } else {
+            0o7777
+        };
+        let change = change_all('=', value, mentioned);
+        Ok(adjust(fperm, &change, considering_dir, 0))
+    } else {
+        parse_symbolic(fperm, mode, considering_dir)
+    }
+}
+
+/// Apply a comma-separated list of symbolic clauses, each of the form
+/// `[ugoa]*([-+=]([rwxXst]*|[ugo]|[0-7]+))+`, to `fperm`. Clauses that
+/// don't say whose permissions they change leave the bits set in the umask
+/// alone.
+pub fn parse_symbolic(mut fperm: u32, mode: &str, considering_dir: bool) -> Result<u32, String> {
     #[cfg(unix)]
     use libc::umask;
 
@@ -39,51 +58,194 @@ pub fn parse_symbolic(
         0
     }
 
-    let (mask, pos) = parse_levels(mode);
-    if pos == mode.len() {
-        return Err(format!("invalid mode ({})", mode));
+    #[cfg(windows)]
+    unsafe fn umask(_mask: u32) -> u32 {
+        // Windows has no umask; files are made as if it were the usual one
+        0o022
     }
-    let respect_umask = pos == 0;
+
+    let changes = compile(mode)?;
     let last_umask = unsafe { umask(0) };
-    mode = &mode[pos..];
-    while !mode.is_empty() {
-        let (op, pos) = parse_op(mode, None)?;
-        mode = &mode[pos..];
-        let (mut srwx, pos) = parse_change(mode, fperm, considering_dir);
-        if respect_umask {
-            srwx &= !(last_umask as u32);
-        }
-        mode = &mode[pos..];
-        match op {
-            '+' => fperm |= srwx & mask,
-            '-' => fperm &= !(srwx & mask),
-            '=' => fperm = (fperm & !mask) | (srwx & mask),
-            _ => unreachable!(),
-        }
-    }
     unsafe {
         umask(last_umask);
     }
+    for change in &changes {
+        fperm = adjust(fperm, change, considering_dir, last_umask as u32);
+    }
     Ok(fperm)
 }
 
-fn parse_levels(mode: &str) -> (u32, usize) {
-    let mut mask = 0;
-    let mut pos = 0;
-    for ch in mode.chars() {
-        mask |= match ch {
-            'u' => 0o7700,
-            'g' => 0o7070,
-            'o' => 0o7007,
-            'a' => 0o7777,
-            _ => break,
-        };
-        pos += 1;
+fn invalid_mode(mode: &str) -> String {
+    format!("invalid mode: '{}'", mode)
+}
+
+fn parse_octal(digits: &str) -> Option<u32> {
+    u32::from_str_radix(digits, 8)
+        .ok()
+        .filter(|&value| value <= 0o7777)
+}
+
+/// What an operator adds, removes or sets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Value {
+    /// exactly these bits
+    Bits(u32),
+    /// the permissions the file has for one class, given by its bits
+    CopyOf(u32),
+    /// these bits, plus execute permission if the file is a directory or
+    /// someone may already execute it (`X`)
+    BitsAndX(u32),
+}
+
+/// One operator of a clause, with what it acts on.
+#[derive(Debug, PartialEq)]
+struct Change {
+    op: char,
+    /// the bits of the classes named before the operator, or none if the
+    /// clause named none
+    affected: u32,
+    value: Value,
+    /// the special bits the clause asks for by name
+    mentioned: u32,
+}
+
+fn change_all(op: char, value: u32, mentioned: u32) -> Change {
+    Change {
+        op,
+        affected: 0o7777,
+        value: Value::Bits(value),
+        mentioned,
+    }
+}
+
+fn compile(mode: &str) -> Result<Vec<Change>, String> {
+    let mut changes = Vec::new();
+    for clause in mode.split(',') {
+        let who_len = clause.find(|c| !"ugoa".contains(c)).unwrap_or(clause.len());
+        let affected = clause[..who_len].chars().fold(0, |bits, c| {
+            bits | match c {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                _ => 0o7777,
+            }
+        });
+        let mut rest = &clause[who_len..];
+        if rest.is_empty() {
+            return Err(invalid_mode(mode));
+        }
+        while let Some(op) = rest.chars().next() {
+            if op != '+' && op != '-' && op != '=' {
+                return Err(invalid_mode(mode));
+            }
+            rest = &rest[1..];
+
+            let octal_len = rest.find(|c: char| !c.is_digit(8)).unwrap_or(rest.len());
+            if octal_len > 0 {
+                // an octal number takes the rest of the clause, and can't be
+                // limited to some classes
+                if affected != 0 || octal_len < rest.len() {
+                    return Err(invalid_mode(mode));
+                }
+                let value = parse_octal(rest).ok_or_else(|| invalid_mode(mode))?;
+                changes.push(change_all(op, value, 0o7777));
+                break;
+            }
+
+            let (value, len) = match rest.chars().next() {
+                Some('u') => (Value::CopyOf(0o700), 1),
+                Some('g') => (Value::CopyOf(0o070), 1),
+                Some('o') => (Value::CopyOf(0o007), 1),
+                _ => {
+                    let len = rest.find(|c| !"rwxXst".contains(c)).unwrap_or(rest.len());
+                    let bits = rest[..len].chars().fold(0, |bits, c| {
+                        bits | match c {
+                            'r' => 0o444,
+                            'w' => 0o222,
+                            'x' => 0o111,
+                            's' => 0o6000,
+                            't' => 0o1000,
+                            _ => 0,
+                        }
+                    });
+                    if rest[..len].contains('X') {
+                        (Value::BitsAndX(bits), len)
+                    } else {
+                        (Value::Bits(bits), len)
+                    }
+                }
+            };
+            let named = match value {
+                Value::CopyOf(_) => 0,
+                Value::Bits(bits) | Value::BitsAndX(bits) => bits,
+            };
+            changes.push(Change {
+                op,
+                affected,
+                value,
+                mentioned: if affected != 0 {
+                    affected & named
+                } else {
+                    named
+                },
+            });
+            rest = &rest[len..];
+        }
     }
-    if pos == 0 {
-        mask = 0o7777; // default to 'a'
+    Ok(changes)
+}
+
+/// Apply `change` to `fperm`, as gnulib's `mode_adjust` does.
+fn adjust(fperm: u32, change: &Change, considering_dir: bool, umask: u32) -> u32 {
+    // a directory keeps its set-ID bits unless they're asked for by name
+    let omit = if considering_dir {
+        0o6000 & !change.mentioned
+    } else {
+        0
+    };
+    let mut value = match change.value {
+        Value::Bits(bits) => bits,
+        Value::CopyOf(class) => {
+            let bits = fperm & class;
+            let mut value = 0;
+            if bits & 0o444 != 0 {
+                value |= 0o444;
+            }
+            if bits & 0o222 != 0 {
+                value |= 0o222;
+            }
+            if bits & 0o111 != 0 {
+                value |= 0o111;
+            }
+            value
+        }
+        Value::BitsAndX(bits) => {
+            if considering_dir || fperm & 0o111 != 0 {
+                bits | 0o111
+            } else {
+                bits
+            }
+        }
+    };
+    let affected = if change.affected != 0 {
+        change.affected
+    } else {
+        !umask & 0o7777
+    };
+    value &= affected & !omit;
+    match change.op {
+        '+' => fperm | value,
+        '-' => fperm & !value,
+        _ => {
+            // bits outside the named classes stay
+            let preserved = if change.affected != 0 {
+                !change.affected & 0o7777
+            } else {
+                0
+            };
+            (fperm & (preserved | omit)) | value
+        }
     }
-    (mask, pos)
 }
 
 fn parse_op(mode: &str, default: Option<char>) -> Result<(char, usize), String> {
@@ -102,30 +264,57 @@ fn parse_op(mode: &str, default: Option<char>) -> Result<(char, usize), String>
     }
 }
 
-fn parse_change(mode: &str, fperm: u32, considering_dir: bool) -> (u32, usize) {
-    let mut srwx = fperm & 0o7000;
-    let mut pos = 0;
-    for ch in mode.chars() {
-        match ch {
-            'r' => srwx |= 0o444,
-            'w' => srwx |= 0o222,
-            'x' => srwx |= 0o111,
-            'X' => {
-                if considering_dir || (fperm & 0o0111) != 0 {
-                    srwx |= 0o111
-                }
-            }
-            's' => srwx |= 0o4000 | 0o2000,
-            't' => srwx |= 0o1000,
-            'u' => srwx = (fperm & 0o700) | ((fperm >> 3) & 0o070) | ((fperm >> 6) & 0o007),
-            'g' => srwx = ((fperm << 3) & 0o700) | (fperm & 0o070) | ((fperm >> 3) & 0o007),
-            'o' => srwx = ((fperm << 6) & 0o700) | ((fperm << 3) & 0o070) | (fperm & 0o007),
-            _ => break,
-        };
-        pos += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(fperm: u32, mode: &str, dir: bool, umask: u32) -> u32 {
+        compile(mode)
+            .unwrap()
+            .iter()
+            .fold(fperm, |fperm, change| adjust(fperm, change, dir, umask))
+    }
+
+    #[test]
+    fn test_clauses() {
+        assert_eq!(0o4710, apply(0o700, "u+s,g-s,o=,g+X", false, 0));
+        assert_eq!(0o755, apply(0o4755, "u=rwx", false, 0));
+        assert_eq!(0o664, apply(0o600, "g=u,o=u-w", false, 0));
+        assert_eq!(0o711, apply(0o600, "u+x,a+X", false, 0));
+        assert_eq!(0o000, apply(0o600, "a=X", false, 0));
+        assert_eq!(0o111, apply(0o000, "a+X", true, 0));
+        assert_eq!(0o700, apply(0o600, "+0100", false, 0));
+        assert_eq!(0o755, apply(0o755, "u+t", false, 0));
+        assert_eq!(0o1755, apply(0o755, "o+t", true, 0));
+    }
+
+    #[test]
+    fn test_umask() {
+        assert_eq!(0o755, apply(0o000, "+rwx", false, 0o022));
+        assert_eq!(0o555, apply(0o755, "-w", false, 0o022));
+        assert_eq!(0o1000, apply(0o555, "=t", false, 0o022));
+        assert_eq!(0o777, apply(0o000, "a+rwx", false, 0o022));
     }
-    if pos == 0 {
-        srwx = 0;
+
+    #[test]
+    fn test_directory_set_id_bits() {
+        assert_eq!(0o2755, parse_mode(0o2755, "755", true).unwrap());
+        assert_eq!(0o2755, parse_mode(0o2755, "0755", true).unwrap());
+        assert_eq!(0o755, parse_mode(0o2755, "00755", true).unwrap());
+        assert_eq!(0o755, parse_mode(0o2755, "755", false).unwrap());
+        assert_eq!(0o2755, apply(0o2755, "u=rwx,g=rx,o=rx", true, 0));
+        assert_eq!(0o755, apply(0o2755, "g-s", true, 0));
+    }
+
+    #[test]
+    fn test_invalid() {
+        for mode in &[
+            "u", "x", "8", "77777", "0=r", "g=ur", "u+x,", "u+0100", "+7a",
+        ] {
+            assert_eq!(
+                Err(format!("invalid mode: '{}'", mode)),
+                parse_mode(0, mode, false)
+            );
+        }
     }
-    (srwx, pos)
 }
@@ -0,0 +1,1094 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// spell-checker:ignore (words) fortnight meridian wednes thur thurs sept
+
+//! Parsing of free-form date strings, as accepted by `date -d` and
+//! `touch -d`, following the grammar of GNU's `parse_datetime`.
+//!
+//! A date string is a sequence of items: calendar dates (`2004-02-29`,
+//! `2/29/2004`, `29 Feb 2004`, `Feb 29, 2004`), times of day (`14:05:09.5`,
+//! `2pm`), time zones (`UTC`, `+0530`), days of the week (`Fri`,
+//! `next Friday`), relative items (`2 weeks ago`, `+3 hours`, `tomorrow`),
+//! and bare numbers. A string of the form `@SECONDS` is a number of seconds
+//! since the epoch. A string may start with `TZ="ZONE"`, naming the time
+//! zone its local times are in.
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    Offset, TimeZone, Timelike,
+};
+
+/// Parse `input` relative to the current time `now`, interpreting local
+/// times in the time zone of `now`.
+///
+/// The result carries the UTC offset in effect at the parsed time (or the
+/// one given in the string); convert it with `with_timezone` for display.
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use uucore::parse_datetime::parse_datetime_at_date;
+///
+/// let now = Utc.ymd(2021, 3, 5).and_hms(12, 0, 0);
+/// let date = parse_datetime_at_date(now, "yesterday 10:00").unwrap();
+/// assert_eq!(date, Utc.ymd(2021, 3, 4).and_hms(10, 0, 0));
+/// let date = parse_datetime_at_date(now, "next fri").unwrap();
+/// assert_eq!(date, Utc.ymd(2021, 3, 12).and_hms(0, 0, 0));
+/// assert!(parse_datetime_at_date(now, "2021-02-30").is_err());
+/// ```
+pub fn parse_datetime_at_date<Tz: TimeZone>(
+    now: DateTime<Tz>,
+    input: &str,
+) -> Result<DateTime<FixedOffset>, String> {
+    parse_datetime_at_date_debug(now, input, |_| {})
+}
+
+/// Like `parse_datetime_at_date`, but describe how the string was understood
+/// to `debug`, one message at a time, as `date --debug` does.
+pub fn parse_datetime_at_date_debug<Tz: TimeZone, F: FnMut(String)>(
+    now: DateTime<Tz>,
+    input: &str,
+    mut debug: F,
+) -> Result<DateTime<FixedOffset>, String> {
+    let (zone, rest) = match split_time_zone(input) {
+        Some((zone, rest)) => (Some(zone), rest),
+        None => (None, input),
+    };
+    let mut items = match tokenize(rest).and_then(|tokens| Parser::new(tokens).parse()) {
+        Some(items) => items,
+        None => {
+            debug("error: parsing failed".to_owned());
+            return Err(format!("invalid date '{}'", input));
+        }
+    };
+    items.time_zone = zone;
+    items.resolve(&now, &mut debug).ok_or_else(|| {
+        debug("error: invalid date/time value".to_owned());
+        format!("invalid date '{}'", input)
+    })
+}
+
+/// The time zone named by a leading `TZ="ZONE"` in `input`, if any.
+///
+/// Local times in the rest of the string are in that zone, so callers should
+/// pass `now` in it to `parse_datetime_at_date`; the prefix itself is
+/// skipped there.
+pub fn time_zone_prefix(input: &str) -> Option<String> {
+    split_time_zone(input).map(|(zone, _)| zone)
+}
+
+/// Split a leading `TZ="ZONE"` from `input`. Within the quotes, `\"` and
+/// `\\` stand for `"` and `\`; any other backslash makes the prefix invalid.
+fn split_time_zone(input: &str) -> Option<(String, &str)> {
+    let input = input.trim_start();
+    if !input.starts_with("TZ=\"") {
+        return None;
+    }
+    let mut zone = String::new();
+    let mut chars = input.char_indices().skip(4);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((zone, &input[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, c)) if c == '"' || c == '\\' => zone.push(c),
+                _ => return None,
+            },
+            _ => zone.push(c),
+        }
+    }
+    None
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// a run of digits
+    Number(String),
+    /// a run of letters, lowercased, with any periods removed
+    Word(String),
+    /// any other non-blank character
+    Punct(char),
+}
+
+fn tokenize(input: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            // comments are parenthesized and may nest
+            let mut depth = 0;
+            for c in &mut chars {
+                if c == '(' {
+                    depth += 1;
+                } else if c == ')' {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            if depth != 0 {
+                return None;
+            }
+        } else if c.is_ascii_digit() {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek() {
+                if !c.is_ascii_digit() {
+                    break;
+                }
+                number.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Number(number));
+        } else if c.is_ascii_alphabetic() {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_alphabetic() {
+                    word.push(c.to_ascii_lowercase());
+                } else if c != '.' {
+                    break;
+                }
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        } else if "+-:/,.@".contains(c) {
+            tokens.push(Token::Punct(c));
+            chars.next();
+        } else {
+            return None;
+        }
+    }
+    Some(tokens)
+}
+
+/// Units of relative items, in months or in seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Unit {
+    Months(i64),
+    Days(i64),
+    Seconds(i64),
+}
+
+fn lookup_month(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    if word == "sept" {
+        return Some(9);
+    }
+    MONTHS
+        .iter()
+        .position(|&month| word == month || (word.len() == 3 && month.starts_with(word)))
+        .map(|i| i as u32 + 1)
+}
+
+/// Day of the week named by `word`, counting from Sunday.
+fn lookup_weekday(word: &str) -> Option<u32> {
+    const DAYS: [&str; 7] = [
+        "sunday",
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+    ];
+    match word {
+        "tues" => return Some(2),
+        "wednes" => return Some(3),
+        "thur" | "thurs" => return Some(4),
+        _ => {}
+    }
+    DAYS.iter()
+        .position(|&day| word == day || (word.len() == 3 && day.starts_with(word)))
+        .map(|i| i as u32)
+}
+
+fn lookup_ordinal(word: &str) -> Option<i64> {
+    let ordinal = match word {
+        "last" => -1,
+        "this" => 0,
+        "next" | "first" => 1,
+        // "second" is a unit
+        "third" => 3,
+        "fourth" => 4,
+        "fifth" => 5,
+        "sixth" => 6,
+        "seventh" => 7,
+        "eighth" => 8,
+        "ninth" => 9,
+        "tenth" => 10,
+        "eleventh" => 11,
+        "twelfth" => 12,
+        _ => return None,
+    };
+    Some(ordinal)
+}
+
+fn lookup_unit(word: &str) -> Option<Unit> {
+    let unit = match word {
+        "year" => Unit::Months(12),
+        "month" => Unit::Months(1),
+        "fortnight" => Unit::Days(14),
+        "week" => Unit::Days(7),
+        "day" => Unit::Days(1),
+        "hour" => Unit::Seconds(60 * 60),
+        "minute" | "min" => Unit::Seconds(60),
+        "second" | "sec" => Unit::Seconds(1),
+        _ if word.len() > 1 && word.ends_with('s') => {
+            return lookup_unit(&word[..word.len() - 1]);
+        }
+        _ => return None,
+    };
+    Some(unit)
+}
+
+/// UTC offset, in minutes, of the time zone named by `word`.
+fn lookup_zone(word: &str) -> Option<i32> {
+    let hours = match word {
+        "utc" | "ut" | "gmt" | "z" | "wet" => 0,
+        "west" | "bst" | "cet" | "met" | "mez" | "wat" => 1,
+        "cest" | "mest" | "mesz" | "eet" | "cat" | "sast" => 2,
+        "eest" | "eat" | "msk" => 3,
+        "msd" => 4,
+        "ist" => return Some(5 * 60 + 30),
+        "sgt" => 8,
+        "kst" | "jst" => 9,
+        "gst" => 10,
+        "nzst" => 12,
+        "nzdt" => 13,
+        "nst" => return Some(-(3 * 60 + 30)),
+        "ndt" => return Some(-(2 * 60 + 30)),
+        "art" | "brt" | "adt" | "clst" => -3,
+        "ast" | "edt" | "clt" | "brst" => -4,
+        "est" | "cdt" => -5,
+        "cst" | "mdt" => -6,
+        "mst" | "pdt" => -7,
+        "pst" | "akdt" => -8,
+        "akst" | "hadt" => -9,
+        "hst" | "hast" => -10,
+        "sst" => -11,
+        _ => return None,
+    };
+    Some(hours * 60)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Meridian {
+    Am,
+    Pm,
+}
+
+fn lookup_meridian(word: &str) -> Option<Meridian> {
+    match word {
+        "am" => Some(Meridian::Am),
+        "pm" => Some(Meridian::Pm),
+        _ => None,
+    }
+}
+
+/// A date, with a year only if one was given.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Date {
+    year: Option<i64>,
+    month: u32,
+    day: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Time {
+    hour: u32,
+    minute: u32,
+    second: u32,
+    nanosecond: u32,
+}
+
+/// Everything a date string says, before it is applied to the current time.
+#[derive(Debug, Default, PartialEq)]
+struct Items {
+    epoch: Option<(i64, u32)>,
+    date: Option<Date>,
+    time: Option<Time>,
+    /// UTC offset in minutes
+    zone: Option<i32>,
+    /// the zone named by a `TZ="ZONE"` prefix
+    time_zone: Option<String>,
+    /// ordinal and day of the week, counting from Sunday
+    weekday: Option<(i64, u32)>,
+    relative: bool,
+    months: i64,
+    days: i64,
+    seconds: i64,
+    nanoseconds: i64,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    items: Items,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Parser {
+        Parser {
+            tokens,
+            pos: 0,
+            items: Items::default(),
+        }
+    }
+
+    fn parse(mut self) -> Option<Items> {
+        if self.punct(0) == Some('@') {
+            self.pos += 1;
+            self.parse_epoch()?;
+        } else {
+            while self.pos < self.tokens.len() {
+                self.parse_item()?;
+            }
+        }
+        Some(self.items)
+    }
+
+    fn token(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn number(&self, offset: usize) -> Option<&str> {
+        match self.token(offset) {
+            Some(Token::Number(digits)) => Some(digits),
+            _ => None,
+        }
+    }
+
+    fn word(&self, offset: usize) -> Option<&str> {
+        match self.token(offset) {
+            Some(Token::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    fn punct(&self, offset: usize) -> Option<char> {
+        match self.token(offset) {
+            Some(&Token::Punct(c)) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Value of the number at `offset`, if it fits.
+    fn value(&self, offset: usize) -> Option<i64> {
+        self.number(offset).and_then(|digits| digits.parse().ok())
+    }
+
+    /// A sign followed by a number, as a signed value.
+    fn signed_value(&self, offset: usize) -> Option<i64> {
+        let sign = match self.punct(offset)? {
+            '+' => 1,
+            '-' => -1,
+            _ => return None,
+        };
+        Some(sign * self.value(offset + 1)?)
+    }
+
+    fn skip_punct(&mut self, c: char) -> bool {
+        let found = self.punct(0) == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn parse_epoch(&mut self) -> Option<()> {
+        let (seconds, consumed) = match self.value(0) {
+            Some(value) => (value, 1),
+            None => (self.signed_value(0)?, 2),
+        };
+        self.pos += consumed;
+        let mut nanoseconds = 0;
+        if self.skip_punct('.') || self.skip_punct(',') {
+            nanoseconds = fraction(self.number(0)?);
+            self.pos += 1;
+        }
+        if self.pos != self.tokens.len() {
+            return None;
+        }
+        // negative times with a fraction count back from the next second
+        if seconds < 0 && nanoseconds > 0 {
+            self.items.epoch = Some((seconds - 1, 1_000_000_000 - nanoseconds));
+        } else {
+            self.items.epoch = Some((seconds, nanoseconds));
+        }
+        Some(())
+    }
+
+    fn parse_item(&mut self) -> Option<()> {
+        match self.tokens[self.pos].clone() {
+            Token::Number(_) => self.parse_number_item(),
+            Token::Word(word) => self.parse_word_item(&word),
+            Token::Punct('+') | Token::Punct('-') => {
+                let value = self.signed_value(0)?;
+                self.pos += 2;
+                self.parse_relative(value)
+            }
+            Token::Punct(_) => None,
+        }
+    }
+
+    fn parse_word_item(&mut self, word: &str) -> Option<()> {
+        self.pos += 1;
+        let days = match word {
+            "tomorrow" => Some(1),
+            "yesterday" => Some(-1),
+            "today" | "now" => Some(0),
+            _ => None,
+        };
+        if let Some(days) = days {
+            self.items.relative = true;
+            self.items.days = self.items.days.checked_add(days)?;
+            return Some(());
+        }
+        if let Some(month) = lookup_month(word) {
+            return self.parse_month_date(month);
+        }
+        if let Some(weekday) = lookup_weekday(word) {
+            self.skip_punct(',');
+            return self.set_weekday(0, weekday);
+        }
+        if let Some(ordinal) = lookup_ordinal(word) {
+            if let Some(weekday) = self.word(0).and_then(lookup_weekday) {
+                self.pos += 1;
+                return self.set_weekday(ordinal, weekday);
+            }
+            return self.parse_relative(ordinal);
+        }
+        if lookup_unit(word).is_some() {
+            self.pos -= 1;
+            return self.parse_relative(1);
+        }
+        if let Some(zone) = lookup_zone(word) {
+            return self.parse_zone(zone);
+        }
+        None
+    }
+
+    /// Parse the rest of a date starting with a month name: `Feb 29`,
+    /// `Feb 29, 2004` or `Feb-29-2004`.
+    fn parse_month_date(&mut self, month: u32) -> Option<()> {
+        if self.punct(0) == Some('-') {
+            let day = self.value(1)?;
+            if self.punct(2) != Some('-') || self.number(3).is_none() {
+                return None;
+            }
+            let year = self.year(3)?;
+            self.pos += 4;
+            return self.set_date(Some(year), month, day);
+        }
+        let day = self.value(0)?;
+        self.pos += 1;
+        let mut year = None;
+        if self.punct(0) == Some(',') && self.number(1).is_some() {
+            year = Some(self.year(1)?);
+            self.pos += 2;
+        }
+        self.set_date(year, month, day)
+    }
+
+    fn parse_number_item(&mut self) -> Option<()> {
+        let value = self.value(0)?;
+        match (self.token(1).cloned(), self.token(2).cloned()) {
+            (Some(Token::Punct(':')), _) => self.parse_time(),
+            (Some(Token::Punct('-')), Some(Token::Number(_)))
+                if self.punct(3) == Some('-') && self.number(4).is_some() =>
+            {
+                self.parse_iso_date()
+            }
+            (Some(Token::Punct('-')), Some(Token::Word(word))) => {
+                // 29-Feb-2004
+                let month = lookup_month(&word)?;
+                if self.punct(3) != Some('-') || self.number(4).is_none() {
+                    return None;
+                }
+                let year = self.year(4)?;
+                self.pos += 5;
+                self.set_date(Some(year), month, value)
+            }
+            (Some(Token::Punct('/')), _) => self.parse_slash_date(),
+            (Some(Token::Punct('.')), Some(Token::Number(digits))) => {
+                // fractional seconds, as in "1.5 seconds ago"
+                match self.word(3).and_then(lookup_unit) {
+                    Some(Unit::Seconds(1)) => {}
+                    _ => return None,
+                }
+                self.pos += 4;
+                self.parse_relative_seconds(value, fraction(&digits) as i64)
+            }
+            (Some(Token::Word(word)), _) => {
+                if let Some(month) = lookup_month(&word) {
+                    // 29 Feb [2004]
+                    self.pos += 2;
+                    let mut year = None;
+                    if self.number(0).is_some() {
+                        year = Some(self.year(0)?);
+                        self.pos += 1;
+                    }
+                    self.set_date(year, month, value)
+                } else if let Some(meridian) = lookup_meridian(&word) {
+                    self.pos += 2;
+                    let hour = to_hour(value, Some(meridian))?;
+                    self.set_time(hour, 0, 0, 0)
+                } else if let Some(weekday) = lookup_weekday(&word) {
+                    self.pos += 2;
+                    self.set_weekday(value, weekday)
+                } else if lookup_unit(&word).is_some() {
+                    self.pos += 1;
+                    self.parse_relative(value)
+                } else {
+                    self.parse_bare_number()
+                }
+            }
+            _ if self.zone_follows(1) => {
+                // an hour with a time zone, as in "5 -0530"
+                self.pos += 1;
+                self.set_time(to_hour(value, None)?, 0, 0, 0)?;
+                let zone = self.numeric_zone()?;
+                self.set_zone(zone)
+            }
+            _ => self.parse_bare_number(),
+        }
+    }
+
+    /// A number on its own is a year after a date, a date when it has more
+    /// than four digits (`20040229`), and a time of day otherwise (`14`,
+    /// `1405`).
+    fn parse_bare_number(&mut self) -> Option<()> {
+        let digits = self.number(0)?.len();
+        let value = self.value(0)?;
+        let year = self.year(0)?;
+        self.pos += 1;
+        match self.items.date {
+            Some(ref mut date)
+                if date.year.is_none()
+                    && !self.items.relative
+                    && (self.items.time.is_some() || digits > 2) =>
+            {
+                date.year = Some(year);
+                Some(())
+            }
+            _ if digits > 4 => {
+                let year = if digits == 6 {
+                    two_digit_year(value / 10000)
+                } else {
+                    value / 10000
+                };
+                self.set_date(Some(year), (value / 100 % 100) as u32, value % 100)
+            }
+            _ if digits <= 2 => self.set_time(value as u32, 0, 0, 0),
+            _ => self.set_time((value / 100) as u32, (value % 100) as u32, 0, 0),
+        }
+    }
+
+    /// `2004-02-29`, optionally followed by `T` and a time.
+    fn parse_iso_date(&mut self) -> Option<()> {
+        let year = self.year(0)?;
+        let month = self.value(2)?;
+        if self.punct(3) != Some('-') {
+            return None;
+        }
+        let day = self.value(4)?;
+        self.pos += 5;
+        self.set_date(Some(year), month as u32, day)?;
+        if self.word(0) == Some("t") && self.number(1).is_some() {
+            self.pos += 1;
+            if self.punct(1) == Some(':') {
+                self.parse_time()?;
+            } else {
+                self.parse_bare_number()?;
+            }
+        }
+        Some(())
+    }
+
+    /// `2/29` and `2/29/2004`, or `2004/2/29` when the first number has four
+    /// digits.
+    fn parse_slash_date(&mut self) -> Option<()> {
+        let first = self.value(0)?;
+        let second = self.value(2)?;
+        if self.punct(3) != Some('/') {
+            self.pos += 3;
+            return self.set_date(None, first as u32, second);
+        }
+        let third = self.value(4)?;
+        if self.number(0)?.len() >= 4 {
+            self.pos += 5;
+            self.set_date(Some(first), second as u32, third)
+        } else {
+            let year = self.year(4)?;
+            self.pos += 5;
+            self.set_date(Some(year), first as u32, second)
+        }
+    }
+
+    /// `HH:MM[:SS[.FRAC]]`, optionally followed by `am`/`pm` and a numeric
+    /// time zone.
+    fn parse_time(&mut self) -> Option<()> {
+        let hour = self.value(0)?;
+        let minute = self.value(2)?;
+        self.pos += 3;
+        let mut second = 0;
+        let mut nanosecond = 0;
+        if self.punct(0) == Some(':') {
+            second = self.value(1)?;
+            self.pos += 2;
+            if (self.punct(0) == Some('.') || self.punct(0) == Some(','))
+                && self.number(1).is_some()
+            {
+                nanosecond = fraction(self.number(1)?);
+                self.pos += 2;
+            }
+        }
+        let meridian = self.word(0).and_then(lookup_meridian);
+        if meridian.is_some() {
+            self.pos += 1;
+        }
+        let hour = to_hour(hour, meridian)?;
+        if minute > 59 || second > 59 {
+            return None;
+        }
+        self.set_time(hour, minute as u32, second as u32, nanosecond)?;
+        if let Some(offset) = self.numeric_zone() {
+            self.set_zone(offset)?;
+        }
+        Some(())
+    }
+
+    /// A numeric time zone at the current position: `+HH`, `+HHMM` or
+    /// `+HH:MM`.
+    fn numeric_zone(&mut self) -> Option<i32> {
+        let value = self.signed_value(0)?;
+        let digits = self.number(1)?.len();
+        let sign = value.signum();
+        let value = value.abs();
+        // the tokens of the zone: the sign and hours, and `:` and minutes
+        let (minutes, tokens) = if self.punct(2) == Some(':') {
+            let minutes = self.value(3)?;
+            if digits > 2 || minutes > 59 {
+                return None;
+            }
+            (value * 60 + minutes, 4)
+        } else if digits <= 2 {
+            (value * 60, 2)
+        } else if digits <= 4 {
+            (value / 100 * 60 + value % 100, 2)
+        } else {
+            return None;
+        };
+        if minutes > 24 * 60 {
+            return None;
+        }
+        self.pos += tokens;
+        Some((sign * minutes) as i32)
+    }
+
+    fn parse_zone(&mut self, zone: i32) -> Option<()> {
+        let mut zone = zone;
+        if self.word(0) == Some("dst") {
+            self.pos += 1;
+            zone += 60;
+        } else if self.zone_follows(0) {
+            zone += self.numeric_zone()?;
+        }
+        self.set_zone(zone)
+    }
+
+    /// Whether a signed number at `offset` is a time zone rather than the
+    /// start of a relative item.
+    fn zone_follows(&self, offset: usize) -> bool {
+        self.signed_value(offset).is_some() && self.word(offset + 2).and_then(lookup_unit).is_none()
+    }
+
+    /// A relative item counted `count` times: `2 days`, `next week`,
+    /// `-3 hours`, optionally followed by `ago`.
+    fn parse_relative(&mut self, count: i64) -> Option<()> {
+        let unit = self.word(0).and_then(lookup_unit)?;
+        self.pos += 1;
+        let count = if self.word(0) == Some("ago") {
+            self.pos += 1;
+            -count
+        } else {
+            count
+        };
+        self.items.relative = true;
+        let (total, step) = match unit {
+            Unit::Months(months) => (&mut self.items.months, months),
+            Unit::Days(days) => (&mut self.items.days, days),
+            Unit::Seconds(seconds) => (&mut self.items.seconds, seconds),
+        };
+        *total = total.checked_add(count.checked_mul(step)?)?;
+        Some(())
+    }
+
+    fn parse_relative_seconds(&mut self, seconds: i64, nanoseconds: i64) -> Option<()> {
+        let sign = if self.word(0) == Some("ago") {
+            self.pos += 1;
+            -1
+        } else {
+            1
+        };
+        self.items.relative = true;
+        self.items.seconds = self.items.seconds.checked_add(sign * seconds)?;
+        self.items.nanoseconds += sign * nanoseconds;
+        Some(())
+    }
+
+    /// The year given by the number at `offset`; two-digit years are in
+    /// 1969-2068.
+    fn year(&self, offset: usize) -> Option<i64> {
+        let value = self.value(offset)?;
+        if self.number(offset)?.len() == 2 {
+            Some(two_digit_year(value))
+        } else {
+            Some(value)
+        }
+    }
+
+    fn set_date(&mut self, year: Option<i64>, month: u32, day: i64) -> Option<()> {
+        if self.items.date.is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        self.items.date = Some(Date {
+            year,
+            month,
+            day: day as u32,
+        });
+        Some(())
+    }
+
+    fn set_time(&mut self, hour: u32, minute: u32, second: u32, nanosecond: u32) -> Option<()> {
+        if self.items.time.is_some() || hour > 23 || minute > 59 {
+            return None;
+        }
+        self.items.time = Some(Time {
+            hour,
+            minute,
+            second,
+            nanosecond,
+        });
+        Some(())
+    }
+
+    fn set_zone(&mut self, zone: i32) -> Option<()> {
+        if self.items.zone.is_some() {
+            return None;
+        }
+        self.items.zone = Some(zone);
+        Some(())
+    }
+
+    fn set_weekday(&mut self, ordinal: i64, weekday: u32) -> Option<()> {
+        if self.items.weekday.is_some() {
+            return None;
+        }
+        self.items.weekday = Some((ordinal, weekday));
+        Some(())
+    }
+}
+
+/// Nanoseconds in the decimal fraction with the given digits.
+fn fraction(digits: &str) -> u32 {
+    let mut nanoseconds = 0;
+    for (i, digit) in digits.bytes().take(9).enumerate() {
+        nanoseconds += u32::from(digit - b'0') * 10u32.pow(8 - i as u32);
+    }
+    nanoseconds
+}
+
+fn two_digit_year(year: i64) -> i64 {
+    if year < 69 {
+        year + 2000
+    } else {
+        year + 1900
+    }
+}
+
+fn to_hour(hour: i64, meridian: Option<Meridian>) -> Option<u32> {
+    let hour = match meridian {
+        None if hour <= 23 => hour,
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(Meridian::Am) => hour % 12,
+        Some(Meridian::Pm) => hour % 12 + 12,
+        None => return None,
+    };
+    Some(hour as u32)
+}
+
+impl Items {
+    fn resolve<Tz: TimeZone>(
+        &self,
+        now: &DateTime<Tz>,
+        debug: &mut dyn FnMut(String),
+    ) -> Option<DateTime<FixedOffset>> {
+        if let Some((seconds, nanoseconds)) = self.epoch {
+            debug(format!(
+                "parsed number of seconds part: number of seconds: {}.{:09}",
+                seconds, nanoseconds
+            ));
+            let utc = NaiveDateTime::from_timestamp_opt(seconds, nanoseconds)?;
+            let offset = now.timezone().offset_from_utc_datetime(&utc).fix();
+            return Some(DateTime::from_utc(utc, offset));
+        }
+
+        let local = now.naive_local();
+        self.describe(local.year(), debug);
+
+        let date = match self.date {
+            Some(date) => NaiveDate::from_ymd_opt(
+                date.year.unwrap_or_else(|| i64::from(local.year())) as i32,
+                date.month,
+                date.day,
+            )?,
+            None => {
+                debug(format!(
+                    "using current date as starting value: '{}'",
+                    show_date(&local.date())
+                ));
+                local.date()
+            }
+        };
+        // Without a time of day, relative items count from the current time
+        // and anything else is at midnight.
+        let time = match self.time {
+            Some(time) => {
+                NaiveTime::from_hms_nano_opt(time.hour, time.minute, time.second, time.nanosecond)?
+            }
+            None if self.relative && self.date.is_none() && self.weekday.is_none() => {
+                debug(format!(
+                    "using current time as starting value: '{}'",
+                    show_time(&local.time())
+                ));
+                local.time()
+            }
+            None => {
+                debug("warning: using midnight as starting time: 00:00:00".to_owned());
+                NaiveTime::from_hms(0, 0, 0)
+            }
+        };
+        let mut target = NaiveDateTime::new(date, time);
+
+        if let (Some((ordinal, weekday)), None) = (self.weekday, self.date) {
+            let current = i64::from(target.weekday().num_days_from_sunday());
+            let weekday = i64::from(weekday);
+            let adjust = if ordinal > 0 && current != weekday {
+                1
+            } else {
+                0
+            };
+            let days = (weekday - current + 7) % 7 + 7 * (ordinal - adjust);
+            target = target.checked_add_signed(days_duration(days)?)?;
+            debug(format!("new start date: '{}'", show_datetime(&target)));
+        }
+        let zone = match self.zone {
+            Some(minutes) => format!(" TZ={}", show_zone(minutes)),
+            None => String::new(),
+        };
+        debug(format!(
+            "starting date/time: '{}{}'",
+            show_datetime(&target),
+            zone
+        ));
+
+        let shifted = if self.months != 0 || self.days != 0 {
+            let months = i64::from(target.year()) * 12 + i64::from(target.month0()) + self.months;
+            let year = months.div_euclid(12);
+            let month = months.rem_euclid(12) as u32 + 1;
+            if year < i64::from(i32::min_value()) || year > i64::from(i32::max_value()) {
+                return None;
+            }
+            // overflowing days spill into the next month, as with mktime
+            let days = i64::from(target.day0()).checked_add(self.days)?;
+            let date = NaiveDate::from_ymd_opt(year as i32, month, 1)?
+                .checked_add_signed(days_duration(days)?)?;
+            let shifted = NaiveDateTime::new(date, target.time());
+            debug(format!(
+                "after date adjustment ({:+} years, {:+} months, {:+} days),",
+                self.months / 12,
+                self.months % 12,
+                self.days
+            ));
+            debug(format!("    new date/time = '{}'", show_datetime(&shifted)));
+            shifted
+        } else {
+            target
+        };
+
+        let result = match self.zone {
+            Some(minutes) => {
+                let offset = FixedOffset::east_opt(minutes * 60)?;
+                offset.from_local_datetime(&shifted).single()?
+            }
+            None => localize(&now.timezone(), &shifted, shifted != target)?,
+        };
+        debug(format!(
+            "'{}{}' = {} epoch-seconds",
+            show_datetime(&shifted),
+            zone,
+            result.timestamp()
+        ));
+        if self.seconds == 0 && self.nanoseconds == 0 {
+            return Some(result);
+        }
+        if self.seconds.abs() > i64::max_value() / 1000 {
+            return None;
+        }
+        let elapsed = Duration::seconds(self.seconds) + Duration::nanoseconds(self.nanoseconds);
+        let result = result.checked_add_signed(elapsed)?;
+        debug(format!(
+            "after time adjustment ({:+} seconds, {:+} ns),",
+            self.seconds, self.nanoseconds
+        ));
+        debug(format!(
+            "    new time = {} epoch-seconds",
+            result.timestamp()
+        ));
+        Some(result)
+    }
+
+    /// Describe the parsed items, filling in `year` where none was given.
+    fn describe(&self, year: i32, debug: &mut dyn FnMut(String)) {
+        if let Some(date) = self.date {
+            debug(format!(
+                "parsed date part: (Y-M-D) {:04}-{:02}-{:02}",
+                date.year.unwrap_or_else(|| i64::from(year)),
+                date.month,
+                date.day
+            ));
+        }
+        if let Some(time) = self.time {
+            let mut msg = format!(
+                "parsed time part: {:02}:{:02}:{:02}",
+                time.hour, time.minute, time.second
+            );
+            if time.nanosecond != 0 {
+                msg.push_str(&format!(".{:09}", time.nanosecond));
+            }
+            debug(msg);
+        }
+        if let Some(minutes) = self.zone {
+            debug(format!("parsed zone part: UTC{}", show_zone(minutes)));
+        }
+        if let Some((ordinal, weekday)) = self.weekday {
+            const DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+            debug(format!(
+                "parsed day part: {} (day ordinal={} number={})",
+                DAYS[weekday as usize], ordinal, weekday
+            ));
+        }
+        if self.relative {
+            let mut parts = Vec::new();
+            for &(value, unit) in &[
+                (self.months / 12, "year"),
+                (self.months % 12, "month"),
+                (self.days, "day"),
+                (self.seconds, "second"),
+                (self.nanoseconds, "nanosecond"),
+            ] {
+                if value != 0 {
+                    parts.push(format!("{:+} {}(s)", value, unit));
+                }
+            }
+            if parts.is_empty() {
+                parts.push("today/this/now".to_owned());
+            }
+            debug(format!("parsed relative part: {}", parts.join(" ")));
+        }
+        if let Some(ref time_zone) = self.time_zone {
+            debug(format!(
+                "input timezone: TZ=\"{}\" in date string",
+                time_zone
+            ));
+        }
+        if let Some(time) = self.time {
+            debug(format!(
+                "using specified time as starting value: '{:02}:{:02}:{:02}'",
+                time.hour, time.minute, time.second
+            ));
+        }
+    }
+}
+
+fn show_date(date: &NaiveDate) -> String {
+    format!(
+        "(Y-M-D) {:04}-{:02}-{:02}",
+        date.year(),
+        date.month(),
+        date.day()
+    )
+}
+
+fn show_time(time: &NaiveTime) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+fn show_datetime(datetime: &NaiveDateTime) -> String {
+    format!(
+        "{} {}",
+        show_date(&datetime.date()),
+        show_time(&datetime.time())
+    )
+}
+
+/// A UTC offset in minutes, as `+01` or `-03:30`.
+fn show_zone(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.abs();
+    if minutes % 60 == 0 {
+        format!("{}{:02}", sign, minutes / 60)
+    } else {
+        format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+    }
+}
+
+/// A duration of `days` days, if it is representable.
+fn days_duration(days: i64) -> Option<Duration> {
+    if days.abs() <= i64::max_value() / (24 * 60 * 60 * 1000) {
+        Some(Duration::days(days))
+    } else {
+        None
+    }
+}
+
+/// Interpret a local time in `tz`. Ambiguous times resolve to the earlier
+/// instant; times skipped by a clock change are invalid, unless they were
+/// reached through relative items, in which case they move forward.
+fn localize<Tz: TimeZone>(
+    tz: &Tz,
+    local: &NaiveDateTime,
+    relative: bool,
+) -> Option<DateTime<FixedOffset>> {
+    let datetime = match tz.from_local_datetime(local) {
+        // some time zones quietly normalize a skipped time, as mktime does
+        LocalResult::Single(datetime) if datetime.naive_local() == *local => datetime,
+        LocalResult::Ambiguous(earliest, _) => earliest,
+        _ if relative => return localize(tz, &(*local + Duration::hours(1)), false),
+        _ => return None,
+    };
+    let offset = datetime.offset().fix();
+    Some(datetime.with_timezone(&offset))
+}
@@ -9,35 +9,47 @@
 
 use std::time::Duration;
 
+/// The longest representable duration, used for `infinity`.
+pub const MAX: Duration = Duration::from_secs(u64::max_value());
+
+/// Parse a duration such as `10`, `1.5m` or `infinity`.
+///
+/// The number may be fractional and may be followed by one of the suffixes
+/// `s` (seconds, the default), `m` (minutes), `h` (hours) or `d` (days).
+/// Fractions of a nanosecond are rounded up, and durations too long to
+/// represent (including `inf` and `infinity`) saturate to [`MAX`].
 pub fn from_str(string: &str) -> Result<Duration, String> {
-    let len = string.len();
-    if len == 0 {
-        return Err("empty string".to_owned());
-    }
-    let slice = &string[..len - 1];
-    let (numstr, times) = match string.chars().next_back().unwrap() {
-        's' | 'S' => (slice, 1),
-        'm' | 'M' => (slice, 60),
-        'h' | 'H' => (slice, 60 * 60),
-        'd' | 'D' => (slice, 60 * 60 * 24),
-        val => {
-            if !val.is_alphabetic() {
-                (string, 1)
-            } else if string == "inf" || string == "infinity" {
-                ("inf", 1)
-            } else {
-                return Err(format!("invalid time interval '{}'", string));
-            }
-        }
+    let invalid = || format!("invalid time interval '{}'", string);
+
+    let (numstr, times) = match string.chars().next_back() {
+        Some('s') | Some('S') => (&string[..string.len() - 1], 1),
+        Some('m') | Some('M') => (&string[..string.len() - 1], 60),
+        Some('h') | Some('H') => (&string[..string.len() - 1], 60 * 60),
+        Some('d') | Some('D') => (&string[..string.len() - 1], 60 * 60 * 24),
+        Some(_) => (string, 1),
+        None => return Err(invalid()),
     };
-    let num = match numstr.parse::<f64>() {
-        Ok(m) => m,
-        Err(e) => return Err(format!("invalid time interval '{}': {}", string, e)),
+
+    let num = {
+        let lower = numstr.to_ascii_lowercase();
+        let unsigned = lower.trim_start_matches('+');
+        if unsigned == "inf" || unsigned == "infinity" {
+            return Ok(MAX);
+        }
+        match numstr.parse::<f64>() {
+            Ok(num) if num >= 0.0 => num * f64::from(times),
+            // rejects negative numbers and NaN
+            _ => return Err(invalid()),
+        }
     };
 
-    const NANOS_PER_SEC: u32 = 1_000_000_000;
-    let whole_secs = num.trunc();
-    let nanos = (num.fract() * (NANOS_PER_SEC as f64)).trunc();
-    let duration = Duration::new(whole_secs as u64, nanos as u32);
-    Ok(duration * times)
+    const NANOS_PER_SEC: f64 = 1_000_000_000.0;
+    if num >= u64::max_value() as f64 {
+        return Ok(MAX);
+    }
+    let whole_secs = num.trunc() as u64;
+    let nanos = (num.fract() * NANOS_PER_SEC).ceil() as u64;
+    Ok(Duration::from_secs(whole_secs)
+        .checked_add(Duration::from_nanos(nanos))
+        .unwrap_or(MAX))
 }
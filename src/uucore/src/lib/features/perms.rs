@@ -3,7 +3,12 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
+use crate::error_message;
 pub use crate::features::entries;
+use crate::features::entries::{Group, Locate, Passwd};
+use crate::features::safe_traversal::{
+    is_root, Entry, Error as WalkError, Symlinks, Visitor, Walker,
+};
 use libc::{self, gid_t, lchown, uid_t};
 
 use std::io::Error as IOError;
@@ -180,3 +185,341 @@ pub fn wrap_chown<P: AsRef<Path>>(
     }
     Ok(out)
 }
+
+/// Which files a `ChownExecutor` changes, by who owns them now.
+pub enum IfFrom {
+    All,
+    User(u32),
+    Group(u32),
+    UserGroup(u32, u32),
+}
+
+/// The user and group an owner spec names, with the names they were given by.
+pub struct OwnerSpec {
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub user: Option<String>,
+    pub group: Option<String>,
+}
+
+impl OwnerSpec {
+    /// The files owned by the user and group named here, as `--from` picks
+    /// them; either may be left out.
+    pub fn if_from(&self) -> IfFrom {
+        match (self.uid, self.gid) {
+            (Some(uid), Some(gid)) => IfFrom::UserGroup(uid, gid),
+            (Some(uid), None) => IfFrom::User(uid),
+            (None, Some(gid)) => IfFrom::Group(gid),
+            (None, None) => IfFrom::All,
+        }
+    }
+}
+
+/// Parse an owner spec as GNU does: `USER`, `USER:` (the user and their login
+/// group), `:GROUP` or `USER:GROUP`, with `separator` in place of the `:`.
+/// Each of the two may be a name or a number, and a number with a leading `+`
+/// is never looked up as a name.
+pub fn parse_spec(spec: &str, separator: char) -> Result<OwnerSpec, String> {
+    let (user, group) = match spec.find(separator) {
+        Some(i) => (&spec[..i], Some(&spec[i + 1..])),
+        None => (spec, None),
+    };
+
+    let mut parsed = OwnerSpec {
+        uid: None,
+        gid: None,
+        user: None,
+        group: None,
+    };
+    if !user.is_empty() {
+        match lookup_name::<Passwd>(user) {
+            Some(passwd) => {
+                parsed.uid = Some(passwd.uid());
+                if group == Some("") {
+                    parsed.gid = Some(passwd.gid());
+                    parsed.group = Some(
+                        entries::gid2grp(passwd.gid()).unwrap_or_else(|_| passwd.gid().to_string()),
+                    );
+                }
+            }
+            // without a name there's no login group to take
+            None if group == Some("") => return Err(format!("invalid spec: '{}'", spec)),
+            None => match parse_id(user) {
+                Some(id) => parsed.uid = Some(id),
+                None => return Err(format!("invalid user: '{}'", spec)),
+            },
+        }
+        parsed.user = Some(user.to_owned());
+    }
+    if let Some(group) = group.filter(|group| !group.is_empty()) {
+        match parse_group(group) {
+            Some(gid) => parsed.gid = Some(gid),
+            None => return Err(format!("invalid group: '{}'", spec)),
+        }
+        parsed.group = Some(group.to_owned());
+    }
+    Ok(parsed)
+}
+
+/// The ID of the group named `group`, or numbered so.
+pub fn parse_group(group: &str) -> Option<gid_t> {
+    match lookup_name::<Group>(group) {
+        Some(grp) => Some(grp.gid()),
+        None => parse_id(group),
+    }
+}
+
+/// Look up a user or group by name; numbers, with or without a `+`, are left
+/// to `parse_id`.
+fn lookup_name<T: for<'a> Locate<&'a str>>(name: &str) -> Option<T> {
+    if name.starts_with('+') || name.parse::<u32>().is_ok() {
+        None
+    } else {
+        T::locate(name).ok()
+    }
+}
+
+/// A numeric user or group ID; -1 means "unchanged" to chown(2), so it isn't
+/// one.
+fn parse_id(id: &str) -> Option<u32> {
+    let digits = if id.starts_with('+') { &id[1..] } else { id };
+    match digits.parse::<u32>() {
+        Ok(id) if id != u32::max_value() && !digits.starts_with('+') => Some(id),
+        _ => None,
+    }
+}
+
+/// Changes the owner and group of the files below some roots, as `chown` and
+/// `chgrp` do.
+pub struct ChownExecutor {
+    /// The name errors are reported under, `executable!()` of the utility.
+    pub util_name: &'static str,
+    pub dest_uid: Option<u32>,
+    pub dest_gid: Option<u32>,
+    /// The name the new owner is shown by. `chown` gives an empty one when
+    /// only a group is given, so that changes are reported as changes of
+    /// ownership rather than of group.
+    pub dest_user: Option<String>,
+    /// The name the new group is shown by.
+    pub dest_group: Option<String>,
+    pub symlinks: Symlinks,
+    pub verbosity: Verbosity,
+    pub filter: IfFrom,
+    pub files: Vec<String>,
+    pub recursive: bool,
+    pub preserve_root: bool,
+}
+
+/// How the change of a file went.
+enum Change {
+    Succeeded,
+    Failed,
+    NotRequested,
+}
+
+/// `USER:GROUP`, either alone, or nothing.
+fn user_group_str(user: Option<&str>, group: Option<&str>) -> Option<String> {
+    match (user, group) {
+        (Some(user), Some(group)) => Some(format!("{}:{}", user, group)),
+        (Some(user), None) => Some(user.to_owned()),
+        (None, Some(group)) => Some(group.to_owned()),
+        (None, None) => None,
+    }
+}
+
+impl ChownExecutor {
+    pub fn exec(&self) -> i32 {
+        let walker = Walker::new(self.symlinks).recursive(self.recursive);
+        let mut visitor = ChownVisitor {
+            executor: self,
+            status: 0,
+        };
+        for file in &self.files {
+            walker.walk(file, &mut visitor);
+        }
+        visitor.status
+    }
+
+    fn show_info(&self, msg: &str) {
+        eprintln!("{}: {}", self.util_name, msg);
+    }
+
+    fn matched(&self, uid: uid_t, gid: gid_t) -> bool {
+        match self.filter {
+            IfFrom::All => true,
+            IfFrom::User(u) => u == uid,
+            IfFrom::Group(g) => g == gid,
+            IfFrom::UserGroup(u, g) => u == uid && g == gid,
+        }
+    }
+
+    /// Report the change of `path`, given its owner and group before, if
+    /// they're known.
+    fn describe_change(&self, path: &Path, change: Change, old: Option<(uid_t, gid_t)>) {
+        let user = self.dest_user.as_deref();
+        let group = self.dest_group.as_deref();
+        let spec = user_group_str(user, group).unwrap_or_default();
+        let old_spec = old.and_then(|(uid, gid)| {
+            let old_user = entries::uid2usr(uid).unwrap_or_else(|_| uid.to_string());
+            let old_group = entries::gid2grp(gid).unwrap_or_else(|_| gid.to_string());
+            user_group_str(
+                user.map(|_| old_user.as_str()),
+                group.map(|_| old_group.as_str()),
+            )
+        });
+        let path = path.display();
+        if user.is_none() && group.is_none() {
+            match change {
+                Change::Succeeded => println!("no change to ownership of '{}'", path),
+                Change::Failed => println!("failed to change ownership of '{}'", path),
+                Change::NotRequested => println!("ownership of '{}' retained", path),
+            }
+            return;
+        }
+        let what = if user.is_some() { "ownership" } else { "group" };
+        match (change, old_spec) {
+            (Change::Succeeded, Some(old_spec)) => {
+                println!(
+                    "changed {} of '{}' from {} to {}",
+                    what, path, old_spec, spec
+                )
+            }
+            (Change::Failed, Some(old_spec)) => println!(
+                "failed to change {} of '{}' from {} to {}",
+                what, path, old_spec, spec
+            ),
+            (Change::Failed, None) => {
+                println!("failed to change {} of '{}' to {}", what, path, spec)
+            }
+            (Change::NotRequested, Some(old_spec)) => {
+                println!("{} of '{}' retained as {}", what, path, old_spec)
+            }
+            // the old owner is known whenever a change was made
+            (_, None) => {}
+        }
+    }
+}
+
+struct ChownVisitor<'a> {
+    executor: &'a ChownExecutor,
+    status: i32,
+}
+
+impl<'a> ChownVisitor<'a> {
+    fn change(&mut self, entry: &Entry) {
+        let executor = self.executor;
+        let verbosity = &executor.verbosity;
+        let old = Some((entry.uid(), entry.gid()));
+        if entry.is_dangling() {
+            if *verbosity != Verbosity::Silent {
+                executor.show_info(&format!(
+                    "cannot dereference '{}': No such file or directory",
+                    entry.path().display()
+                ));
+            }
+            if *verbosity == Verbosity::Verbose {
+                executor.describe_change(entry.path(), Change::Failed, old);
+            }
+            self.status = 1;
+            return;
+        }
+
+        if !executor.matched(entry.uid(), entry.gid()) {
+            if *verbosity == Verbosity::Verbose {
+                executor.describe_change(entry.path(), Change::NotRequested, old);
+            }
+            return;
+        }
+        if let Err(e) = entry.chown(executor.dest_uid, executor.dest_gid) {
+            if *verbosity != Verbosity::Silent {
+                executor.show_info(&format!(
+                    "changing {} of '{}': {}",
+                    if executor.dest_uid.is_some() {
+                        "ownership"
+                    } else {
+                        "group"
+                    },
+                    entry.path().display(),
+                    error_message(&e)
+                ));
+            }
+            if *verbosity == Verbosity::Verbose {
+                executor.describe_change(entry.path(), Change::Failed, old);
+            }
+            self.status = 1;
+            return;
+        }
+        let changed = executor.dest_uid.map_or(false, |uid| uid != entry.uid())
+            || executor.dest_gid.map_or(false, |gid| gid != entry.gid());
+        match verbosity {
+            Verbosity::Changes | Verbosity::Verbose if changed => {
+                executor.describe_change(entry.path(), Change::Succeeded, old)
+            }
+            Verbosity::Verbose => executor.describe_change(entry.path(), Change::NotRequested, old),
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Visitor for ChownVisitor<'a> {
+    fn visit(&mut self, entry: &Entry) -> bool {
+        let executor = self.executor;
+        if !executor.recursive {
+            self.change(entry);
+            return false;
+        }
+        if entry.depth() == 0 && executor.preserve_root && entry.is_dir() && is_root(entry.path()) {
+            if entry.path() == Path::new("/") {
+                executor.show_info("it is dangerous to operate recursively on '/'");
+            } else {
+                executor.show_info(&format!(
+                    "it is dangerous to operate recursively on '{}' (same as '/')",
+                    entry.path().display()
+                ));
+            }
+            executor.show_info("use --no-preserve-root to override this failsafe");
+            self.status = 1;
+            return false;
+        }
+        // like GNU, directories change once everything in them has
+        if !entry.is_dir() {
+            self.change(entry);
+        }
+        true
+    }
+
+    fn leave(&mut self, entry: &Entry) {
+        self.change(entry);
+    }
+
+    fn error(&mut self, path: &Path, err: WalkError) {
+        let executor = self.executor;
+        let silent = executor.verbosity == Verbosity::Silent;
+        match err {
+            WalkError::Access(e) => {
+                if !silent {
+                    executor.show_info(&format!(
+                        "cannot access '{}': {}",
+                        path.display(),
+                        error_message(&e)
+                    ));
+                }
+                if executor.verbosity == Verbosity::Verbose {
+                    executor.describe_change(path, Change::Failed, None);
+                }
+            }
+            WalkError::ReadDir(e) => {
+                if !silent {
+                    executor.show_info(&format!(
+                        "cannot read directory '{}': {}",
+                        path.display(),
+                        error_message(&e)
+                    ));
+                }
+            }
+            // like GNU, a loop met by following links is just not entered again
+            WalkError::Cycle => return,
+        }
+        self.status = 1;
+    }
+}
@@ -91,7 +91,11 @@ pub trait ChildExt {
     /// Send a signal to a Child process.
     fn send_signal(&mut self, signal: usize) -> io::Result<()>;
 
+    /// Send a signal to the process group led by a Child process.
+    fn send_signal_group(&mut self, signal: usize) -> io::Result<()>;
+
     /// Wait for a process to finish or return after the specified duration.
+    /// A zero duration waits for as long as the process runs.
     fn wait_or_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>>;
 }
 
@@ -104,10 +108,24 @@ impl ChildExt for Child {
         }
     }
 
+    fn send_signal_group(&mut self, signal: usize) -> io::Result<()> {
+        if unsafe { libc::kill(-(self.id() as pid_t), signal as i32) } != 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
     fn wait_or_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
         // .try_wait() doesn't drop stdin, so we do it manually
         drop(self.stdin.take());
 
+        if timeout == Duration::new(0, 0) {
+            return self
+                .wait()
+                .map(|status| Some(ExitStatus::from_std_status(status)));
+        }
+
         let start = Instant::now();
         loop {
             if let Some(status) = self.try_wait()? {
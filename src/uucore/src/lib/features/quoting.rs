@@ -0,0 +1,307 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Quoting of file names for display, in the styles of GNU's `quotearg`.
+//!
+//! The shell styles give names that can be pasted back into a shell; the
+//! escape styles give C-like escapes. Characters that can't be shown, and
+//! bytes that aren't UTF-8, are written as octal escapes where the style
+//! has escapes at all.
+
+// spell-checker:ignore (vars) clocale
+
+use std::ffi::OsStr;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QuotingStyle {
+    /// the name as it is
+    Literal,
+    /// single quotes, where the shell needs them
+    Shell,
+    /// single quotes, always
+    ShellAlways,
+    /// like `Shell`, with `$'...'` for characters that can't be shown
+    ShellEscape,
+    /// like `ShellAlways`, with `$'...'` for characters that can't be shown
+    ShellEscapeAlways,
+    /// a C string in double quotes
+    C,
+    /// C escapes without quotes
+    Escape,
+    /// C escapes in single quotes
+    Locale,
+    /// C escapes in double quotes
+    CLocale,
+}
+
+impl FromStr for QuotingStyle {
+    type Err = ();
+
+    /// Parse a style by the name GNU's `--quoting-style` and `QUOTING_STYLE`
+    /// give it.
+    fn from_str(name: &str) -> Result<QuotingStyle, ()> {
+        match name {
+            "literal" => Ok(QuotingStyle::Literal),
+            "shell" => Ok(QuotingStyle::Shell),
+            "shell-always" => Ok(QuotingStyle::ShellAlways),
+            "shell-escape" => Ok(QuotingStyle::ShellEscape),
+            "shell-escape-always" => Ok(QuotingStyle::ShellEscapeAlways),
+            "c" => Ok(QuotingStyle::C),
+            "escape" => Ok(QuotingStyle::Escape),
+            "locale" => Ok(QuotingStyle::Locale),
+            "clocale" => Ok(QuotingStyle::CLocale),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A piece of a name: a character, or a byte that isn't part of one.
+#[derive(Clone, Copy)]
+enum Unit {
+    Char(char),
+    Byte(u8),
+}
+
+impl Unit {
+    fn is_printable(self) -> bool {
+        match self {
+            Unit::Char(c) => !c.is_control(),
+            Unit::Byte(_) => false,
+        }
+    }
+
+    /// The C escape for this unit, octal for those without a letter.
+    fn escaped(self, out: &mut String) {
+        let c = match self {
+            Unit::Char(c) => c,
+            Unit::Byte(b) => return out.push_str(&format!("\\{:03o}", b)),
+        };
+        match c {
+            '\x07' => out.push_str("\\a"),
+            '\x08' => out.push_str("\\b"),
+            '\x0C' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\x0B' => out.push_str("\\v"),
+            c => {
+                let mut buf = [0; 4];
+                for b in c.encode_utf8(&mut buf).bytes() {
+                    out.push_str(&format!("\\{:03o}", b));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn units(name: &OsStr) -> Vec<Unit> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut bytes = name.as_bytes();
+    let mut units = Vec::with_capacity(bytes.len());
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(s) => {
+                units.extend(s.chars().map(Unit::Char));
+                return units;
+            }
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                units.extend(std::str::from_utf8(valid).unwrap().chars().map(Unit::Char));
+                let bad = e.error_len().unwrap_or(rest.len());
+                units.extend(rest[..bad].iter().map(|&b| Unit::Byte(b)));
+                bytes = &rest[bad..];
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn units(name: &OsStr) -> Vec<Unit> {
+    name.to_string_lossy().chars().map(Unit::Char).collect()
+}
+
+/// Quote `name` for display in the given style.
+pub fn quote<S: AsRef<OsStr>>(name: S, style: QuotingStyle) -> String {
+    let name = name.as_ref();
+    match style {
+        QuotingStyle::Literal => name.to_string_lossy().into_owned(),
+        QuotingStyle::Shell => quote_shell(&units(name), false, false),
+        QuotingStyle::ShellAlways => quote_shell(&units(name), true, false),
+        QuotingStyle::ShellEscape => quote_shell(&units(name), false, true),
+        QuotingStyle::ShellEscapeAlways => quote_shell(&units(name), true, true),
+        QuotingStyle::C | QuotingStyle::CLocale => quote_c(&units(name), Some('"')),
+        QuotingStyle::Escape => quote_c(&units(name), None),
+        QuotingStyle::Locale => quote_c(&units(name), Some('\'')),
+    }
+}
+
+fn quote_c(units: &[Unit], quote: Option<char>) -> String {
+    let mut out = String::with_capacity(units.len() + 2);
+    out.extend(quote);
+    for &unit in units {
+        match unit {
+            Unit::Char(c) if c == '\\' || Some(c) == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            Unit::Char(c) if unit.is_printable() => out.push(c),
+            _ => unit.escaped(&mut out),
+        }
+    }
+    out.extend(quote);
+    out
+}
+
+/// Whether the shell would take `c`, at `index` in a name of `len`
+/// characters, for something other than itself.
+fn is_shell_special(c: char, index: usize, len: usize) -> bool {
+    match c {
+        ' ' | '\t' | '\n' | '!' | '"' | '$' | '&' | '\'' | '(' | ')' | '*' | ';' | '<' | '='
+        | '>' | '?' | '[' | '\\' | ']' | '^' | '`' | '|' => true,
+        '#' | '~' => index == 0,
+        '{' | '}' => len == 1,
+        c => c.is_control(),
+    }
+}
+
+fn quote_shell(units: &[Unit], always: bool, escape: bool) -> String {
+    let needs_quotes = units.is_empty()
+        || units.iter().enumerate().any(|(i, &unit)| match unit {
+            Unit::Char(c) => is_shell_special(c, i, units.len()),
+            Unit::Byte(_) => true,
+        });
+    let shown = |unit: Unit| match unit {
+        Unit::Char(c) => c,
+        Unit::Byte(_) => '?',
+    };
+    if !needs_quotes && !always {
+        return units.iter().map(|&unit| shown(unit)).collect();
+    }
+
+    let has_escapes = escape && units.iter().any(|&unit| !unit.is_printable());
+    let has_single_quote = units.iter().any(|&unit| match unit {
+        Unit::Char(c) => c == '\'',
+        Unit::Byte(_) => false,
+    });
+    // a name with a single quote reads better in double quotes, as long as
+    // nothing in it means something there
+    if has_single_quote && !has_escapes {
+        let fits_double_quotes = units.iter().all(|&unit| match unit {
+            Unit::Char(c) => !"\"$`\\!".contains(c),
+            Unit::Byte(_) => true,
+        });
+        if fits_double_quotes {
+            let mut out = String::with_capacity(units.len() + 2);
+            out.push('"');
+            out.extend(units.iter().map(|&unit| shown(unit)));
+            out.push('"');
+            return out;
+        }
+    }
+
+    let mut out = String::with_capacity(units.len() + 2);
+    out.push('\'');
+    let mut in_quotes = true;
+    let mut in_escape = false;
+    for &unit in units {
+        if escape && !unit.is_printable() {
+            if in_escape {
+                // join this escape onto the one before
+                out.pop();
+            } else {
+                if in_quotes {
+                    out.push('\'');
+                    in_quotes = false;
+                }
+                out.push_str("$'");
+            }
+            unit.escaped(&mut out);
+            out.push('\'');
+            in_escape = true;
+            continue;
+        }
+        if !in_quotes {
+            out.push('\'');
+            in_quotes = true;
+            in_escape = false;
+        }
+        match unit {
+            Unit::Char('\'') => out.push_str("'\\''"),
+            unit => out.push(shown(unit)),
+        }
+    }
+    if in_quotes {
+        out.push('\'');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell_escape(name: &str) -> String {
+        quote(name, QuotingStyle::ShellEscapeAlways)
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Ok(QuotingStyle::C), "c".parse());
+        assert_eq!(
+            Ok(QuotingStyle::ShellEscapeAlways),
+            "shell-escape-always".parse()
+        );
+        assert_eq!(Err(()), "bogus".parse::<QuotingStyle>());
+    }
+
+    #[test]
+    fn test_shell() {
+        assert_eq!("abc", quote("abc", QuotingStyle::Shell));
+        assert_eq!("''", quote("", QuotingStyle::Shell));
+        assert_eq!("'a b'", quote("a b", QuotingStyle::Shell));
+        assert_eq!("'~x'", quote("~x", QuotingStyle::Shell));
+        assert_eq!("x~", quote("x~", QuotingStyle::Shell));
+        assert_eq!("'abc'", quote("abc", QuotingStyle::ShellAlways));
+        assert_eq!("'a\nb'", quote("a\nb", QuotingStyle::Shell));
+    }
+
+    #[test]
+    fn test_shell_single_quote() {
+        assert_eq!("\"it's\"", shell_escape("it's"));
+        assert_eq!("'it'\\''s $x'", shell_escape("it's $x"));
+    }
+
+    #[test]
+    fn test_shell_escape() {
+        assert_eq!("'nl'$'\\n''x'", shell_escape("nl\nx"));
+        assert_eq!("'tab'$'\\t'", shell_escape("tab\t"));
+        assert_eq!("''$'\\001\\002''x'", shell_escape("\x01\x02x"));
+        assert_eq!("'it'\\''s'$'\\n'", shell_escape("it's\n"));
+        assert_eq!("'a'$'\\n'", quote("a\n", QuotingStyle::ShellEscape));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_invalid_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = OsStr::from_bytes(b"a\xffb");
+        assert_eq!("'a'$'\\377''b'", quote(name, QuotingStyle::ShellEscape));
+        assert_eq!("'a?b'", quote(name, QuotingStyle::Shell));
+        assert_eq!("\"a\\377b\"", quote(name, QuotingStyle::C));
+    }
+
+    #[test]
+    fn test_c_styles() {
+        assert_eq!("\"a\\\"b\\\\\\n\"", quote("a\"b\\\n", QuotingStyle::C));
+        assert_eq!("a b\\t", quote("a b\t", QuotingStyle::Escape));
+        assert_eq!("'it\\'s'", quote("it's", QuotingStyle::Locale));
+        assert_eq!("\"it's\"", quote("it's", QuotingStyle::CLocale));
+        assert_eq!("it's", quote("it's", QuotingStyle::Literal));
+    }
+}
@@ -0,0 +1,468 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Recursive traversal of directory trees through directory file descriptors.
+//!
+//! Every file below a root is looked up relative to an open descriptor of the
+//! directory holding it, never by its full path, and a directory is only
+//! entered once its descriptor is known to be the directory that was
+//! examined. Replacing a directory with a symbolic link while a tree is being
+//! walked therefore can't send the walk, or the changes made along it,
+//! somewhere else.
+//!
+//! The walk keeps the directories it is in on a stack of its own rather than
+//! recursing, and holds only the innermost few of them open, so that neither
+//! the stack nor the descriptors run out however deep a tree goes.
+
+// spell-checker:ignore (libc) closedir dirent fchmodat fchownat fdopendir fstatat openat readdir unlinkat
+
+use libc::{self, c_int, gid_t, mode_t, uid_t};
+
+use std::collections::HashSet;
+use std::ffi::{CStr, CString, OsStr};
+use std::io::{Error as IOError, ErrorKind, Result as IOResult};
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
+use std::path::{Path, PathBuf};
+use std::vec;
+
+/// How many of the directories being walked are kept open at once; the
+/// others are opened again when the walk gets back to them.
+const MAX_OPEN: usize = 32;
+
+/// Which symbolic links to directories a walk follows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symlinks {
+    /// none (`-P`)
+    Physical,
+    /// those given on the command line (`-H`)
+    CommandLine,
+    /// all of them (`-L`)
+    Logical,
+}
+
+/// What went wrong at some point of a walk.
+#[derive(Debug)]
+pub enum Error {
+    /// the file couldn't be examined
+    Access(IOError),
+    /// the directory couldn't be opened or read
+    ReadDir(IOError),
+    /// the directory is one of those it's in, through a symbolic link
+    Cycle,
+}
+
+/// An open directory, closed when dropped.
+struct DirFd {
+    fd: RawFd,
+}
+
+impl DirFd {
+    /// Open the directory `name` in `parent`, making sure it's the one `stat`
+    /// describes.
+    fn open(parent: RawFd, name: &CStr, follow: bool, stat: &libc::stat) -> IOResult<DirFd> {
+        let mut flags = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC;
+        if !follow {
+            flags |= libc::O_NOFOLLOW;
+        }
+        let fd = unsafe { libc::openat(parent, name.as_ptr(), flags) };
+        if fd < 0 {
+            return Err(IOError::last_os_error());
+        }
+        let dir = DirFd { fd };
+        let opened = fstat(fd)?;
+        if (opened.st_dev, opened.st_ino) != (stat.st_dev, stat.st_ino) {
+            return Err(IOError::new(
+                ErrorKind::Other,
+                "directory changed during traversal",
+            ));
+        }
+        Ok(dir)
+    }
+
+    /// The names in this directory, other than `.` and `..`.
+    fn names(&self) -> IOResult<Vec<CString>> {
+        // the stream takes over the descriptor it's given, so give it a copy
+        let fd = unsafe { libc::dup(self.fd) };
+        if fd < 0 {
+            return Err(IOError::last_os_error());
+        }
+        let stream = unsafe { libc::fdopendir(fd) };
+        if stream.is_null() {
+            let err = IOError::last_os_error();
+            unsafe { libc::close(fd) };
+            return Err(err);
+        }
+        let mut names = Vec::new();
+        loop {
+            let entry = unsafe { libc::readdir(stream) };
+            if entry.is_null() {
+                break;
+            }
+            let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+            match name.to_bytes() {
+                b"." | b".." => {}
+                _ => names.push(name.to_owned()),
+            }
+        }
+        unsafe { libc::closedir(stream) };
+        Ok(names)
+    }
+}
+
+impl Drop for DirFd {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+fn fstatat(dirfd: RawFd, name: &CStr, flags: c_int) -> IOResult<libc::stat> {
+    let mut stat: libc::stat = unsafe { mem::zeroed() };
+    check(unsafe { libc::fstatat(dirfd, name.as_ptr(), &mut stat, flags) })?;
+    Ok(stat)
+}
+
+fn fstat(fd: RawFd) -> IOResult<libc::stat> {
+    let mut stat: libc::stat = unsafe { mem::zeroed() };
+    check(unsafe { libc::fstat(fd, &mut stat) })?;
+    Ok(stat)
+}
+
+fn check(ret: c_int) -> IOResult<()> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(IOError::last_os_error())
+    }
+}
+
+/// A file met on a walk, as it was when it was examined.
+pub struct Entry {
+    /// the directory the file is in, or `AT_FDCWD` for a root
+    dirfd: RawFd,
+    name: CString,
+    path: PathBuf,
+    depth: usize,
+    stat: libc::stat,
+    followed: bool,
+    dangling: bool,
+}
+
+impl Entry {
+    /// The path of the file, the root given to the walk joined with the
+    /// names below it.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How far below the root the file is; the root itself is at depth 0.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Whether the name is a symbolic link that was followed, so that the
+    /// entry describes what it points to.
+    pub fn followed(&self) -> bool {
+        self.followed
+    }
+
+    /// Whether this is a symbolic link the walk would have followed, but
+    /// whose target doesn't exist.
+    pub fn is_dangling(&self) -> bool {
+        self.dangling
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.stat.st_mode & libc::S_IFMT == libc::S_IFDIR
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.stat.st_mode & libc::S_IFMT == libc::S_IFLNK
+    }
+
+    /// The mode of the file, its type bits included.
+    #[allow(clippy::unnecessary_cast)] // the type differs between platforms
+    pub fn mode(&self) -> u32 {
+        self.stat.st_mode as u32
+    }
+
+    pub fn uid(&self) -> uid_t {
+        self.stat.st_uid
+    }
+
+    pub fn gid(&self) -> gid_t {
+        self.stat.st_gid
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn dev(&self) -> u64 {
+        self.stat.st_dev as u64
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn ino(&self) -> u64 {
+        self.stat.st_ino as u64
+    }
+
+    /// Change the permissions of the file, or of the file a symbolic link
+    /// points to.
+    pub fn chmod(&self, mode: u32) -> IOResult<()> {
+        check(unsafe { libc::fchmodat(self.dirfd, self.name.as_ptr(), mode as mode_t, 0) })
+    }
+
+    /// Change the owner and group of the file, those of a symbolic link
+    /// itself unless it was followed. `None` leaves either as it is.
+    pub fn chown(&self, uid: Option<uid_t>, gid: Option<gid_t>) -> IOResult<()> {
+        let flags = if self.followed {
+            0
+        } else {
+            libc::AT_SYMLINK_NOFOLLOW
+        };
+        check(unsafe {
+            libc::fchownat(
+                self.dirfd,
+                self.name.as_ptr(),
+                uid.unwrap_or_else(|| (0 as uid_t).wrapping_sub(1)),
+                gid.unwrap_or_else(|| (0 as gid_t).wrapping_sub(1)),
+                flags,
+            )
+        })
+    }
+
+    /// Remove the file, or the directory once it's empty.
+    pub fn remove(&self) -> IOResult<()> {
+        // a followed link is removed, not the directory it points to
+        let flags = if self.is_dir() && !self.followed {
+            libc::AT_REMOVEDIR
+        } else {
+            0
+        };
+        check(unsafe { libc::unlinkat(self.dirfd, self.name.as_ptr(), flags) })
+    }
+}
+
+/// What a walk does with the files it meets.
+pub trait Visitor {
+    /// Called for every file, and for a directory before anything in it. A
+    /// directory is only entered when this returns true.
+    fn visit(&mut self, entry: &Entry) -> bool;
+
+    /// Called for a directory that was to be entered, once everything in it
+    /// has been visited, or once it turned out it can't be read.
+    fn leave(&mut self, _entry: &Entry) {}
+
+    /// Called for a file that can't be examined, or a directory that can't
+    /// be entered or read.
+    fn error(&mut self, path: &Path, err: Error);
+}
+
+/// A depth-first walk of the trees below some roots.
+pub struct Walker {
+    symlinks: Symlinks,
+    recursive: bool,
+}
+
+impl Walker {
+    /// A walk that follows the symbolic links `symlinks` says, and visits
+    /// only the roots themselves.
+    pub fn new(symlinks: Symlinks) -> Walker {
+        Walker {
+            symlinks,
+            recursive: false,
+        }
+    }
+
+    /// Whether to enter directories.
+    pub fn recursive(mut self, recursive: bool) -> Walker {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Whether symbolic links met at `depth` are followed.
+    fn follows(&self, depth: usize) -> bool {
+        match self.symlinks {
+            Symlinks::Physical => false,
+            Symlinks::CommandLine => depth == 0,
+            Symlinks::Logical => true,
+        }
+    }
+
+    /// Walk the tree below `root`, which names a file relative to the
+    /// current directory.
+    pub fn walk<P: AsRef<Path>, V: Visitor>(&self, root: P, visitor: &mut V) {
+        let root = root.as_ref();
+        let name = match CString::new(root.as_os_str().as_bytes()) {
+            Ok(name) => name,
+            Err(err) => return visitor.error(root, Error::Access(err.into())),
+        };
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut ancestors = HashSet::new();
+        let root = self.visit(
+            libc::AT_FDCWD,
+            name,
+            root.to_path_buf(),
+            0,
+            &ancestors,
+            visitor,
+        );
+        if let Some(frame) = root {
+            ancestors.insert(frame.id());
+            stack.push(frame);
+        }
+
+        while let Some(top) = stack.last_mut() {
+            match top.names.next() {
+                Some(name) => {
+                    // the innermost directory is always open
+                    let dirfd = top.dir.as_ref().unwrap().fd;
+                    let path = top.entry.path.join(OsStr::from_bytes(name.to_bytes()));
+                    let depth = top.entry.depth + 1;
+                    if let Some(frame) = self.visit(dirfd, name, path, depth, &ancestors, visitor) {
+                        ancestors.insert(frame.id());
+                        stack.push(frame);
+                        if stack.len() > MAX_OPEN {
+                            let index = stack.len() - 1 - MAX_OPEN;
+                            stack[index].dir = None;
+                        }
+                    }
+                }
+                None => {
+                    let frame = stack.pop().unwrap();
+                    ancestors.remove(&frame.id());
+                    let mut entry = frame.entry;
+                    if let Some(index) = stack.len().checked_sub(1) {
+                        if stack[index].dir.is_none() {
+                            match reopen(frame.dir.as_ref().unwrap(), &stack, index) {
+                                Ok(dir) => stack[index].dir = Some(dir),
+                                Err(err) => {
+                                    let parent = &mut stack[index];
+                                    visitor.error(&parent.entry.path, Error::ReadDir(err));
+                                    parent.names = Vec::new().into_iter();
+                                }
+                            }
+                        }
+                        entry.dirfd = stack[index].dir.as_ref().map_or(-1, |dir| dir.fd);
+                    }
+                    drop(frame.dir);
+                    visitor.leave(&entry);
+                }
+            }
+        }
+    }
+
+    /// Visit the file `name` in `dirfd`, and give the directory to walk
+    /// next if it is one to enter.
+    fn visit<V: Visitor>(
+        &self,
+        dirfd: RawFd,
+        name: CString,
+        path: PathBuf,
+        depth: usize,
+        ancestors: &HashSet<(u64, u64)>,
+        visitor: &mut V,
+    ) -> Option<Frame> {
+        let mut stat = match fstatat(dirfd, &name, libc::AT_SYMLINK_NOFOLLOW) {
+            Ok(stat) => stat,
+            Err(err) => {
+                visitor.error(&path, Error::Access(err));
+                return None;
+            }
+        };
+        let mut followed = false;
+        let mut dangling = false;
+        if stat.st_mode & libc::S_IFMT == libc::S_IFLNK && self.follows(depth) {
+            // a link whose target is gone is visited as the link itself
+            match fstatat(dirfd, &name, 0) {
+                Ok(target) => {
+                    stat = target;
+                    followed = true;
+                }
+                Err(_) => dangling = true,
+            }
+        }
+        let entry = Entry {
+            dirfd,
+            name,
+            path,
+            depth,
+            stat,
+            followed,
+            dangling,
+        };
+
+        if !visitor.visit(&entry) || !self.recursive || !entry.is_dir() {
+            return None;
+        }
+        if ancestors.contains(&(entry.dev(), entry.ino())) {
+            visitor.error(&entry.path, Error::Cycle);
+            return None;
+        }
+        let opened = DirFd::open(dirfd, &entry.name, entry.followed, &entry.stat)
+            .and_then(|dir| dir.names().map(|names| (dir, names)));
+        match opened {
+            Ok((dir, names)) => Some(Frame {
+                entry,
+                dir: Some(dir),
+                names: names.into_iter(),
+            }),
+            Err(err) => {
+                visitor.error(&entry.path, Error::ReadDir(err));
+                visitor.leave(&entry);
+                None
+            }
+        }
+    }
+}
+
+/// A directory the walk is in, with the names in it that are left to visit.
+struct Frame {
+    entry: Entry,
+    /// `None` while it is closed, to save descriptors
+    dir: Option<DirFd>,
+    names: vec::IntoIter<CString>,
+}
+
+impl Frame {
+    fn id(&self) -> (u64, u64) {
+        (self.entry.dev(), self.entry.ino())
+    }
+}
+
+/// Open the directory of `stack[index]` again, as the one `child` is in.
+/// One that `child` was reached from through a symbolic link is opened from
+/// the nearest directory before it that is still open instead, one name at
+/// a time.
+fn reopen(child: &DirFd, stack: &[Frame], index: usize) -> IOResult<DirFd> {
+    let dotdot = CString::new("..").unwrap();
+    DirFd::open(child.fd, &dotdot, false, &stack[index].entry.stat).or_else(|_| {
+        let open = stack[..index].iter().rposition(|frame| frame.dir.is_some());
+        let mut fd = open.map_or(libc::AT_FDCWD, |i| stack[i].dir.as_ref().unwrap().fd);
+        let mut dir = None;
+        for frame in &stack[open.map_or(0, |i| i + 1)..=index] {
+            let entry = &frame.entry;
+            let opened = DirFd::open(fd, &entry.name, entry.followed, &entry.stat)?;
+            fd = opened.fd;
+            dir = Some(opened);
+        }
+        Ok(dir.unwrap())
+    })
+}
+
+/// Whether `path` names the root directory, following symbolic links.
+pub fn is_root<P: AsRef<Path>>(path: P) -> bool {
+    let path = match CString::new(path.as_ref().as_os_str().as_bytes()) {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+    let root = CString::new("/").unwrap();
+    match (
+        fstatat(libc::AT_FDCWD, &path, 0),
+        fstatat(libc::AT_FDCWD, &root, 0),
+    ) {
+        (Ok(file), Ok(root)) => (file.st_dev, file.st_ino) == (root.st_dev, root.st_ino),
+        _ => false,
+    }
+}
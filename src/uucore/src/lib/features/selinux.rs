@@ -0,0 +1,239 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! SELinux security contexts, of processes and of files, read and set
+//! through the interfaces the kernel provides for them, `/proc`, `selinuxfs`
+//! and extended attributes.
+
+// spell-checker:ignore (ToDO) selinuxfs getxattr lgetxattr setxattr lsetxattr ENODATA
+
+use std::ffi::CString;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{Error as IOError, Read, Result as IOResult, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Where the kernel exposes SELinux to userland.
+static SELINUXFS: &str = "/sys/fs/selinux";
+
+/// The extended attribute a file's context is kept in.
+static XATTR_NAME: &str = "security.selinux";
+
+/// A part of a security context that can be set on its own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Component {
+    User,
+    Role,
+    Type,
+    Range,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Component::User => "user",
+            Component::Role => "role",
+            Component::Type => "type",
+            Component::Range => "range",
+        })
+    }
+}
+
+/// A security context, as "user:role:type" with an optional ":range", which
+/// may have colons of its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Context {
+    user: String,
+    role: String,
+    kind: String,
+    range: Option<String>,
+}
+
+impl Context {
+    /// Split up `context`, failing with `EINVAL` if it isn't one.
+    pub fn parse(context: &str) -> IOResult<Context> {
+        let mut parts = context.splitn(4, ':');
+        let (user, role, kind) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(user), Some(role), Some(kind)) => (user, role, kind),
+            _ => return Err(invalid()),
+        };
+        let mut context = Context {
+            user: String::new(),
+            role: String::new(),
+            kind: String::new(),
+            range: None,
+        };
+        context.set(Component::User, user)?;
+        context.set(Component::Role, role)?;
+        context.set(Component::Type, kind)?;
+        if let Some(range) = parts.next() {
+            context.set(Component::Range, range)?;
+        }
+        Ok(context)
+    }
+
+    /// Replace `component` with `value`, failing with `EINVAL` if it can't
+    /// be one.
+    pub fn set(&mut self, component: Component, value: &str) -> IOResult<()> {
+        let is_range = component == Component::Range;
+        if value
+            .chars()
+            .any(|c| c == '\t' || c == '\n' || c == '\r' || (!is_range && (c == ':' || c == ' ')))
+        {
+            return Err(invalid());
+        }
+        let value = value.to_owned();
+        match component {
+            Component::User => self.user = value,
+            Component::Role => self.role = value,
+            Component::Type => self.kind = value,
+            Component::Range => self.range = Some(value),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.user, self.role, self.kind)?;
+        if let Some(range) = &self.range {
+            write!(f, ":{}", range)?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid() -> IOError {
+    IOError::from_raw_os_error(libc::EINVAL)
+}
+
+/// Whether the kernel runs SELinux.
+pub fn is_enabled() -> bool {
+    cfg!(target_os = "linux") && Path::new(SELINUXFS).join("enforce").exists()
+}
+
+/// A context as the kernel hands it out, without the terminating NUL.
+fn context_from(bytes: &[u8]) -> String {
+    let context = String::from_utf8_lossy(bytes);
+    context.trim_end_matches(&['\0', '\n'][..]).to_owned()
+}
+
+fn path_cstring(path: &Path) -> IOResult<CString> {
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| invalid())
+}
+
+/// The security context of the process.
+pub fn current_context() -> IOResult<String> {
+    fs::read("/proc/self/attr/current").map(|context| context_from(&context))
+}
+
+/// The security context of the file at `path`, or of the symbolic link
+/// itself unless `follow`. A file without one fails with `ENODATA`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn file_context<P: AsRef<Path>>(path: P, follow: bool) -> IOResult<String> {
+    let get = if follow {
+        libc::getxattr
+    } else {
+        libc::lgetxattr
+    };
+    let path = path_cstring(path.as_ref())?;
+    let name = CString::new(XATTR_NAME).unwrap();
+    let mut buf = vec![0_u8; 256];
+    loop {
+        let len = unsafe {
+            get(
+                path.as_ptr(),
+                name.as_ptr(),
+                buf.as_mut_ptr() as *mut _,
+                buf.len(),
+            )
+        };
+        if len >= 0 {
+            buf.truncate(len as usize);
+            return Ok(context_from(&buf));
+        }
+        let err = IOError::last_os_error();
+        if err.raw_os_error() != Some(libc::ERANGE) {
+            return Err(err);
+        }
+        let len = buf.len() * 2;
+        buf.resize(len, 0);
+    }
+}
+
+/// Give the file at `path`, or the symbolic link itself unless `follow`, the
+/// security context `context`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_file_context<P: AsRef<Path>>(path: P, context: &str, follow: bool) -> IOResult<()> {
+    let set = if follow {
+        libc::setxattr
+    } else {
+        libc::lsetxattr
+    };
+    let path = path_cstring(path.as_ref())?;
+    let name = CString::new(XATTR_NAME).unwrap();
+    let value = CString::new(context).map_err(|_| invalid())?;
+    let value = value.as_bytes_with_nul();
+    let ret = unsafe {
+        set(
+            path.as_ptr(),
+            name.as_ptr(),
+            value.as_ptr() as *const _,
+            value.len(),
+            0,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(IOError::last_os_error())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn file_context<P: AsRef<Path>>(_path: P, _follow: bool) -> IOResult<String> {
+    Err(IOError::from_raw_os_error(libc::ENOTSUP))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn set_file_context<P: AsRef<Path>>(_path: P, _context: &str, _follow: bool) -> IOResult<()> {
+    Err(IOError::from_raw_os_error(libc::ENOTSUP))
+}
+
+/// The context an object of class `class` gets when a process of context
+/// `source` creates it for one of context `target`; for the class
+/// "process", when the process runs a file, as the policy has it.
+pub fn compute_create(source: &str, target: &str, class: &str) -> IOResult<String> {
+    let index = Path::new(SELINUXFS).join("class").join(class).join("index");
+    let index: u16 = fs::read_to_string(index)?
+        .trim()
+        .parse()
+        .map_err(|_| invalid())?;
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(Path::new(SELINUXFS).join("create"))?;
+    file.write_all(format!("{} {} {}", source, target, index).as_bytes())?;
+    let mut context = Vec::new();
+    file.read_to_end(&mut context)?;
+    Ok(context_from(&context))
+}
+
+/// Whether the policy knows `context`.
+pub fn check_context(context: &str) -> IOResult<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(Path::new(SELINUXFS).join("context"))?;
+    file.write_all(format!("{}\0", context).as_bytes())
+}
+
+/// Have the next program this process runs get `context`.
+pub fn set_exec_context(context: &str) -> IOResult<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open("/proc/self/attr/exec")?;
+    file.write_all(format!("{}\0", context).as_bytes())
+}
@@ -341,6 +341,13 @@ pub fn signal_by_name_or_value(signal_name_or_value: &str) -> Option<usize> {
         .map(|s| s.value)
 }
 
+pub fn signal_name_by_value(signal_value: usize) -> Option<&'static str> {
+    ALL_SIGNALS
+        .iter()
+        .find(|s| s.value == signal_value)
+        .map(|s| s.name)
+}
+
 #[inline(always)]
 pub fn is_signal(num: usize) -> bool {
     // Named signals start at 1
@@ -379,6 +386,14 @@ fn signal_by_short_name() {
     }
 }
 
+#[test]
+fn name_by_value() {
+    assert_eq!(signal_name_by_value(0), None);
+    for signal in &ALL_SIGNALS {
+        assert_eq!(signal_name_by_value(signal.value), Some(signal.name));
+    }
+}
+
 #[test]
 fn signal_by_long_name() {
     for signal in &ALL_SIGNALS {
@@ -0,0 +1,1153 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Message digests and checksums behind one interface, for the utilities
+//! that print and check them, the escaping of file names in the lines they
+//! print, and the checking of the files a list of those lines names, as
+//! `--check` does.
+
+// spell-checker:ignore (ToDO) sysv
+
+use hex::ToHex;
+
+use digest::{ExtendableOutput, Input, XofReader};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, stdin, BufRead, BufReader, Read};
+use std::path::Path;
+
+pub use blake2_rfc::blake2b::Blake2b;
+pub use blake3::Hasher as Blake3;
+pub use md5::Context as Md5;
+pub use sha1::Sha1;
+pub use sha2::{Sha224, Sha256, Sha384, Sha512};
+pub use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256};
+
+// Send, so that files can be hashed on other threads
+pub trait Digest: Send {
+    fn new() -> Self
+    where
+        Self: Sized;
+    /// A new digest like this one, making digests of the same length.
+    fn new_boxed(&self) -> Box<dyn Digest>;
+    fn input(&mut self, input: &[u8]);
+    fn result(&mut self, out: &mut [u8]);
+    fn reset(&mut self);
+    fn output_bits(&self) -> usize;
+    fn output_bytes(&self) -> usize {
+        (self.output_bits() + 7) / 8
+    }
+    fn result_str(&mut self) -> String {
+        let mut buf: Vec<u8> = vec![0; self.output_bytes()];
+        self.result(&mut buf);
+        buf.to_hex()
+    }
+}
+
+impl Digest for md5::Context {
+    fn new() -> Self {
+        md5::Context::new()
+    }
+
+    fn new_boxed(&self) -> Box<dyn Digest> {
+        Box::new(md5::Context::new())
+    }
+
+    fn input(&mut self, input: &[u8]) {
+        self.consume(input)
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        out.copy_from_slice(&*self.compute());
+    }
+
+    fn reset(&mut self) {
+        *self = md5::Context::new();
+    }
+
+    fn output_bits(&self) -> usize {
+        128
+    }
+}
+
+impl Digest for blake2_rfc::blake2b::Blake2b {
+    fn new() -> Self {
+        blake2_rfc::blake2b::Blake2b::new(64)
+    }
+
+    fn new_boxed(&self) -> Box<dyn Digest> {
+        Box::new(blake2_rfc::blake2b::Blake2b::new(self.output_bytes()))
+    }
+
+    fn input(&mut self, input: &[u8]) {
+        self.update(input);
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        let hash_result = &self.clone().finalize();
+        out.copy_from_slice(&hash_result.as_bytes());
+    }
+
+    fn reset(&mut self) {
+        *self = blake2_rfc::blake2b::Blake2b::new(self.output_bytes());
+    }
+
+    // the state doesn't say what length of digest it was made for, but its
+    // result does
+    fn output_bits(&self) -> usize {
+        self.clone().finalize().len() * 8
+    }
+}
+
+impl Digest for blake3::Hasher {
+    fn new() -> Self {
+        blake3::Hasher::new()
+    }
+
+    fn new_boxed(&self) -> Box<dyn Digest> {
+        Box::new(blake3::Hasher::new())
+    }
+
+    fn input(&mut self, input: &[u8]) {
+        self.update(input);
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        out.copy_from_slice(self.finalize().as_bytes());
+    }
+
+    fn reset(&mut self) {
+        *self = blake3::Hasher::new();
+    }
+
+    fn output_bits(&self) -> usize {
+        256
+    }
+}
+
+impl Digest for sha1::Sha1 {
+    fn new() -> Self {
+        sha1::Sha1::new()
+    }
+
+    fn new_boxed(&self) -> Box<dyn Digest> {
+        Box::new(sha1::Sha1::new())
+    }
+
+    fn input(&mut self, input: &[u8]) {
+        self.update(input);
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        out.copy_from_slice(&self.digest().bytes());
+    }
+
+    fn reset(&mut self) {
+        self.reset();
+    }
+
+    fn output_bits(&self) -> usize {
+        160
+    }
+}
+
+// Implements the Digest trait for sha2 / sha3 algorithms with fixed output
+macro_rules! impl_digest_sha {
+    ($type: ty, $size: expr) => {
+        impl Digest for $type {
+            fn new() -> Self {
+                Self::default()
+            }
+
+            fn new_boxed(&self) -> Box<dyn Digest> {
+                Box::new(Self::new())
+            }
+
+            fn input(&mut self, input: &[u8]) {
+                digest::Digest::input(self, input);
+            }
+
+            fn result(&mut self, out: &mut [u8]) {
+                out.copy_from_slice(digest::Digest::result(*self).as_slice());
+            }
+
+            fn reset(&mut self) {
+                *self = Self::new();
+            }
+
+            fn output_bits(&self) -> usize {
+                $size
+            }
+        }
+    };
+}
+
+// Implements the Digest trait for sha2 / sha3 algorithms with variable output
+macro_rules! impl_digest_shake {
+    ($type: ty) => {
+        impl Digest for $type {
+            fn new() -> Self {
+                Self::default()
+            }
+
+            fn new_boxed(&self) -> Box<dyn Digest> {
+                Box::new(Self::new())
+            }
+
+            fn input(&mut self, input: &[u8]) {
+                self.process(input);
+            }
+
+            fn result(&mut self, out: &mut [u8]) {
+                self.xof_result().read(out);
+            }
+
+            fn reset(&mut self) {
+                *self = Self::new();
+            }
+
+            fn output_bits(&self) -> usize {
+                0
+            }
+        }
+    };
+}
+
+impl_digest_sha!(sha2::Sha224, 224);
+impl_digest_sha!(sha2::Sha256, 256);
+impl_digest_sha!(sha2::Sha384, 384);
+impl_digest_sha!(sha2::Sha512, 512);
+
+impl_digest_sha!(sha3::Sha3_224, 224);
+impl_digest_sha!(sha3::Sha3_256, 256);
+impl_digest_sha!(sha3::Sha3_384, 384);
+impl_digest_sha!(sha3::Sha3_512, 512);
+impl_digest_shake!(sha3::Shake128);
+impl_digest_shake!(sha3::Shake256);
+
+/// The SM3 hash, of GB/T 32905-2016.
+#[derive(Clone)]
+pub struct Sm3 {
+    state: [u32; 8],
+    block: [u8; 64],
+    filled: usize,
+    length: u64,
+}
+
+const SM3_IV: [u32; 8] = [
+    0x7380_166f,
+    0x4914_b2b9,
+    0x1724_42d7,
+    0xda8a_0600,
+    0xa96f_30bc,
+    0x1631_38aa,
+    0xe38d_ee4d,
+    0xb0fb_0e4e,
+];
+
+impl Sm3 {
+    pub fn new() -> Self {
+        Sm3 {
+            state: SM3_IV,
+            block: [0; 64],
+            filled: 0,
+            length: 0,
+        }
+    }
+
+    fn compress(&mut self) {
+        fn p0(x: u32) -> u32 {
+            x ^ x.rotate_left(9) ^ x.rotate_left(17)
+        }
+        fn p1(x: u32) -> u32 {
+            x ^ x.rotate_left(15) ^ x.rotate_left(23)
+        }
+
+        let mut w = [0u32; 68];
+        for (i, word) in self.block.chunks(4).enumerate() {
+            w[i] = u32::from(word[0]) << 24
+                | u32::from(word[1]) << 16
+                | u32::from(word[2]) << 8
+                | u32::from(word[3]);
+        }
+        for j in 16..68 {
+            w[j] = p1(w[j - 16] ^ w[j - 9] ^ w[j - 3].rotate_left(15))
+                ^ w[j - 13].rotate_left(7)
+                ^ w[j - 6];
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for j in 0..64 {
+            let (t, ff, gg) = if j < 16 {
+                (0x79cc_4519u32, a ^ b ^ c, e ^ f ^ g)
+            } else {
+                (0x7a87_9d8a, (a & b) | (a & c) | (b & c), (e & f) | (!e & g))
+            };
+            let ss1 = a
+                .rotate_left(12)
+                .wrapping_add(e)
+                .wrapping_add(t.rotate_left(j as u32 % 32))
+                .rotate_left(7);
+            let ss2 = ss1 ^ a.rotate_left(12);
+            let tt1 = ff
+                .wrapping_add(d)
+                .wrapping_add(ss2)
+                .wrapping_add(w[j] ^ w[j + 4]);
+            let tt2 = gg.wrapping_add(h).wrapping_add(ss1).wrapping_add(w[j]);
+            d = c;
+            c = b.rotate_left(9);
+            b = a;
+            a = tt1;
+            h = g;
+            g = f.rotate_left(19);
+            f = e;
+            e = p0(tt2);
+        }
+        for (state, word) in self.state.iter_mut().zip(&[a, b, c, d, e, f, g, h]) {
+            *state ^= word;
+        }
+    }
+}
+
+impl Default for Sm3 {
+    fn default() -> Self {
+        Sm3::new()
+    }
+}
+
+impl Digest for Sm3 {
+    fn new() -> Self {
+        Sm3::new()
+    }
+
+    fn new_boxed(&self) -> Box<dyn Digest> {
+        Box::new(Sm3::new())
+    }
+
+    fn input(&mut self, mut input: &[u8]) {
+        self.length += input.len() as u64;
+        while !input.is_empty() {
+            let n = (64 - self.filled).min(input.len());
+            self.block[self.filled..self.filled + n].copy_from_slice(&input[..n]);
+            self.filled += n;
+            input = &input[n..];
+            if self.filled == 64 {
+                self.compress();
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        // padded as SHA-256 is, on a copy so that more can still be input
+        let mut padded = self.clone();
+        let bits = self.length * 8;
+        let zeros = (64 + 56 - (self.filled + 1) % 64) % 64;
+        Digest::input(&mut padded, &[0x80]);
+        Digest::input(&mut padded, &vec![0; zeros]);
+        Digest::input(&mut padded, &bits.to_be_bytes());
+        for (bytes, word) in out.chunks_mut(4).zip(&padded.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Sm3::new();
+    }
+
+    fn output_bits(&self) -> usize {
+        256
+    }
+}
+
+// NOTE: CRC_TABLE_LEN *must* be <= 256 as we cast 0..CRC_TABLE_LEN to u8
+const CRC_TABLE_LEN: usize = 256;
+const CRC_TABLE: [u32; CRC_TABLE_LEN] = generate_crc_table();
+// the CRCs of each byte followed by 0 to 15 zero bytes, for slicing by 16
+const CRC_TABLES: [[u32; CRC_TABLE_LEN]; 16] = generate_crc_tables();
+
+// this is basically a hack to get "loops" to work on Rust 1.33.  Once we update to Rust 1.46 or
+// greater, we can just use while loops
+macro_rules! unroll {
+    (256, |$i:ident| $s:expr) => {{
+        unroll!(@ 32, 0 * 32, $i, $s);
+        unroll!(@ 32, 1 * 32, $i, $s);
+        unroll!(@ 32, 2 * 32, $i, $s);
+        unroll!(@ 32, 3 * 32, $i, $s);
+        unroll!(@ 32, 4 * 32, $i, $s);
+        unroll!(@ 32, 5 * 32, $i, $s);
+        unroll!(@ 32, 6 * 32, $i, $s);
+        unroll!(@ 32, 7 * 32, $i, $s);
+    }};
+    (8, |$i:ident| $s:expr) => {{
+        unroll!(@ 8, 0, $i, $s);
+    }};
+
+    (@ 32, $start:expr, $i:ident, $s:expr) => {{
+        unroll!(@ 8, $start + 0 * 8, $i, $s);
+        unroll!(@ 8, $start + 1 * 8, $i, $s);
+        unroll!(@ 8, $start + 2 * 8, $i, $s);
+        unroll!(@ 8, $start + 3 * 8, $i, $s);
+    }};
+    (@ 8, $start:expr, $i:ident, $s:expr) => {{
+        unroll!(@ 4, $start, $i, $s);
+        unroll!(@ 4, $start + 4, $i, $s);
+    }};
+    (@ 4, $start:expr, $i:ident, $s:expr) => {{
+        unroll!(@ 2, $start, $i, $s);
+        unroll!(@ 2, $start + 2, $i, $s);
+    }};
+    (@ 2, $start:expr, $i:ident, $s:expr) => {{
+        unroll!(@ 1, $start, $i, $s);
+        unroll!(@ 1, $start + 1, $i, $s);
+    }};
+    (@ 1, $start:expr, $i:ident, $s:expr) => {{
+        let $i = $start;
+        let _ = $s;
+    }};
+}
+
+const fn generate_crc_table() -> [u32; CRC_TABLE_LEN] {
+    let mut table = [0; CRC_TABLE_LEN];
+
+    // NOTE: works on Rust 1.46
+    //let mut i = 0;
+    //while i < CRC_TABLE_LEN {
+    //    table[i] = crc_entry(i as u8) as u32;
+    //
+    //    i += 1;
+    //}
+    unroll!(256, |i| {
+        table[i] = crc_entry(i as u8) as u32;
+    });
+
+    table
+}
+
+const fn crc_entry(input: u8) -> u32 {
+    let mut crc = (input as u32) << 24;
+
+    // NOTE: this does not work on Rust 1.33, but *does* on 1.46
+    //let mut i = 0;
+    //while i < 8 {
+    //    if crc & 0x8000_0000 != 0 {
+    //        crc <<= 1;
+    //        crc ^= 0x04c1_1db7;
+    //    } else {
+    //        crc <<= 1;
+    //    }
+    //
+    //    i += 1;
+    //}
+    unroll!(8, |_i| {
+        let if_cond = crc & 0x8000_0000;
+        let if_body = (crc << 1) ^ 0x04c1_1db7;
+        let else_body = crc << 1;
+
+        // NOTE: i feel like this is easier to understand than emulating an if statement in bitwise
+        //       ops
+        let cond_table = [else_body, if_body];
+
+        crc = cond_table[(if_cond != 0) as usize];
+    });
+
+    crc
+}
+
+const fn next_crc_table(prev: [u32; CRC_TABLE_LEN]) -> [u32; CRC_TABLE_LEN] {
+    let mut table = [0; CRC_TABLE_LEN];
+    unroll!(256, |i| {
+        table[i] = (prev[i] << 8) ^ CRC_TABLE[(prev[i] >> 24) as usize];
+    });
+    table
+}
+
+const fn generate_crc_tables() -> [[u32; CRC_TABLE_LEN]; 16] {
+    let mut tables = [[0; CRC_TABLE_LEN]; 16];
+    tables[0] = CRC_TABLE;
+    tables[1] = next_crc_table(tables[0]);
+    tables[2] = next_crc_table(tables[1]);
+    tables[3] = next_crc_table(tables[2]);
+    tables[4] = next_crc_table(tables[3]);
+    tables[5] = next_crc_table(tables[4]);
+    tables[6] = next_crc_table(tables[5]);
+    tables[7] = next_crc_table(tables[6]);
+    tables[8] = next_crc_table(tables[7]);
+    tables[9] = next_crc_table(tables[8]);
+    tables[10] = next_crc_table(tables[9]);
+    tables[11] = next_crc_table(tables[10]);
+    tables[12] = next_crc_table(tables[11]);
+    tables[13] = next_crc_table(tables[12]);
+    tables[14] = next_crc_table(tables[13]);
+    tables[15] = next_crc_table(tables[14]);
+    tables
+}
+
+#[inline]
+fn crc_update(crc: u32, input: u8) -> u32 {
+    (crc << 8) ^ CRC_TABLE[((crc >> 24) as usize ^ input as usize) & 0xFF]
+}
+
+#[inline]
+fn crc_final(mut crc: u32, mut length: u64) -> u32 {
+    while length != 0 {
+        crc = crc_update(crc, length as u8);
+        length >>= 8;
+    }
+
+    !crc
+}
+
+/// `crc` updated with `input`, 16 bytes at a time.
+fn crc_update_slice16(mut crc: u32, input: &[u8]) -> u32 {
+    let mut chunks = input.chunks_exact(16);
+    for chunk in &mut chunks {
+        let crc_bytes = crc.to_be_bytes();
+        crc = 0;
+        for (i, &byte) in chunk.iter().enumerate() {
+            let byte = if i < 4 { byte ^ crc_bytes[i] } else { byte };
+            crc ^= CRC_TABLES[15 - i][byte as usize];
+        }
+    }
+    for &byte in chunks.remainder() {
+        crc = crc_update(crc, byte);
+    }
+    crc
+}
+
+/// `crc` updated with `input`, as fast as the CPU allows.
+fn crc_update_bytes(crc: u32, input: &[u8]) -> u32 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("pclmulqdq") {
+            let (crc, done) = unsafe { pclmul::crc_update(crc, input) };
+            return crc_update_slice16(crc, &input[done..]);
+        }
+    }
+    crc_update_slice16(crc, input)
+}
+
+/// The CRC by carry-less multiplication, as in Intel's "Fast CRC Computation
+/// for Generic Polynomials Using PCLMULQDQ Instruction": the input is folded
+/// 64 bytes at a time into 4 blocks of 16 bytes, which are folded into one,
+/// whose CRC is the input's.  The CRC32 instruction of SSE 4.2 can't be used,
+/// as it computes the CRC-32C, with another polynomial and bit order.
+#[cfg(target_arch = "x86_64")]
+mod pclmul {
+    use super::crc_update as crc_update_byte;
+    use std::arch::x86_64::*;
+
+    // x^n mod P, for P the CRC polynomial
+    const X128: i64 = 0xe8a4_5605;
+    const X192: i64 = 0xc5b9_cd4c;
+    const X512: i64 = 0xe622_8b11;
+    const X576: i64 = 0x8833_794c;
+
+    /// The 16 bytes `block` starts with, as a polynomial of degree < 128.
+    #[inline]
+    unsafe fn load(block: &[u8]) -> __m128i {
+        let mut high = [0; 8];
+        let mut low = [0; 8];
+        high.copy_from_slice(&block[..8]);
+        low.copy_from_slice(&block[8..16]);
+        _mm_set_epi64x(i64::from_be_bytes(high), i64::from_be_bytes(low))
+    }
+
+    /// A polynomial congruent to `block` times x^n, for `k` holding
+    /// x^(n + 64) and x^n mod P.
+    #[inline]
+    #[target_feature(enable = "pclmulqdq,sse2")]
+    unsafe fn fold(block: __m128i, k: __m128i) -> __m128i {
+        _mm_xor_si128(
+            _mm_clmulepi64_si128(block, k, 0x11),
+            _mm_clmulepi64_si128(block, k, 0x00),
+        )
+    }
+
+    /// `crc` updated with as much of `input` as is in whole 64 bytes, and
+    /// how much that is.
+    #[target_feature(enable = "pclmulqdq,sse2")]
+    pub unsafe fn crc_update(crc: u32, input: &[u8]) -> (u32, usize) {
+        if input.len() < 64 {
+            return (crc, 0);
+        }
+        let by_512 = _mm_set_epi64x(X576, X512);
+        let by_128 = _mm_set_epi64x(X192, X128);
+
+        let mut chunks = input.chunks_exact(64);
+        let first = chunks.next().unwrap();
+        let mut blocks = [
+            load(&first[0..]),
+            load(&first[16..]),
+            load(&first[32..]),
+            load(&first[48..]),
+        ];
+        // the CRC so far goes in with the first 4 bytes
+        blocks[0] = _mm_xor_si128(blocks[0], _mm_set_epi64x((u64::from(crc) << 32) as i64, 0));
+        for chunk in &mut chunks {
+            for (i, block) in blocks.iter_mut().enumerate() {
+                *block = _mm_xor_si128(fold(*block, by_512), load(&chunk[16 * i..]));
+            }
+        }
+        let mut folded = blocks[0];
+        for block in &blocks[1..] {
+            folded = _mm_xor_si128(fold(folded, by_128), *block);
+        }
+
+        let mut bytes = [0u8; 16];
+        _mm_storeu_si128(bytes.as_mut_ptr() as *mut __m128i, folded);
+        // stored least significant byte first
+        let mut crc = 0;
+        for &byte in bytes.iter().rev() {
+            crc = crc_update_byte(crc, byte);
+        }
+        (crc, input.len() - chunks.remainder().len())
+    }
+}
+
+/// The POSIX `cksum` CRC, of the input and then its length, as 4 bytes.
+#[derive(Clone, Default)]
+pub struct Crc {
+    crc: u32,
+    size: u64,
+}
+
+impl Crc {
+    /// How the CRC is computed on this CPU, as `cksum --debug` says.
+    pub fn implementation() -> &'static str {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("pclmulqdq") {
+                return "pclmul";
+            }
+        }
+        "generic"
+    }
+}
+
+impl Digest for Crc {
+    fn new() -> Self {
+        Crc::default()
+    }
+
+    fn new_boxed(&self) -> Box<dyn Digest> {
+        Box::new(Crc::default())
+    }
+
+    fn input(&mut self, input: &[u8]) {
+        self.crc = crc_update_bytes(self.crc, input);
+        self.size += input.len() as u64;
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        out.copy_from_slice(&crc_final(self.crc, self.size).to_be_bytes());
+    }
+
+    fn reset(&mut self) {
+        *self = Crc::default();
+    }
+
+    fn output_bits(&self) -> usize {
+        32
+    }
+}
+
+/// The 16-bit checksum of BSD `sum`, rotating right before adding each byte.
+#[derive(Clone, Default)]
+pub struct Bsd {
+    state: u16,
+}
+
+impl Digest for Bsd {
+    fn new() -> Self {
+        Bsd::default()
+    }
+
+    fn new_boxed(&self) -> Box<dyn Digest> {
+        Box::new(Bsd::default())
+    }
+
+    fn input(&mut self, input: &[u8]) {
+        for &byte in input {
+            self.state = (self.state >> 1) + ((self.state & 1) << 15);
+            self.state = self.state.wrapping_add(u16::from(byte));
+        }
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        out.copy_from_slice(&self.state.to_be_bytes());
+    }
+
+    fn reset(&mut self) {
+        *self = Bsd::default();
+    }
+
+    fn output_bits(&self) -> usize {
+        16
+    }
+}
+
+/// The 16-bit checksum of System V `sum`, the sum of the bytes folded.
+#[derive(Clone, Default)]
+pub struct SysV {
+    state: u32,
+}
+
+impl Digest for SysV {
+    fn new() -> Self {
+        SysV::default()
+    }
+
+    fn new_boxed(&self) -> Box<dyn Digest> {
+        Box::new(SysV::default())
+    }
+
+    fn input(&mut self, input: &[u8]) {
+        for &byte in input {
+            self.state = self.state.wrapping_add(u32::from(byte));
+        }
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        let mut ret = (self.state & 0xffff) + (self.state >> 16);
+        ret = (ret & 0xffff) + (ret >> 16);
+        out.copy_from_slice(&(ret as u16).to_be_bytes());
+    }
+
+    fn reset(&mut self) {
+        *self = SysV::default();
+    }
+
+    fn output_bits(&self) -> usize {
+        16
+    }
+}
+
+/// Whether the file name `name` has to be escaped to be put on a line, as
+/// one with a backslash, newline or carriage return has.
+pub fn needs_escape(name: &str) -> bool {
+    name.contains(|c| c == '\\' || c == '\n' || c == '\r')
+}
+
+/// `name` with its backslashes, newlines and carriage returns escaped. A
+/// line with a name escaped starts with a backslash.
+pub fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// The name `escape` gives `name`, if it is one.
+pub fn unescape(name: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+/// What checking the files a checksum list names reports, as `--check` and
+/// the options that go with it say.
+pub struct CheckOptions {
+    /// The name messages are shown under, `executable!()` of the utility.
+    pub util_name: &'static str,
+    /// Report nothing, and only exit with the status.
+    pub status: bool,
+    /// Don't report the files that matched.
+    pub quiet: bool,
+    /// Fail if any line isn't properly formatted.
+    pub strict: bool,
+    /// Warn of each line that isn't properly formatted.
+    pub warn: bool,
+    /// Skip the files that don't exist, rather than failing them.
+    pub ignore_missing: bool,
+}
+
+/// A file a checksum line names, and the checksum it should have.
+pub struct Listed {
+    pub filename: String,
+    pub sum: Vec<u8>,
+    /// Whether to read the file in binary mode, which only Windows tells
+    /// apart from text mode.
+    pub binary: bool,
+    /// The digest to make of the file, as long as `sum`.
+    pub digest: Box<dyn Digest>,
+}
+
+/// A line of a checksum list, by its number: the file it names, or, if it
+/// isn't properly formatted, the algorithm it would be a checksum line of;
+/// and, once it is verified, the checksum the file has.
+pub struct ChecksumLine {
+    number: usize,
+    listed: Result<Listed, String>,
+    real_sum: Option<io::Result<Vec<u8>>>,
+}
+
+impl ChecksumLine {
+    /// The line, with the checksum of the file it names made, which can be
+    /// done on a thread of its own.
+    pub fn verify(mut self) -> ChecksumLine {
+        if let Ok(ref mut listed) = self.listed {
+            let bytes = listed.sum.len();
+            let sum = digest_file(&listed.filename, &mut *listed.digest, listed.binary, bytes);
+            self.real_sum = Some(sum.map(|(sum, _)| sum));
+        }
+        self
+    }
+}
+
+/// Check the files named by the checksum list `checksums`, which was read
+/// from `list`, and report on them as `options` say; whether every one
+/// could be read and matched.
+///
+/// `parse` makes each line that isn't blank or a comment into the file it
+/// names, or else the algorithm to name in the warning that it isn't
+/// properly formatted. The lines are all read first, and then given to
+/// `verify_all` to report in the order they were given in; it can call
+/// `ChecksumLine::verify` on them first, on threads of its own, and the
+/// lines it doesn't are verified as they are reported.
+pub fn check<R, P, V>(
+    options: &CheckOptions,
+    list: &str,
+    checksums: R,
+    mut parse: P,
+    verify_all: V,
+) -> bool
+where
+    R: BufRead,
+    P: FnMut(&str) -> Result<Listed, String>,
+    V: FnOnce(Vec<ChecksumLine>, &mut dyn FnMut(ChecksumLine)),
+{
+    let info = |message: fmt::Arguments| eprintln!("{}: {}", options.util_name, message);
+    let list_name = if list == "-" {
+        "'standard input'".to_owned()
+    } else {
+        list.to_owned()
+    };
+
+    let mut lines = vec![];
+    let mut read_error = None;
+    for (i, line) in checksums.split(b'\n').enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                read_error = Some(e);
+                break;
+            }
+        };
+        let line = String::from_utf8_lossy(&line);
+        let line = if line.ends_with('\r') {
+            &line[..line.len() - 1]
+        } else {
+            &line[..]
+        };
+        // blank lines and comments
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        lines.push(ChecksumLine {
+            number: i + 1,
+            listed: parse(line),
+            real_sum: None,
+        });
+    }
+
+    let mut bad_format = 0;
+    let mut properly_formatted = false;
+    let mut failed_cksum = 0;
+    let mut failed_open = 0;
+    let mut matched = false;
+    verify_all(lines, &mut |line| {
+        let line = match line.real_sum {
+            Some(_) => line,
+            None => line.verify(),
+        };
+        let listed = match line.listed {
+            Ok(listed) => listed,
+            Err(algorithm) => {
+                bad_format += 1;
+                if options.warn {
+                    info(format_args!(
+                        "{}: {}: improperly formatted {} checksum line",
+                        list_name, line.number, algorithm
+                    ));
+                }
+                return;
+            }
+        };
+        properly_formatted = true;
+
+        let name = output_name(&listed.filename);
+        match line.real_sum {
+            Some(Ok(ref real_sum)) if *real_sum == listed.sum => {
+                matched = true;
+                if !options.quiet {
+                    println!("{}: OK", name);
+                }
+            }
+            Some(Ok(_)) => {
+                if !options.status {
+                    println!("{}: FAILED", name);
+                }
+                failed_cksum += 1;
+            }
+            Some(Err(ref e)) if options.ignore_missing && e.kind() == io::ErrorKind::NotFound => {}
+            Some(Err(e)) => {
+                info(format_args!(
+                    "{}: {}",
+                    listed.filename,
+                    crate::error_message(&e)
+                ));
+                if !options.status {
+                    println!("{}: FAILED open or read", name);
+                }
+                failed_open += 1;
+            }
+            None => unreachable!(),
+        }
+    });
+    if let Some(e) = read_error {
+        info(format_args!("{}: {}", list_name, crate::error_message(&e)));
+        return false;
+    }
+
+    if !properly_formatted {
+        info(format_args!(
+            "{}: no properly formatted checksum lines found",
+            list_name
+        ));
+        return false;
+    }
+    if !options.status {
+        for &(count, one, more) in &[
+            (
+                bad_format,
+                "line is improperly formatted",
+                "lines are improperly formatted",
+            ),
+            (
+                failed_open,
+                "listed file could not be read",
+                "listed files could not be read",
+            ),
+            (
+                failed_cksum,
+                "computed checksum did NOT match",
+                "computed checksums did NOT match",
+            ),
+        ] {
+            if count > 0 {
+                let what = if count == 1 { one } else { more };
+                info(format_args!("WARNING: {} {}", count, what));
+            }
+        }
+        if options.ignore_missing && !matched {
+            info(format_args!("{}: no file was verified", list_name));
+        }
+    }
+
+    failed_cksum == 0
+        && failed_open == 0
+        && (!options.strict || bad_format == 0)
+        && (!options.ignore_missing || matched)
+}
+
+/// A checksum line taken apart, whatever algorithm it is for.
+#[derive(Debug, PartialEq)]
+pub struct ParsedLine<'a> {
+    /// The tag of a tagged line, and the length in bits it gives, which
+    /// only "BLAKE2b-BITS" does.
+    pub tag: Option<(&'a str, Option<&'a str>)>,
+    pub filename: String,
+    /// The checksum, as it is written.
+    pub sum: &'a str,
+    /// Whether the file is read in binary mode, as a tagged line and
+    /// "SUM *NAME" say.
+    pub binary: bool,
+}
+
+/// The parts of the checksum line `line`: either tagged, "TAG (NAME) = SUM",
+/// or untagged, "SUM  NAME" or "SUM *NAME", and in either case starting
+/// with a backslash if the name is escaped. As GNU does, any spacing around
+/// the "=" is taken, and the name of a tagged line goes up to the last
+/// parenthesis.
+pub fn parse_line(line: &str) -> Option<ParsedLine> {
+    let (escaped, line) = if line.starts_with('\\') {
+        (true, &line[1..])
+    } else {
+        (false, line)
+    };
+    let (tag, filename, sum, binary) = match parse_tagged(line) {
+        Some((tag, filename, sum)) => (Some(tag), filename, sum, true),
+        None => {
+            let space = line.find(' ')?;
+            let (sum, rest) = line.split_at(space);
+            // the second space or a '*', for text or binary mode
+            if !(rest.starts_with("  ") || rest.starts_with(" *")) || rest.len() == 2 {
+                return None;
+            }
+            (None, &rest[2..], sum, rest.starts_with(" *"))
+        }
+    };
+    let filename = if escaped {
+        unescape(filename)?
+    } else {
+        filename.to_owned()
+    };
+    Some(ParsedLine {
+        tag,
+        filename,
+        sum,
+        binary,
+    })
+}
+
+/// The tag and the length it gives, the file name and the checksum of a
+/// tagged checksum line.
+#[allow(clippy::type_complexity)]
+fn parse_tagged(line: &str) -> Option<((&str, Option<&str>), &str, &str)> {
+    let open = line.find('(')?;
+    let close = line.rfind(')')?;
+    if close < open {
+        return None;
+    }
+    let tag = &line[..open];
+    let tag = if tag.ends_with(' ') {
+        &tag[..tag.len() - 1]
+    } else {
+        tag
+    };
+    if tag.is_empty() || tag.contains(' ') {
+        return None;
+    }
+    let tag = match tag.find('-') {
+        Some(dash) if &tag[..dash] == "BLAKE2b" => (&tag[..dash], Some(&tag[dash + 1..])),
+        _ => (tag, None),
+    };
+    let sum = line[close + 1..].trim_start_matches(&[' ', '\t'][..]);
+    if !sum.starts_with('=') {
+        return None;
+    }
+    let sum = sum[1..].trim_start_matches(&[' ', '\t'][..]);
+    Some((tag, &line[open + 1..close], sum))
+}
+
+/// The bytes the hexadecimal `sum` is, in either case.
+pub fn from_hex(sum: &str) -> Option<Vec<u8>> {
+    if sum.is_empty() || sum.len() % 2 != 0 || !sum.is_ascii() {
+        return None;
+    }
+    (0..sum.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&sum[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// How a checked file is named in the results: escaped, as it would be in
+/// a checksum line, if it has a newline.
+pub fn output_name(name: &str) -> String {
+    if name.contains('\n') {
+        format!("\\{}", escape(name))
+    } else {
+        name.to_owned()
+    }
+}
+
+/// The file `name`, or standard input for "-".
+pub fn open<P: AsRef<Path>>(name: P) -> io::Result<BufReader<Box<dyn Read>>> {
+    let name = name.as_ref();
+    let reader = if name == Path::new("-") {
+        Box::new(stdin()) as Box<dyn Read>
+    } else {
+        Box::new(File::open(name)?) as Box<dyn Read>
+    };
+    Ok(BufReader::new(reader))
+}
+
+/// The checksum, `bytes` long, that `digest` makes of the file `name` (see
+/// `open`), and the size of the file. In text mode, on Windows, the file's
+/// "\r\n" line ends are read as "\n".
+pub fn digest_file<P: AsRef<Path>>(
+    name: P,
+    digest: &mut dyn Digest,
+    binary: bool,
+    bytes: usize,
+) -> io::Result<(Vec<u8>, u64)> {
+    let mut reader = open(name)?;
+    let text = cfg!(windows) && !binary;
+    let mut size = 0;
+    let mut buffer = vec![0; 1024 * 1024];
+    let mut converted = vec![];
+    // whether the last chunk ended in a carriage return, which is kept
+    // back until what follows it is known
+    let mut carriage_return = false;
+    loop {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        size += n as u64;
+        if !text {
+            digest.input(&buffer[..n]);
+            continue;
+        }
+        converted.clear();
+        for &byte in &buffer[..n] {
+            if carriage_return && byte != b'\n' {
+                converted.push(b'\r');
+            }
+            carriage_return = byte == b'\r';
+            if !carriage_return {
+                converted.push(byte);
+            }
+        }
+        digest.input(&converted);
+    }
+    if carriage_return {
+        digest.input(b"\r");
+    }
+    let mut sum = vec![0; bytes];
+    digest.result(&mut sum);
+    Ok((sum, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        let parsed = parse_line("BLAKE2b-256 (a (b).txt) = 00ff").unwrap();
+        assert_eq!(parsed.tag, Some(("BLAKE2b", Some("256"))));
+        assert_eq!(parsed.filename, "a (b).txt");
+        assert_eq!(parsed.sum, "00ff");
+        assert!(parsed.binary);
+
+        let parsed = parse_line("SHA3-256(file)=00ff").unwrap();
+        assert_eq!(parsed.tag, Some(("SHA3-256", None)));
+
+        let parsed = parse_line("\\00ff  a\\nb").unwrap();
+        assert_eq!(parsed.tag, None);
+        assert_eq!(parsed.filename, "a\nb");
+        assert!(!parsed.binary);
+        assert!(parse_line("00ff *file").unwrap().binary);
+
+        assert_eq!(parse_line("00ff file"), None);
+        assert_eq!(parse_line("00ff  "), None);
+        assert_eq!(parse_line("MD5 (file) 00ff"), None);
+    }
+}
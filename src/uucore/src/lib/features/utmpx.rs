@@ -186,6 +186,16 @@ impl Utmpx {
         !self.user().is_empty() && self.record_type() == USER_PROCESS
     }
 
+    /// Whether the process the record is for still runs, as far as can be
+    /// told; records left behind by processes that died without cleaning up
+    /// after themselves don't.
+    pub fn process_exists(&self) -> bool {
+        let pid = self.pid();
+        pid <= 0
+            || unsafe { libc::kill(pid, 0) } == 0
+            || IOError::last_os_error().raw_os_error() != Some(libc::ESRCH)
+    }
+
     /// Canonicalize host name using DNS
     pub fn canon_host(&self) -> IOResult<String> {
         const AI_CANONNAME: libc::c_int = 0x2;
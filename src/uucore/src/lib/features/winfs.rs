@@ -0,0 +1,806 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! File metadata on Windows, in the terms of Unix `stat`.
+//!
+//! Windows has neither mode bits nor numeric owners, so they're made up. The
+//! permissions are those the access control entries grant the owner, the
+//! group and Everyone, less the write bits of a read-only file, and a
+//! regular file is only executable when its extension is one of `PATHEXT`.
+//! The user and group IDs are the relative IDs, the last sub-authorities, of
+//! the owner and group SIDs. Symbolic links and junctions, the reparse points
+//! that stand for another name, are both symbolic links here.
+
+// spell-checker:ignore (winapi) aclapi accctrl fileapi ioapiset minwinbase minwindef securitybaseapi winbase winerror winioctl winnt
+// spell-checker:ignore (words) DACL FSCTL PATHEXT RID
+
+use crate::features::wide::{FromWide, ToWide};
+
+use std::collections::HashSet;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Error as IOError, ErrorKind, Result as IOResult};
+use std::mem;
+use std::ops::Deref;
+use std::os::windows::fs::{symlink_dir, symlink_file, OpenOptionsExt};
+use std::os::windows::io::AsRawHandle;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use winapi::shared::minwindef::{DWORD, LPVOID};
+use winapi::shared::winerror::{ERROR_PRIVILEGE_NOT_HELD, ERROR_SUCCESS};
+use winapi::um::accctrl::SE_FILE_OBJECT;
+use winapi::um::aclapi::GetSecurityInfo;
+use winapi::um::fileapi::{
+    GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_ATTRIBUTE_TAG_INFO,
+    FILE_BASIC_INFO, FILE_STANDARD_INFO,
+};
+use winapi::um::ioapiset::DeviceIoControl;
+use winapi::um::minwinbase::{
+    FileAttributeTagInfo, FileBasicInfo, FileStandardInfo, FILE_INFO_BY_HANDLE_CLASS,
+};
+use winapi::um::securitybaseapi::{
+    CreateWellKnownSid, EqualSid, GetAce, GetLengthSid, GetSidSubAuthority, GetSidSubAuthorityCount,
+};
+use winapi::um::winbase::{
+    GetFileInformationByHandleEx, LocalFree, LookupAccountSidW, FILE_FLAG_BACKUP_SEMANTICS,
+    FILE_FLAG_OPEN_REPARSE_POINT,
+};
+use winapi::um::winioctl::FSCTL_SET_REPARSE_POINT;
+use winapi::um::winnt::{
+    IsReparseTagNameSurrogate, WinWorldSid, ACCESS_ALLOWED_ACE, ACCESS_ALLOWED_ACE_TYPE,
+    ACCESS_DENIED_ACE_TYPE, ACCESS_MASK, DACL_SECURITY_INFORMATION, FILE_ATTRIBUTE_READONLY,
+    FILE_ATTRIBUTE_REPARSE_POINT, FILE_EXECUTE, FILE_READ_ATTRIBUTES, FILE_READ_DATA,
+    FILE_WRITE_DATA, GENERIC_ALL, GENERIC_EXECUTE, GENERIC_READ, GENERIC_WRITE,
+    GROUP_SECURITY_INFORMATION, HANDLE, INHERIT_ONLY_ACE, IO_REPARSE_TAG_MOUNT_POINT,
+    MAXIMUM_REPARSE_DATA_BUFFER_SIZE, OWNER_SECURITY_INFORMATION, PACL, PSECURITY_DESCRIPTOR, PSID,
+    READ_CONTROL, SECURITY_MAX_SID_SIZE, SID_NAME_USE,
+};
+
+const S_IFDIR: u32 = 0o040_000;
+const S_IFREG: u32 = 0o100_000;
+const S_IFLNK: u32 = 0o120_000;
+
+/// The 100 ns intervals from 1601, where Windows counts time from, to the
+/// Epoch.
+const EPOCH_INTERVALS: i64 = 116_444_736_000_000_000;
+
+/// A copy of a security identifier.
+#[derive(Clone, Debug)]
+struct Sid(Vec<u32>);
+
+impl Sid {
+    /// Copy `sid`, which may be null.
+    unsafe fn copy(sid: PSID) -> Option<Sid> {
+        if sid.is_null() {
+            return None;
+        }
+        let len = GetLengthSid(sid) as usize;
+        // as a vector of u32, the copy is aligned as a SID needs to be
+        let mut copy = vec![0u32; (len + 3) / 4];
+        ptr::copy_nonoverlapping(sid as *const u8, copy.as_mut_ptr() as *mut u8, len);
+        Some(Sid(copy))
+    }
+
+    fn as_psid(&self) -> PSID {
+        self.0.as_ptr() as PSID
+    }
+
+    /// The relative ID, the last sub-authority.
+    fn rid(&self) -> u32 {
+        unsafe {
+            let count = *GetSidSubAuthorityCount(self.as_psid());
+            if count == 0 {
+                return 0;
+            }
+            *GetSidSubAuthority(self.as_psid(), DWORD::from(count) - 1)
+        }
+    }
+
+    /// The name of the account, without its domain.
+    fn name(&self) -> Option<String> {
+        let mut name = [0u16; 256];
+        let mut name_len = name.len() as DWORD;
+        let mut domain = [0u16; 256];
+        let mut domain_len = domain.len() as DWORD;
+        let mut kind: SID_NAME_USE = 0;
+        let found = unsafe {
+            LookupAccountSidW(
+                ptr::null(),
+                self.as_psid(),
+                name.as_mut_ptr(),
+                &mut name_len,
+                domain.as_mut_ptr(),
+                &mut domain_len,
+                &mut kind,
+            )
+        };
+        if found == 0 {
+            None
+        } else {
+            Some(String::from_wide(&name[..name_len as usize]))
+        }
+    }
+}
+
+/// The read, write and execute bits for the rights in `mask`.
+fn rwx(mask: ACCESS_MASK) -> u32 {
+    let mut bits = 0;
+    if mask & (FILE_READ_DATA | GENERIC_READ | GENERIC_ALL) != 0 {
+        bits |= 0o4;
+    }
+    if mask & (FILE_WRITE_DATA | GENERIC_WRITE | GENERIC_ALL) != 0 {
+        bits |= 0o2;
+    }
+    if mask & (FILE_EXECUTE | GENERIC_EXECUTE | GENERIC_ALL) != 0 {
+        bits |= 0o1;
+    }
+    bits
+}
+
+/// The permission bits `dacl` gives the owner, the group and Everyone. As
+/// Windows checks them, an entry only allows or denies what no entry before
+/// it has, and the entries for Everyone count for the owner and the group
+/// too.
+unsafe fn acl_permissions(dacl: PACL, owner: PSID, group: PSID) -> u32 {
+    if dacl.is_null() {
+        // no DACL at all lets anyone do anything
+        return 0o777;
+    }
+    let mut everyone = [0u32; SECURITY_MAX_SID_SIZE / 4];
+    let mut everyone_len = SECURITY_MAX_SID_SIZE as DWORD;
+    let everyone = everyone.as_mut_ptr() as PSID;
+    let has_everyone =
+        CreateWellKnownSid(WinWorldSid, ptr::null_mut(), everyone, &mut everyone_len) != 0;
+    let is = |sid: PSID, other: PSID| !other.is_null() && EqualSid(sid, other) != 0;
+
+    // the allowed and denied bits of the owner, the group and the others
+    let mut classes = [(0, 0); 3];
+    for index in 0..(*dacl).AceCount {
+        let mut ace: LPVOID = ptr::null_mut();
+        if GetAce(dacl, DWORD::from(index), &mut ace) == 0 {
+            continue;
+        }
+        // a denying entry is laid out as an allowing one
+        let ace = &*(ace as *const ACCESS_ALLOWED_ACE);
+        if ace.Header.AceFlags & INHERIT_ONLY_ACE != 0 {
+            continue;
+        }
+        let allows = match ace.Header.AceType {
+            ACCESS_ALLOWED_ACE_TYPE => true,
+            ACCESS_DENIED_ACE_TYPE => false,
+            _ => continue,
+        };
+        let sid = &ace.SidStart as *const DWORD as PSID;
+        let for_everyone = has_everyone && is(sid, everyone);
+        let applies = [
+            for_everyone || is(sid, owner),
+            for_everyone || is(sid, group),
+            for_everyone,
+        ];
+        let bits = rwx(ace.Mask);
+        for (class, &applies) in classes.iter_mut().zip(applies.iter()) {
+            if !applies {
+                continue;
+            }
+            if allows {
+                class.0 |= bits & !class.1;
+            } else {
+                class.1 |= bits & !class.0;
+            }
+        }
+    }
+    classes[0].0 << 6 | classes[1].0 << 3 | classes[2].0
+}
+
+/// Whether Windows runs files with the extension of `path`.
+fn is_executable(path: &Path) -> bool {
+    let extension = match path.extension() {
+        Some(extension) if !extension.is_empty() => extension.to_string_lossy(),
+        _ => return false,
+    };
+    let pathext = env::var("PATHEXT").unwrap_or_else(|_| String::from(".COM;.EXE;.BAT;.CMD"));
+    pathext.split(';').any(|known| {
+        known
+            .trim_start_matches('.')
+            .eq_ignore_ascii_case(&extension)
+    })
+}
+
+/// One of the `FILE_*_INFO` structures for an open file.
+fn file_info<T>(handle: HANDLE, class: FILE_INFO_BY_HANDLE_CLASS) -> IOResult<T> {
+    let mut info: T = unsafe { mem::zeroed() };
+    let ok = unsafe {
+        GetFileInformationByHandleEx(
+            handle,
+            class,
+            &mut info as *mut T as LPVOID,
+            mem::size_of::<T>() as DWORD,
+        )
+    };
+    if ok == 0 {
+        Err(IOError::last_os_error())
+    } else {
+        Ok(info)
+    }
+}
+
+/// Seconds and nanoseconds since the Epoch, for a time Windows gives as
+/// 100 ns intervals.
+fn unix_time(intervals: i64) -> (i64, i64) {
+    let since_epoch = intervals - EPOCH_INTERVALS;
+    (
+        since_epoch.div_euclid(10_000_000),
+        since_epoch.rem_euclid(10_000_000) * 100,
+    )
+}
+
+/// Open `path` to read its attributes, and its security descriptor when
+/// that's allowed.
+fn open_attributes(path: &Path, follow: bool) -> IOResult<File> {
+    let mut flags = FILE_FLAG_BACKUP_SEMANTICS;
+    if !follow {
+        flags |= FILE_FLAG_OPEN_REPARSE_POINT;
+    }
+    let open = |access| {
+        OpenOptions::new()
+            .access_mode(access)
+            .custom_flags(flags)
+            .open(path)
+    };
+    open(FILE_READ_ATTRIBUTES | READ_CONTROL).or_else(|_| open(FILE_READ_ATTRIBUTES))
+}
+
+/// The metadata of a file, with what Unix `stat` gives beside what
+/// `std::fs::Metadata` has.
+pub struct Metadata {
+    metadata: fs::Metadata,
+    mode: u32,
+    volume: u32,
+    index: u64,
+    links: u32,
+    allocated: u64,
+    accessed: i64,
+    modified: i64,
+    changed: i64,
+    created: i64,
+    reparse_tag: u32,
+    owner: Option<Sid>,
+    group: Option<Sid>,
+}
+
+impl Metadata {
+    /// The metadata of `path`, or of what it points to if it's a symbolic
+    /// link and `follow` is set.
+    pub fn new<P: AsRef<Path>>(path: P, follow: bool) -> IOResult<Metadata> {
+        let path = path.as_ref();
+        let file = open_attributes(path, follow)?;
+        let metadata = file.metadata()?;
+        let handle = file.as_raw_handle() as HANDLE;
+
+        let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { mem::zeroed() };
+        if unsafe { GetFileInformationByHandle(handle, &mut info) } == 0 {
+            return Err(IOError::last_os_error());
+        }
+        let basic: FILE_BASIC_INFO = file_info(handle, FileBasicInfo)?;
+        let standard: FILE_STANDARD_INFO = file_info(handle, FileStandardInfo)?;
+        let reparse_tag = if info.dwFileAttributes & FILE_ATTRIBUTE_REPARSE_POINT != 0 {
+            file_info::<FILE_ATTRIBUTE_TAG_INFO>(handle, FileAttributeTagInfo)?.ReparseTag
+        } else {
+            0
+        };
+
+        let mut owner: PSID = ptr::null_mut();
+        let mut group: PSID = ptr::null_mut();
+        let mut dacl: PACL = ptr::null_mut();
+        let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+        let status = unsafe {
+            GetSecurityInfo(
+                handle,
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION | GROUP_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+                &mut owner,
+                &mut group,
+                &mut dacl,
+                ptr::null_mut(),
+                &mut descriptor,
+            )
+        };
+        let (mut permissions, owner, group) = if status == ERROR_SUCCESS {
+            unsafe {
+                let found = (
+                    acl_permissions(dacl, owner, group),
+                    Sid::copy(owner),
+                    Sid::copy(group),
+                );
+                LocalFree(descriptor);
+                found
+            }
+        } else {
+            // without READ_CONTROL, say what a umask of 022 would
+            (0o755, None, None)
+        };
+
+        let is_symlink = !follow && IsReparseTagNameSurrogate(reparse_tag);
+        let mode = if is_symlink {
+            S_IFLNK | 0o777
+        } else if metadata.is_dir() {
+            S_IFDIR | permissions
+        } else {
+            if !is_executable(path) {
+                permissions &= !0o111;
+            }
+            // the attribute doesn't keep anyone from changing a directory
+            if info.dwFileAttributes & FILE_ATTRIBUTE_READONLY != 0 {
+                permissions &= !0o222;
+            }
+            S_IFREG | permissions
+        };
+
+        Ok(Metadata {
+            metadata,
+            mode,
+            volume: info.dwVolumeSerialNumber,
+            index: u64::from(info.nFileIndexHigh) << 32 | u64::from(info.nFileIndexLow),
+            links: info.nNumberOfLinks,
+            allocated: unsafe { *standard.AllocationSize.QuadPart() } as u64,
+            accessed: unsafe { *basic.LastAccessTime.QuadPart() },
+            modified: unsafe { *basic.LastWriteTime.QuadPart() },
+            changed: unsafe { *basic.ChangeTime.QuadPart() },
+            created: unsafe { *basic.CreationTime.QuadPart() },
+            reparse_tag,
+            owner,
+            group,
+        })
+    }
+
+    /// The mode of the file, its type bits included.
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.mode & 0o170_000 == S_IFLNK
+    }
+
+    /// The tag of a reparse point, or 0 for other files.
+    pub fn reparse_tag(&self) -> u32 {
+        self.reparse_tag
+    }
+
+    /// Whether the file is a junction rather than a symbolic link.
+    pub fn is_junction(&self) -> bool {
+        self.reparse_tag == IO_REPARSE_TAG_MOUNT_POINT
+    }
+
+    pub fn uid(&self) -> u32 {
+        self.owner.as_ref().map_or(0, Sid::rid)
+    }
+
+    pub fn gid(&self) -> u32 {
+        self.group.as_ref().map_or(0, Sid::rid)
+    }
+
+    /// The name of the owner, if it can be looked up.
+    pub fn user(&self) -> Option<String> {
+        self.owner.as_ref().and_then(Sid::name)
+    }
+
+    /// The name of the group, if it can be looked up.
+    pub fn group(&self) -> Option<String> {
+        self.group.as_ref().and_then(Sid::name)
+    }
+
+    pub fn nlink(&self) -> u64 {
+        u64::from(self.links)
+    }
+
+    /// The file index, unique on its volume.
+    pub fn ino(&self) -> u64 {
+        self.index
+    }
+
+    /// The serial number of the volume.
+    pub fn dev(&self) -> u64 {
+        u64::from(self.volume)
+    }
+
+    pub fn rdev(&self) -> u64 {
+        0
+    }
+
+    pub fn size(&self) -> u64 {
+        self.metadata.len()
+    }
+
+    /// The space allocated to the file, in 512-byte blocks.
+    pub fn blocks(&self) -> u64 {
+        (self.allocated + 511) / 512
+    }
+
+    pub fn blksize(&self) -> u64 {
+        4096
+    }
+
+    pub fn atime(&self) -> i64 {
+        unix_time(self.accessed).0
+    }
+
+    pub fn atime_nsec(&self) -> i64 {
+        unix_time(self.accessed).1
+    }
+
+    pub fn mtime(&self) -> i64 {
+        unix_time(self.modified).0
+    }
+
+    pub fn mtime_nsec(&self) -> i64 {
+        unix_time(self.modified).1
+    }
+
+    /// When the metadata last changed, as `ChangeTime` has it.
+    pub fn ctime(&self) -> i64 {
+        unix_time(self.changed).0
+    }
+
+    pub fn ctime_nsec(&self) -> i64 {
+        unix_time(self.changed).1
+    }
+
+    /// When the file was created, in seconds and nanoseconds.
+    pub fn birth(&self) -> (i64, i64) {
+        unix_time(self.created)
+    }
+}
+
+impl Deref for Metadata {
+    type Target = fs::Metadata;
+
+    fn deref(&self) -> &fs::Metadata {
+        &self.metadata
+    }
+}
+
+/// Change the permissions of `path`, as far as Windows has any: a mode
+/// without the owner's write bit makes a file read-only, and one with it
+/// makes it writable again. The access control list is left alone, and so
+/// are directories, which the read-only attribute doesn't protect.
+pub fn set_mode<P: AsRef<Path>>(path: P, mode: u32) -> IOResult<()> {
+    let path = path.as_ref();
+    let metadata = fs::metadata(path)?;
+    if metadata.is_dir() {
+        return Ok(());
+    }
+    let mut permissions = metadata.permissions();
+    permissions.set_readonly(mode & 0o200 == 0);
+    fs::set_permissions(path, permissions)
+}
+
+/// The name of the owner of the file `meta` describes, if it can be looked
+/// up, as `entries::user_name` gives it on Unix.
+pub fn user_name(meta: &Metadata) -> Option<String> {
+    meta.user()
+}
+
+/// The name of the group of the file `meta` describes, if it can be looked
+/// up, as `entries::group_name` gives it on Unix.
+pub fn group_name(meta: &Metadata) -> Option<String> {
+    meta.group()
+}
+
+/// The target of a symbolic link or junction, without the `\\?\` prefix
+/// that junctions and links to absolute paths have.
+pub fn read_link<P: AsRef<Path>>(path: P) -> IOResult<PathBuf> {
+    let target = fs::read_link(path)?;
+    Ok(dunce::simplified(&target).to_path_buf())
+}
+
+/// Make `link` a symbolic link to `target`, which is relative to the
+/// directory of `link`. Windows tells links to directories from links to
+/// files when they're made, so a link to a target that doesn't exist yet is
+/// one to a file. Making symbolic links may need a privilege that junctions
+/// don't, so a link to a directory is made a junction when it's missing.
+pub fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(target: P, link: Q) -> IOResult<()> {
+    let (target, link) = (target.as_ref(), link.as_ref());
+    let resolved = match link.parent() {
+        Some(dir) => dir.join(target),
+        None => target.to_path_buf(),
+    };
+    if resolved.is_dir() {
+        symlink_dir(target, link).or_else(|err| {
+            if err.raw_os_error() == Some(ERROR_PRIVILEGE_NOT_HELD as i32) {
+                junction(&resolved, link)
+            } else {
+                Err(err)
+            }
+        })
+    } else {
+        symlink_file(target, link)
+    }
+}
+
+/// Make `link` a junction to the directory `target`. Unlike a symbolic
+/// link, a junction always points to an absolute path on a local volume.
+pub fn junction<P: AsRef<Path>, Q: AsRef<Path>>(target: P, link: Q) -> IOResult<()> {
+    let target = fs::canonicalize(target)?;
+    let verbatim = target.to_wide();
+    // what is `\\?\` to Win32 is `\??\` to the object manager
+    let mut substitute = r"\??\".to_wide();
+    substitute.extend_from_slice(if verbatim.starts_with(&r"\\?\".to_wide()) {
+        &verbatim[4..]
+    } else {
+        &verbatim[..]
+    });
+    let print = dunce::simplified(&target).to_wide();
+
+    let substitute_len = substitute.len() * 2;
+    let print_len = print.len() * 2;
+    // the name offsets and lengths, then both names ending in a NUL
+    let data_len = 8 + substitute_len + 2 + print_len + 2;
+    if 8 + data_len > MAXIMUM_REPARSE_DATA_BUFFER_SIZE as usize {
+        return Err(IOError::new(
+            ErrorKind::InvalidInput,
+            "the target name is too long for a junction",
+        ));
+    }
+    let mut buffer = Vec::with_capacity((8 + data_len) / 2);
+    buffer.extend_from_slice(&[
+        IO_REPARSE_TAG_MOUNT_POINT as u16,
+        (IO_REPARSE_TAG_MOUNT_POINT >> 16) as u16,
+        data_len as u16,
+        0,
+        0,
+        substitute_len as u16,
+        substitute_len as u16 + 2,
+        print_len as u16,
+    ]);
+    buffer.extend(substitute);
+    buffer.push(0);
+    buffer.extend(print);
+    buffer.push(0);
+
+    let link = link.as_ref();
+    fs::create_dir(link)?;
+    set_reparse_point(link, &mut buffer).map_err(|err| {
+        let _ = fs::remove_dir(link);
+        err
+    })
+}
+
+fn set_reparse_point(path: &Path, buffer: &mut [u16]) -> IOResult<()> {
+    let dir = OpenOptions::new()
+        .write(true)
+        .custom_flags(FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)?;
+    let mut returned = 0;
+    let ok = unsafe {
+        DeviceIoControl(
+            dir.as_raw_handle() as HANDLE,
+            FSCTL_SET_REPARSE_POINT,
+            buffer.as_mut_ptr() as LPVOID,
+            (buffer.len() * 2) as DWORD,
+            ptr::null_mut(),
+            0,
+            &mut returned,
+            ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        Err(IOError::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Which symbolic links to directories a walk follows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symlinks {
+    /// none (`-P`)
+    Physical,
+    /// those given on the command line (`-H`)
+    CommandLine,
+    /// all of them (`-L`)
+    Logical,
+}
+
+/// What went wrong at some point of a walk.
+#[derive(Debug)]
+pub enum Error {
+    /// the file couldn't be examined
+    Access(IOError),
+    /// the directory couldn't be opened or read
+    ReadDir(IOError),
+    /// the directory is one of those it's in, through a symbolic link
+    Cycle,
+}
+
+/// A file met on a walk, as it was when it was examined.
+pub struct Entry {
+    path: PathBuf,
+    depth: usize,
+    metadata: Metadata,
+    followed: bool,
+    dangling: bool,
+}
+
+impl Entry {
+    /// The path of the file, the root given to the walk joined with the
+    /// names below it.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How far below the root the file is; the root itself is at depth 0.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Whether the name is a symbolic link that was followed, so that the
+    /// entry describes what it points to.
+    pub fn followed(&self) -> bool {
+        self.followed
+    }
+
+    /// Whether this is a symbolic link the walk would have followed, but
+    /// whose target doesn't exist.
+    pub fn is_dangling(&self) -> bool {
+        self.dangling
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.metadata.is_dir()
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.metadata.is_symlink()
+    }
+
+    /// The mode of the file, its type bits included.
+    pub fn mode(&self) -> u32 {
+        self.metadata.mode()
+    }
+
+    pub fn uid(&self) -> u32 {
+        self.metadata.uid()
+    }
+
+    pub fn gid(&self) -> u32 {
+        self.metadata.gid()
+    }
+
+    pub fn dev(&self) -> u64 {
+        self.metadata.dev()
+    }
+
+    pub fn ino(&self) -> u64 {
+        self.metadata.ino()
+    }
+
+    /// Change the permissions of the file, or of the file a symbolic link
+    /// points to, as `set_mode` does.
+    pub fn chmod(&self, mode: u32) -> IOResult<()> {
+        set_mode(&self.path, mode)
+    }
+}
+
+/// What a walk does with the files it meets.
+pub trait Visitor {
+    /// Called for every file, and for a directory before anything in it. A
+    /// directory is only entered when this returns true.
+    fn visit(&mut self, entry: &Entry) -> bool;
+
+    /// Called for a directory that was to be entered, once everything in it
+    /// has been visited, or once it turned out it can't be read.
+    fn leave(&mut self, _entry: &Entry) {}
+
+    /// Called for a file that can't be examined, or a directory that can't
+    /// be entered or read.
+    fn error(&mut self, path: &Path, err: Error);
+}
+
+/// A depth-first walk of the trees below some roots. Windows has no
+/// equivalent of `openat`, so files are looked up by their full paths.
+pub struct Walker {
+    symlinks: Symlinks,
+    recursive: bool,
+}
+
+impl Walker {
+    /// A walk that follows the symbolic links `symlinks` says, and visits
+    /// only the roots themselves.
+    pub fn new(symlinks: Symlinks) -> Walker {
+        Walker {
+            symlinks,
+            recursive: false,
+        }
+    }
+
+    /// Whether to enter directories.
+    pub fn recursive(mut self, recursive: bool) -> Walker {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Whether symbolic links met at `depth` are followed.
+    fn follows(&self, depth: usize) -> bool {
+        match self.symlinks {
+            Symlinks::Physical => false,
+            Symlinks::CommandLine => depth == 0,
+            Symlinks::Logical => true,
+        }
+    }
+
+    /// Walk the tree below `root`.
+    pub fn walk<P: AsRef<Path>, V: Visitor>(&self, root: P, visitor: &mut V) {
+        let mut ancestors = HashSet::new();
+        self.walk_entry(root.as_ref().to_path_buf(), 0, &mut ancestors, visitor);
+    }
+
+    fn walk_entry<V: Visitor>(
+        &self,
+        path: PathBuf,
+        depth: usize,
+        ancestors: &mut HashSet<(u64, u64)>,
+        visitor: &mut V,
+    ) {
+        let mut metadata = match Metadata::new(&path, false) {
+            Ok(metadata) => metadata,
+            Err(err) => return visitor.error(&path, Error::Access(err)),
+        };
+        let mut followed = false;
+        let mut dangling = false;
+        if metadata.is_symlink() && self.follows(depth) {
+            // a link whose target is gone is visited as the link itself
+            match Metadata::new(&path, true) {
+                Ok(target) => {
+                    metadata = target;
+                    followed = true;
+                }
+                Err(_) => dangling = true,
+            }
+        }
+        let entry = Entry {
+            path,
+            depth,
+            metadata,
+            followed,
+            dangling,
+        };
+
+        if !visitor.visit(&entry) || !self.recursive || !entry.is_dir() {
+            return;
+        }
+        let id = (entry.dev(), entry.ino());
+        if ancestors.contains(&id) {
+            return visitor.error(&entry.path, Error::Cycle);
+        }
+        let names = fs::read_dir(&entry.path).and_then(|dir| {
+            dir.map(|child| child.map(|child| child.file_name()))
+                .collect::<IOResult<Vec<_>>>()
+        });
+        let names = match names {
+            Ok(names) => names,
+            Err(err) => {
+                visitor.error(&entry.path, Error::ReadDir(err));
+                return visitor.leave(&entry);
+            }
+        };
+        ancestors.insert(id);
+        for name in names {
+            let path = entry.path.join(name);
+            self.walk_entry(path, depth + 1, ancestors, visitor);
+        }
+        ancestors.remove(&id);
+        visitor.leave(&entry);
+    }
+}
+
+/// Whether `path` names the root directory of a drive or share, following
+/// symbolic links.
+pub fn is_root<P: AsRef<Path>>(path: P) -> bool {
+    fs::canonicalize(path)
+        .map(|path| path.parent().is_none())
+        .unwrap_or(false)
+}
@@ -14,7 +14,7 @@ extern crate platform_info;
 // * feature-gated external crates (re-shared as public internal modules)
 #[cfg(feature = "libc")]
 pub extern crate libc;
-#[cfg(feature = "winapi")]
+#[cfg(all(windows, feature = "winapi"))]
 pub extern crate winapi;
 
 //## internal modules
@@ -26,23 +26,40 @@ mod mods; // core cross-platform modules
 
 // * cross-platform modules
 pub use crate::mods::coreopts;
+pub use crate::mods::help;
 pub use crate::mods::panic;
 pub use crate::mods::ranges;
+pub use crate::mods::tabstops;
 
 // * feature-gated modules
 #[cfg(feature = "encoding")]
 pub use crate::features::encoding;
+#[cfg(feature = "fnmatch")]
+pub use crate::features::fnmatch;
 #[cfg(feature = "fs")]
 pub use crate::features::fs;
+#[cfg(feature = "human")]
+pub use crate::features::human;
+#[cfg(feature = "i18n")]
+pub use crate::features::i18n;
+#[cfg(feature = "json")]
+pub use crate::features::json;
+#[cfg(feature = "manpage")]
+pub use crate::features::manpage;
+#[cfg(feature = "mode")]
+pub use crate::features::mode;
+#[cfg(feature = "parse_datetime")]
+pub use crate::features::parse_datetime;
 #[cfg(feature = "parse_time")]
 pub use crate::features::parse_time;
+#[cfg(feature = "quoting")]
+pub use crate::features::quoting;
+#[cfg(feature = "sum")]
+pub use crate::features::sum;
 #[cfg(feature = "zero-copy")]
 pub use crate::features::zero_copy;
 
 // * (platform-specific) feature-gated modules
-// ** non-windows
-#[cfg(all(not(windows), feature = "mode"))]
-pub use crate::features::mode;
 // ** unix-only
 #[cfg(all(unix, feature = "entries"))]
 pub use crate::features::entries;
@@ -50,6 +67,10 @@ pub use crate::features::entries;
 pub use crate::features::perms;
 #[cfg(all(unix, feature = "process"))]
 pub use crate::features::process;
+#[cfg(all(unix, feature = "safe-traversal"))]
+pub use crate::features::safe_traversal;
+#[cfg(all(unix, feature = "selinux"))]
+pub use crate::features::selinux;
 #[cfg(all(unix, not(target_os = "fuchsia"), feature = "signals"))]
 pub use crate::features::signals;
 #[cfg(all(
@@ -62,6 +83,8 @@ pub use crate::features::utmpx;
 // ** windows-only
 #[cfg(all(windows, feature = "wide"))]
 pub use crate::features::wide;
+#[cfg(all(windows, feature = "winfs"))]
+pub use crate::features::winfs;
 
 //## core functions
 
@@ -84,3 +107,12 @@ pub fn args() -> impl Iterator<Item = String> {
 pub fn args_os() -> impl Iterator<Item = OsString> {
     wild::args_os()
 }
+
+/// The description of an I/O error, without the "(os error N)" suffix.
+pub fn error_message(e: &std::io::Error) -> String {
+    let msg = e.to_string();
+    match msg.find(" (os error ") {
+        Some(end) => msg[..end].to_owned(),
+        None => msg,
+    }
+}
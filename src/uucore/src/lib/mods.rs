@@ -1,5 +1,7 @@
 // mods ~ cross-platforms modules (core/bundler file)
 
 pub mod coreopts;
+pub mod help;
 pub mod panic;
 pub mod ranges;
+pub mod tabstops;
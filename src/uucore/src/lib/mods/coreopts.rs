@@ -1,3 +1,5 @@
+use crate::help::{self, Help};
+
 pub struct HelpText<'a> {
     pub name: &'a str,
     pub version: &'a str,
@@ -7,21 +9,49 @@ pub struct HelpText<'a> {
     pub display_usage: bool,
 }
 
+/// An option as it was defined, kept to document it by.
+pub struct OptionDoc {
+    pub short_name: String,
+    pub long_name: String,
+    pub desc: String,
+    /// the name of its value, if it takes one
+    pub hint: Option<String>,
+    /// whether the value can be left out
+    pub optional: bool,
+}
+
 pub struct CoreOptions<'a> {
     options: getopts::Options,
     help_text: HelpText<'a>,
+    docs: Vec<OptionDoc>,
+    /// whether `--help` and `--version` have been added to `options`
+    complete: bool,
 }
 
 impl<'a> CoreOptions<'a> {
     pub fn new(help_text: HelpText<'a>) -> Self {
-        let mut ret = CoreOptions {
+        CoreOptions {
             options: getopts::Options::new(),
             help_text,
-        };
-        ret.options
-            .optflag("", "help", "print usage information")
-            .optflag("", "version", "print name and version number");
-        ret
+            docs: Vec::new(),
+            complete: false,
+        }
+    }
+    /// Add `--help` and `--version`, unless the utility defined them itself
+    /// (to give them short names too), once all the options are in.
+    fn complete(&mut self) -> &getopts::Options {
+        if !self.complete {
+            self.complete = true;
+            for &(long, desc) in &[
+                ("help", "print usage information"),
+                ("version", "print name and version number"),
+            ] {
+                if !self.docs.iter().any(|doc| doc.long_name == long) {
+                    self.options.optflag("", long, desc);
+                }
+            }
+        }
+        &self.options
     }
     pub fn optflagopt(
         &mut self,
@@ -31,6 +61,7 @@ impl<'a> CoreOptions<'a> {
         hint: &str,
     ) -> &mut CoreOptions<'a> {
         self.options.optflagopt(short_name, long_name, desc, hint);
+        self.document(short_name, long_name, desc, Some(hint), true);
         self
     }
     pub fn optflag(
@@ -40,6 +71,7 @@ impl<'a> CoreOptions<'a> {
         desc: &str,
     ) -> &mut CoreOptions<'a> {
         self.options.optflag(short_name, long_name, desc);
+        self.document(short_name, long_name, desc, None, false);
         self
     }
     pub fn optflagmulti(
@@ -49,6 +81,7 @@ impl<'a> CoreOptions<'a> {
         desc: &str,
     ) -> &mut CoreOptions<'a> {
         self.options.optflagmulti(short_name, long_name, desc);
+        self.document(short_name, long_name, desc, None, false);
         self
     }
     pub fn optopt(
@@ -59,6 +92,7 @@ impl<'a> CoreOptions<'a> {
         hint: &str,
     ) -> &mut CoreOptions<'a> {
         self.options.optopt(short_name, long_name, desc, hint);
+        self.document(short_name, long_name, desc, Some(hint), false);
         self
     }
     pub fn optmulti(
@@ -69,13 +103,47 @@ impl<'a> CoreOptions<'a> {
         hint: &str,
     ) -> &mut CoreOptions<'a> {
         self.options.optmulti(short_name, long_name, desc, hint);
+        self.document(short_name, long_name, desc, Some(hint), false);
         self
     }
-    pub fn usage(&self, summary: &str) -> String {
-        self.options.usage(summary)
+    fn document(
+        &mut self,
+        short_name: &str,
+        long_name: &str,
+        desc: &str,
+        hint: Option<&str>,
+        optional: bool,
+    ) {
+        self.docs.push(OptionDoc {
+            short_name: short_name.to_owned(),
+            long_name: long_name.to_owned(),
+            desc: desc.to_owned(),
+            hint: hint.map(str::to_owned),
+            optional,
+        });
+    }
+    pub fn help_text(&self) -> &HelpText<'a> {
+        &self.help_text
+    }
+    /// The options defined, without `--help` and `--version` unless the
+    /// utility defined them itself.
+    pub fn docs(&self) -> &[OptionDoc] {
+        &self.docs
+    }
+    pub fn parsing_style(&mut self, style: getopts::ParsingStyle) -> &mut CoreOptions<'a> {
+        self.options.parsing_style(style);
+        self
+    }
+    pub fn usage(&mut self, summary: &str) -> String {
+        self.complete().usage(summary)
+    }
+    /// The options themselves, for utilities that report errors and show
+    /// their help in a way of their own rather than with `parse`.
+    pub fn options(&mut self) -> &getopts::Options {
+        self.complete()
     }
     pub fn parse(&mut self, args: Vec<String>) -> getopts::Matches {
-        let matches = match self.options.parse(&args[1..]) {
+        let matches = match self.complete().parse(&args[1..]) {
             Ok(m) => Some(m),
             Err(f) => {
                 eprint!("{}: error: ", self.help_text.name);
@@ -85,37 +153,27 @@ impl<'a> CoreOptions<'a> {
         }
         .unwrap();
         if matches.opt_present("help") {
-            let usage_str = if self.help_text.display_usage {
-                format!(
-                    "\n {}\n\n Reference\n",
-                    self.options.usage(self.help_text.summary)
-                )
-                .replace("Options:", " Options:")
-            } else {
-                String::new()
-            };
-            print!(
-                "
- {0} {1}
-
- {0} {2}
-{3}{4}
-",
-                self.help_text.name,
-                self.help_text.version,
-                self.help_text.syntax,
-                usage_str,
-                self.help_text.long_help
-            );
+            let mut help = Help::from(&*self);
+            if !self.help_text.display_usage {
+                help.options.clear();
+            }
+            print!("{}", help);
             crate::exit!(0);
         } else if matches.opt_present("version") {
-            println!("{} {}", self.help_text.name, self.help_text.version);
+            println!(
+                "{}",
+                help::version(self.help_text.name, self.help_text.version)
+            );
             crate::exit!(0);
         }
         matches
     }
 }
 
+/// The options of the utility being built, with its help text. Utilities
+/// whose arguments aren't options at all (an expression, a format, or
+/// nothing that is read) still make one, with no options, to describe them
+/// in their help and manual page.
 #[macro_export]
 macro_rules! app {
     ($syntax: expr, $summary: expr, $long_help: expr) => {
@@ -0,0 +1,546 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! `--help` and `--version`, printed the same way by every utility.
+//!
+//! The help has the layout of a GNU one: the usage, a summary, the options
+//! with their descriptions in a column, any further text, and where to get
+//! help and report bugs. It is made from the arguments the utility is
+//! defined with, as its manual page is, so the two say the same. The
+//! version names the utility, the suite and the version.
+//!
+//! Utilities defined with clap parse their arguments with
+//! [`get_matches_from`] (or [`get_matches_from_safe`]), with the `help`
+//! feature; those defined with `CoreOptions` get this from `parse`.
+
+use crate::coreopts::CoreOptions;
+#[cfg(feature = "help")]
+use clap::{App, AppSettings, ArgMatches, ErrorKind};
+#[cfg(feature = "help")]
+use std::ffi::OsString;
+use std::fmt::{self, Display};
+
+/// The name of the suite, which `--version` gives with the utility's own.
+pub const SUITE: &str = "uutils coreutils";
+const HOMEPAGE: &str = "https://github.com/uutils/coreutils";
+
+/// The width the descriptions of the options are wrapped to, which leaves
+/// the last column of a terminal free.
+const WIDTH: usize = 79;
+/// The widest the names of an option can be with its description beside
+/// them; wider ones have it on the lines below.
+const MAX_TAG: usize = 30;
+/// How far clap indents the description of an option it lists with the
+/// description on the lines below.
+#[cfg(feature = "help")]
+const DESCRIPTION_INDENT: usize = 12;
+
+const HELP_MESSAGE: &str = "display this help and exit";
+const VERSION_MESSAGE: &str = "output version information and exit";
+
+/// The line `--version` prints: "ls (uutils coreutils) 0.0.4".
+pub fn version(name: &str, version: &str) -> String {
+    format!("{} ({}) {}", name, SUITE, version)
+}
+
+/// An option, as the help and the manual page list it.
+#[derive(Debug, Default)]
+pub(crate) struct Opt {
+    pub(crate) short: Option<char>,
+    pub(crate) long: Option<String>,
+    /// the name of its value, if it takes one
+    pub(crate) value: Option<String>,
+    /// whether the value can be left out
+    pub(crate) optional: bool,
+    pub(crate) help: String,
+}
+
+impl Opt {
+    fn new(short: Option<char>, long: Option<&str>, help: &str) -> Opt {
+        Opt {
+            short,
+            long: long.map(str::to_owned),
+            help: help.to_owned(),
+            ..Opt::default()
+        }
+    }
+}
+
+/// The help of a utility, which displays in the layout of GNU's.
+#[derive(Debug, Default)]
+pub struct Help {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) about: String,
+    /// the forms of the command line, without the name
+    pub(crate) synopsis: Vec<String>,
+    pub(crate) description: String,
+    pub(crate) options: Vec<Opt>,
+}
+
+impl Help {
+    /// The line `--version` prints.
+    pub fn version(&self) -> String {
+        version(&self.name, &self.version)
+    }
+
+    /// Add `--help` and `--version`, unless they were defined along with
+    /// the others.
+    fn add_help_and_version(&mut self) {
+        for &(long, help) in &[("help", HELP_MESSAGE), ("version", VERSION_MESSAGE)] {
+            let defined = self
+                .options
+                .iter()
+                .any(|opt| opt.long.as_ref().map(|l| &l[..]) == Some(long));
+            if !defined {
+                self.options.push(Opt::new(None, Some(long), help));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "help")]
+impl<'a, 'b> From<&App<'a, 'b>> for Help {
+    fn from(app: &App<'a, 'b>) -> Help {
+        let texts = Texts::of(app);
+        let name = render(app.clone().template("{bin}"));
+        let usage = render(configure(app.clone(), "{usage}", ""));
+
+        // clap lists the options as `--help` does, but each with its
+        // description on the lines below it, unwrapped, so that they read
+        // back one at a time
+        let mut unified = vec![];
+        let _ = configure(app.clone(), "{unified}", "")
+            .setting(AppSettings::NextLineHelp)
+            .setting(AppSettings::ColorNever)
+            .set_term_width(0)
+            .write_long_help(&mut unified);
+        let mut options: Vec<Opt> = vec![];
+        for line in String::from_utf8_lossy(&unified).lines() {
+            let indent = line.len() - line.trim_start().len();
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if indent < DESCRIPTION_INDENT {
+                options.push(parse_tag(line));
+            } else if let Some(opt) = options.last_mut() {
+                if !opt.help.is_empty() {
+                    opt.help.push('\n');
+                }
+                opt.help.push_str(line);
+            }
+        }
+
+        let description = [&texts.long_about, &texts.after_help]
+            .iter()
+            .filter_map(|text| text.as_ref().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        Help {
+            synopsis: synopsis(&name, &usage),
+            name,
+            version: texts.version,
+            about: texts.about.unwrap_or_default(),
+            description,
+            options,
+        }
+    }
+}
+
+impl<'a> From<&CoreOptions<'a>> for Help {
+    fn from(app: &CoreOptions<'a>) -> Help {
+        let help_text = app.help_text();
+        let options = app
+            .docs()
+            .iter()
+            .map(|doc| Opt {
+                short: doc.short_name.chars().next(),
+                long: Some(doc.long_name.clone()).filter(|long| !long.is_empty()),
+                value: doc.hint.clone(),
+                optional: doc.optional,
+                help: doc.desc.clone(),
+            })
+            .collect();
+        let mut help = Help {
+            name: help_text.name.to_owned(),
+            version: help_text.version.to_owned(),
+            about: help_text.summary.to_owned(),
+            synopsis: synopsis(help_text.name, help_text.syntax),
+            description: help_text.long_help.to_owned(),
+            options,
+        };
+        help.add_help_and_version();
+        help
+    }
+}
+
+impl Display for Help {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.synopsis.is_empty() {
+            writeln!(f, "Usage: {}", self.name)?;
+        }
+        for (i, synopsis) in self.synopsis.iter().enumerate() {
+            let lead = if i == 0 { "Usage:" } else { "  or: " };
+            if synopsis.is_empty() {
+                writeln!(f, "{} {}", lead, self.name)?;
+            } else {
+                writeln!(f, "{} {} {}", lead, self.name, synopsis)?;
+            }
+        }
+        for line in paragraphs(&self.about).join("\n\n").lines() {
+            writeln!(f, "{}", line.trim_start())?;
+        }
+
+        writeln!(f)?;
+        let mandatory = self.options.iter().any(|opt| {
+            opt.short.is_some() && opt.long.is_some() && opt.value.is_some() && !opt.optional
+        });
+        if mandatory {
+            writeln!(
+                f,
+                "Mandatory arguments to long options are mandatory for short options too."
+            )?;
+        }
+        let tags: Vec<String> = self.options.iter().map(tag).collect();
+        let column = tags
+            .iter()
+            .map(String::len)
+            .filter(|&len| len <= MAX_TAG)
+            .max()
+            .unwrap_or(0)
+            + 2;
+        for (opt, tag) in self.options.iter().zip(&tags) {
+            let mut lines = wrap(&opt.help, WIDTH - column).into_iter();
+            if tag.len() < column {
+                match lines.next() {
+                    Some(line) => writeln!(f, "{:width$}{}", tag, line, width = column)?,
+                    None => writeln!(f, "{}", tag)?,
+                }
+            } else {
+                writeln!(f, "{}", tag)?;
+            }
+            for line in lines {
+                writeln!(f, "{:width$}{}", "", line, width = column)?;
+            }
+        }
+
+        for paragraph in paragraphs(&self.description) {
+            writeln!(f)?;
+            writeln!(f, "{}", paragraph)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{} online help: <{}>", SUITE, HOMEPAGE)?;
+        writeln!(f, "Report bugs to: <{}/issues>", HOMEPAGE)
+    }
+}
+
+/// Parse `args` as `app.get_matches_from(args)` does, except that the help
+/// and version printed are ours.
+#[cfg(feature = "help")]
+pub fn get_matches_from<'a, I, T>(app: App<'a, '_>, args: I) -> ArgMatches<'a>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    get_matches_from_safe(app, args).unwrap_or_else(|e| e.exit())
+}
+
+/// Parse `args` as `app.get_matches_from_safe(args)` does, for utilities
+/// that report errors in a way of their own; `--help` and `--version` are
+/// printed, and exit, rather than returned.
+#[cfg(feature = "help")]
+pub fn get_matches_from_safe<'a, I, T>(app: App<'a, '_>, args: I) -> clap::Result<ArgMatches<'a>>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let texts = Texts::of(&app);
+    let template = texts.template();
+    // clap writes the version itself, as the name and then this
+    let version = format!("({}) {}", SUITE, texts.version);
+    match configure(app, &template, &version).get_matches_from_safe(args) {
+        // clap has written them, and `exit` ends the line and exits with 0
+        Err(e) if e.kind == ErrorKind::HelpDisplayed || e.kind == ErrorKind::VersionDisplayed => {
+            e.exit()
+        }
+        result => result,
+    }
+}
+
+/// `app`, to print its help with `template` and its version as `version`,
+/// with the options listed in the order they were defined in.
+#[cfg(feature = "help")]
+fn configure<'a, 'b>(app: App<'a, 'b>, template: &'b str, version: &'b str) -> App<'a, 'b> {
+    app.template(template)
+        .version(version)
+        .help_message(HELP_MESSAGE)
+        .version_message(VERSION_MESSAGE)
+        .setting(AppSettings::UnifiedHelpMessage)
+        .setting(AppSettings::DeriveDisplayOrder)
+        .set_term_width(WIDTH)
+}
+
+/// What `app` prints as its help, which its template makes the text it needs.
+#[cfg(feature = "help")]
+fn render(app: App) -> String {
+    let mut out = vec![];
+    let _ = app.set_term_width(0).write_help(&mut out);
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The texts of an `App` that its help has a place for, each `None` if the
+/// utility didn't give one.
+#[cfg(feature = "help")]
+struct Texts {
+    version: String,
+    about: Option<String>,
+    long_about: Option<String>,
+    after_help: Option<String>,
+}
+
+#[cfg(feature = "help")]
+impl Texts {
+    fn of(app: &App) -> Texts {
+        let rendered = render(
+            app.clone()
+                .template("{version}\0{about}\0{long-about}\0{after-help}"),
+        );
+        let mut texts = rendered.split('\0');
+        // in place of a text that isn't there, clap writes that it is unknown
+        let mut next = |unknown: &str| {
+            texts
+                .next()
+                .filter(|&text| text != unknown)
+                .map(str::to_owned)
+        };
+        Texts {
+            version: next("unknown version").unwrap_or_default(),
+            about: next("unknown about"),
+            long_about: next("unknown about"),
+            after_help: next("unknown after-help"),
+        }
+    }
+
+    /// The template of the help, in the layout of a GNU one, with a place
+    /// for each of the texts there are.
+    fn template(&self) -> String {
+        let mut template = String::from("Usage: {usage}\n");
+        if self.about.is_some() {
+            template.push_str("{about}\n");
+        }
+        template.push_str("\n{unified}\n");
+        if self.long_about.is_some() {
+            template.push_str("\n{long-about}\n");
+        }
+        if self.after_help.is_some() {
+            template.push_str("\n{after-help}\n");
+        }
+        template.push_str(&format!(
+            "\n{} online help: <{}>\nReport bugs to: <{}/issues>",
+            SUITE, HOMEPAGE, HOMEPAGE
+        ));
+        template
+    }
+}
+
+/// An option from the names clap lists it with: "-B, --block-size <SIZE>".
+#[cfg(feature = "help")]
+fn parse_tag(tag: &str) -> Opt {
+    let mut opt = Opt::default();
+    let mut rest = tag;
+    if !rest.starts_with("--") {
+        let mut chars = rest[1..].chars();
+        opt.short = chars.next();
+        rest = chars.as_str().trim_start_matches(',').trim_start();
+    }
+    if rest.starts_with("--") {
+        let end = rest.find(&[' ', '='][..]).unwrap_or(rest.len());
+        opt.long = Some(rest[2..end].to_owned());
+        rest = &rest[end..];
+    }
+    let rest = rest.trim_start_matches(&[' ', '='][..]);
+    if rest.starts_with('<') {
+        opt.value = rest.split(&['<', '>'][..]).nth(1).map(str::to_owned);
+    }
+    opt
+}
+
+/// The forms of the command line in `usage`, one to a line, without the
+/// name and the "or:" before the further ones.
+fn synopsis(name: &str, usage: &str) -> Vec<String> {
+    usage
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let line = match line.find(':') {
+                Some(colon) if line.starts_with("or") && line[2..colon].trim().is_empty() => {
+                    line[colon + 1..].trim_start()
+                }
+                _ => line,
+            };
+            if line == name {
+                String::new()
+            } else if line.starts_with(name) && line[name.len()..].starts_with(' ') {
+                line[name.len()..].trim_start().to_owned()
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect()
+}
+
+/// The names of an option, as the help lists them: "  -B, --block-size=SIZE".
+fn tag(opt: &Opt) -> String {
+    let mut tag = match (opt.short, &opt.long) {
+        (Some(short), Some(long)) => format!("  -{}, --{}", short, long),
+        (Some(short), None) => format!("  -{}", short),
+        (None, Some(long)) => format!("      --{}", long),
+        (None, None) => String::new(),
+    };
+    if let Some(ref value) = opt.value {
+        let separator = if opt.long.is_some() { "=" } else { " " };
+        if opt.optional {
+            tag.push_str(&format!("[{}{}]", separator.trim(), value));
+        } else {
+            tag.push_str(separator);
+            tag.push_str(value);
+        }
+    }
+    tag
+}
+
+/// The lines of `text`, each broken between words to fit in `width`.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for line in text.lines() {
+        let mut wrapped = String::new();
+        for word in line.split_whitespace() {
+            if !wrapped.is_empty() && wrapped.len() + 1 + word.len() > width {
+                lines.push(std::mem::take(&mut wrapped));
+            }
+            if !wrapped.is_empty() {
+                wrapped.push(' ');
+            }
+            wrapped.push_str(word);
+        }
+        if !wrapped.is_empty() {
+            lines.push(wrapped);
+        }
+    }
+    lines
+}
+
+/// The paragraphs of `text`, without the indentation its lines share.
+pub(crate) fn paragraphs(text: &str) -> Vec<String> {
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut paragraphs = vec![];
+    let mut paragraph = String::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !paragraph.is_empty() {
+                paragraphs.push(std::mem::take(&mut paragraph));
+            }
+            continue;
+        }
+        if !paragraph.is_empty() {
+            paragraph.push('\n');
+        }
+        paragraph.push_str(line[indent..].trim_end());
+    }
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph);
+    }
+    paragraphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraphs() {
+        assert_eq!(
+            paragraphs("\n  first line\n    indented\n\n  second\n"),
+            vec!["first line\n  indented".to_owned(), "second".to_owned()]
+        );
+        assert!(paragraphs("").is_empty());
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("one two three\nfour", 8),
+            vec!["one two", "three", "four"]
+        );
+        assert!(wrap("", 8).is_empty());
+    }
+
+    #[test]
+    fn test_synopsis() {
+        assert_eq!(
+            synopsis(
+                "tool",
+                "tool [OPTION]... FILE\n  or:  tool -l\n[ARG]...\ntool"
+            ),
+            vec!["[OPTION]... FILE", "-l", "[ARG]...", ""]
+        );
+    }
+
+    #[test]
+    fn test_version() {
+        assert_eq!(version("tool", "1.2.3"), "tool (uutils coreutils) 1.2.3");
+    }
+
+    #[test]
+    fn test_from_core_options() {
+        let mut app = CoreOptions::new(crate::coreopts::HelpText {
+            name: "tool",
+            version: "1.2.3",
+            syntax: "[-s SIGNAL] PID...\n or:  tool -l",
+            summary: "Signal processes.",
+            long_help: "\n  SIGNAL is a name\n  or a number.\n",
+            display_usage: true,
+        });
+        app.optopt("s", "signal", "the signal to send", "SIGNAL")
+            .optflagopt("", "color", "colorize", "WHEN")
+            .optflag(
+                "",
+                "a-rather-long-option-name",
+                "an option with a name too long for its description to fit beside it",
+            );
+        let help = Help::from(&app);
+        assert_eq!(help.version(), "tool (uutils coreutils) 1.2.3");
+        assert_eq!(
+            help.to_string(),
+            "Usage: tool [-s SIGNAL] PID...
+  or:  tool -l
+Signal processes.
+
+Mandatory arguments to long options are mandatory for short options too.
+  -s, --signal=SIGNAL  the signal to send
+      --color[=WHEN]   colorize
+      --a-rather-long-option-name
+                       an option with a name too long for its description to
+                       fit beside it
+      --help           display this help and exit
+      --version        output version information and exit
+
+SIGNAL is a name
+or a number.
+
+uutils coreutils online help: <https://github.com/uutils/coreutils>
+Report bugs to: <https://github.com/uutils/coreutils/issues>
+"
+        );
+    }
+}
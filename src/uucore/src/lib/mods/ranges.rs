@@ -9,6 +9,10 @@
 
 use std::str::FromStr;
 
+/// The high end of a range that was given without one ("N-"), which goes
+/// on to the end of the line.
+const OPEN_END: usize = std::usize::MAX - 1;
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub struct Range {
     pub low: usize,
@@ -19,8 +23,6 @@ impl FromStr for Range {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Range, &'static str> {
-        use std::usize::MAX;
-
         let mut parts = s.splitn(2, '-');
 
         let field = "fields and positions are numbered from 1";
@@ -42,7 +44,10 @@ impl FromStr for Range {
             (Some(n), Some(m)) if m.is_empty() => {
                 if let Ok(low) = n.parse::<usize>() {
                     if low > 0 {
-                        Ok(Range { low, high: MAX - 1 })
+                        Ok(Range {
+                            low,
+                            high: OPEN_END,
+                        })
                     } else {
                         Err(field)
                     }
@@ -79,6 +84,11 @@ impl FromStr for Range {
 }
 
 impl Range {
+    /// Whether the range goes on to the end of the line, as "N-" does.
+    pub fn is_open_ended(&self) -> bool {
+        self.high == OPEN_END
+    }
+
     pub fn from_list(list: &str) -> Result<Vec<Range>, String> {
         use std::cmp::max;
 
@@ -108,8 +118,6 @@ impl Range {
 }
 
 pub fn complement(ranges: &[Range]) -> Vec<Range> {
-    use std::usize;
-
     let mut complements = Vec::with_capacity(ranges.len() + 1);
 
     if !ranges.is_empty() && ranges[0].low > 1 {
@@ -131,10 +139,10 @@ pub fn complement(ranges: &[Range]) -> Vec<Range> {
                 }
             }
             (Some(last), None) => {
-                if last.high < usize::MAX - 1 {
+                if !last.is_open_ended() {
                     complements.push(Range {
                         low: last.high + 1,
-                        high: usize::MAX - 1,
+                        high: OPEN_END,
                     });
                 }
             }
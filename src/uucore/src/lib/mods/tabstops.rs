@@ -0,0 +1,139 @@
+// This file is part of the uutils coreutils package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Tab stop lists, as given to the `-t`/`--tabs` option of expand and unexpand.
+
+/// How tab stops continue after the last explicitly listed one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Repeat {
+    /// there are no tab stops after the last one
+    Never,
+    /// tab stops at every multiple of N (a single tab size, or `/N`)
+    Every(usize),
+    /// tab stops every N columns after the last listed one (`+N`)
+    After(usize),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TabStops {
+    stops: Vec<usize>,
+    repeat: Repeat,
+}
+
+impl Default for TabStops {
+    fn default() -> TabStops {
+        TabStops::every(8)
+    }
+}
+
+impl TabStops {
+    /// Tab stops every `size` columns.
+    pub fn every(size: usize) -> TabStops {
+        TabStops {
+            stops: Vec::new(),
+            repeat: Repeat::Every(size),
+        }
+    }
+
+    /// Parse a list of tab stops separated by commas or blanks. A single
+    /// number is a tab size; otherwise the numbers are ascending tab stop
+    /// positions, and the last one may be prefixed with `/` to continue with
+    /// tab stops at every multiple of it, or with `+` to continue with tab
+    /// stops that many columns apart.
+    ///
+    /// ```
+    /// use uucore::tabstops::TabStops;
+    ///
+    /// let stops = TabStops::parse("2,5,+4").unwrap();
+    /// assert_eq!(stops.next_tabstop(0), Some(2));
+    /// assert_eq!(stops.next_tabstop(3), Some(2));
+    /// assert_eq!(stops.next_tabstop(6), Some(3));
+    /// assert!(TabStops::parse("4,3").is_err());
+    /// ```
+    pub fn parse(list: &str) -> Result<TabStops, String> {
+        let items: Vec<&str> = list
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|item| !item.is_empty())
+            .collect();
+
+        let mut stops: Vec<usize> = Vec::with_capacity(items.len());
+        let mut repeat = Repeat::Never;
+        for (i, item) in items.iter().enumerate() {
+            let (prefix, digits) = match item.chars().next() {
+                Some(c) if c == '/' || c == '+' => (Some(c), &item[1..]),
+                _ => (None, &item[..]),
+            };
+            if let Some(pos) = digits.find(|c: char| !c.is_ascii_digit()) {
+                return Err(format!(
+                    "tab size contains invalid character(s): '{}'",
+                    &digits[pos..]
+                ));
+            }
+            let value = match digits.parse::<usize>() {
+                Ok(value) => value,
+                Err(_) if digits.is_empty() => 0,
+                Err(_) => return Err(format!("tab stop is too large '{}'", digits)),
+            };
+
+            if let Some(prefix) = prefix {
+                if i + 1 != items.len() {
+                    return Err(format!(
+                        "'{}' specifier only allowed with the last value",
+                        prefix
+                    ));
+                }
+                // like GNU, a zero repeat adds no tab stops
+                if value > 0 {
+                    repeat = if prefix == '/' {
+                        Repeat::Every(value)
+                    } else {
+                        Repeat::After(value)
+                    };
+                }
+                continue;
+            }
+
+            if value == 0 {
+                return Err("tab size cannot be 0".to_owned());
+            }
+            if stops.last().map_or(false, |&last| last >= value) {
+                return Err("tab sizes must be ascending".to_owned());
+            }
+            stops.push(value);
+        }
+
+        // a single number, or a lone repeat, is a tab size
+        match (stops.len(), repeat) {
+            (0, Repeat::Never) => Ok(TabStops::default()),
+            (0, Repeat::Every(size)) | (0, Repeat::After(size)) => Ok(TabStops::every(size)),
+            (1, Repeat::Never) => Ok(TabStops::every(stops[0])),
+            _ => Ok(TabStops { stops, repeat }),
+        }
+    }
+
+    /// The number of columns from `col` to the next tab stop, or `None` if
+    /// `col` is at or past the last tab stop.
+    pub fn next_tabstop(&self, col: usize) -> Option<usize> {
+        if let Some(&stop) = self.stops.iter().find(|&&stop| stop > col) {
+            return Some(stop - col);
+        }
+        match self.repeat {
+            Repeat::Never => None,
+            Repeat::Every(size) => Some(size - col % size),
+            Repeat::After(size) => {
+                let last = self.stops.last().cloned().unwrap_or(0);
+                Some(size - (col - last) % size)
+            }
+        }
+    }
+
+    /// The last tab stop, if there are no tab stops after it.
+    pub fn last_tabstop(&self) -> Option<usize> {
+        match self.repeat {
+            Repeat::Never => self.stops.last().cloned(),
+            _ => None,
+        }
+    }
+}
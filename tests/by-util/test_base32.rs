@@ -36,7 +36,7 @@ fn test_garbage() {
         .arg("-d")
         .pipe_in(input)
         .fails()
-        .stderr_only("base32: error: invalid input\n");
+        .stderr_only("base32: error: invalid input: invalid symbol at 0\n");
 }
 
 #[test]
@@ -87,3 +87,53 @@ fn test_wrap_bad_arg() {
             .stderr_only("base32: error: invalid wrap size: ‘b’: invalid digit found in string\n");
     }
 }
+
+#[test]
+fn test_ignore_garbage_keeps_padding() {
+    new_ucmd!()
+        .args(&["-d", "-i"])
+        .pipe_in("MZXW6===\n*MJQXE===")
+        .succeeds()
+        .stdout_only("foobar");
+}
+
+#[test]
+fn test_invalid_padding() {
+    new_ucmd!()
+        .arg("-d")
+        .pipe_in("JBSWY3DP\n========\n")
+        .fails()
+        .stdout_is("Hello")
+        .stderr_is("base32: error: invalid input: invalid padding length at 9\n");
+}
+
+#[test]
+fn test_truncated_block() {
+    // what comes before the truncated block is still decoded
+    new_ucmd!()
+        .arg("-d")
+        .pipe_in("JBSWY3DP\nMY=====\n")
+        .fails()
+        .stdout_is("Hello")
+        .stderr_is("base32: error: invalid input: invalid length at 9\n");
+}
+
+#[test]
+fn test_wrap_across_chunks() {
+    // larger than the chunks the input is encoded in
+    let input = "f".repeat(100_000);
+    let mut expected = String::new();
+    for line in "MZTGMZTG".repeat(20_000).as_bytes().chunks(76) {
+        expected.push_str(std::str::from_utf8(line).unwrap());
+        expected.push('\n');
+    }
+    new_ucmd!()
+        .pipe_in(input.as_str())
+        .succeeds()
+        .stdout_only(&expected);
+    new_ucmd!()
+        .arg("-d")
+        .pipe_in(expected)
+        .succeeds()
+        .stdout_only(input);
+}
@@ -28,7 +28,8 @@ fn test_garbage() {
         .arg("-d")
         .pipe_in(input)
         .fails()
-        .stderr_only("base64: error: invalid input\n");
+        .stdout_is("hello, world!")
+        .stderr_is("base64: error: invalid input: invalid length at 20\n");
 }
 
 #[test]
@@ -77,3 +78,33 @@ fn test_wrap_bad_arg() {
             .stderr_only("base64: error: invalid wrap size: ‘b’: invalid digit found in string\n");
     }
 }
+
+#[test]
+fn test_wrap_across_chunks() {
+    // larger than the chunks the input is encoded in
+    let input = "a".repeat(200_000);
+    let encoded = format!("{}YWE=", "YWFh".repeat(66_666));
+    let mut expected = String::new();
+    for line in encoded.as_bytes().chunks(76) {
+        expected.push_str(std::str::from_utf8(line).unwrap());
+        expected.push('\n');
+    }
+    new_ucmd!()
+        .pipe_in(input.as_str())
+        .succeeds()
+        .stdout_only(&expected);
+    new_ucmd!()
+        .arg("-d")
+        .pipe_in(expected)
+        .succeeds()
+        .stdout_only(input);
+}
+
+#[test]
+fn test_wrap_zero() {
+    new_ucmd!()
+        .args(&["-w", "0"])
+        .pipe_in("The quick brown fox jumps over the lazy dog.")
+        .succeeds()
+        .stdout_only("VGhlIHF1aWNrIGJyb3duIGZveCBqdW1wcyBvdmVyIHRoZSBsYXp5IGRvZy4=");
+}
@@ -65,6 +65,22 @@ fn test_zero_param() {
     }
 }
 
+#[test]
+fn test_last_suffix_wins() {
+    new_ucmd!()
+        .args(&["-s", ".exe", "-s", ".c", "/foo/bar/baz.c"])
+        .succeeds()
+        .stdout_only("baz\n");
+}
+
+#[test]
+fn test_slashes_and_dots() {
+    new_ucmd!()
+        .args(&["-a", "/", "//", "foo/.", "foo/..", "foo//bar//"])
+        .succeeds()
+        .stdout_only("/\n/\n.\n..\nbar\n");
+}
+
 fn expect_error(input: Vec<&str>) {
     assert!(new_ucmd!().args(&input).fails().no_stdout().stderr.len() > 0);
 }
@@ -0,0 +1,91 @@
+use crate::common::util::*;
+
+#[test]
+fn test_encodings() {
+    for &(option, encoded) in &[
+        ("--base64", "SGVsbG8sIFdvcmxkIQ==\n"),
+        ("--base64url", "SGVsbG8sIFdvcmxkIQ==\n"),
+        ("--base32", "JBSWY3DPFQQFO33SNRSCC===\n"),
+        ("--base32hex", "91IMOR3F5GG5ERRIDHI22===\n"),
+        ("--base16", "48656C6C6F2C20576F726C6421\n"),
+    ] {
+        new_ucmd!()
+            .arg(option)
+            .pipe_in("Hello, World!")
+            .succeeds()
+            .stdout_only(encoded);
+        new_ucmd!()
+            .args(&[option, "-d"])
+            .pipe_in(encoded)
+            .succeeds()
+            .stdout_only("Hello, World!");
+    }
+}
+
+#[test]
+fn test_base64url_alphabet() {
+    new_ucmd!()
+        .arg("--base64url")
+        .pipe_in(&b"\xfb\xff"[..])
+        .succeeds()
+        .stdout_only("-_8=\n");
+}
+
+#[test]
+fn test_base2() {
+    new_ucmd!()
+        .arg("--base2msbf")
+        .pipe_in("AB")
+        .succeeds()
+        .stdout_only("0100000101000010\n");
+    new_ucmd!()
+        .arg("--base2lsbf")
+        .pipe_in("AB")
+        .succeeds()
+        .stdout_only("1000001001000010\n");
+    new_ucmd!()
+        .args(&["--base2lsbf", "-d"])
+        .pipe_in("1000001001000010\n")
+        .succeeds()
+        .stdout_only("AB");
+}
+
+#[test]
+fn test_z85() {
+    new_ucmd!()
+        .arg("--z85")
+        .pipe_in(&b"\x86\x4f\xd2\x6f\xb5\x59\xf7\x5b"[..])
+        .succeeds()
+        .stdout_only("HelloWorld\n");
+    new_ucmd!()
+        .args(&["--z85", "-d"])
+        .pipe_in("HelloWorld\n")
+        .succeeds()
+        .stdout_only_bytes(&b"\x86\x4f\xd2\x6f\xb5\x59\xf7\x5b"[..]);
+}
+
+#[test]
+fn test_z85_bad_length() {
+    new_ucmd!()
+        .arg("--z85")
+        .pipe_in("abc")
+        .fails()
+        .stderr_only("basenc: error: invalid input (length must be multiple of 4 characters)\n");
+}
+
+#[test]
+fn test_last_encoding_wins() {
+    new_ucmd!()
+        .args(&["--base32", "--base16"])
+        .pipe_in("Hi")
+        .succeeds()
+        .stdout_only("4869\n");
+}
+
+#[test]
+fn test_missing_encoding() {
+    new_ucmd!()
+        .pipe_in("Hi")
+        .fails()
+        .stderr_only("basenc: missing encoding type\nTry 'basenc --help' for more information.\n");
+}
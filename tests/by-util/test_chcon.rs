@@ -0,0 +1,78 @@
+use crate::common::util::*;
+
+#[test]
+fn test_missing_operand() {
+    new_ucmd!()
+        .fails()
+        .status_code(1)
+        .stderr_contains(&"missing operand");
+    new_ucmd!()
+        .arg("user_u:role_r:type_t")
+        .fails()
+        .status_code(1)
+        .stderr_contains(&"missing operand after 'user_u:role_r:type_t'");
+    new_ucmd!()
+        .args(&["-t", "type_t"])
+        .fails()
+        .status_code(1)
+        .stderr_contains(&"missing operand");
+}
+
+#[test]
+fn test_conflicting_specifiers() {
+    if !std::path::Path::new("/sys/fs/selinux/enforce").exists() {
+        // without SELinux, the reference has no context to conflict with
+        return;
+    }
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch("file");
+    ucmd.args(&["--reference", "file", "-t", "type_t", "file"])
+        .fails()
+        .status_code(1)
+        .stderr_contains(&"conflicting security context specifiers given");
+}
+
+#[test]
+fn test_recursive_dereference() {
+    new_ucmd!()
+        .args(&["-R", "--dereference", "-t", "type_t", "file"])
+        .fails()
+        .status_code(1)
+        .stderr_is("chcon: -R --dereference requires either -H or -L");
+    new_ucmd!()
+        .args(&["-RLh", "-t", "type_t", "file"])
+        .fails()
+        .status_code(1)
+        .stderr_is("chcon: -R -h requires -P");
+}
+
+#[test]
+fn test_missing_file() {
+    new_ucmd!()
+        .args(&["-t", "type_t", "missing"])
+        .fails()
+        .status_code(1)
+        .stderr_is("chcon: cannot access 'missing': No such file or directory");
+}
+
+#[test]
+fn test_missing_reference() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch("file");
+    ucmd.args(&["--reference", "missing", "file"])
+        .fails()
+        .status_code(1)
+        .stderr_contains(&"failed to get security context of 'missing'");
+}
+
+#[test]
+fn test_preserve_root() {
+    new_ucmd!()
+        .args(&["-R", "--preserve-root", "-t", "type_t", "/"])
+        .fails()
+        .status_code(1)
+        .stderr_is(
+            "chcon: it is dangerous to operate recursively on '/'\n\
+             chcon: use --no-preserve-root to override this failsafe",
+        );
+}
@@ -14,7 +14,7 @@ fn test_invalid_group() {
         .arg("__nosuchgroup__")
         .arg("/")
         .fails()
-        .stderr_is("chgrp: invalid group: __nosuchgroup__");
+        .stderr_is("chgrp: invalid group: '__nosuchgroup__'");
 }
 
 #[test]
@@ -24,7 +24,7 @@ fn test_1() {
             .arg("bin")
             .arg(DIR)
             .fails()
-            .stderr_is("chgrp: changing group of '/tmp': Operation not permitted (os error 1)");
+            .stderr_is("chgrp: changing group of '/tmp': Operation not permitted");
     }
 }
 
@@ -42,6 +42,18 @@ fn test_fail_silently() {
     }
 }
 
+fn preserve_root_message(d: &str) -> String {
+    let same_as = if d == "/" {
+        String::new()
+    } else {
+        " (same as '/')".to_owned()
+    };
+    format!(
+        "chgrp: it is dangerous to operate recursively on '{}'{}\nchgrp: use --no-preserve-root to override this failsafe",
+        d, same_as
+    )
+}
+
 #[test]
 fn test_preserve_root() {
     // It's weird that on OS X, `realpath /etc/..` returns '/private'
@@ -56,7 +68,7 @@ fn test_preserve_root() {
             .arg("-R")
             .arg("bin").arg(d)
             .fails()
-            .stderr_is("chgrp: it is dangerous to operate recursively on '/'\nchgrp: use --no-preserve-root to override this failsafe");
+            .stderr_is(preserve_root_message(d));
     }
 }
 
@@ -75,16 +87,17 @@ fn test_preserve_root_symlink() {
             .arg("-HR")
             .arg("bin").arg(file)
             .fails()
-            .stderr_is("chgrp: it is dangerous to operate recursively on '/'\nchgrp: use --no-preserve-root to override this failsafe");
+            .stderr_is(preserve_root_message(file));
     }
 
     let (at, mut ucmd) = at_and_ucmd!();
     at.symlink_file("///usr", file);
+    let d = format!(".//{}/..//..//../../", file);
     ucmd.arg("--preserve-root")
         .arg("-HR")
-        .arg("bin").arg(format!(".//{}/..//..//../../", file))
+        .arg("bin").arg(&d)
         .fails()
-        .stderr_is("chgrp: it is dangerous to operate recursively on '/'\nchgrp: use --no-preserve-root to override this failsafe");
+        .stderr_is(preserve_root_message(&d));
 
     let (at, mut ucmd) = at_and_ucmd!();
     at.symlink_file("/", "/tmp/__root__");
@@ -92,7 +105,7 @@ fn test_preserve_root_symlink() {
         .arg("-R")
         .arg("bin").arg("/tmp/__root__/.")
         .fails()
-        .stderr_is("chgrp: it is dangerous to operate recursively on '/'\nchgrp: use --no-preserve-root to override this failsafe");
+        .stderr_is(preserve_root_message("/tmp/__root__/."));
 
     use std::fs;
     fs::remove_file("/tmp/__root__").unwrap();
@@ -110,7 +123,8 @@ fn test_reference() {
             .arg("--reference=/etc/passwd")
             .arg("/etc")
             .fails()
-            .stderr_is("chgrp: changing group of '/etc': Operation not permitted (os error 1)\nfailed to change group of '/etc' from root to root");
+            .stderr_is("chgrp: changing group of '/etc': Operation not permitted")
+            .stdout_is("failed to change group of '/etc' from root to root\n");
     }
 }
 
@@ -134,7 +148,7 @@ fn test_big_p() {
             .arg("/proc/self/cwd")
             .fails()
             .stderr_is(
-                "chgrp: changing group of '/proc/self/cwd': Operation not permitted (os error 1)\n",
+                "chgrp: changing group of '/proc/self/cwd': Operation not permitted\n",
             );
     }
 }
@@ -156,3 +170,87 @@ fn test_big_h() {
         );
     }
 }
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_numeric_and_empty_group() {
+    use std::os::unix::fs::MetadataExt;
+
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+    at.touch("f");
+    let gid = at.metadata("f").gid();
+
+    scene
+        .ucmd()
+        .arg("-c")
+        .arg(format!("+{}", gid))
+        .arg("f")
+        .succeeds()
+        .no_stdout();
+    scene
+        .ucmd()
+        .arg("-v")
+        .arg("")
+        .arg("f")
+        .succeeds()
+        .stdout_only("ownership of 'f' retained\n");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_reference_file() {
+    use std::os::unix::fs::MetadataExt;
+
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+    at.touch("ref");
+    at.touch("f");
+    if get_effective_uid() == 0 {
+        scene.ucmd().arg("1").arg("ref").succeeds();
+    }
+
+    scene
+        .ucmd()
+        .arg("--reference=ref")
+        .arg("f")
+        .succeeds()
+        .no_stdout();
+    assert_eq!(at.metadata("f").gid(), at.metadata("ref").gid());
+
+    scene
+        .ucmd()
+        .arg("--reference=nope")
+        .arg("f")
+        .fails()
+        .stderr_is("chgrp: failed to get attributes of 'nope': No such file or directory");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_from() {
+    use std::os::unix::fs::MetadataExt;
+
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+    at.touch("f");
+
+    scene
+        .ucmd()
+        .arg("--from=auserthatdoesntexist")
+        .arg("0")
+        .arg("f")
+        .fails()
+        .stderr_is("chgrp: invalid user: 'auserthatdoesntexist'");
+
+    if get_effective_uid() != 0 {
+        return;
+    }
+    scene.ucmd().arg("1").arg("f").succeeds();
+
+    // the current group doesn't match, so nothing changes
+    scene.ucmd().arg("--from=:2").arg("3").arg("f").succeeds();
+    assert_eq!(at.metadata("f").gid(), 1);
+    scene.ucmd().arg("--from=0:1").arg("3").arg("f").succeeds();
+    assert_eq!(at.metadata("f").gid(), 3);
+}
@@ -1,6 +1,8 @@
+#![cfg(unix)]
+
 use crate::common::util::*;
-use std::fs::{metadata, set_permissions, OpenOptions};
-use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::fs::{metadata, set_permissions, OpenOptions, Permissions};
+use std::os::unix::fs::{symlink, OpenOptionsExt, PermissionsExt};
 use std::sync::Mutex;
 
 extern crate libc;
@@ -247,6 +249,84 @@ fn test_chmod_ugo_copy() {
     run_tests(tests);
 }
 
+#[test]
+fn test_chmod_clause_list() {
+    let _guard = UMASK_MUTEX.lock();
+
+    let original_umask = unsafe { umask(0o022) };
+    let tests = vec![
+        TestCase {
+            args: vec!["u+s,g-s,o=", TEST_FILE],
+            before: 0o102755,
+            after: 0o104750,
+        },
+        TestCase {
+            args: vec!["u=rw,g=u,o=u-w", TEST_FILE],
+            before: 0o100000,
+            after: 0o100664,
+        },
+        TestCase {
+            args: vec!["u+x,a+X", TEST_FILE],
+            before: 0o100600,
+            after: 0o100711,
+        },
+        TestCase {
+            args: vec!["a+X", TEST_FILE],
+            before: 0o100600,
+            after: 0o100600,
+        },
+        TestCase {
+            args: vec!["u=rwx", TEST_FILE],
+            before: 0o104755,
+            after: 0o100755,
+        },
+        TestCase {
+            args: vec!["=t", TEST_FILE],
+            before: 0o100555,
+            after: 0o101000,
+        },
+    ];
+    run_tests(tests);
+    unsafe {
+        umask(original_umask);
+    }
+}
+
+#[test]
+fn test_chmod_directory_special_bits() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+    at.mkdir("d");
+    let set_mode = |mode| set_permissions(at.plus("d"), Permissions::from_mode(mode)).unwrap();
+    let mode = || at.metadata("d").permissions().mode() & 0o7777;
+
+    for (arg, after) in &[
+        ("755", 0o2755),
+        ("0755", 0o2755),
+        ("00755", 0o755),
+        ("g-s", 0o755),
+        ("u=rwx,g=rx,o=rx", 0o2755),
+        ("o+t", 0o3755),
+        ("a+X", 0o2755),
+    ] {
+        set_mode(0o2755);
+        scene.ucmd().arg(arg).arg("d").succeeds();
+        assert_eq!(*after, mode(), "chmod {}", arg);
+    }
+}
+
+#[test]
+fn test_chmod_invalid_mode() {
+    let scene = TestScenario::new(util_name!());
+    scene.fixtures.touch("f");
+    for mode in &["u", "g=ur", "u+x,", "8", "77777"] {
+        scene.ucmd().arg(mode).arg("f").fails().stderr_is(format!(
+            "chmod: invalid mode: '{}'\nTry 'chmod --help' for more information.",
+            mode
+        ));
+    }
+}
+
 #[test]
 fn test_chmod_many_options() {
     let _guard = UMASK_MUTEX.lock();
@@ -282,6 +362,47 @@ fn test_chmod_reference_file() {
     run_single_test(&tests[0], at, ucmd);
 }
 
+#[test]
+fn test_chmod_reference_verbose() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    mkfile(&at.plus_as_string(REFERENCE_FILE), REFERENCE_PERMS);
+    mkfile(&at.plus_as_string(TEST_FILE), 0o100247);
+    let result = ucmd
+        .arg("-v")
+        .arg("--reference")
+        .arg(REFERENCE_FILE)
+        .arg(TEST_FILE)
+        .succeeds();
+    // only permission bits are compared, so nothing changes
+    assert!(result
+        .stdout
+        .contains("mode of 'file' retained as 0247 (-w-r--rwx)"));
+}
+
+#[test]
+fn test_chmod_reference_recursive() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("d");
+    mkfile(&at.plus_as_string(REFERENCE_FILE), 0o100751);
+    mkfile(&at.plus_as_string("d/f"), 0o100600);
+    ucmd.arg("-R")
+        .arg("--reference=reference")
+        .arg("d")
+        .succeeds();
+    assert_eq!(at.metadata("d").permissions().mode(), 0o40751);
+    assert_eq!(at.metadata("d/f").permissions().mode(), 0o100751);
+}
+
+#[test]
+fn test_chmod_reference_missing() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch(TEST_FILE);
+    ucmd.arg("--reference=nope")
+        .arg(TEST_FILE)
+        .fails()
+        .stderr_is("chmod: error: failed to get attributes of 'nope': No such file or directory");
+}
+
 #[test]
 fn test_chmod_recursive() {
     let _guard = UMASK_MUTEX.lock();
@@ -312,14 +433,92 @@ fn test_chmod_recursive() {
     println!("mode {:o}", at.metadata("a").permissions().mode());
     assert_eq!(at.metadata("a").permissions().mode(), 0o40333);
     assert_eq!(at.metadata("z").permissions().mode(), 0o40333);
-    assert!(result.stderr.contains("to 333 (-wx-wx-wx)"));
-    assert!(result.stderr.contains("to 222 (-w--w--w-)"));
+    assert!(result
+        .stdout
+        .contains("mode of 'a/b' changed from 0777 (rwxrwxrwx) to 0333 (-wx-wx-wx)"));
+    assert!(result
+        .stdout
+        .contains("mode of 'z/y' changed from 0444 (r--r--r--) to 0222 (-w--w--w-)"));
+    assert!(result.stderr.is_empty());
 
     unsafe {
         umask(original_umask);
     }
 }
 
+#[test]
+fn test_chmod_recursive_changes() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("a");
+    mkfile(&at.plus_as_string("a/f"), 0o100644);
+    mkfile(&at.plus_as_string("a/g"), 0o100600);
+    set_permissions(at.plus("a"), Permissions::from_mode(0o755)).unwrap();
+
+    ucmd.arg("-R")
+        .arg("-c")
+        .arg("u=rw,go=")
+        .arg("a")
+        .succeeds()
+        .stdout_only(
+            "mode of 'a' changed from 0755 (rwxr-xr-x) to 0600 (rw-------)\n\
+             mode of 'a/f' changed from 0644 (rw-r--r--) to 0600 (rw-------)\n",
+        );
+}
+
+#[test]
+fn test_chmod_recursive_symlinks() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+    at.mkdir("d");
+    at.mkdir("t");
+    mkfile(&at.plus_as_string("t/f"), 0o100644);
+    symlink("../t", at.plus("d/lt")).unwrap();
+    symlink("t", at.plus("lt")).unwrap();
+
+    // the default, like -H, only follows links named on the command line
+    scene.ucmd().arg("-R").arg("700").arg("d").succeeds();
+    assert_eq!(at.metadata("t/f").permissions().mode(), 0o100644);
+    scene.ucmd().arg("-R").arg("700").arg("lt").succeeds();
+    assert_eq!(at.metadata("t/f").permissions().mode(), 0o100700);
+    scene.ucmd().arg("-RH").arg("711").arg("lt").succeeds();
+    assert_eq!(at.metadata("t/f").permissions().mode(), 0o100711);
+
+    scene
+        .ucmd()
+        .arg("-RP")
+        .arg("-v")
+        .arg("600")
+        .arg("lt")
+        .succeeds()
+        .stdout_only("neither symbolic link 'lt' nor referent has been changed\n");
+    assert_eq!(at.metadata("t/f").permissions().mode(), 0o100711);
+
+    scene.ucmd().arg("-RL").arg("755").arg("d").succeeds();
+    assert_eq!(at.metadata("t").permissions().mode(), 0o40755);
+    assert_eq!(at.metadata("t/f").permissions().mode(), 0o100755);
+}
+
+#[test]
+fn test_chmod_recursive_symlink_loop() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("d");
+    symlink("..", at.plus("d/up")).unwrap();
+    ucmd.arg("-RL").arg("700").arg("d").succeeds().no_stdout();
+    assert_eq!(at.metadata("d").permissions().mode(), 0o40700);
+}
+
+#[test]
+fn test_chmod_continues_after_error() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    mkfile(&at.plus_as_string(TEST_FILE), 0o100644);
+    ucmd.arg("600")
+        .arg("dont-exist")
+        .arg(TEST_FILE)
+        .fails()
+        .stderr_is("chmod: error: cannot access 'dont-exist': No such file or directory");
+    assert_eq!(at.metadata(TEST_FILE).permissions().mode(), 0o100600);
+}
+
 #[test]
 fn test_chmod_non_existing_file() {
     let (_at, mut ucmd) = at_and_ucmd!();
@@ -348,6 +547,24 @@ fn test_chmod_preserve_root() {
         .contains("chmod: error: it is dangerous to operate recursively on '/'"));
 }
 
+#[test]
+fn test_chmod_preserve_root_symlink() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.symlink_file("/", "root");
+    let result = ucmd
+        .arg("-R")
+        .arg("--preserve-root")
+        .arg("755")
+        .arg("root")
+        .fails();
+    assert!(result
+        .stderr
+        .contains("it is dangerous to operate recursively on 'root' (same as '/')"));
+    assert!(result
+        .stderr
+        .contains("use --no-preserve-root to override this failsafe"));
+}
+
 #[test]
 fn test_chmod_symlink_non_existing_file() {
     let scene = TestScenario::new(util_name!());
@@ -1,4 +1,5 @@
 use crate::common::util::*;
+use rust_users::get_effective_uid;
 
 #[test]
 fn test_missing_operand() {
@@ -20,14 +21,15 @@ fn test_enter_chroot_fails() {
 
     at.mkdir("jail");
 
-    let result = ucmd.arg("jail").run();
-
-    assert_eq!(
-        true,
-        result.stderr.starts_with(
-            "chroot: error: cannot chroot to jail: Operation not permitted (os error 1)"
-        )
-    )
+    let result = ucmd.arg("jail").fails();
+    if get_effective_uid() == 0 {
+        // the chroot works, but there's no shell inside to run
+        result.status_code(127);
+    } else {
+        result
+            .status_code(125)
+            .stderr_is("chroot: cannot change root directory to 'jail': Operation not permitted");
+    }
 }
 
 #[test]
@@ -38,21 +40,92 @@ fn test_no_such_directory() {
 
     ucmd.arg("a")
         .fails()
-        .stderr_is("chroot: error: cannot change root directory to `a`: no such directory");
+        .status_code(125)
+        .stderr_is("chroot: cannot change root directory to 'a': Not a directory");
 }
 
 #[test]
 fn test_invalid_user_spec() {
+    new_ucmd!()
+        .arg("--userspec=ARABA:")
+        .arg("/")
+        .arg("true")
+        .fails()
+        .status_code(125)
+        .stderr_is("chroot: invalid spec: 'ARABA:'");
+}
+
+#[test]
+fn test_invalid_groups() {
+    new_ucmd!()
+        .arg("--groups=__nosuchgroup__")
+        .arg("/")
+        .arg("true")
+        .fails()
+        .status_code(125)
+        .stderr_is("chroot: invalid group '__nosuchgroup__'");
+}
+
+#[test]
+fn test_skip_chdir() {
     let (at, mut ucmd) = at_and_ucmd!();
 
-    at.mkdir("a");
+    at.mkdir("jail");
+
+    ucmd.arg("--skip-chdir")
+        .arg("jail")
+        .fails()
+        .status_code(125)
+        .stderr_is(
+            "chroot: option --skip-chdir only permitted if NEWROOT is old '/'\n\
+         Try 'chroot --help' for more information.",
+        );
+
+    // with NEWROOT being '/', the command runs where chroot is run
+    new_ucmd!().arg("/").arg("pwd").succeeds().stdout_is("/\n");
+    let result = new_ucmd!()
+        .arg("--skip-chdir")
+        .arg("/")
+        .arg("pwd")
+        .succeeds();
+    assert_ne!(result.stdout, "/\n");
+}
+
+#[test]
+fn test_command_fails_to_run() {
+    let (at, mut ucmd) = at_and_ucmd!();
 
-    let result = ucmd.arg("a").arg("--userspec=ARABA:").run();
+    at.touch("not_executable");
 
-    assert_eq!(
-        true,
-        result.stderr.starts_with("chroot: error: invalid userspec")
-    );
+    ucmd.arg("/")
+        .arg("this_command_does_not_exist")
+        .fails()
+        .status_code(127)
+        .stderr_is(
+            "chroot: failed to run command 'this_command_does_not_exist': No such file or directory",
+        );
+
+    // not even root may run a file no one may execute
+    new_ucmd!()
+        .arg("/")
+        .arg(at.plus("not_executable"))
+        .fails()
+        .status_code(126);
+}
+
+#[test]
+fn test_userspec_and_groups() {
+    if get_effective_uid() != 0 {
+        return;
+    }
+    new_ucmd!()
+        .arg("--userspec=+1:+2")
+        .arg("--groups=+3")
+        .arg("/")
+        .arg("id")
+        .arg("-G")
+        .succeeds()
+        .stdout_is("2 3\n");
 }
 
 #[test]
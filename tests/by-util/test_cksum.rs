@@ -24,3 +24,172 @@ fn test_stdin() {
         .succeeds()
         .stdout_is_fixture("stdin.expected");
 }
+
+#[test]
+fn test_crc_lengths() {
+    // around the 16 bytes CRCs are computed in, and the 64 bytes they can be
+    // folded in
+    for &(len, expected) in &[
+        (15, "3277312009 15\n"),
+        (16, "2127348151 16\n"),
+        (63, "4249778121 63\n"),
+        (64, "602315682 64\n"),
+        (65, "3891930975 65\n"),
+        (1000, "1225309773 1000\n"),
+    ] {
+        let input: Vec<u8> = (0..len).map(|i: u32| (i * 7 % 251) as u8).collect();
+        new_ucmd!().pipe_in(input).succeeds().stdout_only(expected);
+    }
+}
+
+#[test]
+fn test_debug() {
+    new_ucmd!()
+        .args(&["--debug", "lorem_ipsum.txt", "alice_in_wonderland.txt"])
+        .succeeds()
+        .stderr_contains(&"hardware support")
+        .stdout_is_fixture("multiple_files.expected");
+}
+
+#[test]
+fn test_algorithms() {
+    for (algorithm, expected) in &[
+        ("crc", "378294376 772 lorem_ipsum.txt\n"),
+        ("sysv", "6985 2 lorem_ipsum.txt\n"),
+        ("bsd", "08109     1 lorem_ipsum.txt\n"),
+        ("md5", "MD5 (lorem_ipsum.txt) = cd724690f7dc61775dfac400a71f2caa\n"),
+        (
+            "sm3",
+            "SM3 (lorem_ipsum.txt) = 6d296b805d060bfed22808df308dbb9b4317794dd4ed6740a10770a782699bc2\n",
+        ),
+    ] {
+        new_ucmd!()
+            .arg("-a")
+            .arg(algorithm)
+            .arg("lorem_ipsum.txt")
+            .succeeds()
+            .stdout_only(expected);
+    }
+}
+
+#[test]
+fn test_untagged() {
+    new_ucmd!()
+        .args(&["-a", "sha1", "--untagged", "lorem_ipsum.txt"])
+        .succeeds()
+        .stdout_only("ab1dd0bae1d8883a3d18a66de6afbd28252cfbef  lorem_ipsum.txt\n");
+    // the last of --tag and --untagged wins
+    new_ucmd!()
+        .args(&["-a", "sha1", "--untagged", "--tag", "lorem_ipsum.txt"])
+        .succeeds()
+        .stdout_only("SHA1 (lorem_ipsum.txt) = ab1dd0bae1d8883a3d18a66de6afbd28252cfbef\n");
+}
+
+#[test]
+fn test_length() {
+    new_ucmd!()
+        .args(&["-a", "blake2b", "-l", "64", "lorem_ipsum.txt"])
+        .succeeds()
+        .stdout_only("BLAKE2b-64 (lorem_ipsum.txt) = 5802d5dd438a785c\n");
+    new_ucmd!()
+        .args(&["-a", "blake2b", "-l", "12", "lorem_ipsum.txt"])
+        .fails()
+        .stderr_is("cksum: invalid length: '12'\ncksum: length is not a multiple of 8");
+    new_ucmd!()
+        .args(&["-a", "md5", "-l", "64", "lorem_ipsum.txt"])
+        .fails()
+        .stderr_is("cksum: --length is only supported with --algorithm=blake2b");
+}
+
+#[test]
+fn test_invalid_algorithm() {
+    new_ucmd!()
+        .args(&["-a", "sha3", "lorem_ipsum.txt"])
+        .fails()
+        .status_code(1)
+        .stderr_is(
+            "cksum: invalid argument 'sha3' for '--algorithm'\n\
+             Valid arguments are:\n  - 'bsd'\n  - 'sysv'\n  - 'crc'\n  - 'md5'\n  - 'sha1'\n  \
+             - 'sha224'\n  - 'sha256'\n  - 'sha384'\n  - 'sha512'\n  - 'blake2b'\n  - 'sm3'\n\
+             Try 'cksum --help' for more information.",
+        );
+}
+
+#[test]
+fn test_check() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write(
+        "sums",
+        "MD5 (lorem_ipsum.txt) = cd724690f7dc61775dfac400a71f2caa\n\
+         SHA1 (alice_in_wonderland.txt) = 0000000000000000000000000000000000000000\n\
+         cd724690f7dc61775dfac400a71f2caa  lorem_ipsum.txt\n",
+    );
+    // the algorithm of each line is the one it's tagged with
+    ucmd.args(&["-c", "sums"])
+        .fails()
+        .status_code(1)
+        .stdout_is("lorem_ipsum.txt: OK\nalice_in_wonderland.txt: FAILED\n")
+        .stderr_is(
+            "cksum: WARNING: 1 line is improperly formatted\n\
+             cksum: WARNING: 1 computed checksum did NOT match",
+        );
+}
+
+#[test]
+fn test_check_algorithm() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write(
+        "sums",
+        "cd724690f7dc61775dfac400a71f2caa  lorem_ipsum.txt\n\
+         SHA1 (lorem_ipsum.txt) = ab1dd0bae1d8883a3d18a66de6afbd28252cfbef\n",
+    );
+    // untagged lines are read as the given algorithm's, and lines tagged
+    // with another algorithm are improperly formatted
+    ucmd.args(&["-a", "md5", "-c", "-w", "sums"])
+        .succeeds()
+        .stdout_is("lorem_ipsum.txt: OK\n")
+        .stderr_is(
+            "cksum: sums: 2: improperly formatted MD5 checksum line\n\
+             cksum: WARNING: 1 line is improperly formatted",
+        );
+}
+
+#[test]
+fn test_check_legacy() {
+    new_ucmd!()
+        .args(&["-a", "sysv", "-c", "lorem_ipsum.txt"])
+        .fails()
+        .status_code(1)
+        .stderr_is("cksum: --check is not supported with --algorithm={bsd,sysv,crc}");
+}
+
+#[test]
+fn test_base64() {
+    let scene = TestScenario::new(util_name!());
+    scene
+        .ucmd()
+        .args(&["-a", "md5", "--base64", "lorem_ipsum.txt"])
+        .succeeds()
+        .stdout_only("MD5 (lorem_ipsum.txt) = zXJGkPfcYXdd+sQApx8sqg==\n");
+    scene
+        .fixtures
+        .write("sums", "MD5 (lorem_ipsum.txt) = zXJGkPfcYXdd+sQApx8sqg==\n");
+    scene
+        .ucmd()
+        .args(&["-c", "sums"])
+        .succeeds()
+        .stdout_only("lorem_ipsum.txt: OK\n");
+}
+
+#[test]
+fn test_raw() {
+    // 6985, big-endian
+    new_ucmd!()
+        .args(&["-a", "sysv", "--raw", "lorem_ipsum.txt"])
+        .succeeds()
+        .stdout_only_bytes(b"\x1b\x49");
+    new_ucmd!()
+        .args(&["--raw", "lorem_ipsum.txt", "alice_in_wonderland.txt"])
+        .fails()
+        .stderr_is("cksum: the --raw option is not supported with multiple files");
+}
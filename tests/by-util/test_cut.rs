@@ -111,6 +111,104 @@ fn test_complement() {
         .stdout_only("9\n8\n7\n");
 }
 
+#[test]
+fn test_byte_output_delimiter() {
+    new_ucmd!()
+        .args(&["-b", "1-2,5-", "--output-delimiter=:"])
+        .pipe_in("abcdefg\n")
+        .succeeds()
+        .stdout_only("ab:efg\n");
+    // overlapping ranges are merged, adjacent ones are not
+    new_ucmd!()
+        .args(&["-c", "1-2,3-4,4-5", "--output-delimiter=:"])
+        .pipe_in("abcdefg\n")
+        .succeeds()
+        .stdout_only("ab:cde\n");
+}
+
+#[test]
+fn test_empty_output_delimiter_is_nul() {
+    new_ucmd!()
+        .args(&["-b", "1,3", "--output-delimiter="])
+        .pipe_in("abc\n")
+        .succeeds()
+        .stdout_only("a\0c\n");
+}
+
+#[test]
+fn test_byte_complement() {
+    new_ucmd!()
+        .args(&["-b", "1,3", "--complement", "--output-delimiter=:"])
+        .pipe_in("abcdefg\n")
+        .succeeds()
+        .stdout_only("b:defg\n");
+    new_ucmd!()
+        .args(&["-b", "4-", "--complement"])
+        .pipe_in("abcdefg\n")
+        .succeeds()
+        .stdout_only("abc\n");
+}
+
+#[test]
+fn test_characters_are_utf8_aware() {
+    new_ucmd!()
+        .args(&["-c", "2-3"])
+        .pipe_in("aéüb\n")
+        .succeeds()
+        .stdout_only("éü\n");
+    new_ucmd!()
+        .args(&["-c", "2", "--complement"])
+        .pipe_in("日本語\n")
+        .succeeds()
+        .stdout_only("日語\n");
+}
+
+#[test]
+fn test_bytes_split_characters() {
+    new_ucmd!()
+        .args(&["-b", "1-2"])
+        .pipe_in("aé\n")
+        .succeeds()
+        .stdout_is_bytes(b"a\xc3\n");
+}
+
+#[test]
+fn test_bytes_no_split() {
+    new_ucmd!()
+        .args(&["-n", "-b", "1-2"])
+        .pipe_in("aéb\n")
+        .succeeds()
+        .stdout_only("a\n");
+    new_ucmd!()
+        .args(&["-n", "-b", "3-4"])
+        .pipe_in("aéb\n")
+        .succeeds()
+        .stdout_only("éb\n");
+}
+
+#[test]
+fn test_open_field_range() {
+    new_ucmd!()
+        .args(&["-d:", "-f", "1,3-"])
+        .pipe_in("a:b:c:d\ne\nf:g\n")
+        .succeeds()
+        .stdout_only("a:c:d\ne\nf\n");
+    new_ucmd!()
+        .args(&["-d:", "-f", "2-", "--output-delimiter=,"])
+        .pipe_in("a:b:c:d")
+        .succeeds()
+        .stdout_only("b,c,d\n");
+}
+
+#[test]
+fn test_multibyte_delimiter() {
+    new_ucmd!()
+        .args(&["-d", "é", "-f", "2"])
+        .pipe_in("aébéc\n")
+        .succeeds()
+        .stdout_only("b\n");
+}
+
 #[test]
 fn test_zero_terminated() {
     new_ucmd!()
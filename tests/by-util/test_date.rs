@@ -2,6 +2,7 @@ extern crate regex;
 
 use self::regex::Regex;
 use crate::common::util::*;
+use filetime::FileTime;
 #[cfg(all(unix, not(target_os = "macos")))]
 use rust_users::*;
 
@@ -143,7 +144,7 @@ fn test_date_set_valid() {
             .arg("--set")
             .arg("2020-03-12 13:30:00+08:00")
             .succeeds();
-        result.no_stdout().no_stderr();
+        result.no_stderr();
     }
 }
 
@@ -195,30 +196,390 @@ fn test_date_set_valid_2() {
 
 #[test]
 #[cfg(all(unix, not(target_os = "macos")))]
-/// TODO: expected to fail currently; change to succeeds() when required.
 fn test_date_set_valid_3() {
     if get_effective_uid() == 0 {
         let (_, mut ucmd) = at_and_ucmd!();
         let result = ucmd
             .arg("--set")
             .arg("Sat 20 Mar 2021 14:53:01") // Local timezone
-            .fails();
-        let result = result.no_stdout();
-        assert!(result.stderr.starts_with("date: invalid date "));
+            .succeeds();
+        result.no_stderr();
     }
 }
 
 #[test]
 #[cfg(all(unix, not(target_os = "macos")))]
-/// TODO: expected to fail currently; change to succeeds() when required.
 fn test_date_set_valid_4() {
     if get_effective_uid() == 0 {
         let (_, mut ucmd) = at_and_ucmd!();
         let result = ucmd
             .arg("--set")
             .arg("2020-03-11 21:45:00") // Local timezone
-            .fails();
-        let result = result.no_stdout();
-        assert!(result.stderr.starts_with("date: invalid date "));
+            .succeeds();
+        result.no_stderr();
+    }
+}
+
+#[test]
+fn test_date_epoch() {
+    new_ucmd!()
+        .args(&["-u", "-d", "@1234567890", "+%F %T"])
+        .succeeds()
+        .stdout_only("2009-02-13 23:31:30\n");
+    new_ucmd!()
+        .args(&["-u", "-d", "@-1.5", "+%F %T"])
+        .succeeds()
+        .stdout_only("1969-12-31 23:59:58\n");
+}
+
+#[test]
+fn test_date_absolute_strings() {
+    for &(input, expected) in &[
+        ("2004-02-29", "2004-02-29 00:00:00\n"),
+        ("2004-02-29T14:05:09", "2004-02-29 14:05:09\n"),
+        ("2/29/04 3pm", "2004-02-29 15:00:00\n"),
+        ("29 Feb 2004 12:30 am", "2004-02-29 00:30:00\n"),
+        ("Sun, 29 Feb 2004 16:21:42 -0800", "2004-03-01 00:21:42\n"),
+        ("20040229 1405 EST", "2004-02-29 19:05:00\n"),
+    ] {
+        new_ucmd!()
+            .args(&["-u", "-d", input, "+%F %T"])
+            .succeeds()
+            .stdout_only(expected);
+    }
+}
+
+#[test]
+fn test_date_relative_strings() {
+    for &(input, expected) in &[
+        ("2004-02-29 2 weeks ago", "2004-02-15 00:00:00\n"),
+        ("2004-02-29 10:00 tomorrow", "2004-03-01 10:00:00\n"),
+        ("yesterday 2004-03-01", "2004-02-29 00:00:00\n"),
+        ("2004-01-31 +1 month", "2004-03-02 00:00:00\n"),
+        ("2004-02-29 last year", "2003-03-01 00:00:00\n"),
+        ("2004-02-29 23:00 3 hours", "2004-03-01 02:00:00\n"),
+        ("2004-02-29 1 day 2 hours ago", "2004-02-29 22:00:00\n"),
+    ] {
+        new_ucmd!()
+            .args(&["-u", "-d", input, "+%F %T"])
+            .succeeds()
+            .stdout_only(expected);
+    }
+}
+
+#[test]
+fn test_date_weekday_strings() {
+    new_ucmd!()
+        .args(&["-d", "next fri", "+%u %T"])
+        .succeeds()
+        .stdout_only("5 00:00:00\n");
+    new_ucmd!()
+        .args(&["-d", "last sunday 10:00", "+%u %T"])
+        .succeeds()
+        .stdout_only("7 10:00:00\n");
+}
+
+#[test]
+fn test_date_tz_prefix() {
+    new_ucmd!()
+        .args(&["-u", "-d", "TZ=\"XXX-5:30\" 2009-02-14 05:01:30", "+%s"])
+        .succeeds()
+        .stdout_only("1234567890\n");
+    // the prefix applies to the date string, not to the output
+    new_ucmd!()
+        .env("TZ", "EST5EDT,M3.2.0,M11.1.0")
+        .args(&["-d", "TZ=\"UTC0\" 2021-03-14 07:30", "+%F %T %Z"])
+        .succeeds()
+        .stdout_only("2021-03-14 03:30:00 EDT\n");
+    new_ucmd!()
+        .args(&["-d", "TZ=\"a\\b\" 12:00"])
+        .fails()
+        .stderr_is("date: invalid date 'TZ=\"a\\b\" 12:00'");
+}
+
+#[test]
+fn test_date_dst() {
+    for &(input, expected) in &[
+        ("@1615701600", "2021-03-14 01:00:00 EST -0500\n"),
+        ("@1615705200", "2021-03-14 03:00:00 EDT -0400\n"),
+        (
+            "2021-03-13 02:30 tomorrow",
+            "2021-03-14 03:30:00 EDT -0400\n",
+        ),
+    ] {
+        new_ucmd!()
+            .env("TZ", "EST5EDT,M3.2.0,M11.1.0")
+            .args(&["-d", input, "+%F %T %Z %z"])
+            .succeeds()
+            .stdout_only(expected);
+    }
+    // the clocks skip from 02:00 to 03:00
+    new_ucmd!()
+        .env("TZ", "EST5EDT,M3.2.0,M11.1.0")
+        .args(&["-d", "2021-03-14 02:30"])
+        .fails()
+        .stderr_is("date: invalid date '2021-03-14 02:30'");
+}
+
+#[test]
+fn test_date_invalid_string() {
+    for input in &["2004-02-30", "25:00", "10:00 10:00", "next", "@1 day"] {
+        new_ucmd!()
+            .args(&["-d", input])
+            .fails()
+            .no_stdout()
+            .stderr_is(format!("date: invalid date '{}'", input));
+    }
+}
+
+#[test]
+fn test_date_iso_8601_precision() {
+    for &(arg, expected) in &[
+        ("-I", "2009-02-13\n"),
+        ("--iso-8601", "2009-02-13\n"),
+        ("--iso-8601=hours", "2009-02-13T23+00:00\n"),
+        ("--iso-8601=minutes", "2009-02-13T23:31+00:00\n"),
+        ("--iso-8601=sec", "2009-02-13T23:31:30+00:00\n"),
+        ("--iso-8601=ns", "2009-02-13T23:31:30,123456789+00:00\n"),
+    ] {
+        new_ucmd!()
+            .args(&["-u", "-d", "@1234567890.123456789", arg])
+            .succeeds()
+            .stdout_only(expected);
     }
 }
+
+#[test]
+fn test_date_rfc_3339_precision() {
+    for &(arg, expected) in &[
+        ("--rfc-3339=date", "2009-02-13\n"),
+        ("--rfc-3339=seconds", "2009-02-13 23:31:30+00:00\n"),
+        ("--rfc-3339=ns", "2009-02-13 23:31:30.123456789+00:00\n"),
+    ] {
+        new_ucmd!()
+            .args(&["-u", "-d", "@1234567890.123456789", arg])
+            .succeeds()
+            .stdout_only(expected);
+    }
+}
+
+#[test]
+fn test_date_rfc_email_output() {
+    new_ucmd!()
+        .args(&["-u", "-d", "@1234567890", "-R"])
+        .succeeds()
+        .stdout_only("Fri, 13 Feb 2009 23:31:30 +0000\n");
+}
+
+#[test]
+fn test_date_default_format() {
+    new_ucmd!()
+        .args(&["-u", "-d", "@1234567890"])
+        .succeeds()
+        .stdout_only("Fri Feb 13 23:31:30 UTC 2009\n");
+}
+
+#[test]
+fn test_date_format_nanoseconds() {
+    for &(format, expected) in &[
+        ("+%N", "012000000\n"),
+        ("+%3N", "012\n"),
+        ("+%12N", "012000000000\n"),
+        ("+%-5N", "012\n"),
+        ("+%_5N", "012  \n"),
+        ("+%s.%N", "1234567890.012000000\n"),
+    ] {
+        new_ucmd!()
+            .args(&["-u", "-d", "@1234567890.012", format])
+            .succeeds()
+            .stdout_only(expected);
+    }
+}
+
+#[test]
+fn test_date_format_flags() {
+    for &(format, expected) in &[
+        ("+%-m/%-d", "2/5\n"),
+        ("+%_m|%e|%k", " 2| 5|23\n"),
+        ("+%5d|%_5d|%-5d", "00005|    5|5\n"),
+        ("+%^a %^B", "THU FEBRUARY\n"),
+        ("+%#a %#Z %#p", "THU utc pm\n"),
+        ("+%10A|%-10A|%010A", "  Thursday|Thursday|00Thursday\n"),
+        ("+%P %q %j %-j", "pm 1 036 36\n"),
+        (
+            "+%+6Y %C %y %G %g %V %U %W",
+            "+02009 20 09 2009 09 06 05 05\n",
+        ),
+        ("+%10T|%^c", "  23:31:30|THU FEB  5 23:31:30 2009\n"),
+        ("+%Q|%5Q|%:y|%Ey", "%Q|  %5Q|%:y|09\n"),
+    ] {
+        new_ucmd!()
+            .args(&["-u", "-d", "2009-02-05 23:31:30", format])
+            .succeeds()
+            .stdout_only(expected);
+    }
+}
+
+#[test]
+fn test_date_format_zone() {
+    for &(format, expected) in &[
+        ("+%z", "+0000\n"),
+        ("+%:z", "+00:00\n"),
+        ("+%::z", "+00:00:00\n"),
+        ("+%:::z", "+00\n"),
+        ("+%-z|%_z|%10:z", "+0|   +0|+000000:00\n"),
+    ] {
+        new_ucmd!()
+            .args(&["-u", "-d", "@0", format])
+            .succeeds()
+            .stdout_only(expected);
+    }
+}
+
+#[test]
+fn test_date_invalid_precision() {
+    new_ucmd!()
+        .arg("--iso-8601=foo")
+        .fails()
+        .stderr_contains(&"invalid argument 'foo' for '--iso-8601'");
+    new_ucmd!()
+        .arg("--rfc-3339=hours")
+        .fails()
+        .stderr_contains(&"invalid argument 'hours' for '--rfc-3339'");
+}
+
+#[test]
+fn test_date_multiple_formats() {
+    new_ucmd!()
+        .args(&["-I", "-R"])
+        .fails()
+        .stderr_is("date: multiple output formats specified");
+    new_ucmd!()
+        .args(&["--rfc-3339=ns", "+%s"])
+        .fails()
+        .stderr_is("date: multiple output formats specified");
+}
+
+#[test]
+fn test_date_reference_file() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch("ref");
+    let mtime = FileTime::from_unix_time(1_234_567_890, 0);
+    filetime::set_file_times(at.plus("ref"), mtime, mtime).unwrap();
+    ucmd.args(&["-u", "-r", "ref", "+%F %T"])
+        .succeeds()
+        .stdout_only("2009-02-13 23:31:30\n");
+}
+
+#[test]
+fn test_date_reference_file_missing() {
+    new_ucmd!()
+        .args(&["-r", "does-not-exist"])
+        .fails()
+        .no_stdout()
+        .stderr_is("date: does-not-exist: No such file or directory");
+}
+
+#[test]
+fn test_date_file() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("dates", "@0\nfoo\n@1\n");
+    ucmd.args(&["-u", "-f", "dates", "+%s"])
+        .fails()
+        .stdout_is("0\n1\n")
+        .stderr_is("date: invalid date 'foo'");
+}
+
+#[test]
+fn test_date_file_stdin() {
+    new_ucmd!()
+        .args(&["-u", "-f", "-", "+%F %T"])
+        .pipe_in("@5\n2009-02-13 23:31:30\n")
+        .succeeds()
+        .stdout_only("1970-01-01 00:00:05\n2009-02-13 23:31:30\n");
+}
+
+#[test]
+fn test_date_file_missing() {
+    new_ucmd!()
+        .args(&["-f", "does-not-exist"])
+        .fails()
+        .no_stdout()
+        .stderr_is("date: does-not-exist: No such file or directory");
+}
+
+#[test]
+fn test_date_exclusive_sources() {
+    new_ucmd!()
+        .args(&["-d", "now", "-r", "ref"])
+        .fails()
+        .stderr_is("date: the options to specify dates for printing are mutually exclusive");
+    new_ucmd!()
+        .args(&["-d", "now", "-s", "now"])
+        .fails()
+        .stderr_is("date: the options to print and set the time may not be used together");
+}
+
+#[test]
+fn test_date_debug() {
+    let result = new_ucmd!()
+        .args(&[
+            "--debug",
+            "-u",
+            "-d",
+            "2004-02-29 14:05 +0100 1 day",
+            "+%F %T",
+        ])
+        .succeeds();
+    result.stdout_is("2004-03-01 13:05:00\n");
+    assert!(result
+        .stderr
+        .contains("date: parsed date part: (Y-M-D) 2004-02-29\n"));
+    assert!(result.stderr.contains("date: parsed zone part: UTC+01\n"));
+    assert!(result
+        .stderr
+        .contains("date: parsed relative part: +1 day(s)\n"));
+    assert!(result
+        .stderr
+        .contains("date: final: 1078146300.000000000 (epoch-seconds)\n"));
+}
+
+#[test]
+fn test_date_debug_invalid() {
+    new_ucmd!()
+        .args(&["--debug", "-d", "foo"])
+        .fails()
+        .stderr_is("date: error: parsing failed\ndate: invalid date 'foo'");
+}
+
+#[test]
+#[cfg(feature = "feat_nls")]
+fn test_date_translated_names() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir_all("locale/de");
+    at.write(
+        "locale/de/uutils.po",
+        "msgid \"January\"\nmsgstr \"Januar\"\n\n\
+         msgctxt \"abbreviated\"\nmsgid \"Tue\"\nmsgstr \"Di\"\n",
+    );
+    ucmd.env("UUTILS_LOCALEDIR", at.plus_as_string("locale"))
+        .env("LC_ALL", "de_DE.UTF-8")
+        .args(&["-u", "-d", "2024-01-02", "+%a %A %b %B"])
+        .succeeds()
+        .stdout_only("Di Tuesday Jan Januar\n");
+}
+
+#[test]
+fn test_date_rfc_email_untranslated() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir_all("locale/de");
+    at.write(
+        "locale/de/uutils.po",
+        "msgid \"January\"\nmsgstr \"Januar\"\n",
+    );
+    ucmd.env("UUTILS_LOCALEDIR", at.plus_as_string("locale"))
+        .env("LC_ALL", "de")
+        .args(&["-u", "-d", "2024-01-02", "-R"])
+        .succeeds()
+        .stdout_only("Tue, 02 Jan 2024 00:00:00 +0000\n");
+}
@@ -28,4 +28,299 @@ fn test_df_compatible_si() {
     assert!(result.success);
 }
 
-// ToDO: more tests...
+#[test]
+fn test_df_output() {
+    let result = new_ucmd!().arg("--output").arg(".").succeeds();
+    let header = result.stdout_str().lines().next().unwrap();
+    assert_eq!(
+        header.split_whitespace().collect::<Vec<_>>(),
+        vec![
+            "Filesystem",
+            "Type",
+            "Inodes",
+            "IUsed",
+            "IFree",
+            "IUse%",
+            "1K-blocks",
+            "Used",
+            "Avail",
+            "Use%",
+            "File",
+            "Mounted",
+            "on"
+        ]
+    );
+}
+
+#[test]
+fn test_df_output_field_list() {
+    let result = new_ucmd!()
+        .args(&["--output=target,file,size", "."])
+        .succeeds();
+    let lines: Vec<_> = result.stdout_str().lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("Mounted on File "));
+    assert!(lines[0].ends_with("1K-blocks"));
+    assert_eq!(lines[1].split_whitespace().nth(1), Some("."));
+}
+
+#[test]
+fn test_df_output_invalid_field() {
+    new_ucmd!().arg("--output=size,foo").fails().stderr_is(
+        "df: option --output: field 'foo' unknown\n\
+             Try 'df --help' for more information.",
+    );
+}
+
+#[test]
+fn test_df_output_duplicate_field() {
+    new_ucmd!()
+        .arg("--output=size,used,size")
+        .fails()
+        .stderr_is(
+            "df: option --output: field 'size' used more than once\n\
+             Try 'df --help' for more information.",
+        );
+}
+
+#[test]
+fn test_df_output_conflicts() {
+    new_ucmd!().args(&["-i", "--output"]).fails().stderr_is(
+        "df: options -i and --output are mutually exclusive\n\
+             Try 'df --help' for more information.",
+    );
+}
+
+#[test]
+fn test_df_type_both_selected_and_excluded() {
+    new_ucmd!()
+        .args(&["-t", "ext4", "-x", "ext4"])
+        .fails()
+        .stderr_is("df: error: file system type 'ext4' both selected and excluded");
+}
+
+#[test]
+fn test_df_type_none_selected() {
+    new_ucmd!()
+        .args(&["-t", "no-such-fs-type"])
+        .fails()
+        .stderr_is("df: error: no file systems processed");
+}
+
+#[test]
+fn test_df_type_filters() {
+    let result = new_ucmd!().args(&["--output=fstype", "."]).succeeds();
+    let fs_type = result.stdout_str().lines().nth(1).unwrap().trim();
+
+    let result = new_ucmd!()
+        .args(&["-T", "-t", fs_type, "-t", "no-such-fs-type"])
+        .succeeds();
+    let mut lines = result.stdout_str().lines();
+    assert_eq!(
+        lines.next().unwrap().split_whitespace().nth(1),
+        Some("Type")
+    );
+    assert!(lines.all(|line| line.split_whitespace().nth(1) == Some(fs_type)));
+
+    let result = new_ucmd!().args(&["-T", "-x", fs_type]).run();
+    assert!(result
+        .stdout_str()
+        .lines()
+        .skip(1)
+        .all(|line| line.split_whitespace().nth(1) != Some(fs_type)));
+}
+
+#[test]
+fn test_df_local() {
+    new_ucmd!().arg("-l").succeeds();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_df_all_includes_dummy() {
+    let result = new_ucmd!().args(&["--output=target"]).succeeds();
+    assert!(!result.stdout_str().lines().any(|line| line == "/proc"));
+
+    let result = new_ucmd!().args(&["-a", "--output=target"]).succeeds();
+    assert!(result.stdout_str().lines().any(|line| line == "/proc"));
+}
+
+fn size_header(ucmd: &mut UCommand) -> String {
+    let result = ucmd.arg("--output=size").arg(".").succeeds();
+    result
+        .stdout_str()
+        .lines()
+        .next()
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+#[test]
+fn test_df_block_size() {
+    assert_eq!(size_header(new_ucmd!().arg("-B1M")), "1M-blocks");
+    assert_eq!(
+        size_header(new_ucmd!().arg("--block-size=1000")),
+        "1kB-blocks"
+    );
+    assert_eq!(size_header(new_ucmd!().arg("-BKB")), "1kB-blocks");
+    assert_eq!(size_header(new_ucmd!().arg("-B512")), "512B-blocks");
+    assert_eq!(size_header(new_ucmd!().args(&["-h", "-k"])), "1K-blocks");
+    assert_eq!(size_header(new_ucmd!().args(&["-k", "-H"])), "Size");
+    assert_eq!(
+        size_header(new_ucmd!().env("DF_BLOCK_SIZE", "2M")),
+        "2M-blocks"
+    );
+    assert_eq!(
+        size_header(new_ucmd!().env("POSIXLY_CORRECT", "1")),
+        "512B-blocks"
+    );
+}
+
+#[test]
+fn test_df_block_size_scaling() {
+    let kilo = new_ucmd!().args(&["-B1K", "--output=size", "."]).succeeds();
+    let bytes = new_ucmd!().args(&["-B1", "--output=size", "."]).succeeds();
+    let kilo: u64 = kilo
+        .stdout_str()
+        .lines()
+        .nth(1)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    let bytes: u64 = bytes
+        .stdout_str()
+        .lines()
+        .nth(1)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    assert_eq!(kilo, (bytes + 1023) / 1024);
+}
+
+#[test]
+fn test_df_invalid_block_size() {
+    new_ucmd!()
+        .arg("-B0")
+        .fails()
+        .stderr_is("df: error: invalid --block-size argument '0'");
+    new_ucmd!()
+        .arg("-B1Q")
+        .fails()
+        .stderr_is("df: error: invalid suffix in --block-size argument '1Q'");
+    new_ucmd!()
+        .arg("-B1Y")
+        .fails()
+        .stderr_is("df: error: --block-size argument '1Y' too large");
+}
+
+#[test]
+fn test_df_portability() {
+    let result = new_ucmd!().args(&["-P", "."]).succeeds();
+    let header: Vec<_> = result
+        .stdout_str()
+        .lines()
+        .next()
+        .unwrap()
+        .split_whitespace()
+        .collect();
+    assert_eq!(
+        header,
+        vec![
+            "Filesystem",
+            "1024-blocks",
+            "Used",
+            "Available",
+            "Capacity",
+            "Mounted",
+            "on"
+        ]
+    );
+}
+
+#[test]
+fn test_df_total() {
+    let result = new_ucmd!()
+        .args(&["--total", "-B1", "--output=source,size,target"])
+        .succeeds();
+    let lines: Vec<_> = result.stdout_str().lines().collect();
+    let total: Vec<_> = lines.last().unwrap().split_whitespace().collect();
+    assert_eq!(total[0], "total");
+    assert_eq!(total[2], "-");
+    let sum: u64 = lines[1..lines.len() - 1]
+        .iter()
+        .map(|line| {
+            line.split_whitespace()
+                .nth(1)
+                .unwrap()
+                .parse::<u64>()
+                .unwrap()
+        })
+        .sum();
+    assert_eq!(total[1].parse::<u64>().unwrap(), sum);
+}
+
+#[test]
+fn test_df_paths_keep_order() {
+    // the file systems are read concurrently, but listed in the order given
+    new_ucmd!()
+        .args(&["--output=file", "/", ".", "/"])
+        .succeeds()
+        .stdout_only("File\n/\n.\n/\n");
+}
+
+#[test]
+fn test_df_inodes() {
+    let result = new_ucmd!().arg("-i").succeeds();
+    let mut lines = result.stdout_str().lines();
+    assert_eq!(
+        lines.next().unwrap().split_whitespace().collect::<Vec<_>>(),
+        vec![
+            "Filesystem",
+            "Inodes",
+            "IUsed",
+            "IFree",
+            "IUse%",
+            "Mounted",
+            "on"
+        ]
+    );
+    for line in lines {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        if fields[1] == "-" {
+            continue;
+        }
+        let total: u64 = fields[1].parse().unwrap();
+        let used: u64 = fields[2].parse().unwrap();
+        let free: u64 = fields[3].parse().unwrap();
+        assert!(used + free <= total);
+        // file systems without inodes, like btrfs, have no percentage
+        if total == 0 {
+            assert_eq!(fields[4], "-");
+        }
+    }
+}
+
+#[test]
+fn test_df_inodes_ignore_block_size() {
+    let inodes = |args: &[&str]| {
+        new_ucmd!()
+            .args(args)
+            .args(&["--output=itotal", "/"])
+            .succeeds()
+            .stdout_move_str()
+    };
+    assert_eq!(inodes(&["-B1M"]), inodes(&["-B1"]));
+}
+
+#[test]
+fn test_df_json() {
+    let stdout = new_ucmd!()
+        .args(&["--json", "--output=target,iused", "/"])
+        .succeeds()
+        .stdout_move_str();
+    assert!(stdout.starts_with(r#"{"target":"/","iused":"#));
+    assert!(stdout.ends_with("}\n"));
+}
@@ -30,3 +30,33 @@ fn test_pwd() {
 fn test_empty() {
     new_ucmd!().arg("").run().stdout_is(".\n");
 }
+
+#[test]
+fn test_dots() {
+    new_ucmd!().arg("foo/.").run().stdout_is("foo\n");
+    new_ucmd!().arg("foo/..").run().stdout_is("foo\n");
+}
+
+#[test]
+fn test_multiple_operands() {
+    new_ucmd!()
+        .args(&["/foo/bar", "baz", "//qux//"])
+        .succeeds()
+        .stdout_only("/foo\n.\n/\n");
+}
+
+#[test]
+fn test_zero() {
+    new_ucmd!()
+        .args(&["-z", "/foo/bar", "baz"])
+        .succeeds()
+        .stdout_only("/foo\0.\0");
+}
+
+#[test]
+fn test_no_args() {
+    new_ucmd!()
+        .fails()
+        .no_stdout()
+        .stderr_is("dirname: error: missing operand\nTry 'dirname --help' for more information.");
+}
@@ -1,5 +1,7 @@
 use crate::common::util::*;
 
+use filetime::FileTime;
+
 const SUB_DIR: &str = "subdir/deeper";
 const SUB_DIR_LINKS: &str = "subdir/links";
 const SUB_FILE: &str = "subdir/links/subwords.txt";
@@ -146,3 +148,435 @@ fn _du_d_flag(s: String) {
         assert_eq!(s, "8\t./subdir\n8\t./\n");
     }
 }
+
+/// The paths that du lists, in sorted order.
+fn du_paths(stdout: &str) -> Vec<&str> {
+    let mut paths: Vec<_> = stdout
+        .lines()
+        .map(|line| line.splitn(2, '\t').nth(1).unwrap())
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn test_du_exclude() {
+    let result = new_ucmd!()
+        .args(&["-a", "--exclude=*.txt", "--exclude=deeper", "subdir"])
+        .succeeds();
+    assert_eq!(
+        du_paths(result.stdout_str()),
+        vec!["subdir", "subdir/links"]
+    );
+}
+
+#[test]
+fn test_du_exclude_path() {
+    // a pattern is matched against the whole path and each part after a '/'
+    let result = new_ucmd!()
+        .args(&["-a", "--exclude=subdir/links", "--exclude=*/words.txt", "."])
+        .succeeds();
+    assert_eq!(
+        du_paths(result.stdout_str()),
+        vec![".", "./subdir", "./subdir/deeper"]
+    );
+}
+
+#[test]
+fn test_du_exclude_operand() {
+    new_ucmd!()
+        .args(&["--exclude=subdir", "subdir"])
+        .succeeds()
+        .no_stdout();
+}
+
+#[test]
+fn test_du_exclude_from() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("patterns", "sub*.txt\n[d]eeper\n");
+    let result = ucmd.args(&["-a", "-X", "patterns", "subdir"]).succeeds();
+    assert_eq!(
+        du_paths(result.stdout_str()),
+        vec!["subdir", "subdir/links"]
+    );
+}
+
+#[test]
+fn test_du_exclude_from_stdin() {
+    let result = new_ucmd!()
+        .args(&["-a", "--exclude-from=-", "subdir/links"])
+        .pipe_in("subwords*\n")
+        .succeeds();
+    assert_eq!(du_paths(result.stdout_str()), vec!["subdir/links"]);
+}
+
+#[test]
+fn test_du_exclude_from_missing() {
+    new_ucmd!()
+        .args(&["-X", "missing"])
+        .fails()
+        .stderr_contains("missing: No such file or directory");
+}
+
+#[test]
+fn test_du_max_depth_all() {
+    // files are only listed down to the maximum depth as well
+    let result = new_ucmd!().args(&["-a", "-d", "1", "subdir"]).succeeds();
+    assert_eq!(
+        du_paths(result.stdout_str()),
+        vec!["subdir", "subdir/deeper", "subdir/links"]
+    );
+}
+
+#[test]
+fn test_du_max_depth_counts_descendants() {
+    let last_line = |args: &[&str]| {
+        let result = new_ucmd!().args(args).succeeds();
+        result.stdout_str().lines().last().unwrap().to_owned()
+    };
+    assert_eq!(last_line(&["-b", "-d", "1", "."]), last_line(&["-b", "."]));
+    assert_eq!(last_line(&["-b", "-d", "0", "."]), last_line(&["-b", "."]));
+}
+
+#[test]
+fn test_du_threshold() {
+    let result = new_ucmd!()
+        .args(&["-ab", "--threshold=100", "subdir/links"])
+        .succeeds();
+    assert!(!result.stdout_str().contains("subwords2.txt"));
+    assert!(result.stdout_str().contains("subwords.txt"));
+
+    new_ucmd!()
+        .args(&[
+            "-b",
+            "-t",
+            "-1K",
+            "subdir/links/subwords.txt",
+            "subdir/links/subwords2.txt",
+        ])
+        .succeeds()
+        .stdout_only("6\tsubdir/links/subwords2.txt\n");
+}
+
+#[test]
+fn test_du_invalid_threshold() {
+    new_ucmd!()
+        .args(&["--threshold=-0"])
+        .fails()
+        .stderr_contains("invalid --threshold argument '-0'");
+    new_ucmd!()
+        .args(&["-t", "1x"])
+        .fails()
+        .stderr_contains("invalid suffix in --threshold argument '1x'");
+}
+
+#[test]
+fn test_du_time() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("dir");
+    at.write("dir/file", "x");
+    let time = |secs| FileTime::from_unix_time(secs, 0);
+    filetime::set_file_times(
+        at.plus_as_string("dir/file"),
+        time(1_000_086_400),
+        time(1_000_000_000),
+    )
+    .unwrap();
+    filetime::set_file_times(at.plus_as_string("dir"), time(0), time(0)).unwrap();
+
+    // a directory shows the latest time of anything in it
+    let result = ucmd
+        .env("TZ", "UTC0")
+        .args(&["-a", "--time", "--time-style=full-iso", "dir"])
+        .succeeds();
+    for line in result.stdout_str().lines() {
+        assert_eq!(
+            line.split('\t').nth(1).unwrap(),
+            "2001-09-09 01:46:40.000000000 +0000"
+        );
+    }
+}
+
+#[test]
+fn test_du_time_word_and_style() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("file", "x");
+    let time = |secs| FileTime::from_unix_time(secs, 0);
+    filetime::set_file_times(
+        at.plus_as_string("file"),
+        time(1_000_086_400),
+        time(1_000_000_000),
+    )
+    .unwrap();
+    ucmd.env("TZ", "UTC0")
+        .args(&["-b", "--time=atime", "--time-style=+%Y/%m/%d", "file"])
+        .succeeds()
+        .stdout_only("1\t2001/09/10\tfile\n");
+}
+
+#[test]
+fn test_du_time_style_from_env() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("file", "x");
+    let time = FileTime::from_unix_time(1_000_000_000, 0);
+    filetime::set_file_times(at.plus_as_string("file"), time, time).unwrap();
+    ucmd.env("TZ", "UTC0")
+        .env("TIME_STYLE", "posix-iso")
+        .args(&["-b", "--time", "file"])
+        .succeeds()
+        .stdout_only("1\t2001-09-09\tfile\n");
+}
+
+#[test]
+fn test_du_invalid_time() {
+    new_ucmd!()
+        .arg("--time=birth")
+        .fails()
+        .stderr_contains("invalid argument 'birth' for '--time'");
+    new_ucmd!()
+        .args(&["--time", "--time-style=long"])
+        .fails()
+        .stderr_contains("invalid argument 'long' for 'time style'");
+    // the style is only checked when times are shown
+    new_ucmd!().arg("--time-style=long").succeeds();
+}
+
+#[test]
+fn test_du_hard_link_operands() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("file", &"x".repeat(1000));
+    std::fs::hard_link(at.plus("file"), at.plus("link")).unwrap();
+    ucmd.args(&["-b", "-c", "file", "link"])
+        .succeeds()
+        .stdout_only("1000\tfile\n1000\ttotal\n");
+}
+
+#[test]
+fn test_du_hard_link_all() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("dir");
+    at.write("dir/file", "x");
+    std::fs::hard_link(at.plus("dir/file"), at.plus("dir/link")).unwrap();
+    let result = ucmd.args(&["-a", "dir"]).succeeds();
+    assert_eq!(result.stdout_str().lines().count(), 2);
+}
+
+#[test]
+fn test_du_count_links() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("file", &"x".repeat(1000));
+    std::fs::hard_link(at.plus("file"), at.plus("link")).unwrap();
+    ucmd.args(&["-b", "-c", "-l", "file", "link"])
+        .succeeds()
+        .stdout_only("1000\tfile\n1000\tlink\n2000\ttotal\n");
+}
+
+#[test]
+fn test_du_repeated_operand() {
+    // a directory given twice is only counted once
+    let once = new_ucmd!().arg("subdir").succeeds().stdout_move_str();
+    new_ucmd!()
+        .args(&["subdir", "subdir"])
+        .succeeds()
+        .stdout_only(once);
+}
+
+#[test]
+fn test_du_apparent_size_scaling() {
+    let ts = TestScenario::new("du");
+    ts.fixtures.write("file", &"x".repeat(1500));
+    let scaled = |args: &[&str]| {
+        ts.ucmd()
+            .args(args)
+            .arg("file")
+            .succeeds()
+            .stdout_move_str()
+    };
+    assert_eq!(scaled(&["-b"]), "1500\tfile\n");
+    assert_eq!(scaled(&["--apparent-size", "-k"]), "2\tfile\n");
+    assert_eq!(scaled(&["--apparent-size", "-m"]), "1\tfile\n");
+    assert_eq!(scaled(&["--apparent-size", "-B512"]), "3\tfile\n");
+    assert_eq!(scaled(&["--apparent-size", "-h"]), "1.5K\tfile\n");
+    assert_eq!(scaled(&["--apparent-size", "--si"]), "1.5k\tfile\n");
+    assert_eq!(scaled(&["--apparent-size", "-BK"]), "2K\tfile\n");
+    assert_eq!(scaled(&["--apparent-size", "-BkB"]), "2kB\tfile\n");
+    // the last option setting the block size wins
+    assert_eq!(scaled(&["-b", "-k"]), "2\tfile\n");
+    assert_eq!(scaled(&["-k", "-b"]), "1500\tfile\n");
+}
+
+#[test]
+fn test_du_block_size_env() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("file", &"x".repeat(1500));
+    ucmd.env("DU_BLOCK_SIZE", "100")
+        .args(&["--apparent-size", "file"])
+        .succeeds()
+        .stdout_only("15\tfile\n");
+}
+
+#[test]
+fn test_du_invalid_block_size() {
+    new_ucmd!()
+        .arg("-B1x")
+        .fails()
+        .stderr_contains("invalid suffix in --block-size argument '1x'");
+    new_ucmd!()
+        .arg("--block-size=0")
+        .fails()
+        .stderr_contains("invalid --block-size argument '0'");
+}
+
+#[test]
+fn test_du_files0_from() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("list", "subdir/deeper\0words.txt\0");
+    let result = ucmd.args(&["-b", "--files0-from=list"]).succeeds();
+    assert_eq!(
+        du_paths(result.stdout_str()),
+        vec!["subdir/deeper", "words.txt"]
+    );
+}
+
+#[test]
+fn test_du_files0_from_stdin() {
+    let result = new_ucmd!()
+        .args(&["-b", "--files0-from=-"])
+        .pipe_in("words.txt\0\0missing\0")
+        .fails();
+    assert_eq!(result.stdout_str(), "3\twords.txt\n");
+    assert!(result
+        .stderr_str()
+        .contains("-:2: invalid zero-length file name"));
+    assert!(result.stderr_str().contains("missing"));
+}
+
+#[test]
+fn test_du_files0_from_with_operand() {
+    new_ucmd!()
+        .args(&["--files0-from=-", "words.txt"])
+        .fails()
+        .stderr_contains("file operands cannot be combined with --files0-from");
+}
+
+#[test]
+fn test_du_files0_from_missing() {
+    new_ucmd!()
+        .arg("--files0-from=missing")
+        .fails()
+        .stderr_contains("cannot open 'missing' for reading");
+}
+
+#[test]
+fn test_du_null() {
+    new_ucmd!()
+        .args(&["-b", "-0", "-c", "words.txt"])
+        .succeeds()
+        .stdout_only("3\twords.txt\x003\ttotal\0");
+}
+
+#[test]
+fn test_du_files0_from_newline_name() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("a b\nc", "x");
+    ucmd.args(&["-b", "-0", "--files0-from=-"])
+        .pipe_in("a b\nc\0")
+        .succeeds()
+        .stdout_only("1\ta b\nc\0");
+}
+
+#[test]
+fn test_du_directories_after_contents() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    for i in 0..20 {
+        at.mkdir_all(&format!("tree/{}/sub", i));
+        at.write(&format!("tree/{}/sub/file", i), "x");
+    }
+    let result = ucmd.args(&["-a", "tree"]).succeeds();
+    let paths: Vec<_> = result
+        .stdout_str()
+        .lines()
+        .map(|line| line.splitn(2, '\t').nth(1).unwrap())
+        .collect();
+    assert_eq!(paths.len(), 61);
+    for (i, path) in paths.iter().enumerate() {
+        let prefix = format!("{}/", path);
+        assert!(!paths[i + 1..].iter().any(|p| p.starts_with(&prefix)));
+    }
+}
+
+#[test]
+fn test_du_totals_add_up() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    for i in 0..20 {
+        at.mkdir_all(&format!("tree/{}/sub", i));
+        at.write(&format!("tree/{}/file", i), &"x".repeat(i));
+        at.write(&format!("tree/{}/sub/file", i), &"x".repeat(100 * i));
+    }
+    let separate: u64 = TestScenario::new("du")
+        .ucmd()
+        .args(&["-bS", &at.plus_as_string("tree")])
+        .succeeds()
+        .stdout_str()
+        .lines()
+        .map(|line| line.split('\t').next().unwrap().parse::<u64>().unwrap())
+        .sum();
+    let result = ucmd.args(&["-b", "tree"]).succeeds();
+    let total = result.stdout_str().lines().last().unwrap();
+    assert_eq!(total, format!("{}\ttree", separate));
+}
+
+#[test]
+fn test_du_inodes() {
+    new_ucmd!()
+        .args(&["--inodes", "-s", "-c", "subdir", "words.txt"])
+        .succeeds()
+        .stdout_only("6\tsubdir\n1\twords.txt\n7\ttotal\n");
+    new_ucmd!()
+        .args(&["--inodes", "-d", "0", "-S", "-BK", "subdir"])
+        .succeeds()
+        .stdout_only("1\tsubdir\n");
+}
+
+#[test]
+fn test_du_inodes_threshold() {
+    let result = new_ucmd!()
+        .args(&["--inodes", "-t", "3", "subdir"])
+        .succeeds();
+    assert_eq!(
+        du_paths(result.stdout_str()),
+        vec!["subdir", "subdir/links"]
+    );
+}
+
+#[test]
+fn test_du_inodes_hard_links() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("dir");
+    at.write("dir/file", "x");
+    std::fs::hard_link(at.plus("dir/file"), at.plus("dir/link")).unwrap();
+    ucmd.args(&["--inodes", "dir"])
+        .succeeds()
+        .stdout_only("2\tdir\n");
+}
+
+#[test]
+fn test_du_inodes_apparent_size() {
+    new_ucmd!()
+        .args(&["--inodes", "-b", "words.txt"])
+        .succeeds()
+        .stdout_is("1\twords.txt\n")
+        .stderr_is("du: warning: options --apparent-size and -b are ineffective with --inodes\n");
+}
+
+#[test]
+fn test_du_json() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("f", "abc");
+    let stdout = ucmd
+        .args(&["--json", "--apparent-size", "f"])
+        .succeeds()
+        .stdout_move_str();
+    assert!(stdout.starts_with(r#"{"path":"f","size":3,"disk_usage":"#));
+    assert_eq!(stdout.lines().count(), 1);
+}
@@ -145,7 +145,31 @@ fn test_escape_no_octal() {
     new_ucmd!()
         .args(&["-e", "foo\\0 bar"])
         .succeeds()
-        .stdout_only("foo\\0 bar\n");
+        .stdout_only("foo\0 bar\n");
+}
+
+#[test]
+fn test_escape_octal_without_zero() {
+    new_ucmd!()
+        .args(&["-e", "\\101\\1012\\18"])
+        .succeeds()
+        .stdout_only("AA2\x018\n");
+}
+
+#[test]
+fn test_escape_unicode() {
+    new_ucmd!()
+        .args(&["-e", "\\u00e9t\\u00E9 \\U0001F600"])
+        .succeeds()
+        .stdout_only("\u{e9}t\u{e9} \u{1f600}\n");
+}
+
+#[test]
+fn test_escape_short_unicode() {
+    new_ucmd!()
+        .args(&["-e", "\\u12 \\U0001F60 \\uD800"])
+        .succeeds()
+        .stdout_only("\\u12 \\U0001F60 \\uD800\n");
 }
 
 #[test]
@@ -228,3 +252,46 @@ fn test_hyphen_values_between() {
     assert_eq!(result.stdout, "dumdum  dum dum dum -e dum\n");
     assert_eq!(true, result.stdout.contains("-e"));
 }
+
+#[test]
+fn test_options_only_letters() {
+    new_ucmd!()
+        .args(&["-neEn", "a\\tb"])
+        .succeeds()
+        .stdout_only("a\\tb");
+
+    new_ucmd!()
+        .args(&["-en", "-x", "a\\tb"])
+        .succeeds()
+        .stdout_only("-x a\tb");
+
+    new_ucmd!()
+        .args(&["--", "-", "-n"])
+        .succeeds()
+        .stdout_only("-- - -n\n");
+}
+
+#[test]
+fn test_help_only_alone() {
+    new_ucmd!()
+        .args(&["--help", "me"])
+        .succeeds()
+        .stdout_only("--help me\n");
+}
+
+#[test]
+fn test_posixly_correct() {
+    // Escapes are always on, and options off
+    new_ucmd!()
+        .env("POSIXLY_CORRECT", "1")
+        .args(&["-E", "a\\tb"])
+        .succeeds()
+        .stdout_only("-E a\tb\n");
+
+    // unless the first argument is -n, but even -E leaves escapes on then
+    new_ucmd!()
+        .env("POSIXLY_CORRECT", "1")
+        .args(&["-n", "-E", "a\\tb"])
+        .succeeds()
+        .stdout_only("a\tb");
+}
@@ -13,7 +13,7 @@ fn test_env_help() {
         .succeeds()
         .no_stderr()
         .stdout
-        .contains("OPTIONS:"));
+        .contains("Usage: env"));
 }
 
 #[test]
@@ -23,7 +23,7 @@ fn test_env_version() {
         .succeeds()
         .no_stderr()
         .stdout
-        .contains(util_name!()));
+        .starts_with("env (uutils coreutils) "));
 }
 
 #[test]
@@ -153,8 +153,59 @@ fn test_unset_variable() {
 
 #[test]
 fn test_fail_null_with_program() {
-    let out = new_ucmd!().arg("--null").arg("cd").fails().stderr;
-    assert!(out.contains("cannot specify --null (-0) with command"));
+    new_ucmd!()
+        .arg("--null")
+        .arg("cd")
+        .fails()
+        .status_code(125)
+        .stderr_contains(&"cannot specify --null (-0) with command");
+}
+
+#[test]
+fn test_invalid_option() {
+    new_ucmd!().arg("--bogus").fails().status_code(125);
+}
+
+#[test]
+fn test_command_not_found() {
+    new_ucmd!()
+        .arg("no_such_command")
+        .fails()
+        .status_code(127)
+        .stderr_contains(&"env: 'no_such_command': ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_not_executable() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch("not_executable");
+    ucmd.arg("./not_executable")
+        .fails()
+        .status_code(126)
+        .stderr_is("env: './not_executable': Permission denied");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_argv0() {
+    new_ucmd!()
+        .arg("-a")
+        .arg("custom_name")
+        .arg("sh")
+        .pipe_in("echo $0\n")
+        .succeeds()
+        .stdout_only("custom_name\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_argv0_requires_command() {
+    new_ucmd!()
+        .arg("--argv0=custom_name")
+        .fails()
+        .status_code(125)
+        .stderr_contains(&"env: must specify command with --argv0 (-a)");
 }
 
 #[cfg(not(windows))]
@@ -217,3 +268,161 @@ fn test_fail_change_directory() {
         .stderr;
     assert!(out.contains("env: cannot change directory to "));
 }
+
+#[test]
+fn test_split_string() {
+    let out = new_ucmd!()
+        .arg("-S")
+        .arg("-i FOO='a b' BAR=\"c\\_d\" BAZ=e\\_ABC=x${SRC}y # QUX=z")
+        .env("SRC", "source")
+        .succeeds()
+        .stdout;
+
+    let mut vars: Vec<_> = out.lines().collect();
+    vars.sort();
+    assert_eq!(vars, ["ABC=xsourcey", "BAR=c d", "BAZ=e", "FOO=a b"]);
+}
+
+#[test]
+fn test_split_string_in_cluster() {
+    let out = new_ucmd!()
+        .arg("-iS--split-string=FOO=\\c ignored")
+        .arg("BAR=b")
+        .succeeds()
+        .stdout;
+
+    let mut vars: Vec<_> = out.lines().collect();
+    vars.sort();
+    assert_eq!(vars, ["BAR=b", "FOO="]);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_split_string_shebang() {
+    // the kernel hands the rest of a "#!/usr/bin/env -S ..." line over as one argument
+    new_ucmd!()
+        .arg("-S echo -n 'hello  world' \\t")
+        .arg("script")
+        .succeeds()
+        .stdout_only("hello  world \t script");
+}
+
+#[test]
+fn test_split_string_errors() {
+    for &(string, error) in &[
+        ("echo 'a", "env: no terminating quote in -S string"),
+        ("echo \"a", "env: no terminating quote in -S string"),
+        ("echo a\\", "env: invalid backslash at end of string in -S"),
+        ("echo a\\x", "env: invalid sequence '\\x' in -S"),
+        (
+            "echo \"a\\cb\"",
+            "env: '\\c' must not appear in double-quoted -S string",
+        ),
+        (
+            "echo $HOME",
+            "env: only ${VARNAME} expansion is supported, error at: $HOME",
+        ),
+        (
+            "echo ${1}",
+            "env: only ${VARNAME} expansion is supported, error at: ${1}",
+        ),
+    ] {
+        new_ucmd!()
+            .arg("-S")
+            .arg(string)
+            .fails()
+            .status_code(125)
+            .stderr_is(error);
+    }
+}
+
+#[test]
+fn test_chdir_requires_command() {
+    new_ucmd!()
+        .arg("-C")
+        .arg(".")
+        .fails()
+        .status_code(125)
+        .stderr_contains(&"env: must specify command with --chdir (-C)");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_change_directory_short() {
+    let temporary_directory = tempdir().unwrap();
+    let temporary_path = fs::canonicalize(temporary_directory.path()).unwrap();
+
+    let out = new_ucmd!()
+        .arg("-C")
+        .arg(&temporary_path)
+        .arg("pwd")
+        .succeeds()
+        .stdout;
+    assert_eq!(out.trim(), temporary_path.as_os_str())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ignore_signal() {
+    new_ucmd!()
+        .arg("--ignore-signal=int")
+        .args(&["sh", "-c", "kill -INT $$; echo survived"])
+        .succeeds()
+        .stdout_only("survived\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_default_signal() {
+    new_ucmd!()
+        .arg("--ignore-signal")
+        .arg("--default-signal=SIGINT")
+        .args(&["sh", "-c", "kill -INT $$; echo survived"])
+        .fails()
+        .no_stdout();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_block_signal() {
+    new_ucmd!()
+        .arg("--block-signal=USR1,13")
+        .args(&["sh", "-c", "kill -USR1 $$; echo survived"])
+        .succeeds()
+        .stdout_only("survived\n");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_list_signal_handling() {
+    new_ucmd!()
+        .arg("--ignore-signal=INT")
+        .arg("--block-signal=USR1,INT")
+        .arg("--list-signal-handling")
+        .arg("true")
+        .succeeds()
+        .stderr_contains(&"INT        ( 2): BLOCK,IGNORE\n")
+        .stderr_contains(&"USR1       (10): BLOCK\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_invalid_signal() {
+    new_ucmd!()
+        .arg("--default-signal=INT,FOO")
+        .arg("true")
+        .fails()
+        .status_code(125)
+        .stderr_contains(&"env: 'FOO': invalid signal");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ignore_kill_signal() {
+    new_ucmd!()
+        .arg("--ignore-signal=KILL")
+        .arg("true")
+        .fails()
+        .status_code(125)
+        .stderr_contains(&"env: failed to set signal action for signal 9");
+}
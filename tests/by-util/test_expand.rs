@@ -56,3 +56,55 @@ fn test_with_multiple_files() {
     assert!(result.stdout.contains("    return"));
     assert!(result.stdout.contains("        "));
 }
+
+#[test]
+fn test_tab_list_repeat_every() {
+    new_ucmd!()
+        .args(&["-t", "2,5,/4"])
+        .pipe_in("a\tb\tc\td\te\n")
+        .succeeds()
+        .stdout_is("a b  c  d   e\n");
+}
+
+#[test]
+fn test_tab_list_repeat_after_last() {
+    new_ucmd!()
+        .args(&["-t", "2 5 +4"])
+        .pipe_in("a\tb\tc\td\te\n")
+        .succeeds()
+        .stdout_is("a b  c   d   e\n");
+}
+
+#[test]
+fn test_tab_list_past_last_stop() {
+    new_ucmd!()
+        .args(&["-t", "1,3"])
+        .pipe_in("a\tb\tc\td\n")
+        .succeeds()
+        .stdout_is("a  b c d\n");
+}
+
+#[test]
+fn test_multiple_tab_options() {
+    new_ucmd!()
+        .args(&["-t", "3", "-t", "6"])
+        .pipe_in("a\tb\tc\td\n")
+        .succeeds()
+        .stdout_is("a  b  c d\n");
+}
+
+#[test]
+fn test_invalid_tab_lists() {
+    for (list, error) in &[
+        ("/2,4", "'/' specifier only allowed with the last value"),
+        ("4,+2,6", "'+' specifier only allowed with the last value"),
+        ("4,4", "tab sizes must be ascending"),
+        ("0", "tab size cannot be 0"),
+        ("4x", "tab size contains invalid character(s): 'x'"),
+    ] {
+        new_ucmd!()
+            .args(&["-t", list])
+            .fails()
+            .stderr_contains(error);
+    }
+}
@@ -13,11 +13,20 @@ fn test_simple_arithmetic() {
 
 #[test]
 fn test_complex_arithmetic() {
-    let run = new_ucmd!()
+    new_ucmd!()
         .args(&["9223372036854775807", "+", "9223372036854775807"])
-        .run();
-    run.stdout_is("");
-    run.stderr_is("expr: error: +: Numerical result out of range");
+        .run()
+        .stdout_is("18446744073709551614\n");
+
+    new_ucmd!()
+        .args(&["-9223372036854775808", "/", "-1"])
+        .run()
+        .stdout_is("9223372036854775808\n");
+
+    new_ucmd!()
+        .args(&["-123456789012345678901234567891", "%", "7"])
+        .run()
+        .stdout_is("-1\n");
 
     let run = new_ucmd!().args(&["9", "/", "0"]).run();
     run.stdout_is("");
@@ -54,3 +63,75 @@ fn test_and() {
 
     new_ucmd!().args(&["", "&", "1"]).run().stdout_is("0\n");
 }
+
+#[test]
+fn test_match_is_anchored() {
+    new_ucmd!()
+        .args(&["abcabc", ":", "abc"])
+        .run()
+        .stdout_is("3\n");
+
+    new_ucmd!()
+        .args(&["abcabc", ":", "bc"])
+        .run()
+        .status_code(1)
+        .stdout_is("0\n");
+
+    new_ucmd!()
+        .args(&["match", "abcabc", "a.*"])
+        .run()
+        .stdout_is("6\n");
+}
+
+#[test]
+fn test_match_group() {
+    new_ucmd!()
+        .args(&["foo.tar.gz", ":", "\\(.*\\)\\.gz"])
+        .run()
+        .stdout_is("foo.tar\n");
+
+    new_ucmd!()
+        .args(&["foo.tar", ":", "\\(.*\\)\\.gz"])
+        .run()
+        .status_code(1)
+        .stdout_is("\n");
+}
+
+#[test]
+fn test_string_functions() {
+    new_ucmd!()
+        .args(&["length", "h\u{e9}llo"])
+        .run()
+        .stdout_is("5\n");
+
+    new_ucmd!()
+        .args(&["index", "abcdef", "fd"])
+        .run()
+        .stdout_is("4\n");
+
+    new_ucmd!()
+        .args(&["index", "abcdef", "xyz"])
+        .run()
+        .status_code(1)
+        .stdout_is("0\n");
+
+    new_ucmd!()
+        .args(&["substr", "abcdef", "2", "3"])
+        .run()
+        .stdout_is("bcd\n");
+}
+
+#[test]
+fn test_plus_quotes() {
+    new_ucmd!().args(&["+", "match"]).run().stdout_is("match\n");
+
+    new_ucmd!()
+        .args(&["length", "+", "length"])
+        .run()
+        .stdout_is("6\n");
+
+    new_ucmd!()
+        .args(&["1", "+", "+", "2"])
+        .run()
+        .stdout_is("3\n");
+}
@@ -195,6 +195,51 @@ fn test_big_primes() {
     run(instring.as_bytes(), outstring.as_bytes());
 }
 
+#[test]
+fn test_u128() {
+    run(
+        b"170141183460469231731687303715884105727 18446744073709551617 \
+          20282409602046803624663334261773",
+        b"170141183460469231731687303715884105727: 170141183460469231731687303715884105727
+18446744073709551617: 274177 67280421310721
+20282409602046803624663334261773: 1099511627689 18446744073709551557
+",
+    );
+}
+
+#[test]
+fn test_exponents() {
+    new_ucmd!()
+        .args(&[
+            "--exponents",
+            "1267650600228229401496703205376",
+            "4669332093657730250775",
+        ])
+        .succeeds()
+        .stdout_only(
+            "1267650600228229401496703205376: 2^100
+4669332093657730250775: 3^4 5^2 2305843009213693951
+",
+        );
+
+    new_ucmd!()
+        .arg("-h")
+        .pipe_in("12 7")
+        .succeeds()
+        .stdout_only("12: 2^2 3\n7: 7\n");
+}
+
+#[test]
+fn test_stdin_tokens() {
+    // Numbers on any whitespace, with the last one unterminated
+    run(b"\t4\n\n 6  \r\n9", b"4: 2 2\n6: 2 3\n9: 3 3\n");
+
+    // and split across reads
+    let mut instring = " ".repeat(8191);
+    instring.push_str("1234567 10");
+    run(instring.as_bytes(), b"1234567: 127 9721\n10: 2 5\n");
+}
+
 fn run(instring: &[u8], outstring: &[u8]) {
     println!("STDIN='{}'", String::from_utf8_lossy(instring));
     println!("STDOUT(expected)='{}'", String::from_utf8_lossy(outstring));
@@ -48,3 +48,79 @@ fn test_fmt_w() {
 
 fmt is pretty broken in general, needs more works to have more tests
  */
+
+#[test]
+fn test_fmt_tagged_paragraph_single_line() {
+    new_ucmd!()
+        .args(&["-t", "-q", "-w", "10"])
+        .pipe_in("aaa bbb ccc\n")
+        .succeeds()
+        .stdout_is("aaa bbb\n   ccc\n");
+}
+
+#[test]
+fn test_fmt_tagged_paragraph_same_indent_splits() {
+    new_ucmd!()
+        .args(&["-t"])
+        .pipe_in("aaa\nbbb\n")
+        .succeeds()
+        .stdout_is("aaa\nbbb\n");
+}
+
+#[test]
+fn test_fmt_crown_margin() {
+    new_ucmd!()
+        .args(&["-c"])
+        .pipe_in("  aaa\nbbb\nccc\n")
+        .succeeds()
+        .stdout_is("  aaa bbb ccc\n");
+}
+
+#[test]
+fn test_fmt_prefix() {
+    new_ucmd!()
+        .args(&["-p", "# ", "-q", "-w", "10"])
+        .pipe_in("# aaa bbb ccc\nplain text line\n")
+        .succeeds()
+        .stdout_is("# aaa bbb\n# ccc\nplain text line\n");
+}
+
+#[test]
+fn test_fmt_prefers_sentence_breaks() {
+    new_ucmd!()
+        .args(&["-w", "30"])
+        .pipe_in(
+            "The quick brown fox jumps over the lazy dog. It was a sunny day and everyone was out. Go.\n",
+        )
+        .succeeds()
+        .stdout_is(
+            "The quick brown fox jumps\nover the lazy dog. It was a\nsunny day and everyone was\nout. Go.\n",
+        );
+}
+
+#[test]
+fn test_fmt_avoids_orphans() {
+    new_ucmd!()
+        .args(&["-w", "8"])
+        .pipe_in("out.  a  fox I\n")
+        .succeeds()
+        .stdout_is("out.\na  fox\nI\n");
+}
+
+#[test]
+fn test_fmt_width_is_exclusive() {
+    new_ucmd!()
+        .args(&["-w", "7"])
+        .pipe_in("aaa bbb\n")
+        .succeeds()
+        .stdout_is("aaa\nbbb\n");
+}
+
+#[test]
+fn test_fmt_multiple_paragraphs() {
+    new_ucmd!()
+        .args(&["-w", "20"])
+        .pipe_in("one two three four five six seven eight nine ten eleven twelve.\n\nNext para here.\n")
+        .succeeds()
+        .stdout_is("one two three four\nfive six seven\neight nine ten\neleven twelve.\n\nNext para here.\n");
+}
@@ -31,3 +31,80 @@ fn test_default_warp_with_newlines() {
         .run()
         .stdout_is_fixture("lorem_ipsum_new_line_80_column.expected");
 }
+
+#[test]
+fn test_tab_advances_to_next_tab_stop() {
+    new_ucmd!()
+        .args(&["-w", "10"])
+        .pipe_in("a\tbcdefghij\n")
+        .succeeds()
+        .stdout_is("a\tbc\ndefghij\n");
+}
+
+#[test]
+fn test_bytes_counts_tab_as_one() {
+    new_ucmd!()
+        .args(&["-w", "10", "-b"])
+        .pipe_in("a\tbcdefghij\n")
+        .succeeds()
+        .stdout_is("a\tbcdefghi\nj\n");
+}
+
+#[test]
+fn test_backspace_moves_column_back() {
+    new_ucmd!()
+        .args(&["-w", "8"])
+        .pipe_in("abcdefgh\x08\x08ij\n")
+        .succeeds()
+        .stdout_is("abcdefgh\x08\x08ij\n");
+}
+
+#[test]
+fn test_carriage_return_resets_column() {
+    new_ucmd!()
+        .args(&["-w", "8"])
+        .pipe_in("abcdef\rghijkl\n")
+        .succeeds()
+        .stdout_is("abcdef\rghijkl\n");
+}
+
+#[test]
+fn test_spaces_breaks_after_last_blank() {
+    new_ucmd!()
+        .args(&["-s", "-w", "10"])
+        .pipe_in("aaa bbb\tccc\n")
+        .succeeds()
+        .stdout_is("aaa bbb\t\nccc\n");
+}
+
+#[test]
+fn test_no_trailing_newline() {
+    new_ucmd!()
+        .args(&["-w", "3"])
+        .pipe_in("abcdefg")
+        .succeeds()
+        .stdout_is("abc\ndef\ng");
+}
+
+#[test]
+fn test_multibyte_characters_are_not_split() {
+    new_ucmd!()
+        .args(&["-w", "2"])
+        .pipe_in("ééé\n")
+        .succeeds()
+        .stdout_is("éé\né\n");
+}
+
+#[test]
+fn test_wide_characters_take_two_columns() {
+    new_ucmd!()
+        .args(&["-w", "5"])
+        .pipe_in("日本語です\n")
+        .succeeds()
+        .stdout_is("日本\n語で\nす\n");
+    new_ucmd!()
+        .args(&["-s", "-w", "6"])
+        .pipe_in("日本 語です\n")
+        .succeeds()
+        .stdout_is("日本 \n語です\n");
+}
@@ -42,5 +42,25 @@ fn test_groups_arg() {
     println!("result.stdout {}", result.stdout);
     println!("result.stderr = {}", result.stderr);
     assert!(result.success);
-    assert!(!result.stdout.is_empty());
+    assert!(result.stdout.starts_with(&format!("{} : ", username)));
+}
+
+#[test]
+fn test_groups_multiple_users() {
+    let result = new_ucmd!().arg("root").succeeds();
+    let root = result.stdout;
+    assert!(root.starts_with("root : root"));
+
+    new_ucmd!()
+        .arg("root")
+        .arg("root")
+        .succeeds()
+        .stdout_only(format!("{}{}", root, root));
+
+    new_ucmd!()
+        .arg("__nosuchuser__")
+        .arg("root")
+        .fails()
+        .stderr_is("groups: '__nosuchuser__': no such user")
+        .stdout_is(root);
 }
@@ -44,4 +44,568 @@ test_digest! {
     shake128_256 shake128 256
     shake256_512 shake256 512
     b2sum b2sum 512
+    b3sum b3sum 256
+}
+
+use crate::common::util::*;
+
+// the MD5 sums of "hello\n" and "world\n"
+static HELLO_MD5: &str = "b1946ac92492d2347c6235b4d2611184";
+static WORLD_MD5: &str = "591785b794601e212b260e25925636fd";
+
+/// A scene with the files `hello` and `world` to sum.
+fn scene() -> TestScenario {
+    let scene = TestScenario::new("hashsum");
+    scene.fixtures.write("hello", "hello\n");
+    scene.fixtures.write("world", "world\n");
+    scene
+}
+
+mod check {
+    use super::{scene, HELLO_MD5, WORLD_MD5};
+    use crate::common::util::*;
+
+    #[test]
+    fn test_check_ok() {
+        let scene = scene();
+        scene.fixtures.write(
+            "sums.md5",
+            &format!("{}  hello\n{} *world\n", HELLO_MD5, WORLD_MD5),
+        );
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "sums.md5"])
+            .succeeds()
+            .no_stderr()
+            .stdout_is("hello: OK\nworld: OK\n");
+    }
+
+    #[test]
+    fn test_check_failed() {
+        let scene = scene();
+        scene.fixtures.write(
+            "sums.md5",
+            &format!(
+                "{}  hello\n{}  world\ngarbage\n\n# comment\n{}  missing\n",
+                HELLO_MD5, HELLO_MD5, HELLO_MD5
+            ),
+        );
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "sums.md5"])
+            .fails()
+            .status_code(1)
+            .stdout_is("hello: OK\nworld: FAILED\nmissing: FAILED open or read\n")
+            .stderr_is(
+                "hashsum: missing: No such file or directory
+hashsum: WARNING: 1 line is improperly formatted
+hashsum: WARNING: 1 listed file could not be read
+hashsum: WARNING: 1 computed checksum did NOT match",
+            );
+    }
+
+    #[test]
+    fn test_check_quiet() {
+        let scene = scene();
+        scene.fixtures.write(
+            "sums.md5",
+            &format!("{}  hello\n{}  world\n", HELLO_MD5, HELLO_MD5),
+        );
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "--quiet", "sums.md5"])
+            .fails()
+            .stdout_is("world: FAILED\n")
+            .stderr_is("hashsum: WARNING: 1 computed checksum did NOT match");
+    }
+
+    #[test]
+    fn test_check_status() {
+        let scene = scene();
+        scene.fixtures.write(
+            "sums.md5",
+            &format!("{}  hello\n{}  world\n", HELLO_MD5, HELLO_MD5),
+        );
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "--status", "sums.md5"])
+            .fails()
+            .status_code(1)
+            .no_stdout()
+            .no_stderr();
+        scene
+            .fixtures
+            .write("good.md5", &format!("{}  hello\n", HELLO_MD5));
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "--status", "good.md5"])
+            .succeeds()
+            .no_stdout()
+            .no_stderr();
+    }
+
+    #[test]
+    fn test_check_warn_and_strict() {
+        let scene = scene();
+        scene.fixtures.write(
+            "sums.md5",
+            &format!("{}  hello\nimproper line\n", HELLO_MD5),
+        );
+        // improperly formatted lines alone don't fail it
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "--warn", "sums.md5"])
+            .succeeds()
+            .stdout_is("hello: OK\n")
+            .stderr_is(
+                "hashsum: sums.md5: 2: improperly formatted MD5 checksum line
+hashsum: WARNING: 1 line is improperly formatted",
+            );
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "--strict", "sums.md5"])
+            .fails()
+            .status_code(1)
+            .stdout_is("hello: OK\n")
+            .stderr_is("hashsum: WARNING: 1 line is improperly formatted");
+    }
+
+    #[test]
+    fn test_check_ignore_missing() {
+        let scene = scene();
+        scene.fixtures.write(
+            "sums.md5",
+            &format!("{}  hello\n{}  missing\n", HELLO_MD5, WORLD_MD5),
+        );
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "--ignore-missing", "sums.md5"])
+            .succeeds()
+            .no_stderr()
+            .stdout_is("hello: OK\n");
+        scene
+            .fixtures
+            .write("missing.md5", &format!("{}  missing\n", WORLD_MD5));
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "--ignore-missing", "missing.md5"])
+            .fails()
+            .no_stdout()
+            .stderr_is("hashsum: missing.md5: no file was verified");
+    }
+
+    #[test]
+    fn test_check_no_properly_formatted_lines() {
+        let scene = scene();
+        scene.fixtures.write("sums.md5", "garbage\n");
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "sums.md5"])
+            .fails()
+            .stderr_is("hashsum: sums.md5: no properly formatted checksum lines found");
+    }
+
+    #[test]
+    fn test_check_options_need_check() {
+        for option in &[
+            "--ignore-missing",
+            "--quiet",
+            "--status",
+            "--strict",
+            "--warn",
+        ] {
+            scene()
+                .ucmd()
+                .args(&["--md5", *option, "hello"])
+                .fails()
+                .status_code(1)
+                .stderr_contains(&format!(
+                    "the {} option is meaningful only when verifying checksums",
+                    option
+                ));
+        }
+    }
+}
+
+mod tag {
+    use super::{scene, HELLO_MD5, WORLD_MD5};
+    use crate::common::util::*;
+
+    #[test]
+    fn test_tag() {
+        scene()
+            .ucmd()
+            .args(&["--md5", "--tag", "hello", "world"])
+            .succeeds()
+            .stdout_is(format!(
+                "MD5 (hello) = {}\nMD5 (world) = {}\n",
+                HELLO_MD5, WORLD_MD5
+            ));
+    }
+
+    #[test]
+    fn test_tag_b2sum_name() {
+        scene()
+            .ucmd()
+            .args(&["--b2sum", "--tag", "hello"])
+            .succeeds()
+            .stdout_contains("BLAKE2b (hello) = ");
+    }
+
+    #[test]
+    fn test_check_tagged_and_untagged() {
+        let scene = scene();
+        scene.fixtures.write(
+            "sums.md5",
+            &format!(
+                "MD5 (hello) = {}\n{}  world\nMD5(world)={}\n",
+                HELLO_MD5, WORLD_MD5, WORLD_MD5
+            ),
+        );
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "sums.md5"])
+            .succeeds()
+            .no_stderr()
+            .stdout_is("hello: OK\nworld: OK\nworld: OK\n");
+    }
+
+    #[test]
+    fn test_check_tagged_other_algorithm() {
+        let scene = scene();
+        scene
+            .fixtures
+            .write("sums", &format!("SHA1 (hello) = {}\n", HELLO_MD5));
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "sums"])
+            .fails()
+            .stderr_is("hashsum: sums: no properly formatted checksum lines found");
+    }
+
+    #[test]
+    fn test_tag_with_check_or_text() {
+        scene()
+            .ucmd()
+            .args(&["--md5", "--tag", "-c", "hello"])
+            .fails()
+            .stderr_contains(&"the --tag option is meaningless when verifying checksums");
+        scene()
+            .ucmd()
+            .args(&["--md5", "--tag", "--text", "hello"])
+            .fails()
+            .stderr_contains(&"--tag does not support --text mode");
+    }
+}
+
+mod length {
+    use super::scene;
+    use crate::common::util::*;
+
+    // the BLAKE2b sums of "hello\n" and "world\n", 128 and 8 bits long
+    static HELLO_B2_128: &str = "ea41b4de6c03f13a95758b28dd75693e";
+    static WORLD_B2_8: &str = "3a";
+
+    #[test]
+    fn test_length() {
+        let scene = scene();
+        scene
+            .ucmd()
+            .args(&["--b2sum", "-l", "128", "hello"])
+            .succeeds()
+            .stdout_is(format!("{}  hello\n", HELLO_B2_128));
+        scene
+            .ucmd()
+            .args(&["--b2sum", "--length=8", "--tag", "world"])
+            .succeeds()
+            .stdout_is(format!("BLAKE2b-8 (world) = {}\n", WORLD_B2_8));
+    }
+
+    #[test]
+    fn test_full_length() {
+        let scene = scene();
+        let full = scene
+            .ucmd()
+            .args(&["--b2sum", "--tag", "hello"])
+            .succeeds()
+            .stdout_move_str();
+        assert!(full.starts_with("BLAKE2b (hello) = "));
+        // 0 is the default
+        scene
+            .ucmd()
+            .args(&["--b2sum", "--tag", "-l", "0", "hello"])
+            .succeeds()
+            .stdout_is(&full);
+        scene
+            .ucmd()
+            .args(&["--b2sum", "--tag", "-l", "512", "hello"])
+            .succeeds()
+            .stdout_is(&full);
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        new_ucmd!()
+            .args(&["--b2sum", "-l", "7"])
+            .fails()
+            .stderr_is("hashsum: invalid length: '7'\nhashsum: length is not a multiple of 8");
+        new_ucmd!()
+            .args(&["--b2sum", "-l", "520"])
+            .fails()
+            .stderr_is(
+                "hashsum: invalid length: '520'\n\
+                 hashsum: maximum digest length for 'BLAKE2b' is 512 bits",
+            );
+        new_ucmd!()
+            .args(&["--b2sum", "-l", "x"])
+            .fails()
+            .stderr_is("hashsum: invalid length: 'x'");
+        new_ucmd!()
+            .args(&["--md5", "-l", "8"])
+            .fails()
+            .stderr_contains(&"--length is only supported with --b2sum");
+    }
+
+    #[test]
+    fn test_check_lengths() {
+        let scene = scene();
+        scene.fixtures.write(
+            "sums",
+            &format!(
+                "{}  hello\nBLAKE2b-8 (world) = {}\n",
+                HELLO_B2_128, WORLD_B2_8
+            ),
+        );
+        // each line's own length is used, whatever --length says
+        scene
+            .ucmd()
+            .args(&["--b2sum", "-l", "256", "-c", "sums"])
+            .succeeds()
+            .no_stderr()
+            .stdout_is("hello: OK\nworld: OK\n");
+    }
+
+    #[test]
+    fn test_check_wrong_tag_length() {
+        let scene = scene();
+        scene.fixtures.write(
+            "sums",
+            &format!(
+                "BLAKE2b-16 (world) = {}\nBLAKE2b (hello) = {}\n",
+                WORLD_B2_8, HELLO_B2_128
+            ),
+        );
+        scene
+            .ucmd()
+            .args(&["--b2sum", "-c", "sums"])
+            .fails()
+            .stderr_is("hashsum: sums: no properly formatted checksum lines found");
+    }
+}
+
+mod sha3 {
+    use crate::common::util::*;
+
+    // the SHA3-256 sum of "hello\n", and its SHAKE128 sum 32 bits long
+    static HELLO_SHA3_256: &str =
+        "b314e28493eae9dab57ac4f0c6d887bddbbeb810e900d818395ace558e96516d";
+    static HELLO_SHAKE128_32: &str = "4a361de3";
+
+    #[test]
+    fn test_binary_names() {
+        let scene = TestScenario::new("sha3-256sum");
+        scene.fixtures.write("hello", "hello\n");
+        scene
+            .ucmd()
+            .arg("--tag")
+            .arg("hello")
+            .succeeds()
+            .stdout_is(format!("SHA3-256 (hello) = {}\n", HELLO_SHA3_256));
+
+        let scene = TestScenario::new("shake128sum");
+        scene.fixtures.write("hello", "hello\n");
+        scene
+            .ucmd()
+            .args(&["--bits=32", "hello"])
+            .succeeds()
+            .stdout_is(format!("{}  hello\n", HELLO_SHAKE128_32));
+    }
+
+    #[test]
+    fn test_check_shake() {
+        let scene = super::scene();
+        scene.fixtures.write(
+            "sums",
+            &format!("{0}  hello\nSHAKE128 (hello) = {0}\n", HELLO_SHAKE128_32),
+        );
+        scene
+            .ucmd()
+            .args(&["--shake128", "--bits=32", "-c", "sums"])
+            .succeeds()
+            .no_stderr()
+            .stdout_is("hello: OK\nhello: OK\n");
+    }
+
+    #[test]
+    fn test_shake_bits() {
+        new_ucmd!()
+            .args(&["--shake128"])
+            .fails()
+            .stderr_contains(&"--bits required for SHAKE-128");
+        new_ucmd!()
+            .args(&["--shake256", "--bits=12"])
+            .fails()
+            .stderr_contains(&"Invalid output size for SHAKE-256");
+    }
+}
+
+mod jobs {
+    use crate::common::util::*;
+
+    /// The scene, with 50 more files to sum and their names.
+    fn scene_with_files() -> (TestScenario, Vec<String>) {
+        let scene = super::scene();
+        let names: Vec<String> = (0..50).map(|i| format!("f{}", i)).collect();
+        for (i, name) in names.iter().enumerate() {
+            // of different sizes, to be done at different times
+            scene.fixtures.write(name, &"x".repeat((50 - i) * 1000));
+        }
+        (scene, names)
+    }
+
+    #[test]
+    fn test_jobs_keep_order() {
+        let (scene, names) = scene_with_files();
+        let serial = scene
+            .ucmd()
+            .arg("--md5")
+            .arg("--jobs=1")
+            .args(&names)
+            .succeeds()
+            .stdout_move_str();
+        let parallel = scene
+            .ucmd()
+            .arg("--md5")
+            .arg("--jobs=4")
+            .args(&names)
+            .succeeds()
+            .stdout_move_str();
+        assert_eq!(serial, parallel);
+        for (line, name) in parallel.lines().zip(&names) {
+            assert!(line.ends_with(&format!("  {}", name)));
+        }
+
+        scene.fixtures.write("sums", &serial);
+        let checked: String = names.iter().map(|name| format!("{}: OK\n", name)).collect();
+        scene
+            .ucmd()
+            .args(&["--md5", "--jobs=4", "-c", "sums"])
+            .succeeds()
+            .stdout_is(checked);
+    }
+
+    #[test]
+    fn test_jobs_with_errors() {
+        let (scene, _) = scene_with_files();
+        scene
+            .ucmd()
+            .args(&["--md5", "--jobs=3", "f1", "missing", "f2"])
+            .fails()
+            .stderr_is("hashsum: missing: No such file or directory")
+            .stdout_is(
+                "c6d49453f2e809db56e5d4101f3b479c  f1\n\
+                 32d49226ecb5fe9ac5a3da91000d3d44  f2\n",
+            );
+    }
+
+    #[test]
+    fn test_invalid_jobs() {
+        new_ucmd!()
+            .args(&["--md5", "--jobs=0"])
+            .fails()
+            .stderr_contains(&"invalid number of jobs: '0'");
+    }
+}
+
+// names with a backslash or newline can't be made on Windows
+#[cfg(unix)]
+mod escape {
+    use super::HELLO_MD5;
+    use crate::common::util::*;
+
+    #[test]
+    fn test_escaped_names() {
+        let scene = TestScenario::new("hashsum");
+        scene.fixtures.write("a\\b", "hello\n");
+        scene.fixtures.write("c\nd", "hello\n");
+        scene
+            .ucmd()
+            .args(&["--md5", "a\\b", "c\nd"])
+            .succeeds()
+            .stdout_is(format!("\\{0}  a\\\\b\n\\{0}  c\\nd\n", HELLO_MD5));
+        scene
+            .ucmd()
+            .args(&["--md5", "--tag", "a\\b"])
+            .succeeds()
+            .stdout_is(format!("\\MD5 (a\\\\b) = {}\n", HELLO_MD5));
+    }
+
+    #[test]
+    fn test_zero() {
+        let scene = TestScenario::new("hashsum");
+        scene.fixtures.write("a\\b", "hello\n");
+        scene.fixtures.write("plain", "hello\n");
+        scene
+            .ucmd()
+            .args(&["--md5", "-z", "a\\b", "plain"])
+            .succeeds()
+            .stdout_is(format!("{0}  a\\b\0{0}  plain\0", HELLO_MD5));
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "-z", "plain"])
+            .fails()
+            .stderr_contains(&"the --zero option is not supported when verifying checksums");
+    }
+
+    #[test]
+    fn test_check_escaped_names() {
+        let scene = TestScenario::new("hashsum");
+        scene.fixtures.write("a\\b", "hello\n");
+        scene.fixtures.write("c\nd", "hello\n");
+        scene.fixtures.write(
+            "sums.md5",
+            &format!("\\{0}  a\\\\b\n\\{0}  c\\nd\n", HELLO_MD5),
+        );
+        // only a name with a newline needs escaping in the results
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "sums.md5"])
+            .succeeds()
+            .no_stderr()
+            .stdout_is("a\\b: OK\n\\c\\nd: OK\n");
+    }
+
+    #[test]
+    fn test_check_unescaped_backslash() {
+        let scene = TestScenario::new("hashsum");
+        scene.fixtures.write("a\\b", "hello\n");
+        // without the leading backslash, the name is as it is
+        scene
+            .fixtures
+            .write("sums.md5", &format!("{}  a\\b\n", HELLO_MD5));
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "sums.md5"])
+            .succeeds()
+            .stdout_is("a\\b: OK\n");
+        // with it, "\b" isn't an escape
+        scene
+            .fixtures
+            .write("sums.md5", &format!("\\{}  a\\b\n", HELLO_MD5));
+        scene
+            .ucmd()
+            .args(&["--md5", "-c", "sums.md5"])
+            .fails()
+            .stderr_is("hashsum: sums.md5: no properly formatted checksum lines found");
+    }
 }
@@ -11,3 +11,12 @@ fn test_normal() {
     let re = Regex::new(r"^[0-9a-f]{8}").unwrap();
     assert!(re.is_match(&result.stdout.trim()));
 }
+
+#[test]
+fn test_extra_operand() {
+    new_ucmd!()
+        .arg("a")
+        .fails()
+        .status_code(1)
+        .stderr_contains(&"extra operand 'a'");
+}
@@ -185,3 +185,89 @@ fn test_id_password_style() {
     assert!(result.success);
     assert!(result.stdout.starts_with(&username));
 }
+
+#[test]
+fn test_id_multiple_users() {
+    let scene = TestScenario::new(util_name!());
+
+    let result = scene.ucmd().arg("root").succeeds();
+    let root = result.stdout;
+    assert!(root.starts_with("uid=0(root) gid=0(root) groups=0(root)"));
+
+    scene
+        .ucmd()
+        .arg("root")
+        .arg("0")
+        .succeeds()
+        .stdout_only(format!("{}{}", root, root));
+
+    scene
+        .ucmd()
+        .arg("__nosuchuser__")
+        .arg("root")
+        .fails()
+        .stderr_is("id: '__nosuchuser__': no such user")
+        .stdout_is(root);
+}
+
+#[test]
+fn test_id_zero() {
+    new_ucmd!()
+        .arg("-uz")
+        .arg("root")
+        .arg("root")
+        .succeeds()
+        .stdout_only("0\u{0}0\u{0}");
+    new_ucmd!()
+        .arg("-nuz")
+        .arg("root")
+        .succeeds()
+        .stdout_only("root\u{0}");
+    new_ucmd!()
+        .arg("-z")
+        .fails()
+        .stderr_is("id: option --zero not permitted in default format");
+}
+
+#[test]
+fn test_id_real() {
+    let scene = TestScenario::new(util_name!());
+
+    let uid = scene.ucmd().arg("-u").succeeds().stdout;
+    scene.ucmd().arg("-ru").succeeds().stdout_only(uid);
+    let gid = scene.ucmd().arg("-g").succeeds().stdout;
+    scene.ucmd().arg("-rg").succeeds().stdout_only(gid);
+}
+
+#[test]
+fn test_id_conflicting_options() {
+    new_ucmd!()
+        .arg("-ug")
+        .fails()
+        .stderr_is("id: cannot print \"only\" of more than one choice");
+    new_ucmd!()
+        .arg("-n")
+        .fails()
+        .stderr_is("id: cannot print only names or real IDs in default format");
+    new_ucmd!()
+        .arg("-r")
+        .fails()
+        .stderr_is("id: cannot print only names or real IDs in default format");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_id_context() {
+    if std::path::Path::new("/sys/fs/selinux/enforce").exists() {
+        new_ucmd!()
+            .arg("-Z")
+            .arg("root")
+            .fails()
+            .stderr_is("id: cannot print security context when user specified");
+    } else {
+        new_ucmd!()
+            .arg("-Z")
+            .fails()
+            .stderr_is("id: --context (-Z) works only on an SELinux-enabled kernel");
+    }
+}
@@ -14,7 +14,7 @@ fn test_install_help() {
         .succeeds()
         .no_stderr()
         .stdout
-        .contains("FLAGS:"));
+        .contains("Usage: install"));
 }
 
 #[test]
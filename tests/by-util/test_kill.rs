@@ -1 +1,161 @@
-// ToDO: add tests
+use crate::common::util::*;
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Child, Command};
+
+// A child for kill to send signals to, killed when it goes out of scope.
+struct Target {
+    child: Child,
+}
+
+impl Target {
+    fn new() -> Target {
+        Target {
+            child: Command::new("sleep").arg("30").spawn().unwrap(),
+        }
+    }
+
+    fn pid(&self) -> String {
+        self.child.id().to_string()
+    }
+
+    fn wait_for_signal(&mut self) -> Option<i32> {
+        self.child.wait().unwrap().signal()
+    }
+}
+
+impl Drop for Target {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_kill_list_all_signals() {
+    new_ucmd!()
+        .arg("-l")
+        .succeeds()
+        .stdout_contains("KILL\n")
+        .stdout_contains("TERM\n")
+        .stdout_contains("HUP\n");
+}
+
+#[test]
+fn test_kill_list_convert() {
+    new_ucmd!()
+        .args(&["-l", "9", "SIGTERM", "hup"])
+        .succeeds()
+        .stdout_is("KILL\n15\n1\n");
+}
+
+#[test]
+fn test_kill_list_exit_status() {
+    new_ucmd!()
+        .args(&["-l", "143", "271"])
+        .succeeds()
+        .stdout_is("TERM\nTERM\n");
+}
+
+#[test]
+fn test_kill_list_invalid_signal() {
+    new_ucmd!()
+        .args(&["-l", "IAMNOTASIGNAL", "9"])
+        .fails()
+        .status_code(1)
+        .stdout_is("KILL\n")
+        .stderr_is("kill: 'IAMNOTASIGNAL': invalid signal");
+}
+
+#[test]
+fn test_kill_table() {
+    new_ucmd!()
+        .arg("-L")
+        .succeeds()
+        .stdout_contains(" 9 KILL")
+        .stdout_contains("15 TERM");
+    new_ucmd!()
+        .args(&["-t", "KILL"])
+        .succeeds()
+        .stdout_contains(" 9 KILL");
+}
+
+#[test]
+fn test_kill_list_and_signal() {
+    new_ucmd!()
+        .args(&["-l", "-s", "KILL"])
+        .fails()
+        .stderr_contains(&"cannot combine signal with -l or -t");
+}
+
+#[test]
+fn test_kill_no_pid() {
+    new_ucmd!()
+        .arg("-9")
+        .fails()
+        .stderr_contains(&"no process ID specified");
+}
+
+#[test]
+fn test_kill_default_signal() {
+    let mut target = Target::new();
+    new_ucmd!().arg(target.pid()).succeeds();
+    assert_eq!(target.wait_for_signal(), Some(libc::SIGTERM));
+}
+
+#[test]
+fn test_kill_with_signal_option() {
+    let mut target = Target::new();
+    new_ucmd!().args(&["-s", "INT", &target.pid()]).succeeds();
+    assert_eq!(target.wait_for_signal(), Some(libc::SIGINT));
+}
+
+#[test]
+fn test_kill_with_obsolete_signal() {
+    let mut target = Target::new();
+    new_ucmd!().args(&["-HUP", &target.pid()]).succeeds();
+    assert_eq!(target.wait_for_signal(), Some(libc::SIGHUP));
+
+    let mut target = Target::new();
+    new_ucmd!().args(&["-9", &target.pid()]).succeeds();
+    assert_eq!(target.wait_for_signal(), Some(libc::SIGKILL));
+}
+
+#[test]
+fn test_kill_with_exit_status_signal() {
+    let mut target = Target::new();
+    new_ucmd!().args(&["-s", "130", &target.pid()]).succeeds();
+    assert_eq!(target.wait_for_signal(), Some(libc::SIGINT));
+}
+
+#[test]
+fn test_kill_invalid_signal() {
+    new_ucmd!()
+        .args(&["-s", "IAMNOTASIGNAL", "1"])
+        .fails()
+        .stderr_contains(&"'IAMNOTASIGNAL': invalid signal");
+}
+
+#[test]
+fn test_kill_multiple_signals() {
+    new_ucmd!()
+        .args(&["-KILL", "-s", "TERM", "1"])
+        .fails()
+        .stderr_contains(&"multiple signals specified");
+}
+
+#[test]
+fn test_kill_invalid_pid() {
+    new_ucmd!()
+        .args(&["--", "abc"])
+        .fails()
+        .stderr_is("kill: 'abc': invalid process id");
+}
+
+#[test]
+fn test_kill_negative_pid_after_signal() {
+    // a process group that doesn't exist, rather than an option
+    new_ucmd!()
+        .args(&["-s", "0", "-999999"])
+        .fails()
+        .stderr_is("kill: '-999999': No such process");
+}
@@ -284,8 +284,10 @@ fn test_ls_long() {
         #[cfg(not(windows))]
         assert!(result.stdout.contains("-rw-rw-r--"));
 
+        // a new file is writable by its owner, and not executable without
+        // an extension from PATHEXT
         #[cfg(windows)]
-        assert!(result.stdout.contains("---------- 1 somebody somegroup"));
+        assert!(result.stdout.starts_with("-rw-"));
     }
 
     #[cfg(not(windows))]
@@ -303,7 +305,7 @@ fn test_ls_long_formats() {
     at.touch(&at.plus_as_string("test-long-formats"));
 
     // Regex for three names, so all of author, group and owner
-    let re_three = Regex::new(r"[xrw-]{9} \d ([-0-9_a-z]+ ){3}0").unwrap();
+    let re_three = Regex::new(r"[xrw-]{9} \d ([-0-9_a-zA-Z]+ ){3}0").unwrap();
 
     #[cfg(unix)]
     let re_three_num = Regex::new(r"[xrw-]{9} \d (\d+ ){3}0").unwrap();
@@ -312,13 +314,13 @@ fn test_ls_long_formats() {
     // - group and owner
     // - author and owner
     // - author and group
-    let re_two = Regex::new(r"[xrw-]{9} \d ([-0-9_a-z]+ ){2}0").unwrap();
+    let re_two = Regex::new(r"[xrw-]{9} \d ([-0-9_a-zA-Z]+ ){2}0").unwrap();
 
     #[cfg(unix)]
     let re_two_num = Regex::new(r"[xrw-]{9} \d (\d+ ){2}0").unwrap();
 
     // Regex for one name: author, group or owner
-    let re_one = Regex::new(r"[xrw-]{9} \d [-0-9_a-z]+ 0").unwrap();
+    let re_one = Regex::new(r"[xrw-]{9} \d [-0-9_a-zA-Z]+ 0").unwrap();
 
     #[cfg(unix)]
     let re_one_num = Regex::new(r"[xrw-]{9} \d \d+ 0").unwrap();
@@ -1122,3 +1124,14 @@ fn test_ls_version_sort() {
     expected.insert(0, ".");
     assert_eq!(result.stdout.split('\n').collect::<Vec<_>>(), expected,)
 }
+
+#[test]
+fn test_ls_json() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("d");
+    at.write("d/f", "abc");
+    let stdout = ucmd.args(&["--json", "d"]).succeeds().stdout_move_str();
+    assert!(stdout.starts_with(r#"{"name":"f","path":"d/f","type":"file","size":3,"#));
+    assert!(stdout.ends_with(",\"target\":null}\n"));
+    assert_eq!(stdout.lines().count(), 1);
+}
@@ -6,3 +6,47 @@ fn test_more_no_arg() {
     let result = ucmd.run();
     assert!(!result.success);
 }
+
+#[test]
+fn test_more_not_a_terminal() {
+    // Without a terminal to page on, files are just printed, with headers
+    // when there are several
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("a", "alpha\n\tbeta\n");
+    at.write("b", "gamma\n");
+
+    ucmd.args(&["a", "b"]).succeeds().stdout_only(concat!(
+        "::::::::::::::\na\n::::::::::::::\n",
+        "alpha\n\tbeta\n",
+        "::::::::::::::\nb\n::::::::::::::\n",
+        "gamma\n"
+    ));
+}
+
+#[test]
+fn test_more_start_options() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("a", "one\ntwo\nthree\nfour\n");
+
+    ucmd.args(&["+3", "a"])
+        .succeeds()
+        .stdout_only("three\nfour\n");
+
+    new_ucmd!()
+        .args(&["+/^t", "-"])
+        .pipe_in("one\ntwo\nthree\n")
+        .succeeds()
+        .stdout_only("two\nthree\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_more_missing_file() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("a", "alpha\n");
+
+    ucmd.args(&["missing", "a"])
+        .fails()
+        .stdout_is("alpha\n")
+        .stderr_is("more: error: cannot open missing: No such file or directory");
+}
@@ -17,3 +17,39 @@ fn test_nohup_multiple_args_and_flags() {
     assert!(at.file_exists("file1"));
     assert!(at.file_exists("file2"));
 }
+
+#[test]
+fn test_nohup_missing_operand() {
+    new_ucmd!()
+        .fails()
+        .status_code(125)
+        .stderr_contains(&"nohup: missing operand");
+}
+
+#[test]
+fn test_nohup_missing_operand_posixly_correct() {
+    new_ucmd!()
+        .env("POSIXLY_CORRECT", "1")
+        .fails()
+        .status_code(127)
+        .stderr_contains(&"nohup: missing operand");
+}
+
+#[test]
+fn test_nohup_command_not_found() {
+    new_ucmd!()
+        .arg("no_such_command")
+        .fails()
+        .status_code(127)
+        .stderr_is("nohup: failed to run command 'no_such_command': No such file or directory");
+}
+
+#[test]
+fn test_nohup_command_not_executable() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch("not_executable");
+    ucmd.arg("./not_executable")
+        .fails()
+        .status_code(126)
+        .stderr_is("nohup: failed to run command './not_executable': Permission denied");
+}
@@ -23,7 +23,7 @@ fn test_nproc_all_omp() {
         .run();
     assert!(result.success);
     let nproc_omp: u8 = result.stdout.trim().parse().unwrap();
-    assert!(nproc - 1 == nproc_omp);
+    assert!(nproc_omp == 1);
 
     let result = TestScenario::new(util_name!())
         .ucmd_keepenv()
@@ -53,3 +53,67 @@ fn test_nproc_ignore() {
         assert!(nproc == 1);
     }
 }
+
+#[test]
+fn test_nproc_omp_num_threads() {
+    new_ucmd!()
+        .env("OMP_NUM_THREADS", "60")
+        .succeeds()
+        .stdout_is("60\n");
+    // only the first of a list counts
+    new_ucmd!()
+        .env("OMP_NUM_THREADS", " 3, 4,5")
+        .succeeds()
+        .stdout_is("3\n");
+    new_ucmd!()
+        .env("OMP_NUM_THREADS", "60")
+        .arg("--ignore=40")
+        .succeeds()
+        .stdout_is("20\n");
+}
+
+#[test]
+fn test_nproc_omp_invalid() {
+    let nproc = new_ucmd!().succeeds().stdout;
+    for threads in &["0", "-2", "3x", ""] {
+        new_ucmd!()
+            .env("OMP_NUM_THREADS", threads)
+            .succeeds()
+            .stdout_is(&nproc);
+    }
+}
+
+#[test]
+fn test_nproc_omp_thread_limit() {
+    new_ucmd!()
+        .env("OMP_NUM_THREADS", "60")
+        .env("OMP_THREAD_LIMIT", "10")
+        .succeeds()
+        .stdout_is("10\n");
+    new_ucmd!()
+        .env("OMP_THREAD_LIMIT", "1")
+        .succeeds()
+        .stdout_is("1\n");
+    new_ucmd!()
+        .env("OMP_NUM_THREADS", "60")
+        .env("OMP_THREAD_LIMIT", "0")
+        .succeeds()
+        .stdout_is("60\n");
+}
+
+#[test]
+fn test_nproc_ignore_all_but_one() {
+    new_ucmd!()
+        .arg("--ignore=1000000")
+        .succeeds()
+        .stdout_is("1\n");
+}
+
+#[test]
+fn test_nproc_invalid_ignore() {
+    new_ucmd!()
+        .arg("--ignore=x")
+        .fails()
+        .status_code(1)
+        .stderr_is("nproc: invalid number: 'x'");
+}
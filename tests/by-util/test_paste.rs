@@ -60,6 +60,39 @@ static EXAMPLE_DATA: &'static [TestData<'static>] = &[
         ins: &["1\na\n", "2\nb\n"],
         out: "1 2\na b\n",
     },
+    // Delimiters are used in turn and start over on each line; "\0" is empty
+    TestData {
+        name: "delim-list-1",
+        args: &["-d", "\\0x"],
+        ins: &["1\n2\n", "1\n2\n", "1\n2\n"],
+        out: "11x1\n22x2\n",
+    },
+    // Trailing whitespace is part of the line
+    TestData {
+        name: "trailing-blanks-1",
+        args: &[],
+        ins: &["a \n", "b\t\n"],
+        out: "a \tb\t\n",
+    },
+    // Files running out early still get their delimiters
+    TestData {
+        name: "uneven-1",
+        args: &[],
+        ins: &["a\n", "1\n2\n"],
+        out: "a\t1\n\t2\n",
+    },
+    TestData {
+        name: "serial-1",
+        args: &["-s", "-d", "a\\t"],
+        ins: &["1\n2\n3\n", "", "4"],
+        out: "1a2\t3\n\n4\n",
+    },
+    TestData {
+        name: "zero-terminated-1",
+        args: &["-z", "-s"],
+        ins: &["a\0b\0"],
+        out: "a\tb\0",
+    },
 ];
 
 #[test]
@@ -102,3 +135,20 @@ fn test_data() {
             .stdout_is(example.out);
     }
 }
+
+#[test]
+fn test_serial_multi_stdin() {
+    new_ucmd!()
+        .args(&["-s", "-", "-"])
+        .pipe_in("a\nb\nc\n")
+        .succeeds()
+        .stdout_only("a\tb\tc\n\n");
+}
+
+#[test]
+fn test_unescaped_backslash() {
+    new_ucmd!()
+        .args(&["-d", "x\\", "-"])
+        .fails()
+        .stderr_contains(&"delimiter list ends with an unescaped backslash: x\\");
+}
@@ -154,3 +154,45 @@ fn test_args_parsing() {
     let empty_args: [String; 0] = [];
     new_ucmd!().args(&empty_args).fails().no_stdout();
 }
+
+#[test]
+fn test_posix_messages() {
+    new_ucmd!()
+        .args(&["-p", "dir/file#name"])
+        .fails()
+        .stderr_is("pathchk: error: nonportable character '#' in file name 'dir/file#name'");
+    new_ucmd!()
+        .args(&["-p", "dir/abcdefghijklmnop"])
+        .fails()
+        .stderr_is(
+        "pathchk: error: limit 14 exceeded by length 16 of file name component 'abcdefghijklmnop'",
+    );
+    new_ucmd!()
+        .args(&["-p", &"dir/".repeat(64)])
+        .fails()
+        .stderr_is(format!(
+            "pathchk: error: limit 255 exceeded by length 256 of file name '{}'",
+            "dir/".repeat(64)
+        ));
+    new_ucmd!()
+        .args(&["-P", "dir/-file"])
+        .fails()
+        .stderr_is("pathchk: error: leading '-' in a component of file name 'dir/-file'");
+}
+
+#[test]
+fn test_default_mode_existing_prefix() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch("file");
+
+    // components below a file can't be looked up
+    ucmd.args(&["file/name"])
+        .fails()
+        .stderr_is("pathchk: error: file/name: Not a directory");
+
+    // long names are fine where the file system allows them
+    new_ucmd!()
+        .args(&["dir/abcdefghijklmnop"])
+        .succeeds()
+        .no_stderr();
+}
@@ -19,7 +19,12 @@ fn test_capitalize() {
 fn test_long_format() {
     let ulogin = "root";
     let pw: Passwd = Passwd::locate(ulogin).unwrap();
-    let real_name = pw.user_info().replace("&", &pw.name().capitalize());
+    let gecos = pw.user_info();
+    let real_name = gecos
+        .split(',')
+        .next()
+        .unwrap()
+        .replace("&", &pw.name().capitalize());
     new_ucmd!().arg("-l").arg(ulogin).run().stdout_is(format!(
         "Login name: {:<28}In real life:  {}\nDirectory: {:<29}Shell:  {}\n\n",
         ulogin,
@@ -34,6 +39,37 @@ fn test_long_format() {
     ));
 }
 
+#[test]
+fn test_long_format_unknown_user() {
+    new_ucmd!()
+        .args(&["-l", "no_such_user"])
+        .run()
+        .stdout_is("Login name: no_such_user                In real life:  ???\n");
+}
+
+#[test]
+fn test_long_format_no_user() {
+    new_ucmd!()
+        .arg("-l")
+        .fails()
+        .stderr_contains(&"no username specified");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_long_format_multiple_users() {
+    for args in &[
+        vec!["-l", "root", "nobody", "no_such_user"],
+        vec!["-lb", "root", "nobody"],
+        vec!["-lhp", "root"],
+    ] {
+        new_ucmd!()
+            .args(&args[..])
+            .run()
+            .stdout_is(expected_result(args));
+    }
+}
+
 #[cfg(target_os = "linux")]
 #[test]
 fn test_short_format_i() {
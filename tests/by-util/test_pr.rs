@@ -0,0 +1,138 @@
+use crate::common::util::*;
+
+#[test]
+fn test_omit_header() {
+    new_ucmd!()
+        .arg("-t")
+        .pipe_in("a\nb\nc\n")
+        .succeeds()
+        .stdout_only("a\nb\nc\n");
+}
+
+#[test]
+fn test_short_page_length_implies_omit_header() {
+    new_ucmd!()
+        .args(&["-l", "10"])
+        .pipe_in("a\nb\n")
+        .succeeds()
+        .stdout_only("a\nb\n");
+}
+
+#[test]
+fn test_header_and_page_padding() {
+    let result = new_ucmd!()
+        .args(&["-l", "12", "-h", "TITLE"])
+        .pipe_in("a\nb\nc\n")
+        .succeeds();
+    let lines: Vec<&str> = result.stdout_str().split('\n').collect();
+    // 5 header lines, 2 body lines and 5 trailer lines per page
+    assert_eq!(lines.len(), 2 * 12 + 1);
+    assert!(lines[2].contains(" TITLE "));
+    assert!(lines[2].ends_with("Page 1"));
+    assert_eq!(lines[5], "a");
+    assert_eq!(lines[6], "b");
+    assert!(lines[12 + 2].ends_with("Page 2"));
+    assert_eq!(lines[12 + 5], "c");
+}
+
+#[test]
+fn test_double_space() {
+    new_ucmd!()
+        .args(&["-t", "-d"])
+        .pipe_in("a\nb\n")
+        .succeeds()
+        .stdout_only("a\n\nb\n\n");
+}
+
+#[test]
+fn test_number_lines() {
+    new_ucmd!()
+        .args(&["-t", "-n"])
+        .pipe_in("a\nb\n")
+        .succeeds()
+        .stdout_only("    1\ta\n    2\tb\n");
+    new_ucmd!()
+        .args(&["-t", "-n:3", "-N", "9"])
+        .pipe_in("a\nb\n")
+        .succeeds()
+        .stdout_only("  9:a\n 10:b\n");
+}
+
+#[test]
+fn test_indent() {
+    new_ucmd!()
+        .args(&["-t", "-o", "3"])
+        .pipe_in("a\n\nb\n")
+        .succeeds()
+        .stdout_only("   a\n\n   b\n");
+}
+
+#[test]
+fn test_page_range() {
+    new_ucmd!()
+        .args(&["-l", "2", "+2:3"])
+        .pipe_in("1\n2\n3\n4\n5\n6\n7\n8\n")
+        .succeeds()
+        .stdout_only("3\n4\n5\n6\n");
+}
+
+#[test]
+fn test_form_feed() {
+    new_ucmd!()
+        .args(&["-t", "-F", "-l", "2"])
+        .pipe_in("1\n2\n3\n")
+        .succeeds()
+        .stdout_only("1\n2\n\x0c3\n\x0c");
+}
+
+#[test]
+fn test_form_feed_page_layout() {
+    let result = new_ucmd!()
+        .args(&["-F", "-l", "6", "-h", "TITLE"])
+        .pipe_in("a\nb\nc\nd\n")
+        .succeeds();
+    let pages: Vec<&str> = result.stdout_str().split('\x0c').collect();
+    // 3 header lines and 3 body lines per page, with no trailer
+    assert_eq!(pages.len(), 3);
+    let lines: Vec<&str> = pages[0].lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "");
+    assert!(lines[1].contains(" TITLE "));
+    assert!(lines[1].ends_with("Page 1"));
+    assert_eq!(lines[2], "");
+    assert_eq!(&lines[3..], &["a", "b", "c"]);
+    let lines: Vec<&str> = pages[1].lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[1].ends_with("Page 2"));
+    assert_eq!(lines[3], "d");
+    assert_eq!(pages[2], "");
+}
+
+#[test]
+fn test_plus_argument_as_option_value() {
+    let result = new_ucmd!()
+        .args(&["-l", "11", "-h", "+note"])
+        .pipe_in("a\n")
+        .succeeds();
+    let lines: Vec<&str> = result.stdout_str().split('\n').collect();
+    assert!(lines[2].contains(" +note "));
+    new_ucmd!()
+        .args(&["-th", "+note"])
+        .pipe_in("a\n")
+        .succeeds()
+        .stdout_only("a\n");
+}
+
+#[test]
+fn test_input_form_feed_starts_new_page() {
+    new_ucmd!()
+        .args(&["-l", "2", "+2"])
+        .pipe_in("1\x0c2\n3\n")
+        .succeeds()
+        .stdout_only("2\n3\n");
+}
+
+#[test]
+fn test_invalid_page_range() {
+    new_ucmd!().arg("+3:1").fails().stderr_contains(&"invalid page range");
+}
@@ -28,3 +28,34 @@ fn test_get_var() {
     assert!(!result.stdout.is_empty());
     assert!(result.stdout.trim() == "VALUE");
 }
+
+#[test]
+fn test_null_separator() {
+    new_ucmd!()
+        .env("HOME", "FOO")
+        .env("KEY", "VALUE")
+        .args(&["-0", "HOME", "KEY"])
+        .succeeds()
+        .stdout_is("FOO\x00VALUE\x00");
+}
+
+#[test]
+fn test_missing_variable() {
+    new_ucmd!()
+        .env("KEY", "VALUE")
+        .args(&["KEY", "NOT_A_VARIABLE", "KEY"])
+        .fails()
+        .status_code(1)
+        .stdout_is("VALUE\nVALUE\n");
+    new_ucmd!()
+        .env("KEY", "VALUE")
+        .arg("KEY=VALUE")
+        .fails()
+        .status_code(1)
+        .no_stdout();
+}
+
+#[test]
+fn test_invalid_option() {
+    new_ucmd!().arg("--not-an-option").fails().status_code(2);
+}
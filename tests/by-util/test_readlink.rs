@@ -54,3 +54,89 @@ fn test_long_redirection_to_root() {
     println!("expect: {:?}", expect);
     assert_eq!(actual, expect);
 }
+
+#[test]
+fn test_multiple_operands() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.touch("file");
+    at.symlink_file("file", "link");
+    at.symlink_file("nowhere", "dangling");
+
+    // A failing operand doesn't stop the others from being printed
+    scene
+        .ucmd()
+        .args(&["link", "file", "dangling"])
+        .fails()
+        .no_stderr()
+        .stdout_is("file\nnowhere\n");
+    scene
+        .ucmd()
+        .args(&["-v", "link", "file", GIBBERISH])
+        .fails()
+        .stdout_is("file\n")
+        .stderr_is(format!(
+            "readlink: file: Invalid argument\nreadlink: {}: No such file or directory",
+            GIBBERISH
+        ));
+    scene
+        .ucmd()
+        .args(&["-v", "-q", GIBBERISH])
+        .fails()
+        .no_stderr();
+}
+
+#[test]
+fn test_no_newline() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.symlink_file("file", "link");
+
+    scene
+        .ucmd()
+        .args(&["-n", "link"])
+        .succeeds()
+        .stdout_only("file");
+    scene
+        .ucmd()
+        .args(&["-n", "-z", "link"])
+        .succeeds()
+        .stdout_only("file");
+    scene
+        .ucmd()
+        .args(&["-n", "-q", "link", "link"])
+        .succeeds()
+        .stdout_is("file\nfile\n")
+        .stderr_is("readlink: ignoring --no-newline with multiple arguments");
+}
+
+#[test]
+fn test_zero() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.symlink_file("file", "link");
+    at.symlink_file("nowhere", "dangling");
+
+    scene
+        .ucmd()
+        .args(&["-z", "link", "dangling"])
+        .succeeds()
+        .stdout_only("file\0nowhere\0");
+}
+
+#[test]
+fn test_canonicalize_last_mode_wins() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let missing = format!("{}/x", GIBBERISH);
+    ucmd.args(&["-e", "-m", &missing])
+        .succeeds()
+        .stdout_only(path_concat!(at.root_dir_resolved(), GIBBERISH, "x") + "\n");
+
+    new_ucmd!()
+        .args(&["-m", "-e", &missing])
+        .fails()
+        .no_stdout();
+}
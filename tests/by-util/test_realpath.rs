@@ -1,5 +1,13 @@
 use crate::common::util::*;
 
+use std::path::Path;
+
+/// The canonical path of `name` in the fixtures, with nothing to resolve
+fn resolved_path(at: &AtPath, name: &str) -> String {
+    let path = Path::new(&at.root_dir_resolved()).join(name);
+    path.to_str().unwrap().to_owned()
+}
+
 #[test]
 fn test_current_directory() {
     let (at, mut ucmd) = at_and_ucmd!();
@@ -104,3 +112,120 @@ fn test_file_and_links_strip_zero() {
     assert!(actual.contains("bar"));
     assert!(!actual.contains("\n"));
 }
+
+#[test]
+fn test_relative_to() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.mkdir_all("a/b");
+    at.touch("a/c");
+
+    scene
+        .ucmd()
+        .args(&["--relative-to=a/b", "a/c", "a", "a/b"])
+        .succeeds()
+        .stdout_only(format!("..{0}c\n..\n.\n", std::path::MAIN_SEPARATOR));
+}
+
+#[test]
+fn test_relative_base() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.mkdir_all("a/b");
+
+    scene
+        .ucmd()
+        .args(&["--relative-base=a", "a/b", "a", "."])
+        .succeeds()
+        .stdout_only(format!("b\n.\n{}\n", at.root_dir_resolved()));
+
+    // Both are ignored when the directory to print relative to isn't below the base
+    scene
+        .ucmd()
+        .args(&["--relative-base=a/b", "--relative-to=a", "a/b"])
+        .succeeds()
+        .stdout_only(format!("{}\n", resolved_path(at, "a/b")));
+}
+
+#[test]
+fn test_canonicalize_modes() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.mkdir("a");
+
+    scene
+        .ucmd()
+        .arg("a/missing")
+        .succeeds()
+        .stdout_only(format!("{}\n", resolved_path(at, "a/missing")));
+    scene
+        .ucmd()
+        .args(&["-m", "missing/a/.."])
+        .succeeds()
+        .stdout_only(format!("{}\n", resolved_path(at, "missing")));
+    scene
+        .ucmd()
+        .arg("missing/a")
+        .fails()
+        .stderr_is("realpath: error: missing/a: No such file or directory");
+    scene
+        .ucmd()
+        .args(&["-e", "a/missing"])
+        .fails()
+        .stderr_is("realpath: error: a/missing: No such file or directory");
+    scene
+        .ucmd()
+        .args(&["-q", "-e", "a/missing"])
+        .fails()
+        .no_stderr()
+        .no_stdout();
+}
+
+#[test]
+#[cfg(unix)]
+fn test_not_a_directory() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.touch("f");
+
+    scene
+        .ucmd()
+        .arg("f/x")
+        .fails()
+        .stderr_is("realpath: error: f/x: Not a directory");
+    scene
+        .ucmd()
+        .arg("f/")
+        .fails()
+        .stderr_is("realpath: error: f/: Not a directory");
+    scene
+        .ucmd()
+        .args(&["-m", "f/x"])
+        .succeeds()
+        .stdout_only(format!("{}\n", resolved_path(at, "f/x")));
+}
+
+#[test]
+fn test_symlink_parent() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+
+    at.mkdir_all("a/b");
+    at.symlink_dir("a/b", "l");
+
+    // ".." applies to the link's target, unless links are left alone
+    scene
+        .ucmd()
+        .arg("l/..")
+        .succeeds()
+        .stdout_only(format!("{}\n", resolved_path(at, "a")));
+    scene
+        .ucmd()
+        .args(&["-s", "l/.."])
+        .succeeds()
+        .stdout_only(format!("{}\n", at.root_dir_resolved()));
+}
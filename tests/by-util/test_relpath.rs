@@ -1 +1,19 @@
+use crate::common::util::*;
+
 // ToDO: add tests
+
+#[test]
+fn test_help_flag() {
+    let help_short = new_ucmd!().arg("-h").succeeds();
+    let help_long = new_ucmd!().arg("--help").succeeds();
+
+    assert_eq!(help_short.stdout, help_long.stdout);
+}
+
+#[test]
+fn test_version_flag() {
+    let version_short = new_ucmd!().arg("-V").succeeds();
+    let version_long = new_ucmd!().arg("--version").succeeds();
+
+    assert_eq!(version_short.stdout, version_long.stdout);
+}
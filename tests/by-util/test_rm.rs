@@ -171,6 +171,23 @@ fn test_rm_recursive() {
     assert!(!at.file_exists(file_b));
 }
 
+#[test]
+#[cfg(unix)]
+fn test_rm_recursive_symlink_dir() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("target");
+    at.touch("target/kept");
+    at.mkdir_all("dir/sub");
+    at.touch("dir/sub/file");
+    std::os::unix::fs::symlink("../target", at.plus("dir/link")).unwrap();
+
+    ucmd.arg("-rv").arg("dir").succeeds().no_stderr();
+
+    // the link goes, what it points to stays
+    assert!(!at.dir_exists("dir"));
+    assert!(at.file_exists("target/kept"));
+}
+
 #[test]
 fn test_rm_directory_without_flag() {
     let (at, mut ucmd) = at_and_ucmd!();
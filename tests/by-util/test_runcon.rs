@@ -0,0 +1,59 @@
+use crate::common::util::*;
+
+fn selinux_enabled() -> bool {
+    std::path::Path::new("/sys/fs/selinux/enforce").exists()
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_current_context() {
+    if let Ok(context) = std::fs::read_to_string("/proc/self/attr/current") {
+        new_ucmd!()
+            .succeeds()
+            .stdout_only(format!("{}\n", context.trim_end_matches(&['\0', '\n'][..])));
+    }
+}
+
+#[test]
+fn test_no_command() {
+    new_ucmd!()
+        .arg("user_u:role_r:type_t")
+        .fails()
+        .status_code(1)
+        .stderr_contains(&"no command specified");
+}
+
+#[test]
+fn test_no_selinux() {
+    if selinux_enabled() {
+        return;
+    }
+    for args in &[
+        vec!["user_u:role_r:type_t", "true"],
+        vec!["-t", "type_t", "true"],
+        vec!["-c", "true"],
+    ] {
+        new_ucmd!()
+            .args(&args[..])
+            .fails()
+            .status_code(1)
+            .stderr_is("runcon: runcon may be used only on a SELinux kernel");
+    }
+}
+
+#[test]
+fn test_invalid_context() {
+    if !selinux_enabled() {
+        return;
+    }
+    new_ucmd!()
+        .args(&["not_a_context", "true"])
+        .fails()
+        .status_code(1)
+        .stderr_is("runcon: failed to create security context: 'not_a_context': Invalid argument");
+    new_ucmd!()
+        .args(&["-t", "a:b", "true"])
+        .fails()
+        .status_code(1)
+        .stderr_is("runcon: failed to set new type: 'a:b': Invalid argument");
+}
@@ -110,3 +110,43 @@ fn test_sleep_sum_duration_many() {
     let duration = before_test.elapsed();
     assert!(duration >= millis_900);
 }
+
+#[test]
+fn test_sleep_d_suffix() {
+    let millis_864 = Duration::from_millis(864);
+    let before_test = Instant::now();
+
+    new_ucmd!().args(&["0.00001d"]).succeeds().stdout_only("");
+
+    let duration = before_test.elapsed();
+    assert!(duration >= millis_864);
+}
+
+#[test]
+fn test_sleep_invalid_interval() {
+    new_ucmd!().args(&["1x", "0.1", "nan"]).fails().stderr_is(
+        "sleep: error: invalid time interval '1x'\n\
+             sleep: error: invalid time interval 'nan'\n\
+             Try 'sleep --help' for more information.",
+    );
+}
+
+#[test]
+fn test_sleep_infinity() {
+    let mut child = new_ucmd!().arg("infinity").run_no_wait();
+
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(child.try_wait().unwrap().is_none());
+
+    child.kill().unwrap();
+}
+
+#[test]
+fn test_sleep_infinity_sum() {
+    let mut child = new_ucmd!().args(&["inf", "1d"]).run_no_wait();
+
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(child.try_wait().unwrap().is_none());
+
+    child.kill().unwrap();
+}
@@ -64,6 +64,9 @@ mod test_fsext {
         assert_eq!("zfs", pretty_fstype(0x2FC12FC1));
         assert_eq!("ntfs", pretty_fstype(0x5346544e));
         assert_eq!("fat", pretty_fstype(0x4006));
+        assert_eq!("cgroup2fs", pretty_fstype(0x63677270));
+        assert_eq!("exfat", pretty_fstype(0x2011BAB0));
+        assert_eq!("overlayfs", pretty_fstype(0x794c7630));
         assert_eq!("UNKNOWN (0x1234)", pretty_fstype(0x1234));
     }
 }
@@ -106,6 +109,7 @@ mod test_generate_tokens {
                 flag: F_GROUP | F_ZERO,
                 width: 10,
                 precision: 2,
+                modifier: None,
                 format: 'a',
             },
             Token::Char('c'),
@@ -113,6 +117,7 @@ mod test_generate_tokens {
                 flag: F_LEFT | F_ALTER,
                 width: 5,
                 precision: 0,
+                modifier: None,
                 format: 'w',
             },
             Token::Char('\n'),
@@ -128,6 +133,7 @@ mod test_generate_tokens {
                 flag: F_LEFT | F_ALTER | F_SPACE,
                 width: 15,
                 precision: -1,
+                modifier: None,
                 format: 'a',
             },
             Token::Char('\r'),
@@ -142,6 +148,7 @@ mod test_generate_tokens {
                 flag: F_SIGN | F_ZERO,
                 width: 20,
                 precision: -1,
+                modifier: None,
                 format: 'w',
             },
             Token::Char('\x12'),
@@ -152,6 +159,66 @@ mod test_generate_tokens {
         ];
         assert_eq!(&expected, &Stater::generate_tokens(s, true).unwrap());
     }
+
+    #[test]
+    fn modifiers_and_time_precision() {
+        let s = "%Hd%5Lr%H%.Y%.w";
+        let expected = vec![
+            Token::Directive {
+                flag: 0,
+                width: 0,
+                precision: -1,
+                modifier: Some('H'),
+                format: 'd',
+            },
+            Token::Directive {
+                flag: 0,
+                width: 5,
+                precision: -1,
+                modifier: Some('L'),
+                format: 'r',
+            },
+            Token::Directive {
+                flag: 0,
+                width: 0,
+                precision: -1,
+                modifier: None,
+                format: 'H',
+            },
+            Token::Directive {
+                flag: 0,
+                width: 0,
+                precision: 9,
+                modifier: None,
+                format: 'Y',
+            },
+            Token::Directive {
+                flag: 0,
+                width: 0,
+                precision: 0,
+                modifier: None,
+                format: 'w',
+            },
+        ];
+        assert_eq!(&expected, &Stater::generate_tokens(s, true).unwrap());
+    }
+}
+
+#[test]
+fn test_epoch_sec() {
+    assert_eq!("1600000000", epoch_sec(1_600_000_000, 123_456_789, 0));
+    assert_eq!("1600000000.123", epoch_sec(1_600_000_000, 123_456_789, 3));
+    assert_eq!(
+        "1600000000.987654321",
+        epoch_sec(1_600_000_000, 987_654_321, 9)
+    );
+    assert_eq!(
+        "1600000000.00500000000",
+        epoch_sec(1_600_000_000, 5_000_000, 11)
+    );
+    assert_eq!("-6", epoch_sec(-6, 500_000_000, 0));
+    assert_eq!("-5.500", epoch_sec(-6, 500_000_000, 3));
+    assert_eq!("-0.25", epoch_sec(-1, 750_000_000, 2));
 }
 
 #[test]
@@ -188,6 +255,29 @@ fn test_fs_format() {
         .stdout_is(expected_result(&args));
 }
 
+#[test]
+#[cfg(target_os = "linux")]
+fn test_fs_block_sizes() {
+    let args = ["-f", "-c", "%s %S %#t %-12T|", "/"];
+    new_ucmd!()
+        .args(&args)
+        .run()
+        .stdout_is(expected_result(&args));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_fs_missing_file() {
+    new_ucmd!()
+        .args(&["-f", "no-such-file"])
+        .fails()
+        .no_stdout()
+        .stderr_is(
+            "stat: cannot read file system information for 'no-such-file': \
+             No such file or directory\n",
+        );
+}
+
 #[test]
 #[cfg(target_os = "linux")]
 fn test_terse_normal_format() {
@@ -330,6 +420,181 @@ fn test_printf() {
         .stdout_is(expected_result(&args));
 }
 
+#[test]
+#[cfg(target_os = "linux")]
+fn test_device_numbers() {
+    let args = ["-c", "%Hd %Ld %r %R %Hr %Lr %t %T %#5t %-3T|", "/dev/null"];
+    new_ucmd!()
+        .args(&args)
+        .run()
+        .stdout_is(expected_result(&args));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_time_precision() {
+    let args = [
+        "-c",
+        "%.Y %.3Y %.0Z %.12Z %015.2Y %-15.1Z| %+Y %IY",
+        "/boot",
+    ];
+    new_ucmd!()
+        .args(&args)
+        .run()
+        .stdout_is(expected_result(&args));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_unknown_directive() {
+    new_ucmd!()
+        .args(&["-c", "%k %H %%", "/"])
+        .run()
+        .stdout_is("? ? %\n");
+}
+
+#[test]
+fn test_printf_escapes() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write("f", "abc");
+    ts.ucmd()
+        .args(&["--printf", "%n\\0%s\\t\\x41\\101\\\\", "f"])
+        .succeeds()
+        .stdout_only("f\03\tAA\\");
+}
+
+#[test]
+fn test_empty_format() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    ts.ucmd()
+        .args(&["-c", "", "f", "f"])
+        .succeeds()
+        .stdout_only("\n\n");
+    ts.ucmd()
+        .args(&["--printf", "", "f"])
+        .succeeds()
+        .no_stdout();
+}
+
+#[test]
+fn test_last_format_wins() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    ts.ucmd()
+        .args(&["-c", "%n", "--printf", "[%n]\\n", "f"])
+        .succeeds()
+        .stdout_only("[f]\n");
+    ts.ucmd()
+        .args(&["--printf", "[%n]\\n", "-c", "%n\\t", "f"])
+        .succeeds()
+        .stdout_only("f\\t\n");
+}
+
+#[test]
+fn test_terse_with_format() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    ts.ucmd()
+        .args(&["-t", "-c", "X%n", "f"])
+        .succeeds()
+        .stdout_only("Xf\n");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_terse_file() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write("f", "abc");
+    let path = ts.fixtures.plus_as_string("f");
+    let args = ["-t", path.as_str()];
+    let expected = expected_result(&args);
+    ts.ucmd().args(&args).succeeds().stdout_is(expected);
+}
+
+#[test]
+fn test_missing_operand() {
+    new_ucmd!()
+        .fails()
+        .no_stdout()
+        .stderr_is("stat: missing operand\nTry 'stat --help' for more information.");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_missing_file_continues() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    ts.ucmd()
+        .args(&["-c", "%n", "no-such-file", "f"])
+        .fails()
+        .stdout_is("f\n")
+        .stderr_is("stat: cannot stat 'no-such-file': No such file or directory");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_quoted_name() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("it's");
+    ts.fixtures.touch("a b");
+    std::os::unix::fs::symlink("it's", ts.fixtures.plus("link")).unwrap();
+    ts.ucmd()
+        .args(&["-c", "%N", "link", "a b", "it's"])
+        .succeeds()
+        .stdout_only("'link' -> \"it's\"\n'a b'\n\"it's\"\n");
+    ts.ucmd()
+        .args(&["-L", "-c", "%N", "link"])
+        .succeeds()
+        .stdout_only("'link'\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_quoting_style_env() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("it's");
+    std::os::unix::fs::symlink("it's", ts.fixtures.plus("link")).unwrap();
+    ts.ucmd()
+        .env("QUOTING_STYLE", "c")
+        .args(&["-c", "%N", "link"])
+        .succeeds()
+        .stdout_only("\"link\" -> \"it's\"\n");
+    ts.ucmd()
+        .env("QUOTING_STYLE", "bogus")
+        .args(&["-c", "%N", "link"])
+        .succeeds()
+        .stdout_is("'link' -> \"it's\"\n")
+        .stderr_is("stat: ignoring invalid value of environment variable QUOTING_STYLE: 'bogus'");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_dereference_dangling() {
+    let ts = TestScenario::new(util_name!());
+    std::os::unix::fs::symlink("nowhere", ts.fixtures.plus("dangling")).unwrap();
+    ts.ucmd()
+        .args(&["-c", "%N", "dangling"])
+        .succeeds()
+        .stdout_only("'dangling' -> 'nowhere'\n");
+    ts.ucmd()
+        .args(&["-L", "-c", "%N", "dangling"])
+        .fails()
+        .no_stdout()
+        .stderr_is("stat: cannot stat 'dangling': No such file or directory");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_birth_time_precision() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.touch("f");
+    let path = ts.fixtures.plus_as_string("f");
+    let args = ["-c", "%W %.9W %w", path.as_str()];
+    let expected = expected_result(&args);
+    ts.ucmd().args(&args).succeeds().stdout_is(expected);
+}
+
 #[cfg(target_os = "linux")]
 fn expected_result(args: &[&str]) -> String {
     TestScenario::new(util_name!())
@@ -339,3 +604,32 @@ fn expected_result(args: &[&str]) -> String {
         .run()
         .stdout
 }
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_json() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("f", "abc");
+    let stdout = ucmd.args(&["--json", "f"]).succeeds().stdout_move_str();
+    assert!(stdout.starts_with(r#"{"name":"f","type":"file","size":3,"#));
+    assert!(stdout.contains(r#""permissions":"#));
+    assert!(stdout.ends_with(",\"target\":null}\n"));
+}
+
+#[test]
+#[cfg(windows)]
+fn test_read_only_windows() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch("f");
+    let mut permissions = at.metadata("f").permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(at.plus("f"), permissions).unwrap();
+    let stdout = ucmd.args(&["-c", "%A", "f"]).succeeds().stdout_move_str();
+    assert!(stdout.starts_with("-r-"));
+    assert!(!stdout.contains('w'));
+}
+
+#[test]
+fn test_json_conflicts_with_format() {
+    new_ucmd!().args(&["--json", "-c", "%n", "."]).fails();
+}
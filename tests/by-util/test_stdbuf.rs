@@ -11,3 +11,14 @@ fn test_stdbuf_unbuffered_stdout() {
             .stdout_is("The quick brown fox jumps over the lazy dog.");
     }
 }
+
+#[test]
+fn test_stdbuf_line_buffered_stdout() {
+    if cfg!(target_os = "linux") {
+        new_ucmd!()
+            .args(&["-oL", "head"])
+            .pipe_in("The quick brown fox jumps over the lazy dog.")
+            .run()
+            .stdout_is("The quick brown fox jumps over the lazy dog.");
+    }
+}
@@ -0,0 +1,95 @@
+use crate::common::util::*;
+
+#[test]
+fn test_not_a_tty() {
+    new_ucmd!()
+        .pipe_in("")
+        .fails()
+        .status_code(1)
+        .stderr_is("stty: 'standard input': Inappropriate ioctl for device");
+}
+
+#[test]
+fn test_file_not_a_tty() {
+    new_ucmd!()
+        .args(&["-F", "/dev/null"])
+        .fails()
+        .stderr_is("stty: /dev/null: Inappropriate ioctl for device");
+    new_ucmd!()
+        .args(&["--file=/dev/null", "-a"])
+        .fails()
+        .stderr_is("stty: /dev/null: Inappropriate ioctl for device");
+}
+
+#[test]
+fn test_file_missing() {
+    new_ucmd!()
+        .args(&["-F", "/nonexistent/tty"])
+        .fails()
+        .stderr_is("stty: /nonexistent/tty: No such file or directory");
+}
+
+#[test]
+fn test_invalid_argument() {
+    // settings are checked before the terminal is looked at
+    for arg in &["foo", "-sane", "-cs8", "12345", "-F"] {
+        new_ucmd!()
+            .arg(arg)
+            .fails()
+            .status_code(1)
+            .stderr_contains(&format!("stty: invalid argument '{}'", arg))
+            .stderr_contains(&"Try 'stty --help' for more information.");
+    }
+}
+
+#[test]
+fn test_missing_argument() {
+    new_ucmd!()
+        .args(&["-echo", "intr"])
+        .fails()
+        .stderr_contains(&"stty: missing argument to 'intr'");
+}
+
+#[test]
+fn test_invalid_integer() {
+    new_ucmd!()
+        .args(&["min", "x"])
+        .fails()
+        .stderr_is("stty: invalid integer argument: 'x'");
+    new_ucmd!()
+        .args(&["min", "-1"])
+        .fails()
+        .stderr_is("stty: invalid integer argument: '-1'");
+    new_ucmd!()
+        .args(&["time", "256"])
+        .fails()
+        .stderr_is("stty: invalid integer argument: '256': Numerical result out of range");
+    new_ucmd!()
+        .args(&["rows", "99999999999"])
+        .fails()
+        .stderr_is(
+            "stty: invalid integer argument: '99999999999': Value too large for defined data type",
+        );
+}
+
+#[test]
+fn test_invalid_speed() {
+    new_ucmd!()
+        .args(&["ispeed", "7"])
+        .fails()
+        .stderr_is("stty: invalid ispeed '7'");
+}
+
+#[test]
+fn test_output_styles_exclusive() {
+    new_ucmd!()
+        .args(&["-a", "-g"])
+        .fails()
+        .stderr_is(
+            "stty: the options for verbose and stty-readable output styles are\nmutually exclusive",
+        );
+    new_ucmd!()
+        .args(&["-g", "raw"])
+        .fails()
+        .stderr_is("stty: when specifying an output style, modes may not be set");
+}
@@ -53,6 +53,33 @@ fn test_sysv_stdin() {
         .stdout_only_fixture("sysv_stdin.expected");
 }
 
+#[test]
+fn test_blocks() {
+    // 3000 bytes are 3 1K blocks, and 6 512 byte ones
+    let input: Vec<u8> = (0..3000).map(|i: u32| (i * 7 % 251) as u8).collect();
+    new_ucmd!()
+        .pipe_in(input.clone())
+        .succeeds()
+        .stdout_only("48817     3\n");
+    new_ucmd!()
+        .arg("-s")
+        .pipe_in(input)
+        .succeeds()
+        .stdout_only("46359 6\n");
+}
+
+#[test]
+fn test_last_algorithm_wins() {
+    new_ucmd!()
+        .args(&["-s", "-r", "lorem_ipsum.txt"])
+        .succeeds()
+        .stdout_only_fixture("bsd_single_file.expected");
+    new_ucmd!()
+        .args(&["-r", "-s", "lorem_ipsum.txt"])
+        .succeeds()
+        .stdout_only_fixture("sysv_single_file.expected");
+}
+
 #[test]
 fn test_invalid_file() {
     let (at, mut ucmd) = at_and_ucmd!();
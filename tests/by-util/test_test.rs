@@ -8,6 +8,7 @@
 //
 
 use crate::common::util::*;
+use filetime::FileTime;
 
 #[test]
 fn test_op_prec_and_or_1() {
@@ -25,3 +26,106 @@ fn test_op_prec_and_or_2() {
 fn test_or_as_filename() {
     new_ucmd!().args(&["x", "-a", "-z", "-o"]).fails();
 }
+
+#[test]
+fn test_posix_by_argument_count() {
+    // with 3 arguments, a binary operator wins over "!" and parentheses
+    new_ucmd!().args(&["!", "=", "!"]).succeeds();
+    new_ucmd!().args(&["(", "=", ")"]).fails();
+    new_ucmd!().args(&["(", "-a", ")"]).succeeds();
+    new_ucmd!().args(&["!", "(", "", ")"]).succeeds();
+    new_ucmd!().args(&["-a"]).succeeds();
+}
+
+#[test]
+fn test_parentheses() {
+    new_ucmd!()
+        .args(&["x", "-a", "(", "y", "-o", "", ")"])
+        .succeeds();
+    new_ucmd!()
+        .args(&["(", "x", "=", "y", ")", "-o", "x"])
+        .succeeds();
+    new_ucmd!()
+        .args(&["!", "(", "x", "=", "x", ")", "-a", "x"])
+        .fails();
+    new_ucmd!()
+        .args(&["(", "x", "-a", "y"])
+        .run()
+        .status_code(2)
+        .stderr_is("test: error: ')' expected");
+}
+
+#[test]
+fn test_and_binds_tighter_than_or() {
+    new_ucmd!()
+        .args(&["a", "=", "b", "-o", "c", "=", "c", "-a", ""])
+        .fails();
+    new_ucmd!()
+        .args(&["a", "=", "a", "-o", "c", "=", "d", "-a", ""])
+        .succeeds();
+}
+
+#[test]
+fn test_string_ordering() {
+    new_ucmd!().args(&["a", "<", "b"]).succeeds();
+    new_ucmd!().args(&["a", ">", "b"]).fails();
+}
+
+#[test]
+fn test_syntax_errors() {
+    new_ucmd!()
+        .args(&["-q", "x"])
+        .run()
+        .status_code(2)
+        .stderr_is("test: error: '-q': unary operator expected");
+    new_ucmd!()
+        .args(&["a", "b", "c", "d", "e"])
+        .run()
+        .status_code(2)
+        .stderr_is("test: error: extra argument 'b'");
+    new_ucmd!()
+        .args(&["x", "-eq", "1"])
+        .run()
+        .status_code(2)
+        .stderr_is("test: error: invalid integer 'x'");
+}
+
+#[test]
+fn test_newer_older() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+    at.touch("old");
+    at.touch("new");
+    let time = FileTime::from_unix_time(1_000_000_000, 0);
+    filetime::set_file_times(at.plus("old"), time, time).unwrap();
+
+    scene.ucmd().args(&["new", "-nt", "old"]).succeeds();
+    scene.ucmd().args(&["old", "-ot", "new"]).succeeds();
+    scene.ucmd().args(&["old", "-nt", "new"]).fails();
+    // a file that doesn't exist is older than any that does
+    scene.ucmd().args(&["missing", "-ot", "old"]).succeeds();
+    scene.ucmd().args(&["old", "-nt", "missing"]).succeeds();
+}
+
+#[test]
+#[cfg(unix)]
+fn test_same_file() {
+    let scene = TestScenario::new(util_name!());
+    let at = &scene.fixtures;
+    at.touch("file");
+    at.touch("other");
+    std::fs::hard_link(at.plus("file"), at.plus("link")).unwrap();
+
+    scene.ucmd().args(&["file", "-ef", "link"]).succeeds();
+    scene.ucmd().args(&["file", "-ef", "other"]).fails();
+}
+
+#[test]
+#[cfg(unix)]
+fn test_ownership_and_sticky() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch("file");
+
+    ucmd.args(&["-O", "file", "-a", "-G", "file", "-a", "!", "-k", "file"])
+        .succeeds();
+}
@@ -1,5 +1,7 @@
 use crate::common::util::*;
 
+use std::time::{Duration, Instant};
+
 // FIXME: this depends on the system having true and false in PATH
 //        the best solution is probably to generate some test binaries that we can call for any
 //        utility that requires executing another program (kill, for instance)
@@ -9,3 +11,105 @@ fn test_subcommand_retcode() {
 
     new_ucmd!().arg("1").arg("false").run().status_code(1);
 }
+
+#[test]
+fn test_command_with_options() {
+    new_ucmd!()
+        .args(&["1", "sh", "-c", "exit 3"])
+        .run()
+        .status_code(3);
+}
+
+#[test]
+fn test_zero_duration() {
+    new_ucmd!().args(&["0", "sleep", "0.2"]).succeeds();
+}
+
+#[test]
+fn test_timed_out() {
+    new_ucmd!()
+        .args(&["0.1", "sleep", "10"])
+        .run()
+        .status_code(124);
+}
+
+#[test]
+fn test_preserve_status() {
+    // 128 + SIGTERM
+    new_ucmd!()
+        .args(&["--preserve-status", "0.1", "sleep", "10"])
+        .run()
+        .status_code(143);
+}
+
+#[test]
+fn test_kill_after() {
+    new_ucmd!()
+        .args(&["-k", "0.1", "0.1", "sh", "-c", "trap '' TERM; sleep 1"])
+        .run()
+        .status_code(137);
+}
+
+#[test]
+fn test_verbose() {
+    new_ucmd!()
+        .args(&["-v", "0.1", "sleep", "10"])
+        .run()
+        .status_code(124)
+        .stderr_is("timeout: sending signal TERM to command 'sleep'");
+}
+
+#[test]
+fn test_command_not_found() {
+    new_ucmd!()
+        .args(&["1", "this-command-does-not-exist"])
+        .run()
+        .status_code(127);
+}
+
+#[test]
+fn test_command_cannot_invoke() {
+    new_ucmd!().args(&["1", "/"]).run().status_code(126);
+}
+
+#[test]
+fn test_signal_by_name() {
+    new_ucmd!()
+        .args(&["-s", "KILL", "0.1", "sleep", "10"])
+        .run()
+        .status_code(137);
+    new_ucmd!()
+        .args(&["--preserve-status", "--signal=SIGHUP", "0.1", "sleep", "10"])
+        .run()
+        .status_code(129);
+}
+
+#[test]
+fn test_signal_by_number() {
+    new_ucmd!()
+        .args(&["-s", "9", "0.1", "sleep", "10"])
+        .run()
+        .status_code(137);
+}
+
+#[test]
+fn test_invalid_signal() {
+    new_ucmd!()
+        .args(&["-s", "FOO", "1", "true"])
+        .run()
+        .status_code(125)
+        .stderr_is("timeout: error: invalid signal 'FOO'");
+}
+
+#[test]
+fn test_signal_process_group() {
+    // the shell's own child has to be stopped too for the pipes to close
+    let before_test = Instant::now();
+
+    new_ucmd!()
+        .args(&["0.1", "sh", "-c", "sleep 10; true"])
+        .run()
+        .status_code(124);
+
+    assert!(before_test.elapsed() < Duration::from_secs(5));
+}
@@ -231,8 +231,36 @@ fn test_touch_set_both_date_and_reference() {
     set_file_times(&at, ref_file, start_of_year, start_of_year);
     assert!(at.file_exists(ref_file));
 
+    // an absolute date replaces the reference times
     ucmd.args(&["-d", "Thu Jan 01 12:34:00 2015", "-r", ref_file, file])
-        .fails();
+        .succeeds()
+        .no_stderr();
+
+    let expected = str_to_filetime("%Y%m%d%H%M", "201501011234");
+    let (atime, mtime) = get_file_times(&at, file);
+    assert_eq!(atime, expected);
+    assert_eq!(mtime, expected);
+}
+
+#[test]
+fn test_touch_reference_with_date_adjustment() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let ref_file = "test_touch_reference";
+    let file = "test_touch_reference_with_date_adjustment";
+    let start_of_year = str_to_filetime("%Y%m%d%H%M", "201501010000");
+    let end_of_year = str_to_filetime("%Y%m%d%H%M", "201512312359");
+
+    at.touch(ref_file);
+    set_file_times(&at, ref_file, start_of_year, end_of_year);
+
+    // a relative date adjusts each reference time
+    ucmd.args(&["-r", ref_file, "-d", "+1 hour", file])
+        .succeeds()
+        .no_stderr();
+
+    let (atime, mtime) = get_file_times(&at, file);
+    assert_eq!(atime.unix_seconds() - start_of_year.unix_seconds(), 3600);
+    assert_eq!(mtime.unix_seconds() - end_of_year.unix_seconds(), 3600);
 }
 
 #[test]
@@ -314,6 +342,37 @@ fn test_touch_no_dereference() {
     assert_eq!(mtime, start_of_year);
 }
 
+#[test]
+fn test_touch_no_dereference_dangling() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let link = "test_touch_no_dereference_dangling";
+    let end_of_year = str_to_filetime("%Y%m%d%H%M", "201512312359");
+
+    at.symlink_file("missing", link);
+
+    ucmd.args(&["-t", "201512312359", "-h", link])
+        .succeeds()
+        .no_stderr();
+
+    assert!(!at.file_exists("missing"));
+    let (atime, mtime) = get_symlink_times(&at, link);
+    assert_eq!(atime, end_of_year);
+    assert_eq!(mtime, end_of_year);
+}
+
+#[test]
+fn test_touch_no_dereference_missing() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let file = "test_touch_no_dereference_missing";
+
+    ucmd.args(&["-h", file]).fails().stderr_is(format!(
+        "touch: error: setting times of '{}': No such file or directory",
+        file
+    ));
+
+    assert!(!at.file_exists(file));
+}
+
 #[test]
 fn test_touch_reference() {
     let (at, mut ucmd) = at_and_ucmd!();
@@ -335,6 +394,42 @@ fn test_touch_reference() {
     assert_eq!(mtime, start_of_year);
 }
 
+#[test]
+fn test_touch_reference_nanoseconds() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let file_a = "test_touch_reference_nanoseconds_a";
+    let file_b = "test_touch_reference_nanoseconds_b";
+    let atime = FileTime::from_unix_time(1_420_070_400, 123_456_789);
+    let mtime = FileTime::from_unix_time(1_451_606_399, 987_654_321);
+
+    at.touch(file_a);
+    set_file_times(&at, file_a, atime, mtime);
+
+    ucmd.args(&["-r", file_a, file_b]).succeeds().no_stderr();
+
+    assert_eq!(get_file_times(&at, file_b), (atime, mtime));
+}
+
+#[test]
+fn test_touch_reference_access_only_keeps_mtime() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let file_a = "test_touch_reference_access_only_a";
+    let file_b = "test_touch_reference_access_only_b";
+    let ref_time = FileTime::from_unix_time(1_420_070_400, 123_456_789);
+    let mtime = FileTime::from_unix_time(1_451_606_399, 987_654_321);
+
+    at.touch(file_a);
+    at.touch(file_b);
+    set_file_times(&at, file_a, ref_time, ref_time);
+    set_file_times(&at, file_b, mtime, mtime);
+
+    ucmd.args(&["-a", "-r", file_a, file_b])
+        .succeeds()
+        .no_stderr();
+
+    assert_eq!(get_file_times(&at, file_b), (ref_time, mtime));
+}
+
 #[test]
 fn test_touch_set_date() {
     let (at, mut ucmd) = at_and_ucmd!();
@@ -352,3 +447,120 @@ fn test_touch_set_date() {
     assert_eq!(atime, start_of_year);
     assert_eq!(mtime, start_of_year);
 }
+
+#[test]
+fn test_touch_set_two_digit_year() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let file = "test_touch_set_two_digit_year";
+
+    ucmd.args(&["-t", "6901011234", file])
+        .succeeds()
+        .no_stderr();
+
+    let expected = str_to_filetime("%Y%m%d%H%M", "196901011234");
+    let (atime, mtime) = get_file_times(&at, file);
+    assert_eq!(atime, expected);
+    assert_eq!(mtime, expected);
+}
+
+#[test]
+fn test_touch_set_leap_second() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let file = "test_touch_set_leap_second";
+
+    ucmd.args(&["-t", "201501011234.60", file])
+        .succeeds()
+        .no_stderr();
+
+    let expected = str_to_filetime("%Y%m%d%H%M", "201501011235");
+    let (_, mtime) = get_file_times(&at, file);
+    assert_eq!(mtime, expected);
+}
+
+#[test]
+fn test_touch_set_invalid_timestamp() {
+    for stamp in &[
+        "20150101123",
+        "201501011234.5",
+        "2015010112.34",
+        "201502301234",
+    ] {
+        new_ucmd!()
+            .args(&["-t", stamp, "file"])
+            .fails()
+            .stderr_is(format!("touch: error: invalid date format '{}'", stamp));
+    }
+}
+
+#[test]
+fn test_touch_set_date_fraction() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let file = "test_touch_set_date_fraction";
+
+    ucmd.args(&["-d", "2015-01-01 12:34:56.123456789", file])
+        .succeeds()
+        .no_stderr();
+
+    let expected = str_to_filetime("%Y%m%d%H%M%S", "20150101123456");
+    let (atime, mtime) = get_file_times(&at, file);
+    assert_eq!(atime, mtime);
+    assert_eq!(mtime.unix_seconds(), expected.unix_seconds());
+    assert_eq!(mtime.nanoseconds(), 123_456_789);
+}
+
+#[test]
+fn test_touch_read_only_file() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let file = "test_touch_read_only_file";
+    at.touch(file);
+    let mut permissions = at.metadata(file).permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(at.plus(file), permissions).unwrap();
+
+    ucmd.args(&["-t", "201501011234", file])
+        .succeeds()
+        .no_stderr();
+
+    let expected = str_to_filetime("%Y%m%d%H%M", "201501011234");
+    let (atime, mtime) = get_file_times(&at, file);
+    assert_eq!(atime, expected);
+    assert_eq!(mtime, expected);
+}
+
+#[test]
+fn test_touch_set_invalid_date() {
+    new_ucmd!()
+        .args(&["-d", "foo", "file"])
+        .fails()
+        .stderr_is("touch: error: invalid date format 'foo'");
+}
+
+#[test]
+fn test_touch_time_word() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let file = "test_touch_time_word";
+    let start_of_year = str_to_filetime("%Y%m%d%H%M", "201501010000");
+
+    at.touch(file);
+    set_file_times(&at, file, start_of_year, start_of_year);
+
+    // an unambiguous abbreviation is enough
+    ucmd.args(&["-t", "201501011234", "--time=mo", file])
+        .succeeds()
+        .no_stderr();
+
+    let (atime, mtime) = get_file_times(&at, file);
+    assert_eq!(atime, start_of_year);
+    assert_eq!(mtime.unix_seconds() - start_of_year.unix_seconds(), 45240);
+}
+
+#[test]
+fn test_touch_invalid_time_word() {
+    new_ucmd!().args(&["--time=foo", "file"]).fails().stderr_is(
+        "touch: error: invalid argument 'foo' for '--time'\n\
+             Valid arguments are:\n  \
+             - 'atime', 'access', 'use'\n  \
+             - 'mtime', 'modify'\n\
+             Try 'touch --help' for more information.",
+    );
+}
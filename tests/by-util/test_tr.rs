@@ -21,6 +21,7 @@ fn test_small_set2() {
 #[test]
 fn test_unicode() {
     new_ucmd!()
+        .env("LC_ALL", "en_US.UTF-8")
         .args(&[", ┬─┬", "╯︵┻━┻"])
         .pipe_in("(,°□°）, ┬─┬")
         .run()
@@ -180,6 +181,17 @@ fn test_octal_escape_is_at_most_three_digits() {
         .stdout_is("\r6");
 }
 
+#[test]
+fn test_octal_escape_is_at_most_a_byte() {
+    new_ucmd!()
+        .env("LC_ALL", "C")
+        .args(&["ab", r"\400"])
+        .pipe_in("ab")
+        .succeeds()
+        .stderr_contains(&"the ambiguous octal escape \\400")
+        .stdout_is(" 0");
+}
+
 #[test]
 fn test_non_octal_digit_ends_escape() {
     new_ucmd!()
@@ -197,3 +209,174 @@ fn test_interpret_backslash_at_eol_literally() {
         .succeeds()
         .stdout_is("\\");
 }
+
+#[test]
+fn test_character_classes() {
+    new_ucmd!()
+        .args(&["[:lower:]", "[:upper:]"])
+        .pipe_in("abcXYZ")
+        .succeeds()
+        .stdout_is("ABCXYZ");
+    new_ucmd!()
+        .args(&["[:upper:][:lower:]", "[:lower:][:upper:]"])
+        .pipe_in("abcXYZ")
+        .succeeds()
+        .stdout_is("ABCxyz");
+    new_ucmd!()
+        .args(&["-d", "[:digit:][:space:]"])
+        .pipe_in("a1 b2\tc")
+        .succeeds()
+        .stdout_is("abc");
+}
+
+#[test]
+fn test_misaligned_case_classes() {
+    new_ucmd!()
+        .args(&["a-z", "[:upper:]"])
+        .fails()
+        .stderr_contains(&"misaligned [:upper:] and/or [:lower:] construct");
+    new_ucmd!()
+        .args(&["[:digit:]", "[:alpha:]"])
+        .fails()
+        .stderr_contains(&"the only character classes that may appear in string2");
+}
+
+#[test]
+fn test_invalid_class() {
+    new_ucmd!()
+        .args(&["-d", "[:xyz:]"])
+        .fails()
+        .stderr_contains(&"invalid character class 'xyz'");
+}
+
+#[test]
+fn test_equivalence_class() {
+    new_ucmd!()
+        .args(&["[=a=]b", "xy"])
+        .pipe_in("abcde")
+        .succeeds()
+        .stdout_is("xycde");
+}
+
+#[test]
+fn test_repeat() {
+    new_ucmd!()
+        .args(&["a-e", "x[y*]z"])
+        .pipe_in("abcde")
+        .succeeds()
+        .stdout_is("xyyyz");
+    new_ucmd!()
+        .args(&["a-e", "[y*2]z"])
+        .pipe_in("abcde")
+        .succeeds()
+        .stdout_is("yyzzz");
+    new_ucmd!()
+        .args(&["a", "[b*x]"])
+        .fails()
+        .stderr_contains(&"invalid repeat count 'x' in [c*n] construct");
+}
+
+#[test]
+fn test_repeat_in_set1_fails() {
+    for set1 in &["[a*]", "[a*3]"] {
+        new_ucmd!()
+            .args(&[set1, "x"])
+            .fails()
+            .stderr_contains(&"the [c*] repeat construct may not appear in string1");
+    }
+}
+
+#[test]
+fn test_unmatched_bracket_is_literal() {
+    new_ucmd!()
+        .args(&["[b*", "x"])
+        .pipe_in("a[b*c")
+        .succeeds()
+        .stdout_is("axxxc");
+}
+
+#[test]
+fn test_translate_complement_and_squeeze() {
+    new_ucmd!()
+        .args(&["-cs", "[:alpha:]", r"\n"])
+        .pipe_in("hello, world\n")
+        .succeeds()
+        .stdout_is("hello\nworld\n");
+}
+
+#[test]
+fn test_translate_and_squeeze() {
+    new_ucmd!()
+        .args(&["-s", "a-c", "x"])
+        .pipe_in("aabbd")
+        .succeeds()
+        .stdout_is("xd");
+}
+
+#[test]
+fn test_empty_set2_fails() {
+    new_ucmd!()
+        .args(&["abc", ""])
+        .fails()
+        .stderr_contains(&"when not truncating set1, string2 must be non-empty");
+}
+
+#[test]
+fn test_reverse_range_fails() {
+    new_ucmd!()
+        .args(&["c-a", "x"])
+        .fails()
+        .stderr_contains(&"range-endpoints of 'c-a' are in reverse collating sequence order");
+}
+
+#[test]
+fn test_utf8_locale_translates_characters() {
+    new_ucmd!()
+        .env("LC_ALL", "en_US.UTF-8")
+        .args(&["äö", "ao"])
+        .pipe_in("Käse, Öl, öde")
+        .succeeds()
+        .stdout_is("Kase, Öl, ode");
+    new_ucmd!()
+        .env("LANG", "C.UTF-8")
+        .args(&["-d", "ä"])
+        .pipe_in("Käse")
+        .succeeds()
+        .stdout_is("Kse");
+}
+
+#[test]
+fn test_utf8_invalid_input_is_passed_through() {
+    new_ucmd!()
+        .env("LC_ALL", "en_US.UTF-8")
+        .args(&["a", "b"])
+        .pipe_in(&b"a\xffa"[..])
+        .succeeds()
+        .stdout_is_bytes(b"b\xffb");
+}
+
+#[test]
+fn test_c_locale_translates_bytes() {
+    new_ucmd!()
+        .env("LC_ALL", "C")
+        .args(&["-d", "\\303"])
+        .pipe_in("Käse")
+        .succeeds()
+        .stdout_is_bytes(b"K\xa4se");
+}
+
+#[test]
+fn test_complement_characters_and_values() {
+    new_ucmd!()
+        .env("LC_ALL", "en_US.UTF-8")
+        .args(&["-C", "a-z", "_"])
+        .pipe_in("aä")
+        .succeeds()
+        .stdout_is("a_");
+    new_ucmd!()
+        .env("LC_ALL", "en_US.UTF-8")
+        .args(&["-c", "a-z", "_"])
+        .pipe_in("aä")
+        .succeeds()
+        .stdout_is("a__");
+}
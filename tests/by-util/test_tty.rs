@@ -6,6 +6,7 @@ fn test_dev_null() {
     new_ucmd!()
         .pipe_in("</dev/null")
         .fails()
+        .status_code(1)
         .stdout_is("not a tty\n");
 }
 
@@ -16,6 +17,7 @@ fn test_dev_null_silent() {
         .args(&["-s"])
         .pipe_in("</dev/null")
         .fails()
+        .status_code(1)
         .stdout_is("");
 }
 
@@ -53,5 +55,5 @@ fn test_close_stdin_silent_alias() {
 
 #[test]
 fn test_wrong_argument() {
-    new_ucmd!().args(&["a"]).fails();
+    new_ucmd!().args(&["a"]).fails().status_code(2);
 }
@@ -22,7 +22,12 @@ fn test_uname_processor() {
 
     let result = ucmd.arg("-p").run();
     assert!(result.success);
-    assert_eq!(result.stdout.trim_end(), "unknown");
+    #[cfg(target_os = "linux")]
+    assert_eq!(
+        result.stdout,
+        new_ucmd!().arg("-m").succeeds().stdout,
+        "the processor is the machine hardware name"
+    );
 }
 
 #[test]
@@ -31,7 +36,20 @@ fn test_uname_hwplatform() {
 
     let result = ucmd.arg("-i").run();
     assert!(result.success);
-    assert_eq!(result.stdout.trim_end(), "unknown");
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    assert_eq!(result.stdout, "x86_64\n");
+}
+
+#[test]
+fn test_uname_all_order() {
+    let fields = |args: &[&str]| new_ucmd!().args(args).succeeds().stdout;
+    let expected = fields(&["-s", "-n", "-r", "-v", "-m", "-p", "-i", "-o"]);
+    assert_eq!(fields(&["-a"]), expected);
+    // the order options are given in doesn't matter
+    assert_eq!(
+        fields(&["-o", "-i", "-p", "-m", "-v", "-r", "-n", "-s"]),
+        expected
+    );
 }
 
 #[test]
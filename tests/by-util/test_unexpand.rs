@@ -155,3 +155,64 @@ fn unexpand_read_from_two_file() {
         .run()
         .success();
 }
+
+#[test]
+fn unexpand_tab_list_repeat_every() {
+    new_ucmd!()
+        .args(&["-t", "4,/3"])
+        .pipe_in("        a       b\n")
+        .succeeds()
+        .stdout_is("\t\t  a\t\t b\n");
+}
+
+#[test]
+fn unexpand_tab_list_repeat_after_last() {
+    new_ucmd!()
+        .args(&["-t", "2,+5"])
+        .pipe_in("            a   b\n")
+        .succeeds()
+        .stdout_is("\t\t\ta   b\n");
+}
+
+#[test]
+fn unexpand_backspace_moves_column_back() {
+    new_ucmd!()
+        .args(&["-a"])
+        .pipe_in("abcdefg\x08  x\n")
+        .succeeds()
+        .stdout_is("abcdefg\x08\tx\n");
+    new_ucmd!()
+        .args(&["-a"])
+        .pipe_in("abcdefgh\x08   x\n")
+        .succeeds()
+        .stdout_is("abcdefgh\x08\t  x\n");
+}
+
+#[test]
+fn unexpand_existing_tab_absorbs_blanks() {
+    new_ucmd!()
+        .args(&["-a"])
+        .pipe_in(" \t  x  y\n")
+        .succeeds()
+        .stdout_is("\t  x  y\n");
+}
+
+#[test]
+fn unexpand_expanded_round_trip() {
+    // unexpand -a undoes what expand did
+    new_ucmd!()
+        .args(&["-a"])
+        .pipe_in("x       y   z\n")
+        .succeeds()
+        .stdout_is("x\ty   z\n");
+}
+
+#[test]
+fn unexpand_multiple_files() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("a", "        a\n");
+    at.write("b", "        b\n");
+    ucmd.args(&["a", "b"])
+        .succeeds()
+        .stdout_is("\ta\n\tb\n");
+}
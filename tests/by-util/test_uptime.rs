@@ -29,6 +29,15 @@ fn test_uptime_since() {
     assert!(re.is_match(&result.stdout.trim()));
 }
 
+#[test]
+fn test_uptime_pretty() {
+    for opt in vec!["-p", "--pretty"] {
+        let result = new_ucmd!().arg(opt).succeeds();
+        let re = Regex::new(r"^up \d+ (year|week|day|hour|minute)s?(, \d+ \w+)*\n$").unwrap();
+        assert!(re.is_match(&result.stdout), "{:?}", result.stdout);
+    }
+}
+
 #[test]
 fn test_failed() {
     let (_at, mut ucmd) = at_and_ucmd!();
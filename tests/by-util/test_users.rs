@@ -28,3 +28,21 @@ fn test_users_check_name() {
         }
     }
 }
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_users_file() {
+    // the fixture holds login records for alice and bob, whose processes are
+    // long gone; they're listed nonetheless, as no file but the default one
+    // is checked for that
+    new_ucmd!()
+        .arg("utmp")
+        .succeeds()
+        .stdout_only("alice bob\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_users_missing_file() {
+    new_ucmd!().arg("nonexistent").succeeds().no_stdout();
+}
@@ -73,12 +73,60 @@ fn test_all() {
     }
 }
 
+#[cfg(target_os = "linux")]
+#[test]
+fn test_am_i() {
+    new_ucmd!()
+        .args(&["am", "i"])
+        .run()
+        .stdout_is(expected_result_of(&["am", "i"]));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_file() {
+    // the fixture holds one record of each type, with made up ttys and pids
+    // so that none of them is current
+    for opt in vec![
+        "-a",
+        "-b",
+        "-d",
+        "-l",
+        "-p",
+        "-q",
+        "-r",
+        "-s",
+        "-t",
+        "-T",
+        "-w",
+        "--mesg",
+        "--writable",
+        "-u",
+        "-H",
+        "-aH",
+    ] {
+        let ts = TestScenario::new(util_name!());
+        let expected = ts
+            .cmd_keepenv(util_name!())
+            .env("LANGUAGE", "C")
+            .args(&[opt, "utmp"])
+            .run()
+            .stdout;
+        ts.ucmd().args(&[opt, "utmp"]).run().stdout_is(expected);
+    }
+}
+
 #[cfg(target_os = "linux")]
 fn expected_result(arg: &str) -> String {
+    expected_result_of(&[arg])
+}
+
+#[cfg(target_os = "linux")]
+fn expected_result_of(args: &[&str]) -> String {
     TestScenario::new(util_name!())
         .cmd_keepenv(util_name!())
         .env("LANGUAGE", "C")
-        .args(&[arg])
+        .args(args)
         .run()
         .stdout
 }
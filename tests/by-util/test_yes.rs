@@ -1 +1,38 @@
-// ToDO: add tests
+use crate::common::util::*;
+use std::io::Read;
+
+fn run(args: &[&str], expected: &str) {
+    let mut child = new_ucmd!().args(args).run_no_wait();
+    let mut stdout = child.stdout.take().unwrap();
+    let mut output = vec![0; expected.len()];
+    stdout.read_exact(&mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+    // Closing the pipe ends yes, quietly
+    drop(stdout);
+    let result = child.wait_with_output().unwrap();
+    assert!(result.status.success());
+    assert!(result.stderr.is_empty());
+}
+
+#[test]
+fn test_simple() {
+    run(&[], &"y\n".repeat(10));
+}
+
+#[test]
+fn test_args() {
+    run(&["a", "bar", "c"], &"a bar c\n".repeat(10));
+}
+
+#[test]
+fn test_across_buffers() {
+    // 16 KiB of lines don't divide evenly into 7 byte ones
+    run(&["abcdef"], &"abcdef\n".repeat(10_000));
+}
+
+#[test]
+fn test_long_line() {
+    let line = "x".repeat(20_000);
+    run(&[&line], &format!("{}\n", line).repeat(3));
+}